@@ -1,14 +1,24 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{any::Any, borrow::Cow, collections::HashMap};
 
 pub use exgui_core::builder::*;
 use exgui_core::{
-    AlignHor, AlignVer, Circle, Clip, Comp, EventName, Fill, Group, Listener, Model, Node, Padding, Path, PathCommand,
-    Prim, Real, RealValue, Rect, Rounding, Shape, Stroke, Text, Transform,
+    AlignHor, AlignVer, Circle, Clip, Comp, CursorIcon, Custom, CustomShape, Ellipse, EventName, Fill, FillRule,
+    Filter, FontStyle, FontWeight, GestureThresholds, Group, Image, Listener, Margin, Marker, Memo, Model, Node,
+    Padding, Path, PathCommand, Polygon, Prim, Real, RealValue, Rect, Ring, Rounding, ScrollArea, Sector, Shape,
+    StateStyles, Stroke, Style, Text, TextDecoration, TextInput, TextPath, TextSpan, Transform, TransformOrigin,
+    UserData,
 };
 
+pub mod markup;
+
 pub struct PrimBuilder<M: Model> {
     pub children: Vec<Node<M>>,
     pub listeners: HashMap<EventName, Vec<Listener<M>>>,
+    pub states: StateStyles,
+    pub z_index: i32,
+    pub gestures: GestureThresholds,
+    pub focusable: bool,
+    pub cursor: Option<CursorIcon>,
 }
 
 impl<M: Model> Default for PrimBuilder<M> {
@@ -16,6 +26,21 @@ impl<M: Model> Default for PrimBuilder<M> {
         Self {
             children: Default::default(),
             listeners: Default::default(),
+            states: Default::default(),
+            z_index: 0,
+            gestures: Default::default(),
+            focusable: false,
+            cursor: None,
+        }
+    }
+}
+
+impl<M: Model> PrimBuilder<M> {
+    /// Adds the child, the children of the fragment are added in its place.
+    pub fn add_child(&mut self, child: Node<M>) {
+        match child {
+            Node::Prim(prim) if prim.is_fragment() => self.children.extend(prim.children),
+            child => self.children.push(child),
         }
     }
 }
@@ -82,16 +107,62 @@ impl<M: Model> CircleBuilder<M> {
         self.shape.padding.right = padding;
         self
     }
+
+    pub fn margin(mut self, margin: impl Into<Margin>) -> Self {
+        self.shape.margin = margin.into();
+        self
+    }
+
+    pub fn margin_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.shape.margin.top = top.into();
+        self
+    }
+
+    pub fn margin_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.shape.margin.left = left.into();
+        self
+    }
+
+    pub fn margin_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.shape.margin.right = right.into();
+        self
+    }
+
+    pub fn margin_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.shape.margin.bottom = bottom.into();
+        self
+    }
+
+    pub fn margin_top_and_bottom(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.top = margin;
+        self.shape.margin.bottom = margin;
+        self
+    }
+
+    pub fn margin_left_and_right(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.left = margin;
+        self.shape.margin.right = margin;
+        self
+    }
 }
 
 impl<M: Model> Builder<M> for CircleBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let prim = Prim::new(
             Cow::Borrowed(Circle::NAME),
             Shape::Circle(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
     }
 }
 
@@ -109,12 +180,59 @@ impl<M: Model> Entity for CircleBuilder<M> {
 
 impl<M: Model> Primitive<M> for CircleBuilder<M> {
     fn child(mut self, child: impl Builder<M>) -> Self {
-        self.prim.children.push(child.build());
+        self.prim.add_child(child.build());
         self
     }
 
     fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
-        self.prim.children.extend(children);
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
         self
     }
 
@@ -150,6 +268,16 @@ impl<M: Model> Primitive<M> for CircleBuilder<M> {
         self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
         self
     }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
 }
 
 impl<M: Model> EventHandler<M> for CircleBuilder<M> {
@@ -162,6 +290,252 @@ impl<M: Model> EventHandler<M> for CircleBuilder<M> {
     }
 }
 
+pub fn ellipse<M: Model>() -> EllipseBuilder<M> {
+    EllipseBuilder {
+        shape: Default::default(),
+        prim: Default::default(),
+    }
+}
+
+pub struct EllipseBuilder<M: Model> {
+    shape: Ellipse,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> EllipseBuilder<M> {
+    pub fn center(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.cx = x.into();
+        self.shape.cy = y.into();
+        self
+    }
+
+    pub fn radius(mut self, rx: impl Into<RealValue>, ry: impl Into<RealValue>) -> Self {
+        self.shape.rx = rx.into();
+        self.shape.ry = ry.into();
+        self
+    }
+
+    pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
+        self.shape.padding = padding.into();
+        self
+    }
+
+    pub fn padding_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.shape.padding.top = top.into();
+        self
+    }
+
+    pub fn padding_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.shape.padding.left = left.into();
+        self
+    }
+
+    pub fn padding_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.shape.padding.right = right.into();
+        self
+    }
+
+    pub fn padding_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.shape.padding.bottom = bottom.into();
+        self
+    }
+
+    pub fn padding_top_and_bottom(mut self, padding: impl Into<RealValue>) -> Self {
+        let padding = padding.into();
+        self.shape.padding.top = padding;
+        self.shape.padding.bottom = padding;
+        self
+    }
+
+    pub fn padding_left_and_right(mut self, padding: impl Into<RealValue>) -> Self {
+        let padding = padding.into();
+        self.shape.padding.left = padding;
+        self.shape.padding.right = padding;
+        self
+    }
+
+    pub fn margin(mut self, margin: impl Into<Margin>) -> Self {
+        self.shape.margin = margin.into();
+        self
+    }
+
+    pub fn margin_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.shape.margin.top = top.into();
+        self
+    }
+
+    pub fn margin_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.shape.margin.left = left.into();
+        self
+    }
+
+    pub fn margin_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.shape.margin.right = right.into();
+        self
+    }
+
+    pub fn margin_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.shape.margin.bottom = bottom.into();
+        self
+    }
+
+    pub fn margin_top_and_bottom(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.top = margin;
+        self.shape.margin.bottom = margin;
+        self
+    }
+
+    pub fn margin_left_and_right(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.left = margin;
+        self.shape.margin.right = margin;
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for EllipseBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Ellipse::NAME),
+            Shape::Ellipse(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for EllipseBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for EllipseBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for EllipseBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
 pub fn rect<M: Model>() -> RectBuilder<M> {
     RectBuilder {
         shape: Default::default(),
@@ -244,77 +618,1060 @@ impl<M: Model> RectBuilder<M> {
         self
     }
 
-    pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
-        self.shape.padding = padding.into();
+    pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
+        self.shape.padding = padding.into();
+        self
+    }
+
+    pub fn padding_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.shape.padding.top = top.into();
+        self
+    }
+
+    pub fn padding_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.shape.padding.left = left.into();
+        self
+    }
+
+    pub fn padding_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.shape.padding.right = right.into();
+        self
+    }
+
+    pub fn padding_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.shape.padding.bottom = bottom.into();
+        self
+    }
+
+    pub fn padding_top_and_bottom(mut self, padding: impl Into<RealValue>) -> Self {
+        let padding = padding.into();
+        self.shape.padding.top = padding;
+        self.shape.padding.bottom = padding;
+        self
+    }
+
+    pub fn padding_left_and_right(mut self, padding: impl Into<RealValue>) -> Self {
+        let padding = padding.into();
+        self.shape.padding.left = padding;
+        self.shape.padding.right = padding;
+        self
+    }
+
+    pub fn margin(mut self, margin: impl Into<Margin>) -> Self {
+        self.shape.margin = margin.into();
+        self
+    }
+
+    pub fn margin_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.shape.margin.top = top.into();
+        self
+    }
+
+    pub fn margin_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.shape.margin.left = left.into();
+        self
+    }
+
+    pub fn margin_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.shape.margin.right = right.into();
+        self
+    }
+
+    pub fn margin_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.shape.margin.bottom = bottom.into();
+        self
+    }
+
+    pub fn margin_top_and_bottom(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.top = margin;
+        self.shape.margin.bottom = margin;
+        self
+    }
+
+    pub fn margin_left_and_right(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.shape.margin.left = margin;
+        self.shape.margin.right = margin;
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for RectBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Rect::NAME),
+            Shape::Rect(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for RectBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for RectBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for RectBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+pub fn text<M: Model>(content: impl Into<String>) -> TextBuilder<M> {
+    TextBuilder {
+        shape: Text {
+            content: content.into(),
+            ..Text::default()
+        },
+        prim: Default::default(),
+    }
+}
+
+/// Editable text with the caret at the end of the content.
+pub fn text_input<M: Model>(content: impl Into<String>) -> TextBuilder<M> {
+    let content = content.into();
+    let mut input = TextInput::new();
+    input.set_caret(content.chars().count(), false);
+    text(content).input(input)
+}
+
+pub struct TextBuilder<M: Model> {
+    shape: Text,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> TextBuilder<M> {
+    pub fn pos(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.x = x.into();
+        self.shape.y = y.into();
+        self
+    }
+
+    pub fn font_name(mut self, name: impl Into<String>) -> Self {
+        self.shape.font_name = name.into();
+        self
+    }
+
+    pub fn font_size(mut self, size: impl Into<RealValue>) -> Self {
+        self.shape.font_size = size.into();
+        self
+    }
+
+    pub fn font_weight(mut self, weight: FontWeight) -> Self {
+        self.shape.font_weight = weight;
+        self
+    }
+
+    pub fn font_style(mut self, style: FontStyle) -> Self {
+        self.shape.font_style = style;
+        self
+    }
+
+    pub fn align(mut self, align: impl Into<(AlignHor, AlignVer)>) -> Self {
+        self.shape.align = align.into();
+        self
+    }
+
+    pub fn decoration(mut self, decoration: TextDecoration) -> Self {
+        self.shape.decoration = decoration;
+        self
+    }
+
+    pub fn span(mut self, span: TextSpan) -> Self {
+        self.shape.spans.push(span);
+        self
+    }
+
+    pub fn spans(mut self, spans: impl IntoIterator<Item = TextSpan>) -> Self {
+        self.shape.spans.extend(spans);
+        self
+    }
+
+    pub fn on_path(mut self, path: TextPath) -> Self {
+        self.shape.path = Some(Box::new(path));
+        self
+    }
+
+    pub fn input(mut self, input: TextInput) -> Self {
+        self.shape.input = Some(input);
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for TextBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Text::NAME),
+            Shape::Text(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for TextBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for TextBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for TextBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+pub fn path<M: Model>(cmd: impl Into<Vec<PathCommand>>) -> PathBuilder<M> {
+    PathBuilder {
+        shape: Path {
+            cmd: cmd.into(),
+            ..Path::default()
+        },
+        prim: Default::default(),
+    }
+}
+
+pub struct PathBuilder<M: Model> {
+    shape: Path,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> PathBuilder<M> {
+    pub fn marker_start(mut self, shape: impl Into<Shape>) -> Self {
+        self.shape.marker_start = Some(Marker::new(shape));
+        self
+    }
+
+    pub fn marker_end(mut self, shape: impl Into<Shape>) -> Self {
+        self.shape.marker_end = Some(Marker::new(shape));
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for PathBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Path::NAME),
+            Shape::Path(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for PathBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for PathBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for PathBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+pub fn polygon<M: Model>(points: impl Into<Vec<[Real; 2]>>) -> PolygonBuilder<M> {
+    PolygonBuilder {
+        shape: Polygon {
+            contours: vec![points.into()],
+            ..Polygon::default()
+        },
+        prim: Default::default(),
+    }
+}
+
+pub struct PolygonBuilder<M: Model> {
+    shape: Polygon,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> PolygonBuilder<M> {
+    pub fn contour(mut self, points: impl Into<Vec<[Real; 2]>>) -> Self {
+        self.shape.contours.push(points.into());
+        self
+    }
+
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.shape.fill_rule = fill_rule;
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for PolygonBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Polygon::NAME),
+            Shape::Polygon(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for PolygonBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for PolygonBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for PolygonBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+pub fn image<M: Model>(src: impl Into<String>) -> ImageBuilder<M> {
+    ImageBuilder {
+        shape: Image {
+            src: src.into(),
+            ..Image::default()
+        },
+        prim: Default::default(),
+    }
+}
+
+pub struct ImageBuilder<M: Model> {
+    shape: Image,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> ImageBuilder<M> {
+    pub fn left_top_pos(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.x = x.into();
+        self.shape.y = y.into();
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<RealValue>) -> Self {
+        self.shape.width = width.into();
+        self
+    }
+
+    pub fn height(mut self, height: impl Into<RealValue>) -> Self {
+        self.shape.height = height.into();
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for ImageBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Image::NAME),
+            Shape::Image(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for ImageBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for ImageBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn clip(
+        mut self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for ImageBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+pub fn custom<M: Model>(shape: impl CustomShape + 'static) -> CustomBuilder<M> {
+    CustomBuilder {
+        shape: Custom::new(shape),
+        prim: Default::default(),
+    }
+}
+
+pub struct CustomBuilder<M: Model> {
+    shape: Custom,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> Builder<M> for CustomBuilder<M> {
+    fn build(self) -> Node<M> {
+        let prim = Prim::new(
+            Cow::Borrowed(Custom::NAME),
+            Shape::Custom(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
+    }
+}
+
+impl<M: Model> Entity for CustomBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
         self
     }
+}
 
-    pub fn padding_top(mut self, top: impl Into<RealValue>) -> Self {
-        self.shape.padding.top = top.into();
+impl<M: Model> Primitive<M> for CustomBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.add_child(child.build());
         self
     }
 
-    pub fn padding_left(mut self, left: impl Into<RealValue>) -> Self {
-        self.shape.padding.left = left.into();
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        for child in children {
+            self.prim.add_child(child);
+        }
         self
     }
 
-    pub fn padding_right(mut self, right: impl Into<RealValue>) -> Self {
-        self.shape.padding.right = right.into();
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
         self
     }
 
-    pub fn padding_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
-        self.shape.padding.bottom = bottom.into();
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
         self
     }
 
-    pub fn padding_top_and_bottom(mut self, padding: impl Into<RealValue>) -> Self {
-        let padding = padding.into();
-        self.shape.padding.top = padding;
-        self.shape.padding.bottom = padding;
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
         self
     }
 
-    pub fn padding_left_and_right(mut self, padding: impl Into<RealValue>) -> Self {
-        let padding = padding.into();
-        self.shape.padding.left = padding;
-        self.shape.padding.right = padding;
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
         self
     }
-}
 
-impl<M: Model> Builder<M> for RectBuilder<M> {
-    fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
-            Cow::Borrowed(Rect::NAME),
-            Shape::Rect(self.shape),
-            self.prim.children,
-            self.prim.listeners,
-        ))
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
     }
-}
 
-impl<M: Model> Entity for RectBuilder<M> {
-    fn id(mut self, id: impl Into<String>) -> Self {
-        self.shape.id = Some(id.into());
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
         self
     }
 
-    fn transform(mut self, transform: impl Into<Transform>) -> Self {
-        self.shape.transform = transform.into();
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
         self
     }
-}
 
-impl<M: Model> Primitive<M> for RectBuilder<M> {
-    fn child(mut self, child: impl Builder<M>) -> Self {
-        self.prim.children.push(child.build());
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
         self
     }
 
-    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
-        self.prim.children.extend(children);
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
         self
     }
 
@@ -350,9 +1707,19 @@ impl<M: Model> Primitive<M> for RectBuilder<M> {
         self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
         self
     }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
 }
 
-impl<M: Model> EventHandler<M> for RectBuilder<M> {
+impl<M: Model> EventHandler<M> for CustomBuilder<M> {
     fn add_listener(&mut self, listener: Listener<M>) {
         self.prim
             .listeners
@@ -362,56 +1729,56 @@ impl<M: Model> EventHandler<M> for RectBuilder<M> {
     }
 }
 
-pub fn text<M: Model>(content: impl Into<String>) -> TextBuilder<M> {
-    TextBuilder {
-        shape: Text {
-            content: content.into(),
-            ..Text::default()
-        },
+pub fn sector<M: Model>() -> SectorBuilder<M> {
+    SectorBuilder {
+        shape: Default::default(),
         prim: Default::default(),
     }
 }
 
-pub struct TextBuilder<M: Model> {
-    shape: Text,
+pub struct SectorBuilder<M: Model> {
+    shape: Sector,
     prim: PrimBuilder<M>,
 }
 
-impl<M: Model> TextBuilder<M> {
-    pub fn pos(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
-        self.shape.x = x.into();
-        self.shape.y = y.into();
-        self
-    }
-
-    pub fn font_name(mut self, name: impl Into<String>) -> Self {
-        self.shape.font_name = name.into();
+impl<M: Model> SectorBuilder<M> {
+    pub fn center(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.cx = x.into();
+        self.shape.cy = y.into();
         self
     }
 
-    pub fn font_size(mut self, size: impl Into<RealValue>) -> Self {
-        self.shape.font_size = size.into();
+    pub fn radius(mut self, r: impl Into<RealValue>) -> Self {
+        self.shape.r = r.into();
         self
     }
 
-    pub fn align(mut self, align: impl Into<(AlignHor, AlignVer)>) -> Self {
-        self.shape.align = align.into();
+    pub fn angles(mut self, start: impl Into<Real>, end: impl Into<Real>) -> Self {
+        self.shape.start_angle = start.into();
+        self.shape.end_angle = end.into();
         self
     }
 }
 
-impl<M: Model> Builder<M> for TextBuilder<M> {
+impl<M: Model> Builder<M> for SectorBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
-            Cow::Borrowed(Text::NAME),
-            Shape::Text(self.shape),
+        let prim = Prim::new(
+            Cow::Borrowed(Sector::NAME),
+            Shape::Sector(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
     }
 }
 
-impl<M: Model> Entity for TextBuilder<M> {
+impl<M: Model> Entity for SectorBuilder<M> {
     fn id(mut self, id: impl Into<String>) -> Self {
         self.shape.id = Some(id.into());
         self
@@ -423,14 +1790,61 @@ impl<M: Model> Entity for TextBuilder<M> {
     }
 }
 
-impl<M: Model> Primitive<M> for TextBuilder<M> {
+impl<M: Model> Primitive<M> for SectorBuilder<M> {
     fn child(mut self, child: impl Builder<M>) -> Self {
-        self.prim.children.push(child.build());
+        self.prim.add_child(child.build());
         self
     }
 
     fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
-        self.prim.children.extend(children);
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
         self
     }
 
@@ -466,9 +1880,19 @@ impl<M: Model> Primitive<M> for TextBuilder<M> {
         self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
         self
     }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
 }
 
-impl<M: Model> EventHandler<M> for TextBuilder<M> {
+impl<M: Model> EventHandler<M> for SectorBuilder<M> {
     fn add_listener(&mut self, listener: Listener<M>) {
         self.prim
             .listeners
@@ -478,35 +1902,61 @@ impl<M: Model> EventHandler<M> for TextBuilder<M> {
     }
 }
 
-pub fn path<M: Model>(cmd: impl Into<Vec<PathCommand>>) -> PathBuilder<M> {
-    PathBuilder {
-        shape: Path {
-            cmd: cmd.into(),
-            ..Path::default()
-        },
+pub fn ring<M: Model>() -> RingBuilder<M> {
+    RingBuilder {
+        shape: Default::default(),
         prim: Default::default(),
     }
 }
 
-pub struct PathBuilder<M: Model> {
-    shape: Path,
+pub struct RingBuilder<M: Model> {
+    shape: Ring,
     prim: PrimBuilder<M>,
 }
 
-impl<M: Model> PathBuilder<M> {}
+impl<M: Model> RingBuilder<M> {
+    pub fn center(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.cx = x.into();
+        self.shape.cy = y.into();
+        self
+    }
+
+    pub fn radius(mut self, r: impl Into<RealValue>) -> Self {
+        self.shape.r = r.into();
+        self
+    }
 
-impl<M: Model> Builder<M> for PathBuilder<M> {
+    pub fn inner_radius(mut self, r: impl Into<RealValue>) -> Self {
+        self.shape.inner_r = r.into();
+        self
+    }
+
+    pub fn angles(mut self, start: impl Into<Real>, end: impl Into<Real>) -> Self {
+        self.shape.start_angle = start.into();
+        self.shape.end_angle = end.into();
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for RingBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
-            Cow::Borrowed(Path::NAME),
-            Shape::Path(self.shape),
+        let prim = Prim::new(
+            Cow::Borrowed(Ring::NAME),
+            Shape::Ring(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor),
+        )
     }
 }
 
-impl<M: Model> Entity for PathBuilder<M> {
+impl<M: Model> Entity for RingBuilder<M> {
     fn id(mut self, id: impl Into<String>) -> Self {
         self.shape.id = Some(id.into());
         self
@@ -518,14 +1968,61 @@ impl<M: Model> Entity for PathBuilder<M> {
     }
 }
 
-impl<M: Model> Primitive<M> for PathBuilder<M> {
+impl<M: Model> Primitive<M> for RingBuilder<M> {
     fn child(mut self, child: impl Builder<M>) -> Self {
-        self.prim.children.push(child.build());
+        self.prim.add_child(child.build());
         self
     }
 
     fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
-        self.prim.children.extend(children);
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
         self
     }
 
@@ -561,9 +2058,19 @@ impl<M: Model> Primitive<M> for PathBuilder<M> {
         self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
         self
     }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
 }
 
-impl<M: Model> EventHandler<M> for PathBuilder<M> {
+impl<M: Model> EventHandler<M> for RingBuilder<M> {
     fn add_listener(&mut self, listener: Listener<M>) {
         self.prim
             .listeners
@@ -577,24 +2084,103 @@ pub fn group<M: Model>() -> GroupBuilder<M> {
     GroupBuilder {
         shape: Default::default(),
         prim: Default::default(),
+        memo: None,
+    }
+}
+
+/// Sibling nodes without the wrapping shape, e.g. the several roots of the view. The builders add the children of the
+/// fragment in its place, and the fragment, which is not added to a builder, is the plain group.
+pub fn fragment<M: Model>(children: impl IntoIterator<Item = Node<M>>) -> Node<M> {
+    Node::Prim(Prim::new(
+        Cow::Borrowed(Group::FRAGMENT),
+        Shape::Group(Group::default()),
+        children.into_iter().collect(),
+        HashMap::new(),
+    ))
+}
+
+/// Group that clips the children by the viewport and scrolls them by the mouse wheel.
+pub fn scroll_area<M: Model>(
+    x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>, height: impl Into<RealValue>,
+) -> GroupBuilder<M> {
+    group().clip(x, y, width, height).scroll(ScrollArea::new())
+}
+
+/// Group that is drawn over the whole tree and is not clipped by the ancestors, e.g. the tooltip or the popup.
+pub fn portal<M: Model>() -> GroupBuilder<M> {
+    group().portal(true)
+}
+
+/// Group, whose child is viewed again only when the `key` is changed, e.g. the static decoration of the large view.
+/// The children of the builder are replaced by the view.
+pub fn memo<M: Model, K: PartialEq + 'static>(key: K, view: impl FnOnce() -> Node<M> + 'static) -> GroupBuilder<M> {
+    GroupBuilder {
+        memo: Some(Memo::new(key, view)),
+        ..group()
     }
 }
 
+/// Memo group, which passes the key to the view, so the key is the input of the view: `memo_with(size, |size| ...)`.
+pub fn memo_with<M: Model, K: PartialEq + Clone + 'static>(
+    key: K, view: impl FnOnce(K) -> Node<M> + 'static,
+) -> GroupBuilder<M> {
+    memo(key.clone(), move || view(key))
+}
+
 pub struct GroupBuilder<M: Model> {
     shape: Group,
     prim: PrimBuilder<M>,
+    memo: Option<Memo<M>>,
 }
 
-impl<M: Model> GroupBuilder<M> {}
+impl<M: Model> GroupBuilder<M> {
+    pub fn layer(mut self, layer: bool) -> Self {
+        self.shape.layer = layer;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.shape.filters.push(filter);
+        self
+    }
+
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.shape.cache = cache;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollArea) -> Self {
+        self.shape.scroll = Some(scroll);
+        self
+    }
+
+    pub fn align_baselines(mut self, align: bool) -> Self {
+        self.shape.align_baselines = align;
+        self
+    }
+
+    pub fn portal(mut self, portal: bool) -> Self {
+        self.shape.portal = portal;
+        self
+    }
+}
 
 impl<M: Model> Builder<M> for GroupBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let prim = Prim::new(
             Cow::Borrowed(Group::NAME),
             Shape::Group(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        );
+        Node::Prim(
+            prim.with_states(self.prim.states)
+                .with_z_index(self.prim.z_index)
+                .with_gestures(self.prim.gestures)
+                .with_focusable(self.prim.focusable)
+                .with_cursor(self.prim.cursor)
+                .with_memo(self.memo),
+        )
     }
 }
 
@@ -612,12 +2198,59 @@ impl<M: Model> Entity for GroupBuilder<M> {
 
 impl<M: Model> Primitive<M> for GroupBuilder<M> {
     fn child(mut self, child: impl Builder<M>) -> Self {
-        self.prim.children.push(child.build());
+        self.prim.add_child(child.build());
         self
     }
 
     fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
-        self.prim.children.extend(children);
+        for child in children {
+            self.prim.add_child(child);
+        }
+        self
+    }
+
+    fn style(mut self, name: impl Into<String>) -> Self {
+        self.shape.style = Some(name.into());
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.shape.user_data = Some(UserData::new(data));
+        self
+    }
+
+    fn hover(mut self, style: Style) -> Self {
+        self.prim.states.hover = Some(style);
+        self
+    }
+
+    fn pressed(mut self, style: Style) -> Self {
+        self.prim.states.pressed = Some(style);
+        self
+    }
+
+    fn focused(mut self, style: Style) -> Self {
+        self.prim.states.focused = Some(style);
+        self
+    }
+
+    fn z_index(mut self, z_index: i32) -> Self {
+        self.prim.z_index = z_index;
+        self
+    }
+
+    fn gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.prim.gestures = gestures;
+        self
+    }
+
+    fn focusable(mut self) -> Self {
+        self.prim.focusable = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
         self
     }
 
@@ -653,6 +2286,16 @@ impl<M: Model> Primitive<M> for GroupBuilder<M> {
         self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
         self
     }
+
+    fn clip_by(mut self, clip: Clip) -> Self {
+        self.shape.clip = clip;
+        self
+    }
+
+    fn transform_origin(mut self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        self.shape.transform_origin = Some(TransformOrigin::new(x, y));
+        self
+    }
 }
 
 impl<M: Model> EventHandler<M> for GroupBuilder<M> {
@@ -665,8 +2308,20 @@ impl<M: Model> EventHandler<M> for GroupBuilder<M> {
     }
 }
 
+/// Component of the model, which is created when it is viewed first. The rebuilt view of the parent keeps the
+/// existing component with its state, use `comp_of` to pass the new properties to it.
 pub fn comp(model: impl Model) -> CompBuilder {
-    CompBuilder { comp: Comp::new(model) }
+    CompBuilder {
+        comp: Comp::from_model(model),
+    }
+}
+
+/// Component of the `M` model, which is created from its properties: `comp_of::<Hand>(HandProperties { .. })`. The
+/// rebuilt view of the parent passes the new properties to the existing component, so it keeps its state.
+pub fn comp_of<M: Model>(props: M::Properties) -> CompBuilder {
+    CompBuilder {
+        comp: Comp::from_props::<M>(props),
+    }
 }
 
 pub struct CompBuilder {
@@ -700,6 +2355,20 @@ pub fn rotate(theta: impl Into<Real>) -> Transform {
     Transform::new().with_rotation(theta.into())
 }
 
+/// Rotation around the point, e.g. the center of the shape, instead of the origin of the coordinates.
+pub fn rotate_around(theta: impl Into<Real>, x: impl Into<Real>, y: impl Into<Real>) -> Transform {
+    Transform::new().with_rotation_around(theta.into(), x.into(), y.into())
+}
+
 pub fn scale(x: impl Into<Real>, y: impl Into<Real>) -> Transform {
     Transform::new().with_scale(x.into(), y.into())
 }
+
+/// Horizontal skew by the angle, e.g. for the slanted labels and the cards in the pseudo perspective.
+pub fn skew_x(theta: impl Into<Real>) -> Transform {
+    Transform::new().with_skew_x(theta.into())
+}
+
+pub fn skew_y(theta: impl Into<Real>) -> Transform {
+    Transform::new().with_skew_y(theta.into())
+}
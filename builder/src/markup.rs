@@ -0,0 +1,666 @@
+//! Views, which are described by the XML-like markup and loaded at runtime, so the layout and the styles can be
+//! tweaked without recompiling the application:
+//!
+//! ```xml
+//! <group id="toolbar" style="panel">
+//!     <rect x="10" y="10" width="50%" height="2em" fill="{accent}" on_click="save">
+//!         <text x="20" y="30" font-size="14">Save {name}</text>
+//!     </rect>
+//! </group>
+//! ```
+//!
+//! The `{name}` placeholders of the attributes and the texts are replaced by the values of the bindings, and the
+//! event attributes refer to the bound messages by the name.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+
+use exgui_core::{Color, Model, Node, Real, RealValue, Transform};
+
+use crate::{circle, group, rect, text, Builder, Entity, EventHandler, Primitive};
+
+/// Element of the markup with the raw attribute values and the trimmed text content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub content: String,
+}
+
+#[derive(Debug)]
+pub enum MarkupError {
+    Io(io::Error),
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    UnknownElement(String),
+    UnknownAttribute {
+        element: String,
+        attr: String,
+    },
+    UnknownBinding(String),
+    InvalidValue {
+        attr: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::Io(err) => write!(f, "Markup reading error: {}", err),
+            MarkupError::Syntax { line, column, message } => {
+                write!(f, "Markup syntax error at {}:{}: {}", line, column, message)
+            },
+            MarkupError::UnknownElement(name) => write!(f, "Unknown markup element: {}", name),
+            MarkupError::UnknownAttribute { element, attr } => {
+                write!(f, "Unknown attribute of the {} element: {}", element, attr)
+            },
+            MarkupError::UnknownBinding(name) => write!(f, "Unknown markup binding: {}", name),
+            MarkupError::InvalidValue { attr, value } => {
+                write!(f, "Invalid value of the {} attribute: {}", attr, value)
+            },
+        }
+    }
+}
+
+impl Error for MarkupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MarkupError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MarkupError {
+    fn from(err: io::Error) -> Self {
+        MarkupError::Io(err)
+    }
+}
+
+/// Values and messages of the component, which are referred by the markup by the names.
+pub struct Bindings<M: Model> {
+    values: HashMap<String, String>,
+    messages: HashMap<String, Rc<dyn Fn() -> M::Message>>,
+}
+
+impl<M: Model> Default for Bindings<M> {
+    fn default() -> Self {
+        Self {
+            values: Default::default(),
+            messages: Default::default(),
+        }
+    }
+}
+
+impl<M: Model> Bindings<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.values.insert(name.into(), value.to_string());
+        self
+    }
+
+    pub fn message(mut self, name: impl Into<String>, trigger: impl Fn() -> M::Message + 'static) -> Self {
+        self.messages.insert(name.into(), Rc::new(trigger));
+        self
+    }
+
+    /// Replaces the `{name}` placeholders by the bound values, the `{{` and `}}` are the escaped braces.
+    fn substitute(&self, source: &str) -> Result<String, MarkupError> {
+        let mut result = String::with_capacity(source.len());
+        let mut rest = source;
+        while let Some(idx) = rest.find(['{', '}']) {
+            result.push_str(&rest[..idx]);
+            let brace = rest[idx..].chars().next().unwrap_or_default();
+            rest = &rest[idx + 1..];
+            if rest.starts_with(brace) {
+                result.push(brace);
+                rest = &rest[1..];
+            } else if brace == '{' {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| MarkupError::UnknownBinding(rest.to_string()))?;
+                let name = rest[..end].trim();
+                let value = self
+                    .values
+                    .get(name)
+                    .ok_or_else(|| MarkupError::UnknownBinding(name.to_string()))?;
+                result.push_str(value);
+                rest = &rest[end + 1..];
+            } else {
+                result.push(brace);
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    fn trigger(&self, name: &str) -> Result<Rc<dyn Fn() -> M::Message>, MarkupError> {
+        self.messages
+            .get(name.trim())
+            .cloned()
+            .ok_or_else(|| MarkupError::UnknownBinding(name.trim().to_string()))
+    }
+}
+
+/// Parsed markup, its view is built for the each bindings of the component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Markup {
+    pub root: Element,
+}
+
+impl Markup {
+    pub fn parse(source: &str) -> Result<Self, MarkupError> {
+        Parser { source, pos: 0 }.document().map(|root| Self { root })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MarkupError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn build<M: Model>(&self, bindings: &Bindings<M>) -> Result<Node<M>, MarkupError> {
+        build_element(&self.root, bindings)
+    }
+}
+
+/// Markup file, which is reloaded after it is changed on the disk. The file is not watched, the component polls its
+/// modification time periodically, e.g. by the interval, and rebuilds the view after the reload.
+#[derive(Debug)]
+pub struct MarkupFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    markup: Markup,
+}
+
+impl MarkupFile {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, MarkupError> {
+        let path = path.into();
+        let modified = fs::metadata(&path)?.modified().ok();
+        let markup = Markup::load(&path)?;
+        Ok(Self { path, modified, markup })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn markup(&self) -> &Markup {
+        &self.markup
+    }
+
+    /// Checks the modification time of the file once and reloads the markup, if the time is changed since the last
+    /// check. Returns true if the markup is reloaded. The previous markup is kept, if the changed file is not parsed,
+    /// so the view stays on the last valid one.
+    pub fn poll_reload(&mut self) -> Result<bool, MarkupError> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        if modified.is_some() && modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        self.markup = Markup::load(&self.path)?;
+        Ok(true)
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn document(&mut self) -> Result<Element, MarkupError> {
+        self.skip_misc()?;
+        let root = self.element()?;
+        self.skip_misc()?;
+        if self.pos < self.source.len() {
+            return Err(self.error("content after the root element"));
+        }
+        Ok(root)
+    }
+
+    /// Skips the whitespaces, the comments and the declarations around the root element.
+    fn skip_misc(&mut self) -> Result<(), MarkupError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element, MarkupError> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attrs: vec![],
+            children: vec![],
+            content: String::new(),
+        };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            } else if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let attr = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.quoted()?;
+            if element.attrs.iter().any(|(name, _)| *name == attr) {
+                return Err(self.error(&format!("duplicate attribute {}", attr)));
+            }
+            element.attrs.push((attr, value));
+        }
+
+        let mut texts = vec![];
+        loop {
+            if self.rest().starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("expected </{}>", element.name)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                element.content = texts.join(" ");
+                return Ok(element);
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with('<') {
+                element.children.push(self.element()?);
+            } else if self.rest().is_empty() {
+                return Err(self.error(&format!("unclosed element {}", element.name)));
+            } else {
+                let end = self.rest().find('<').unwrap_or_else(|| self.rest().len());
+                let text = unescape(&self.source[self.pos..self.pos + end]).map_err(|msg| self.error(&msg))?;
+                self.pos += end;
+                let text = text.trim();
+                if !text.is_empty() {
+                    texts.push(text.to_string());
+                }
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, MarkupError> {
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == ':' || c == '.'))
+            .unwrap_or_else(|| self.rest().len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        let name = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(name)
+    }
+
+    fn quoted(&mut self) -> Result<String, MarkupError> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => quote,
+            _ => return Err(self.error("expected a quoted value")),
+        };
+        self.pos += 1;
+        let len = self
+            .rest()
+            .find(quote)
+            .ok_or_else(|| self.error("unclosed attribute value"))?;
+        let value = unescape(&self.rest()[..len]).map_err(|msg| self.error(&msg))?;
+        self.pos += len + 1;
+        Ok(value)
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), MarkupError> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {}", token)))
+        }
+    }
+
+    fn skip_past(&mut self, token: &str) -> Result<(), MarkupError> {
+        let idx = self
+            .rest()
+            .find(token)
+            .ok_or_else(|| self.error(&format!("expected {}", token)))?;
+        self.pos += idx + token.len();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> MarkupError {
+        let before = &self.source[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        MarkupError::Syntax {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn unescape(source: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(idx) = rest.find('&') {
+        result.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let end = rest.find(';').ok_or_else(|| "unclosed entity".to_string())?;
+        let entity = &rest[1..end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(dec) = entity.strip_prefix('#') {
+                    dec.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(std::char::from_u32)
+                    .ok_or_else(|| format!("unknown entity &{};", entity))?
+            },
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Attributes of the element with the substituted values, each one is taken by the element builder.
+struct Attrs<'a, M: Model> {
+    element: &'a str,
+    values: BTreeMap<&'a str, &'a str>,
+    bindings: &'a Bindings<M>,
+}
+
+impl<'a, M: Model> Attrs<'a, M> {
+    fn new(element: &'a Element, bindings: &'a Bindings<M>) -> Self {
+        Self {
+            element: &element.name,
+            values: element
+                .attrs
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+            bindings,
+        }
+    }
+
+    fn string(&mut self, name: &str) -> Result<Option<String>, MarkupError> {
+        self.values
+            .remove(name)
+            .map(|value| self.bindings.substitute(value))
+            .transpose()
+    }
+
+    fn parse<T>(&mut self, name: &str, parse: impl FnOnce(&str) -> Option<T>) -> Result<Option<T>, MarkupError> {
+        self.string(name)?
+            .map(|value| {
+                parse(value.trim()).ok_or_else(|| MarkupError::InvalidValue {
+                    attr: name.to_string(),
+                    value,
+                })
+            })
+            .transpose()
+    }
+
+    fn number(&mut self, name: &str) -> Result<Option<Real>, MarkupError> {
+        self.parse(name, |value| value.parse().ok())
+    }
+
+    /// The pixels, the percents like `50%` or the font sizes like `2em`.
+    fn length(&mut self, name: &str) -> Result<Option<RealValue>, MarkupError> {
+        self.parse(name, |value| {
+            if let Some(pct) = value.strip_suffix('%') {
+                pct.trim().parse().ok().map(RealValue::pct)
+            } else if let Some(em) = value.strip_suffix("em") {
+                em.trim().parse().ok().map(RealValue::em)
+            } else {
+                value
+                    .strip_suffix("px")
+                    .unwrap_or(value)
+                    .trim()
+                    .parse()
+                    .ok()
+                    .map(RealValue::px)
+            }
+        })
+    }
+
+    fn color(&mut self, name: &str) -> Result<Option<Color>, MarkupError> {
+        self.parse(name, |value| value.parse().ok())
+    }
+
+    /// Applies the attributes, which are common for all elements, and checks that no attribute is left.
+    fn apply<B: Entity + Primitive<M> + EventHandler<M>>(mut self, mut builder: B) -> Result<B, MarkupError> {
+        if let Some(id) = self.string("id")? {
+            builder = builder.id(id);
+        }
+        if let Some(style) = self.string("style")? {
+            builder = builder.style(style);
+        }
+        let translate = (self.number("translate-x")?, self.number("translate-y")?);
+        if translate.0.is_some() || translate.1.is_some() {
+            let (x, y) = (translate.0.unwrap_or_default(), translate.1.unwrap_or_default());
+            builder = builder.transform(Transform::new().with_translation(x, y));
+        }
+        if let Some(fill) = self.color("fill")? {
+            builder = builder.fill(fill);
+        }
+        if let Some(stroke) = self.color("stroke")? {
+            let width = self.number("stroke-width")?.unwrap_or(1.0);
+            builder = builder.stroke((stroke, width));
+        }
+        if let Some(transparency) = self.number("transparency")? {
+            builder = builder.transparency(transparency);
+        }
+        if let Some(name) = self.values.remove("on_click") {
+            let trigger = self.bindings.trigger(name)?;
+            builder = builder.on_click(move |_| trigger());
+        }
+        if let Some(name) = self.values.remove("on_double_click") {
+            let trigger = self.bindings.trigger(name)?;
+            builder = builder.on_double_click(move |_| trigger());
+        }
+        if let Some(name) = self.values.remove("on_mouse_down") {
+            let trigger = self.bindings.trigger(name)?;
+            builder = builder.on_mouse_down(move |_| trigger());
+        }
+        if let Some(name) = self.values.remove("on_mouse_up") {
+            let trigger = self.bindings.trigger(name)?;
+            builder = builder.on_mouse_up(move |_| trigger());
+        }
+        match self.values.keys().next() {
+            Some(attr) => Err(MarkupError::UnknownAttribute {
+                element: self.element.to_string(),
+                attr: attr.to_string(),
+            }),
+            None => Ok(builder),
+        }
+    }
+}
+
+fn build_element<M: Model>(element: &Element, bindings: &Bindings<M>) -> Result<Node<M>, MarkupError> {
+    let mut attrs = Attrs::new(element, bindings);
+    // The children are built by the known elements only, so the unknown element is reported before its children.
+    let children = || {
+        element
+            .children
+            .iter()
+            .map(|child| build_element(child, bindings))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let node = match element.name.as_str() {
+        "group" => attrs.apply(group())?.children(children()?).build(),
+        "rect" => {
+            let x = attrs.length("x")?.unwrap_or_default();
+            let y = attrs.length("y")?.unwrap_or_default();
+            let mut rect = rect().left_top_pos(x, y);
+            if let Some(width) = attrs.length("width")? {
+                rect = rect.width(width);
+            }
+            if let Some(height) = attrs.length("height")? {
+                rect = rect.height(height);
+            }
+            attrs.apply(rect)?.children(children()?).build()
+        },
+        "circle" => {
+            let cx = attrs.length("cx")?.unwrap_or_default();
+            let cy = attrs.length("cy")?.unwrap_or_default();
+            let mut circle = circle().center(cx, cy);
+            if let Some(r) = attrs.length("r")? {
+                circle = circle.radius(r);
+            }
+            attrs.apply(circle)?.children(children()?).build()
+        },
+        "text" => {
+            let x = attrs.length("x")?.unwrap_or_default();
+            let y = attrs.length("y")?.unwrap_or_default();
+            let mut text = text(bindings.substitute(&element.content)?).pos(x, y);
+            if let Some(name) = attrs.string("font-name")? {
+                text = text.font_name(name);
+            }
+            if let Some(size) = attrs.length("font-size")? {
+                text = text.font_size(size);
+            }
+            attrs.apply(text)?.children(children()?).build()
+        },
+        name => return Err(MarkupError::UnknownElement(name.to_string())),
+    };
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use exgui_core::{Circle, Command, Shaped};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Msg {
+        Increment,
+    }
+
+    struct Counter;
+
+    impl Model for Counter {
+        type Message = Msg;
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Counter
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            Command::none()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn markup_view() {
+        let source = r#"<?xml version="1.0"?>
+            <group id="root">
+                <!-- the counter buttons -->
+                <rect width="50%" height="20" fill="{color}" on_click="inc"><text>+</text></rect>
+                <text id="counter" x="60" font-size="2em">Count: {count} &amp; more</text>
+            </group>"#;
+        let markup = Markup::parse(source).unwrap();
+        let bindings = Bindings::<Counter>::new()
+            .value("color", "#ff0000")
+            .value("count", 3)
+            .message("inc", || Msg::Increment);
+        let view = markup.build(&bindings).unwrap();
+
+        let root = view.as_prim().unwrap();
+        assert_eq!(root.children.len(), 2);
+        let button = root.children[0].as_prim().unwrap();
+        assert_eq!(
+            button.shape.rect().unwrap().fill,
+            Some(Color::RGB(1.0, 0.0, 0.0).into())
+        );
+        let counter = view.get_prim("counter").unwrap();
+        assert_eq!(counter.shape.text().unwrap().content, "Count: 3 & more");
+
+        assert!(matches!(
+            markup.build(&Bindings::<Counter>::new()),
+            Err(MarkupError::UnknownBinding(name)) if name == "color"
+        ));
+        assert!(matches!(
+            Markup::parse("<group>\n  <rect></group>"),
+            Err(MarkupError::Syntax { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_element() {
+        let markup = Markup::parse(r#"<group><polygon><rect width="wide"/></polygon></group>"#).unwrap();
+        assert!(matches!(
+            markup.build(&Bindings::<Counter>::new()),
+            Err(MarkupError::UnknownElement(name)) if name == "polygon"
+        ));
+    }
+
+    #[test]
+    fn poll_markup_file() {
+        let path = std::env::temp_dir().join(format!("exgui_markup_{}.xml", std::process::id()));
+        fs::write(&path, "<rect/>").unwrap();
+        let mut file = MarkupFile::open(&path).unwrap();
+        assert!(!file.poll_reload().unwrap());
+        fs::write(&path, "<circle r=\"5\"/>").unwrap();
+        let modified = SystemTime::now() + std::time::Duration::from_secs(1);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(file.poll_reload().unwrap());
+        assert_eq!(file.markup().root.name, Circle::NAME);
+        fs::remove_file(&path).unwrap();
+    }
+}
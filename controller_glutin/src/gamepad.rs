@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use exgui_core::{Comp, GamepadAxis, GamepadAxisEvent, GamepadButton, GamepadButtonEvent, GamepadController};
+use gilrs::{ev::Code, Axis, Button, EventType, Gilrs};
+use glutin::event_loop::ControlFlow;
+
+/// The gamepad events do not wake the event loop up, so it polls them at this interval while a gamepad is connected.
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Gamepads read by the gilrs, the window system does not report them.
+pub struct Gamepads {
+    gilrs: Option<Gilrs>,
+    controller: GamepadController,
+}
+
+impl Gamepads {
+    /// The app goes on without the gamepads, if the platform backend is not available.
+    pub fn new() -> Self {
+        Self {
+            gilrs: Gilrs::new().ok(),
+            controller: GamepadController::new(),
+        }
+    }
+
+    /// Sends the pending gamepad events to the component. Returns true if there were any.
+    pub fn poll(&mut self, comp: &mut Comp) -> bool {
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return false,
+        };
+        let mut received = false;
+        while let Some(event) = gilrs.next_event() {
+            received = true;
+            let gamepad = usize::from(event.id);
+            match event.event {
+                EventType::ButtonPressed(button, code) => {
+                    let button = convert_button(button, code);
+                    self.controller
+                        .pressed_comp(comp, GamepadButtonEvent { gamepad, button });
+                },
+                EventType::ButtonReleased(button, code) => {
+                    let button = convert_button(button, code);
+                    self.controller
+                        .released_comp(comp, GamepadButtonEvent { gamepad, button });
+                },
+                EventType::AxisChanged(axis, value, code) => {
+                    let axis = convert_axis(axis, code);
+                    self.controller
+                        .axis_moved_comp(comp, GamepadAxisEvent { gamepad, axis, value });
+                },
+                _ => (),
+            }
+        }
+        received
+    }
+
+    /// Limits the wait of the idle loop, so the gamepads are polled while any of them is connected.
+    pub fn limit_wait(&self, control_flow: &mut ControlFlow) {
+        let connected = self
+            .gilrs
+            .as_ref()
+            .is_some_and(|gilrs| gilrs.gamepads().next().is_some());
+        if !connected {
+            return;
+        }
+        let deadline = Instant::now() + POLL_INTERVAL;
+        match *control_flow {
+            ControlFlow::Wait => *control_flow = ControlFlow::WaitUntil(deadline),
+            ControlFlow::WaitUntil(time) if time > deadline => *control_flow = ControlFlow::WaitUntil(deadline),
+            _ => (),
+        }
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn convert_button(button: Button, code: Code) -> GamepadButton {
+    match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => GamepadButton::Other(code.into_u32()),
+    }
+}
+
+fn convert_axis(axis: Axis, code: Code) -> GamepadAxis {
+    match axis {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        Axis::LeftZ => GamepadAxis::LeftTrigger,
+        Axis::RightZ => GamepadAxis::RightTrigger,
+        _ => GamepadAxis::Other(code.into_u32()),
+    }
+}
@@ -1,18 +1,39 @@
-use std::{
-    thread,
-    time::{Duration, Instant},
-};
+use std::{mem, time::Instant};
 
-use exgui_core::{controller, Color, Comp, KeyboardController, MouseController, Real, Render, SystemMessage};
+use copypasta::{ClipboardContext, ClipboardProvider};
+use exgui_core::{
+    controller, set_frame_waker, Clipboard, ClipboardBackend, Color, Comp, KeyboardController, MouseController,
+    MousePos, NextFrame, Real, Render, SystemMessage, Theme, TouchController, Viewport, WindowHandle,
+};
 pub use gl;
 pub use glutin;
 use glutin::{
-    event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    dpi::{LogicalSize, PhysicalSize},
+    event::{
+        ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{CursorIcon, Fullscreen, Window, WindowBuilder},
     ContextBuilder, ContextError, CreationError, NotCurrent, PossiblyCurrent, WindowedContext,
 };
 
+#[cfg(feature = "gamepad")]
+mod gamepad;
+
+/// System clipboard, which is set as the clipboard service backend by the app.
+pub struct SystemClipboard(ClipboardContext);
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.0.get_contents().ok()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.0.set_contents(text).ok();
+    }
+}
+
 pub enum AppState {
     Exit,
     Continue,
@@ -47,12 +68,17 @@ impl AppContext {
     }
 }
 
+/// Handler of the render errors, e.g. of the not found fonts, it decides whether the app goes on.
+pub type RenderErrorHandler<E> = Box<dyn FnMut(&E) -> AppState>;
+
 pub struct App<R: Render> {
     event_loop: EventLoop<()>,
     context: AppContext,
     renderer: R,
     background_color: Color,
     exit_by_escape: bool,
+    redraw_on_demand: bool,
+    render_error_handler: Option<RenderErrorHandler<R::Error>>,
 }
 
 #[derive(Debug)]
@@ -82,13 +108,23 @@ impl<R: Render + 'static> App<R> {
         window_builder: WindowBuilder, context_builder: ContextBuilder<NotCurrent>, renderer: R,
     ) -> Result<Self, AppError<R::Error>> {
         let event_loop = EventLoop::new();
+        let context_builder = match renderer.multisampling() {
+            Some(samples) => context_builder.with_multisampling(samples),
+            None => context_builder,
+        };
         let context = AppContext::NotCurrent(Some(context_builder.build_windowed(window_builder, &event_loop)?));
+        // The in-memory clipboard stays in use, if the system one is not available.
+        if let Ok(clipboard) = ClipboardContext::new() {
+            Clipboard::set_backend(SystemClipboard(clipboard));
+        }
         Ok(App {
             event_loop,
             context,
             renderer,
             background_color: Color::RGBA(0.8, 0.8, 0.8, 1.0),
             exit_by_escape: true,
+            redraw_on_demand: true,
+            render_error_handler: None,
         })
     }
 
@@ -97,11 +133,30 @@ impl<R: Render + 'static> App<R> {
         self
     }
 
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.renderer.set_theme(theme);
+        self
+    }
+
     pub fn with_exit_by_escape(mut self, exit: bool) -> Self {
         self.exit_by_escape = exit;
         self
     }
 
+    /// By default the frames are drawn only when the view is changed or animated, on the input and on the timers, so
+    /// the idle app does not load the CPU and the GPU. Otherwise the frames are drawn continuously.
+    pub fn with_redraw_on_demand(mut self, on_demand: bool) -> Self {
+        self.redraw_on_demand = on_demand;
+        self
+    }
+
+    /// Sets the handler of the render errors. Without the handler the render error is fatal, like the other errors of
+    /// the event loop.
+    pub fn with_render_error_handler(mut self, handler: impl FnMut(&R::Error) -> AppState + 'static) -> Self {
+        self.render_error_handler = Some(Box::new(handler));
+        self
+    }
+
     pub fn init(&mut self) -> Result<&mut Self, AppError<R::Error>> {
         if let Some(context) = self.context.take_not_current() {
             let context = unsafe { context.make_current().map_err(|(_, err)| err)? };
@@ -119,7 +174,9 @@ impl<R: Render + 'static> App<R> {
         self.renderer
             .set_dimensions(size.width, size.height, context.window().scale_factor());
         self.renderer
-            .init(self.background_color)
+            .init_gl(self.background_color, &mut |symbol| {
+                context.get_proc_address(symbol) as *const _
+            })
             .map_err(|err| AppError::RendererError(err))?;
         Ok(self)
     }
@@ -138,79 +195,173 @@ impl<R: Render + 'static> App<R> {
             mut context,
             mut renderer,
             exit_by_escape,
+            redraw_on_demand,
+            mut render_error_handler,
             ..
         } = self;
         let mut mouse_controller = MouseController::new();
         let keyboard_controller = KeyboardController::new();
+        let touch_controller = TouchController::new();
+        #[cfg(feature = "gamepad")]
+        let mut gamepads = gamepad::Gamepads::new();
+        let mut modifiers = controller::Modifiers::NONE;
         let context = context.take_current().expect("PossiblyCurrent context does not exist"); //ok_or(AppError::PossiblyCurrentContextNotExist)?;
         let mut last_time = Instant::now();
+        let resized = |size: PhysicalSize<u32>, scale_factor| {
+            SystemMessage::WindowResized(Viewport {
+                width: size.width,
+                height: size.height,
+                device_pixel_ratio: scale_factor,
+            })
+        };
+        comp.send_system_msg(resized(context.window().inner_size(), context.window().scale_factor()));
+        // The messages of the links and the woken tasks wake the idle loop up.
+        let proxy = event_loop.create_proxy();
+        set_frame_waker(move || {
+            proxy.send_event(()).ok();
+        });
+        let mut frame_requested = true;
 
         event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Poll;
-
             match event {
                 Event::LoopDestroyed => return,
-                Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::Resized(size) => {
-                        context.resize(size);
-                        comp.send_system_msg(SystemMessage::WindowResized {
-                            width: size.width,
-                            height: size.height,
-                        });
-                    }
-                    WindowEvent::CloseRequested => {
-                        *control_flow = ControlFlow::Exit;
-                        return;
-                    }
-                    WindowEvent::ReceivedCharacter(ch) => {
-                        keyboard_controller.input_char(&mut comp, ch);
-                    }
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                Event::UserEvent(()) => frame_requested = true,
+                Event::WindowEvent { event, .. } => {
+                    // The input and the window changes are handled by the next frame.
+                    frame_requested = true;
+                    match event {
+                        WindowEvent::Resized(size) => {
+                            let scale_factor = context.window().scale_factor();
+                            context.resize(size);
+                            renderer.set_dimensions(size.width, size.height, scale_factor);
+                            comp.send_system_msg(resized(size, scale_factor));
+                        }
+                        WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size,
+                        } => {
+                            context.resize(*new_inner_size);
+                            renderer.set_dimensions(new_inner_size.width, new_inner_size.height, scale_factor);
+                            comp.send_system_msg(resized(*new_inner_size, scale_factor));
+                        }
+                        WindowEvent::CloseRequested => {
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                        WindowEvent::ModifiersChanged(state) => {
+                            modifiers = convert_modifiers(state);
+                            mouse_controller.set_modifiers(modifiers);
+                        }
+                        WindowEvent::ReceivedCharacter(ch) => {
+                            keyboard_controller.input_char(&mut comp, ch);
+                        }
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                                    ..
+                                },
+                            ..
+                        } if exit_by_escape => {
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                        WindowEvent::KeyboardInput { input, .. } => {
+                            let KeyboardInput {
+                                scancode,
+                                state,
+                                virtual_keycode,
                                 ..
-                            },
-                        ..
-                    } if exit_by_escape => {
-                        *control_flow = ControlFlow::Exit;
-                        return;
-                    }
-                    WindowEvent::KeyboardInput { input, .. } => {
-                        let KeyboardInput {
-                            scancode,
-                            state,
-                            virtual_keycode,
+                            } = input;
+                            let event = convert_keyboard_event(scancode, virtual_keycode, modifiers);
+                            if let ElementState::Pressed = state {
+                                keyboard_controller.pressed_comp(&mut comp, event);
+                            } else {
+                                keyboard_controller.released_comp(&mut comp, event);
+                            }
+                        }
+                        WindowEvent::CursorMoved { position, .. } => {
+                            // The shapes are laid out in the logical pixels.
+                            let position = position.to_logical::<Real>(context.window().scale_factor());
+                            mouse_controller.update_pos(position.x, position.y);
+                            mouse_controller.moved_comp(&mut comp);
+                            if let Some(cursor) = mouse_controller.update_cursor(&comp) {
+                                context.window().set_cursor_icon(convert_cursor_icon(cursor));
+                            }
+                        }
+                        WindowEvent::MouseInput {
+                            state: ElementState::Pressed,
+                            button,
                             ..
-                        } = input;
-                        if let ElementState::Pressed = state {
-                            keyboard_controller
-                                .pressed_comp(&mut comp, convert_keyboard_event(scancode, virtual_keycode));
-                        } else {
-                            keyboard_controller
-                                .released_comp(&mut comp, convert_keyboard_event(scancode, virtual_keycode));
+                        } => {
+                            mouse_controller.pressed_comp(&mut comp, convert_mouse_button(button));
                         }
+                        WindowEvent::MouseInput {
+                            state: ElementState::Released,
+                            button,
+                            ..
+                        } => {
+                            mouse_controller.released_comp(&mut comp, convert_mouse_button(button));
+                        }
+                        WindowEvent::MouseWheel {
+                            delta: MouseScrollDelta::LineDelta(x, y),
+                            ..
+                        } => {
+                            mouse_controller.mouse_scroll(&mut comp, (x, y));
+                        }
+                        WindowEvent::Touch(Touch {
+                            phase, location, id, ..
+                        }) => {
+                            let location = location.to_logical::<Real>(context.window().scale_factor());
+                            let pos = MousePos {
+                                x: location.x,
+                                y: location.y,
+                            };
+                            let touch = controller::Touch::new(id, pos);
+                            match phase {
+                                TouchPhase::Started => touch_controller.pressed_comp(&mut comp, touch),
+                                TouchPhase::Moved => touch_controller.moved_comp(&mut comp, touch),
+                                TouchPhase::Ended | TouchPhase::Cancelled => {
+                                    touch_controller.released_comp(&mut comp, touch)
+                                }
+                            }
+                        }
+                        WindowEvent::HoveredFile(path) => {
+                            mouse_controller.file_hovered_comp(&mut comp, path);
+                        }
+                        WindowEvent::HoveredFileCancelled => {
+                            mouse_controller.file_hover_cancelled_comp(&mut comp);
+                        }
+                        WindowEvent::DroppedFile(path) => {
+                            mouse_controller.file_dropped_comp(&mut comp, path);
+                        }
+                        _ => (),
                     }
-                    WindowEvent::CursorMoved { position, .. } => {
-                        mouse_controller.update_pos(position.x as Real, position.y as Real);
-                    }
-                    WindowEvent::MouseInput {
-                        state: ElementState::Pressed,
-                        button,
-                        ..
-                    } => {
-                        mouse_controller.pressed_comp(&mut comp, convert_mouse_button(button));
+                }
+                Event::MainEventsCleared => {
+                    #[cfg(feature = "gamepad")]
+                    {
+                        frame_requested |= gamepads.poll(&mut comp);
                     }
-                    WindowEvent::MouseWheel {
-                        delta: MouseScrollDelta::LineDelta(x, y),
-                        ..
-                    } => {
-                        mouse_controller.mouse_scroll(&mut comp, (x, y));
+                    apply_window_changes(context.window());
+                    let next_frame = if !redraw_on_demand || mem::take(&mut frame_requested) {
+                        NextFrame::Now
+                    } else {
+                        comp.next_frame()
+                    };
+                    match next_frame {
+                        // The timers are advanced by the elapsed time of the frames, so they expire after the last one.
+                        NextFrame::After(delay) if last_time + delay > Instant::now() => {
+                            *control_flow = ControlFlow::WaitUntil(last_time + delay);
+                        }
+                        NextFrame::Now | NextFrame::After(_) => {
+                            *control_flow = ControlFlow::Poll;
+                            context.window().request_redraw();
+                        }
+                        NextFrame::Idle => *control_flow = ControlFlow::Wait,
                     }
-                    _ => (),
-                },
-                Event::MainEventsCleared => {
-                    context.window().request_redraw();
+                    #[cfg(feature = "gamepad")]
+                    gamepads.limit_wait(control_flow);
                 }
                 Event::RedrawRequested(_) => {
                     let size = context.window().inner_size();
@@ -226,14 +377,26 @@ impl<R: Render + 'static> App<R> {
 
                     let elapsed = last_time.elapsed();
                     last_time = Instant::now();
-                    comp.send_system_msg(SystemMessage::Draw(elapsed));
-                    if !comp.update_view().is_none() {
-                        renderer.set_dimensions(size.width, size.height, context.window().scale_factor());
-                        if renderer.render(&mut comp).expect("Renderer error") {
-                            context.swap_buffers().expect("Swap buffers fail");
+                    comp.poll_commands();
+                    let changed = comp.send_system_msg(SystemMessage::Draw(elapsed));
+                    if changed && !comp.update_view().is_none() {
+                        match renderer.render(&mut comp) {
+                            Ok(true) => context.swap_buffers().expect("Swap buffers fail"),
+                            Ok(false) => (),
+                            Err(err) => match &mut render_error_handler {
+                                Some(handler) => {
+                                    if let AppState::Exit = handler(&err) {
+                                        *control_flow = ControlFlow::Exit;
+                                        return;
+                                    }
+                                }
+                                None => panic!("Render fail: {:?}", AppError::RendererError(err)),
+                            },
                         }
-                    } else {
-                        thread::sleep(Duration::from_millis(10));
+                    }
+                    // The view or the window cursor may be changed under the resting mouse.
+                    if let Some(cursor) = mouse_controller.update_cursor(&comp) {
+                        context.window().set_cursor_icon(convert_cursor_icon(cursor));
                     }
                 }
                 _ => (),
@@ -254,7 +417,9 @@ impl<R: Render + 'static> App<R> {
     }
 }
 
-fn convert_keyboard_event(scancode: u32, keycode: Option<VirtualKeyCode>) -> controller::KeyboardEvent {
+fn convert_keyboard_event(
+    scancode: u32, keycode: Option<VirtualKeyCode>, modifiers: controller::Modifiers,
+) -> controller::KeyboardEvent {
     let keycode = keycode.map(|code| match code {
         VirtualKeyCode::Key1 => controller::VirtualKeyCode::Key1,
         VirtualKeyCode::Key2 => controller::VirtualKeyCode::Key2,
@@ -418,7 +583,57 @@ fn convert_keyboard_event(scancode: u32, keycode: Option<VirtualKeyCode>) -> con
         VirtualKeyCode::Paste => controller::VirtualKeyCode::Paste,
         VirtualKeyCode::Cut => controller::VirtualKeyCode::Cut,
     });
-    controller::KeyboardEvent { scancode, keycode }
+    controller::KeyboardEvent {
+        scancode,
+        keycode,
+        modifiers,
+    }
+}
+
+/// Applies the changes of the window requested by the `WindowHandle`.
+fn apply_window_changes(window: &Window) {
+    let changes = WindowHandle::take_changes();
+    if let Some(title) = changes.title {
+        window.set_title(&title);
+    }
+    if let Some((width, height)) = changes.size {
+        window.set_inner_size(LogicalSize::new(width, height));
+    }
+    if let Some(fullscreen) = changes.fullscreen {
+        let mode = if fullscreen {
+            Some(Fullscreen::Borderless(window.current_monitor()))
+        } else {
+            None
+        };
+        window.set_fullscreen(mode);
+    }
+}
+
+fn convert_cursor_icon(cursor: controller::CursorIcon) -> CursorIcon {
+    match cursor {
+        controller::CursorIcon::Default => CursorIcon::Default,
+        controller::CursorIcon::Pointer => CursorIcon::Hand,
+        controller::CursorIcon::Text => CursorIcon::Text,
+        controller::CursorIcon::Crosshair => CursorIcon::Crosshair,
+        controller::CursorIcon::Move => CursorIcon::Move,
+        controller::CursorIcon::Grab => CursorIcon::Grab,
+        controller::CursorIcon::Grabbing => CursorIcon::Grabbing,
+        controller::CursorIcon::NotAllowed => CursorIcon::NotAllowed,
+        controller::CursorIcon::Wait => CursorIcon::Wait,
+        controller::CursorIcon::ResizeHorizontal => CursorIcon::EwResize,
+        controller::CursorIcon::ResizeVertical => CursorIcon::NsResize,
+        controller::CursorIcon::ResizeNeSw => CursorIcon::NeswResize,
+        controller::CursorIcon::ResizeNwSe => CursorIcon::NwseResize,
+    }
+}
+
+fn convert_modifiers(state: ModifiersState) -> controller::Modifiers {
+    controller::Modifiers {
+        shift: state.shift(),
+        ctrl: state.ctrl(),
+        alt: state.alt(),
+        logo: state.logo(),
+    }
 }
 
 fn convert_mouse_button(button: MouseButton) -> controller::MouseButton {
@@ -0,0 +1,498 @@
+use std::time::Duration;
+
+use crate::{Model, Node, NodeMut, Real, Shape};
+
+/// Rate of the change of the value from the previous keyframe.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress of the linear progress `t` in the range `0.0..=1.0`.
+    pub fn apply(self, t: Real) -> Real {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Property of the shape animated by the track. The transform properties are set in the decomposition of the shape
+/// transform, so its other components are kept, e.g. the spinner is rotated in the place where it is translated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimatedProperty {
+    Transparency,
+    TranslateX,
+    TranslateY,
+    /// Angle in radians.
+    Rotation,
+    /// Horizontal skew angle in radians.
+    Skew,
+    ScaleX,
+    ScaleY,
+    /// Uniform scale, e.g. for the pulse.
+    Scale,
+}
+
+impl AnimatedProperty {
+    pub fn apply(self, shape: &mut Shape, value: Real) {
+        if self == AnimatedProperty::Transparency {
+            shape.set_transparency(Some(value));
+            return;
+        }
+        shape.transform_mut().transform(|matrix| {
+            let mut parts = matrix.decompose();
+            match self {
+                AnimatedProperty::TranslateX => parts.translate_x = value,
+                AnimatedProperty::TranslateY => parts.translate_y = value,
+                AnimatedProperty::Rotation => parts.rotation = value,
+                AnimatedProperty::Skew => parts.skew = value,
+                AnimatedProperty::ScaleX => parts.scale_x = value,
+                AnimatedProperty::ScaleY => parts.scale_y = value,
+                AnimatedProperty::Scale => {
+                    parts.scale_x = value;
+                    parts.scale_y = value;
+                },
+                AnimatedProperty::Transparency => (),
+            }
+            *matrix = parts.recompose();
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    /// Position of the keyframe in the iteration from 0 to 1.
+    pub offset: Real,
+    pub value: Real,
+    /// Easing of the change from the previous keyframe to this one.
+    pub easing: Easing,
+}
+
+/// Keyframes of the property of the shape with the id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub target: String,
+    pub property: AnimatedProperty,
+    /// Keyframes ordered by the offset.
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    pub fn new(target: impl Into<String>, property: AnimatedProperty) -> Self {
+        Self {
+            target: target.into(),
+            property,
+            keyframes: vec![],
+        }
+    }
+
+    pub fn keyframe(self, offset: Real, value: Real) -> Self {
+        self.keyframe_eased(offset, value, Easing::Linear)
+    }
+
+    pub fn keyframe_eased(mut self, offset: Real, value: Real, easing: Easing) -> Self {
+        let idx = self.keyframes.iter().take_while(|frame| frame.offset <= offset).count();
+        self.keyframes.insert(idx, Keyframe { offset, value, easing });
+        self
+    }
+
+    /// Value at the progress of the iteration, the value of the nearest keyframe is kept before the first and after
+    /// the last keyframes. The track without keyframes has no value.
+    pub fn value_at(&self, progress: Real) -> Option<Real> {
+        let first = self.keyframes.first()?;
+        let next = match self.keyframes.iter().position(|frame| frame.offset > progress) {
+            Some(0) => return Some(first.value),
+            Some(next) => next,
+            None => return self.keyframes.last().map(|frame| frame.value),
+        };
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        let t = (progress - from.offset) / (to.offset - from.offset);
+        Some(from.value + (to.value - from.value) * to.easing.apply(t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// The number of the iterations, the timeline is played once by default.
+    Times(u32),
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+/// Keyframed tracks, which animate the properties of the shapes found by their ids, e.g. the loading spinner or the
+/// attention pulse. The model advances the timeline by the draw messages, applies it to the view in the
+/// `modify_view` and animates while the timeline is not finished.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    duration: Duration,
+    stagger: Duration,
+    repeat: Repeat,
+    yoyo: bool,
+    elapsed: Duration,
+}
+
+impl Timeline {
+    /// Timeline with the duration of the iteration of each track.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            tracks: vec![],
+            duration,
+            stagger: Duration::default(),
+            repeat: Repeat::default(),
+            yoyo: false,
+            elapsed: Duration::default(),
+        }
+    }
+
+    pub fn track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Delays the tracks of each next target after the previous one in the order of their first tracks, e.g. for
+    /// the dots of the loading indicator.
+    pub fn stagger(mut self, stagger: Duration) -> Self {
+        self.stagger = stagger;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every second iteration backwards, so the repeated animation returns smoothly to the start.
+    pub fn yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::default();
+    }
+
+    /// Returns true when the last iteration of the last staggered target is played.
+    pub fn is_finished(&self) -> bool {
+        match self.repeat {
+            Repeat::Times(times) => self.elapsed >= self.last_delay() + self.duration * times.max(1),
+            Repeat::Forever => false,
+        }
+    }
+
+    /// Current value of the track of the timeline.
+    pub fn value(&self, track: &Track) -> Option<Real> {
+        track.value_at(self.progress(self.delay(&track.target)))
+    }
+
+    /// Sets the current values of the tracks to the shapes of the view, the targets are found in the nested
+    /// components too. The shapes, which are not found, are skipped.
+    pub fn apply<M: Model>(&self, view: &mut Node<M>) {
+        for track in &self.tracks {
+            let value = self.value(track);
+            let shape = view.find_by_id_mut(&track.target).and_then(NodeMut::into_shape);
+            if let (Some(value), Some(shape)) = (value, shape) {
+                track.property.apply(shape, value);
+            }
+        }
+    }
+
+    fn targets(&self) -> Vec<&str> {
+        let mut targets = Vec::new();
+        for track in &self.tracks {
+            if !targets.contains(&track.target.as_str()) {
+                targets.push(track.target.as_str());
+            }
+        }
+        targets
+    }
+
+    fn delay(&self, target: &str) -> Duration {
+        let idx = self.targets().iter().position(|&other| other == target).unwrap_or(0);
+        self.stagger * idx as u32
+    }
+
+    fn last_delay(&self) -> Duration {
+        self.stagger * self.targets().len().saturating_sub(1) as u32
+    }
+
+    /// Progress of the current iteration of the target with the delay. It stays at the start before the delay and at
+    /// the end of the last iteration after it.
+    fn progress(&self, delay: Duration) -> Real {
+        let iterations = match self.repeat {
+            Repeat::Times(times) => times.max(1) as Real,
+            Repeat::Forever => Real::INFINITY,
+        };
+        let played = if self.duration > Duration::default() {
+            self.elapsed.saturating_sub(delay).as_secs_f32() / self.duration.as_secs_f32()
+        } else {
+            iterations
+        };
+        let (iteration, progress) = if played >= iterations {
+            (iterations - 1.0, 1.0)
+        } else {
+            (played.floor(), played.fract())
+        };
+        if self.yoyo && iteration % 2.0 == 1.0 {
+            1.0 - progress
+        } else {
+            progress
+        }
+    }
+}
+
+/// Value animated by the damped spring towards the target, e.g. the dragged card released back into its place or the
+/// overscrolled list. Unlike the timeline it has no duration, the motion continues the velocity of the release and
+/// stops when the value rests at the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub stiffness: Real,
+    /// The damping below the critical `2 * sqrt(stiffness * mass)` overshoots the target and bounces.
+    pub damping: Real,
+    pub mass: Real,
+    value: Real,
+    velocity: Real,
+    target: Real,
+}
+
+impl Spring {
+    pub const DEFAULT_STIFFNESS: Real = 170.0;
+    pub const DEFAULT_DAMPING: Real = 26.0;
+    /// Distance to the target and speed per second, below which the spring is at rest.
+    pub const REST_THRESHOLD: Real = 0.01;
+    /// Longest step of the integration, the longer frames are split, so the stiff springs stay stable.
+    const MAX_STEP: Real = 1.0 / 240.0;
+
+    /// Spring at rest at the value.
+    pub fn new(value: Real) -> Self {
+        Self {
+            stiffness: Self::DEFAULT_STIFFNESS,
+            damping: Self::DEFAULT_DAMPING,
+            mass: 1.0,
+            value,
+            velocity: 0.0,
+            target: value,
+        }
+    }
+
+    /// Panics if the stiffness or the mass is not positive or the damping is negative, such spring never settles.
+    pub fn with_params(mut self, stiffness: Real, damping: Real, mass: Real) -> Self {
+        assert!(stiffness > 0.0, "Spring stiffness must be positive, got {}", stiffness);
+        assert!(damping >= 0.0, "Spring damping must not be negative, got {}", damping);
+        assert!(mass > 0.0, "Spring mass must be positive, got {}", mass);
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self.mass = mass;
+        self
+    }
+
+    pub fn value(&self) -> Real {
+        self.value
+    }
+
+    /// Velocity of the value per second.
+    pub fn velocity(&self) -> Real {
+        self.velocity
+    }
+
+    pub fn target(&self) -> Real {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Real) {
+        self.target = target;
+    }
+
+    /// Moves the value right away, e.g. by the drag, the velocity is kept.
+    pub fn set_value(&mut self, value: Real) {
+        self.value = value;
+    }
+
+    /// Sets the velocity per second, e.g. of the released drag, so the motion continues it.
+    pub fn set_velocity(&mut self, velocity: Real) {
+        self.velocity = velocity;
+    }
+
+    pub fn is_at_rest(&self) -> bool {
+        self.velocity == 0.0 && self.value == self.target
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        if self.is_at_rest() {
+            return;
+        }
+        let mut remaining = elapsed.as_secs_f32();
+        while remaining > 0.0 {
+            let step = remaining.min(Self::MAX_STEP);
+            let force = -self.stiffness * (self.value - self.target) - self.damping * self.velocity;
+            self.velocity += force / self.mass * step;
+            self.value += self.velocity * step;
+            remaining -= step;
+        }
+        if (self.value - self.target).abs() < Self::REST_THRESHOLD && self.velocity.abs() < Self::REST_THRESHOLD {
+            self.value = self.target;
+            self.velocity = 0.0;
+        }
+    }
+
+    /// Sets the value to the property of the shape with the id in the view, like the track of the timeline.
+    pub fn apply<M: Model>(&self, view: &mut Node<M>, target: &str, property: AnimatedProperty) {
+        if let Some(shape) = view.find_by_id_mut(target).and_then(NodeMut::into_shape) {
+            property.apply(shape, self.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Prim, Rect};
+
+    struct Dots;
+
+    impl Model for Dots {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Dots
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            Command::none()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            let dot = |id: &str| {
+                let rect = Rect {
+                    id: Some(id.to_string()),
+                    ..Default::default()
+                };
+                Node::Prim(Prim::new(
+                    Rect::NAME.into(),
+                    Shape::Rect(rect),
+                    vec![],
+                    Default::default(),
+                ))
+            };
+            Node::Prim(Prim::new(
+                Rect::NAME.into(),
+                Shape::Rect(Rect::default()),
+                vec![dot("first"), dot("second")],
+                Default::default(),
+            ))
+        }
+    }
+
+    #[test]
+    fn play_staggered_tracks() {
+        let ms = Duration::from_millis;
+        let pulse = |target| {
+            Track::new(target, AnimatedProperty::Scale)
+                .keyframe(0.0, 1.0)
+                .keyframe(1.0, 2.0)
+        };
+        let fade = Track::new("first", AnimatedProperty::Transparency)
+            .keyframe(0.0, 0.0)
+            .keyframe(1.0, 0.5);
+        let mut timeline = Timeline::new(ms(100))
+            .track(pulse("first"))
+            .track(fade)
+            .track(pulse("second"))
+            .stagger(ms(50))
+            .repeat(Repeat::Times(2))
+            .yoyo(true);
+        let mut view = Dots.build_view();
+
+        timeline.advance(ms(75));
+        timeline.apply(&mut view);
+        let scale = |view: &Node<Dots>, id| view.get_prim(id).unwrap().transform().matrix().scale_xy().0;
+        let near = |value: Real, expected: Real| (value - expected).abs() < 1e-4;
+        assert!(near(scale(&view, "first"), 1.75));
+        assert!(near(scale(&view, "second"), 1.25));
+        let transparency = view.get_prim("first").unwrap().shape.transparency().unwrap();
+        assert!(near(transparency, 0.375));
+
+        // The second iteration is played backwards.
+        timeline.advance(ms(50));
+        timeline.apply(&mut view);
+        assert!(near(scale(&view, "first"), 1.75));
+        assert!(near(scale(&view, "second"), 1.75));
+        assert!(!timeline.is_finished());
+
+        timeline.advance(ms(125));
+        timeline.apply(&mut view);
+        assert!(timeline.is_finished());
+        assert!(near(scale(&view, "first"), 1.0));
+        assert!(near(scale(&view, "second"), 1.0));
+    }
+
+    #[test]
+    fn settle_spring_at_target() {
+        let frame = Duration::from_millis(16);
+        let settle = |mut spring: Spring| {
+            spring.set_target(100.0);
+            let mut max_value: Real = 0.0;
+            for _ in 0..300 {
+                spring.advance(frame);
+                max_value = max_value.max(spring.value());
+            }
+            (spring, max_value)
+        };
+
+        let (spring, max_value) = settle(Spring::new(0.0));
+        assert!(spring.is_at_rest());
+        assert_eq!(spring.value(), 100.0);
+        assert!(max_value < 100.5);
+
+        let (spring, max_value) = settle(Spring::new(0.0).with_params(170.0, 5.0, 1.0));
+        assert!(spring.is_at_rest());
+        assert!(max_value > 110.0);
+
+        let mut released = Spring::new(0.0);
+        released.set_velocity(-500.0);
+        released.advance(frame);
+        assert!(released.value() < 0.0 && !released.is_at_rest());
+    }
+
+    #[test]
+    fn reject_invalid_spring_params() {
+        let params = [
+            (0.0, 26.0, 1.0),
+            (-170.0, 26.0, 1.0),
+            (170.0, -1.0, 1.0),
+            (170.0, 26.0, 0.0),
+        ];
+        for (stiffness, damping, mass) in params {
+            let spring = std::panic::catch_unwind(|| Spring::new(0.0).with_params(stiffness, damping, mass));
+            assert!(spring.is_err(), "{:?} are accepted", (stiffness, damping, mass));
+        }
+        assert_eq!(Spring::new(0.0).with_params(170.0, 0.0, 1.0).damping, 0.0);
+    }
+}
@@ -0,0 +1,90 @@
+use std::{any::Any, cell::RefCell, fmt, rc::Rc};
+
+thread_local! {
+    /// Messages, which are emitted by the callbacks, for each component, whose view is dispatching now.
+    static EMITTED: RefCell<Vec<Vec<Box<dyn Any>>>> = RefCell::new(vec![]);
+}
+
+/// Handler of the parent component, which is passed to the child one by the properties. The child emits the value
+/// from its update, and the parent gets the message of the callback, when the dispatch to its view is finished.
+pub struct Callback<T> {
+    func: Rc<dyn Fn(T) -> Box<dyn Any>>,
+}
+
+impl<T> Callback<T> {
+    pub fn new<Msg: 'static>(func: impl Fn(T) -> Msg + 'static) -> Self {
+        Self {
+            func: Rc::new(move |value| Box::new(func(value))),
+        }
+    }
+
+    /// Sends the message of the callback to the component, which is dispatching its view. The message is dropped, if
+    /// the emitting component is not in the view of another one, e.g. it is the root.
+    pub fn emit(&self, value: T) {
+        let msg = (self.func)(value);
+        EMITTED.with(|emitted| {
+            if let Some(frame) = emitted.borrow_mut().last_mut() {
+                frame.push(msg);
+            }
+        });
+    }
+}
+
+impl<T> Clone for Callback<T> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func.clone(),
+        }
+    }
+}
+
+/// The callbacks are equal if they are the clones of one callback.
+impl<T> PartialEq for Callback<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl<T> fmt::Debug for Callback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Calls the `dispatch` and collects the messages of the `Msg` type, which are emitted by the nested components
+/// during it.
+pub(crate) fn with_emitted<Msg: 'static, R>(dispatch: impl FnOnce() -> R) -> (R, Vec<Msg>) {
+    EMITTED.with(|emitted| emitted.borrow_mut().push(vec![]));
+    let result = dispatch();
+    let frame = EMITTED.with(|emitted| emitted.borrow_mut().pop()).unwrap_or_default();
+    let messages = frame
+        .into_iter()
+        .filter_map(|msg| msg.downcast::<Msg>().ok())
+        .map(|msg| *msg)
+        .collect();
+    (result, messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum ParentMsg {
+        Selected(usize),
+    }
+
+    #[test]
+    fn emit_to_dispatching_parent() {
+        let callback = Callback::new(ParentMsg::Selected);
+        assert_eq!(callback, callback.clone());
+        callback.emit(0);
+
+        let ((), messages) = with_emitted::<ParentMsg, _>(|| {
+            callback.emit(1);
+            let ((), nested) = with_emitted::<ParentMsg, _>(|| callback.emit(2));
+            assert_eq!(nested, [ParentMsg::Selected(2)]);
+        });
+        assert_eq!(messages, [ParentMsg::Selected(1)]);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    time::Duration,
+};
+
+use crate::{
+    scheduler::wake_frame,
+    timer::{Timer, TimerOp},
+    ChangeView,
+};
+
+/// Result of the model update: the change of the view and the futures, e.g. the data fetching, which are polled by
+/// the runtime on the UI thread. The output of each future is sent back to the component as the message.
+pub struct Command<Msg> {
+    pub change_view: ChangeView,
+    pub(crate) tasks: Vec<Task<Msg>>,
+    pub(crate) timers: Vec<TimerOp<Msg>>,
+}
+
+impl<Msg: 'static> Command<Msg> {
+    pub fn none() -> Self {
+        ChangeView::None.into()
+    }
+
+    /// Spawns the future, its output is converted to the message by the `map`.
+    pub fn perform<T>(future: impl Future<Output = T> + 'static, map: impl FnOnce(T) -> Msg + 'static) -> Self {
+        Self::none().and_perform(future, map)
+    }
+
+    pub fn and_perform<T>(
+        mut self, future: impl Future<Output = T> + 'static, map: impl FnOnce(T) -> Msg + 'static,
+    ) -> Self {
+        self.tasks.push(Task::new(async move { map(future.await) }));
+        self
+    }
+
+    /// Sends the message to the component after the delay. The timers are advanced by the draw messages, so the
+    /// delay is measured by the main loop of the controller.
+    pub fn set_timeout(mut self, delay: Duration, msg: Msg) -> Self {
+        self.timers.push(TimerOp::Set(Timer::timeout(delay, msg)));
+        self
+    }
+
+    /// Sends the message of the `trigger` to the component every period, until the interval is cleared. The interval
+    /// replaces the previous one with the same id.
+    pub fn set_interval(
+        mut self, id: impl Into<String>, period: Duration, trigger: impl Fn() -> Msg + 'static,
+    ) -> Self {
+        self.timers
+            .push(TimerOp::Set(Timer::interval(id.into(), period, trigger)));
+        self
+    }
+
+    pub fn clear_interval(mut self, id: impl Into<String>) -> Self {
+        self.timers.push(TimerOp::Clear(id.into()));
+        self
+    }
+
+    pub fn change_view(mut self, change_view: ChangeView) -> Self {
+        self.change_view = change_view;
+        self
+    }
+
+    pub fn has_tasks(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+}
+
+impl<Msg> From<ChangeView> for Command<Msg> {
+    fn from(change_view: ChangeView) -> Self {
+        Self {
+            change_view,
+            tasks: vec![],
+            timers: vec![],
+        }
+    }
+}
+
+/// Wake flag of the task, the task is polled again only after it is woken.
+#[derive(Debug)]
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        wake_frame();
+    }
+}
+
+pub(crate) struct Task<Msg> {
+    future: Pin<Box<dyn Future<Output = Msg>>>,
+    waker: Arc<TaskWaker>,
+}
+
+impl<Msg> Task<Msg> {
+    fn new(future: impl Future<Output = Msg> + 'static) -> Self {
+        Self {
+            future: Box::pin(future),
+            waker: Arc::new(TaskWaker {
+                woken: AtomicBool::new(true),
+            }),
+        }
+    }
+
+    pub(crate) fn is_woken(&self) -> bool {
+        self.waker.woken.load(Ordering::Acquire)
+    }
+
+    /// Polls the future, if it is woken since the last poll. Returns the message, when the future is completed.
+    pub(crate) fn poll(&mut self) -> Option<Msg> {
+        if !self.waker.woken.swap(false, Ordering::Acquire) {
+            return None;
+        }
+        let waker = Waker::from(self.waker.clone());
+        match self.future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(msg) => Some(msg),
+            Poll::Pending => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    /// The future, which is pending until the flag is set, and wakes itself on each poll.
+    struct Flag(Rc<Cell<bool>>);
+
+    impl Future for Flag {
+        type Output = &'static str;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+            if self.0.get() {
+                Poll::Ready("done")
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn poll_woken_tasks() {
+        let flag = Rc::new(Cell::new(false));
+        let command = Command::perform(Flag(flag.clone()), |msg| msg).change_view(ChangeView::Modify);
+        assert_eq!(command.change_view, ChangeView::Modify);
+
+        let mut tasks = command.tasks;
+        assert_eq!(tasks[0].poll(), None);
+        flag.set(true);
+        assert_eq!(tasks[0].poll(), Some("done"));
+    }
+}
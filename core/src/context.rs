@@ -0,0 +1,100 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use crate::Viewport;
+
+thread_local! {
+    /// Providers of the components, whose views are building or updating now, the innermost one is the last.
+    static PROVIDERS: RefCell<Vec<Rc<Provider>>> = const { RefCell::new(Vec::new()) };
+    /// The last viewport, which is sent to the root component.
+    static VIEWPORT: Cell<Option<Viewport>> = const { Cell::new(None) };
+}
+
+/// Values, which the component provides to the nested components, one value of each type, e.g. the theme or the
+/// service handle.
+#[derive(Default)]
+pub struct Provider {
+    values: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Provider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: 'static>(mut self, value: T) -> Self {
+        self.values.insert(TypeId::of::<T>(), Box::new(value));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn get<T: 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+}
+
+/// Access to the values of the providers, it is available during the create, the view build and the view modify.
+pub struct Context;
+
+impl Context {
+    /// The value of the nearest component, which provides the `T` type.
+    pub fn get<T: Clone + 'static>() -> Option<T> {
+        Self::with(T::clone)
+    }
+
+    pub fn with<T: 'static, R>(f: impl FnOnce(&T) -> R) -> Option<R> {
+        PROVIDERS.with(|providers| {
+            let providers = providers.borrow();
+            providers.iter().rev().find_map(|provider| provider.get()).map(f)
+        })
+    }
+
+    /// The window viewport, it is known after the first resize message of the controller.
+    pub fn viewport() -> Option<Viewport> {
+        VIEWPORT.with(Cell::get)
+    }
+}
+
+pub(crate) fn set_viewport(viewport: Viewport) {
+    VIEWPORT.with(|current| current.set(Some(viewport)));
+}
+
+/// Calls the `f` with the values of the `provider` in the context.
+pub(crate) fn with_provider<R>(provider: &Rc<Provider>, f: impl FnOnce() -> R) -> R {
+    if provider.is_empty() {
+        return f();
+    }
+    PROVIDERS.with(|providers| providers.borrow_mut().push(provider.clone()));
+    let result = f();
+    PROVIDERS.with(|providers| providers.borrow_mut().pop());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_provider() {
+        let outer = Rc::new(Provider::new().with("outer").with(1u32));
+        let inner = Rc::new(Provider::new().with("inner"));
+
+        with_provider(&outer, || {
+            with_provider(&inner, || {
+                assert_eq!(Context::get::<&str>(), Some("inner"));
+                assert_eq!(Context::get::<u32>(), Some(1));
+            });
+            assert_eq!(Context::get::<&str>(), Some("outer"));
+        });
+        assert_eq!(Context::get::<u32>(), None);
+    }
+}
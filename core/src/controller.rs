@@ -1,20 +1,58 @@
-pub use self::{keyboard::*, mouse::*};
+pub use self::{clipboard::*, focus::*, gamepad::*, keyboard::*, mouse::*, shortcut::*, touch::*, window::*};
 
+pub mod clipboard;
+pub mod focus;
+pub mod gamepad;
 pub mod keyboard;
 pub mod mouse;
+pub mod shortcut;
+pub mod touch;
+pub mod window;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum InputEvent {
     MouseDown(MouseDown),
+    MouseUp(MouseUp),
+    MouseMove(MousePos),
     MouseScroll(MouseScroll),
     KeyDown(KeyboardEvent),
     KeyUp(KeyboardEvent),
     Char(char),
+    TouchDown(Touch),
+    TouchMove(Touch),
+    TouchUp(Touch),
+    /// The file is dragged over the window, the event is sent for every dragged file.
+    FileHover(FileDrag),
+    /// The dragged files have left the window or the drag is cancelled.
+    FileHoverCancel,
+    /// The file is dropped on the window, the event is sent for every dropped file.
+    FileDrop(FileDrag),
+    GamepadButtonDown(GamepadButtonEvent),
+    GamepadButtonUp(GamepadButtonEvent),
+    GamepadAxis(GamepadAxisEvent),
 }
 
 impl InputEvent {
-    pub fn mouse_down(pos: MousePos, button: MouseButton) -> Self {
-        Self::MouseDown(MouseDown { pos, button })
+    pub fn mouse_down(pos: MousePos, button: MouseButton, modifiers: Modifiers) -> Self {
+        Self::MouseDown(MouseDown {
+            pos,
+            local_pos: pos,
+            button,
+            modifiers,
+        })
+    }
+
+    pub fn mouse_up(pos: MousePos, button: MouseButton, modifiers: Modifiers) -> Self {
+        Self::MouseUp(MouseUp {
+            pos,
+            local_pos: pos,
+            button,
+            modifiers,
+        })
+    }
+
+    pub fn mouse_move(pos: MousePos) -> Self {
+        Self::MouseMove(pos)
     }
 
     pub fn mouse_scroll(scroll: MouseScroll) -> Self {
@@ -32,4 +70,19 @@ impl InputEvent {
     pub fn char(ch: char) -> Self {
         Self::Char(ch)
     }
+
+    /// The pointer events, which bubble from the deepest hit primitive to its ancestors. The other events are
+    /// delivered to every primitive.
+    pub fn is_bubbling(&self) -> bool {
+        matches!(
+            self,
+            Self::MouseDown(_)
+                | Self::MouseUp(_)
+                | Self::MouseScroll(_)
+                | Self::TouchDown(_)
+                | Self::TouchUp(_)
+                | Self::FileHover(_)
+                | Self::FileDrop(_)
+        )
+    }
 }
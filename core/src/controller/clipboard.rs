@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+
+/// Storage of the clipboard text. The controller replaces the default in-memory storage with the system clipboard.
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: String);
+}
+
+/// Clipboard of the application only, it is used if the controller does not provide the system clipboard.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.text = Some(text);
+    }
+}
+
+thread_local! {
+    static BACKEND: RefCell<Box<dyn ClipboardBackend>> = RefCell::new(Box::new(MemoryClipboard::default()));
+}
+
+/// Clipboard service of the UI thread, it is available from anywhere, e.g. from the model update.
+pub struct Clipboard;
+
+impl Clipboard {
+    pub fn get_text() -> Option<String> {
+        BACKEND.with(|backend| backend.borrow_mut().get_text())
+    }
+
+    pub fn set_text(text: impl Into<String>) {
+        let text = text.into();
+        BACKEND.with(|backend| backend.borrow_mut().set_text(text));
+    }
+
+    pub fn set_backend(backend: impl ClipboardBackend + 'static) {
+        BACKEND.with(|current| *current.borrow_mut() = Box::new(backend));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_clipboard() {
+        Clipboard::set_backend(MemoryClipboard::default());
+        assert_eq!(Clipboard::get_text(), None);
+
+        Clipboard::set_text("copied");
+        assert_eq!(Clipboard::get_text(), Some("copied".to_string()));
+    }
+}
@@ -0,0 +1,55 @@
+use super::MouseDown;
+
+/// Cause of the focus change of the focusable primitive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FocusChange {
+    /// The button is pressed over the primitive, which gets the focus, or outside of the focused one.
+    Mouse(MouseDown),
+    /// The focus is moved by the Tab key, backward if the Shift is held.
+    Tab { backward: bool },
+}
+
+/// Focusable primitives of the tree counted in the declaration order, and the index of the focused one.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct FocusOrder {
+    pub count: usize,
+    pub focused: Option<usize>,
+}
+
+impl FocusOrder {
+    /// Index of the primitive, which gets the focus by the Tab. The traversal wraps around at the ends.
+    pub fn next(&self, backward: bool) -> Option<usize> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(match (self.focused, backward) {
+            (None, false) => 0,
+            (None, true) => self.count - 1,
+            (Some(idx), false) => (idx + 1) % self.count,
+            (Some(idx), true) => (idx + self.count - 1) % self.count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_order_wraps() {
+        let order = FocusOrder {
+            count: 3,
+            focused: None,
+        };
+        assert_eq!(order.next(false), Some(0));
+        assert_eq!(order.next(true), Some(2));
+
+        let order = FocusOrder {
+            focused: Some(2),
+            ..order
+        };
+        assert_eq!(order.next(false), Some(0));
+        assert_eq!(order.next(true), Some(1));
+        assert_eq!(FocusOrder::default().next(false), None);
+    }
+}
@@ -0,0 +1,141 @@
+use super::InputEvent;
+use crate::{Comp, Real, SystemMessage};
+
+/// Gamepad button in the layout of the common controllers, the face buttons are named by their position.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    /// The central button, e.g. the Xbox or the PS button.
+    Mode,
+    /// Press of the left stick.
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Other(u32),
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    Other(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadButtonEvent {
+    /// Id of the gamepad given by the window system, it tells apart the players.
+    pub gamepad: usize,
+    pub button: GamepadButton,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadAxisEvent {
+    pub gamepad: usize,
+    pub axis: GamepadAxis,
+    /// Position of the stick from -1 to 1, the Y axis goes up, or the pressure of the trigger from 0 to 1.
+    pub value: Real,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct GamepadController;
+
+impl GamepadController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Sends the button press to the component. The D-pad moves the focus after that, like the Tab does, so the
+    /// menus are navigated without the mouse. Returns true if the view update is needed.
+    pub fn pressed_comp(&self, comp: &mut Comp, event: GamepadButtonEvent) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::GamepadButtonDown(event)));
+        match event.button {
+            GamepadButton::DPadDown | GamepadButton::DPadRight => {
+                comp.move_focus(false);
+            }
+            GamepadButton::DPadUp | GamepadButton::DPadLeft => {
+                comp.move_focus(true);
+            }
+            _ => (),
+        }
+        comp.has_changes()
+    }
+
+    pub fn released_comp(&self, comp: &mut Comp, event: GamepadButtonEvent) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::GamepadButtonUp(event)))
+    }
+
+    pub fn axis_moved_comp(&self, comp: &mut Comp, event: GamepadAxisEvent) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::GamepadAxis(event)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Group, Listener, Model, Node, Prim, Rect, Shape};
+
+    struct Menu {
+        chosen: Option<usize>,
+    }
+
+    impl Model for Menu {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Menu { chosen: None }
+        }
+
+        fn update(&mut self, item: Self::Message) -> Command<Self::Message> {
+            self.chosen = Some(item);
+            Command::none()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            let item = |idx: usize| {
+                let listener = Listener::OnGamepadButtonDown(Box::new(move |_| idx));
+                let listeners = vec![(listener.event_name(), vec![listener])].into_iter().collect();
+                Node::Prim(
+                    Prim::new(Rect::NAME.into(), Shape::Rect(Rect::default()), vec![], listeners).with_focusable(true),
+                )
+            };
+            Node::Prim(Prim::new(
+                "group".into(),
+                Shape::Group(Group::default()),
+                vec![item(0), item(1)],
+                Default::default(),
+            ))
+        }
+    }
+
+    #[test]
+    fn navigate_by_dpad() {
+        let controller = GamepadController::new();
+        let press = |button| GamepadButtonEvent { gamepad: 0, button };
+        let mut comp = Comp::new(Menu::create(()));
+
+        controller.pressed_comp(&mut comp, press(GamepadButton::DPadUp));
+        assert_eq!(comp.focus_order().focused, Some(1));
+        controller.pressed_comp(&mut comp, press(GamepadButton::DPadDown));
+        assert_eq!(comp.focus_order().focused, Some(0));
+
+        controller.pressed_comp(&mut comp, press(GamepadButton::South));
+        assert_eq!(comp.model::<Menu>().chosen, Some(0));
+    }
+}
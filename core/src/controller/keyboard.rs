@@ -182,10 +182,43 @@ pub enum VirtualKeyCode {
     Cut,
 }
 
+/// State of the modifier keys at the moment of the input event.
+#[derive(Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// The Windows key on PC, the Command key on Mac.
+    pub logo: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        logo: false,
+    };
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// Checks the modifier of the editing shortcuts, e.g. Ctrl+C: the Command key on Mac, the Ctrl key otherwise.
+    pub fn command(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.logo
+        } else {
+            self.ctrl
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct KeyboardEvent {
     pub scancode: u32,
     pub keycode: Option<VirtualKeyCode>,
+    pub modifiers: Modifiers,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
@@ -202,15 +235,24 @@ impl KeyboardController {
         self.last_event.as_ref()
     }
 
-    pub fn pressed_comp(&self, comp: &mut Comp, event: KeyboardEvent) {
-        comp.send_system_msg(SystemMessage::Input(InputEvent::key_down(event)))
+    /// Sends the key press to the component, unless it triggers a shortcut. The Tab press moves the focus after that.
+    /// Returns true if the view update is needed.
+    pub fn pressed_comp(&self, comp: &mut Comp, event: KeyboardEvent) -> bool {
+        if comp.trigger_shortcut(event) {
+            return comp.has_changes();
+        }
+        comp.send_system_msg(SystemMessage::Input(InputEvent::key_down(event)));
+        if event.keycode == Some(VirtualKeyCode::Tab) {
+            comp.move_focus(event.modifiers.shift);
+        }
+        comp.has_changes()
     }
 
-    pub fn released_comp(&self, comp: &mut Comp, event: KeyboardEvent) {
+    pub fn released_comp(&self, comp: &mut Comp, event: KeyboardEvent) -> bool {
         comp.send_system_msg(SystemMessage::Input(InputEvent::key_up(event)))
     }
 
-    pub fn input_char(&self, comp: &mut Comp, ch: char) {
+    pub fn input_char(&self, comp: &mut Comp, ch: char) -> bool {
         comp.send_system_msg(SystemMessage::Input(InputEvent::char(ch)))
     }
 }
@@ -1,5 +1,7 @@
-use super::InputEvent;
-use crate::{Comp, Real, SystemMessage};
+use std::{path::PathBuf, time::Duration};
+
+use super::{InputEvent, Modifiers, WindowHandle};
+use crate::{Comp, CompositeShape, Real, SystemMessage};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub enum MouseButton {
@@ -11,20 +13,140 @@ pub enum MouseButton {
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MouseDown {
+    /// Position in the window coordinates.
+    pub pos: MousePos,
+    /// Position in the local coordinates of the listener shape. The controller sets it equal to the window position,
+    /// the primitive converts it before calling the listeners.
+    pub local_pos: MousePos,
+    pub button: MouseButton,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseUp {
     pub pos: MousePos,
+    pub local_pos: MousePos,
     pub button: MouseButton,
+    pub modifiers: Modifiers,
+}
+
+/// Mouse cursor, which is shown while the cursor is over the shape.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum CursorIcon {
+    Default,
+    /// The hand, which marks the clickable shapes.
+    Pointer,
+    Text,
+    Crosshair,
+    Move,
+    Grab,
+    Grabbing,
+    NotAllowed,
+    Wait,
+    /// Resizing by the left or the right edge.
+    ResizeHorizontal,
+    /// Resizing by the top or the bottom edge.
+    ResizeVertical,
+    /// Resizing by the top right or the bottom left corner.
+    ResizeNeSw,
+    /// Resizing by the top left or the bottom right corner.
+    ResizeNwSe,
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self {
+        CursorIcon::Default
+    }
+}
+
+/// Press and release of the button over the same primitive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Click {
+    /// Release position in the window coordinates.
+    pub pos: MousePos,
+    /// Release position in the local coordinates of the clicked shape.
+    pub local_pos: MousePos,
+    pub button: MouseButton,
+    /// Modifiers at the release, e.g. for the shift-click selection.
+    pub modifiers: Modifiers,
+}
+
+/// Movement of the mouse with the button pressed over the primitive. The primitive captures the mouse, so it gets the
+/// drag events even if the cursor leaves its bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    /// Press position in the window coordinates.
+    pub start: MousePos,
+    pub pos: MousePos,
+    /// Current position in the local coordinates of the dragged shape.
+    pub local_pos: MousePos,
+    /// Movement since the previous drag event.
+    pub delta: (Real, Real),
+    pub button: MouseButton,
+    /// Modifiers at the press, they are kept for the whole drag.
+    pub modifiers: Modifiers,
+}
+
+impl Drag {
+    /// Moves the drag to the position, the delta is updated by the previous position.
+    pub fn move_to(&mut self, pos: MousePos) -> Drag {
+        self.delta = (pos.x - self.pos.x, pos.y - self.pos.y);
+        self.pos = pos;
+        *self
+    }
+}
+
+/// Thresholds of the double click and the long press, which are recognized by the primitive. The time is counted by
+/// the draw messages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureThresholds {
+    /// Maximum time between the clicks of the double click.
+    pub double_click_time: Duration,
+    /// Maximum distance between the clicks of the double click.
+    pub double_click_distance: Real,
+    /// Hold time of the press, after which the long press is fired.
+    pub long_press_time: Duration,
+    /// Maximum movement of the held press, the larger movement cancels the long press and starts the drag.
+    pub long_press_distance: Real,
+    /// Maximum movement of the tap, the larger movement of the touch starts the pan.
+    pub tap_distance: Real,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(500),
+            double_click_distance: 4.0,
+            long_press_time: Duration::from_millis(600),
+            long_press_distance: 8.0,
+            tap_distance: 10.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MouseScroll {
     pub pos: MousePos,
+    pub local_pos: MousePos,
     pub delta: (f32, f32),
+    pub modifiers: Modifiers,
+}
+
+/// File dragged from the file manager over the window. The window system does not report the cursor while dragging,
+/// so the position is the last known cursor position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDrag {
+    pub pos: MousePos,
+    pub local_pos: MousePos,
+    pub path: PathBuf,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct MouseController {
     last_pos: Option<MousePos>,
     last_offset: Option<MousePos>,
+    modifiers: Modifiers,
+    cursor: CursorIcon,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
@@ -33,11 +155,19 @@ pub struct MousePos {
     pub y: Real,
 }
 
+impl MousePos {
+    pub fn distance(&self, other: MousePos) -> Real {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
 impl MouseController {
     pub fn new() -> Self {
         MouseController {
             last_pos: None,
             last_offset: None,
+            modifiers: Modifiers::NONE,
+            cursor: CursorIcon::Default,
         }
     }
 
@@ -54,20 +184,77 @@ impl MouseController {
         self.last_offset = Some(offset);
     }
 
+    /// Sets the modifiers, which are attached to the following mouse events.
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
     pub fn last_pos(&self) -> MousePos {
         self.last_pos.unwrap_or_default()
     }
 
-    pub fn pressed_comp(&self, comp: &mut Comp, button: MouseButton) {
+    /// Finds the cursor of the shape under the last position, unless the cursor of the whole window is set by the
+    /// `WindowHandle`. Returns the cursor if it differs from the previous one, so the window cursor should be changed.
+    pub fn update_cursor(&mut self, comp: &Comp) -> Option<CursorIcon> {
         let pos = self.last_pos();
-        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_down(pos, button)))
+        let cursor = WindowHandle::cursor()
+            .or_else(|| comp.cursor_at(pos.x, pos.y))
+            .unwrap_or_default();
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            Some(cursor)
+        } else {
+            None
+        }
+    }
+
+    pub fn pressed_comp(&self, comp: &mut Comp, button: MouseButton) -> bool {
+        let (pos, modifiers) = (self.last_pos(), self.modifiers);
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_down(pos, button, modifiers)))
     }
 
-    pub fn mouse_scroll(&self, comp: &mut Comp, delta: (f32, f32)) {
+    pub fn released_comp(&self, comp: &mut Comp, button: MouseButton) -> bool {
+        let (pos, modifiers) = (self.last_pos(), self.modifiers);
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_up(pos, button, modifiers)))
+    }
+
+    pub fn moved_comp(&self, comp: &mut Comp) -> bool {
+        let pos = self.last_pos();
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_move(pos)))
+    }
+
+    pub fn mouse_scroll(&self, comp: &mut Comp, delta: (f32, f32)) -> bool {
         let pos = self.last_pos();
         comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_scroll(MouseScroll {
             pos,
+            local_pos: pos,
             delta,
+            modifiers: self.modifiers,
         })))
     }
+
+    pub fn file_hovered_comp(&self, comp: &mut Comp, path: PathBuf) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::FileHover(self.file_drag(path))))
+    }
+
+    pub fn file_hover_cancelled_comp(&self, comp: &mut Comp) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::FileHoverCancel))
+    }
+
+    pub fn file_dropped_comp(&self, comp: &mut Comp, path: PathBuf) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::FileDrop(self.file_drag(path))))
+    }
+
+    fn file_drag(&self, path: PathBuf) -> FileDrag {
+        let pos = self.last_pos();
+        FileDrag {
+            pos,
+            local_pos: pos,
+            path,
+        }
+    }
 }
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use super::{KeyboardEvent, Modifiers, VirtualKeyCode};
+use crate::Model;
+
+/// Key chord, e.g. Ctrl+S.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct Shortcut {
+    pub key: VirtualKeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl Shortcut {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.modifiers.alt = true;
+        self
+    }
+
+    pub fn logo(mut self) -> Self {
+        self.modifiers.logo = true;
+        self
+    }
+
+    /// The modifiers must be the same, so Ctrl+S does not match the Ctrl+Shift+S press.
+    pub fn matches(&self, event: &KeyboardEvent) -> bool {
+        event.keycode == Some(self.key) && event.modifiers == self.modifiers
+    }
+}
+
+pub type ShortcutTrigger<M> = fn() -> <M as Model>::Message;
+
+/// Shortcuts of the component, they are matched before the key press is dispatched to the views.
+pub struct Shortcuts<M: Model> {
+    bindings: Vec<(Shortcut, ShortcutTrigger<M>)>,
+}
+
+impl<M: Model> Default for Shortcuts<M> {
+    fn default() -> Self {
+        Self { bindings: vec![] }
+    }
+}
+
+impl<M: Model> Shortcuts<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(mut self, shortcut: Shortcut, trigger: ShortcutTrigger<M>) -> Self {
+        self.bindings.push((shortcut, trigger));
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Shortcut> + '_ {
+        self.bindings.iter().map(|(shortcut, _)| *shortcut)
+    }
+
+    /// The message of the first shortcut, which matches the key press.
+    pub fn trigger(&self, event: &KeyboardEvent) -> Option<M::Message> {
+        self.bindings
+            .iter()
+            .find(|(shortcut, _)| shortcut.matches(event))
+            .map(|(_, trigger)| trigger())
+    }
+}
+
+/// The shortcuts, which are registered more than once. Only the first registration in the declaration order is
+/// triggered by the key press.
+pub fn shortcut_conflicts(shortcuts: impl IntoIterator<Item = Shortcut>) -> Vec<Shortcut> {
+    let mut registered = HashSet::new();
+    let mut conflicts = vec![];
+    for shortcut in shortcuts {
+        if !registered.insert(shortcut) && !conflicts.contains(&shortcut) {
+            conflicts.push(shortcut);
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_chords() {
+        let save = Shortcut::new(VirtualKeyCode::S).ctrl();
+        let press = |modifiers| KeyboardEvent {
+            scancode: 0,
+            keycode: Some(VirtualKeyCode::S),
+            modifiers,
+        };
+        assert!(save.matches(&press(save.modifiers)));
+        assert!(!save.matches(&press(save.shift().modifiers)));
+        assert!(!save.matches(&press(Modifiers::NONE)));
+
+        let conflicts = shortcut_conflicts(vec![save, save.shift(), save, save]);
+        assert_eq!(conflicts, [save]);
+    }
+}
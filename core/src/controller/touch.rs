@@ -0,0 +1,213 @@
+use super::{GestureThresholds, InputEvent, MousePos};
+use crate::{Comp, Real, SystemMessage};
+
+/// The finger contact, the `id` is unique while the finger touches the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Touch {
+    pub id: u64,
+    pub pos: MousePos,
+    /// Position in the local coordinates of the listener shape, it is set by the primitive.
+    pub local_pos: MousePos,
+}
+
+impl Touch {
+    pub fn new(id: u64, pos: MousePos) -> Self {
+        Self {
+            id,
+            pos,
+            local_pos: pos,
+        }
+    }
+}
+
+/// Short touch without moving, like the click of the mouse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tap {
+    /// Release position in the window coordinates.
+    pub pos: MousePos,
+    /// Release position in the local coordinates of the tapped shape.
+    pub local_pos: MousePos,
+}
+
+/// Movement of the touches, the position is the center of all the touches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pan {
+    pub start: MousePos,
+    pub pos: MousePos,
+    pub local_pos: MousePos,
+    /// Movement since the previous pan event.
+    pub delta: (Real, Real),
+}
+
+/// Change of the distance between the first two touches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pinch {
+    pub center: MousePos,
+    pub local_center: MousePos,
+    /// The distance relative to the distance at the pinch start.
+    pub scale: Real,
+    /// The scale relative to the previous pinch event.
+    pub delta: Real,
+}
+
+/// Recognizer of the tap, pan and pinch gestures by the touches, which have started over the primitive.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TouchGestures {
+    touches: Vec<Touch>,
+    start: MousePos,
+    center: MousePos,
+    panning: bool,
+    multi_touch: bool,
+    pinch_distance: Option<Real>,
+    scale: Real,
+}
+
+impl TouchGestures {
+    pub fn is_tracked(&self, id: u64) -> bool {
+        self.touches.iter().any(|touch| touch.id == id)
+    }
+
+    pub fn down(&mut self, touch: Touch) {
+        if self.touches.is_empty() {
+            self.start = touch.pos;
+            self.panning = false;
+            self.multi_touch = false;
+        }
+        self.touches.push(touch);
+        self.multi_touch |= self.touches.len() > 1;
+        self.update_touches();
+    }
+
+    /// Moves the tracked touch. Returns the pan if the center of the touches is moved far enough from the start, and
+    /// the pinch if there are two touches at least.
+    pub fn move_to(&mut self, touch: Touch, thresholds: &GestureThresholds) -> (Option<Pan>, Option<Pinch>) {
+        match self.touches.iter_mut().find(|tracked| tracked.id == touch.id) {
+            Some(tracked) if tracked.pos != touch.pos => tracked.pos = touch.pos,
+            _ => return (None, None),
+        }
+
+        let center = self.center();
+        let delta = (center.x - self.center.x, center.y - self.center.y);
+        self.center = center;
+        self.panning |= self.start.distance(center) > thresholds.tap_distance;
+        let pan = if self.panning {
+            Some(Pan {
+                start: self.start,
+                pos: center,
+                local_pos: center,
+                delta,
+            })
+        } else {
+            None
+        };
+
+        let pinch = match (self.pinch_distance, self.distance()) {
+            (Some(start), Some(distance)) if start > 0.0 => {
+                let scale = distance / start;
+                let delta = scale / self.scale;
+                self.scale = scale;
+                Some(Pinch {
+                    center,
+                    local_center: center,
+                    scale,
+                    delta,
+                })
+            }
+            _ => None,
+        };
+        (pan, pinch)
+    }
+
+    /// Releases the tracked touch. Returns the tap position, if the single touch is released without the pan.
+    pub fn up(&mut self, touch: Touch) -> Option<MousePos> {
+        let idx = self.touches.iter().position(|tracked| tracked.id == touch.id)?;
+        self.touches.remove(idx);
+        self.update_touches();
+        if self.touches.is_empty() && !self.panning && !self.multi_touch {
+            Some(touch.pos)
+        } else {
+            None
+        }
+    }
+
+    /// Restarts the center and the pinch, when the touch count is changed, so the gestures do not jump.
+    fn update_touches(&mut self) {
+        self.center = self.center();
+        self.pinch_distance = self.distance();
+        self.scale = 1.0;
+    }
+
+    fn center(&self) -> MousePos {
+        let count = self.touches.len().max(1) as Real;
+        let (x, y) = self
+            .touches
+            .iter()
+            .fold((0.0, 0.0), |(x, y), touch| (x + touch.pos.x, y + touch.pos.y));
+        MousePos {
+            x: x / count,
+            y: y / count,
+        }
+    }
+
+    fn distance(&self) -> Option<Real> {
+        match self.touches.as_slice() {
+            [first, second, ..] => Some(first.pos.distance(second.pos)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct TouchController;
+
+impl TouchController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn pressed_comp(&self, comp: &mut Comp, touch: Touch) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::TouchDown(touch)))
+    }
+
+    pub fn moved_comp(&self, comp: &mut Comp, touch: Touch) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::TouchMove(touch)))
+    }
+
+    /// Sends the release of the touch, the canceled touch is released too.
+    pub fn released_comp(&self, comp: &mut Comp, touch: Touch) -> bool {
+        comp.send_system_msg(SystemMessage::Input(InputEvent::TouchUp(touch)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(id: u64, x: Real, y: Real) -> Touch {
+        Touch::new(id, MousePos { x, y })
+    }
+
+    #[test]
+    fn recognize_gestures() {
+        let thresholds = GestureThresholds::default();
+        let mut gestures = TouchGestures::default();
+
+        gestures.down(touch(1, 10.0, 10.0));
+        assert_eq!(gestures.move_to(touch(1, 12.0, 10.0), &thresholds), (None, None));
+        assert_eq!(gestures.up(touch(1, 12.0, 10.0)), Some(MousePos { x: 12.0, y: 10.0 }));
+
+        gestures.down(touch(1, 10.0, 10.0));
+        let (pan, _) = gestures.move_to(touch(1, 40.0, 10.0), &thresholds);
+        assert_eq!(pan.map(|pan| pan.delta), Some((30.0, 0.0)));
+        assert_eq!(gestures.up(touch(1, 40.0, 10.0)), None);
+
+        gestures.down(touch(1, 0.0, 0.0));
+        gestures.down(touch(2, 10.0, 0.0));
+        let (_, pinch) = gestures.move_to(touch(2, 20.0, 0.0), &thresholds);
+        assert_eq!(pinch.map(|pinch| pinch.scale), Some(2.0));
+        let (_, pinch) = gestures.move_to(touch(1, 10.0, 0.0), &thresholds);
+        assert_eq!(pinch.map(|pinch| (pinch.scale, pinch.delta)), Some((1.0, 0.5)));
+        gestures.up(touch(1, 10.0, 0.0));
+        assert_eq!(gestures.up(touch(2, 20.0, 0.0)), None);
+    }
+}
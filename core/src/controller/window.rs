@@ -0,0 +1,105 @@
+use std::{cell::RefCell, mem};
+
+use super::CursorIcon;
+use crate::{scheduler::wake_frame, Real};
+
+/// Changes of the window requested since the last frame, the controller applies them to its window.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct WindowChanges {
+    pub title: Option<String>,
+    /// Inner size in the logical pixels, the window system may adjust it.
+    pub size: Option<(Real, Real)>,
+    pub fullscreen: Option<bool>,
+}
+
+impl WindowChanges {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Default)]
+struct WindowState {
+    changes: WindowChanges,
+    fullscreen: bool,
+    cursor: Option<CursorIcon>,
+}
+
+thread_local! {
+    static STATE: RefCell<WindowState> = RefCell::new(WindowState::default());
+}
+
+/// Window service of the UI thread, the model update changes the window by it without the access to the windowing
+/// library. The controller is woken up and applies the changes before the next frame.
+pub struct WindowHandle;
+
+impl WindowHandle {
+    pub fn set_title(title: impl Into<String>) {
+        let title = title.into();
+        Self::change(|state| state.changes.title = Some(title));
+    }
+
+    /// Requests the inner size of the window in the logical pixels. The window gets the `WindowResized` message, when
+    /// the size is changed.
+    pub fn request_size(width: Real, height: Real) {
+        Self::change(|state| state.changes.size = Some((width, height)));
+    }
+
+    pub fn set_fullscreen(fullscreen: bool) {
+        Self::change(|state| {
+            state.fullscreen = fullscreen;
+            state.changes.fullscreen = Some(fullscreen);
+        });
+    }
+
+    pub fn toggle_fullscreen() {
+        Self::set_fullscreen(!Self::is_fullscreen());
+    }
+
+    /// The last requested fullscreen mode.
+    pub fn is_fullscreen() -> bool {
+        STATE.with(|state| state.borrow().fullscreen)
+    }
+
+    /// Sets the cursor over the whole window, e.g. the wait cursor during the long operation. It replaces the cursors
+    /// of the shapes until it is reset by `None`.
+    pub fn set_cursor(cursor: Option<CursorIcon>) {
+        Self::change(|state| state.cursor = cursor);
+    }
+
+    pub fn cursor() -> Option<CursorIcon> {
+        STATE.with(|state| state.borrow().cursor)
+    }
+
+    /// Takes the changes requested since the last call.
+    pub fn take_changes() -> WindowChanges {
+        STATE.with(|state| mem::take(&mut state.borrow_mut().changes))
+    }
+
+    fn change(change: impl FnOnce(&mut WindowState)) {
+        STATE.with(|state| change(&mut state.borrow_mut()));
+        wake_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_window_changes() {
+        WindowHandle::set_title("Draft");
+        WindowHandle::set_title("Document");
+        WindowHandle::request_size(640.0, 480.0);
+        WindowHandle::toggle_fullscreen();
+        assert!(WindowHandle::is_fullscreen());
+
+        assert_eq!(WindowHandle::take_changes(), WindowChanges {
+            title: Some("Document".to_string()),
+            size: Some((640.0, 480.0)),
+            fullscreen: Some(true),
+        });
+        assert!(WindowHandle::take_changes().is_empty());
+        assert!(WindowHandle::is_fullscreen());
+    }
+}
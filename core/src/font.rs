@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use font_kit::{
+    family_name::FamilyName,
+    font::Font,
+    hinting::HintingOptions,
+    outline::OutlineSink,
+    properties::{Properties, Style, Weight},
+    source::SystemSource,
+};
+
+use crate::{FontStyle, FontWeight, GlyphPos, Real, TextMetrics};
+
+/// Error of the font lookup, which the renders wrap into their errors.
+#[derive(Debug)]
+pub enum FontError {
+    FontNotFound(String),
+    GlyphNotFound(u32, String),
+}
+
+/// Resolves the font names of the texts to the loaded fonts. The names which are not loaded by the render are looked
+/// up as the system font families, the CSS generic families like `sans-serif` or `monospace` are supported too. If
+/// the font is not found, the fallback fonts are tried in order. The glyph data `G` of the render, e.g. the glyph
+/// mesh or path, is built from the glyph outline once and cached.
+pub struct FontCache<G = ()> {
+    pub fallbacks: Vec<String>,
+    fonts: HashMap<String, Font>,
+    /// Names of the fallback fonts used for the not found fonts.
+    resolved: HashMap<String, String>,
+    /// Loaded faces of the font families.
+    faces: HashMap<String, Vec<FontFace>>,
+    matched_faces: HashMap<(String, FontWeight, FontStyle), FaceMatch>,
+    glyphs: HashMap<(String, u32), G>,
+}
+
+#[derive(Debug, Clone)]
+struct FontFace {
+    name: String,
+    weight: FontWeight,
+    style: FontStyle,
+}
+
+/// Font face found for the requested weight and style. If the font family has no such face, the closest face is
+/// used and the missing italic is synthesized by the render.
+#[derive(Debug, Clone)]
+pub struct FaceMatch {
+    pub name: String,
+    pub synthetic_bold: bool,
+    pub synthetic_italic: bool,
+}
+
+impl<G> Default for FontCache<G> {
+    fn default() -> Self {
+        Self {
+            fallbacks: vec!["sans-serif".to_string()],
+            fonts: HashMap::new(),
+            resolved: HashMap::new(),
+            faces: HashMap::new(),
+            matched_faces: HashMap::new(),
+            glyphs: HashMap::new(),
+        }
+    }
+}
+
+impl<G: Clone> FontCache<G> {
+    pub fn add(&mut self, name: impl Into<String>, font: Font) {
+        self.fonts.insert(name.into(), font);
+    }
+
+    /// Resolves the font by the name and returns the name of the loaded font.
+    pub fn resolve(&mut self, name: &str) -> Result<String, FontError> {
+        if let Ok(name) = self.find(name) {
+            return Ok(name.to_string());
+        }
+        if let Some(font) = load_system_font(name) {
+            self.add(name, font);
+            return Ok(name.to_string());
+        }
+
+        for fallback in self.fallbacks.clone() {
+            if !self.fonts.contains_key(&fallback) {
+                if let Some(font) = load_system_font(&fallback) {
+                    self.add(fallback.clone(), font);
+                }
+            }
+            if self.fonts.contains_key(&fallback) {
+                self.resolved.insert(name.to_string(), fallback.clone());
+                return Ok(fallback);
+            }
+        }
+        Err(FontError::FontNotFound(name.to_string()))
+    }
+
+    /// Finds the name of the font, that is already loaded or resolved.
+    pub fn find<'a>(&'a self, name: &'a str) -> Result<&'a str, FontError> {
+        let loaded_name = self.resolved.get(name).map(|name| name.as_str()).unwrap_or(name);
+        if self.fonts.contains_key(loaded_name) {
+            Ok(loaded_name)
+        } else {
+            Err(FontError::FontNotFound(name.to_string()))
+        }
+    }
+
+    /// Registers the loaded font as the face of the font family.
+    pub fn add_face(
+        &mut self, family: impl Into<String>, weight: FontWeight, style: FontStyle, name: impl Into<String>,
+    ) {
+        let family = family.into();
+        self.matched_faces
+            .retain(|(matched_family, ..), _| *matched_family != family);
+        self.faces.entry(family).or_default().push(FontFace {
+            name: name.into(),
+            weight,
+            style,
+        });
+    }
+
+    /// Resolves the face of the font family. The loaded faces go first, then the system faces. If the family has no
+    /// face of the weight and style, the closest face is matched.
+    pub fn resolve_face(&mut self, family: &str, weight: FontWeight, style: FontStyle) -> Result<FaceMatch, FontError> {
+        let key = (family.to_string(), weight, style);
+        if let Some(face_match) = self.matched_faces.get(&key) {
+            return Ok(face_match.clone());
+        }
+
+        let faces = self.faces.get(family).map(|faces| faces.as_slice()).unwrap_or_default();
+        let exact = faces.iter().find(|face| face.weight == weight && face.style == style);
+        let face = if let Some(face) = exact {
+            face.clone()
+        } else if let Some((face, font)) = load_system_face(family, weight, style) {
+            self.add(face.name.clone(), font);
+            self.faces.entry(family.to_string()).or_default().push(face.clone());
+            face
+        } else if let Some(face) = faces.iter().min_by_key(|face| {
+            (
+                u16::from(face.style != style),
+                (i32::from(face.weight.0) - i32::from(weight.0)).abs(),
+            )
+        }) {
+            face.clone()
+        } else {
+            FontFace {
+                name: self.resolve(family)?,
+                weight: FontWeight::NORMAL,
+                style: FontStyle::Normal,
+            }
+        };
+
+        let face_match = FaceMatch {
+            synthetic_bold: weight.is_bold() && !face.weight.is_bold(),
+            synthetic_italic: style != FontStyle::Normal && face.style == FontStyle::Normal,
+            name: face.name,
+        };
+        self.matched_faces.insert(key, face_match.clone());
+        Ok(face_match)
+    }
+
+    /// Finds the face, that is already resolved.
+    pub fn find_face(&self, family: &str, weight: FontWeight, style: FontStyle) -> FaceMatch {
+        self.matched_faces
+            .get(&(family.to_string(), weight, style))
+            .cloned()
+            .unwrap_or_else(|| FaceMatch {
+                name: self.find(family).unwrap_or(family).to_string(),
+                synthetic_bold: false,
+                synthetic_italic: false,
+            })
+    }
+
+    /// Loaded font by the name, which is resolved before.
+    pub fn font(&self, name: &str) -> Result<&Font, FontError> {
+        self.fonts
+            .get(name)
+            .ok_or_else(|| FontError::FontNotFound(name.to_string()))
+    }
+
+    pub fn metrics(&self, name: &str, font_size: Real) -> Result<TextMetrics, FontError> {
+        let metrics = self.font(name)?.metrics();
+        let scale = font_size / metrics.units_per_em as f32;
+        Ok(TextMetrics {
+            ascender: metrics.ascent * scale,
+            descender: metrics.descent * scale,
+            line_height: (metrics.ascent - metrics.descent + metrics.line_gap) * scale,
+        })
+    }
+
+    /// Glyph ids of the characters, the missing glyphs are replaced by the glyph of `?`.
+    pub fn glyph_ids(&self, name: &str, text: &str) -> Result<Vec<u32>, FontError> {
+        let font = self.font(name)?;
+        let fallback = font.glyph_for_char('?').unwrap_or(0);
+        Ok(text
+            .chars()
+            .map(|ch| font.glyph_for_char(ch).unwrap_or(fallback))
+            .collect())
+    }
+
+    /// Positions of the glyphs started at the zero origin by the glyph advances.
+    pub fn glyph_positions(&self, name: &str, text: &str, font_size: Real) -> Result<Vec<GlyphPos>, FontError> {
+        let font = self.font(name)?;
+        let scale = font_size / font.metrics().units_per_em as f32;
+        let mut x = 0.0;
+        let mut positions = Vec::with_capacity(text.len());
+        for glyph_id in self.glyph_ids(name, text)? {
+            let width = font.advance(glyph_id).map(|advance| advance.x()).unwrap_or(0.0) * scale;
+            positions.push(GlyphPos { x, y: 0.0, width });
+            x += width;
+        }
+        Ok(positions)
+    }
+
+    /// Draws the outline of the glyph in the font units into the sink.
+    pub fn outline(&self, name: &str, glyph_id: u32, sink: &mut impl OutlineSink) -> Result<(), FontError> {
+        self.font(name)?
+            .outline(glyph_id, HintingOptions::None, sink)
+            .map_err(|_| FontError::GlyphNotFound(glyph_id, name.to_string()))
+    }
+
+    /// Number of the font units in the em, the glyph outlines are scaled by its inverse to the em units.
+    pub fn units_per_em(&self, name: &str) -> Result<u32, FontError> {
+        Ok(self.font(name)?.metrics().units_per_em)
+    }
+
+    /// Returns the cached data of the glyph, the data is built by the render on the first use.
+    pub fn glyph<E>(&mut self, name: &str, glyph_id: u32, build: impl FnOnce(&Self) -> Result<G, E>) -> Result<G, E> {
+        let key = (name.to_string(), glyph_id);
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return Ok(glyph.clone());
+        }
+
+        let glyph = build(self)?;
+        self.glyphs.insert(key, glyph.clone());
+        Ok(glyph)
+    }
+}
+
+fn family_name(name: &str) -> FamilyName {
+    match name {
+        "serif" => FamilyName::Serif,
+        "sans-serif" => FamilyName::SansSerif,
+        "monospace" => FamilyName::Monospace,
+        "cursive" => FamilyName::Cursive,
+        "fantasy" => FamilyName::Fantasy,
+        _ => FamilyName::Title(name.to_string()),
+    }
+}
+
+/// Loads the best match of the system font family.
+fn load_system_font(name: &str) -> Option<Font> {
+    SystemSource::new()
+        .select_best_match(&[family_name(name)], &Properties::new())
+        .ok()?
+        .load()
+        .ok()
+}
+
+/// Loads the system face of the font family, if the family has the face of the weight and style.
+fn load_system_face(family: &str, weight: FontWeight, style: FontStyle) -> Option<(FontFace, Font)> {
+    if weight == FontWeight::NORMAL && style == FontStyle::Normal {
+        return None;
+    }
+
+    let source = SystemSource::new();
+    let properties = Properties {
+        weight: Weight(f32::from(weight.0)),
+        style: match style {
+            FontStyle::Normal => Style::Normal,
+            FontStyle::Italic => Style::Italic,
+            FontStyle::Oblique => Style::Oblique,
+        },
+        ..Properties::new()
+    };
+    let font = source
+        .select_best_match(&[family_name(family)], &properties)
+        .ok()?
+        .load()
+        .ok()?;
+    // The best match is the regular face, if the family has no face of the weight and style.
+    let properties = font.properties();
+    if properties.weight.0 < Weight::SEMIBOLD.0 && weight.is_bold()
+        || properties.style == Style::Normal && style != FontStyle::Normal
+    {
+        return None;
+    }
+
+    let name = format!("{}:{}:{:?}", family, weight.0, style);
+    Some((FontFace { name, weight, style }, font))
+}
@@ -0,0 +1,1104 @@
+use std::{collections::HashMap, mem, rc::Rc};
+
+use crate::{
+    align_text_baselines, AlignHor, BoundingBox, Clip, CompositeShape, Fill, Filter, FontStyle, FontWeight, GlyphPos,
+    Group, Padding, Real, RecalcCache, Scissor, Shape, Stroke, Text, TextFace, TextMeasurer, TextMetrics, Theme,
+    TransformMatrix,
+};
+
+/// Measurements of the render resources, which the layout pass needs besides the texts.
+pub trait LayoutMeasurer: TextMeasurer {
+    /// Returns the natural size of the image, the image is loaded by the render if it is not loaded yet.
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error>;
+
+    /// Prepares the render resources of the shape before it is laid out, e.g. the images of the conic gradients.
+    #[allow(unused_variables)]
+    fn prepare_shape(&mut self, shape: &Shape, defaults: &ShapeDefaults) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Cache of the measured text lines, the measurers without it measure the texts on every recalculation.
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        None
+    }
+}
+
+/// Properties inherited by the shapes from the groups and the styles of their ancestors.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShapeDefaults {
+    pub transparency: Real,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+    pub clip: Clip,
+    pub font_size: Real,
+    pub theme: Rc<Theme>,
+    /// Distance, by which the filters of the ancestor groups spread the drawn shapes.
+    pub filter_spread: Real,
+}
+
+impl ShapeDefaults {
+    pub fn new(theme: Rc<Theme>) -> Self {
+        Self {
+            theme,
+            font_size: Text::DEFAULT_FONT_SIZE,
+            ..Default::default()
+        }
+    }
+
+    /// Defaults of the styled shape and its children, the shape style is set over the inherited values.
+    pub fn styled(&self, shape: Option<&Shape>) -> Option<ShapeDefaults> {
+        let style = self.theme.resolve(shape?.style()?);
+        let mut defaults = self.clone();
+        if let Some(transparency) = style.transparency {
+            defaults.transparency = transparency;
+        }
+        if style.fill.is_some() {
+            defaults.fill = style.fill;
+        }
+        if style.stroke.is_some() {
+            defaults.stroke = style.stroke;
+        }
+        if let Some(font_size) = style.font_size {
+            defaults.font_size = font_size;
+        }
+        Some(defaults)
+    }
+
+    /// Sets the group overrides for the children of the group. The group clip is intersected with the clips of the
+    /// ancestors.
+    pub fn apply_group(&mut self, group: &Group) {
+        if let Some(transparency) = group.transparency {
+            self.transparency = transparency;
+        }
+        if let Some(fill) = &group.fill {
+            self.fill = Some(fill.clone());
+        }
+        if let Some(stroke) = &group.stroke {
+            self.stroke = Some(stroke.clone());
+        }
+        if !group.clip.is_none() {
+            self.clip = group.clip.intersect(&self.clip).into_owned();
+        }
+        self.filter_spread += group.filters.iter().map(Filter::spread).sum::<Real>();
+    }
+}
+
+/// Inputs of the rendered layer of the cached group, the render reuses the layer while they are the same.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerState {
+    revision: u64,
+    defaults: ShapeDefaults,
+}
+
+impl LayerState {
+    /// Returns the state of the cached group layer, which is rendered with the defaults. The group, which is not
+    /// cached or is not laid out yet, has no state.
+    pub fn of(composite: &dyn CompositeShape, defaults: &ShapeDefaults) -> Option<Self> {
+        match composite.shape() {
+            Some(Shape::Group(group)) if group.cache => Some(Self {
+                revision: composite.recalc_cache()?.revision,
+                defaults: defaults.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Window areas, which are changed since the last frame and have to be redrawn. The overlapping areas are merged.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Damage {
+    rects: Vec<BoundingBox>,
+}
+
+impl Damage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the changed area, the empty areas are ignored.
+    pub fn add(&mut self, mut area: BoundingBox) {
+        if !(area.width() > 0.0 && area.height() > 0.0) {
+            return;
+        }
+        while let Some(idx) = self.rects.iter().position(|rect| rect.intersect(&area).is_some()) {
+            area = area.union(&self.rects.swap_remove(idx));
+        }
+        self.rects.push(area);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Changed areas, which do not overlap each other.
+    pub fn rects(&self) -> &[BoundingBox] {
+        &self.rects
+    }
+
+    /// Bound of all the changed areas.
+    pub fn bound(&self) -> Option<BoundingBox> {
+        let (first, rest) = self.rects.split_first()?;
+        Some(rest.iter().fold(*first, |bound, rect| bound.union(rect)))
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+}
+
+/// Measured text lines, which are kept between the recalculations, so the texts with the same content, face and size
+/// are not measured again. The lines, which are not used for a long time, are dropped.
+#[derive(Debug)]
+pub struct TextCache {
+    capacity: usize,
+    current: HashMap<TextKey, TextLayout>,
+    /// Lines of the previous generation, they are moved into the current one when used.
+    last: HashMap<TextKey, TextLayout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    content: String,
+    family: String,
+    weight: FontWeight,
+    style: FontStyle,
+    font_size: u32,
+}
+
+impl TextKey {
+    fn new(text: &str, face: TextFace, font_size: Real) -> Self {
+        Self {
+            content: text.to_string(),
+            family: face.family.to_string(),
+            weight: face.weight,
+            style: face.style,
+            font_size: font_size.to_bits(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TextLayout {
+    advance: Real,
+    metrics: TextMetrics,
+    glyph_positions: Option<Vec<GlyphPos>>,
+}
+
+impl Default for TextCache {
+    fn default() -> Self {
+        Self::with_capacity(TextCache::DEFAULT_CAPACITY)
+    }
+}
+
+impl TextCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cache keeps from `capacity` to the twice as many of the recently used lines.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            current: HashMap::new(),
+            last: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.len() + self.last.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops all the lines, e.g. when the fonts are loaded and the names resolve to the other fonts.
+    pub fn clear(&mut self) {
+        self.current.clear();
+        self.last.clear();
+    }
+
+    fn get(&mut self, key: &TextKey) -> Option<&mut TextLayout> {
+        if !self.current.contains_key(key) {
+            let layout = self.last.remove(key)?;
+            self.insert(key.clone(), layout);
+        }
+        self.current.get_mut(key)
+    }
+
+    fn insert(&mut self, key: TextKey, layout: TextLayout) {
+        if self.current.len() >= self.capacity {
+            self.last = mem::take(&mut self.current);
+        }
+        self.current.insert(key, layout);
+    }
+}
+
+/// Lays out the whole tree in the `bound` of the window. The percent and auto values are resolved, the texts are
+/// measured and the bounds and global transforms of the shapes are calculated, so the render only draws them.
+pub fn recalc<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, composite: &mut dyn CompositeShape, bound: BoundingBox, theme: Rc<Theme>,
+) -> Result<BoundingBox, M::Error> {
+    recalc_with_damage(measurer, composite, bound, theme, &mut Damage::new())
+}
+
+/// Lays out the whole tree like `recalc` and adds the window areas of the shapes, which are changed since the last
+/// recalculation, into the `damage`, so the render is able to redraw only them.
+pub fn recalc_with_damage<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, composite: &mut dyn CompositeShape, bound: BoundingBox, theme: Rc<Theme>, damage: &mut Damage,
+) -> Result<BoundingBox, M::Error> {
+    recalc_in_view(measurer, composite, bound, TransformMatrix::identity(), theme, damage)
+}
+
+/// Lays out the whole tree like `recalc_with_damage` under the camera transform of the view, which transforms the
+/// scene into the window. The global transforms of the shapes include it, the `bound` is not transformed.
+pub fn recalc_in_view<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, composite: &mut dyn CompositeShape, bound: BoundingBox, view_transform: TransformMatrix,
+    theme: Rc<Theme>, damage: &mut Damage,
+) -> Result<BoundingBox, M::Error> {
+    recalc_composite(
+        measurer,
+        composite,
+        bound,
+        view_transform,
+        &mut ShapeDefaults::new(theme),
+        damage,
+    )
+}
+
+/// Lays out the subtree in the parent bound, the subtree which is not changed since the last recalculation with the
+/// same inputs is skipped. The areas of the changed shapes are added into the `damage`.
+pub fn recalc_composite<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, composite: &mut dyn CompositeShape, parent_bound: BoundingBox,
+    mut parent_global_transform: TransformMatrix, defaults: &mut ShapeDefaults, damage: &mut Damage,
+) -> Result<BoundingBox, M::Error> {
+    if let Some(area) = composite.damage() {
+        damage.add(area.outset(defaults.filter_spread));
+        composite.set_damage(None);
+    }
+    let (parent_transform, parent_font_size) = (parent_global_transform, defaults.font_size);
+    if let Some(bound) = composite.cached_bound(parent_bound, parent_transform, parent_font_size) {
+        return Ok(bound);
+    }
+
+    let mut styled_defaults = defaults.styled(composite.shape());
+    let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+    let mut bound = parent_bound;
+
+    if let Some(shape) = composite.shape() {
+        measurer.prepare_shape(shape, defaults)?;
+    }
+    let inherited_font_size = defaults.font_size;
+    let mut inherited_clip = None;
+    if let Some(shape) = composite.shape_mut() {
+        defaults.font_size = shape.set_by_em(defaults.font_size);
+        match shape {
+            Shape::Rect(rect) => {
+                if rect.x.set_by_pct(parent_bound.width()) {
+                    rect.x.0 += parent_bound.min_x;
+                }
+                if rect.y.set_by_pct(parent_bound.height()) {
+                    rect.y.0 += parent_bound.min_y;
+                }
+                rect.width.set_by_pct(parent_bound.width());
+                rect.height.set_by_pct(parent_bound.height());
+                if let Some(rounding) = &mut rect.rounding {
+                    let radius = parent_bound.width().min(parent_bound.height());
+                    rounding.top_left.set_by_pct(radius);
+                    rounding.top_right.set_by_pct(radius);
+                    rounding.bottom_left.set_by_pct(radius);
+                    rounding.bottom_right.set_by_pct(radius);
+                }
+                set_by_pct_padding(&mut rect.padding, &parent_bound);
+                set_by_pct_padding(&mut rect.margin, &parent_bound);
+                set_by_pct_clip(&mut rect.clip, &parent_bound);
+
+                bound = BoundingBox {
+                    min_x: rect.x.val(),
+                    min_y: rect.y.val(),
+                    max_x: rect.x.val() + rect.width.val(),
+                    max_y: rect.y.val() + rect.height.val(),
+                };
+
+                parent_global_transform = rect.recalculate_transform(parent_global_transform, &bound);
+                let (scale_x, scale_y) = parent_global_transform.scale_xy();
+                parent_global_transform
+                    .translate_add(rect.padding.left.val() * scale_x, rect.padding.top.val() * scale_y);
+            }
+            Shape::Circle(circle) => {
+                if circle.cx.set_by_pct(parent_bound.width()) {
+                    circle.cx.0 += parent_bound.min_x;
+                }
+                if circle.cy.set_by_pct(parent_bound.height()) {
+                    circle.cy.0 += parent_bound.min_y;
+                }
+                circle.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
+                set_by_pct_padding(&mut circle.padding, &parent_bound);
+                set_by_pct_padding(&mut circle.margin, &parent_bound);
+                set_by_pct_clip(&mut circle.clip, &parent_bound);
+
+                let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                bound = BoundingBox {
+                    min_x: cx - r,
+                    min_y: cy - r,
+                    max_x: cx + r,
+                    max_y: cy + r,
+                };
+
+                parent_global_transform = circle.recalculate_transform(parent_global_transform, &bound);
+                let (scale_x, scale_y) = parent_global_transform.scale_xy();
+                parent_global_transform
+                    .translate_add(circle.padding.left.val() * scale_x, circle.padding.top.val() * scale_y);
+            }
+            Shape::Ellipse(ellipse) => {
+                if ellipse.cx.set_by_pct(parent_bound.width()) {
+                    ellipse.cx.0 += parent_bound.min_x;
+                }
+                if ellipse.cy.set_by_pct(parent_bound.height()) {
+                    ellipse.cy.0 += parent_bound.min_y;
+                }
+                ellipse.rx.set_by_pct(parent_bound.width());
+                ellipse.ry.set_by_pct(parent_bound.height());
+                set_by_pct_padding(&mut ellipse.padding, &parent_bound);
+                set_by_pct_padding(&mut ellipse.margin, &parent_bound);
+                set_by_pct_clip(&mut ellipse.clip, &parent_bound);
+
+                let (cx, cy, rx, ry) = (ellipse.cx.val(), ellipse.cy.val(), ellipse.rx.val(), ellipse.ry.val());
+                bound = BoundingBox {
+                    min_x: cx - rx,
+                    min_y: cy - ry,
+                    max_x: cx + rx,
+                    max_y: cy + ry,
+                };
+
+                parent_global_transform = ellipse.recalculate_transform(parent_global_transform, &bound);
+                let (scale_x, scale_y) = parent_global_transform.scale_xy();
+                parent_global_transform.translate_add(
+                    ellipse.padding.left.val() * scale_x,
+                    ellipse.padding.top.val() * scale_y,
+                );
+            }
+            Shape::Text(text) => {
+                if text.x.set_by_pct(parent_bound.width()) {
+                    text.x.0 += parent_bound.min_x;
+                }
+                if text.y.set_by_pct(parent_bound.height()) {
+                    text.y.0 += parent_bound.min_y;
+                }
+                set_by_pct_clip(&mut text.clip, &parent_bound);
+
+                let face = TextFace::of_text(text);
+                let font_size = text.font_size.val();
+                let (advance, metrics) = cached_measure_face(measurer, &text.content, face, font_size)?;
+
+                if text.spans.is_empty() {
+                    let origin_x = text.x.val() + align_shift(text.align.0, advance);
+                    let glyph_positions = cached_glyph_positions(measurer, &text.content, face, font_size)?;
+                    text.glyph_positions = glyph_positions
+                        .into_iter()
+                        .map(|pos| GlyphPos {
+                            x: origin_x + pos.x,
+                            ..pos
+                        })
+                        .collect();
+                } else {
+                    recalc_spans(measurer, text)?;
+                }
+                text.metrics = Some(metrics);
+                text.layout_on_path();
+                bound = BoundingBox {
+                    min_x: text.x.val(),
+                    min_y: text.y.val(),
+                    max_x: text.x.val() + text.glyph_positions.last().map(|pos| pos.max_x()).unwrap_or(0.0),
+                    max_y: text.y.val() + metrics.line_height as Real,
+                };
+
+                parent_global_transform = text.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Path(path) => {
+                set_by_pct_clip(&mut path.clip, &parent_bound);
+                let path_bound = path.flattened_bound().unwrap_or_default();
+                parent_global_transform = path.recalculate_transform(parent_global_transform, &path_bound);
+
+                let (start_placement, end_placement) = path.marker_placements();
+                let markers = vec![
+                    (path.marker_start.as_mut(), start_placement),
+                    (path.marker_end.as_mut(), end_placement),
+                ];
+                for (marker, placement) in markers {
+                    if let (Some(marker), Some(placement)) = (marker, placement) {
+                        recalc_composite(
+                            measurer,
+                            marker.shape.as_mut(),
+                            parent_bound,
+                            parent_global_transform * placement,
+                            &mut defaults.clone(),
+                            damage,
+                        )?;
+                    }
+                }
+            }
+            Shape::Polygon(polygon) => {
+                set_by_pct_clip(&mut polygon.clip, &parent_bound);
+                if let Some((min_x, min_y, max_x, max_y)) = polygon.bounds() {
+                    bound = BoundingBox {
+                        min_x,
+                        min_y,
+                        max_x,
+                        max_y,
+                    };
+                }
+                parent_global_transform = polygon.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Image(image) => {
+                if image.x.set_by_pct(parent_bound.width()) {
+                    image.x.0 += parent_bound.min_x;
+                }
+                if image.y.set_by_pct(parent_bound.height()) {
+                    image.y.0 += parent_bound.min_y;
+                }
+                image.width.set_by_pct(parent_bound.width());
+                image.height.set_by_pct(parent_bound.height());
+                set_by_pct_clip(&mut image.clip, &parent_bound);
+
+                let (natural_width, natural_height) = measurer.image_size(&image.src)?;
+                image.natural_size = Some((natural_width, natural_height));
+                image.width.set_by_auto(natural_width);
+                image.height.set_by_auto(natural_height);
+
+                bound = BoundingBox {
+                    min_x: image.x.val(),
+                    min_y: image.y.val(),
+                    max_x: image.x.val() + image.width.val(),
+                    max_y: image.y.val() + image.height.val(),
+                };
+
+                parent_global_transform = image.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Custom(custom) => {
+                set_by_pct_clip(&mut custom.clip, &parent_bound);
+                bound = custom.shape.bounds();
+                parent_global_transform = custom.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Sector(sector) => {
+                if sector.cx.set_by_pct(parent_bound.width()) {
+                    sector.cx.0 += parent_bound.min_x;
+                }
+                if sector.cy.set_by_pct(parent_bound.height()) {
+                    sector.cy.0 += parent_bound.min_y;
+                }
+                sector.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
+                set_by_pct_clip(&mut sector.clip, &parent_bound);
+
+                let (cx, cy, r) = (sector.cx.val(), sector.cy.val(), sector.r.val());
+                bound = BoundingBox {
+                    min_x: cx - r,
+                    min_y: cy - r,
+                    max_x: cx + r,
+                    max_y: cy + r,
+                };
+
+                parent_global_transform = sector.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Ring(ring) => {
+                if ring.cx.set_by_pct(parent_bound.width()) {
+                    ring.cx.0 += parent_bound.min_x;
+                }
+                if ring.cy.set_by_pct(parent_bound.height()) {
+                    ring.cy.0 += parent_bound.min_y;
+                }
+                ring.inner_r.set_by_pct(parent_bound.width().min(parent_bound.height()));
+                ring.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
+                set_by_pct_clip(&mut ring.clip, &parent_bound);
+
+                let (cx, cy, r) = (ring.cx.val(), ring.cy.val(), ring.r.val());
+                bound = BoundingBox {
+                    min_x: cx - r,
+                    min_y: cy - r,
+                    max_x: cx + r,
+                    max_y: cy + r,
+                };
+
+                parent_global_transform = ring.recalculate_transform(parent_global_transform, &bound);
+            }
+            Shape::Group(group) => {
+                set_by_pct_clip(&mut group.clip, &parent_bound);
+                parent_global_transform = group.recalculate_transform(parent_global_transform, &parent_bound);
+
+                inherited_clip = Some(defaults.clip.clone());
+                defaults.apply_group(group);
+                if let Some(scroll) = &mut group.scroll {
+                    scroll.clip_bound = defaults.clip.scissor().and_then(Scissor::global_bound);
+                }
+            }
+        }
+    }
+
+    let inner_bound = calc_inner_bound(measurer, composite, bound, parent_global_transform, defaults, damage)?;
+
+    if let Some(shape) = composite.shape_mut() {
+        match shape {
+            Shape::Rect(rect) => {
+                rect.x.set_by_auto(inner_bound.min_x);
+                rect.y.set_by_auto(inner_bound.min_y);
+                rect.width
+                    .set_by_auto(inner_bound.max_x - rect.x.val() + rect.padding.left_and_right().val());
+                rect.height
+                    .set_by_auto(inner_bound.max_y - rect.y.val() + rect.padding.top_and_bottom().val());
+
+                bound = BoundingBox {
+                    min_x: rect.x.val(),
+                    min_y: rect.y.val(),
+                    max_x: rect.x.val() + rect.width.val(),
+                    max_y: rect.y.val() + rect.height.val(),
+                };
+            }
+            Shape::Circle(circle) => {
+                circle.cx.set_by_auto(inner_bound.min_x + inner_bound.width() / 2.0);
+                circle.cy.set_by_auto(inner_bound.min_y + inner_bound.height() / 2.0);
+                circle.r.set_by_auto(
+                    (inner_bound.width() + circle.padding.left_and_right().val())
+                        .max(inner_bound.height() + circle.padding.top_and_bottom().val())
+                        / 2.0,
+                );
+
+                let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                bound = BoundingBox {
+                    min_x: cx - r,
+                    min_y: cy - r,
+                    max_x: cx + r,
+                    max_y: cy + r,
+                };
+            }
+            Shape::Ellipse(ellipse) => {
+                ellipse.cx.set_by_auto(inner_bound.min_x + inner_bound.width() / 2.0);
+                ellipse.cy.set_by_auto(inner_bound.min_y + inner_bound.height() / 2.0);
+                ellipse
+                    .rx
+                    .set_by_auto((inner_bound.width() + ellipse.padding.left_and_right().val()) / 2.0);
+                ellipse
+                    .ry
+                    .set_by_auto((inner_bound.height() + ellipse.padding.top_and_bottom().val()) / 2.0);
+
+                let (cx, cy, rx, ry) = (ellipse.cx.val(), ellipse.cy.val(), ellipse.rx.val(), ellipse.ry.val());
+                bound = BoundingBox {
+                    min_x: cx - rx,
+                    min_y: cy - ry,
+                    max_x: cx + rx,
+                    max_y: cy + ry,
+                };
+            }
+            Shape::Text(text) => {
+                let mut transform = text.transform.matrix();
+                if let (Some(origin), Some(_)) = (text.transform_origin, text.transform.local_matrix()) {
+                    let (x, y) = origin.resolve(&bound);
+                    transform = transform.around(x, y);
+                }
+                let inner_bound_points = transform * inner_bound;
+                let bound_points = transform * bound;
+
+                bound.min_x = bound_points[0].0;
+                bound.max_x = bound.min_x;
+                bound.min_y = bound_points[0].1;
+                bound.max_y = bound.min_y;
+                for idx in 0..4 {
+                    bound.min_x = bound.min_x.min(bound_points[idx].0).min(inner_bound_points[idx].0);
+                    bound.max_x = bound.max_x.max(bound_points[idx].0).max(inner_bound_points[idx].0);
+                    bound.min_y = bound.min_y.min(bound_points[idx].1).min(inner_bound_points[idx].1);
+                    bound.max_y = bound.max_y.max(bound_points[idx].1).max(inner_bound_points[idx].1);
+                }
+            }
+            Shape::Group(group) => group.set_content_bound(inner_bound),
+            _ => (),
+        }
+        shape.set_bound(bound);
+    }
+    let global_bound = composite
+        .shape()
+        .map(|shape| drawn_bound(shape, parent_transform, inner_bound, defaults))
+        .unwrap_or_default();
+    if let Some(shape) = composite.shape() {
+        if let Some(margin) = shape.margin() {
+            bound = bound.expand(margin);
+        }
+    }
+    defaults.font_size = inherited_font_size;
+    if let Some(clip) = inherited_clip {
+        defaults.clip = clip;
+    }
+
+    let prev_global_bound = composite.recalc_cache().map(|cache| cache.global_bound);
+    if prev_global_bound != Some(global_bound) {
+        if let Some(prev_global_bound) = prev_global_bound {
+            damage.add(prev_global_bound.outset(defaults.filter_spread));
+        }
+        damage.add(global_bound.outset(defaults.filter_spread));
+    }
+    composite.set_recalc_cache(Some(RecalcCache {
+        parent_bound,
+        parent_transform,
+        font_size: parent_font_size,
+        bound,
+        global_bound,
+        revision: RecalcCache::next_revision(),
+    }));
+    Ok(bound)
+}
+
+/// Window area, which the laid out shape draws. The stroke and the antialiasing pixels are included.
+fn drawn_bound(
+    shape: &Shape, parent_transform: TransformMatrix, inner_bound: BoundingBox, defaults: &ShapeDefaults,
+) -> BoundingBox {
+    let transform = shape.transform();
+    let matrix = match shape {
+        // The text bound is calculated with the local transform already.
+        Shape::Text(_) if transform.local_matrix().is_some() => parent_transform,
+        Shape::Text(_) => TransformMatrix::identity(),
+        _ => transform.calculated_matrix().unwrap_or_else(|| transform.matrix()),
+    };
+    let bound = match shape {
+        Shape::Group(group) => group
+            .filters
+            .iter()
+            .fold(inner_bound, |bound, filter| bound.outset(filter.spread())),
+        Shape::Path(path) => path.flattened_bound().unwrap_or_default(),
+        _ => shape.bound(),
+    };
+    let stroke_width = match shape {
+        Shape::Group(_) | Shape::Image(_) => 0.0,
+        _ => shape
+            .stroke()
+            .or(defaults.stroke.as_ref())
+            .map(|stroke| stroke.width * stroke.miter_limit.max(1.0) / 2.0)
+            .unwrap_or(0.0),
+    };
+    let (scale_x, scale_y) = matrix.scale_xy();
+    let outset = stroke_width * scale_x.abs().max(scale_y.abs());
+    bound.outset(outset).transformed(matrix).outset(1.0)
+}
+
+fn calc_inner_bound<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, composite: &mut dyn CompositeShape, bound: BoundingBox, parent_global_transform: TransformMatrix,
+    defaults: &mut ShapeDefaults, damage: &mut Damage,
+) -> Result<BoundingBox, M::Error> {
+    let mut child_bounds = Vec::new();
+    if let Some(children) = composite.children_mut() {
+        for child in children {
+            child_bounds.push(recalc_composite(
+                measurer,
+                child,
+                bound,
+                parent_global_transform,
+                defaults,
+                damage,
+            )?);
+        }
+    }
+
+    if matches!(composite.shape(), Some(Shape::Group(group)) if group.align_baselines) {
+        align_text_baselines(composite, &mut child_bounds);
+    }
+
+    Ok(if child_bounds.is_empty() {
+        BoundingBox::default()
+    } else {
+        let mut inner_bound = child_bounds[0];
+        for bound in &child_bounds[1..] {
+            inner_bound = inner_bound.union(bound);
+        }
+        inner_bound
+    })
+}
+
+/// Measures the spans and calculates the glyph positions of the whole span row.
+fn recalc_spans<M: LayoutMeasurer + ?Sized>(measurer: &mut M, text: &mut Text) -> Result<(), M::Error> {
+    let mut widths = Vec::with_capacity(text.spans.len());
+    for span in &text.spans {
+        let face = TextFace::of_span(text, span);
+        let (advance, _) = cached_measure_face(measurer, &span.content, face, text.span_font_size(span))?;
+        widths.push(advance);
+    }
+    text.arrange_spans(widths);
+
+    let mut glyph_positions = Vec::new();
+    for span in &text.spans {
+        let origin_x = text.x.val() + span.offset;
+        let positions = cached_glyph_positions(
+            measurer,
+            &span.content,
+            TextFace::of_span(text, span),
+            text.span_font_size(span),
+        )?;
+        glyph_positions.extend(positions.into_iter().map(|pos| GlyphPos {
+            x: origin_x + pos.x,
+            ..pos
+        }));
+    }
+    text.glyph_positions = glyph_positions;
+    Ok(())
+}
+
+/// Measures the text line by the measurer, if it is not in the text cache of the measurer.
+fn cached_measure_face<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, text: &str, face: TextFace, font_size: Real,
+) -> Result<(Real, TextMetrics), M::Error> {
+    let key = TextKey::new(text, face, font_size);
+    if let Some(layout) = measurer.text_cache().and_then(|cache| cache.get(&key)) {
+        return Ok((layout.advance, layout.metrics));
+    }
+    let (advance, metrics) = measurer.measure_face(text, face, font_size)?;
+    if let Some(cache) = measurer.text_cache() {
+        let layout = TextLayout {
+            advance,
+            metrics,
+            glyph_positions: None,
+        };
+        cache.insert(key, layout);
+    }
+    Ok((advance, metrics))
+}
+
+/// Calculates the glyph positions of the text line, the line is measured before, so the positions are kept in its
+/// cached layout.
+fn cached_glyph_positions<M: LayoutMeasurer + ?Sized>(
+    measurer: &mut M, text: &str, face: TextFace, font_size: Real,
+) -> Result<Vec<GlyphPos>, M::Error> {
+    let key = TextKey::new(text, face, font_size);
+    let cached = measurer
+        .text_cache()
+        .and_then(|cache| cache.get(&key))
+        .and_then(|layout| layout.glyph_positions.clone());
+    if let Some(positions) = cached {
+        return Ok(positions);
+    }
+    let positions = measurer.glyph_positions(text, face, font_size)?;
+    if let Some(layout) = measurer.text_cache().and_then(|cache| cache.get(&key)) {
+        layout.glyph_positions = Some(positions.clone());
+    }
+    Ok(positions)
+}
+
+/// Horizontal shift of the line start from the text position by the alignment.
+fn align_shift(align: AlignHor, advance: Real) -> Real {
+    match align {
+        AlignHor::Left => 0.0,
+        AlignHor::Center => -advance / 2.0,
+        AlignHor::Right => -advance,
+    }
+}
+
+fn set_by_pct_padding(padding: &mut Padding, parent_bound: &BoundingBox) {
+    padding.left.set_by_pct(parent_bound.width());
+    padding.right.set_by_pct(parent_bound.width());
+    padding.top.set_by_pct(parent_bound.height());
+    padding.bottom.set_by_pct(parent_bound.height());
+}
+
+fn set_by_pct_clip(clip: &mut Clip, parent_bound: &BoundingBox) {
+    match clip {
+        Clip::Scissor(scissor) => {
+            scissor.x.set_by_pct(parent_bound.width());
+            scissor.y.set_by_pct(parent_bound.height());
+            scissor.width.set_by_pct(parent_bound.width());
+            scissor.height.set_by_pct(parent_bound.height());
+        }
+        Clip::Circle(circle) => {
+            circle.cx.set_by_pct(parent_bound.width());
+            circle.cy.set_by_pct(parent_bound.height());
+            circle.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
+        }
+        Clip::Path(_) | Clip::None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::{testing::TestModel, Ellipse, Node, Prim, RealValue, Rect, Sector, Transform, TransformOrigin};
+
+    struct MonospaceMeasurer;
+
+    /// Counts the measured text lines, which are not found in its text cache.
+    #[derive(Default)]
+    struct CachedMeasurer {
+        cache: TextCache,
+        measured: usize,
+    }
+
+    impl TextMeasurer for MonospaceMeasurer {
+        type Error = ();
+
+        fn measure(&mut self, text: &str, _font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), ()> {
+            let metrics = TextMetrics {
+                ascender: font_size * 0.8,
+                descender: -font_size * 0.2,
+                line_height: font_size,
+            };
+            Ok((text.chars().count() as Real * font_size * 0.5, metrics))
+        }
+    }
+
+    impl LayoutMeasurer for MonospaceMeasurer {
+        fn image_size(&mut self, _src: &str) -> Result<(Real, Real), ()> {
+            Ok((32.0, 24.0))
+        }
+    }
+
+    impl TextMeasurer for CachedMeasurer {
+        type Error = ();
+
+        fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), ()> {
+            self.measured += 1;
+            MonospaceMeasurer.measure(text, font_name, font_size)
+        }
+    }
+
+    impl LayoutMeasurer for CachedMeasurer {
+        fn image_size(&mut self, _src: &str) -> Result<(Real, Real), ()> {
+            Ok((32.0, 24.0))
+        }
+
+        fn text_cache(&mut self) -> Option<&mut TextCache> {
+            Some(&mut self.cache)
+        }
+    }
+
+    fn window() -> BoundingBox {
+        BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 200.0,
+            max_y: 100.0,
+        }
+    }
+
+    #[test]
+    fn resolve_percents() {
+        let mut shape = Shape::Rect(Rect {
+            x: RealValue::pct(10.0),
+            width: RealValue::pct(50.0),
+            height: 20.0.into(),
+            ..Default::default()
+        });
+        let bound = recalc(&mut MonospaceMeasurer, &mut shape, window(), Rc::default()).unwrap();
+        assert_eq!((bound.min_x, bound.max_x), (20.0, 120.0));
+        assert_eq!(bound.height(), 20.0);
+    }
+
+    #[test]
+    fn ellipse_and_sector_bound() {
+        let mut ellipse = Shape::Ellipse(Ellipse {
+            cx: RealValue::pct(50.0),
+            cy: 50.0.into(),
+            rx: RealValue::pct(10.0),
+            ry: 20.0.into(),
+            ..Default::default()
+        });
+        let bound = recalc(&mut MonospaceMeasurer, &mut ellipse, window(), Rc::default()).unwrap();
+        assert_eq!((bound.min_x, bound.max_x), (80.0, 120.0));
+        assert_eq!((bound.min_y, bound.max_y), (30.0, 70.0));
+
+        let mut sector = Shape::Sector(Sector {
+            cx: 50.0.into(),
+            cy: 50.0.into(),
+            r: RealValue::pct(20.0),
+            end_angle: PI / 2.0,
+            ..Default::default()
+        });
+        let bound = recalc(&mut MonospaceMeasurer, &mut sector, window(), Rc::default()).unwrap();
+        assert_eq!((bound.min_x, bound.max_x), (30.0, 70.0));
+        assert_eq!((bound.min_y, bound.max_y), (30.0, 70.0));
+    }
+
+    #[test]
+    fn transform_by_view() {
+        let mut shape = Shape::Rect(Rect {
+            width: 20.0.into(),
+            height: 20.0.into(),
+            ..Default::default()
+        });
+        let view_transform = TransformMatrix::identity().with_translation(10.0, 0.0).with_scale(2.0, 2.0);
+        let (theme, mut damage) = (Rc::default(), Damage::new());
+        recalc_in_view(&mut MonospaceMeasurer, &mut shape, window(), view_transform, theme, &mut damage).unwrap();
+        let rect = match &shape {
+            Shape::Rect(rect) => rect,
+            _ => unreachable!(),
+        };
+        assert_eq!(rect.transform.global_matrix(), Some(view_transform));
+        assert_eq!(damage.bound().map(|bound| bound.width()), Some(42.0));
+    }
+
+    #[test]
+    fn scale_around_origin() {
+        let mut shape = Shape::Rect(Rect {
+            x: 20.0.into(),
+            y: 20.0.into(),
+            width: 40.0.into(),
+            height: 20.0.into(),
+            transform: Transform::new().with_scale(2.0, 2.0),
+            transform_origin: Some(TransformOrigin::center()),
+            ..Default::default()
+        });
+        recalc(&mut MonospaceMeasurer, &mut shape, window(), Rc::default()).unwrap();
+        let global = shape.transform().global_matrix().unwrap();
+        assert_eq!(global * (40.0, 30.0), (40.0, 30.0));
+        assert_eq!(global * (20.0, 20.0), (0.0, 10.0));
+    }
+
+    #[test]
+    fn measure_centered_text() {
+        let mut shape = Shape::Text(Text {
+            content: "abcd".to_string(),
+            x: 100.0.into(),
+            font_size: 10.0.into(),
+            align: (AlignHor::Center, Default::default()),
+            ..Default::default()
+        });
+        recalc(&mut MonospaceMeasurer, &mut shape, window(), Rc::default()).unwrap();
+        let text = match &shape {
+            Shape::Text(text) => text,
+            _ => unreachable!(),
+        };
+        assert_eq!(text.metrics.map(|metrics| metrics.line_height), Some(10.0));
+        let xs: Vec<_> = text.glyph_positions.iter().map(|pos| pos.x).collect();
+        assert_eq!(xs, vec![90.0, 95.0, 100.0, 105.0]);
+    }
+
+    #[test]
+    fn measure_cached_text_once() {
+        let text = |content: &str| {
+            Shape::Text(Text {
+                content: content.to_string(),
+                font_size: 10.0.into(),
+                ..Default::default()
+            })
+        };
+        let mut measurer = CachedMeasurer::default();
+        recalc(&mut measurer, &mut text("abcd"), window(), Rc::default()).unwrap();
+        let measured = measurer.measured;
+        assert!(measured > 0);
+
+        let mut shape = text("abcd");
+        recalc(&mut measurer, &mut shape, window(), Rc::default()).unwrap();
+        assert_eq!(measurer.measured, measured);
+        let xs: Vec<_> = match &shape {
+            Shape::Text(text) => text.glyph_positions.iter().map(|pos| pos.x).collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(xs, vec![0.0, 5.0, 10.0, 15.0]);
+
+        recalc(&mut measurer, &mut text("abc"), window(), Rc::default()).unwrap();
+        assert!(measurer.measured > measured);
+    }
+
+    #[test]
+    fn merge_overlapping_damage() {
+        let area = |min_x, max_x| BoundingBox {
+            min_x,
+            min_y: 0.0,
+            max_x,
+            max_y: 10.0,
+        };
+        let mut damage = Damage::new();
+        damage.add(area(0.0, 10.0));
+        damage.add(area(20.0, 30.0));
+        damage.add(area(5.0, 5.0));
+        assert_eq!(damage.rects(), [area(0.0, 10.0), area(20.0, 30.0)]);
+
+        damage.add(area(8.0, 22.0));
+        assert_eq!(damage.rects(), [area(0.0, 30.0)]);
+    }
+
+    #[test]
+    fn damage_recalculated_shape() {
+        let mut shape = Shape::Rect(Rect {
+            x: 10.0.into(),
+            y: 10.0.into(),
+            width: 20.0.into(),
+            height: 20.0.into(),
+            stroke: Some(Stroke {
+                width: 2.0,
+                miter_limit: 1.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut damage = Damage::new();
+        recalc_with_damage(&mut MonospaceMeasurer, &mut shape, window(), Rc::default(), &mut damage).unwrap();
+        assert_eq!(
+            damage.bound(),
+            Some(BoundingBox {
+                min_x: 8.0,
+                min_y: 8.0,
+                max_x: 32.0,
+                max_y: 32.0,
+            })
+        );
+    }
+
+    #[test]
+    fn keep_layer_state_of_clean_group() {
+        let rect = Shape::Rect(Rect {
+            width: 20.0.into(),
+            height: 20.0.into(),
+            ..Default::default()
+        });
+        let group = Shape::Group(Group {
+            cache: true,
+            ..Default::default()
+        });
+        let mut view = Prim::<TestModel>::new(
+            "group".into(),
+            group,
+            vec![Node::Prim(Prim::new("rect".into(), rect, vec![], Default::default()))],
+            Default::default(),
+        );
+        let defaults = ShapeDefaults::default();
+
+        recalc(&mut MonospaceMeasurer, &mut view, window(), Rc::default()).unwrap();
+        let state = LayerState::of(&view, &defaults);
+        assert!(state.is_some());
+
+        recalc(&mut MonospaceMeasurer, &mut view, window(), Rc::default()).unwrap();
+        assert_eq!(LayerState::of(&view, &defaults), state);
+
+        view.invalidate();
+        recalc(&mut MonospaceMeasurer, &mut view, window(), Rc::default()).unwrap();
+        assert_ne!(LayerState::of(&view, &defaults), state);
+    }
+    #[test]
+    fn clip_nested_scroll_area() {
+        let outer = Shape::Group(Group {
+            clip: Clip::new_scissor(0.into(), 0.into(), 100.into(), 50.into()),
+            ..Default::default()
+        });
+        let inner = Shape::Group(Group {
+            clip: Clip::new_scissor(0.into(), 30.into(), 100.into(), 50.into()),
+            scroll: Some(Default::default()),
+            ..Default::default()
+        });
+        let mut view = Prim::<TestModel>::new(
+            "outer".into(),
+            outer,
+            vec![Node::Prim(Prim::new("inner".into(), inner, vec![], Default::default()))],
+            Default::default(),
+        );
+
+        recalc(&mut MonospaceMeasurer, &mut view, window(), Rc::default()).unwrap();
+        let inner = match &view.children[0] {
+            Node::Prim(Prim {
+                shape: Shape::Group(group),
+                ..
+            }) => group,
+            _ => unreachable!(),
+        };
+        let clip_bound = inner.scroll.and_then(|scroll| scroll.clip_bound).unwrap();
+        assert_eq!((clip_bound.min_y, clip_bound.max_y), (30.0, 50.0));
+        assert!(inner.intersect(10.0, 40.0));
+        assert!(!inner.intersect(10.0, 60.0));
+    }
+}
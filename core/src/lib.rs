@@ -1,7 +1,25 @@
-pub use self::{controller::*, listener::*, model::*, node::*, render::*};
+pub use self::{
+    animation::*, callback::*, command::*, context::*, controller::*, layout::*, listener::*, model::*, node::*,
+    registry::*, render::*, scheduler::*, store::*,
+};
+#[cfg(feature = "font-kit")]
+pub use self::font::*;
 
+pub mod animation;
+pub mod callback;
+pub mod command;
+pub mod context;
 pub mod controller;
+#[cfg(feature = "font-kit")]
+pub mod font;
+pub mod layout;
 pub mod listener;
 pub mod model;
 pub mod node;
+pub mod registry;
 pub mod render;
+pub mod scheduler;
+pub mod store;
+#[cfg(test)]
+mod testing;
+mod timer;
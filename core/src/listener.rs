@@ -1,10 +1,72 @@
-use std::{ops::Deref, time::Duration};
+use std::{cell::Cell, ops::Deref, time::Duration};
 
-use crate::{KeyboardEvent, Model, MouseDown, MouseScroll, Prim};
+use crate::{
+    Click, Drag, FileDrag, FocusChange, GamepadAxisEvent, GamepadButtonEvent, KeyboardEvent, Model, MouseDown,
+    MouseScroll, MouseUp, Pan, Pinch, Prim, ScrollArea, SystemMessage, Tap, Touch, Viewport,
+};
 
 pub struct On<'a, M: Model, E> {
     pub prim: &'a Prim<M>,
     pub event: E,
+    pub(crate) propagation: &'a Propagation,
+}
+
+impl<'a, M: Model, E> On<'a, M, E> {
+    /// Stops the bubbling of the event, so the ancestors and the underlying siblings of the primitive do not get it.
+    /// The other listeners of the primitive are still called.
+    pub fn stop_propagation(&self) {
+        self.propagation.stop();
+    }
+}
+
+/// Propagation state of the message dispatch. The bubbling input is delivered to the deepest hit primitive first and
+/// then to its ancestors, until a listener stops it.
+#[derive(Default, Debug)]
+pub struct Propagation {
+    stopped: Cell<bool>,
+    pass: Cell<DispatchPass>,
+}
+
+/// Part of the tree, which handles the message in the current dispatch pass.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DispatchPass {
+    #[default]
+    All,
+    /// The portals are drawn over the rest of the tree, so they get the bubbling input first.
+    Portals,
+    InPortal,
+    /// The tree without the portals, which are already handled.
+    Rest,
+}
+
+impl Propagation {
+    pub fn stop(&self) {
+        self.stopped.set(true);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.get()
+    }
+
+    pub(crate) fn pass(&self) -> DispatchPass {
+        self.pass.get()
+    }
+
+    pub(crate) fn set_pass(&self, pass: DispatchPass) {
+        self.pass.set(pass);
+    }
+
+    /// Delivers the message to the tree by the `dispatch`, the bubbling input is delivered in two passes: to the
+    /// portals and then to the rest of the tree.
+    pub(crate) fn dispatch(msg: &SystemMessage, mut dispatch: impl FnMut(&Propagation)) {
+        let propagation = Propagation::default();
+        if matches!(msg, SystemMessage::Input(input) if input.is_bubbling()) {
+            propagation.set_pass(DispatchPass::Portals);
+            dispatch(&propagation);
+            propagation.set_pass(DispatchPass::Rest);
+        }
+        dispatch(&propagation);
+    }
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -14,11 +76,32 @@ impl EventName {
     pub const DRAW: EventName = EventName("Draw");
     pub const ON_BLUR: EventName = EventName("OnBlur");
     pub const ON_CLICK: EventName = EventName("OnClick");
+    pub const ON_CONTEXT_MENU: EventName = EventName("OnContextMenu");
+    pub const ON_DOUBLE_CLICK: EventName = EventName("OnDoubleClick");
+    pub const ON_DRAG: EventName = EventName("OnDrag");
+    pub const ON_DRAG_END: EventName = EventName("OnDragEnd");
+    pub const ON_DRAG_START: EventName = EventName("OnDragStart");
+    pub const ON_FILE_DROP: EventName = EventName("OnFileDrop");
+    pub const ON_FILE_HOVER: EventName = EventName("OnFileHover");
+    pub const ON_FILE_HOVER_CANCEL: EventName = EventName("OnFileHoverCancel");
+    pub const ON_FOCUS: EventName = EventName("OnFocus");
+    pub const ON_GAMEPAD_AXIS: EventName = EventName("OnGamepadAxis");
+    pub const ON_GAMEPAD_BUTTON_DOWN: EventName = EventName("OnGamepadButtonDown");
+    pub const ON_GAMEPAD_BUTTON_UP: EventName = EventName("OnGamepadButtonUp");
     pub const ON_INPUT_CHAR: EventName = EventName("OnInputChar");
     pub const ON_KEY_DOWN: EventName = EventName("OnKeyDown");
     pub const ON_KEY_UP: EventName = EventName("OnKeyUp");
+    pub const ON_LONG_PRESS: EventName = EventName("OnLongPress");
     pub const ON_MOUSE_DOWN: EventName = EventName("OnMouseDown");
+    pub const ON_MOUSE_UP: EventName = EventName("OnMouseUp");
     pub const ON_MOUSE_SCROLL: EventName = EventName("OnMouseScroll");
+    pub const ON_PAN: EventName = EventName("OnPan");
+    pub const ON_PINCH: EventName = EventName("OnPinch");
+    pub const ON_SCROLL: EventName = EventName("OnScroll");
+    pub const ON_TAP: EventName = EventName("OnTap");
+    pub const ON_TOUCH_DOWN: EventName = EventName("OnTouchDown");
+    pub const ON_TOUCH_MOVE: EventName = EventName("OnTouchMove");
+    pub const ON_TOUCH_UP: EventName = EventName("OnTouchUp");
     pub const WINDOW_RESIZED: EventName = EventName("WindowResized");
 }
 
@@ -30,16 +113,59 @@ impl Deref for EventName {
     }
 }
 
+/// The event handler, which returns the message for the model. It is the closure, so it may capture the data of the
+/// view, e.g. the index of the list item, for which the handler is built.
+pub type Handler<M, E> = Box<dyn Fn(On<M, E>) -> <M as Model>::Message>;
+
 pub enum Listener<M: Model> {
-    WindowResized(fn(u32, u32) -> M::Message),
-    Draw(fn(Duration) -> M::Message),
-    OnMouseDown(fn(On<M, MouseDown>) -> M::Message),
-    OnMouseScroll(fn(On<M, MouseScroll>) -> M::Message),
-    OnKeyDown(fn(On<M, KeyboardEvent>) -> M::Message),
-    OnKeyUp(fn(On<M, KeyboardEvent>) -> M::Message),
-    OnClick(fn(On<M, MouseDown>) -> M::Message),
-    OnInputChar(fn(On<M, char>) -> M::Message),
-    OnBlur(fn(On<M, MouseDown>) -> M::Message),
+    WindowResized(Box<dyn Fn(Viewport) -> M::Message>),
+    Draw(Box<dyn Fn(Duration) -> M::Message>),
+    OnMouseDown(Handler<M, MouseDown>),
+    /// The button is released over the primitive, or anywhere if the primitive has captured the mouse by the press.
+    OnMouseUp(Handler<M, MouseUp>),
+    OnMouseScroll(Handler<M, MouseScroll>),
+    /// The right button is pressed over the primitive, it is fired after the `OnMouseDown`. The menu should be shown
+    /// at the event position.
+    OnContextMenu(Handler<M, MouseDown>),
+    /// The scroll area offset is changed, the event is the scroll state after the change.
+    OnScroll(Handler<M, ScrollArea>),
+    /// The key is pressed. The focusable primitive gets the keyboard events only while it is focused.
+    OnKeyDown(Handler<M, KeyboardEvent>),
+    OnKeyUp(Handler<M, KeyboardEvent>),
+    OnClick(Handler<M, Click>),
+    /// The second click follows the first one within the time and the distance of the gesture thresholds.
+    OnDoubleClick(Handler<M, Click>),
+    /// The press is held without moving for the long press time, the click is not fired on the release then.
+    OnLongPress(Handler<M, Click>),
+    OnInputChar(Handler<M, char>),
+    /// The focusable primitive gets the focus by the mouse press or by the Tab.
+    OnFocus(Handler<M, FocusChange>),
+    /// The focusable primitive loses the focus. The other primitives get it on any press outside of them.
+    OnBlur(Handler<M, FocusChange>),
+    OnDragStart(Handler<M, Drag>),
+    OnDrag(Handler<M, Drag>),
+    OnDragEnd(Handler<M, Drag>),
+    OnTouchDown(Handler<M, Touch>),
+    /// The touch, which has started over the primitive, is moved.
+    OnTouchMove(Handler<M, Touch>),
+    /// The touch is released over the primitive, or anywhere if it has started over the primitive.
+    OnTouchUp(Handler<M, Touch>),
+    OnTap(Handler<M, Tap>),
+    OnPan(Handler<M, Pan>),
+    OnPinch(Handler<M, Pinch>),
+    /// The file is dragged over the primitive, e.g. to highlight the drop target.
+    OnFileHover(Handler<M, FileDrag>),
+    /// The dragged files have left the window or the drag is cancelled. Every primitive gets it, not only the hovered
+    /// one, so the drop target can reset its highlight.
+    OnFileHoverCancel(Handler<M, ()>),
+    /// The file is dropped on the primitive.
+    OnFileDrop(Handler<M, FileDrag>),
+    /// The gamepad button is pressed. Like the keyboard events, the focusable primitive gets it only while it is
+    /// focused.
+    OnGamepadButtonDown(Handler<M, GamepadButtonEvent>),
+    OnGamepadButtonUp(Handler<M, GamepadButtonEvent>),
+    /// The stick or the trigger of the gamepad is moved.
+    OnGamepadAxis(Handler<M, GamepadAxisEvent>),
 }
 
 impl<M: Model> Listener<M> {
@@ -48,12 +174,33 @@ impl<M: Model> Listener<M> {
             Listener::WindowResized(_) => EventName::WINDOW_RESIZED,
             Listener::Draw(_) => EventName::DRAW,
             Listener::OnMouseDown(_) => EventName::ON_MOUSE_DOWN,
+            Listener::OnMouseUp(_) => EventName::ON_MOUSE_UP,
             Listener::OnMouseScroll(_) => EventName::ON_MOUSE_SCROLL,
+            Listener::OnContextMenu(_) => EventName::ON_CONTEXT_MENU,
+            Listener::OnScroll(_) => EventName::ON_SCROLL,
             Listener::OnKeyDown(_) => EventName::ON_KEY_DOWN,
             Listener::OnKeyUp(_) => EventName::ON_KEY_UP,
             Listener::OnClick(_) => EventName::ON_CLICK,
+            Listener::OnDoubleClick(_) => EventName::ON_DOUBLE_CLICK,
+            Listener::OnLongPress(_) => EventName::ON_LONG_PRESS,
             Listener::OnInputChar(_) => EventName::ON_INPUT_CHAR,
+            Listener::OnFocus(_) => EventName::ON_FOCUS,
             Listener::OnBlur(_) => EventName::ON_BLUR,
+            Listener::OnDragStart(_) => EventName::ON_DRAG_START,
+            Listener::OnDrag(_) => EventName::ON_DRAG,
+            Listener::OnDragEnd(_) => EventName::ON_DRAG_END,
+            Listener::OnTouchDown(_) => EventName::ON_TOUCH_DOWN,
+            Listener::OnTouchMove(_) => EventName::ON_TOUCH_MOVE,
+            Listener::OnTouchUp(_) => EventName::ON_TOUCH_UP,
+            Listener::OnTap(_) => EventName::ON_TAP,
+            Listener::OnPan(_) => EventName::ON_PAN,
+            Listener::OnPinch(_) => EventName::ON_PINCH,
+            Listener::OnFileHover(_) => EventName::ON_FILE_HOVER,
+            Listener::OnFileHoverCancel(_) => EventName::ON_FILE_HOVER_CANCEL,
+            Listener::OnFileDrop(_) => EventName::ON_FILE_DROP,
+            Listener::OnGamepadButtonDown(_) => EventName::ON_GAMEPAD_BUTTON_DOWN,
+            Listener::OnGamepadButtonUp(_) => EventName::ON_GAMEPAD_BUTTON_UP,
+            Listener::OnGamepadAxis(_) => EventName::ON_GAMEPAD_AXIS,
         }
     }
 }
@@ -1,9 +1,9 @@
 use std::time::Duration;
 
-use crate::{InputEvent, Node};
+use crate::{Command, InputEvent, Node, Provider, Real, Shortcuts};
 
 pub trait Model: Sized + 'static {
-    type Message;
+    type Message: 'static;
     type Properties;
 
     fn create(props: Self::Properties) -> Self;
@@ -13,7 +13,30 @@ pub trait Model: Sized + 'static {
         None
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView;
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message>;
+
+    /// Applies the new properties of the parent. Returns true if the view should be rebuilt. By default the model is
+    /// created again from the properties, so its own state is lost.
+    fn change(&mut self, props: Self::Properties) -> bool {
+        *self = Self::create(props);
+        true
+    }
+
+    /// Key chords of the component, which are handled by the update regardless of the focus.
+    fn shortcuts(&self) -> Shortcuts<Self> {
+        Shortcuts::new()
+    }
+
+    /// Returns true while the model animates its view by the draw messages, so the controller draws the frames
+    /// continuously. Otherwise the frames are drawn only on the view changes, the input and the timers.
+    fn is_animating(&self) -> bool {
+        false
+    }
+
+    /// Values for the nested components, which get them by the `Context` during their create and view build.
+    fn provide(&self) -> Provider {
+        Provider::new()
+    }
 
     fn build_view(&self) -> Node<Self>;
 
@@ -39,6 +62,10 @@ pub struct ChangeViewState {
 }
 
 impl ChangeViewState {
+    pub fn is_changed(&self) -> bool {
+        self.need_rebuild || self.need_modify || self.need_recalc
+    }
+
     pub fn update(&mut self, change_view: ChangeView) {
         match change_view {
             ChangeView::Rebuild => self.need_rebuild = true,
@@ -49,9 +76,27 @@ impl ChangeViewState {
     }
 }
 
+/// Physical size of the window and the device pixel ratio, e.g. to switch the layout at the breakpoints. The shapes
+/// are laid out and the input events are positioned in the logical pixels of the `logical_size`.
 #[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+    pub device_pixel_ratio: f64,
+}
+
+impl Viewport {
+    /// Size in the device independent pixels.
+    pub fn logical_size(&self) -> (Real, Real) {
+        let ratio = self.device_pixel_ratio as Real;
+        (self.width as Real / ratio, self.height as Real / ratio)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum SystemMessage {
-    WindowResized { width: u32, height: u32 },
+    /// The window is resized or moved to the screen with the other pixel ratio.
+    WindowResized(Viewport),
     Draw(Duration),
     Input(InputEvent),
 }
@@ -1,12 +1,16 @@
-pub use self::{clip::*, comp::*, converter::*, prim::*, shape::*, transform::*, value::*};
-use crate::{Model, SystemMessage};
+pub use self::{bound::*, clip::*, comp::*, converter::*, prim::*, shape::*, style::*, transform::*, value::*};
+use crate::{
+    CursorIcon, FocusChange, FocusOrder, KeyboardEvent, Model, NextFrame, Propagation, Shortcut, SystemMessage,
+};
 
+pub mod bound;
 pub mod builder;
 pub mod clip;
 pub mod comp;
 pub mod converter;
 pub mod prim;
 pub mod shape;
+pub mod style;
 pub mod transform;
 pub mod value;
 
@@ -15,6 +19,57 @@ pub enum Node<M: Model> {
     Comp(Comp),
 }
 
+/// Shape or component of any model, which is found in the subtree.
+#[derive(Clone, Copy)]
+pub enum NodeRef<'a> {
+    Shape(&'a Shape),
+    Comp(&'a Comp),
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn id(&self) -> Option<&'a str> {
+        match self {
+            NodeRef::Shape(shape) => shape.id(),
+            NodeRef::Comp(comp) => comp.id(),
+        }
+    }
+
+    pub fn as_shape(&self) -> Option<&'a Shape> {
+        match self {
+            NodeRef::Shape(shape) => Some(shape),
+            _ => None,
+        }
+    }
+
+    pub fn as_comp(&self) -> Option<&'a Comp> {
+        match self {
+            NodeRef::Comp(comp) => Some(comp),
+            _ => None,
+        }
+    }
+}
+
+pub enum NodeMut<'a> {
+    Shape(&'a mut Shape),
+    Comp(&'a mut Comp),
+}
+
+impl<'a> NodeMut<'a> {
+    pub fn into_shape(self) -> Option<&'a mut Shape> {
+        match self {
+            NodeMut::Shape(shape) => Some(shape),
+            _ => None,
+        }
+    }
+
+    pub fn into_comp(self) -> Option<&'a mut Comp> {
+        match self {
+            NodeMut::Comp(comp) => Some(comp),
+            _ => None,
+        }
+    }
+}
+
 impl<M: Model> Node<M> {
     pub fn get_id(&self) -> Option<&str> {
         match self {
@@ -167,10 +222,161 @@ impl<M: Model> Node<M> {
         }
     }
 
+    /// Converts the point from the window coordinates to the local coordinates of the node shape by the inverse of
+    /// its calculated global transform, e.g. the drag position on the rotated shape. The node, which is not laid out
+    /// yet, uses its local transform.
+    pub fn to_local(&self, (x, y): (Real, Real)) -> (Real, Real) {
+        self.shape().map(|shape| shape.local_point(x, y)).unwrap_or((x, y))
+    }
+
+    /// Finds the shape or the component by the id in the subtree. Unlike the `get`, it looks into the views of the
+    /// nested components too.
+    pub fn find_by_id(&self, id: &str) -> Option<NodeRef> {
+        match self {
+            Node::Prim(prim) if prim.id() == Some(id) => Some(NodeRef::Shape(&prim.shape)),
+            Node::Prim(prim) => prim.children.iter().find_map(|child| child.find_by_id(id)),
+            Node::Comp(comp) if comp.id() == Some(id) => Some(NodeRef::Comp(comp)),
+            Node::Comp(comp) => comp.find_by_id(id),
+        }
+    }
+
+    /// The found shape is marked as changed, so the next view update recalculates and redraws it.
+    pub fn find_by_id_mut(&mut self, id: &str) -> Option<NodeMut> {
+        match self {
+            Node::Prim(prim) => prim.find_by_id_mut(id),
+            Node::Comp(comp) => {
+                if comp.id() == Some(id) {
+                    Some(NodeMut::Comp(comp))
+                } else {
+                    comp.find_by_id_mut(id)
+                }
+            }
+        }
+    }
+
+    /// Shapes and components of the subtree in the declaration order, the node itself goes first. The views of the
+    /// nested components are included.
+    pub fn subtree(&self) -> impl Iterator<Item = NodeRef> {
+        let mut nodes = vec![];
+        self.collect_subtree(&mut nodes);
+        nodes.into_iter()
+    }
+
+    pub(crate) fn collect_subtree<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        match self {
+            Node::Prim(prim) => {
+                nodes.push(NodeRef::Shape(&prim.shape));
+                for child in prim.children.iter() {
+                    child.collect_subtree(nodes);
+                }
+            }
+            Node::Comp(comp) => {
+                nodes.push(NodeRef::Comp(comp));
+                comp.collect_subtree(nodes);
+            }
+        }
+    }
+
     pub fn send_system_msg(&mut self, msg: SystemMessage, outputs: &mut Vec<M::Message>) {
+        Propagation::dispatch(&msg, |propagation| self.dispatch(&msg, propagation, outputs));
+    }
+
+    pub(crate) fn dispatch(&mut self, msg: &SystemMessage, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        match self {
+            Node::Prim(prim) => prim.dispatch(msg, propagation, outputs),
+            Node::Comp(comp) => comp.dispatch(msg, propagation),
+        }
+    }
+
+    pub(crate) fn collect_focus_order(&self, order: &mut FocusOrder) {
+        match self {
+            Node::Prim(prim) => prim.collect_focus_order(order),
+            Node::Comp(comp) => comp.collect_focus_order(order),
+        }
+    }
+
+    pub(crate) fn set_focus(
+        &mut self, target: usize, idx: &mut usize, change: FocusChange, outputs: &mut Vec<M::Message>,
+    ) {
         match self {
-            Node::Prim(prim) => prim.send_system_msg(msg, outputs),
-            Node::Comp(comp) => comp.send_system_msg(msg),
+            Node::Prim(prim) => prim.set_focus(target, idx, change, outputs),
+            Node::Comp(comp) => comp.set_focus(target, idx, change),
+        }
+    }
+
+    pub(crate) fn collect_shortcuts(&self, shortcuts: &mut Vec<Shortcut>) {
+        match self {
+            Node::Prim(prim) => {
+                for child in prim.children.iter() {
+                    child.collect_shortcuts(shortcuts);
+                }
+            }
+            Node::Comp(comp) => comp.collect_shortcuts(shortcuts),
+        }
+    }
+
+    pub(crate) fn trigger_shortcut(&mut self, event: &KeyboardEvent) -> bool {
+        match self {
+            Node::Prim(prim) => prim.children.iter_mut().any(|child| child.trigger_shortcut(event)),
+            Node::Comp(comp) => comp.trigger_shortcut_in(event),
+        }
+    }
+
+    /// Checks that the node is updated by the rebuilt one in place, instead of being replaced.
+    pub(crate) fn is_patched_by(&self, new: &Node<M>) -> bool {
+        match (self, new) {
+            (Node::Prim(prim), Node::Prim(new)) => prim.name == new.name && prim.id() == new.id(),
+            (Node::Comp(comp), Node::Comp(new)) => comp.is_patched_by(new),
+            _ => false,
+        }
+    }
+
+    /// Updates the node by the rebuilt one, the primitives with the same name and id and the components with the same
+    /// model and id are patched, the other nodes are replaced. Returns true if the node needs the recalculation.
+    pub(crate) fn patch(&mut self, new: Node<M>) -> bool {
+        match (&mut *self, new) {
+            (Node::Prim(prim), Node::Prim(new)) if prim.name == new.name && prim.id() == new.id() => prim.patch(new),
+            (Node::Comp(comp), Node::Comp(new)) if comp.is_patched_by(&new) => comp.patch(new),
+            (_, new) => {
+                *self = new;
+                self.view_memos();
+                true
+            }
+        }
+    }
+
+    /// The memos are viewed and the components declared by their properties are created on the creation of the node.
+    pub(crate) fn view_memos(&mut self) {
+        match self {
+            Node::Prim(prim) => prim.view_memos(),
+            Node::Comp(comp) => comp.mount(),
+        }
+    }
+
+    pub(crate) fn poll_commands(&mut self) -> bool {
+        match self {
+            Node::Prim(prim) => {
+                let mut polled = false;
+                for child in prim.children.iter_mut() {
+                    polled |= child.poll_commands();
+                }
+                polled
+            }
+            Node::Comp(comp) => comp.poll_commands(),
+        }
+    }
+
+    pub fn has_changes(&self) -> bool {
+        match self {
+            Node::Prim(prim) => prim.has_changes(),
+            Node::Comp(comp) => comp.has_changes(),
+        }
+    }
+
+    pub fn next_frame(&self) -> NextFrame {
+        match self {
+            Node::Prim(prim) => prim.next_frame(),
+            Node::Comp(comp) => comp.next_frame(),
         }
     }
 
@@ -224,4 +430,76 @@ impl<M: Model> CompositeShape for Node<M> {
             Node::Comp(comp) => CompositeShape::need_redraw(comp),
         }
     }
+
+    fn z_index(&self) -> i32 {
+        match self {
+            Node::Prim(prim) => CompositeShape::z_index(prim),
+            Node::Comp(comp) => CompositeShape::z_index(comp),
+        }
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        match self {
+            Node::Prim(prim) => CompositeShape::cursor(prim),
+            Node::Comp(comp) => CompositeShape::cursor(comp),
+        }
+    }
+
+    fn recalc_cache(&self) -> Option<RecalcCache> {
+        match self {
+            Node::Prim(prim) => CompositeShape::recalc_cache(prim),
+            Node::Comp(comp) => CompositeShape::recalc_cache(comp),
+        }
+    }
+
+    fn set_recalc_cache(&mut self, cache: Option<RecalcCache>) {
+        match self {
+            Node::Prim(prim) => CompositeShape::set_recalc_cache(prim, cache),
+            Node::Comp(comp) => CompositeShape::set_recalc_cache(comp, cache),
+        }
+    }
+
+    fn damage(&self) -> Option<BoundingBox> {
+        match self {
+            Node::Prim(prim) => CompositeShape::damage(prim),
+            Node::Comp(comp) => CompositeShape::damage(comp),
+        }
+    }
+
+    fn set_damage(&mut self, damage: Option<BoundingBox>) {
+        match self {
+            Node::Prim(prim) => CompositeShape::set_damage(prim, damage),
+            Node::Comp(comp) => CompositeShape::set_damage(comp, damage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::TestModel, Rect};
+
+    #[test]
+    fn convert_to_local_of_rotated_shape() {
+        let global = TransformMatrix::identity()
+            .with_translation(100.0, 50.0)
+            .with_rotation(90_f32.to_radians());
+        let rect = Rect {
+            width: 40.0.into(),
+            height: 20.0.into(),
+            transform: Transform::Global(global),
+            ..Default::default()
+        };
+        let node: Node<TestModel> = Node::Prim(Prim::new(
+            Rect::NAME.into(),
+            Shape::Rect(rect),
+            vec![],
+            Default::default(),
+        ));
+
+        let near = |(x, y): (Real, Real), (ex, ey): (Real, Real)| (x - ex).abs() < 1e-4 && (y - ey).abs() < 1e-4;
+        assert!(near(node.to_local(global * (30.0, 10.0)), (30.0, 10.0)));
+        assert!(near(node.to_local((100.0, 50.0)), (0.0, 0.0)));
+        assert!(near(node.to_local((90.0, 50.0)), (0.0, 10.0)));
+    }
 }
@@ -0,0 +1,146 @@
+use std::ops::Mul;
+
+use crate::{Padding, Real, TransformMatrix};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    pub min_x: Real,
+    pub min_y: Real,
+    pub max_x: Real,
+    pub max_y: Real,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> Real {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> Real {
+        self.max_y - self.min_y
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// Returns `None` when the boxes do not overlap.
+    pub fn intersect(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        let bound = BoundingBox {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+        if bound.min_x <= bound.max_x && bound.min_y <= bound.max_y {
+            Some(bound)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_point(&self, x: Real, y: Real) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    pub fn expand(&self, padding: &Padding) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x - padding.left.val(),
+            min_y: self.min_y - padding.top.val(),
+            max_x: self.max_x + padding.right.val(),
+            max_y: self.max_y + padding.bottom.val(),
+        }
+    }
+
+    /// Box extended by the distance to all the sides.
+    pub fn outset(&self, distance: Real) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x - distance,
+            min_y: self.min_y - distance,
+            max_x: self.max_x + distance,
+            max_y: self.max_y + distance,
+        }
+    }
+
+    /// Bound of the corners of the box transformed by the matrix.
+    pub fn transformed(&self, matrix: TransformMatrix) -> BoundingBox {
+        let points = matrix * *self;
+        let (x, y) = points[0];
+        let mut bound = BoundingBox {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        };
+        for &(x, y) in &points[1..] {
+            bound.min_x = bound.min_x.min(x);
+            bound.min_y = bound.min_y.min(y);
+            bound.max_x = bound.max_x.max(x);
+            bound.max_y = bound.max_y.max(y);
+        }
+        bound
+    }
+}
+
+impl Mul<BoundingBox> for TransformMatrix {
+    type Output = [(Real, Real); 4];
+
+    fn mul(self, rhs: BoundingBox) -> Self::Output {
+        [
+            self * (rhs.min_x, rhs.min_y),
+            self * (rhs.min_x, rhs.max_y),
+            self * (rhs.max_x, rhs.min_y),
+            self * (rhs.max_x, rhs.max_y),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound(min_x: Real, min_y: Real, max_x: Real, max_y: Real) -> BoundingBox {
+        BoundingBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    #[test]
+    fn union_and_intersect() {
+        let (a, b) = (bound(0.0, 0.0, 20.0, 10.0), bound(10.0, 5.0, 30.0, 40.0));
+        assert_eq!(a.union(&b), bound(0.0, 0.0, 30.0, 40.0));
+        assert_eq!(a.intersect(&b), Some(bound(10.0, 5.0, 20.0, 10.0)));
+        assert_eq!(a.intersect(&bound(20.0, 10.0, 25.0, 15.0)), Some(bound(20.0, 10.0, 20.0, 10.0)));
+        assert_eq!(a.intersect(&bound(21.0, 0.0, 25.0, 10.0)), None);
+    }
+
+    #[test]
+    fn contains_point() {
+        let a = bound(0.0, 0.0, 20.0, 10.0);
+        assert!(a.contains_point(0.0, 0.0));
+        assert!(a.contains_point(20.0, 10.0));
+        assert!(a.contains_point(5.0, 5.0));
+        assert!(!a.contains_point(-1.0, 5.0));
+        assert!(!a.contains_point(5.0, 11.0));
+    }
+
+    #[test]
+    fn expand_by_padding() {
+        let padding = Padding {
+            top: 1.0.into(),
+            left: 2.0.into(),
+            right: 3.0.into(),
+            bottom: 4.0.into(),
+        };
+        assert_eq!(bound(0.0, 0.0, 20.0, 10.0).expand(&padding), bound(-2.0, -1.0, 23.0, 14.0));
+        assert_eq!(bound(0.0, 0.0, 20.0, 10.0).expand(&Padding::default()), bound(0.0, 0.0, 20.0, 10.0));
+    }
+}
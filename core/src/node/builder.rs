@@ -1,5 +1,9 @@
+use std::any::Any;
+
 use crate::{
-    Fill, KeyboardEvent, Listener, Model, MouseDown, MouseScroll, Node, On, Real, RealValue, Stroke, Transform,
+    Click, Clip, CursorIcon, Drag, FileDrag, Fill, FocusChange, GamepadAxisEvent, GamepadButtonEvent,
+    GestureThresholds, KeyboardEvent, Listener, Model, MouseDown, MouseScroll, MouseUp, Node, On, Pan, Path, Pinch,
+    Real, RealValue, ScrollArea, Stroke, Style, Tap, Touch, Transform,
 };
 
 pub trait Builder<M: Model> {
@@ -17,9 +21,18 @@ pub trait Entity {
     fn transform(self, transform: impl Into<Transform>) -> Self;
 }
 
-pub trait Primitive<M: Model> {
+pub trait Primitive<M: Model>: Sized {
     fn child(self, child: impl Builder<M>) -> Self;
     fn children(self, children: impl IntoIterator<Item = Node<M>>) -> Self;
+    fn style(self, name: impl Into<String>) -> Self;
+    fn user_data(self, data: impl Any) -> Self;
+    fn hover(self, style: Style) -> Self;
+    fn pressed(self, style: Style) -> Self;
+    fn focused(self, style: Style) -> Self;
+    fn z_index(self, z_index: i32) -> Self;
+    fn gestures(self, gestures: GestureThresholds) -> Self;
+    fn focusable(self) -> Self;
+    fn cursor(self, cursor: CursorIcon) -> Self;
     fn transparency(self, transparency: impl Into<Real>) -> Self;
     fn stroke(self, stroke: impl Into<Stroke>) -> Self;
     fn fill(self, fill: impl Into<Fill>) -> Self;
@@ -29,42 +42,366 @@ pub trait Primitive<M: Model> {
         self, x: impl Into<RealValue>, y: impl Into<RealValue>, width: impl Into<RealValue>,
         height: impl Into<RealValue>,
     ) -> Self;
+    fn clip_by(self, clip: Clip) -> Self;
+
+    /// Sets the point, around which the shape is rotated and scaled by its transform, in the shape coordinates or in
+    /// the percents of the shape size: `.transform_origin(Pct(50), Pct(50))`.
+    fn transform_origin(self, x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self;
+
+    /// Clips the shape by the circle, e.g. for the round avatars.
+    fn clip_circle(self, cx: impl Into<RealValue>, cy: impl Into<RealValue>, r: impl Into<RealValue>) -> Self {
+        self.clip_by(Clip::new_circle(cx.into(), cy.into(), r.into()))
+    }
+
+    /// Clips the shape by the region filled by the path.
+    fn clip_path(self, path: Path) -> Self {
+        self.clip_by(Clip::new_path(path))
+    }
+
+    /// Adds the child built by the `view` for each item, instead of collecting the nodes in the loop beforehand:
+    /// `.children_for(&self.items, |item| text(&item.title))`.
+    fn children_for<I: IntoIterator, B: Builder<M>>(self, items: I, mut view: impl FnMut(I::Item) -> B) -> Self {
+        self.children(items.into_iter().map(|item| view(item).build()))
+    }
+
+    /// Adds the child of the branch chosen by the `cond`, the branches may be the builders of the different shapes:
+    /// `.child_when(self.expanded, || details(), || text("More"))`. The `match` expression is passed to the `child`
+    /// as is, if its arms are built to the nodes.
+    fn child_when<A: Builder<M>, B: Builder<M>>(
+        self, cond: bool, then: impl FnOnce() -> A, otherwise: impl FnOnce() -> B,
+    ) -> Self {
+        if cond {
+            self.child(then())
+        } else {
+            self.child(otherwise())
+        }
+    }
+
+    fn child_if(self, cond: bool, child: impl Builder<M>) -> Self {
+        if cond {
+            self.child(child)
+        } else {
+            self
+        }
+    }
+
+    fn maybe_child(self, child: Option<impl Builder<M>>) -> Self {
+        match child {
+            Some(child) => self.child(child),
+            None => self,
+        }
+    }
+
+    /// Sets the optional attribute by the `set`, the `None` value is skipped:
+    /// `.maybe(self.highlight, |rect, color| rect.fill(color))`.
+    fn maybe<T>(self, value: Option<T>, set: impl FnOnce(Self, T) -> Self) -> Self {
+        match value {
+            Some(value) => set(self, value),
+            None => self,
+        }
+    }
 }
 
 pub trait EventHandler<M: Model>: Sized {
     fn add_listener(&mut self, listener: Listener<M>);
 
-    fn on_click(self, _trigger: fn(()) -> M::Message) -> Self {
+    fn on_click(mut self, trigger: impl Fn(On<M, Click>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnClick(Box::new(trigger)));
+        self
+    }
+
+    fn on_double_click(mut self, trigger: impl Fn(On<M, Click>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnDoubleClick(Box::new(trigger)));
+        self
+    }
+
+    fn on_long_press(mut self, trigger: impl Fn(On<M, Click>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnLongPress(Box::new(trigger)));
+        self
+    }
+
+    fn on_mouse_down(mut self, trigger: impl Fn(On<M, MouseDown>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnMouseDown(Box::new(trigger)));
+        self
+    }
+
+    fn on_mouse_up(mut self, trigger: impl Fn(On<M, MouseUp>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnMouseUp(Box::new(trigger)));
+        self
+    }
+
+    fn on_context_menu(mut self, trigger: impl Fn(On<M, MouseDown>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnContextMenu(Box::new(trigger)));
+        self
+    }
+
+    fn on_mouse_scroll(mut self, trigger: impl Fn(On<M, MouseScroll>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnMouseScroll(Box::new(trigger)));
+        self
+    }
+
+    fn on_scroll(mut self, trigger: impl Fn(On<M, ScrollArea>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnScroll(Box::new(trigger)));
+        self
+    }
+
+    fn on_key_down(mut self, trigger: impl Fn(On<M, KeyboardEvent>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnKeyDown(Box::new(trigger)));
+        self
+    }
+
+    fn on_key_up(mut self, trigger: impl Fn(On<M, KeyboardEvent>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnKeyUp(Box::new(trigger)));
+        self
+    }
+
+    fn on_input_char(mut self, trigger: impl Fn(On<M, char>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnInputChar(Box::new(trigger)));
+        self
+    }
+
+    fn on_focus(mut self, trigger: impl Fn(On<M, FocusChange>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnFocus(Box::new(trigger)));
+        self
+    }
+
+    fn on_blur(mut self, trigger: impl Fn(On<M, FocusChange>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnBlur(Box::new(trigger)));
+        self
+    }
+
+    fn on_drag_start(mut self, trigger: impl Fn(On<M, Drag>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnDragStart(Box::new(trigger)));
+        self
+    }
+
+    fn on_drag(mut self, trigger: impl Fn(On<M, Drag>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnDrag(Box::new(trigger)));
+        self
+    }
+
+    fn on_drag_end(mut self, trigger: impl Fn(On<M, Drag>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnDragEnd(Box::new(trigger)));
+        self
+    }
+
+    fn on_touch_down(mut self, trigger: impl Fn(On<M, Touch>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnTouchDown(Box::new(trigger)));
+        self
+    }
+
+    fn on_touch_move(mut self, trigger: impl Fn(On<M, Touch>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnTouchMove(Box::new(trigger)));
         self
     }
 
-    fn on_mouse_down(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnMouseDown(trigger));
+    fn on_touch_up(mut self, trigger: impl Fn(On<M, Touch>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnTouchUp(Box::new(trigger)));
         self
     }
 
-    fn on_mouse_scroll(mut self, trigger: fn(On<M, MouseScroll>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnMouseScroll(trigger));
+    fn on_tap(mut self, trigger: impl Fn(On<M, Tap>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnTap(Box::new(trigger)));
         self
     }
 
-    fn on_key_down(mut self, trigger: fn(On<M, KeyboardEvent>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnKeyDown(trigger));
+    fn on_pan(mut self, trigger: impl Fn(On<M, Pan>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnPan(Box::new(trigger)));
         self
     }
 
-    fn on_key_up(mut self, trigger: fn(On<M, KeyboardEvent>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnKeyUp(trigger));
+    fn on_pinch(mut self, trigger: impl Fn(On<M, Pinch>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnPinch(Box::new(trigger)));
         self
     }
 
-    fn on_input_char(mut self, trigger: fn(On<M, char>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnInputChar(trigger));
+    fn on_file_hover(mut self, trigger: impl Fn(On<M, FileDrag>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnFileHover(Box::new(trigger)));
         self
     }
 
-    fn on_blur(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
-        self.add_listener(Listener::OnBlur(trigger));
+    fn on_file_hover_cancel(mut self, trigger: impl Fn(On<M, ()>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnFileHoverCancel(Box::new(trigger)));
         self
     }
+
+    fn on_file_drop(mut self, trigger: impl Fn(On<M, FileDrag>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnFileDrop(Box::new(trigger)));
+        self
+    }
+
+    fn on_gamepad_button_down(mut self, trigger: impl Fn(On<M, GamepadButtonEvent>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnGamepadButtonDown(Box::new(trigger)));
+        self
+    }
+
+    fn on_gamepad_button_up(mut self, trigger: impl Fn(On<M, GamepadButtonEvent>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnGamepadButtonUp(Box::new(trigger)));
+        self
+    }
+
+    fn on_gamepad_axis(mut self, trigger: impl Fn(On<M, GamepadAxisEvent>) -> M::Message + 'static) -> Self {
+        self.add_listener(Listener::OnGamepadAxis(Box::new(trigger)));
+        self
+    }
+
+    /// Adds the click listener, which gets the `key` captured at the view build, e.g. the index of the list item:
+    /// `.on_click_with(idx, |idx, _| Msg::Select(idx))`. The other `_with` methods are the same for their events.
+    fn on_click_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Click>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_click(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_double_click_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Click>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_double_click(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_long_press_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Click>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_long_press(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_mouse_down_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, MouseDown>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_mouse_down(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_mouse_up_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, MouseUp>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_mouse_up(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_context_menu_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, MouseDown>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_context_menu(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_mouse_scroll_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, MouseScroll>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_mouse_scroll(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_scroll_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, ScrollArea>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_scroll(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_key_down_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, KeyboardEvent>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_key_down(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_key_up_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, KeyboardEvent>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_key_up(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_input_char_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, char>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_input_char(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_focus_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, FocusChange>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_focus(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_blur_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, FocusChange>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_blur(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_drag_start_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Drag>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_drag_start(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_drag_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Drag>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_drag(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_drag_end_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Drag>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_drag_end(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_touch_down_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Touch>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_touch_down(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_touch_move_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Touch>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_touch_move(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_touch_up_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Touch>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_touch_up(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_tap_with<K: Clone + 'static>(self, key: K, trigger: impl Fn(K, On<M, Tap>) -> M::Message + 'static) -> Self {
+        self.on_tap(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_pan_with<K: Clone + 'static>(self, key: K, trigger: impl Fn(K, On<M, Pan>) -> M::Message + 'static) -> Self {
+        self.on_pan(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_pinch_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, Pinch>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_pinch(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_file_hover_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, FileDrag>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_file_hover(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_file_drop_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, FileDrag>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_file_drop(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_gamepad_button_down_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, GamepadButtonEvent>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_gamepad_button_down(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_gamepad_button_up_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, GamepadButtonEvent>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_gamepad_button_up(move |on| trigger(key.clone(), on))
+    }
+
+    fn on_gamepad_axis_with<K: Clone + 'static>(
+        self, key: K, trigger: impl Fn(K, On<M, GamepadAxisEvent>) -> M::Message + 'static,
+    ) -> Self {
+        self.on_gamepad_axis(move |on| trigger(key.clone(), on))
+    }
 }
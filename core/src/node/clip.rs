@@ -1,8 +1,11 @@
-use crate::{RealValue, Transform};
+use std::borrow::Cow;
+
+use crate::{BoundingBox, Path, PathCommand, RealValue, Transform, TransformMatrix};
 
 /// A scissor defines a region on the screen in which drawing operations are allowed.
 /// Pixels drawn outside of this region are clipped.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scissor {
     pub x: RealValue,
     pub y: RealValue,
@@ -11,10 +14,42 @@ pub struct Scissor {
     pub transform: Transform,
 }
 
-/// Define how to clip specified region.
+impl Scissor {
+    /// Bound of the scissor in the global coordinates. The scissor, which is rotated or skewed, or which transform is
+    /// not calculated yet, has no such bound.
+    pub fn global_bound(&self) -> Option<BoundingBox> {
+        let matrix = self.transform.global_matrix()?;
+        if matrix.matrix[1] != 0.0 || matrix.matrix[2] != 0.0 {
+            return None;
+        }
+        let bound = BoundingBox {
+            min_x: self.x.val(),
+            min_y: self.y.val(),
+            max_x: self.x.val() + self.width.val(),
+            max_y: self.y.val() + self.height.val(),
+        };
+        Some(bound.transformed(matrix))
+    }
+}
+
+/// A circle region, e.g. for the round avatars.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClipCircle {
+    pub cx: RealValue,
+    pub cy: RealValue,
+    pub r: RealValue,
+    pub transform: Transform,
+}
+
+/// Define how to clip specified region.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Clip {
     Scissor(Scissor),
+    Circle(ClipCircle),
+    /// The region filled by the path with the non-zero rule, the fill and the stroke of the path are not used.
+    Path(Box<Path>),
     None,
 }
 
@@ -29,6 +64,19 @@ impl Clip {
         })
     }
 
+    pub fn new_circle(cx: RealValue, cy: RealValue, r: RealValue) -> Self {
+        Clip::Circle(ClipCircle {
+            cx,
+            cy,
+            r,
+            transform: Transform::default(),
+        })
+    }
+
+    pub fn new_path(path: Path) -> Self {
+        Clip::Path(Box::new(path))
+    }
+
     pub fn is_none(&self) -> bool {
         if let Clip::None = self {
             true
@@ -37,7 +85,7 @@ impl Clip {
         }
     }
 
-    pub fn or(self, other: Self) -> Self {
+    pub fn or<'a>(&'a self, other: &'a Clip) -> &'a Clip {
         if self.is_none() {
             other
         } else {
@@ -45,26 +93,129 @@ impl Clip {
         }
     }
 
+    /// Region, which is inside both the clip and the `outer` clip of the ancestors. The scissors, which are not
+    /// rotated or skewed, are intersected in the global coordinates. The other clips can not be intersected, so the
+    /// clip is used as is, like the inner one clips the children.
+    pub fn intersect<'a>(&'a self, outer: &'a Clip) -> Cow<'a, Clip> {
+        let (inner_scissor, outer_scissor) = match (self, outer) {
+            (_, Clip::None) => return Cow::Borrowed(self),
+            (Clip::None, _) => return Cow::Borrowed(outer),
+            (Clip::Scissor(inner_scissor), Clip::Scissor(outer_scissor)) => (inner_scissor, outer_scissor),
+            _ => return Cow::Borrowed(self),
+        };
+        match (inner_scissor.global_bound(), outer_scissor.global_bound()) {
+            (Some(inner_bound), Some(outer_bound)) => {
+                // The scissors, which do not overlap, clip everything out.
+                let bound = inner_bound.intersect(&outer_bound).unwrap_or(BoundingBox {
+                    max_x: inner_bound.min_x,
+                    max_y: inner_bound.min_y,
+                    ..inner_bound
+                });
+                Cow::Owned(Clip::Scissor(Scissor {
+                    x: bound.min_x.into(),
+                    y: bound.min_y.into(),
+                    width: bound.width().into(),
+                    height: bound.height().into(),
+                    transform: Transform::Global(TransformMatrix::identity()),
+                }))
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+
     pub fn scissor(&self) -> Option<&Scissor> {
         match self {
             Clip::Scissor(scissor) => Some(scissor),
-            Clip::None => None,
+            _ => None,
         }
     }
 
     pub fn scissor_mut(&mut self) -> Option<&mut Scissor> {
         match self {
             Clip::Scissor(scissor) => Some(scissor),
-            Clip::None => None,
+            _ => None,
         }
     }
 
     pub fn transform(&self) -> Option<&Transform> {
-        self.scissor().map(|scissor| &scissor.transform)
+        match self {
+            Clip::Scissor(scissor) => Some(&scissor.transform),
+            Clip::Circle(circle) => Some(&circle.transform),
+            Clip::Path(path) => Some(&path.transform),
+            Clip::None => None,
+        }
     }
 
     pub fn transform_mut(&mut self) -> Option<&mut Transform> {
-        self.scissor_mut().map(|scissor| &mut scissor.transform)
+        match self {
+            Clip::Scissor(scissor) => Some(&mut scissor.transform),
+            Clip::Circle(circle) => Some(&mut circle.transform),
+            Clip::Path(path) => Some(&mut path.transform),
+            Clip::None => None,
+        }
+    }
+
+    /// Outline of the clip region in the coordinates of the clip transform, which the renders fill the clip mask
+    /// with.
+    pub fn outline(&self) -> Option<Vec<PathCommand>> {
+        match self {
+            Clip::Scissor(scissor) => {
+                let (x, y) = (scissor.x.val(), scissor.y.val());
+                let (width, height) = (scissor.width.val(), scissor.height.val());
+                Some(vec![
+                    PathCommand::Move([x, y]),
+                    PathCommand::Line([x + width, y]),
+                    PathCommand::Line([x + width, y + height]),
+                    PathCommand::Line([x, y + height]),
+                    PathCommand::Close,
+                ])
+            }
+            Clip::Circle(circle) => {
+                let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                let arc = |to| PathCommand::Arc {
+                    radius: [r, r],
+                    rotation: 0.0,
+                    large_arc: false,
+                    sweep: true,
+                    to,
+                };
+                Some(vec![
+                    PathCommand::Move([cx + r, cy]),
+                    arc([cx - r, cy]),
+                    arc([cx + r, cy]),
+                    PathCommand::Close,
+                ])
+            }
+            Clip::Path(path) => Some(path.cmd.clone()),
+            Clip::None => None,
+        }
+    }
+
+    /// Scissor around the clip region, the renders which are not able to clip by the paths clip by it.
+    pub fn bounding_scissor(&self) -> Option<Scissor> {
+        let (bound, transform) = match self {
+            Clip::Scissor(scissor) => return Some(*scissor),
+            Clip::Circle(circle) => {
+                let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                let bound = BoundingBox {
+                    min_x: cx - r,
+                    min_y: cy - r,
+                    max_x: cx + r,
+                    max_y: cy + r,
+                };
+                (bound, circle.transform)
+            }
+            // The path without the points clips everything out.
+            Clip::Path(path) => (path.flattened_bound().unwrap_or_default(), path.transform),
+            Clip::None => return None,
+        };
+        Some(Scissor {
+            x: bound.min_x.into(),
+            y: bound.min_y.into(),
+            width: bound.width().into(),
+            height: bound.height().into(),
+            transform,
+        })
     }
 }
 
@@ -73,3 +224,58 @@ impl Default for Clip {
         Clip::None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_scissor_of_circle() {
+        let clip = Clip::new_circle(20.0.into(), 30.0.into(), 10.0.into());
+        let scissor = clip.bounding_scissor().unwrap();
+        assert_eq!((scissor.x.val(), scissor.y.val()), (10.0, 20.0));
+        assert_eq!((scissor.width.val(), scissor.height.val()), (20.0, 20.0));
+        assert_eq!(clip.outline().map(|outline| outline.len()), Some(4));
+    }
+
+    #[test]
+    fn bounding_scissor_of_path() {
+        let path = Path {
+            cmd: vec![
+                PathCommand::Move([5.0, 0.0]),
+                PathCommand::Line([10.0, 10.0]),
+                PathCommand::Line([0.0, 10.0]),
+                PathCommand::Close,
+            ],
+            ..Default::default()
+        };
+        let scissor = Clip::new_path(path).bounding_scissor().unwrap();
+        assert_eq!((scissor.x.val(), scissor.y.val()), (0.0, 0.0));
+        assert_eq!((scissor.width.val(), scissor.height.val()), (10.0, 10.0));
+        assert!(Clip::None.bounding_scissor().is_none());
+    }
+
+    #[test]
+    fn intersect_nested_scissors() {
+        let mut outer = Clip::new_scissor(0.0.into(), 0.0.into(), 100.0.into(), 50.0.into());
+        outer.transform_mut().unwrap().calculate_global(TransformMatrix::identity().with_translation(10.0, 10.0));
+        let mut inner = Clip::new_scissor(50.0.into(), 20.0.into(), 100.0.into(), 100.0.into());
+        inner.transform_mut().unwrap().calculate_global(TransformMatrix::identity());
+
+        let scissor = *inner.intersect(&outer).scissor().unwrap();
+        assert_eq!((scissor.x.val(), scissor.y.val()), (50.0, 20.0));
+        assert_eq!((scissor.width.val(), scissor.height.val()), (60.0, 40.0));
+        assert_eq!(scissor.global_bound().map(|bound| bound.width()), Some(60.0));
+        assert_eq!(*inner.intersect(&Clip::None), inner);
+        assert_eq!(*Clip::None.intersect(&outer), outer);
+
+        let mut apart = Clip::new_scissor(200.0.into(), 0.0.into(), 10.0.into(), 10.0.into());
+        apart.transform_mut().unwrap().calculate_global(TransformMatrix::identity());
+        let scissor = *apart.intersect(&outer).scissor().unwrap();
+        assert_eq!((scissor.width.val(), scissor.height.val()), (0.0, 0.0));
+
+        inner.transform_mut().unwrap().rotate(1.0);
+        inner.transform_mut().unwrap().calculate_global(TransformMatrix::identity());
+        assert_eq!(*inner.intersect(&outer), inner);
+    }
+}
@@ -1,8 +1,19 @@
-use std::any::{type_name, Any};
+use std::{
+    any::{type_name, Any, TypeId},
+    rc::Rc,
+    sync::mpsc::{self, Receiver, Sender},
+};
 
 use crate::{
-    ChangeViewState, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, Model, Node, Shape, SystemMessage,
-    Transform,
+    callback::with_emitted,
+    context::{set_viewport, with_provider},
+    listener::DispatchPass,
+    scheduler::wake_frame,
+    shortcut_conflicts,
+    timer::Timers,
+    BoundingBox, ChangeView, ChangeViewState, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, CursorIcon,
+    FocusChange, FocusOrder, KeyboardEvent, Model, NextFrame, Node, NodeMut, NodeRef, Propagation, Provider,
+    RecalcCache, Shape, Shortcut, SystemMessage, Task, Transform,
 };
 
 pub trait AsAny: Any {
@@ -33,10 +44,26 @@ pub trait CompApi: AsAny {
     fn set_transform(&mut self, transform: Transform);
     fn as_composite_shape(&self) -> Option<&dyn CompositeShape>;
     fn as_composite_shape_mut(&mut self) -> Option<&mut dyn CompositeShape>;
-    fn send_system_msg(&mut self, msg: SystemMessage);
+    fn send_system_msg(&mut self, msg: &SystemMessage, propagation: &Propagation);
+    fn focus_order(&self, order: &mut FocusOrder);
+    fn set_focus(&mut self, target: usize, idx: &mut usize, change: FocusChange);
+    fn collect_shortcuts(&self, shortcuts: &mut Vec<Shortcut>);
+    fn trigger_shortcut(&mut self, event: &KeyboardEvent) -> bool;
+    fn poll_commands(&mut self) -> bool;
+    fn has_changes(&self) -> bool;
+    fn next_frame(&self) -> NextFrame;
     fn update_view(&mut self) -> UpdateView;
     fn need_recalc(&self) -> bool;
     fn need_redraw(&self) -> bool;
+    fn find_by_id(&self, id: &str) -> Option<NodeRef>;
+    fn find_by_id_mut(&mut self, id: &str) -> Option<NodeMut>;
+    fn collect_subtree<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>);
+    fn model_type(&self) -> TypeId;
+    /// Passes the properties of the rebuilt component of the same model to the model. Returns true if the component
+    /// needs the recalculation.
+    fn patch(&mut self, new: Box<dyn CompApi>) -> bool;
+    /// Creates the declared component, returns None if it is created already.
+    fn mount(&mut self) -> Option<Box<dyn CompApi>>;
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -85,6 +112,33 @@ impl Comp {
         }
     }
 
+    /// Component declared by its properties. The model is created from them when the component is viewed or
+    /// accessed mutably first, and the rebuilt view passes them to the existing component of the same model and id by
+    /// `Model::change`, so its state is kept.
+    pub fn from_props<M: Model>(props: M::Properties) -> Self {
+        Self::declared(Source::<M>::Props(props))
+    }
+
+    /// Component of the model, which is created when the component is viewed or accessed mutably first, so its view
+    /// is not built before. The rebuilt view keeps the existing component of the same model and id with its state,
+    /// only the properties of `from_props` change it.
+    pub fn from_model(model: impl Model) -> Self {
+        Self::declared(Source::Model(model))
+    }
+
+    fn declared<M: Model>(source: Source<M>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            inner: Box::new(Declared {
+                source: Some(source),
+                id: None,
+                transform: Default::default(),
+                link: Link { sender },
+                receiver: Some(receiver),
+            }),
+        }
+    }
+
     pub fn id(&self) -> Option<&str> {
         self.inner.id()
     }
@@ -105,22 +159,42 @@ impl Comp {
         self.inner.set_transform(transform.into());
     }
 
+    /// The inner state, if the component has the `M` model. The declared component has none until it is created.
+    #[inline]
+    pub fn try_inner<M: Model>(&self) -> Option<&CompInner<M>> {
+        (*self.inner).as_any().downcast_ref::<CompInner<M>>()
+    }
+
+    /// The declared component is created first, so it has the inner state of its model.
+    #[inline]
+    pub fn try_inner_mut<M: Model>(&mut self) -> Option<&mut CompInner<M>> {
+        self.mount();
+        (*self.inner).as_any_mut().downcast_mut::<CompInner<M>>()
+    }
+
     #[inline]
     pub fn inner<M: Model>(&self) -> &CompInner<M> {
-        (*self.inner)
-            .as_any()
-            .downcast_ref::<CompInner<M>>()
+        self.try_inner()
             .unwrap_or_else(|| panic!("Can't downcast CompInner to {}", type_name::<CompInner<M>>()))
     }
 
     #[inline]
     pub fn inner_mut<M: Model>(&mut self) -> &mut CompInner<M> {
-        (*self.inner)
-            .as_any_mut()
-            .downcast_mut::<CompInner<M>>()
+        self.try_inner_mut()
             .unwrap_or_else(|| panic!("Can't downcast mut CompInner to {}", type_name::<CompInner<M>>()))
     }
 
+    #[inline]
+    pub fn try_model<M: Model>(&self) -> Option<&M> {
+        self.try_inner().map(|inner| &inner.model)
+    }
+
+    #[inline]
+    pub fn try_model_mut<M: Model>(&mut self) -> Option<&mut M> {
+        self.try_inner_mut().map(|inner| &mut inner.model)
+    }
+
+    /// Panics if the component has another model or is declared and not created yet, unlike `try_model`.
     #[inline]
     pub fn model<M: Model>(&self) -> &M {
         &self.inner::<M>().model
@@ -132,17 +206,146 @@ impl Comp {
     }
 
     pub fn send<M: Model>(&mut self, msg: M::Message) {
-        let inner = self.inner_mut::<M>();
-        inner.view_state.update(inner.model.update(msg));
+        self.inner_mut::<M>().update(msg);
+    }
+
+    /// The link of the declared component is valid before it is created, its messages are received after that.
+    pub fn link<M: Model>(&self) -> Link<M> {
+        match (*self.inner).as_any().downcast_ref::<Declared<M>>() {
+            Some(declared) => declared.link.clone(),
+            None => self.inner::<M>().link(),
+        }
     }
 
-    pub fn send_system_msg(&mut self, msg: SystemMessage) {
-        self.inner.send_system_msg(msg);
+    /// Passes the new properties to the model, the view is rebuilt on the next update if the model is changed.
+    pub fn set_props<M: Model>(&mut self, props: M::Properties) -> bool {
+        self.inner_mut::<M>().set_props(props)
+    }
+
+    /// Checks that the component is updated by the rebuilt one in place, i.e. they have the same model and id.
+    pub(crate) fn is_patched_by(&self, new: &Comp) -> bool {
+        self.inner.model_type() == new.inner.model_type() && self.id() == new.id()
+    }
+
+    /// Updates the component by the rebuilt one with the same model, the model keeps its state and gets the new
+    /// properties. Returns true if the component needs the recalculation.
+    pub(crate) fn patch(&mut self, new: Comp) -> bool {
+        self.mount();
+        self.inner.patch(new.inner)
+    }
+
+    /// Creates the model of the component declared by its properties.
+    pub(crate) fn mount(&mut self) {
+        if let Some(inner) = self.inner.mount() {
+            self.inner = inner;
+        }
+    }
+
+    /// Sends the message to the component. Returns true if the view of the component or of the nested ones is
+    /// changed, so the view update is needed.
+    pub fn send_system_msg(&mut self, msg: SystemMessage) -> bool {
+        if let SystemMessage::WindowResized(viewport) = msg {
+            set_viewport(viewport);
+        }
+        Propagation::dispatch(&msg, |propagation| self.inner.send_system_msg(&msg, propagation));
+        self.has_changes()
+    }
+
+    /// Checks that the view update is needed, i.e. some model has requested the view change or some primitive has
+    /// changed its interaction state since the last update.
+    pub fn has_changes(&self) -> bool {
+        self.inner.has_changes()
+    }
+
+    pub(crate) fn dispatch(&mut self, msg: &SystemMessage, propagation: &Propagation) {
+        self.inner.send_system_msg(msg, propagation);
+    }
+
+    /// Focusable primitives of the component view, including the nested components.
+    pub fn focus_order(&self) -> FocusOrder {
+        let mut order = FocusOrder::default();
+        self.inner.focus_order(&mut order);
+        order
+    }
+
+    /// Moves the focus to the next focusable primitive in the declaration order, or to the previous one if
+    /// `backward`. Returns false if there is nothing to focus.
+    pub fn move_focus(&mut self, backward: bool) -> bool {
+        match self.focus_order().next(backward) {
+            Some(target) => {
+                self.set_focus(target, &mut 0, FocusChange::Tab { backward });
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn collect_focus_order(&self, order: &mut FocusOrder) {
+        self.inner.focus_order(order);
+    }
+
+    pub(crate) fn set_focus(&mut self, target: usize, idx: &mut usize, change: FocusChange) {
+        self.inner.set_focus(target, idx, change);
+    }
+
+    /// Sends the message of the first shortcut in the declaration order, which matches the key press, to its
+    /// component. Returns false if there is no such shortcut.
+    pub fn trigger_shortcut(&mut self, event: KeyboardEvent) -> bool {
+        self.inner.trigger_shortcut(&event)
+    }
+
+    /// The shortcuts, which are registered by several components or several times by one.
+    pub fn shortcut_conflicts(&self) -> Vec<Shortcut> {
+        let mut shortcuts = vec![];
+        self.inner.collect_shortcuts(&mut shortcuts);
+        shortcut_conflicts(shortcuts)
+    }
+
+    pub(crate) fn collect_shortcuts(&self, shortcuts: &mut Vec<Shortcut>) {
+        self.inner.collect_shortcuts(shortcuts);
+    }
+
+    pub(crate) fn trigger_shortcut_in(&mut self, event: &KeyboardEvent) -> bool {
+        self.inner.trigger_shortcut(event)
+    }
+
+    /// Polls the futures of the commands, which are returned by the updates of the component and the nested ones,
+    /// and receives the messages of their links. The completed futures are sent to their components as the messages.
+    /// Returns true if any message is sent.
+    pub fn poll_commands(&mut self) -> bool {
+        self.inner.poll_commands()
+    }
+
+    /// When the controller has to draw the next frame: right away, if the view is changed or animated, or at the
+    /// nearest timer of the component and the nested ones.
+    pub fn next_frame(&self) -> NextFrame {
+        self.inner.next_frame()
     }
 
     pub fn update_view(&mut self) -> UpdateView {
         self.inner.update_view()
     }
+
+    /// Finds the shape or the component by the id in the view, including the views of the nested components.
+    pub fn find_by_id(&self, id: &str) -> Option<NodeRef> {
+        self.inner.find_by_id(id)
+    }
+
+    /// The found shape is marked as changed, so the next view update recalculates and redraws it.
+    pub fn find_by_id_mut(&mut self, id: &str) -> Option<NodeMut> {
+        self.inner.find_by_id_mut(id)
+    }
+
+    /// Shapes and components of the view in the declaration order, including the views of the nested components.
+    pub fn subtree(&self) -> impl Iterator<Item = NodeRef> {
+        let mut nodes = vec![];
+        self.inner.collect_subtree(&mut nodes);
+        nodes.into_iter()
+    }
+
+    pub(crate) fn collect_subtree<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        self.inner.collect_subtree(nodes);
+    }
 }
 
 impl CompositeShape for Comp {
@@ -163,12 +366,66 @@ impl CompositeShape for Comp {
     }
 
     fn need_recalc(&self) -> Option<bool> {
-        Some(self.inner.need_recalc())
+        let view_changed = self.inner.as_composite_shape().and_then(|view| view.need_recalc());
+        Some(self.inner.need_recalc() || view_changed.unwrap_or(false))
     }
 
     fn need_redraw(&self) -> Option<bool> {
         Some(self.inner.need_redraw())
     }
+
+    fn z_index(&self) -> i32 {
+        self.inner.as_composite_shape().map_or(0, |shape| shape.z_index())
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        self.inner.as_composite_shape()?.cursor()
+    }
+
+    fn recalc_cache(&self) -> Option<RecalcCache> {
+        self.inner.as_composite_shape()?.recalc_cache()
+    }
+
+    fn set_recalc_cache(&mut self, cache: Option<RecalcCache>) {
+        if let Some(view) = self.inner.as_composite_shape_mut() {
+            view.set_recalc_cache(cache);
+        }
+    }
+
+    fn damage(&self) -> Option<BoundingBox> {
+        self.inner.as_composite_shape()?.damage()
+    }
+
+    fn set_damage(&mut self, damage: Option<BoundingBox>) {
+        if let Some(view) = self.inner.as_composite_shape_mut() {
+            view.set_damage(damage);
+        }
+    }
+}
+
+/// Handle of the component for sending the messages from outside of its view, e.g. from the other thread. The
+/// messages are received by the runtime on the next frame, the idle controller is woken for it.
+pub struct Link<M: Model> {
+    sender: Sender<M::Message>,
+}
+
+impl<M: Model> Link<M> {
+    /// Enqueues the message. Returns false if the component is dropped.
+    pub fn send(&self, msg: M::Message) -> bool {
+        let sent = self.sender.send(msg).is_ok();
+        if sent {
+            wake_frame();
+        }
+        sent
+    }
+}
+
+impl<M: Model> Clone for Link<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
 }
 
 pub struct CompInner<M: Model> {
@@ -178,12 +435,29 @@ pub struct CompInner<M: Model> {
     view: Option<Node<M>>,
     view_state: ChangeViewState,
     view_update: UpdateView,
+    tasks: Vec<Task<M::Message>>,
+    timers: Timers<M::Message>,
+    link: Link<M>,
+    receiver: Receiver<M::Message>,
+    /// Values of the model for the nested components, they are provided again on the view update after the model is
+    /// changed.
+    provider: Option<Rc<Provider>>,
     transform: Transform,
 }
 
 impl<M: Model> CompInner<M> {
     pub fn new(model: M) -> Self {
-        let view = model.build_view();
+        let (sender, receiver) = mpsc::channel();
+        Self::with_link(model, Link { sender }, receiver)
+    }
+
+    fn with_link(model: M, link: Link<M>, receiver: Receiver<M::Message>) -> Self {
+        let provider = Rc::new(model.provide());
+        let view = with_provider(&provider, || {
+            let mut view = model.build_view();
+            view.view_memos();
+            view
+        });
 
         Self {
             id: None,
@@ -195,9 +469,92 @@ impl<M: Model> CompInner<M> {
                 ..Default::default()
             },
             view_update: UpdateView::RecalcAndRedraw,
+            tasks: vec![],
+            timers: Timers::default(),
+            link,
+            receiver,
+            provider: Some(provider),
             transform: Default::default(),
         }
     }
+
+    pub fn link(&self) -> Link<M> {
+        self.link.clone()
+    }
+
+    pub fn set_props(&mut self, props: M::Properties) -> bool {
+        let changed = self.model.change(props);
+        if changed {
+            self.view_state.update(ChangeView::Rebuild);
+            self.provider = None;
+        }
+        changed
+    }
+
+    fn apply_view_state(&mut self) -> UpdateView {
+        let mut update = UpdateView::None;
+
+        if self.view_state.need_rebuild {
+            let mut view = self.model.build_view();
+            let changed = match self.view.as_mut() {
+                Some(old) => {
+                    // The replaced view is not drawn anymore, so its area is damaged by the new one.
+                    let replaced_area = if old.is_patched_by(&view) {
+                        None
+                    } else {
+                        old.drawn_area()
+                    };
+                    let changed = old.patch(view);
+                    if let Some(area) = replaced_area {
+                        old.add_damage(area);
+                    }
+                    changed
+                },
+                None => {
+                    view.view_memos();
+                    self.view = Some(view);
+                    true
+                }
+            };
+            self.view_state.need_rebuild = false;
+            if changed {
+                update = UpdateView::RecalcAndRedraw;
+            }
+        }
+
+        if self.view_state.need_modify || self.view_state.need_recalc {
+            if let Some(view) = self.view.as_mut() {
+                self.model.modify_view(view);
+                if let Node::Prim(prim) = view {
+                    prim.invalidate();
+                }
+            }
+            if self.view_state.need_recalc {
+                self.view_state.need_recalc = false;
+                update = UpdateView::Recalc;
+            }
+            if self.view_state.need_modify {
+                self.view_state.need_modify = false;
+                update = UpdateView::RecalcAndRedraw;
+            }
+        }
+
+        if let Some(view) = self.view.as_mut() {
+            update = view.update_view().merge(update);
+        }
+        self.view_update = update;
+        update
+    }
+
+    fn update(&mut self, msg: M::Message) {
+        let command = self.model.update(msg);
+        self.view_state.update(command.change_view);
+        self.tasks.extend(command.tasks);
+        for op in command.timers {
+            self.timers.apply(op);
+        }
+        self.provider = None;
+    }
 }
 
 impl<M: Model> CompApi for CompInner<M> {
@@ -229,54 +586,116 @@ impl<M: Model> CompApi for CompInner<M> {
         self.view.as_mut().map(|node| node as &mut dyn CompositeShape)
     }
 
-    fn send_system_msg(&mut self, msg: SystemMessage) {
+    fn send_system_msg(&mut self, msg: &SystemMessage, propagation: &Propagation) {
         let mut outputs = vec![];
-        if let Some(msg) = self.model.system_update(msg) {
-            outputs.push(msg);
+        // The component out of the portals only passes the message through to its view in the portal pass.
+        if propagation.pass() != DispatchPass::Portals {
+            if let Some(msg) = self.model.system_update(msg.clone()) {
+                outputs.push(msg);
+            }
+        }
+        if let SystemMessage::Draw(elapsed) = *msg {
+            outputs.extend(self.timers.advance(elapsed));
         }
 
         if let Some(view) = self.view.as_mut() {
-            view.send_system_msg(msg, &mut outputs);
+            let ((), emitted) = with_emitted(|| view.dispatch(msg, propagation, &mut outputs));
+            outputs.extend(emitted);
         }
 
         for msg in outputs {
-            self.view_state.update(self.model.update(msg));
+            self.update(msg);
         }
     }
 
-    fn update_view(&mut self) -> UpdateView {
-        let mut need_to_propagate_update = true;
-        let mut update = UpdateView::None;
+    fn focus_order(&self, order: &mut FocusOrder) {
+        if let Some(view) = self.view.as_ref() {
+            view.collect_focus_order(order);
+        }
+    }
 
-        if self.view_state.need_rebuild {
-            let view = self.model.build_view();
-            self.view = Some(view);
-            self.view_state.need_rebuild = false;
-            need_to_propagate_update = false;
-            update = UpdateView::RecalcAndRedraw;
+    fn set_focus(&mut self, target: usize, idx: &mut usize, change: FocusChange) {
+        let mut outputs = vec![];
+        if let Some(view) = self.view.as_mut() {
+            let ((), emitted) = with_emitted(|| view.set_focus(target, idx, change, &mut outputs));
+            outputs.extend(emitted);
         }
 
-        if self.view_state.need_modify || self.view_state.need_recalc {
-            let mut view = self.view.take().unwrap();
-            self.model.modify_view(&mut view);
-            self.view = Some(view);
-            if self.view_state.need_recalc {
-                self.view_state.need_recalc = false;
-                update = UpdateView::Recalc;
+        for msg in outputs {
+            self.update(msg);
+        }
+    }
+
+    fn collect_shortcuts(&self, shortcuts: &mut Vec<Shortcut>) {
+        shortcuts.extend(self.model.shortcuts().iter());
+        if let Some(view) = self.view.as_ref() {
+            view.collect_shortcuts(shortcuts);
+        }
+    }
+
+    fn trigger_shortcut(&mut self, event: &KeyboardEvent) -> bool {
+        if let Some(msg) = self.model.shortcuts().trigger(event) {
+            self.update(msg);
+            return true;
+        }
+        let (triggered, emitted) = match self.view.as_mut() {
+            Some(view) => with_emitted(|| view.trigger_shortcut(event)),
+            None => return false,
+        };
+        for msg in emitted {
+            self.update(msg);
+        }
+        triggered
+    }
+
+    fn poll_commands(&mut self) -> bool {
+        let mut outputs = vec![];
+        self.tasks.retain_mut(|task| match task.poll() {
+            Some(msg) => {
+                outputs.push(msg);
+                false
             }
-            if self.view_state.need_modify {
-                self.view_state.need_modify = false;
-                update = UpdateView::RecalcAndRedraw;
+            None => true,
+        });
+        outputs.extend(self.receiver.try_iter());
+
+        let mut polled = !outputs.is_empty();
+        for msg in outputs {
+            self.update(msg);
+        }
+        if let Some(view) = self.view.as_mut() {
+            let (view_polled, emitted) = with_emitted(|| view.poll_commands());
+            polled |= view_polled;
+            for msg in emitted {
+                self.update(msg);
             }
         }
+        polled
+    }
 
-        if need_to_propagate_update {
-            if let Some(view) = self.view.as_mut() {
-                update = view.update_view().merge(update);
-            }
+    fn has_changes(&self) -> bool {
+        self.view_state.is_changed() || self.view.as_ref().is_some_and(|view| view.has_changes())
+    }
+
+    fn next_frame(&self) -> NextFrame {
+        let next_frame = if self.view_state.is_changed()
+            || self.model.is_animating()
+            || self.tasks.iter().any(|task| task.is_woken())
+        {
+            NextFrame::Now
+        } else {
+            self.timers.next_expiry().map_or(NextFrame::Idle, NextFrame::After)
+        };
+        match self.view.as_ref() {
+            Some(view) => next_frame.min(view.next_frame()),
+            None => next_frame,
         }
-        self.view_update = update;
-        update
+    }
+
+    fn update_view(&mut self) -> UpdateView {
+        let model = &self.model;
+        let provider = self.provider.get_or_insert_with(|| Rc::new(model.provide())).clone();
+        with_provider(&provider, || self.apply_view_state())
     }
 
     fn need_recalc(&self) -> bool {
@@ -286,4 +705,157 @@ impl<M: Model> CompApi for CompInner<M> {
     fn need_redraw(&self) -> bool {
         self.view_update.is_redraw()
     }
+
+    fn find_by_id(&self, id: &str) -> Option<NodeRef> {
+        self.view.as_ref()?.find_by_id(id)
+    }
+
+    fn find_by_id_mut(&mut self, id: &str) -> Option<NodeMut> {
+        self.view.as_mut()?.find_by_id_mut(id)
+    }
+
+    fn collect_subtree<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        if let Some(view) = self.view.as_ref() {
+            view.collect_subtree(nodes);
+        }
+    }
+
+    fn model_type(&self) -> TypeId {
+        TypeId::of::<M>()
+    }
+
+    fn patch(&mut self, new: Box<dyn CompApi>) -> bool {
+        let transform_changed = self.transform != *new.transform();
+        self.id = new.id().map(Into::into);
+        self.transform = *new.transform();
+        // The model keeps its state, only the new properties are passed to it, the rebuilt model is dropped.
+        let changed = match new.into_any().downcast::<Declared<M>>() {
+            Ok(mut declared) => match declared.source.take() {
+                Some(Source::Props(props)) => self.set_props(props),
+                _ => false,
+            },
+            Err(_) => false,
+        };
+        transform_changed || changed
+    }
+
+    fn mount(&mut self) -> Option<Box<dyn CompApi>> {
+        None
+    }
+}
+
+/// Component, which is declared by its properties or its model, but is not created yet.
+struct Declared<M: Model> {
+    source: Option<Source<M>>,
+    id: Option<String>,
+    transform: Transform,
+    /// Channel of the links, which are given before the component is created.
+    link: Link<M>,
+    receiver: Option<Receiver<M::Message>>,
+}
+
+impl<M: Model> CompApi for Declared<M> {
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn set_id(&mut self, id: String) {
+        self.id = Some(id);
+    }
+
+    fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    fn as_composite_shape(&self) -> Option<&dyn CompositeShape> {
+        None
+    }
+
+    fn as_composite_shape_mut(&mut self) -> Option<&mut dyn CompositeShape> {
+        None
+    }
+
+    fn send_system_msg(&mut self, _msg: &SystemMessage, _propagation: &Propagation) {}
+
+    fn focus_order(&self, _order: &mut FocusOrder) {}
+
+    fn set_focus(&mut self, _target: usize, _idx: &mut usize, _change: FocusChange) {}
+
+    fn collect_shortcuts(&self, _shortcuts: &mut Vec<Shortcut>) {}
+
+    fn trigger_shortcut(&mut self, _event: &KeyboardEvent) -> bool {
+        false
+    }
+
+    fn poll_commands(&mut self) -> bool {
+        false
+    }
+
+    fn has_changes(&self) -> bool {
+        false
+    }
+
+    fn next_frame(&self) -> NextFrame {
+        NextFrame::Idle
+    }
+
+    fn update_view(&mut self) -> UpdateView {
+        UpdateView::None
+    }
+
+    fn need_recalc(&self) -> bool {
+        false
+    }
+
+    fn need_redraw(&self) -> bool {
+        false
+    }
+
+    fn find_by_id(&self, _id: &str) -> Option<NodeRef<'_>> {
+        None
+    }
+
+    fn find_by_id_mut(&mut self, _id: &str) -> Option<NodeMut<'_>> {
+        None
+    }
+
+    fn collect_subtree<'a>(&'a self, _nodes: &mut Vec<NodeRef<'a>>) {}
+
+    fn model_type(&self) -> TypeId {
+        TypeId::of::<M>()
+    }
+
+    fn patch(&mut self, new: Box<dyn CompApi>) -> bool {
+        if let Ok(mut new) = new.into_any().downcast::<Declared<M>>() {
+            self.source = new.source.take();
+            self.id = new.id.take();
+            self.transform = new.transform;
+        }
+        true
+    }
+
+    fn mount(&mut self) -> Option<Box<dyn CompApi>> {
+        let model = match self.source.take()? {
+            Source::Props(props) => M::create(props),
+            Source::Model(model) => model,
+        };
+        let mut inner = CompInner::with_link(model, self.link.clone(), self.receiver.take()?);
+        inner.id = self.id.take();
+        inner.transform = self.transform;
+        Some(Box::new(inner))
+    }
+}
+
+/// What the declared component is created from.
+enum Source<M: Model> {
+    Props(M::Properties),
+    Model(M),
 }
@@ -1,8 +1,11 @@
-use std::{borrow::Cow, collections::HashMap, marker::PhantomData};
+use std::{any::Any, borrow::Cow, collections::HashMap, marker::PhantomData, mem, time::Duration};
 
 use crate::{
-    CompositeShape, CompositeShapeIter, CompositeShapeIterMut, EventName, InputEvent, Listener, Model, Node, On, Shape,
-    SystemMessage, Transform, UpdateView,
+    listener::DispatchPass, BoundingBox, Click, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, CursorIcon,
+    Drag, EventName, FileDrag, Fill, FocusChange, FocusOrder, GamepadButtonEvent, GestureThresholds, Group, InputEvent,
+    Listener, Model, Modifiers, MouseButton, MouseDown, MousePos, MouseScroll, MouseUp, NextFrame, Node, NodeMut, On,
+    Pan, Pinch, Propagation, Real, RecalcCache, Shape, ShapeState, StateStyles, Stroke, Style, SystemMessage, Tap,
+    Touch, TouchGestures, Transform, UpdateView,
 };
 
 pub struct Prim<M: Model> {
@@ -10,22 +13,222 @@ pub struct Prim<M: Model> {
     pub shape: Shape,
     pub children: Vec<Node<M>>,
     pub listeners: HashMap<EventName, Vec<Listener<M>>>,
+    pub states: StateStyles,
+    /// Draw order among the siblings, the children with the greater index are drawn later, i.e. on top.
+    pub z_index: i32,
+    pub gestures: GestureThresholds,
+    /// The primitive takes part in the Tab traversal and gets the keyboard events only while it is focused. The text
+    /// inputs are always focusable.
+    pub focusable: bool,
+    /// Cursor over the primitive, the text inputs have the text cursor by default.
+    pub cursor: Option<CursorIcon>,
+    /// The shape as it is built, it is compared with the rebuilt shape on the view patch.
+    declared: Shape,
+    memo: Option<Memo<M>>,
+    focused: bool,
+    state: ShapeState,
+    replaced: Option<ReplacedProps>,
+    state_changed: bool,
+    input_changed: bool,
+    need_recalc: bool,
+    recalc_cache: Option<RecalcCache>,
+    damage: Option<BoundingBox>,
+    capture: Option<Capture>,
+    /// The last click and the time since it, while the double click is possible.
+    last_click: Option<(Click, Duration)>,
+    touch: TouchGestures,
     _model: PhantomData<M>,
 }
 
+/// Mouse capture by the button press over the primitive. The primitive gets the drag events and the button release
+/// even if the cursor leaves its bounds.
+#[derive(Debug, Clone, Copy)]
+struct Capture {
+    drag: Drag,
+    dragging: bool,
+    /// Hold time of the press, it is reset if the cursor moves away from the press position.
+    held: Option<Duration>,
+    long_pressed: bool,
+}
+
+impl Capture {
+    fn new(press: MouseDown) -> Self {
+        Self {
+            drag: Drag {
+                start: press.pos,
+                pos: press.pos,
+                local_pos: press.local_pos,
+                delta: (0.0, 0.0),
+                button: press.button,
+                modifiers: press.modifiers,
+            },
+            dragging: false,
+            held: Some(Duration::default()),
+            long_pressed: false,
+        }
+    }
+
+    /// Advances the hold time. Returns true once the press becomes the long press.
+    fn hold(&mut self, elapsed: Duration, long_press_time: Duration) -> bool {
+        match &mut self.held {
+            Some(held) if !self.long_pressed => {
+                *held += elapsed;
+                self.long_pressed = *held >= long_press_time;
+                self.long_pressed
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Key and the deferred view of the memo primitive children. The view is called only when the primitive is new or
+/// its key is changed since the last patch, otherwise the old children are kept.
+pub struct Memo<M: Model> {
+    key: Box<dyn Any>,
+    same_key: fn(&dyn Any, &dyn Any) -> bool,
+    view: Option<Box<dyn FnOnce() -> Node<M>>>,
+}
+
+impl<M: Model> Memo<M> {
+    pub fn new<K: PartialEq + 'static>(key: K, view: impl FnOnce() -> Node<M> + 'static) -> Self {
+        Self {
+            key: Box::new(key),
+            same_key: |key, other| {
+                key.downcast_ref::<K>()
+                    .is_some_and(|key| other.downcast_ref() == Some(key))
+            },
+            view: Some(Box::new(view)),
+        }
+    }
+
+    fn same_key(&self, other: &Self) -> bool {
+        (self.same_key)(&*self.key, &*other.key)
+    }
+}
+
+/// Moves the input state of the replaced shape to the rebuilt one: the text input keeps the caret and the selection,
+/// the scroll area keeps the offset.
+fn keep_input_state(shape: &mut Shape, old: &Shape) {
+    match (shape, old) {
+        (Shape::Text(text), Shape::Text(old)) => {
+            let len = text.char_count();
+            if let (Some(input), Some(old)) = (&mut text.input, &old.input) {
+                input.keep_state(old, len);
+            }
+        }
+        (Shape::Group(group), Shape::Group(old)) => {
+            if let (Some(scroll), Some(old)) = (&mut group.scroll, old.scroll) {
+                scroll.content_size = old.content_size;
+                scroll.viewport_size = old.viewport_size;
+                group.scroll_to(old.offset.0, old.offset.1);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Own properties of the shape, which are replaced by the state style while the state is active.
+struct ReplacedProps {
+    style: Style,
+    transparency: Option<Real>,
+    fill: Option<Fill>,
+    stroke: Option<Stroke>,
+}
+
 impl<M: Model> Prim<M> {
+    /// The events, which require the mouse capture by the press.
+    const CAPTURE_EVENTS: [EventName; 7] = [
+        EventName::ON_CLICK,
+        EventName::ON_DOUBLE_CLICK,
+        EventName::ON_LONG_PRESS,
+        EventName::ON_MOUSE_UP,
+        EventName::ON_DRAG_START,
+        EventName::ON_DRAG,
+        EventName::ON_DRAG_END,
+    ];
+
+    /// The events, which require the tracking of the touches started over the primitive.
+    const TOUCH_EVENTS: [EventName; 5] = [
+        EventName::ON_TOUCH_MOVE,
+        EventName::ON_TOUCH_UP,
+        EventName::ON_TAP,
+        EventName::ON_PAN,
+        EventName::ON_PINCH,
+    ];
+
     pub fn new(
         name: Cow<'static, str>, shape: Shape, children: Vec<Node<M>>, listeners: HashMap<EventName, Vec<Listener<M>>>,
     ) -> Self {
         Self {
             name,
+            declared: shape.clone(),
             shape,
+            memo: None,
             children,
             listeners,
+            states: Default::default(),
+            z_index: 0,
+            gestures: Default::default(),
+            focusable: false,
+            cursor: None,
+            focused: false,
+            state: Default::default(),
+            replaced: None,
+            state_changed: false,
+            input_changed: false,
+            need_recalc: true,
+            recalc_cache: None,
+            damage: None,
+            capture: None,
+            last_click: None,
+            touch: Default::default(),
             _model: PhantomData,
         }
     }
 
+    pub fn with_states(mut self, states: StateStyles) -> Self {
+        self.states = states;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn with_gestures(mut self, gestures: GestureThresholds) -> Self {
+        self.gestures = gestures;
+        self
+    }
+
+    pub fn with_focusable(mut self, focusable: bool) -> Self {
+        self.focusable = focusable;
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<CursorIcon>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Sets the memo, whose view replaces the children of the primitive.
+    pub fn with_memo(mut self, memo: Option<Memo<M>>) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    pub fn is_fragment(&self) -> bool {
+        self.name == Group::FRAGMENT
+    }
+
+    pub fn is_focusable(&self) -> bool {
+        self.focusable || matches!(&self.shape, Shape::Text(text) if text.input.is_some())
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
     pub fn id(&self) -> Option<&str> {
         self.shape.id()
     }
@@ -34,6 +237,11 @@ impl<M: Model> Prim<M> {
         self.shape.set_id(id);
     }
 
+    /// User data of the shape, if it has the `T` type.
+    pub fn user_data<T: Any>(&self) -> Option<&T> {
+        self.shape.user_data()?.downcast_ref()
+    }
+
     pub fn set_text(&mut self, content: impl Into<String>) -> bool {
         match self.shape {
             Shape::Text(ref mut text) => {
@@ -52,37 +260,185 @@ impl<M: Model> Prim<M> {
         self.shape.transform_mut()
     }
 
+    /// Interaction state of the primitive, it is tracked by the input events only if the state styles are set.
+    pub fn state(&self) -> ShapeState {
+        self.state
+    }
+
+    /// Sets the interaction state and applies the style of the state over the shape properties. The replaced
+    /// properties are restored when the state style is no longer active.
+    pub fn set_state(&mut self, state: ShapeState) {
+        if self.state == state {
+            return;
+        }
+        self.state = state;
+
+        let style = self.states.resolve(state);
+        if style.as_ref() == self.replaced.as_ref().map(|replaced| &replaced.style) {
+            return;
+        }
+        if let Some(replaced) = self.replaced.take() {
+            if replaced.style.transparency.is_some() {
+                self.shape.set_transparency(replaced.transparency);
+            }
+            if replaced.style.fill.is_some() {
+                self.shape.set_fill(replaced.fill);
+            }
+            if replaced.style.stroke.is_some() {
+                self.shape.set_stroke(replaced.stroke);
+            }
+        }
+        if let Some(style) = style {
+            let replaced = ReplacedProps {
+                transparency: self.shape.transparency(),
+                fill: self.shape.fill().cloned(),
+                stroke: self.shape.stroke().cloned(),
+                style,
+            };
+            if let Some(transparency) = replaced.style.transparency {
+                self.shape.set_transparency(Some(transparency));
+            }
+            if let Some(fill) = &replaced.style.fill {
+                self.shape.set_fill(Some(fill.clone()));
+            }
+            if let Some(stroke) = &replaced.style.stroke {
+                self.shape.set_stroke(Some(stroke.clone()));
+            }
+            self.replaced = Some(replaced);
+        }
+        self.state_changed = true;
+    }
+
+    fn update_state(&mut self, input: &InputEvent) {
+        if self.states.is_empty() {
+            return;
+        }
+
+        let mut state = self.state;
+        match *input {
+            InputEvent::MouseMove(pos) => state.hovered = self.intersect(pos.x, pos.y),
+            InputEvent::MouseDown(press) => {
+                let hit = self.intersect(press.pos.x, press.pos.y);
+                state.focused = hit;
+                if press.button == MouseButton::Left {
+                    state.pressed = hit;
+                }
+            }
+            InputEvent::MouseUp(release) if release.button == MouseButton::Left => state.pressed = false,
+            _ => return,
+        }
+        self.set_state(state);
+    }
+
+    /// Edits the text input before the listeners are called, so that they get the changed text.
+    fn update_text_input(&mut self, msg: &SystemMessage) {
+        if let Shape::Text(text) = &mut self.shape {
+            let changed = match (msg, &mut text.input) {
+                (_, None) => false,
+                (SystemMessage::Input(input), Some(_)) => text.handle_input(input.clone()),
+                (&SystemMessage::Draw(elapsed), Some(input)) => input.blink(elapsed),
+                _ => false,
+            };
+            self.input_changed |= changed;
+        }
+    }
+
     pub fn send_system_msg(&mut self, msg: SystemMessage, outputs: &mut Vec<M::Message>) {
-        match msg {
-            SystemMessage::Input(input) => match input {
+        Propagation::dispatch(&msg, |propagation| self.dispatch(&msg, propagation, outputs));
+    }
+
+    /// Delivers the message to the subtree. The bubbling input goes to the children first, starting from the topmost
+    /// one, and then to the primitive. The other messages go to the primitive first.
+    pub(crate) fn dispatch(&mut self, msg: &SystemMessage, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        if !matches!(msg, SystemMessage::Input(input) if input.is_bubbling()) {
+            self.handle_system_msg(msg, propagation, outputs);
+            for child in self.children.iter_mut() {
+                child.dispatch(msg, propagation, outputs);
+            }
+            return;
+        }
+
+        let pass = propagation.pass();
+        let portal = self.is_portal();
+        if pass == DispatchPass::Rest && portal {
+            return;
+        }
+        if pass == DispatchPass::Portals && portal {
+            propagation.set_pass(DispatchPass::InPortal);
+        }
+
+        let mut draw_order: Vec<usize> = (0..self.children.len()).collect();
+        draw_order.sort_by_key(|&idx| self.children[idx].z_index());
+        for idx in draw_order.into_iter().rev() {
+            self.children[idx].dispatch(msg, propagation, outputs);
+        }
+        // Out of the portals the portal pass only looks for them in the children.
+        if pass != DispatchPass::Portals || portal {
+            self.handle_system_msg(msg, propagation, outputs);
+        }
+        propagation.set_pass(pass);
+    }
+
+    fn handle_system_msg(&mut self, msg: &SystemMessage, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        self.update_text_input(msg);
+        // The stopped bubbling input only updates the primitive state, the listeners do not get it.
+        let deliver = !propagation.is_stopped();
+
+        match *msg {
+            SystemMessage::Input(ref input) => match *input {
                 InputEvent::MouseDown(press) => {
-                    if self.intersect(press.pos.x, press.pos.y) {
+                    let press = MouseDown {
+                        local_pos: self.local_pos(press.pos),
+                        ..press
+                    };
+                    let hit = self.intersect(press.pos.x, press.pos.y);
+                    if hit && deliver {
+                        if Self::CAPTURE_EVENTS
+                            .iter()
+                            .any(|name| self.listeners.contains_key(name))
+                        {
+                            self.capture = Some(Capture::new(press));
+                        }
                         if let Some(listeners) = self.listeners.get(&EventName::ON_MOUSE_DOWN) {
                             for listener in listeners {
                                 let msg = match listener {
                                     Listener::OnMouseDown(func) => func(On {
                                         prim: self,
                                         event: press,
+                                        propagation,
                                     }),
                                     _ => continue,
                                 };
                                 outputs.push(msg);
                             }
                         }
-                    } else if let Some(listeners) = self.listeners.get(&EventName::ON_BLUR) {
-                        for listener in listeners {
-                            let msg = match listener {
-                                Listener::OnBlur(func) => func(On {
-                                    prim: self,
-                                    event: press,
-                                }),
-                                _ => continue,
-                            };
-                            outputs.push(msg);
+                        if press.button == MouseButton::Right {
+                            if let Some(listeners) = self.listeners.get(&EventName::ON_CONTEXT_MENU) {
+                                for listener in listeners {
+                                    let msg = match listener {
+                                        Listener::OnContextMenu(func) => func(On {
+                                            prim: self,
+                                            event: press,
+                                            propagation,
+                                        }),
+                                        _ => continue,
+                                    };
+                                    outputs.push(msg);
+                                }
+                            }
                         }
                     }
+                    if self.is_focusable() {
+                        self.set_focused(hit, FocusChange::Mouse(press), propagation, outputs);
+                    } else if !hit {
+                        self.send_focus(EventName::ON_BLUR, FocusChange::Mouse(press), propagation, outputs);
+                    }
                 }
-                InputEvent::MouseScroll(scroll) => {
+                InputEvent::MouseScroll(scroll) if deliver => {
+                    let scroll = MouseScroll {
+                        local_pos: self.local_pos(scroll.pos),
+                        ..scroll
+                    };
                     if self.intersect(scroll.pos.x, scroll.pos.y) {
                         if let Some(listeners) = self.listeners.get(&EventName::ON_MOUSE_SCROLL) {
                             for listener in listeners {
@@ -90,6 +446,36 @@ impl<M: Model> Prim<M> {
                                     Listener::OnMouseScroll(func) => func(On {
                                         prim: self,
                                         event: scroll,
+                                        propagation,
+                                    }),
+                                    _ => continue,
+                                };
+                                outputs.push(msg);
+                            }
+                        }
+                    }
+
+                    let scrolled = match &mut self.shape {
+                        Shape::Group(group) => {
+                            if group.scroll_by_wheel(scroll) {
+                                group.scroll
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    };
+                    if let Some(area) = scrolled {
+                        // The inner scroll area consumes the wheel, the outer ones are not scrolled.
+                        propagation.stop();
+                        self.input_changed = true;
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_SCROLL) {
+                            for listener in listeners {
+                                let msg = match listener {
+                                    Listener::OnScroll(func) => func(On {
+                                        prim: self,
+                                        event: area,
+                                        propagation,
                                     }),
                                     _ => continue,
                                 };
@@ -98,41 +484,252 @@ impl<M: Model> Prim<M> {
                         }
                     }
                 }
-                InputEvent::KeyDown(event) => {
+                InputEvent::KeyDown(event) if self.accepts_keys() => {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_KEY_DOWN) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnKeyDown(func) => func(On { prim: self, event }),
+                                Listener::OnKeyDown(func) => func(On {
+                                    prim: self,
+                                    event,
+                                    propagation,
+                                }),
                                 _ => continue,
                             };
                             outputs.push(msg);
                         }
                     }
                 }
-                InputEvent::KeyUp(event) => {
+                InputEvent::KeyUp(event) if self.accepts_keys() => {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_KEY_UP) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnKeyUp(func) => func(On { prim: self, event }),
+                                Listener::OnKeyUp(func) => func(On {
+                                    prim: self,
+                                    event,
+                                    propagation,
+                                }),
                                 _ => continue,
                             };
                             outputs.push(msg);
                         }
                     }
                 }
-                InputEvent::Char(ch) => {
+                InputEvent::MouseUp(release) => {
+                    let release = MouseUp {
+                        local_pos: self.local_pos(release.pos),
+                        ..release
+                    };
+                    let capture = match self.capture {
+                        Some(capture) if capture.drag.button == release.button => self.capture.take(),
+                        _ => None,
+                    };
+                    let hit = self.intersect(release.pos.x, release.pos.y);
+                    if (hit && deliver) || capture.is_some() {
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_MOUSE_UP) {
+                            for listener in listeners {
+                                let msg = match listener {
+                                    Listener::OnMouseUp(func) => func(On {
+                                        prim: self,
+                                        event: release,
+                                        propagation,
+                                    }),
+                                    _ => continue,
+                                };
+                                outputs.push(msg);
+                            }
+                        }
+                    }
+                    if let Some(mut capture) = capture {
+                        if capture.dragging {
+                            let drag = capture.drag.move_to(release.pos);
+                            self.send_drag(EventName::ON_DRAG_END, drag, propagation, outputs);
+                        }
+                        if hit && deliver && !capture.long_pressed && !capture.dragging {
+                            let click = self.click_at(release.pos, release.button, release.modifiers);
+                            self.send_click(EventName::ON_CLICK, click, propagation, outputs);
+
+                            let double = match self.last_click.take() {
+                                Some((last, since)) => {
+                                    last.button == click.button
+                                        && since <= self.gestures.double_click_time
+                                        && last.pos.distance(click.pos) <= self.gestures.double_click_distance
+                                }
+                                None => false,
+                            };
+                            if double {
+                                self.send_click(EventName::ON_DOUBLE_CLICK, click, propagation, outputs);
+                            } else {
+                                self.last_click = Some((click, Duration::default()));
+                            }
+                        }
+                    }
+                }
+                InputEvent::MouseMove(pos) => {
+                    if let Some(capture) = &mut self.capture {
+                        let moved = capture.drag.start.distance(pos) > self.gestures.long_press_distance;
+                        if moved {
+                            capture.held = None;
+                        }
+                        if (moved || capture.dragging) && pos != capture.drag.pos {
+                            let drag = capture.drag.move_to(pos);
+                            let started = !capture.dragging;
+                            capture.dragging = true;
+                            if started {
+                                self.send_drag(EventName::ON_DRAG_START, drag, propagation, outputs);
+                            }
+                            self.send_drag(EventName::ON_DRAG, drag, propagation, outputs);
+                        }
+                    }
+                }
+                InputEvent::TouchDown(touch) if deliver && self.intersect(touch.pos.x, touch.pos.y) => {
+                    if Self::TOUCH_EVENTS.iter().any(|name| self.listeners.contains_key(name)) {
+                        self.touch.down(touch);
+                    }
+                    self.send_touch(EventName::ON_TOUCH_DOWN, touch, propagation, outputs);
+                }
+                InputEvent::TouchMove(touch) if self.touch.is_tracked(touch.id) => {
+                    self.send_touch(EventName::ON_TOUCH_MOVE, touch, propagation, outputs);
+                    let (pan, pinch) = self.touch.move_to(touch, &self.gestures);
+                    if let Some(pan) = pan {
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_PAN) {
+                            for listener in listeners {
+                                let msg = match listener {
+                                    Listener::OnPan(func) => func(On {
+                                        prim: self,
+                                        event: Pan {
+                                            local_pos: self.local_pos(pan.pos),
+                                            ..pan
+                                        },
+                                        propagation,
+                                    }),
+                                    _ => continue,
+                                };
+                                outputs.push(msg);
+                            }
+                        }
+                    }
+                    if let Some(pinch) = pinch {
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_PINCH) {
+                            for listener in listeners {
+                                let msg = match listener {
+                                    Listener::OnPinch(func) => func(On {
+                                        prim: self,
+                                        event: Pinch {
+                                            local_center: self.local_pos(pinch.center),
+                                            ..pinch
+                                        },
+                                        propagation,
+                                    }),
+                                    _ => continue,
+                                };
+                                outputs.push(msg);
+                            }
+                        }
+                    }
+                }
+                InputEvent::TouchUp(touch) => {
+                    let hit = self.intersect(touch.pos.x, touch.pos.y) && deliver;
+                    if hit || self.touch.is_tracked(touch.id) {
+                        self.send_touch(EventName::ON_TOUCH_UP, touch, propagation, outputs);
+                    }
+                    if let Some(pos) = self.touch.up(touch).filter(|_| hit) {
+                        let tap = Tap {
+                            pos,
+                            local_pos: self.local_pos(pos),
+                        };
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_TAP) {
+                            for listener in listeners {
+                                let msg = match listener {
+                                    Listener::OnTap(func) => func(On {
+                                        prim: self,
+                                        event: tap,
+                                        propagation,
+                                    }),
+                                    _ => continue,
+                                };
+                                outputs.push(msg);
+                            }
+                        }
+                    }
+                }
+                InputEvent::Char(ch) if self.accepts_keys() => {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_INPUT_CHAR) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnInputChar(func) => func(On { prim: self, event: ch }),
+                                Listener::OnInputChar(func) => func(On {
+                                    prim: self,
+                                    event: ch,
+                                    propagation,
+                                }),
                                 _ => continue,
                             };
                             outputs.push(msg);
                         }
                     }
                 }
+                InputEvent::FileHover(ref drag) if deliver && self.intersect(drag.pos.x, drag.pos.y) => {
+                    self.send_file_drag(EventName::ON_FILE_HOVER, drag, propagation, outputs);
+                }
+                InputEvent::FileHoverCancel => {
+                    if let Some(listeners) = self.listeners.get(&EventName::ON_FILE_HOVER_CANCEL) {
+                        for listener in listeners {
+                            let msg = match listener {
+                                Listener::OnFileHoverCancel(func) => func(On {
+                                    prim: self,
+                                    event: (),
+                                    propagation,
+                                }),
+                                _ => continue,
+                            };
+                            outputs.push(msg);
+                        }
+                    }
+                }
+                InputEvent::FileDrop(ref drag) if deliver && self.intersect(drag.pos.x, drag.pos.y) => {
+                    self.send_file_drag(EventName::ON_FILE_DROP, drag, propagation, outputs);
+                }
+                InputEvent::GamepadButtonDown(event) if self.accepts_keys() => {
+                    self.send_gamepad_button(EventName::ON_GAMEPAD_BUTTON_DOWN, event, propagation, outputs);
+                }
+                InputEvent::GamepadButtonUp(event) if self.accepts_keys() => {
+                    self.send_gamepad_button(EventName::ON_GAMEPAD_BUTTON_UP, event, propagation, outputs);
+                }
+                InputEvent::GamepadAxis(event) if self.accepts_keys() => {
+                    if let Some(listeners) = self.listeners.get(&EventName::ON_GAMEPAD_AXIS) {
+                        for listener in listeners {
+                            let msg = match listener {
+                                Listener::OnGamepadAxis(func) => func(On {
+                                    prim: self,
+                                    event,
+                                    propagation,
+                                }),
+                                _ => continue,
+                            };
+                            outputs.push(msg);
+                        }
+                    }
+                }
+                _ => (),
             },
             SystemMessage::Draw(duration) => {
+                if let Some((_, since)) = &mut self.last_click {
+                    *since += duration;
+                    if *since > self.gestures.double_click_time {
+                        self.last_click = None;
+                    }
+                }
+                let long_press_time = self.gestures.long_press_time;
+                let long_press = self.capture.as_mut().and_then(|capture| {
+                    if capture.hold(duration, long_press_time) {
+                        Some(capture.drag)
+                    } else {
+                        None
+                    }
+                });
+                if let Some(drag) = long_press {
+                    let click = self.click_at(drag.pos, drag.button, drag.modifiers);
+                    self.send_click(EventName::ON_LONG_PRESS, click, propagation, outputs);
+                }
                 if let Some(listeners) = self.listeners.get(&EventName::DRAW) {
                     for listener in listeners {
                         let msg = match listener {
@@ -143,11 +740,11 @@ impl<M: Model> Prim<M> {
                     }
                 }
             }
-            SystemMessage::WindowResized { width, height } => {
+            SystemMessage::WindowResized(viewport) => {
                 if let Some(listeners) = self.listeners.get(&EventName::WINDOW_RESIZED) {
                     for listener in listeners {
                         let msg = match listener {
-                            Listener::WindowResized(func) => func(width, height),
+                            Listener::WindowResized(func) => func(viewport),
                             _ => continue,
                         };
                         outputs.push(msg);
@@ -156,16 +753,381 @@ impl<M: Model> Prim<M> {
             }
         }
 
+        if let SystemMessage::Input(input) = msg {
+            self.update_state(input);
+        }
+    }
+
+    /// Marks the primitive and the nested primitives as changed, e.g. after the model has modified the view. The
+    /// nested components track their changes by themselves.
+    pub fn invalidate(&mut self) {
+        self.need_recalc = true;
+        self.damage_drawn_shape();
         for child in self.children.iter_mut() {
-            child.send_system_msg(msg, outputs);
+            if let Node::Prim(prim) = child {
+                prim.invalidate();
+            }
+        }
+    }
+
+    /// Updates the primitive by the rebuilt one. The shapes, which are declared the same, keep their calculated values
+    /// and the recalc caches, the changed ones are replaced, but keep the interaction state. Returns true if the
+    /// subtree needs the recalculation, i.e. it is changed or it is not calculated yet.
+    pub(crate) fn patch(&mut self, new: Prim<M>) -> bool {
+        let Prim {
+            mut shape,
+            declared,
+            memo,
+            mut children,
+            listeners,
+            states,
+            z_index,
+            gestures,
+            focusable,
+            cursor,
+            ..
+        } = new;
+        self.listeners = listeners;
+        self.gestures = gestures;
+        self.focusable = focusable;
+        self.cursor = cursor;
+
+        let mut changed = self.z_index != z_index;
+        self.z_index = z_index;
+        if changed {
+            self.damage_drawn_shape();
+        }
+        if declared != self.declared || states != self.states {
+            self.damage_drawn_shape();
+            keep_input_state(&mut shape, &self.shape);
+            self.shape = shape;
+            self.declared = declared;
+            self.states = states;
+            self.replaced = None;
+            let state = mem::take(&mut self.state);
+            self.set_state(state);
+            self.recalc_cache = None;
+            changed = true;
+        }
+
+        match memo {
+            Some(memo) if self.memo.as_ref().is_some_and(|old| old.same_key(&memo)) => {
+                self.need_recalc |= changed;
+                return self.need_recalc;
+            }
+            Some(mut memo) => {
+                children = memo.view.take().map(|view| vec![view()]).unwrap_or_default();
+                self.memo = Some(memo);
+            }
+            None => self.memo = None,
+        }
+
+        // The children with the ids are matched by them, so the moved ones keep their state, the others are matched by
+        // the index among the children without the ids.
+        let mut old_children: Vec<_> = mem::take(&mut self.children).into_iter().map(Some).collect();
+        let mut unkeyed = old_children
+            .iter()
+            .enumerate()
+            .filter(|(_, old)| old.as_ref().is_some_and(|old| old.get_id().is_none()))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>()
+            .into_iter();
+        for (idx, mut child) in children.into_iter().enumerate() {
+            let old_idx = match child.get_id() {
+                Some(id) => old_children
+                    .iter()
+                    .position(|old| old.as_ref().is_some_and(|old| old.get_id() == Some(id))),
+                None => unkeyed.next(),
+            };
+            match old_idx.and_then(|old_idx| old_children[old_idx].take()) {
+                Some(mut old) => {
+                    let moved = old_idx != Some(idx);
+                    changed |= moved;
+                    // The replaced or moved node is not drawn there anymore, so its area is damaged by the parent.
+                    let replaced_area = if old.is_patched_by(&child) && !moved {
+                        None
+                    } else {
+                        old.drawn_area()
+                    };
+                    changed |= old.patch(child);
+                    if let Some(area) = replaced_area {
+                        self.add_damage(area);
+                    }
+                    self.children.push(old);
+                },
+                None => {
+                    child.view_memos();
+                    self.children.push(child);
+                    changed = true;
+                },
+            }
+        }
+        let removed_area = old_children
+            .iter()
+            .flatten()
+            .filter_map(|child| child.drawn_area())
+            .reduce(|area, other| area.union(&other));
+        if let Some(area) = removed_area {
+            self.add_damage(area);
+        }
+        changed |= old_children.iter().any(Option::is_some);
+
+        self.need_recalc |= changed;
+        self.need_recalc
+    }
+
+    pub(crate) fn find_by_id_mut(&mut self, id: &str) -> Option<NodeMut> {
+        if self.id() == Some(id) {
+            // The caller may change the shape, so the view is updated as after the input.
+            self.input_changed = true;
+            return Some(NodeMut::Shape(&mut self.shape));
+        }
+        self.children.iter_mut().find_map(|child| child.find_by_id_mut(id))
+    }
+
+    /// Views the children of the new memo primitives in the subtree.
+    pub(crate) fn view_memos(&mut self) {
+        if let Some(view) = self.memo.as_mut().and_then(|memo| memo.view.take()) {
+            self.children = vec![view()];
+        }
+        for child in self.children.iter_mut() {
+            child.view_memos();
+        }
+    }
+
+    /// Counts the focusable primitives of the subtree in the declaration order.
+    pub(crate) fn collect_focus_order(&self, order: &mut FocusOrder) {
+        if self.is_focusable() {
+            if self.focused {
+                order.focused = Some(order.count);
+            }
+            order.count += 1;
+        }
+        for child in self.children.iter() {
+            child.collect_focus_order(order);
+        }
+    }
+
+    /// Focuses the focusable primitive of the subtree with the `target` index in the declaration order and blurs
+    /// the others. The `idx` is the index of this primitive, it is advanced by the traversal.
+    pub(crate) fn set_focus(
+        &mut self, target: usize, idx: &mut usize, change: FocusChange, outputs: &mut Vec<M::Message>,
+    ) {
+        if self.is_focusable() {
+            self.set_focused(*idx == target, change, &Propagation::default(), outputs);
+            *idx += 1;
+        }
+        for child in self.children.iter_mut() {
+            child.set_focus(target, idx, change, outputs);
+        }
+    }
+
+    fn set_focused(
+        &mut self, focused: bool, change: FocusChange, propagation: &Propagation, outputs: &mut Vec<M::Message>,
+    ) {
+        if self.focused == focused {
+            return;
+        }
+        self.focused = focused;
+
+        if let Shape::Text(text) = &mut self.shape {
+            if let Some(input) = &mut text.input {
+                if input.focused != focused {
+                    input.focused = focused;
+                    input.anchor = input.caret;
+                    self.input_changed = true;
+                }
+            }
+        }
+        if !self.states.is_empty() {
+            let state = ShapeState { focused, ..self.state };
+            self.set_state(state);
+        }
+
+        let name = if focused {
+            EventName::ON_FOCUS
+        } else {
+            EventName::ON_BLUR
+        };
+        self.send_focus(name, change, propagation, outputs);
+    }
+
+    fn accepts_keys(&self) -> bool {
+        self.focused || !self.is_focusable()
+    }
+
+    fn send_focus(
+        &self, name: EventName, change: FocusChange, propagation: &Propagation, outputs: &mut Vec<M::Message>,
+    ) {
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnFocus(func) | Listener::OnBlur(func) => func(On {
+                        prim: self,
+                        event: change,
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn local_pos(&self, pos: MousePos) -> MousePos {
+        let (x, y) = self.shape.local_point(pos.x, pos.y);
+        MousePos { x, y }
+    }
+
+    fn click_at(&self, pos: MousePos, button: MouseButton, modifiers: Modifiers) -> Click {
+        Click {
+            pos,
+            local_pos: self.local_pos(pos),
+            button,
+            modifiers,
+        }
+    }
+
+    fn send_click(&self, name: EventName, click: Click, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnClick(func) | Listener::OnDoubleClick(func) | Listener::OnLongPress(func) => func(On {
+                        prim: self,
+                        event: click,
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn send_touch(&self, name: EventName, touch: Touch, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        let touch = Touch {
+            local_pos: self.local_pos(touch.pos),
+            ..touch
+        };
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnTouchDown(func) | Listener::OnTouchMove(func) | Listener::OnTouchUp(func) => func(On {
+                        prim: self,
+                        event: touch,
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn send_drag(&self, name: EventName, drag: Drag, propagation: &Propagation, outputs: &mut Vec<M::Message>) {
+        let drag = Drag {
+            local_pos: self.local_pos(drag.pos),
+            ..drag
+        };
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnDragStart(func) | Listener::OnDrag(func) | Listener::OnDragEnd(func) => func(On {
+                        prim: self,
+                        event: drag,
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn send_file_drag(
+        &self, name: EventName, drag: &FileDrag, propagation: &Propagation, outputs: &mut Vec<M::Message>,
+    ) {
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnFileHover(func) | Listener::OnFileDrop(func) => func(On {
+                        prim: self,
+                        event: FileDrag {
+                            local_pos: self.local_pos(drag.pos),
+                            ..drag.clone()
+                        },
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn send_gamepad_button(
+        &self, name: EventName, event: GamepadButtonEvent, propagation: &Propagation, outputs: &mut Vec<M::Message>,
+    ) {
+        if let Some(listeners) = self.listeners.get(&name) {
+            for listener in listeners {
+                let msg = match listener {
+                    Listener::OnGamepadButtonDown(func) | Listener::OnGamepadButtonUp(func) => func(On {
+                        prim: self,
+                        event,
+                        propagation,
+                    }),
+                    _ => continue,
+                };
+                outputs.push(msg);
+            }
+        }
+    }
+
+    /// Damages the area of the shape in the last recalculation, because the shape is changed since then.
+    fn damage_drawn_shape(&mut self) {
+        if let Some(cache) = self.recalc_cache {
+            self.add_damage(cache.global_bound);
+        }
+    }
+
+    /// Checks that the interaction state of the primitive or of the nested ones is changed since the last update.
+    pub fn has_changes(&self) -> bool {
+        self.state_changed || self.input_changed || self.children.iter().any(|child| child.has_changes())
+    }
+
+    /// The nearest frame of the primitive and the nested components. The changed primitive, the held press, which is
+    /// not long yet, and the draw listeners need the next frame right away, the focused input waits for the caret
+    /// blink.
+    pub fn next_frame(&self) -> NextFrame {
+        let pressing = self
+            .capture
+            .is_some_and(|capture| capture.held.is_some() && !capture.long_pressed);
+        if self.state_changed || self.input_changed || pressing || self.listeners.contains_key(&EventName::DRAW) {
+            return NextFrame::Now;
         }
+        let next_blink = match &self.shape {
+            Shape::Text(text) => text.input.as_ref().and_then(|input| input.next_blink()),
+            _ => None,
+        };
+        self.children.iter().fold(
+            next_blink.map_or(NextFrame::Idle, NextFrame::After),
+            |next_frame, child| next_frame.min(child.next_frame()),
+        )
     }
 
     pub fn update_view(&mut self) -> UpdateView {
-        let mut update = UpdateView::None;
+        let state_changed = mem::take(&mut self.state_changed);
+        let input_changed = mem::take(&mut self.input_changed);
+        let mut update = if state_changed || input_changed {
+            self.damage_drawn_shape();
+            UpdateView::RecalcAndRedraw
+        } else {
+            UpdateView::None
+        };
         for child in self.children.iter_mut() {
             update = child.update_view().merge(update);
         }
+        self.need_recalc |= update.is_recalc();
         update
     }
 }
@@ -190,10 +1152,395 @@ impl<M: Model> CompositeShape for Prim<M> {
     }
 
     fn need_recalc(&self) -> Option<bool> {
-        None
+        Some(self.need_recalc)
     }
 
     fn need_redraw(&self) -> Option<bool> {
         None
     }
+
+    fn z_index(&self) -> i32 {
+        self.z_index
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        match &self.shape {
+            Shape::Text(text) if self.cursor.is_none() && text.input.is_some() => Some(CursorIcon::Text),
+            _ => self.cursor,
+        }
+    }
+
+    fn recalc_cache(&self) -> Option<RecalcCache> {
+        self.recalc_cache
+    }
+
+    fn set_recalc_cache(&mut self, cache: Option<RecalcCache>) {
+        self.need_recalc = cache.is_none();
+        self.recalc_cache = cache;
+    }
+
+    fn damage(&self) -> Option<BoundingBox> {
+        self.damage
+    }
+
+    fn set_damage(&mut self, damage: Option<BoundingBox>) {
+        self.damage = damage;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::{
+        testing::{recalc_cache, TestModel},
+        Rect, Viewport,
+    };
+
+    fn rect(size: Real, listener: Listener<TestModel>, children: Vec<Node<TestModel>>) -> Prim<TestModel> {
+        let shape = Shape::Rect(Rect {
+            width: size.into(),
+            height: size.into(),
+            ..Default::default()
+        });
+        let listeners = vec![(listener.event_name(), vec![listener])].into_iter().collect();
+        Prim::new("rect".into(), shape, children, listeners)
+    }
+
+    fn press(x: Real) -> SystemMessage {
+        SystemMessage::Input(InputEvent::mouse_down(
+            MousePos { x, y: 5.0 },
+            MouseButton::Left,
+            Modifiers::NONE,
+        ))
+    }
+
+    fn move_to(x: Real) -> SystemMessage {
+        SystemMessage::Input(InputEvent::mouse_move(MousePos { x, y: 5.0 }))
+    }
+
+    fn release(x: Real) -> SystemMessage {
+        SystemMessage::Input(InputEvent::mouse_up(
+            MousePos { x, y: 5.0 },
+            MouseButton::Left,
+            Modifiers::NONE,
+        ))
+    }
+
+    fn send(prim: &mut Prim<TestModel>, msgs: impl IntoIterator<Item = SystemMessage>) -> Vec<&'static str> {
+        let mut outputs = vec![];
+        for msg in msgs {
+            prim.send_system_msg(msg, &mut outputs);
+        }
+        outputs
+    }
+
+    fn listen(prim: &mut Prim<TestModel>, listener: Listener<TestModel>) {
+        prim.listeners.entry(listener.event_name()).or_default().push(listener);
+    }
+
+    #[test]
+    fn bubble_from_deepest_hit() {
+        let mut outer = rect(100.0, Listener::OnMouseDown(Box::new(|_| "outer")), vec![
+            Node::Prim(rect(50.0, Listener::OnMouseDown(Box::new(|_| "inner")), vec![])),
+            Node::Prim(rect(
+                20.0,
+                Listener::OnMouseDown(Box::new(|on| {
+                    on.stop_propagation();
+                    "top"
+                })),
+                vec![],
+            )),
+        ]);
+
+        let mut outputs = vec![];
+        outer.send_system_msg(press(40.0), &mut outputs);
+        assert_eq!(outputs, ["inner", "outer"]);
+
+        outputs.clear();
+        outer.send_system_msg(press(10.0), &mut outputs);
+        assert_eq!(outputs, ["top"]);
+    }
+
+    #[test]
+    fn drop_file_on_hit_shape() {
+        let drop = |x| {
+            SystemMessage::Input(InputEvent::FileDrop(FileDrag {
+                pos: MousePos { x, y: 5.0 },
+                local_pos: MousePos { x, y: 5.0 },
+                path: "assets/tile.png".into(),
+            }))
+        };
+        let mut outer = rect(100.0, Listener::OnFileHoverCancel(Box::new(|_| "cancel")), vec![
+            Node::Prim(rect(
+                50.0,
+                Listener::OnFileDrop(Box::new(|on| {
+                    assert_eq!(on.event.path, std::path::Path::new("assets/tile.png"));
+                    "inner"
+                })),
+                vec![],
+            )),
+        ]);
+
+        let mut outputs = vec![];
+        outer.send_system_msg(drop(40.0), &mut outputs);
+        assert_eq!(outputs, ["inner"]);
+
+        outputs.clear();
+        outer.send_system_msg(drop(80.0), &mut outputs);
+        outer.send_system_msg(SystemMessage::Input(InputEvent::FileHoverCancel), &mut outputs);
+        assert_eq!(outputs, ["cancel"]);
+    }
+
+    #[test]
+    fn drag_then_release_without_click() {
+        let mut prim = rect(100.0, Listener::OnClick(Box::new(|_| "click")), vec![]);
+        listen(&mut prim, Listener::OnDragStart(Box::new(|_| "start")));
+        listen(&mut prim, Listener::OnDrag(Box::new(|_| "drag")));
+        listen(&mut prim, Listener::OnDragEnd(Box::new(|_| "end")));
+
+        assert_eq!(send(&mut prim, [press(10.0), move_to(12.0), release(12.0)]), ["click"]);
+        let drag = [press(10.0), move_to(12.0), move_to(30.0), move_to(40.0), release(40.0)];
+        assert_eq!(send(&mut prim, drag), ["start", "drag", "drag", "end"]);
+    }
+
+    #[test]
+    fn click_released_over_prim() {
+        let mut prim = rect(
+            50.0,
+            Listener::OnClick(Box::new(|on| {
+                assert_eq!((on.event.local_pos.x, on.event.local_pos.y), (12.0, 5.0));
+                "click"
+            })),
+            vec![],
+        );
+
+        assert_eq!(send(&mut prim, [press(10.0), release(12.0)]), ["click"]);
+        assert!(send(&mut prim, [press(10.0), release(60.0)]).is_empty());
+        assert!(send(&mut prim, [press(60.0), release(12.0)]).is_empty());
+    }
+
+    #[test]
+    fn double_click_and_long_press() {
+        let draw = |millis| SystemMessage::Draw(Duration::from_millis(millis));
+        let mut prim = rect(50.0, Listener::OnClick(Box::new(|_| "click")), vec![]);
+        listen(&mut prim, Listener::OnDoubleClick(Box::new(|_| "double")));
+        listen(&mut prim, Listener::OnLongPress(Box::new(|_| "long")));
+
+        let double = [press(10.0), release(10.0), draw(100), press(12.0), release(12.0)];
+        assert_eq!(send(&mut prim, double), ["click", "click", "double"]);
+        let slow = [press(10.0), release(10.0), draw(600), press(10.0), release(10.0)];
+        assert_eq!(send(&mut prim, slow), ["click", "click"]);
+
+        let held = [press(20.0), draw(300), draw(400), draw(400), release(20.0)];
+        assert_eq!(send(&mut prim, held), ["long"]);
+        let moved = [press(20.0), move_to(40.0), draw(700), release(40.0)];
+        assert!(send(&mut prim, moved).is_empty());
+    }
+
+    #[test]
+    fn click_with_modifiers() {
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::NONE
+        };
+        let shift_release = SystemMessage::Input(InputEvent::mouse_up(
+            MousePos { x: 10.0, y: 5.0 },
+            MouseButton::Left,
+            shift,
+        ));
+        let mut prim = rect(
+            50.0,
+            Listener::OnClick(Box::new(|on| {
+                if on.event.modifiers.shift {
+                    "shift-click"
+                } else {
+                    "click"
+                }
+            })),
+            vec![],
+        );
+
+        assert_eq!(send(&mut prim, [press(10.0), release(10.0)]), ["click"]);
+        assert_eq!(send(&mut prim, [press(10.0), shift_release]), ["shift-click"]);
+    }
+
+    #[test]
+    fn context_menu_on_right_press() {
+        let right_press = |x| {
+            SystemMessage::Input(InputEvent::mouse_down(
+                MousePos { x, y: 5.0 },
+                MouseButton::Right,
+                Modifiers::NONE,
+            ))
+        };
+        let mut prim = rect(50.0, Listener::OnContextMenu(Box::new(|_| "menu")), vec![]);
+        listen(&mut prim, Listener::OnMouseDown(Box::new(|_| "down")));
+
+        assert_eq!(send(&mut prim, [press(10.0)]), ["down"]);
+        assert_eq!(send(&mut prim, [right_press(10.0)]), ["down", "menu"]);
+        assert!(send(&mut prim, [right_press(60.0)]).is_empty());
+    }
+
+    #[test]
+    fn input_in_logical_pixels() {
+        let viewport = Viewport {
+            width: 200,
+            height: 100,
+            device_pixel_ratio: 2.0,
+        };
+        let mut prim = rect(
+            50.0,
+            Listener::WindowResized(Box::new(|viewport| {
+                assert_eq!(viewport.logical_size(), (100.0, 50.0));
+                "resized"
+            })),
+            vec![],
+        );
+        listen(&mut prim, Listener::OnMouseDown(Box::new(|_| "down")));
+
+        let input = [SystemMessage::WindowResized(viewport), press(40.0), press(80.0)];
+        assert_eq!(send(&mut prim, input), ["resized", "down"]);
+    }
+
+    #[test]
+    fn memo_views_changed_key() {
+        let views = Rc::new(Cell::new(0));
+        let build = |key: u32| {
+            let views = views.clone();
+            let memo = Memo::new(key, move || {
+                views.set(views.get() + 1);
+                Node::Prim(rect(10.0, Listener::OnMouseDown(Box::new(|_| "decoration")), vec![]))
+            });
+            Prim::new(
+                "group".into(),
+                Shape::Group(Group::default()),
+                vec![],
+                Default::default(),
+            )
+            .with_memo(Some(memo))
+        };
+
+        let mut view = build(1);
+        view.view_memos();
+        assert_eq!((views.get(), view.children.len()), (1, 1));
+
+        view.patch(build(1));
+        assert_eq!((views.get(), view.children.len()), (1, 1));
+        view.patch(build(2));
+        assert_eq!((views.get(), view.children.len()), (2, 1));
+    }
+
+    #[test]
+    fn portal_gets_input_first() {
+        let stop = |name| {
+            Listener::OnMouseDown(Box::new(move |on: On<TestModel, MouseDown>| {
+                on.stop_propagation();
+                name
+            }))
+        };
+        let portal = Shape::Group(Group {
+            portal: true,
+            ..Default::default()
+        });
+        let popup = Prim::new(
+            "group".into(),
+            portal,
+            vec![Node::Prim(rect(50.0, stop("popup"), vec![]))],
+            Default::default(),
+        );
+        let mut outer = rect(100.0, Listener::OnMouseDown(Box::new(|_| "outer")), vec![
+            Node::Prim(popup),
+            Node::Prim(rect(50.0, stop("under"), vec![])),
+        ]);
+
+        let mut outputs = vec![];
+        outer.send_system_msg(
+            SystemMessage::Input(InputEvent::mouse_down(
+                MousePos { x: 10.0, y: 10.0 },
+                MouseButton::Left,
+                Modifiers::NONE,
+            )),
+            &mut outputs,
+        );
+        assert_eq!(outputs, ["popup"]);
+    }
+
+    #[test]
+    fn patch_keeps_unchanged_shapes() {
+        let build = |size| {
+            rect(100.0, Listener::OnMouseDown(Box::new(|_| "outer")), vec![
+                Node::Prim(rect(size, Listener::OnMouseDown(Box::new(|_| "inner")), vec![])),
+                Node::Prim(rect(20.0, Listener::OnMouseDown(Box::new(|_| "top")), vec![])),
+            ])
+        };
+        let cache = recalc_cache(Default::default(), 1);
+
+        let mut view = build(50.0);
+        assert!(view.patch(build(50.0)));
+        view.set_recalc_cache(Some(cache));
+        for child in view.children.iter_mut() {
+            child.set_recalc_cache(Some(cache));
+        }
+        assert!(!view.patch(build(50.0)));
+
+        assert!(view.patch(build(40.0)));
+        assert_eq!(view.children[0].recalc_cache(), None);
+        assert_eq!(view.children[1].recalc_cache(), Some(cache));
+    }
+
+    #[test]
+    fn patch_damages_changed_shapes() {
+        let build = |sizes: &[Real]| {
+            let children = sizes
+                .iter()
+                .map(|&size| Node::Prim(rect(size, Listener::OnMouseDown(Box::new(|_| "inner")), vec![])))
+                .collect();
+            rect(100.0, Listener::OnMouseDown(Box::new(|_| "outer")), children)
+        };
+        let area = |size| BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: size,
+            max_y: size,
+        };
+        let cache = |size| recalc_cache(area(size), 1);
+
+        let mut view = build(&[50.0, 20.0, 10.0]);
+        view.set_recalc_cache(Some(cache(100.0)));
+        for (child, size) in view.children.iter_mut().zip([50.0, 20.0, 10.0]) {
+            child.set_recalc_cache(Some(cache(size)));
+        }
+        view.patch(build(&[50.0, 30.0]));
+        assert_eq!(view.damage(), Some(area(10.0)));
+        assert_eq!(view.children[0].damage(), None);
+        assert_eq!(view.children[1].damage(), Some(area(20.0)));
+    }
+
+    #[test]
+    fn patch_matches_children_by_id() {
+        let build = |ids: &[&str]| {
+            let children = ids
+                .iter()
+                .map(|&id| {
+                    let mut child = rect(10.0, Listener::OnMouseDown(Box::new(|_| "inner")), vec![]);
+                    child.shape.set_id(id);
+                    Node::Prim(child)
+                })
+                .collect();
+            rect(100.0, Listener::OnMouseDown(Box::new(|_| "outer")), children)
+        };
+        let cache = |revision| recalc_cache(Default::default(), revision);
+
+        let mut view = build(&["first", "second"]);
+        for (child, revision) in view.children.iter_mut().zip([1, 2]) {
+            child.set_recalc_cache(Some(cache(revision)));
+        }
+        view.patch(build(&["new", "second", "first"]));
+        assert_eq!(view.children[0].recalc_cache(), None);
+        assert_eq!(view.children[1].recalc_cache(), Some(cache(2)));
+        assert_eq!(view.children[2].recalc_cache(), Some(cache(1)));
+        assert_eq!(view.children[2].get_id(), Some("first"));
+    }
 }
@@ -1,27 +1,52 @@
 pub use self::{
-    circle::*, fill::*, group::*, padding::*, paint::*, path::*, rect::*, rounding::*, stroke::*, text::*, translate::*,
+    circle::*, custom::*, ellipse::*, fill::*, filter::*, group::*, image::*, padding::*, paint::*, path::*,
+    polygon::*, rect::*, ring::*, rounding::*, scroll_area::*, sector::*, stroke::*, text::*, text_input::*,
+    translate::*, user_data::*,
 };
-use crate::{Real, Transform};
+use std::{
+    any::Any,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{BoundingBox, Clip, CursorIcon, Real, RealValue, Transform, TransformMatrix, TransformOrigin};
 
 pub mod circle;
+pub mod custom;
+pub mod ellipse;
 pub mod fill;
+pub mod filter;
 pub mod group;
+pub mod image;
 pub mod padding;
 pub mod paint;
 pub mod path;
+pub mod polygon;
 pub mod rect;
+pub mod ring;
 pub mod rounding;
+pub mod scroll_area;
+pub mod sector;
 pub mod stroke;
 pub mod text;
+pub mod text_input;
 pub mod translate;
+pub mod user_data;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shape {
     Rect(Rect),
     Circle(Circle),
+    Ellipse(Ellipse),
     Path(Path),
     Group(Group),
     Text(Text),
+    Polygon(Polygon),
+    Image(Image),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Custom),
+    Sector(Sector),
+    Ring(Ring),
 }
 
 pub trait Shaped {
@@ -31,6 +56,9 @@ pub trait Shaped {
     fn circle(&self) -> Option<&Circle>;
     fn circle_mut(&mut self) -> Option<&mut Circle>;
 
+    fn ellipse(&self) -> Option<&Ellipse>;
+    fn ellipse_mut(&mut self) -> Option<&mut Ellipse>;
+
     fn path(&self) -> Option<&Path>;
     fn path_mut(&mut self) -> Option<&mut Path>;
 
@@ -39,6 +67,27 @@ pub trait Shaped {
 
     fn text(&self) -> Option<&Text>;
     fn text_mut(&mut self) -> Option<&mut Text>;
+
+    fn polygon(&self) -> Option<&Polygon>;
+    fn polygon_mut(&mut self) -> Option<&mut Polygon>;
+
+    fn image(&self) -> Option<&Image>;
+    fn image_mut(&mut self) -> Option<&mut Image>;
+
+    fn custom(&self) -> Option<&Custom>;
+    fn custom_mut(&mut self) -> Option<&mut Custom>;
+
+    fn sector(&self) -> Option<&Sector>;
+    fn sector_mut(&mut self) -> Option<&mut Sector>;
+
+    fn ring(&self) -> Option<&Ring>;
+    fn ring_mut(&mut self) -> Option<&mut Ring>;
+}
+
+/// Point hit test. The point is given in the window coordinates, so the calculated global transform of the shape is
+/// taken into account.
+pub trait HitTest {
+    fn contains_point(&self, x: Real, y: Real) -> bool;
 }
 
 pub struct ShapeRef<'a>(pub &'a Shape);
@@ -49,9 +98,15 @@ impl Shape {
         match self {
             Shape::Rect(rect) => rect.id(),
             Shape::Circle(circle) => circle.id(),
+            Shape::Ellipse(ellipse) => ellipse.id(),
             Shape::Path(path) => path.id(),
             Shape::Group(group) => group.id(),
             Shape::Text(text) => text.id(),
+            Shape::Polygon(polygon) => polygon.id(),
+            Shape::Image(image) => image.id(),
+            Shape::Custom(custom) => custom.id(),
+            Shape::Sector(sector) => sector.id(),
+            Shape::Ring(ring) => ring.id(),
         }
     }
 
@@ -60,9 +115,15 @@ impl Shape {
         match self {
             Shape::Rect(rect) => rect.id = id,
             Shape::Circle(circle) => circle.id = id,
+            Shape::Ellipse(ellipse) => ellipse.id = id,
             Shape::Path(path) => path.id = id,
             Shape::Group(group) => group.id = id,
             Shape::Text(text) => text.id = id,
+            Shape::Polygon(polygon) => polygon.id = id,
+            Shape::Image(image) => image.id = id,
+            Shape::Custom(custom) => custom.id = id,
+            Shape::Sector(sector) => sector.id = id,
+            Shape::Ring(ring) => ring.id = id,
         }
     }
 
@@ -70,9 +131,15 @@ impl Shape {
         match self {
             Shape::Rect(rect) => &rect.transform,
             Shape::Circle(circle) => &circle.transform,
+            Shape::Ellipse(ellipse) => &ellipse.transform,
             Shape::Path(path) => &path.transform,
             Shape::Group(group) => &group.transform,
             Shape::Text(text) => &text.transform,
+            Shape::Polygon(polygon) => &polygon.transform,
+            Shape::Image(image) => &image.transform,
+            Shape::Custom(custom) => &custom.transform,
+            Shape::Sector(sector) => &sector.transform,
+            Shape::Ring(ring) => &ring.transform,
         }
     }
 
@@ -80,9 +147,323 @@ impl Shape {
         match self {
             Shape::Rect(rect) => &mut rect.transform,
             Shape::Circle(circle) => &mut circle.transform,
+            Shape::Ellipse(ellipse) => &mut ellipse.transform,
             Shape::Path(path) => &mut path.transform,
             Shape::Group(group) => &mut group.transform,
             Shape::Text(text) => &mut text.transform,
+            Shape::Polygon(polygon) => &mut polygon.transform,
+            Shape::Image(image) => &mut image.transform,
+            Shape::Custom(custom) => &mut custom.transform,
+            Shape::Sector(sector) => &mut sector.transform,
+            Shape::Ring(ring) => &mut ring.transform,
+        }
+    }
+
+    pub fn transform_origin(&self) -> Option<TransformOrigin> {
+        match self {
+            Shape::Rect(rect) => rect.transform_origin,
+            Shape::Circle(circle) => circle.transform_origin,
+            Shape::Ellipse(ellipse) => ellipse.transform_origin,
+            Shape::Path(path) => path.transform_origin,
+            Shape::Group(group) => group.transform_origin,
+            Shape::Text(text) => text.transform_origin,
+            Shape::Polygon(polygon) => polygon.transform_origin,
+            Shape::Image(image) => image.transform_origin,
+            Shape::Custom(custom) => custom.transform_origin,
+            Shape::Sector(sector) => sector.transform_origin,
+            Shape::Ring(ring) => ring.transform_origin,
+        }
+    }
+
+    pub fn set_transform_origin(&mut self, origin: Option<TransformOrigin>) {
+        match self {
+            Shape::Rect(rect) => rect.transform_origin = origin,
+            Shape::Circle(circle) => circle.transform_origin = origin,
+            Shape::Ellipse(ellipse) => ellipse.transform_origin = origin,
+            Shape::Path(path) => path.transform_origin = origin,
+            Shape::Group(group) => group.transform_origin = origin,
+            Shape::Text(text) => text.transform_origin = origin,
+            Shape::Polygon(polygon) => polygon.transform_origin = origin,
+            Shape::Image(image) => image.transform_origin = origin,
+            Shape::Custom(custom) => custom.transform_origin = origin,
+            Shape::Sector(sector) => sector.transform_origin = origin,
+            Shape::Ring(ring) => ring.transform_origin = origin,
+        }
+    }
+
+    /// Name of the theme style of the shape.
+    pub fn style(&self) -> Option<&str> {
+        match self {
+            Shape::Rect(rect) => rect.style.as_deref(),
+            Shape::Circle(circle) => circle.style.as_deref(),
+            Shape::Ellipse(ellipse) => ellipse.style.as_deref(),
+            Shape::Path(path) => path.style.as_deref(),
+            Shape::Group(group) => group.style.as_deref(),
+            Shape::Text(text) => text.style.as_deref(),
+            Shape::Polygon(polygon) => polygon.style.as_deref(),
+            Shape::Image(image) => image.style.as_deref(),
+            Shape::Custom(custom) => custom.style.as_deref(),
+            Shape::Sector(sector) => sector.style.as_deref(),
+            Shape::Ring(ring) => ring.style.as_deref(),
+        }
+    }
+
+    pub fn user_data(&self) -> Option<&UserData> {
+        match self {
+            Shape::Rect(rect) => rect.user_data.as_ref(),
+            Shape::Circle(circle) => circle.user_data.as_ref(),
+            Shape::Ellipse(ellipse) => ellipse.user_data.as_ref(),
+            Shape::Path(path) => path.user_data.as_ref(),
+            Shape::Group(group) => group.user_data.as_ref(),
+            Shape::Text(text) => text.user_data.as_ref(),
+            Shape::Polygon(polygon) => polygon.user_data.as_ref(),
+            Shape::Image(image) => image.user_data.as_ref(),
+            Shape::Custom(custom) => custom.user_data.as_ref(),
+            Shape::Sector(sector) => sector.user_data.as_ref(),
+            Shape::Ring(ring) => ring.user_data.as_ref(),
+        }
+    }
+
+    pub fn set_user_data(&mut self, data: impl Any) {
+        let user_data = Some(UserData::new(data));
+        match self {
+            Shape::Rect(rect) => rect.user_data = user_data,
+            Shape::Circle(circle) => circle.user_data = user_data,
+            Shape::Ellipse(ellipse) => ellipse.user_data = user_data,
+            Shape::Path(path) => path.user_data = user_data,
+            Shape::Group(group) => group.user_data = user_data,
+            Shape::Text(text) => text.user_data = user_data,
+            Shape::Polygon(polygon) => polygon.user_data = user_data,
+            Shape::Image(image) => image.user_data = user_data,
+            Shape::Custom(custom) => custom.user_data = user_data,
+            Shape::Sector(sector) => sector.user_data = user_data,
+            Shape::Ring(ring) => ring.user_data = user_data,
+        }
+    }
+
+    pub fn fill(&self) -> Option<&Fill> {
+        match self {
+            Shape::Rect(rect) => rect.fill.as_ref(),
+            Shape::Circle(circle) => circle.fill.as_ref(),
+            Shape::Ellipse(ellipse) => ellipse.fill.as_ref(),
+            Shape::Path(path) => path.fill.as_ref(),
+            Shape::Group(group) => group.fill.as_ref(),
+            Shape::Text(text) => text.fill.as_ref(),
+            Shape::Polygon(polygon) => polygon.fill.as_ref(),
+            Shape::Image(image) => image.fill.as_ref(),
+            Shape::Custom(custom) => custom.fill.as_ref(),
+            Shape::Sector(sector) => sector.fill.as_ref(),
+            Shape::Ring(ring) => ring.fill.as_ref(),
+        }
+    }
+
+    pub fn stroke(&self) -> Option<&Stroke> {
+        match self {
+            Shape::Rect(rect) => rect.stroke.as_ref(),
+            Shape::Circle(circle) => circle.stroke.as_ref(),
+            Shape::Ellipse(ellipse) => ellipse.stroke.as_ref(),
+            Shape::Path(path) => path.stroke.as_ref(),
+            Shape::Group(group) => group.stroke.as_ref(),
+            Shape::Text(text) => text.stroke.as_ref(),
+            Shape::Polygon(polygon) => polygon.stroke.as_ref(),
+            Shape::Image(image) => image.stroke.as_ref(),
+            Shape::Custom(custom) => custom.stroke.as_ref(),
+            Shape::Sector(sector) => sector.stroke.as_ref(),
+            Shape::Ring(ring) => ring.stroke.as_ref(),
+        }
+    }
+
+    pub fn clip(&self) -> &Clip {
+        match self {
+            Shape::Rect(rect) => &rect.clip,
+            Shape::Circle(circle) => &circle.clip,
+            Shape::Ellipse(ellipse) => &ellipse.clip,
+            Shape::Path(path) => &path.clip,
+            Shape::Group(group) => &group.clip,
+            Shape::Text(text) => &text.clip,
+            Shape::Polygon(polygon) => &polygon.clip,
+            Shape::Image(image) => &image.clip,
+            Shape::Custom(custom) => &custom.clip,
+            Shape::Sector(sector) => &sector.clip,
+            Shape::Ring(ring) => &ring.clip,
+        }
+    }
+
+    /// Outer spacing of the shape. Only the rect, the circle and the ellipse have the margin, the other shapes have
+    /// none.
+    pub fn margin(&self) -> Option<&Margin> {
+        match self {
+            Shape::Rect(rect) => Some(&rect.margin),
+            Shape::Circle(circle) => Some(&circle.margin),
+            Shape::Ellipse(ellipse) => Some(&ellipse.margin),
+            _ => None,
+        }
+    }
+
+    /// Converts the point from the window coordinates to the local coordinates of the shape.
+    pub fn local_point(&self, x: Real, y: Real) -> (Real, Real) {
+        let transform = self.transform();
+        let matrix = transform.global_matrix().unwrap_or_else(|| transform.matrix());
+        if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Resolves the em values of the shape by the inherited font size. Returns the effective font size for the
+    /// children, which is the own font size of the text.
+    pub fn set_by_em(&mut self, font_size: Real) -> Real {
+        match self {
+            Shape::Rect(rect) => {
+                if let Some(rounding) = &mut rect.rounding {
+                    set_by_em(
+                        &mut [
+                            &mut rounding.top_left,
+                            &mut rounding.top_right,
+                            &mut rounding.bottom_left,
+                            &mut rounding.bottom_right,
+                        ],
+                        font_size,
+                    );
+                }
+                set_by_em(
+                    &mut [&mut rect.x, &mut rect.y, &mut rect.width, &mut rect.height],
+                    font_size,
+                );
+                set_by_em_padding(&mut rect.padding, &mut rect.margin, font_size);
+            }
+            Shape::Circle(circle) => {
+                set_by_em(&mut [&mut circle.cx, &mut circle.cy, &mut circle.r], font_size);
+                set_by_em_padding(&mut circle.padding, &mut circle.margin, font_size);
+            }
+            Shape::Ellipse(ellipse) => {
+                set_by_em(
+                    &mut [&mut ellipse.cx, &mut ellipse.cy, &mut ellipse.rx, &mut ellipse.ry],
+                    font_size,
+                );
+                set_by_em_padding(&mut ellipse.padding, &mut ellipse.margin, font_size);
+            }
+            Shape::Text(text) => {
+                if !text.font_size.set_by_em(font_size) {
+                    text.font_size.set_by_auto(font_size);
+                }
+                let font_size = text.font_size.val();
+                set_by_em(&mut [&mut text.x, &mut text.y], font_size);
+                for span_font_size in text.spans.iter_mut().filter_map(|span| span.font_size.as_mut()) {
+                    span_font_size.set_by_em(font_size);
+                }
+                return font_size;
+            }
+            Shape::Image(image) => set_by_em(
+                &mut [&mut image.x, &mut image.y, &mut image.width, &mut image.height],
+                font_size,
+            ),
+            Shape::Sector(sector) => set_by_em(&mut [&mut sector.cx, &mut sector.cy, &mut sector.r], font_size),
+            Shape::Ring(ring) => set_by_em(
+                &mut [&mut ring.cx, &mut ring.cy, &mut ring.inner_r, &mut ring.r],
+                font_size,
+            ),
+            Shape::Path(_) | Shape::Group(_) | Shape::Polygon(_) | Shape::Custom(_) => (),
+        }
+        font_size
+    }
+
+    /// Transparency of the shape, the group transparency is optional and inherited from the parent if not set.
+    pub fn transparency(&self) -> Option<Real> {
+        match self {
+            Shape::Rect(rect) => Some(rect.transparency),
+            Shape::Circle(circle) => Some(circle.transparency),
+            Shape::Ellipse(ellipse) => Some(ellipse.transparency),
+            Shape::Path(path) => Some(path.transparency),
+            Shape::Group(group) => group.transparency,
+            Shape::Text(text) => Some(text.transparency),
+            Shape::Polygon(polygon) => Some(polygon.transparency),
+            Shape::Image(image) => Some(image.transparency),
+            Shape::Custom(custom) => Some(custom.transparency),
+            Shape::Sector(sector) => Some(sector.transparency),
+            Shape::Ring(ring) => Some(ring.transparency),
+        }
+    }
+
+    pub fn set_transparency(&mut self, transparency: Option<Real>) {
+        match self {
+            Shape::Rect(rect) => rect.transparency = transparency.unwrap_or_default(),
+            Shape::Circle(circle) => circle.transparency = transparency.unwrap_or_default(),
+            Shape::Ellipse(ellipse) => ellipse.transparency = transparency.unwrap_or_default(),
+            Shape::Path(path) => path.transparency = transparency.unwrap_or_default(),
+            Shape::Group(group) => group.transparency = transparency,
+            Shape::Text(text) => text.transparency = transparency.unwrap_or_default(),
+            Shape::Polygon(polygon) => polygon.transparency = transparency.unwrap_or_default(),
+            Shape::Image(image) => image.transparency = transparency.unwrap_or_default(),
+            Shape::Custom(custom) => custom.transparency = transparency.unwrap_or_default(),
+            Shape::Sector(sector) => sector.transparency = transparency.unwrap_or_default(),
+            Shape::Ring(ring) => ring.transparency = transparency.unwrap_or_default(),
+        }
+    }
+
+    pub fn set_fill(&mut self, fill: Option<Fill>) {
+        match self {
+            Shape::Rect(rect) => rect.fill = fill,
+            Shape::Circle(circle) => circle.fill = fill,
+            Shape::Ellipse(ellipse) => ellipse.fill = fill,
+            Shape::Path(path) => path.fill = fill,
+            Shape::Group(group) => group.fill = fill,
+            Shape::Text(text) => text.fill = fill,
+            Shape::Polygon(polygon) => polygon.fill = fill,
+            Shape::Image(image) => image.fill = fill,
+            Shape::Custom(custom) => custom.fill = fill,
+            Shape::Sector(sector) => sector.fill = fill,
+            Shape::Ring(ring) => ring.fill = fill,
+        }
+    }
+
+    pub fn set_stroke(&mut self, stroke: Option<Stroke>) {
+        match self {
+            Shape::Rect(rect) => rect.stroke = stroke,
+            Shape::Circle(circle) => circle.stroke = stroke,
+            Shape::Ellipse(ellipse) => ellipse.stroke = stroke,
+            Shape::Path(path) => path.stroke = stroke,
+            Shape::Group(group) => group.stroke = stroke,
+            Shape::Text(text) => text.stroke = stroke,
+            Shape::Polygon(polygon) => polygon.stroke = stroke,
+            Shape::Image(image) => image.stroke = stroke,
+            Shape::Custom(custom) => custom.stroke = stroke,
+            Shape::Sector(sector) => sector.stroke = stroke,
+            Shape::Ring(ring) => ring.stroke = stroke,
+        }
+    }
+
+    /// Bounding box of the shape calculated by the renderer on the last recalc.
+    pub fn bound(&self) -> BoundingBox {
+        match self {
+            Shape::Rect(rect) => rect.bound,
+            Shape::Circle(circle) => circle.bound,
+            Shape::Ellipse(ellipse) => ellipse.bound,
+            Shape::Path(path) => path.bound,
+            Shape::Group(group) => group.bound,
+            Shape::Text(text) => text.bound,
+            Shape::Polygon(polygon) => polygon.bound,
+            Shape::Image(image) => image.bound,
+            Shape::Custom(custom) => custom.bound,
+            Shape::Sector(sector) => sector.bound,
+            Shape::Ring(ring) => ring.bound,
+        }
+    }
+
+    pub fn set_bound(&mut self, bound: BoundingBox) {
+        match self {
+            Shape::Rect(rect) => rect.bound = bound,
+            Shape::Circle(circle) => circle.bound = bound,
+            Shape::Ellipse(ellipse) => ellipse.bound = bound,
+            Shape::Path(path) => path.bound = bound,
+            Shape::Group(group) => group.bound = bound,
+            Shape::Text(text) => text.bound = bound,
+            Shape::Polygon(polygon) => polygon.bound = bound,
+            Shape::Image(image) => image.bound = bound,
+            Shape::Custom(custom) => custom.bound = bound,
+            Shape::Sector(sector) => sector.bound = bound,
+            Shape::Ring(ring) => ring.bound = bound,
         }
     }
 
@@ -130,6 +511,22 @@ impl Shaped for Shape {
         }
     }
 
+    #[inline]
+    fn ellipse(&self) -> Option<&Ellipse> {
+        match self {
+            Shape::Ellipse(ellipse) => Some(ellipse),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn ellipse_mut(&mut self) -> Option<&mut Ellipse> {
+        match self {
+            Shape::Ellipse(ellipse) => Some(ellipse),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn path(&self) -> Option<&Path> {
         match self {
@@ -177,6 +574,86 @@ impl Shaped for Shape {
             _ => None,
         }
     }
+
+    #[inline]
+    fn polygon(&self) -> Option<&Polygon> {
+        match self {
+            Shape::Polygon(polygon) => Some(polygon),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn polygon_mut(&mut self) -> Option<&mut Polygon> {
+        match self {
+            Shape::Polygon(polygon) => Some(polygon),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn image(&self) -> Option<&Image> {
+        match self {
+            Shape::Image(image) => Some(image),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn image_mut(&mut self) -> Option<&mut Image> {
+        match self {
+            Shape::Image(image) => Some(image),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn custom(&self) -> Option<&Custom> {
+        match self {
+            Shape::Custom(custom) => Some(custom),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn custom_mut(&mut self) -> Option<&mut Custom> {
+        match self {
+            Shape::Custom(custom) => Some(custom),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn sector(&self) -> Option<&Sector> {
+        match self {
+            Shape::Sector(sector) => Some(sector),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn sector_mut(&mut self) -> Option<&mut Sector> {
+        match self {
+            Shape::Sector(sector) => Some(sector),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn ring(&self) -> Option<&Ring> {
+        match self {
+            Shape::Ring(ring) => Some(ring),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn ring_mut(&mut self) -> Option<&mut Ring> {
+        match self {
+            Shape::Ring(ring) => Some(ring),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> ShapeRef<'a> {
@@ -190,6 +667,11 @@ impl<'a> ShapeRef<'a> {
         self.0.circle()
     }
 
+    #[inline]
+    pub fn ellipse(&self) -> Option<&Ellipse> {
+        self.0.ellipse()
+    }
+
     #[inline]
     pub fn path(&self) -> Option<&Path> {
         self.0.path()
@@ -204,6 +686,31 @@ impl<'a> ShapeRef<'a> {
     pub fn text(&self) -> Option<&Text> {
         self.0.text()
     }
+
+    #[inline]
+    pub fn polygon(&self) -> Option<&Polygon> {
+        self.0.polygon()
+    }
+
+    #[inline]
+    pub fn image(&self) -> Option<&Image> {
+        self.0.image()
+    }
+
+    #[inline]
+    pub fn custom(&self) -> Option<&Custom> {
+        self.0.custom()
+    }
+
+    #[inline]
+    pub fn sector(&self) -> Option<&Sector> {
+        self.0.sector()
+    }
+
+    #[inline]
+    pub fn ring(&self) -> Option<&Ring> {
+        self.0.ring()
+    }
 }
 
 impl<'a> ShapeRefMut<'a> {
@@ -217,6 +724,11 @@ impl<'a> ShapeRefMut<'a> {
         self.0.circle_mut()
     }
 
+    #[inline]
+    pub fn ellipse(&mut self) -> Option<&mut Ellipse> {
+        self.0.ellipse_mut()
+    }
+
     #[inline]
     pub fn path(&mut self) -> Option<&mut Path> {
         self.0.path_mut()
@@ -231,6 +743,115 @@ impl<'a> ShapeRefMut<'a> {
     pub fn text(&mut self) -> Option<&mut Text> {
         self.0.text_mut()
     }
+
+    #[inline]
+    pub fn polygon(&mut self) -> Option<&mut Polygon> {
+        self.0.polygon_mut()
+    }
+
+    #[inline]
+    pub fn image(&mut self) -> Option<&mut Image> {
+        self.0.image_mut()
+    }
+
+    #[inline]
+    pub fn custom(&mut self) -> Option<&mut Custom> {
+        self.0.custom_mut()
+    }
+
+    #[inline]
+    pub fn sector(&mut self) -> Option<&mut Sector> {
+        self.0.sector_mut()
+    }
+
+    #[inline]
+    pub fn ring(&mut self) -> Option<&mut Ring> {
+        self.0.ring_mut()
+    }
+}
+
+impl HitTest for Shape {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains_point(x, y),
+            Shape::Circle(circle) => circle.contains_point(x, y),
+            Shape::Ellipse(ellipse) => ellipse.contains_point(x, y),
+            Shape::Path(path) => path.contains_point(x, y),
+            Shape::Group(group) => group.contains_point(x, y),
+            Shape::Text(text) => text.contains_point(x, y),
+            Shape::Polygon(polygon) => polygon.contains_point(x, y),
+            Shape::Image(image) => image.contains_point(x, y),
+            Shape::Custom(custom) => custom.contains_point(x, y),
+            Shape::Sector(sector) => sector.contains_point(x, y),
+            Shape::Ring(ring) => ring.contains_point(x, y),
+        }
+    }
+}
+
+impl HitTest for Rect {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Circle {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Ellipse {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Path {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Group {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Text {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Polygon {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Image {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Custom {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Sector {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
+}
+
+impl HitTest for Ring {
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.intersect(x, y)
+    }
 }
 
 impl From<Rect> for Shape {
@@ -245,6 +866,12 @@ impl From<Circle> for Shape {
     }
 }
 
+impl From<Ellipse> for Shape {
+    fn from(ellipse: Ellipse) -> Self {
+        Shape::Ellipse(ellipse)
+    }
+}
+
 impl From<Path> for Shape {
     fn from(path: Path) -> Self {
         Shape::Path(path)
@@ -263,6 +890,36 @@ impl From<Text> for Shape {
     }
 }
 
+impl From<Polygon> for Shape {
+    fn from(polygon: Polygon) -> Self {
+        Shape::Polygon(polygon)
+    }
+}
+
+impl From<Image> for Shape {
+    fn from(image: Image) -> Self {
+        Shape::Image(image)
+    }
+}
+
+impl From<Custom> for Shape {
+    fn from(custom: Custom) -> Self {
+        Shape::Custom(custom)
+    }
+}
+
+impl From<Sector> for Shape {
+    fn from(sector: Sector) -> Self {
+        Shape::Sector(sector)
+    }
+}
+
+impl From<Ring> for Shape {
+    fn from(ring: Ring) -> Self {
+        Shape::Ring(ring)
+    }
+}
+
 impl From<String> for Shape {
     fn from(text: String) -> Self {
         Shape::Text(Text {
@@ -272,6 +929,28 @@ impl From<String> for Shape {
     }
 }
 
+/// Inputs and result of the last recalculation of the composite. The recalculation of the clean composite is skipped,
+/// if it is recalculated with the same inputs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecalcCache {
+    pub parent_bound: BoundingBox,
+    pub parent_transform: TransformMatrix,
+    pub font_size: Real,
+    pub bound: BoundingBox,
+    /// Area of the drawn shape in the window coordinates.
+    pub global_bound: BoundingBox,
+    /// Unique number of the recalculation, the subtree is not changed while the revision is the same.
+    pub revision: u64,
+}
+
+impl RecalcCache {
+    /// Returns the revision, which no recalculation has got before.
+    pub fn next_revision() -> u64 {
+        static REVISION: AtomicU64 = AtomicU64::new(0);
+        REVISION.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
 pub type CompositeShapeIter<'a> = Box<dyn Iterator<Item = &'a dyn CompositeShape> + 'a>;
 pub type CompositeShapeIterMut<'a> = Box<dyn Iterator<Item = &'a mut dyn CompositeShape> + 'a>;
 
@@ -289,15 +968,200 @@ pub trait CompositeShape {
     fn need_redraw(&self) -> Option<bool>;
 
     fn intersect(&self, x: Real, y: Real) -> bool {
-        if let Some(shape) = self.shape() {
-            match shape {
-                Shape::Rect(rect) => rect.intersect(x, y),
-                Shape::Circle(circle) => circle.intersect(x, y),
-                Shape::Path(path) => path.intersect(x, y),
-                _ => false,
+        self.shape().map(|shape| shape.contains_point(x, y)).unwrap_or(false)
+    }
+
+    fn z_index(&self) -> i32 {
+        0
+    }
+
+    fn cursor(&self) -> Option<CursorIcon> {
+        None
+    }
+
+    /// The cursor of the topmost shape under the point, which has the cursor. The children are over their parent.
+    fn cursor_at(&self, x: Real, y: Real) -> Option<CursorIcon> {
+        if let Some(children) = self.children_in_draw_order() {
+            if let Some(cursor) = children.into_iter().rev().find_map(|child| child.cursor_at(x, y)) {
+                return Some(cursor);
             }
-        } else {
-            false
         }
+        self.cursor().filter(|_| self.intersect(x, y))
+    }
+
+    fn recalc_cache(&self) -> Option<RecalcCache> {
+        None
+    }
+
+    /// Stores the result of the recalculation, the `None` cache marks the composite as changed.
+    fn set_recalc_cache(&mut self, _cache: Option<RecalcCache>) {}
+
+    /// Window area, which has to be redrawn because of the changes since the last recalculation, e.g. the area of the
+    /// changed shape before the change or of the removed children.
+    fn damage(&self) -> Option<BoundingBox> {
+        None
+    }
+
+    fn set_damage(&mut self, _damage: Option<BoundingBox>) {}
+
+    fn add_damage(&mut self, area: BoundingBox) {
+        let damage = self.damage().map_or(area, |damage| damage.union(&area));
+        self.set_damage(Some(damage));
+    }
+
+    /// Window area of the whole subtree in the last recalculation together with its not redrawn damage.
+    fn drawn_area(&self) -> Option<BoundingBox> {
+        let mut area = union_areas(self.recalc_cache().map(|cache| cache.global_bound), self.damage());
+        if let Some(children) = self.children() {
+            for child in children {
+                area = union_areas(area, child.drawn_area());
+            }
+        }
+        area
+    }
+
+    /// Drops the caches of the whole subtree, e.g. when the theme is changed.
+    fn clear_recalc_cache(&mut self) {
+        self.set_recalc_cache(None);
+        if let Some(children) = self.children_mut() {
+            for child in children {
+                child.clear_recalc_cache();
+            }
+        }
+    }
+
+    /// The bound of the last recalculation, if the subtree is not changed since then and the inputs are the same.
+    fn cached_bound(
+        &self, parent_bound: BoundingBox, parent_transform: TransformMatrix, font_size: Real,
+    ) -> Option<BoundingBox> {
+        match (self.need_recalc(), self.recalc_cache()) {
+            (Some(false), Some(cache))
+                if cache.parent_bound == parent_bound
+                    && cache.parent_transform == parent_transform
+                    && cache.font_size == font_size =>
+            {
+                Some(cache.bound)
+            }
+            _ => None,
+        }
+    }
+
+    /// Children sorted by the z-index, the children with the equal indices keep the declaration order.
+    fn children_in_draw_order(&self) -> Option<Vec<&dyn CompositeShape>> {
+        let mut children: Vec<_> = self.children()?.collect();
+        children.sort_by_key(|child| child.z_index());
+        Some(children)
+    }
+
+    fn is_portal(&self) -> bool {
+        matches!(self.shape(), Some(Shape::Group(group)) if group.portal)
+    }
+
+    /// Portal groups of the subtree in the draw order. The render skips them among the children and draws them after
+    /// the whole tree, the nested portals follow their outer ones.
+    fn portals(&self) -> Vec<&dyn CompositeShape> {
+        let mut portals = vec![];
+        for child in self.children_in_draw_order().unwrap_or_default() {
+            collect_portals(child, &mut portals);
+        }
+        portals
+    }
+}
+
+fn union_areas(area: Option<BoundingBox>, other: Option<BoundingBox>) -> Option<BoundingBox> {
+    match (area, other) {
+        (Some(area), Some(other)) => Some(area.union(&other)),
+        (area, other) => area.or(other),
+    }
+}
+
+fn collect_portals<'a>(composite: &'a dyn CompositeShape, portals: &mut Vec<&'a dyn CompositeShape>) {
+    if composite.is_portal() {
+        portals.push(composite);
+    }
+    for child in composite.children_in_draw_order().unwrap_or_default() {
+        collect_portals(child, portals);
+    }
+}
+
+impl CompositeShape for Shape {
+    fn shape(&self) -> Option<&Shape> {
+        Some(self)
+    }
+
+    fn shape_mut(&mut self) -> Option<&mut Shape> {
+        Some(self)
+    }
+
+    fn children(&self) -> Option<CompositeShapeIter> {
+        None
+    }
+
+    fn children_mut(&mut self) -> Option<CompositeShapeIterMut> {
+        None
+    }
+
+    fn need_recalc(&self) -> Option<bool> {
+        None
+    }
+
+    fn need_redraw(&self) -> Option<bool> {
+        None
+    }
+}
+
+fn set_by_em(values: &mut [&mut RealValue], font_size: Real) {
+    for value in values {
+        value.set_by_em(font_size);
+    }
+}
+
+fn set_by_em_padding(padding: &mut Padding, margin: &mut Margin, font_size: Real) {
+    set_by_em(
+        &mut [
+            &mut padding.top,
+            &mut padding.left,
+            &mut padding.right,
+            &mut padding.bottom,
+            &mut margin.top,
+            &mut margin.left,
+            &mut margin.right,
+            &mut margin.bottom,
+        ],
+        font_size,
+    );
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::RealValue;
+
+    #[test]
+    fn serde_round_trip() {
+        let shape = Shape::Rect(Rect {
+            id: Some("button".to_string()),
+            user_data: Some(UserData::new(1)),
+            width: RealValue::pct(50.0),
+            height: 20.into(),
+            stroke: Some((Color::Black, 2.0).into()),
+            fill: Some(
+                Gradient::Linear {
+                    start: (0.0, 0.0),
+                    end: (0.0, 20.0),
+                    start_color: Color::White,
+                    end_color: Color::RGBA(0.5, 0.5, 0.5, 1.0),
+                }
+                .into(),
+            ),
+            transform: Transform::new().with_translation(10.0, 5.0),
+            ..Default::default()
+        });
+        let json = serde_json::to_string(&shape).unwrap();
+        let loaded: Shape = serde_json::from_str(&json).unwrap();
+
+        let mut expected = shape;
+        expected.rect_mut().unwrap().user_data = None;
+        assert_eq!(loaded, expected);
     }
 }
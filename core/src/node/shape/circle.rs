@@ -1,17 +1,27 @@
-use crate::node::{Clip, Fill, Padding, Real, RealValue, Stroke, Transform, TransformMatrix};
+use crate::node::{
+    BoundingBox, Clip, Fill, Margin, Padding, Real, RealValue, Stroke, Transform, TransformMatrix, TransformOrigin,
+    UserData,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
     pub cx: RealValue,
     pub cy: RealValue,
     pub r: RealValue,
     pub padding: Padding,
+    pub margin: Margin,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
     pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
 }
 
 impl Circle {
@@ -21,11 +31,17 @@ impl Circle {
         self.id.as_ref().map(|s| s.as_str())
     }
 
-    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
-        self.transform.calculate_global(parent_global)
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
     }
 
     #[inline]
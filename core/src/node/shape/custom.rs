@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use crate::node::{
+    BoundingBox, Clip, Fill, PathCommand, Real, Stroke, Transform, TransformMatrix, TransformOrigin, UserData,
+};
+
+/// Application-defined shape. It is drawn by any renderer from the backend-agnostic list of paths.
+pub trait CustomShape: Debug {
+    /// Bounds of the shape in the local coordinates.
+    fn bounds(&self) -> BoundingBox;
+
+    fn draw(&self) -> Vec<CustomPath>;
+
+    fn clone_box(&self) -> Box<dyn CustomShape>;
+
+    /// Hit test in the local coordinates, by default the bounds are tested.
+    fn contains_point(&self, x: Real, y: Real) -> bool {
+        self.bounds().contains_point(x, y)
+    }
+}
+
+impl Clone for Box<dyn CustomShape> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CustomPath {
+    pub cmd: Vec<PathCommand>,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Custom {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    pub user_data: Option<UserData>,
+    pub shape: Box<dyn CustomShape>,
+    pub transparency: Real,
+    /// Stroke of the paths that have no own stroke.
+    pub stroke: Option<Stroke>,
+    /// Fill of the paths that have no own fill.
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl PartialEq for Custom {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.style == other.style
+            && self.user_data == other.user_data
+            && self.shape.bounds() == other.shape.bounds()
+            && self.shape.draw() == other.shape.draw()
+            && self.transparency == other.transparency
+            && self.stroke == other.stroke
+            && self.fill == other.fill
+            && self.clip == other.clip
+            && self.transform == other.transform
+            && self.bound == other.bound
+    }
+}
+
+impl Custom {
+    pub const NAME: &'static str = "custom";
+
+    pub fn new(shape: impl CustomShape + 'static) -> Self {
+        Self {
+            id: None,
+            style: None,
+            user_data: None,
+            shape: Box::new(shape),
+            transparency: 0.0,
+            stroke: None,
+            fill: None,
+            clip: Clip::default(),
+            transform: Transform::default(),
+            transform_origin: None,
+            bound: BoundingBox::default(),
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        self.shape.contains_point(x, y)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::node::{
+    BoundingBox, Clip, Fill, Margin, Padding, Real, RealValue, Stroke, Transform, TransformMatrix, TransformOrigin,
+    UserData,
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ellipse {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
+    pub cx: RealValue,
+    pub cy: RealValue,
+    pub rx: RealValue,
+    pub ry: RealValue,
+    pub padding: Padding,
+    pub margin: Margin,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl Ellipse {
+    pub const NAME: &'static str = "ellipse";
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        let (rx, ry) = (self.rx.val(), self.ry.val());
+        if rx <= 0.0 || ry <= 0.0 {
+            return false;
+        }
+        ((x - self.cx.val()) / rx).powi(2) + ((y - self.cy.val()) / ry).powi(2) <= 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipse_intersect() {
+        let mut ellipse = Ellipse {
+            cx: 50.0.into(),
+            cy: 50.0.into(),
+            rx: 40.0.into(),
+            ry: 20.0.into(),
+            ..Ellipse::default()
+        };
+        assert!(ellipse.intersect(50.0, 50.0));
+        assert!(ellipse.intersect(89.0, 50.0));
+        assert!(ellipse.intersect(50.0, 31.0));
+        assert!(!ellipse.intersect(50.0, 25.0));
+        assert!(!ellipse.intersect(85.0, 65.0));
+
+        ellipse.ry = 0.0.into();
+        assert!(!ellipse.intersect(50.0, 50.0));
+    }
+}
@@ -1,7 +1,8 @@
 use super::{Color, Gradient, Paint};
 use crate::node::ConvertTo;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fill {
     pub paint: Paint,
 }
@@ -0,0 +1,242 @@
+use crate::node::{Color, Real};
+
+/// Effect applied to the rendered group content. The sizes are in the logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Filter {
+    /// Blur with the standard deviation of the radius.
+    Blur(Real),
+    /// Desaturation, the amount is in the range `0.0..=1.0`.
+    Grayscale(Real),
+    /// Multiplier of the color channels, `1.0` keeps the colors.
+    Brightness(Real),
+    /// Blurred shadow of the content shape, shifted by the offset.
+    DropShadow {
+        offset: (Real, Real),
+        blur: Real,
+        color: Color,
+    },
+}
+
+impl Filter {
+    /// Distance, by which the filter spreads the content out of its bound.
+    pub fn spread(&self) -> Real {
+        match *self {
+            Filter::Blur(radius) => 3.0 * radius.abs(),
+            Filter::DropShadow {
+                offset: (x, y),
+                blur,
+                ..
+            } => x.abs().max(y.abs()) + 3.0 * blur.abs(),
+            Filter::Grayscale(_) | Filter::Brightness(_) => 0.0,
+        }
+    }
+}
+
+type Pixel = [f32; 4];
+
+/// Applies the filters on the CPU to the premultiplied RGBA pixels of the layer. The filter sizes are multiplied by
+/// the `scale`, which is the number of the layer pixels in the logical pixel.
+pub fn apply_filters(filters: &[Filter], pixels: Vec<u32>, width: usize, height: usize, scale: Real) -> Vec<u32> {
+    if filters.is_empty() {
+        return pixels;
+    }
+
+    let mut layer = Pixels {
+        data: pixels.into_iter().map(unpack).collect(),
+        width,
+        height,
+    };
+    for filter in filters {
+        match *filter {
+            Filter::Blur(radius) => layer.blur(radius * scale),
+            Filter::Grayscale(amount) => layer.grayscale(amount),
+            Filter::Brightness(factor) => layer.brightness(factor),
+            Filter::DropShadow {
+                offset: (dx, dy),
+                blur,
+                color,
+            } => layer.drop_shadow(
+                ((dx * scale).round() as isize, (dy * scale).round() as isize),
+                blur * scale,
+                color,
+            ),
+        }
+    }
+    layer.data.into_iter().map(pack).collect()
+}
+
+fn unpack(pixel: u32) -> Pixel {
+    let [r, g, b, a] = pixel.to_ne_bytes();
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0]
+}
+
+fn pack(pixel: Pixel) -> u32 {
+    let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    u32::from_ne_bytes([
+        to_byte(pixel[0]),
+        to_byte(pixel[1]),
+        to_byte(pixel[2]),
+        to_byte(pixel[3]),
+    ])
+}
+
+struct Pixels {
+    data: Vec<Pixel>,
+    width: usize,
+    height: usize,
+}
+
+impl Pixels {
+    fn grayscale(&mut self, amount: f32) {
+        for pixel in &mut self.data {
+            let luma = 0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2];
+            for channel in &mut pixel[..3] {
+                *channel += (luma - *channel) * amount;
+            }
+        }
+    }
+
+    fn brightness(&mut self, factor: f32) {
+        for pixel in &mut self.data {
+            let alpha = pixel[3];
+            for channel in &mut pixel[..3] {
+                // Premultiplied channels can not exceed the alpha.
+                *channel = (*channel * factor).min(alpha);
+            }
+        }
+    }
+
+    /// Three box blurs approximate the gaussian blur with the `sigma` deviation.
+    fn blur(&mut self, sigma: f32) {
+        let half = ((4.0 * sigma * sigma + 1.0).sqrt() / 2.0).floor() as usize;
+        if half == 0 {
+            return;
+        }
+
+        let mut line = Vec::with_capacity(self.width.max(self.height));
+        for _ in 0..3 {
+            for y in 0..self.height {
+                let row = &mut self.data[y * self.width..(y + 1) * self.width];
+                box_blur_line(row.iter_mut(), half, &mut line);
+            }
+            for x in 0..self.width {
+                let column = self.data.iter_mut().skip(x).step_by(self.width);
+                box_blur_line(column, half, &mut line);
+            }
+        }
+    }
+
+    fn drop_shadow(&mut self, (dx, dy): (isize, isize), blur: f32, color: Color) {
+        let [r, g, b, a] = color.as_arr();
+        let mut shadow = Pixels {
+            data: vec![[0.0; 4]; self.data.len()],
+            width: self.width,
+            height: self.height,
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (src_x, src_y) = (x as isize - dx, y as isize - dy);
+                if src_x >= 0 && src_y >= 0 && (src_x as usize) < self.width && (src_y as usize) < self.height {
+                    let alpha = self.data[src_y as usize * self.width + src_x as usize][3] * a;
+                    shadow.data[y * self.width + x] = [r * alpha, g * alpha, b * alpha, alpha];
+                }
+            }
+        }
+        shadow.blur(blur);
+
+        for (pixel, shadow_pixel) in self.data.iter_mut().zip(shadow.data) {
+            let transparency = 1.0 - pixel[3];
+            for (channel, shadow_channel) in pixel.iter_mut().zip(shadow_pixel.iter()) {
+                *channel += shadow_channel * transparency;
+            }
+        }
+    }
+}
+
+/// Blurs the line of pixels by the moving average, the pixels out of the line are transparent.
+fn box_blur_line<'a>(pixels: impl Iterator<Item = &'a mut Pixel>, half: usize, line: &mut Vec<Pixel>) {
+    let pixels: Vec<_> = pixels.collect();
+    line.clear();
+    line.extend(pixels.iter().map(|pixel| **pixel));
+
+    let size = (2 * half + 1) as f32;
+    let mut sum = [0.0; 4];
+    for pixel in line.iter().take(half + 1) {
+        add(&mut sum, pixel, 1.0);
+    }
+    for (idx, pixel) in pixels.into_iter().enumerate() {
+        *pixel = [sum[0] / size, sum[1] / size, sum[2] / size, sum[3] / size];
+        if let Some(next) = line.get(idx + half + 1) {
+            add(&mut sum, next, 1.0);
+        }
+        if idx >= half {
+            add(&mut sum, &line[idx - half], -1.0);
+        }
+    }
+}
+
+fn add(sum: &mut Pixel, pixel: &Pixel, sign: f32) {
+    for (value, channel) in sum.iter_mut().zip(pixel.iter()) {
+        *value += sign * channel;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+        u32::from_ne_bytes([r, g, b, a])
+    }
+
+    #[test]
+    fn color_filters() {
+        let pixels = vec![rgba(255, 0, 0, 255), rgba(100, 50, 200, 200)];
+        assert_eq!(apply_filters(&[], pixels.clone(), 2, 1, 1.0), pixels);
+
+        let gray = apply_filters(&[Filter::Grayscale(1.0)], pixels.clone(), 2, 1, 1.0);
+        assert_eq!(gray[0], rgba(54, 54, 54, 255));
+        let half_gray = apply_filters(&[Filter::Grayscale(0.5)], pixels.clone(), 2, 1, 1.0);
+        assert_eq!(half_gray[0], rgba(155, 27, 27, 255));
+
+        let bright = apply_filters(&[Filter::Brightness(2.0)], pixels, 2, 1, 1.0);
+        assert_eq!(bright, vec![rgba(255, 0, 0, 255), rgba(200, 100, 200, 200)]);
+    }
+
+    #[test]
+    fn blur_spreads_pixel() {
+        let (width, height) = (9, 9);
+        let mut pixels = vec![0; width * height];
+        pixels[4 * width + 4] = rgba(255, 255, 255, 255);
+
+        let blurred = apply_filters(&[Filter::Blur(1.0)], pixels.clone(), width, height, 1.0);
+        let alpha = |x: usize, y: usize| blurred[y * width + x].to_ne_bytes()[3];
+        assert!(alpha(4, 4) < 255);
+        assert!(alpha(3, 4) > 0 && alpha(3, 4) < alpha(4, 4));
+        assert_eq!(alpha(3, 4), alpha(5, 4));
+        assert_eq!(alpha(4, 3), alpha(4, 5));
+        assert_eq!(alpha(0, 0), 0);
+
+        // The radius is scaled, so the larger scale spreads the pixel further.
+        let scaled = apply_filters(&[Filter::Blur(1.0)], pixels, width, height, 2.0);
+        assert!(scaled[4 * width + 4].to_ne_bytes()[3] < alpha(4, 4));
+    }
+
+    #[test]
+    fn drop_shadow_under_pixels() {
+        let (width, height) = (4, 4);
+        let mut pixels = vec![0; width * height];
+        pixels[width + 1] = rgba(255, 255, 255, 255);
+        let shadow = Filter::DropShadow {
+            offset: (1.0, 1.0),
+            blur: 0.0,
+            color: Color::RGBA(1.0, 0.0, 0.0, 0.5),
+        };
+
+        let shadowed = apply_filters(&[shadow], pixels, width, height, 1.0);
+        assert_eq!(shadowed[width + 1], rgba(255, 255, 255, 255));
+        assert_eq!(shadowed[2 * width + 2], rgba(128, 0, 0, 128));
+        assert_eq!(shadowed.iter().filter(|&&pixel| pixel != 0).count(), 2);
+    }
+}
@@ -1,27 +1,63 @@
-use crate::node::{Clip, Fill, Real, Stroke, Transform, TransformMatrix};
+use crate::node::{
+    BoundingBox, Clip, Fill, Filter, Real, ScrollArea, Stroke, Transform, TransformMatrix, TransformOrigin, UserData,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
     pub transparency: Option<Real>,
+    /// Render the children into the offscreen layer, that is blended at once with the group transparency. So the
+    /// overlapping children do not show through each other, but it costs an extra render pass.
+    pub layer: bool,
+    /// Filters applied in order to the group layer.
+    pub filters: Vec<Filter>,
+    /// Keep the rendered layer between the frames and render the children again only when the subtree is changed,
+    /// e.g. for the static backgrounds and the complex vector icons. It costs the memory of the layer image.
+    pub cache: bool,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
+    /// Scrolling state, which makes the group the scroll area with the clip scissor as the viewport.
+    pub scroll: Option<ScrollArea>,
+    /// Align the text children on the common baseline, e.g. the label and the value with the different font sizes.
+    pub align_baselines: bool,
+    /// Draw the group over the whole tree, out of the clip scissors of the ancestors, e.g. the tooltip or the
+    /// dropdown popup. The group also gets the pointer input before the rest of the tree.
+    pub portal: bool,
     pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
 }
 
 impl Group {
     pub const NAME: &'static str = "group";
+    /// Name of the fragment primitive, which has the group shape without the attributes.
+    pub const FRAGMENT: &'static str = "fragment";
 
     pub fn id(&self) -> Option<&str> {
         self.id.as_ref().map(|s| s.as_str())
     }
 
-    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
-        self.transform.calculate_global(parent_global)
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    /// The group is rendered into the offscreen layer, when it is requested, cached or the filters are set.
+    pub fn need_layer(&self) -> bool {
+        self.layer || self.cache || !self.filters.is_empty()
     }
 
     pub fn empty_overrides(&self) -> bool {
@@ -0,0 +1,65 @@
+use crate::node::{
+    BoundingBox, Clip, Fill, Real, RealValue, Stroke, Transform, TransformMatrix, TransformOrigin, UserData,
+};
+
+/// Raster image loaded by the renderer from the `src` path. The `fill` is painted under the image and the `stroke`
+/// outlines it.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Image {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
+    pub src: String,
+    pub natural_size: Option<(Real, Real)>,
+    pub x: RealValue,
+    pub y: RealValue,
+    pub width: RealValue,
+    pub height: RealValue,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl Image {
+    pub const NAME: &'static str = "image";
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        x >= self.x.val()
+            && x <= self.x.val() + self.width.val()
+            && y >= self.y.val()
+            && y <= self.y.val() + self.height.val()
+    }
+}
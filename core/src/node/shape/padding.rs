@@ -1,6 +1,7 @@
 use crate::RealValue;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Padding {
     pub top: RealValue,
     pub left: RealValue,
@@ -29,3 +30,7 @@ impl<T: Into<RealValue>> From<T> for Padding {
         }
     }
 }
+
+/// Outer spacing of the shape, which is added around its bound when the parent calculates the inner bound. Like the
+/// padding, only the rect, the circle and the ellipse have the margin, so only their builders can set it.
+pub type Margin = Padding;
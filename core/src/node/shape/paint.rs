@@ -1,6 +1,9 @@
+use std::{error::Error, fmt, rc::Rc, str::FromStr};
+
 use crate::Real;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Paint {
     Color(Color),
     Gradient(Gradient),
@@ -13,6 +16,7 @@ impl Default for Paint {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Red,
     Green,
@@ -25,6 +29,80 @@ pub enum Color {
 }
 
 impl Color {
+    /// Color from the hue in degrees, the saturation and the lightness in the range `0.0..=1.0`.
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Color from the hue in degrees, the saturation and the value in the range `0.0..=1.0`.
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        Self::from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    fn from_hue_chroma(hue: f32, chroma: f32, min: f32) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Color::RGB(r + min, g + min, b + min)
+    }
+
+    /// Returns the hue in degrees, the saturation and the lightness.
+    pub fn as_hsl(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        [hue, saturation, lightness]
+    }
+
+    /// Returns the hue in degrees, the saturation and the value.
+    pub fn as_hsv(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        [hue, saturation, max]
+    }
+
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.as_arr();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// Increases the HSL lightness by the `amount`, keeping the alpha.
+    pub fn lighten(self, amount: f32) -> Color {
+        let [hue, saturation, lightness] = self.as_hsl();
+        let alpha = self.as_arr()[3];
+        Color::hsl(hue, saturation, (lightness + amount).clamp(0.0, 1.0)).with_alpha(alpha)
+    }
+
+    /// Decreases the HSL lightness by the `amount`, keeping the alpha.
+    pub fn darken(self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
     pub fn with_alpha(self, alpha: f32) -> Color {
         let [r, g, b, _] = self.as_arr();
         Color::RGBA(r, g, b, alpha)
@@ -42,6 +120,61 @@ impl Color {
             Color::RGBA(r, g, b, a) => [r, g, b, a],
         }
     }
+
+    /// Parses the `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` hex notation, the leading `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Color, ParseColorError> {
+        let digits = hex.trim().trim_start_matches('#');
+        let channels = if digits.is_ascii() {
+            match digits.len() {
+                3 | 4 => digits
+                    .chars()
+                    .map(|digit| digit.to_digit(16).map(|value| value * 17))
+                    .collect::<Option<Vec<_>>>(),
+                6 | 8 => (0..digits.len())
+                    .step_by(2)
+                    .map(|idx| u32::from_str_radix(&digits[idx..idx + 2], 16).ok())
+                    .collect::<Option<Vec<_>>>(),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let to_channel = |value: u32| value as f32 / 255.0;
+        match channels.as_deref() {
+            Some(&[r, g, b]) => Ok(Color::RGB(to_channel(r), to_channel(g), to_channel(b))),
+            Some(&[r, g, b, a]) => Ok(Color::RGBA(to_channel(r), to_channel(g), to_channel(b), to_channel(a))),
+            _ => Err(ParseColorError::InvalidHex(hex.to_string())),
+        }
+    }
+
+    /// Looks up the CSS color name, case insensitive.
+    pub fn from_name(name: &str) -> Result<Color, ParseColorError> {
+        let name = name.trim().to_ascii_lowercase();
+        if name == "transparent" {
+            return Ok(Color::RGBA(0.0, 0.0, 0.0, 0.0));
+        }
+        COLOR_NAMES
+            .binary_search_by_key(&name.as_str(), |(color_name, _)| color_name)
+            .map(|idx| {
+                let rgb = COLOR_NAMES[idx].1;
+                let to_channel = |shift: u32| ((rgb >> shift) & 0xff) as f32 / 255.0;
+                Color::RGB(to_channel(16), to_channel(8), to_channel(0))
+            })
+            .map_err(|_| ParseColorError::UnknownName(name))
+    }
+
+    /// Linear interpolation between the colors, `t` is in the range `0.0..=1.0`.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let [r1, g1, b1, a1] = self.as_arr();
+        let [r2, g2, b2, a2] = other.as_arr();
+        Color::RGBA(
+            r1 + (r2 - r1) * t,
+            g1 + (g2 - g1) * t,
+            b1 + (b2 - b1) * t,
+            a1 + (a2 - a1) * t,
+        )
+    }
 }
 
 impl Default for Color {
@@ -50,6 +183,36 @@ impl Default for Color {
     }
 }
 
+/// Parses either the hex notation starting with `#` or the CSS color name.
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().starts_with('#') {
+            Color::from_hex(s)
+        } else {
+            Color::from_name(s)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseColorError {
+    InvalidHex(String),
+    UnknownName(String),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidHex(hex) => write!(f, "Invalid hex color: {}", hex),
+            ParseColorError::UnknownName(name) => write!(f, "Unknown color name: {}", name),
+        }
+    }
+}
+
+impl Error for ParseColorError {}
+
 impl From<(Color, f32)> for Color {
     fn from((color, alpha): (Color, f32)) -> Self {
         color.with_alpha(alpha)
@@ -69,7 +232,8 @@ impl From<(Color, f32)> for Paint {
 }
 
 /// Gradient paint used to fill or stroke paths with gradient.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gradient {
     Linear {
         start: (Real, Real),
@@ -92,6 +256,29 @@ pub enum Gradient {
         start_color: Color,
         end_color: Color,
     },
+    /// Sweep gradient around the `center`. The `stops` are the offsets in the range `0.0..=1.0` of the full turn
+    /// with their colors, the turn starts from the `angle` in radians. The clones of the gradient share the stops.
+    Conic {
+        center: (Real, Real),
+        angle: Real,
+        stops: Rc<[(Real, Color)]>,
+    },
+}
+
+impl Gradient {
+    /// Color at the `offset` between the gradient `stops`, which have to be sorted by offset.
+    pub fn stops_color(stops: &[(Real, Color)], offset: Real) -> Color {
+        match stops.iter().position(|(stop_offset, _)| *stop_offset >= offset) {
+            Some(0) => stops[0].1,
+            Some(idx) => {
+                let (start_offset, start_color) = stops[idx - 1];
+                let (end_offset, end_color) = stops[idx];
+                let t = (offset - start_offset) / (end_offset - start_offset);
+                start_color.lerp(end_color, t)
+            }
+            None => stops.last().map(|(_, color)| *color).unwrap_or_default(),
+        }
+    }
 }
 
 impl From<Gradient> for Paint {
@@ -99,3 +286,202 @@ impl From<Gradient> for Paint {
         Paint::Gradient(gradient)
     }
 }
+
+/// CSS color names sorted for the binary search.
+const COLOR_NAMES: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_color() {
+        let stops = [(0.0, Color::Black), (0.5, Color::White), (1.0, Color::Red)];
+        assert_eq!(Gradient::stops_color(&stops, 0.0), Color::Black);
+        assert_eq!(Gradient::stops_color(&stops, 0.25), Color::RGBA(0.5, 0.5, 0.5, 1.0));
+        assert_eq!(Gradient::stops_color(&stops, 0.75), Color::RGBA(1.0, 0.5, 0.5, 1.0));
+        assert_eq!(Gradient::stops_color(&stops, 2.0), Color::Red);
+        assert_eq!(Gradient::stops_color(&[], 0.5), Color::default());
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(Color::from_hex("#ff0000"), Ok(Color::RGB(1.0, 0.0, 0.0)));
+        assert_eq!(Color::from_hex("#0f08"), Ok(Color::RGBA(0.0, 1.0, 0.0, 136.0 / 255.0)));
+        assert!(Color::from_hex("#ff000").is_err());
+        assert!(Color::from_hex("#gg0000").is_err());
+        assert_eq!(Color::from_name("White"), Ok(Color::RGB(1.0, 1.0, 1.0)));
+        assert_eq!(
+            "slategray".parse(),
+            Ok(Color::RGB(112.0 / 255.0, 128.0 / 255.0, 144.0 / 255.0))
+        );
+        assert_eq!(
+            "unknown".parse::<Color>(),
+            Err(ParseColorError::UnknownName("unknown".to_string()))
+        );
+        assert!(COLOR_NAMES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn hsl_and_hsv() {
+        assert_eq!(Color::hsl(0.0, 1.0, 0.5), Color::RGB(1.0, 0.0, 0.0));
+        assert_eq!(Color::hsl(240.0, 1.0, 0.5), Color::RGB(0.0, 0.0, 1.0));
+        assert_eq!(Color::hsv(120.0, 1.0, 1.0), Color::RGB(0.0, 1.0, 0.0));
+        assert_eq!(Color::RGB(0.0, 0.5, 1.0).as_hsl(), [210.0, 1.0, 0.5]);
+        assert_eq!(Color::RGB(0.0, 0.5, 1.0).as_hsv(), [210.0, 1.0, 1.0]);
+        assert_eq!(Color::Red.darken(0.25), Color::RGBA(0.5, 0.0, 0.0, 1.0));
+        assert_eq!(
+            Color::Black.with_alpha(0.5).lighten(1.0),
+            Color::RGBA(1.0, 1.0, 1.0, 0.5)
+        );
+    }
+}
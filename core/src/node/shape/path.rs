@@ -1,36 +1,348 @@
-use crate::node::{Clip, Fill, Real, Stroke, Transform, TransformMatrix};
+use crate::node::{
+    winding_number, BoundingBox, Clip, Fill, Real, Shape, Stroke, Transform, TransformMatrix, TransformOrigin, UserData,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path {
     pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
     pub cmd: Vec<PathCommand>,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
     pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+    pub marker_start: Option<Marker>,
+    pub marker_end: Option<Marker>,
+}
+
+/// Shape drawn at the path end, like the SVG marker. The x-axis of the marker is aligned with the path direction.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    pub shape: Box<Shape>,
+}
+
+impl Marker {
+    pub fn new(shape: impl Into<Shape>) -> Self {
+        Self {
+            shape: Box::new(shape.into()),
+        }
+    }
 }
 
 impl Path {
     pub const NAME: &'static str = "path";
+    pub const FLATTEN_TOLERANCE: Real = 0.25;
+    /// The smaller, non-positive or NaN tolerances are clamped to it, so the curves are split into a finite number of
+    /// segments.
+    pub const MIN_FLATTEN_TOLERANCE: Real = 1e-3;
+
+    pub fn builder() -> PathBuilder {
+        PathBuilder::default()
+    }
 
     pub fn id(&self) -> Option<&str> {
         self.id.as_ref().map(|s| s.as_str())
     }
 
-    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
-        self.transform.calculate_global(parent_global)
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    /// Tests the point against the filled area of the path using the non-zero winding rule.
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        self.flatten(Self::FLATTEN_TOLERANCE)
+            .iter()
+            .map(|polyline| winding_number(polyline, x, y))
+            .sum::<i32>()
+            != 0
+    }
+
+    /// Bound of the flattened path points, the stroke and the markers are not included.
+    pub fn flattened_bound(&self) -> Option<BoundingBox> {
+        let mut points = self.flatten(Self::FLATTEN_TOLERANCE).into_iter().flatten();
+        let [x, y] = points.next()?;
+        let bound = BoundingBox {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        };
+        Some(points.fold(bound, |bound, [x, y]| BoundingBox {
+            min_x: bound.min_x.min(x),
+            min_y: bound.min_y.min(y),
+            max_x: bound.max_x.max(x),
+            max_y: bound.max_y.max(y),
+        }))
+    }
+
+    /// Approximates the path by polylines, one for each subpath. The `tolerance` is the maximum distance between
+    /// the curves and their approximation, it is not less than `MIN_FLATTEN_TOLERANCE`.
+    pub fn flatten(&self, tolerance: Real) -> Vec<Vec<[Real; 2]>> {
+        use PathCommand::*;
+
+        let tolerance = tolerance.max(Self::MIN_FLATTEN_TOLERANCE);
+
+        let mut polylines: Vec<Vec<[Real; 2]>> = Vec::new();
+        let mut start_xy = [0.0, 0.0];
+        let mut last_xy: [Real; 2] = [0.0, 0.0];
+        let mut bez_ctrls = [[0.0, 0.0], [0.0, 0.0]];
+
+        for cmd in self.cmd.iter() {
+            let from = last_xy;
+            let mut points = Vec::new();
+            match *cmd {
+                Move(xy) => {
+                    last_xy = xy;
+                    start_xy = last_xy;
+                    polylines.push(vec![last_xy]);
+                    continue;
+                }
+                MoveRel(xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    start_xy = last_xy;
+                    polylines.push(vec![last_xy]);
+                    continue;
+                }
+                Line(xy) => last_xy = xy,
+                LineRel(xy) => last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]],
+                LineAlonX(x) => last_xy[0] = x,
+                LineAlonXRel(x) => last_xy[0] += x,
+                LineAlonY(y) => last_xy[1] = y,
+                LineAlonYRel(y) => last_xy[1] += y,
+                Close => last_xy = start_xy,
+                BezCtrl(xy) => {
+                    bez_ctrls = [bez_ctrls[1], xy];
+                    continue;
+                }
+                BezCtrlRel(xy) => {
+                    bez_ctrls = [bez_ctrls[1], [last_xy[0] + xy[0], last_xy[1] + xy[1]]];
+                    continue;
+                }
+                BezReflectCtrl => {
+                    let reflected = [2.0 * last_xy[0] - bez_ctrls[1][0], 2.0 * last_xy[1] - bez_ctrls[1][1]];
+                    bez_ctrls = [bez_ctrls[1], reflected];
+                    continue;
+                }
+                QuadBezTo(xy) | QuadBezToRel(xy) => {
+                    last_xy = if let QuadBezToRel(_) = cmd {
+                        [last_xy[0] + xy[0], last_xy[1] + xy[1]]
+                    } else {
+                        xy
+                    };
+                    flatten_quad_bez(from, bez_ctrls[1], last_xy, tolerance, &mut points);
+                }
+                CubBezTo(xy) | CubBezToRel(xy) => {
+                    last_xy = if let CubBezToRel(_) = cmd {
+                        [last_xy[0] + xy[0], last_xy[1] + xy[1]]
+                    } else {
+                        xy
+                    };
+                    flatten_cub_bez(from, bez_ctrls[0], bez_ctrls[1], last_xy, tolerance, &mut points);
+                }
+                Arc {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                }
+                | ArcRel {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    last_xy = if let ArcRel { .. } = cmd {
+                        [last_xy[0] + to[0], last_xy[1] + to[1]]
+                    } else {
+                        to
+                    };
+                    let mut segment_from = from;
+                    for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(from, radius, rotation, large_arc, sweep, last_xy) {
+                        flatten_cub_bez(segment_from, ctrl1, ctrl2, xy, tolerance, &mut points);
+                        segment_from = xy;
+                    }
+                }
+            }
+            if points.is_empty() {
+                points.push(last_xy);
+            }
+
+            match polylines.last_mut() {
+                Some(polyline) => polyline.extend(points),
+                None => {
+                    let mut polyline = vec![from];
+                    polyline.extend(points);
+                    polylines.push(polyline);
+                }
+            }
+        }
+        polylines
+    }
+
+    /// Positions and direction angles of the path start and end.
+    pub fn endpoints(&self) -> Option<[([Real; 2], Real); 2]> {
+        let mut points: Vec<[Real; 2]> = Vec::new();
+        for point in self.flatten(Self::FLATTEN_TOLERANCE).into_iter().flatten() {
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+        if points.len() < 2 {
+            return None;
+        }
+
+        let angle = |from: [Real; 2], to: [Real; 2]| (to[1] - from[1]).atan2(to[0] - from[0]);
+        let last = points.len() - 1;
+        Some([
+            (points[0], angle(points[0], points[1])),
+            (points[last], angle(points[last - 1], points[last])),
+        ])
+    }
+
+    /// Local transforms of the start and end markers, relative to the path.
+    pub fn marker_placements(&self) -> (Option<TransformMatrix>, Option<TransformMatrix>) {
+        let placement = |(point, angle): ([Real; 2], Real)| {
+            TransformMatrix::identity()
+                .with_rotation(angle)
+                .with_translation(point[0], point[1])
+        };
+        match self.endpoints() {
+            Some([start, end]) => (
+                self.marker_start.as_ref().map(|_| placement(start)),
+                self.marker_end.as_ref().map(|_| placement(end)),
+            ),
+            None => (None, None),
+        }
+    }
+
+    /// The length of the path, approximated with the `FLATTEN_TOLERANCE`.
+    pub fn total_length(&self) -> Real {
+        self.total_length_with_tolerance(Self::FLATTEN_TOLERANCE)
+    }
+
+    pub fn total_length_with_tolerance(&self, tolerance: Real) -> Real {
+        self.flatten(tolerance)
+            .iter()
+            .map(|polyline| polyline_length(polyline))
+            .sum()
+    }
+
+    /// The point at the `t` fraction of the path length, where `t` is from `0.0` to `1.0`.
+    pub fn point_at(&self, t: Real) -> Option<[Real; 2]> {
+        self.point_at_with_tolerance(t, Self::FLATTEN_TOLERANCE)
+    }
+
+    pub fn point_at_with_tolerance(&self, t: Real, tolerance: Real) -> Option<[Real; 2]> {
+        let polylines = self.flatten(tolerance);
+        let total_length: Real = polylines.iter().map(|polyline| polyline_length(polyline)).sum();
+        point_at_length(&polylines, total_length * t.clamp(0.0, 1.0))
+            .map(|(point, _)| point)
+            .or_else(|| polylines.last().and_then(|polyline| polyline.last()).copied())
+    }
+
+    /// The point at the `length` distance from the path start and the path direction angle at it. There is no point
+    /// out of the path.
+    pub fn point_at_length(&self, length: Real) -> Option<([Real; 2], Real)> {
+        point_at_length(&self.flatten(Self::FLATTEN_TOLERANCE), length)
+    }
+}
+
+pub(crate) fn point_at_length(polylines: &[Vec<[Real; 2]>], mut length: Real) -> Option<([Real; 2], Real)> {
+    if length < 0.0 {
+        return None;
+    }
+    for polyline in polylines.iter() {
+        for line in polyline.windows(2) {
+            let line_length = distance(line[0], line[1]);
+            if length <= line_length && line_length > 0.0 {
+                let k = length / line_length;
+                let (dx, dy) = (line[1][0] - line[0][0], line[1][1] - line[0][1]);
+                return Some(([line[0][0] + dx * k, line[0][1] + dy * k], dy.atan2(dx)));
+            }
+            length -= line_length;
+        }
     }
+    None
+}
+
+fn distance(a: [Real; 2], b: [Real; 2]) -> Real {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
 
-    pub fn intersect(&self, _x: Real, _y: Real) -> bool {
-        false // TODO: need impl
+fn polyline_length(points: &[[Real; 2]]) -> Real {
+    points.windows(2).map(|line| distance(line[0], line[1])).sum()
+}
+
+/// Pushes the quadratic bezier points except the first one.
+fn flatten_quad_bez(from: [Real; 2], ctrl: [Real; 2], to: [Real; 2], tolerance: Real, points: &mut Vec<[Real; 2]>) {
+    let dd = distance([0.0, 0.0], [
+        from[0] - 2.0 * ctrl[0] + to[0],
+        from[1] - 2.0 * ctrl[1] + to[1],
+    ]);
+    let segments = (dd / (4.0 * tolerance)).sqrt().ceil().max(1.0) as usize;
+    for idx in 1..=segments {
+        let t = idx as Real / segments as Real;
+        let mt = 1.0 - t;
+        points.push([
+            mt * mt * from[0] + 2.0 * mt * t * ctrl[0] + t * t * to[0],
+            mt * mt * from[1] + 2.0 * mt * t * ctrl[1] + t * t * to[1],
+        ]);
+    }
+}
+
+/// Pushes the cubic bezier points except the first one.
+fn flatten_cub_bez(
+    from: [Real; 2], ctrl1: [Real; 2], ctrl2: [Real; 2], to: [Real; 2], tolerance: Real, points: &mut Vec<[Real; 2]>,
+) {
+    let dd1 = distance([0.0, 0.0], [
+        from[0] - 2.0 * ctrl1[0] + ctrl2[0],
+        from[1] - 2.0 * ctrl1[1] + ctrl2[1],
+    ]);
+    let dd2 = distance([0.0, 0.0], [
+        ctrl1[0] - 2.0 * ctrl2[0] + to[0],
+        ctrl1[1] - 2.0 * ctrl2[1] + to[1],
+    ]);
+    let segments = (0.75 * dd1.max(dd2) / tolerance).sqrt().ceil().max(1.0) as usize;
+    for idx in 1..=segments {
+        let t = idx as Real / segments as Real;
+        let mt = 1.0 - t;
+        let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+        points.push([
+            a * from[0] + b * ctrl1[0] + c * ctrl2[0] + d * to[0],
+            a * from[1] + b * ctrl1[1] + c * ctrl2[1] + d * to[1],
+        ]);
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathCommand {
     Move([Real; 2]),
     MoveRel([Real; 2]),
@@ -43,9 +355,282 @@ pub enum PathCommand {
     Close,
     BezCtrl([Real; 2]),
     BezCtrlRel([Real; 2]),
+    /// Reflection of the previous control point about the current point, as in the SVG `S`/`T` commands.
     BezReflectCtrl,
     QuadBezTo([Real; 2]),
     QuadBezToRel([Real; 2]),
     CubBezTo([Real; 2]),
     CubBezToRel([Real; 2]),
+    /// Elliptical arc to the `to` point, same as the SVG `A` command. The `rotation` of the ellipse x-axis is in
+    /// radians.
+    Arc {
+        radius: [Real; 2],
+        rotation: Real,
+        large_arc: bool,
+        sweep: bool,
+        to: [Real; 2],
+    },
+    ArcRel {
+        radius: [Real; 2],
+        rotation: Real,
+        large_arc: bool,
+        sweep: bool,
+        to: [Real; 2],
+    },
+}
+
+/// Fluent builder of the path commands, which takes care of the bezier control points.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct PathBuilder {
+    cmd: Vec<PathCommand>,
+}
+
+impl PathBuilder {
+    pub fn move_to(mut self, to: [Real; 2]) -> Self {
+        self.cmd.push(PathCommand::Move(to));
+        self
+    }
+
+    pub fn line_to(mut self, to: [Real; 2]) -> Self {
+        self.cmd.push(PathCommand::Line(to));
+        self
+    }
+
+    pub fn quad_to(mut self, ctrl: [Real; 2], to: [Real; 2]) -> Self {
+        self.cmd.push(PathCommand::BezCtrl(ctrl));
+        self.cmd.push(PathCommand::QuadBezTo(to));
+        self
+    }
+
+    pub fn cubic_to(mut self, ctrl1: [Real; 2], ctrl2: [Real; 2], to: [Real; 2]) -> Self {
+        self.cmd.push(PathCommand::BezCtrl(ctrl1));
+        self.cmd.push(PathCommand::BezCtrl(ctrl2));
+        self.cmd.push(PathCommand::CubBezTo(to));
+        self
+    }
+
+    pub fn arc_to(mut self, radius: [Real; 2], rotation: Real, large_arc: bool, sweep: bool, to: [Real; 2]) -> Self {
+        self.cmd.push(PathCommand::Arc {
+            radius,
+            rotation,
+            large_arc,
+            sweep,
+            to,
+        });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.cmd.push(PathCommand::Close);
+        self
+    }
+
+    pub fn build(self) -> Vec<PathCommand> {
+        self.cmd
+    }
+}
+
+impl From<PathBuilder> for Vec<PathCommand> {
+    fn from(builder: PathBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Converts the SVG elliptical arc from the `from` point into cubic bezier segments. Each segment is
+/// `[first control point, second control point, end point]`.
+pub fn arc_to_cub_beziers(
+    from: [Real; 2], radius: [Real; 2], rotation: Real, large_arc: bool, sweep: bool, to: [Real; 2],
+) -> Vec<[[Real; 2]; 3]> {
+    use std::f32::consts::PI;
+
+    if from == to {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (radius[0].abs(), radius[1].abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![[from, to, to]];
+    }
+
+    let (sin_phi, cos_phi) = rotation.sin_cos();
+    let (dx, dy) = ((from[0] - to[0]) / 2.0, (from[1] - to[1]) / 2.0);
+    let x1 = cos_phi * dx + sin_phi * dy;
+    let y1 = -sin_phi * dx + cos_phi * dy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let cx = cos_phi * cx1 - sin_phi * cy1 + (from[0] + to[0]) / 2.0;
+    let cy = sin_phi * cx1 + cos_phi * cy1 + (from[1] + to[1]) / 2.0;
+
+    let angle = |ux: Real, uy: Real, vx: Real, vy: Real| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let start_angle = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut sweep_angle = angle((x1 - cx1) / rx, (y1 - cy1) / ry, (-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    } else if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    }
+
+    let segments = (sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let delta = sweep_angle / segments as Real;
+    let t = 4.0 / 3.0 * (delta / 4.0).tan();
+    let map = |x: Real, y: Real| {
+        [
+            cx + cos_phi * rx * x - sin_phi * ry * y,
+            cy + sin_phi * rx * x + cos_phi * ry * y,
+        ]
+    };
+
+    (0..segments)
+        .map(|idx| {
+            let (sin_start, cos_start) = (start_angle + delta * idx as Real).sin_cos();
+            let (sin_end, cos_end) = (start_angle + delta * (idx + 1) as Real).sin_cos();
+            let end = if idx + 1 == segments { to } else { map(cos_end, sin_end) };
+            [
+                map(cos_start - t * sin_start, sin_start + t * cos_start),
+                map(cos_end + t * sin_end, sin_end - t * cos_end),
+                end,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let cmd = Path::builder()
+            .move_to([0.0, 0.0])
+            .quad_to([5.0, 10.0], [10.0, 0.0])
+            .cubic_to([12.0, 5.0], [18.0, 5.0], [20.0, 0.0])
+            .close()
+            .build();
+        assert_eq!(cmd, vec![
+            PathCommand::Move([0.0, 0.0]),
+            PathCommand::BezCtrl([5.0, 10.0]),
+            PathCommand::QuadBezTo([10.0, 0.0]),
+            PathCommand::BezCtrl([12.0, 5.0]),
+            PathCommand::BezCtrl([18.0, 5.0]),
+            PathCommand::CubBezTo([20.0, 0.0]),
+            PathCommand::Close,
+        ]);
+    }
+
+    #[test]
+    fn length_and_point_at() {
+        let path = Path {
+            cmd: Path::builder()
+                .move_to([0.0, 0.0])
+                .line_to([10.0, 0.0])
+                .line_to([10.0, 10.0])
+                .build(),
+            ..Path::default()
+        };
+        assert_eq!(path.total_length(), 20.0);
+        assert_eq!(path.point_at(0.25), Some([5.0, 0.0]));
+        assert_eq!(path.point_at(1.0), Some([10.0, 10.0]));
+        assert_eq!(
+            path.point_at_length(15.0),
+            Some(([10.0, 5.0], std::f32::consts::FRAC_PI_2))
+        );
+        assert_eq!(path.point_at_length(25.0), None);
+
+        let circle = Path {
+            cmd: Path::builder()
+                .move_to([0.0, 0.0])
+                .arc_to([10.0, 10.0], 0.0, false, true, [20.0, 0.0])
+                .arc_to([10.0, 10.0], 0.0, false, true, [0.0, 0.0])
+                .build(),
+            ..Path::default()
+        };
+        let length = circle.total_length_with_tolerance(0.01);
+        assert!((length - 20.0 * std::f32::consts::PI).abs() < 0.1);
+        assert!(circle.intersect(10.0, 5.0));
+        assert!(!circle.intersect(10.0, 15.0));
+    }
+
+    #[test]
+    fn marker_placements() {
+        let marker = || Some(Marker::new(Shape::Rect(Default::default())));
+        let near = |(x, y): (Real, Real), (ex, ey): (Real, Real)| (x - ex).abs() < 1e-4 && (y - ey).abs() < 1e-4;
+        let mut path = Path {
+            cmd: Path::builder()
+                .move_to([0.0, 0.0])
+                .line_to([10.0, 0.0])
+                .line_to([10.0, 10.0])
+                .build(),
+            marker_start: marker(),
+            marker_end: marker(),
+            ..Path::default()
+        };
+
+        // The markers are placed at the path ends and their x-axes go along the path, the middle vertex does not
+        // change the directions.
+        let (start, end) = path.marker_placements();
+        let (start, end) = (start.unwrap(), end.unwrap());
+        assert!(near(start * (0.0, 0.0), (0.0, 0.0)));
+        assert!(near(start * (1.0, 0.0), (1.0, 0.0)));
+        assert!(near(start * (0.0, 1.0), (0.0, 1.0)));
+        assert!(near(end * (0.0, 0.0), (10.0, 10.0)));
+        assert!(near(end * (1.0, 0.0), (10.0, 11.0)));
+        assert!(near(end * (0.0, 1.0), (9.0, 10.0)));
+
+        // The end direction of the curve is close to its tangent, the repeated end points are skipped.
+        path.cmd = Path::builder()
+            .move_to([0.0, 0.0])
+            .quad_to([10.0, 0.0], [10.0, -10.0])
+            .line_to([10.0, -10.0])
+            .build();
+        path.marker_start = None;
+        let (start, end) = path.marker_placements();
+        assert_eq!(start, None);
+        let end = end.unwrap();
+        assert!(near(end * (0.0, 0.0), (10.0, -10.0)));
+        let (x, y) = end * (1.0, 0.0);
+        assert!((x - 10.0).abs() < 0.2 && (y + 11.0).abs() < 0.05);
+
+        path.cmd = Path::builder().move_to([5.0, 5.0]).line_to([5.0, 5.0]).build();
+        assert_eq!(path.endpoints(), None);
+        assert_eq!(path.marker_placements(), (None, None));
+    }
+
+    #[test]
+    fn flatten_with_invalid_tolerance() {
+        let path = Path {
+            cmd: Path::builder()
+                .move_to([0.0, 0.0])
+                .quad_to([5.0, 10.0], [10.0, 0.0])
+                .build(),
+            ..Path::default()
+        };
+        let expected = path.flatten(Path::MIN_FLATTEN_TOLERANCE);
+        assert!(expected[0].len() > 2);
+        assert_eq!(path.flatten(0.0), expected);
+        assert_eq!(path.flatten(-1.0), expected);
+        assert_eq!(path.flatten(Real::NAN), expected);
+    }
+
+    #[test]
+    fn arc_to_cub_beziers_half_circle() {
+        let segments = arc_to_cub_beziers([0.0, 0.0], [10.0, 10.0], 0.0, false, true, [20.0, 0.0]);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1][2], [20.0, 0.0]);
+
+        let [_, _, top] = segments[0];
+        assert!((top[0] - 10.0).abs() < 1e-4);
+        assert!((top[1] + 10.0).abs() < 1e-4);
+    }
 }
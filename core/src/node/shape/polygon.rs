@@ -0,0 +1,275 @@
+use crate::node::{BoundingBox, Clip, Fill, Real, Stroke, Transform, TransformMatrix, TransformOrigin, UserData};
+
+/// Rule used to determine which regions of self-intersecting or nested contours are inside the shape.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Checks whether the point with the `winding` number is inside the filled region.
+    pub fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Closed polygon made of one or several contours. Additional contours can be used to cut holes,
+/// depending on the `fill_rule`.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
+    pub contours: Vec<Vec<[Real; 2]>>,
+    pub fill_rule: FillRule,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl Polygon {
+    pub const NAME: &'static str = "polygon";
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    /// Returns `(min_x, min_y, max_x, max_y)` of all contour points, or `None` if the polygon is empty.
+    pub fn bounds(&self) -> Option<(Real, Real, Real, Real)> {
+        let mut points = self.contours.iter().flatten();
+        let first = points.next()?;
+        Some(points.fold(
+            (first[0], first[1], first[0], first[1]),
+            |(min_x, min_y, max_x, max_y), point| {
+                (
+                    min_x.min(point[0]),
+                    min_y.min(point[1]),
+                    max_x.max(point[0]),
+                    max_y.max(point[1]),
+                )
+            },
+        ))
+    }
+
+    /// Number of other contours enclosing the first point of the contour with index `idx`.
+    /// Under the even-odd rule, contours with an odd depth are holes.
+    pub fn contour_depth(&self, idx: usize) -> usize {
+        let point = match self.contours.get(idx).and_then(|contour| contour.first()) {
+            Some(point) => *point,
+            None => return 0,
+        };
+        self.contours
+            .iter()
+            .enumerate()
+            .filter(|(other_idx, contour)| *other_idx != idx && winding_number(contour, point[0], point[1]) % 2 != 0)
+            .count()
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        let winding: i32 = self.contours.iter().map(|contour| winding_number(contour, x, y)).sum();
+        self.fill_rule.is_inside(winding)
+    }
+
+    /// Splits the region filled by the `fill_rule`, which is tested by the `intersect`, into the non-overlapping
+    /// trapezoids, so the renders without the fill rule support fill the same region. Each trapezoid is
+    /// `[top_left, top_right, bottom_right, bottom_left]`, it may degenerate into a triangle.
+    pub fn fill_trapezoids(&self) -> Vec<[[Real; 2]; 4]> {
+        let edges: Vec<([Real; 2], [Real; 2])> = self
+            .contours
+            .iter()
+            .flat_map(|contour| {
+                let next = contour.iter().cycle().skip(1);
+                contour.iter().copied().zip(next.copied())
+            })
+            .filter(|(a, b)| a[1] != b[1])
+            .collect();
+
+        // The edges do not cross each other between the neighboring levels.
+        let mut levels: Vec<Real> = edges.iter().flat_map(|(a, b)| [a[1], b[1]]).collect();
+        for (idx, edge) in edges.iter().enumerate() {
+            levels.extend(edges[idx + 1..].iter().filter_map(|other| crossing_y(*edge, *other)));
+        }
+        levels.sort_by(|a, b| a.total_cmp(b));
+        levels.dedup();
+
+        let mut trapezoids = Vec::new();
+        for band in levels.windows(2) {
+            let (top, bottom) = (band[0], band[1]);
+            let mut crossings: Vec<_> = edges
+                .iter()
+                .filter(|(a, b)| a[1].min(b[1]) <= top && a[1].max(b[1]) >= bottom)
+                .map(|&(a, b)| {
+                    let dir = if a[1] < b[1] { 1 } else { -1 };
+                    let mid_x = x_at(a, b, (top + bottom) / 2.0);
+                    (mid_x, x_at(a, b, top), x_at(a, b, bottom), dir)
+                })
+                .collect();
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            let mut left = None;
+            for (_, top_x, bottom_x, dir) in crossings {
+                let was_inside = self.fill_rule.is_inside(winding);
+                winding += dir;
+                match (was_inside, self.fill_rule.is_inside(winding)) {
+                    (false, true) => left = Some((top_x, bottom_x)),
+                    (true, false) => {
+                        if let Some((left_top, left_bottom)) = left.take() {
+                            trapezoids.push([[left_top, top], [top_x, top], [bottom_x, bottom], [left_bottom, bottom]]);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+        trapezoids
+    }
+}
+
+/// The `x` coordinate of the non-horizontal line through the `a` and `b` points at the `y`.
+fn x_at(a: [Real; 2], b: [Real; 2], y: Real) -> Real {
+    a[0] + (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1])
+}
+
+/// The `y` coordinate of the crossing point, which is inside both segments.
+fn crossing_y((a, b): ([Real; 2], [Real; 2]), (c, d): ([Real; 2], [Real; 2])) -> Option<Real> {
+    let (r, s) = ([b[0] - a[0], b[1] - a[1]], [d[0] - c[0], d[1] - c[1]]);
+    let denom = r[0] * s[1] - r[1] * s[0];
+    if denom == 0.0 {
+        return None;
+    }
+    let (qx, qy) = (c[0] - a[0], c[1] - a[1]);
+    let t = (qx * s[1] - qy * s[0]) / denom;
+    let u = (qx * r[1] - qy * r[0]) / denom;
+    (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then(|| a[1] + t * r[1])
+}
+
+/// Signed area of a closed contour. Positive when the points go clockwise in the y-down screen space.
+pub fn signed_area(points: &[[Real; 2]]) -> Real {
+    let mut area = 0.0;
+    for (idx, a) in points.iter().enumerate() {
+        let b = points[(idx + 1) % points.len()];
+        area += a[0] * b[1] - b[0] * a[1];
+    }
+    area / 2.0
+}
+
+/// Winding number of a closed contour around the point `(x, y)`.
+pub fn winding_number(points: &[[Real; 2]], x: Real, y: Real) -> i32 {
+    let mut winding = 0;
+    for (idx, a) in points.iter().enumerate() {
+        let b = points[(idx + 1) % points.len()];
+        let is_left = (b[0] - a[0]) * (y - a[1]) - (x - a[0]) * (b[1] - a[1]);
+        if a[1] <= y {
+            if b[1] > y && is_left > 0.0 {
+                winding += 1;
+            }
+        } else if b[1] <= y && is_left < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: Real, max: Real) -> Vec<[Real; 2]> {
+        vec![[min, min], [max, min], [max, max], [min, max]]
+    }
+
+    #[test]
+    fn fill_rule_intersect() {
+        let mut polygon = Polygon {
+            contours: vec![square(0.0, 10.0), square(3.0, 7.0)],
+            ..Default::default()
+        };
+        assert!(polygon.intersect(1.0, 1.0));
+        assert!(polygon.intersect(5.0, 5.0));
+        assert!(!polygon.intersect(11.0, 5.0));
+        assert_eq!(polygon.contour_depth(0), 0);
+        assert_eq!(polygon.contour_depth(1), 1);
+
+        polygon.fill_rule = FillRule::EvenOdd;
+        assert!(polygon.intersect(1.0, 1.0));
+        assert!(!polygon.intersect(5.0, 5.0));
+
+        let star = vec![[50.0, 0.0], [79.0, 90.0], [2.0, 35.0], [98.0, 35.0], [21.0, 90.0]];
+        let mut polygon = Polygon {
+            contours: vec![star],
+            ..Default::default()
+        };
+        assert!(polygon.intersect(50.0, 50.0));
+        polygon.fill_rule = FillRule::EvenOdd;
+        assert!(!polygon.intersect(50.0, 50.0));
+        assert_eq!(polygon.bounds(), Some((2.0, 0.0, 98.0, 90.0)));
+    }
+
+    #[test]
+    fn fill_trapezoids_match_intersect() {
+        let star = vec![[50.0, 0.0], [79.0, 90.0], [2.0, 35.0], [98.0, 35.0], [21.0, 90.0]];
+        let in_trapezoid = |[top_left, top_right, bottom_right, bottom_left]: [[Real; 2]; 4], x: Real, y: Real| {
+            let k = (y - top_left[1]) / (bottom_left[1] - top_left[1]);
+            let left = top_left[0] + (bottom_left[0] - top_left[0]) * k;
+            let right = top_right[0] + (bottom_right[0] - top_right[0]) * k;
+            y >= top_left[1] && y < bottom_left[1] && x >= left && x < right
+        };
+
+        for contours in [vec![square(0.0, 50.0), square(15.0, 35.0)], vec![star]] {
+            for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                let polygon = Polygon {
+                    contours: contours.clone(),
+                    fill_rule,
+                    ..Default::default()
+                };
+                let trapezoids = polygon.fill_trapezoids();
+                for y in (0..100).map(|y| y as Real + 0.37) {
+                    for x in (0..100).map(|x| x as Real + 0.61) {
+                        let count = trapezoids
+                            .iter()
+                            .filter(|&&trapezoid| in_trapezoid(trapezoid, x, y))
+                            .count();
+                        assert!(count <= 1, "Overlapped trapezoids at ({}, {})", x, y);
+                        assert_eq!(count == 1, polygon.intersect(x, y), "{:?} at ({}, {})", fill_rule, x, y);
+                    }
+                }
+            }
+        }
+    }
+}
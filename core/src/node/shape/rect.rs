@@ -1,19 +1,29 @@
-use crate::{Clip, Fill, Padding, Real, RealValue, Rounding, Stroke, Transform, TransformMatrix};
+use crate::{
+    BoundingBox, Clip, Fill, Margin, Padding, Real, RealValue, Rounding, Stroke, Transform, TransformMatrix,
+    TransformOrigin, UserData,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
     pub x: RealValue,
     pub y: RealValue,
     pub width: RealValue,
     pub height: RealValue,
     pub rounding: Option<Rounding>,
     pub padding: Padding,
+    pub margin: Margin,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
     pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
 }
 
 impl Rect {
@@ -23,11 +33,17 @@ impl Rect {
         self.id.as_ref().map(|s| s.as_str())
     }
 
-    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
-        self.transform.calculate_global(parent_global)
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
     }
 
     #[inline]
@@ -41,6 +57,9 @@ impl Rect {
         } else {
             (x, y)
         };
-        x >= self.x.val() && x <= self.width.val() && y >= self.y.val() && y <= self.height.val()
+        x >= self.x.val()
+            && x <= self.x.val() + self.width.val()
+            && y >= self.y.val()
+            && y <= self.y.val() + self.height.val()
     }
 }
@@ -0,0 +1,123 @@
+use super::sector::{angle_in_range, is_full_turn, point_at_angle, push_arc};
+use crate::node::{
+    BoundingBox, Clip, Fill, PathCommand, Real, RealValue, Stroke, Transform, TransformMatrix, TransformOrigin,
+    UserData,
+};
+
+/// Ring (annulus) between the `inner_r` and `r` radii, from `start_angle` to `end_angle` in radians. Equal angles
+/// mean the full ring.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ring {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
+    pub cx: RealValue,
+    pub cy: RealValue,
+    pub inner_r: RealValue,
+    pub r: RealValue,
+    pub start_angle: Real,
+    pub end_angle: Real,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl Ring {
+    pub const NAME: &'static str = "ring";
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        is_full_turn(self.start_angle, self.end_angle)
+    }
+
+    /// Outline of the ring as path commands. The inner arc goes in the opposite direction, so the full ring is
+    /// drawn as two contours with the hole in the middle.
+    pub fn commands(&self) -> Vec<PathCommand> {
+        let (cx, cy) = (self.cx.val(), self.cy.val());
+        let (inner_r, r) = (self.inner_r.val(), self.r.val());
+        let end_angle = if self.is_full() {
+            self.start_angle + 2.0 * std::f32::consts::PI
+        } else {
+            self.end_angle
+        };
+
+        let mut cmd = vec![PathCommand::Move(point_at_angle(cx, cy, r, self.start_angle))];
+        push_arc(&mut cmd, cx, cy, r, self.start_angle, end_angle);
+        let inner_start = point_at_angle(cx, cy, inner_r, end_angle);
+        if self.is_full() {
+            cmd.push(PathCommand::Close);
+            cmd.push(PathCommand::Move(inner_start));
+        } else {
+            cmd.push(PathCommand::Line(inner_start));
+        }
+        push_arc(&mut cmd, cx, cy, inner_r, end_angle, self.start_angle);
+        cmd.push(PathCommand::Close);
+        cmd
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        let (dx, dy) = (x - self.cx.val(), y - self.cy.val());
+        let distance = (dx * dx + dy * dy).sqrt();
+        distance >= self.inner_r.val()
+            && distance <= self.r.val()
+            && angle_in_range(dy.atan2(dx), self.start_angle, self.end_angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn ring_intersect() {
+        let mut ring = Ring {
+            cx: 50.0.into(),
+            cy: 50.0.into(),
+            inner_r: 20.0.into(),
+            r: 40.0.into(),
+            ..Ring::default()
+        };
+        assert!(ring.intersect(80.0, 50.0));
+        assert!(ring.intersect(50.0, 20.0));
+        assert!(!ring.intersect(50.0, 50.0));
+        assert!(!ring.intersect(95.0, 50.0));
+
+        ring.end_angle = PI / 2.0;
+        assert!(ring.intersect(50.0, 80.0));
+        assert!(!ring.intersect(50.0, 20.0));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::RealValue;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rounding {
     pub top_left: RealValue,
     pub top_right: RealValue,
@@ -0,0 +1,199 @@
+use crate::{
+    controller::MouseScroll,
+    node::{BoundingBox, Group, Real, TransformMatrix},
+};
+
+/// Scrolling state of the group, which makes it the scroll area. The group clip scissor is the viewport, the children
+/// are translated by the negative scroll offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScrollArea {
+    pub offset: (Real, Real),
+    /// Size of the children bound, it is updated by the render on the recalculation.
+    pub content_size: (Real, Real),
+    /// Size of the clip scissor, it is updated by the render on the recalculation.
+    pub viewport_size: (Real, Real),
+    /// Scroll distance of the one mouse wheel line.
+    pub line_step: Real,
+    /// Global bound of the viewport clipped by the scissors of the ancestors, it is updated by the render on the
+    /// recalculation. The nested scroll area is not scrolled by the wheel over its clipped out part.
+    pub clip_bound: Option<BoundingBox>,
+}
+
+impl Default for ScrollArea {
+    fn default() -> Self {
+        Self {
+            offset: (0.0, 0.0),
+            content_size: (0.0, 0.0),
+            viewport_size: (0.0, 0.0),
+            line_step: Self::LINE_STEP,
+            clip_bound: None,
+        }
+    }
+}
+
+impl ScrollArea {
+    pub const LINE_STEP: Real = 20.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn line_step(mut self, step: Real) -> Self {
+        self.line_step = step;
+        self
+    }
+
+    /// The largest offset, at which the content end is at the viewport end.
+    pub fn max_offset(&self) -> (Real, Real) {
+        (
+            (self.content_size.0 - self.viewport_size.0).max(0.0),
+            (self.content_size.1 - self.viewport_size.1).max(0.0),
+        )
+    }
+
+    /// The visible part of the content as the fractions of the content size, e.g. for drawing the scrollbar thumb.
+    pub fn visible_range(&self) -> ((Real, Real), (Real, Real)) {
+        let range = |offset: Real, viewport: Real, content: Real| {
+            if content > viewport {
+                (offset / content, (offset + viewport) / content)
+            } else {
+                (0.0, 1.0)
+            }
+        };
+        (
+            range(self.offset.0, self.viewport_size.0, self.content_size.0),
+            range(self.offset.1, self.viewport_size.1, self.content_size.1),
+        )
+    }
+}
+
+impl Group {
+    /// The clip scissor bound in the local coordinates of the group parent.
+    pub fn viewport(&self) -> Option<BoundingBox> {
+        self.clip.scissor().map(|scissor| BoundingBox {
+            min_x: scissor.x.val(),
+            min_y: scissor.y.val(),
+            max_x: scissor.x.val() + scissor.width.val(),
+            max_y: scissor.y.val() + scissor.height.val(),
+        })
+    }
+
+    /// Scrolls the content to the offset, which is clamped by the content size. Returns true if the offset is changed.
+    pub fn scroll_to(&mut self, x: Real, y: Real) -> bool {
+        let scroll = match &mut self.scroll {
+            Some(scroll) => scroll,
+            None => return false,
+        };
+        let (max_x, max_y) = scroll.max_offset();
+        let offset = (x.max(0.0).min(max_x), y.max(0.0).min(max_y));
+        if offset == scroll.offset {
+            return false;
+        }
+        let (dx, dy) = (offset.0 - scroll.offset.0, offset.1 - scroll.offset.1);
+        scroll.offset = offset;
+        self.transform.translate_add(-dx, -dy);
+        true
+    }
+
+    pub fn scroll_by(&mut self, dx: Real, dy: Real) -> bool {
+        match self.scroll {
+            Some(scroll) => self.scroll_to(scroll.offset.0 + dx, scroll.offset.1 + dy),
+            None => false,
+        }
+    }
+
+    /// Scrolls the content by the mouse wheel lines, if the wheel is over the viewport.
+    pub fn scroll_by_wheel(&mut self, wheel: MouseScroll) -> bool {
+        match self.scroll {
+            Some(scroll) if self.intersect(wheel.pos.x, wheel.pos.y) => {
+                self.scroll_by(-wheel.delta.0 * scroll.line_step, -wheel.delta.1 * scroll.line_step)
+            }
+            _ => false,
+        }
+    }
+
+    /// Updates the content and the viewport sizes of the scroll area. The `inner_bound` is the bound of the children
+    /// without the scroll offset, the content starts at the viewport origin.
+    pub fn set_content_bound(&mut self, inner_bound: BoundingBox) {
+        let viewport = match self.viewport() {
+            Some(viewport) => viewport,
+            None => return,
+        };
+        if let Some(scroll) = &mut self.scroll {
+            scroll.viewport_size = (viewport.width(), viewport.height());
+            scroll.content_size = (
+                (inner_bound.max_x - viewport.min_x).max(0.0),
+                (inner_bound.max_y - viewport.min_y).max(0.0),
+            );
+            let (x, y) = scroll.offset;
+            self.scroll_to(x, y);
+        }
+    }
+
+    /// Checks that the point is in the viewport of the scroll area, the other groups are not hit. The part of the
+    /// viewport, which is clipped out by the ancestors, is not hit too.
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let viewport = match self.viewport() {
+            Some(viewport) if self.scroll.is_some() => viewport,
+            _ => return false,
+        };
+        let clip_bound = self.scroll.and_then(|scroll| scroll.clip_bound);
+        if matches!(clip_bound, Some(clip_bound) if !clip_bound.contains_point(x, y)) {
+            return false;
+        }
+        let matrix = self
+            .clip
+            .transform()
+            .and_then(|transform| transform.global_matrix())
+            .unwrap_or_else(TransformMatrix::identity);
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        x >= viewport.min_x && x <= viewport.max_x && y >= viewport.min_y && y <= viewport.max_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        controller::{Modifiers, MousePos},
+        Clip,
+    };
+
+    #[test]
+    fn scroll_content() {
+        let mut group = Group {
+            clip: Clip::new_scissor(0.into(), 0.into(), 100.into(), 50.into()),
+            scroll: Some(ScrollArea::new()),
+            ..Default::default()
+        };
+        group.set_content_bound(BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 100.0,
+            max_y: 200.0,
+        });
+        assert_eq!(group.scroll.unwrap().max_offset(), (0.0, 150.0));
+
+        let wheel = |y| MouseScroll {
+            pos: MousePos { x: 10.0, y: 10.0 },
+            local_pos: MousePos { x: 10.0, y: 10.0 },
+            delta: (0.0, y),
+            modifiers: Modifiers::NONE,
+        };
+        assert!(group.scroll_by_wheel(wheel(-2.0)));
+        assert_eq!(group.scroll.unwrap().offset, (0.0, 40.0));
+        assert_eq!(group.transform.matrix().translate_xy(), (0.0, -40.0));
+        assert_eq!(group.scroll.unwrap().visible_range().1, (0.2, 0.45));
+
+        assert!(group.scroll_by(0.0, 1000.0));
+        assert_eq!(group.scroll.unwrap().offset, (0.0, 150.0));
+        assert!(!group.scroll_by_wheel(wheel(-1.0)));
+        assert!(group.scroll_by_wheel(wheel(10.0)));
+        assert_eq!(group.transform.matrix().translate_xy(), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,153 @@
+use std::f32::consts::PI;
+
+use crate::node::{
+    BoundingBox, Clip, Fill, PathCommand, Real, RealValue, Stroke, Transform, TransformMatrix, TransformOrigin,
+    UserData,
+};
+
+/// Circular sector (pie slice) from `start_angle` to `end_angle` in radians. Equal angles mean the full circle.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sector {
+    pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
+    pub cx: RealValue,
+    pub cy: RealValue,
+    pub r: RealValue,
+    pub start_angle: Real,
+    pub end_angle: Real,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
+}
+
+impl Sector {
+    pub const NAME: &'static str = "sector";
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        is_full_turn(self.start_angle, self.end_angle)
+    }
+
+    /// Outline of the sector as path commands.
+    pub fn commands(&self) -> Vec<PathCommand> {
+        let (cx, cy, r) = (self.cx.val(), self.cy.val(), self.r.val());
+        let start = point_at_angle(cx, cy, r, self.start_angle);
+        let mut cmd = Vec::new();
+        if self.is_full() {
+            cmd.push(PathCommand::Move(start));
+        } else {
+            cmd.push(PathCommand::Move([cx, cy]));
+            cmd.push(PathCommand::Line(start));
+        }
+        push_arc(&mut cmd, cx, cy, r, self.start_angle, self.end_angle);
+        cmd.push(PathCommand::Close);
+        cmd
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        let (x, y) = if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        };
+        let (dx, dy) = (x - self.cx.val(), y - self.cy.val());
+        (dx * dx + dy * dy).sqrt() <= self.r.val() && angle_in_range(dy.atan2(dx), self.start_angle, self.end_angle)
+    }
+}
+
+pub(crate) fn is_full_turn(start_angle: Real, end_angle: Real) -> bool {
+    start_angle == end_angle || (end_angle - start_angle).abs() >= 2.0 * PI
+}
+
+pub(crate) fn angle_in_range(angle: Real, start_angle: Real, end_angle: Real) -> bool {
+    if is_full_turn(start_angle, end_angle) {
+        return true;
+    }
+    let (from, sweep) = if end_angle > start_angle {
+        (start_angle, end_angle - start_angle)
+    } else {
+        (end_angle, start_angle - end_angle)
+    };
+    (angle - from).rem_euclid(2.0 * PI) <= sweep
+}
+
+pub(crate) fn point_at_angle(cx: Real, cy: Real, r: Real, angle: Real) -> [Real; 2] {
+    [cx + r * angle.cos(), cy + r * angle.sin()]
+}
+
+/// Pushes the arc commands from the current point at `start_angle` to `end_angle`. The arc is split into parts of
+/// at most a half turn, so that the full circle can be drawn too.
+pub(crate) fn push_arc(cmd: &mut Vec<PathCommand>, cx: Real, cy: Real, r: Real, start_angle: Real, end_angle: Real) {
+    let sweep_angle = if start_angle == end_angle {
+        2.0 * PI
+    } else {
+        end_angle - start_angle
+    };
+    let parts = (sweep_angle.abs() / PI).ceil().max(1.0) as usize;
+    for idx in 1..=parts {
+        let angle = start_angle + sweep_angle * idx as Real / parts as Real;
+        cmd.push(PathCommand::Arc {
+            radius: [r, r],
+            rotation: 0.0,
+            large_arc: false,
+            sweep: sweep_angle > 0.0,
+            to: point_at_angle(cx, cy, r, angle),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sector_intersect() {
+        let mut sector = Sector {
+            cx: 50.0.into(),
+            cy: 50.0.into(),
+            r: 40.0.into(),
+            ..Sector::default()
+        };
+        assert!(sector.intersect(50.0, 50.0));
+        assert!(sector.intersect(20.0, 50.0));
+        assert!(!sector.intersect(95.0, 50.0));
+
+        sector.end_angle = PI / 2.0;
+        assert!(sector.intersect(70.0, 70.0));
+        assert!(!sector.intersect(30.0, 70.0));
+        assert!(!sector.intersect(70.0, 30.0));
+
+        sector.start_angle = PI;
+        sector.end_angle = 3.0 * PI / 2.0;
+        assert!(sector.intersect(30.0, 30.0));
+        assert!(!sector.intersect(70.0, 70.0));
+    }
+}
@@ -1,6 +1,10 @@
-use crate::node::{Clip, ConvertTo, Fill, Real, RealValue, Stroke, Transform, TransformMatrix};
+use crate::node::{
+    path::point_at_length, BoundingBox, Clip, CompositeShape, ConvertTo, Fill, Path, PathCommand, Real, RealValue,
+    Shape, Shaped, Stroke, TextInput, Transform, TransformMatrix, TransformOrigin, UserData,
+};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlyphPos {
     pub x: Real,
     pub y: Real,
@@ -15,6 +19,7 @@ impl GlyphPos {
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextMetrics {
     pub ascender: f32,
     pub descender: f32,
@@ -22,41 +27,179 @@ pub struct TextMetrics {
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
     pub id: Option<String>,
+    pub style: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub user_data: Option<UserData>,
     pub content: String,
+    /// Inline spans with own styling, which are drawn instead of the content if set.
+    pub spans: Vec<TextSpan>,
+    /// Editing state, if the text is editable.
+    pub input: Option<TextInput>,
+    /// Path along which the glyphs are laid out instead of the straight line.
+    pub path: Option<Box<TextPath>>,
     pub glyph_positions: Vec<GlyphPos>,
     pub metrics: Option<TextMetrics>,
     pub x: RealValue,
     pub y: RealValue,
     pub font_name: String,
     pub font_size: RealValue,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+    pub decoration: TextDecoration,
     pub align: (AlignHor, AlignVer),
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
     pub transform: Transform,
+    pub transform_origin: Option<TransformOrigin>,
+    pub bound: BoundingBox,
 }
 
 impl Text {
     pub const NAME: &'static str = "text";
+    /// Font size that the em values are relative to outside of any text or style with the font size.
+    pub const DEFAULT_FONT_SIZE: Real = 16.0;
 
     pub fn id(&self) -> Option<&str> {
         self.id.as_ref().map(|s| s.as_str())
     }
 
-    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix, bound: &BoundingBox) -> TransformMatrix {
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
-        self.transform.calculate_global(parent_global)
+        match self.transform_origin {
+            Some(origin) => {
+                let origin = origin.resolve(bound);
+                self.transform.calculate_global_around(parent_global, origin)
+            }
+            None => self.transform.calculate_global(parent_global),
+        }
     }
 
     #[inline]
-    pub fn intersect(&self, _x: Real, _y: Real) -> bool {
-        // TODO: calvulate intersect
-        false
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let (x, y) = self.local_point(x, y);
+        let width = self
+            .glyph_positions
+            .last()
+            .map(|pos| pos.max_x())
+            .unwrap_or(0.0)
+            .max(self.input.as_ref().map(|input| input.min_width).unwrap_or(0.0));
+        let height = self.metrics.map(|metrics| metrics.line_height).unwrap_or(0.0);
+        x >= self.x.val() && x <= self.x.val() + width && y >= self.y.val() && y <= self.y.val() + height
+    }
+
+    /// Converts the point from the window coordinates to the local coordinates of the text.
+    pub fn local_point(&self, x: Real, y: Real) -> (Real, Real) {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Font size of the span, inherited from the text if not set.
+    pub fn span_font_size(&self, span: &TextSpan) -> Real {
+        span.font_size.unwrap_or(self.font_size).val()
+    }
+
+    /// Places the spans in a row by their measured widths, the row is aligned horizontally as the whole text.
+    pub fn arrange_spans(&mut self, widths: impl IntoIterator<Item = Real>) {
+        let mut offset = 0.0;
+        for (span, width) in self.spans.iter_mut().zip(widths) {
+            span.offset = offset;
+            span.width = width;
+            offset += width;
+        }
+
+        let shift = match self.align.0 {
+            AlignHor::Left => 0.0,
+            AlignHor::Center => -offset / 2.0,
+            AlignHor::Right => -offset,
+        };
+        for span in &mut self.spans {
+            span.offset += shift;
+        }
+    }
+
+    /// The `y` coordinate of the baseline in the local coordinates, it is known after the recalculation only.
+    pub fn baseline(&self) -> Option<Real> {
+        self.metrics.map(|metrics| self.baseline_y(metrics))
+    }
+
+    /// Rects of the decoration lines in the local coordinates. The lines are placed by the text metrics, so they
+    /// are known after the recalculation only.
+    pub fn decoration_lines(&self) -> Vec<BoundingBox> {
+        let metrics = match self.metrics {
+            Some(metrics) if !self.decoration.is_none() => metrics,
+            _ => return Vec::new(),
+        };
+        let (ascender, descender) = (metrics.ascender.abs(), metrics.descender.abs());
+        let baseline = self.baseline_y(metrics);
+        let thickness = (self.font_size.val() * TextDecoration::THICKNESS).max(1.0);
+        let (min_x, max_x) = (self.caret_x(0), self.caret_x(self.glyph_positions.len()));
+        let line = |y: Real| BoundingBox {
+            min_x,
+            min_y: y - thickness / 2.0,
+            max_x,
+            max_y: y + thickness / 2.0,
+        };
+
+        let mut lines = Vec::new();
+        if self.decoration.underline {
+            lines.push(line(baseline + descender / 2.0));
+        }
+        if self.decoration.line_through {
+            lines.push(line(baseline - ascender * TextDecoration::LINE_THROUGH_HEIGHT));
+        }
+        lines
+    }
+
+    fn baseline_y(&self, metrics: TextMetrics) -> Real {
+        // The renders differ in the sign of the descender.
+        let (ascender, descender) = (metrics.ascender.abs(), metrics.descender.abs());
+        self.y.val()
+            + match self.align.1 {
+                AlignVer::Top => ascender,
+                AlignVer::Middle => (ascender - descender) / 2.0,
+                AlignVer::Bottom => -descender,
+                AlignVer::Baseline => 0.0,
+            }
+    }
+
+    /// Calculates the glyph transforms of the text on the path by the glyph positions. Each glyph is rotated along
+    /// the path around its center on the text `y` position.
+    pub fn layout_on_path(&mut self) {
+        let start_x = self.caret_x(0);
+        let y = self.y.val();
+        let text_path = match &mut self.path {
+            Some(text_path) => text_path,
+            None => return,
+        };
+
+        let polylines = text_path.path.flatten(Path::FLATTEN_TOLERANCE);
+        text_path.glyph_transforms = self
+            .glyph_positions
+            .iter()
+            .map(|pos| {
+                let center_x = pos.x + pos.width / 2.0;
+                point_at_length(&polylines, text_path.start_offset + center_x - start_x).map(|(point, angle)| {
+                    TransformMatrix::identity()
+                        .with_rotation(angle)
+                        .with_translation(point[0], point[1])
+                        * TransformMatrix::identity().with_translation(-center_x, -y)
+                })
+            })
+            .collect();
     }
 
     pub fn insert(&mut self, idx: usize, ch: char) {
@@ -81,7 +224,188 @@ impl Text {
     }
 }
 
+/// Styled part of the text, the unset properties are taken from the text. The span position is calculated by the
+/// renderer.
+/// Aligns the text children of the composite on the common baseline, which is the lowest of their baselines. The
+/// texts are moved down by changing `y`, so it is done after the children recalculation, when the metrics are known.
+/// The `child_bounds` are the recalculated bounds of the children, they are moved along with the texts.
+pub fn align_text_baselines(composite: &mut dyn CompositeShape, child_bounds: &mut [BoundingBox]) {
+    let baseline = match composite.children() {
+        Some(children) => children
+            .filter_map(|child| child.shape()?.text()?.baseline())
+            .fold(None, |lowest: Option<Real>, baseline| {
+                Some(lowest.unwrap_or(baseline).max(baseline))
+            }),
+        None => return,
+    };
+    let (baseline, children) = match (baseline, composite.children_mut()) {
+        (Some(baseline), Some(children)) => (baseline, children),
+        _ => return,
+    };
+    for (child, child_bound) in children.zip(child_bounds) {
+        if let Some(Shape::Text(text)) = child.shape_mut() {
+            let shift = baseline - text.baseline().unwrap_or(baseline);
+            text.y.set_val(text.y.val() + shift);
+            for bound in [&mut text.bound, child_bound].iter_mut() {
+                bound.min_y += shift;
+                bound.max_y += shift;
+            }
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextSpan {
+    pub content: String,
+    pub font_name: Option<String>,
+    pub font_size: Option<RealValue>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub fill: Option<Fill>,
+    /// Horizontal offset of the span from the text position.
+    pub offset: Real,
+    pub width: Real,
+}
+
+impl TextSpan {
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn font_name(mut self, name: impl Into<String>) -> Self {
+        self.font_name = Some(name.into());
+        self
+    }
+
+    pub fn font_size(mut self, size: impl Into<RealValue>) -> Self {
+        self.font_size = Some(size.into());
+        self
+    }
+
+    pub fn font_weight(mut self, weight: FontWeight) -> Self {
+        self.font_weight = Some(weight);
+        self
+    }
+
+    pub fn font_style(mut self, style: FontStyle) -> Self {
+        self.font_style = Some(style);
+        self
+    }
+
+    pub fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+}
+
+/// Path of the text like the SVG `textPath`. The glyphs beyond the path end are not drawn, the spans are not
+/// supported on the path.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextPath {
+    pub path: Path,
+    /// Distance of the text start from the path start.
+    pub start_offset: Real,
+    /// Local transforms of the glyphs onto the path, they are calculated by the renderer.
+    pub glyph_transforms: Vec<Option<TransformMatrix>>,
+}
+
+impl TextPath {
+    pub fn new(cmd: impl Into<Vec<PathCommand>>) -> Self {
+        Self {
+            path: Path {
+                cmd: cmd.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn start_offset(mut self, offset: Real) -> Self {
+        self.start_offset = offset;
+        self
+    }
+}
+
+/// Lines drawn with the text fill, like the CSS `text-decoration`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub line_through: bool,
+}
+
+impl TextDecoration {
+    pub const NONE: TextDecoration = TextDecoration {
+        underline: false,
+        line_through: false,
+    };
+    pub const UNDERLINE: TextDecoration = TextDecoration {
+        underline: true,
+        line_through: false,
+    };
+    pub const LINE_THROUGH: TextDecoration = TextDecoration {
+        underline: false,
+        line_through: true,
+    };
+
+    /// Line thickness relative to the font size.
+    const THICKNESS: Real = 1.0 / 16.0;
+    /// Height of the line through above the baseline relative to the ascender, about a half of the x-height.
+    const LINE_THROUGH_HEIGHT: Real = 0.35;
+
+    pub fn is_none(&self) -> bool {
+        !self.underline && !self.line_through
+    }
+}
+
+/// Font weight in the CSS scale from 100 to 900.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const THIN: FontWeight = FontWeight(100);
+    pub const EXTRA_LIGHT: FontWeight = FontWeight(200);
+    pub const LIGHT: FontWeight = FontWeight(300);
+    pub const NORMAL: FontWeight = FontWeight(400);
+    pub const MEDIUM: FontWeight = FontWeight(500);
+    pub const SEMI_BOLD: FontWeight = FontWeight(600);
+    pub const BOLD: FontWeight = FontWeight(700);
+    pub const EXTRA_BOLD: FontWeight = FontWeight(800);
+    pub const BLACK: FontWeight = FontWeight(900);
+
+    pub fn is_bold(&self) -> bool {
+        *self >= Self::SEMI_BOLD
+    }
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::NORMAL
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl Default for FontStyle {
+    fn default() -> Self {
+        FontStyle::Normal
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignHor {
     Left,
     Right,
@@ -95,6 +419,7 @@ impl Default for AlignHor {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignVer {
     Bottom,
     Middle,
@@ -131,3 +456,149 @@ impl ConvertTo<(AlignHor, AlignVer)> for AlignVer {
         self.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompositeShapeIter, CompositeShapeIterMut, Em, Group};
+
+    #[test]
+    fn arrange_spans() {
+        let mut text = Text {
+            spans: vec![TextSpan::new("bold"), TextSpan::new(" price")],
+            ..Default::default()
+        };
+        text.arrange_spans(vec![40.0, 60.0]);
+        assert_eq!((text.spans[0].offset, text.spans[0].width), (0.0, 40.0));
+        assert_eq!((text.spans[1].offset, text.spans[1].width), (40.0, 60.0));
+
+        text.align.0 = AlignHor::Center;
+        text.arrange_spans(vec![40.0, 60.0]);
+        assert_eq!(text.spans[0].offset, -50.0);
+        assert_eq!(text.spans[1].offset, -10.0);
+    }
+
+    #[test]
+    fn decoration_lines() {
+        let mut text = Text {
+            font_size: 16.0.into(),
+            glyph_positions: vec![GlyphPos {
+                x: 0.0,
+                y: 0.0,
+                width: 10.0,
+            }],
+            metrics: Some(TextMetrics {
+                ascender: 12.0,
+                descender: -4.0,
+                line_height: 16.0,
+            }),
+            ..Default::default()
+        };
+        assert!(text.decoration_lines().is_empty());
+
+        text.decoration = TextDecoration::UNDERLINE;
+        assert_eq!(text.decoration_lines(), vec![BoundingBox {
+            min_x: 0.0,
+            min_y: 13.5,
+            max_x: 10.0,
+            max_y: 14.5,
+        }]);
+    }
+
+    #[test]
+    fn layout_on_path() {
+        let mut text = Text {
+            glyph_positions: vec![
+                GlyphPos {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 10.0,
+                },
+                GlyphPos {
+                    x: 10.0,
+                    y: 0.0,
+                    width: 10.0,
+                },
+            ],
+            path: Some(Box::new(
+                TextPath::new(Path::builder().move_to([0.0, 0.0]).line_to([0.0, 20.0])).start_offset(10.0),
+            )),
+            ..Default::default()
+        };
+        text.layout_on_path();
+
+        let glyph_transforms = &text.path.as_ref().unwrap().glyph_transforms;
+        let (x, y) = glyph_transforms[0].unwrap() * (5.0, 0.0);
+        assert!(x.abs() < 1e-5 && (y - 15.0).abs() < 1e-5);
+        assert_eq!(glyph_transforms[1], None);
+    }
+
+    #[test]
+    fn resolve_em() {
+        let mut shape = Shape::Text(Text {
+            x: Em(2).into(),
+            font_size: Em(1.5).into(),
+            spans: vec![TextSpan::new("small").font_size(Em(0.5))],
+            ..Default::default()
+        });
+        assert_eq!(shape.set_by_em(16.0), 24.0);
+
+        let text = shape.text().unwrap();
+        assert_eq!(text.x.val(), 48.0);
+        assert_eq!(text.spans[0].font_size.map(|size| size.val()), Some(12.0));
+        assert_eq!(Shape::Text(Text::default()).set_by_em(16.0), 16.0);
+    }
+
+    #[test]
+    fn align_baselines() {
+        struct Row(Vec<Shape>);
+
+        impl CompositeShape for Row {
+            fn shape(&self) -> Option<&Shape> {
+                None
+            }
+
+            fn shape_mut(&mut self) -> Option<&mut Shape> {
+                None
+            }
+
+            fn children(&self) -> Option<CompositeShapeIter> {
+                Some(Box::new(self.0.iter().map(|shape| shape as &dyn CompositeShape)))
+            }
+
+            fn children_mut(&mut self) -> Option<CompositeShapeIterMut> {
+                Some(Box::new(
+                    self.0.iter_mut().map(|shape| shape as &mut dyn CompositeShape),
+                ))
+            }
+
+            fn need_recalc(&self) -> Option<bool> {
+                None
+            }
+
+            fn need_redraw(&self) -> Option<bool> {
+                None
+            }
+        }
+
+        let text = |font_size: Real| {
+            Shape::Text(Text {
+                metrics: Some(TextMetrics {
+                    ascender: font_size * 0.8,
+                    descender: -font_size * 0.2,
+                    line_height: font_size,
+                }),
+                ..Default::default()
+            })
+        };
+        let mut row = Row(vec![text(10.0), text(20.0), Shape::Group(Group::default())]);
+        let mut bounds = vec![BoundingBox::default(); 3];
+        align_text_baselines(&mut row, &mut bounds);
+
+        let texts: Vec<_> = row.0.iter().filter_map(|shape| shape.text()).collect();
+        assert_eq!((texts[0].y.val(), texts[1].y.val()), (8.0, 0.0));
+        assert_eq!(texts[0].baseline(), texts[1].baseline());
+        assert_eq!(bounds[0].min_y, 8.0);
+        assert_eq!(bounds[2].min_y, 0.0);
+    }
+}
@@ -0,0 +1,398 @@
+use std::{ops::Range, time::Duration};
+
+use crate::{
+    controller::{Clipboard, InputEvent, KeyboardEvent, MouseButton, VirtualKeyCode},
+    node::{Color, Real, Text},
+};
+
+/// Editing state of the text, which makes it the text input. The caret and the selection ends are the char indices
+/// in the text content.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextInput {
+    pub caret: usize,
+    /// The other end of the selection, there is no selection if it is equal to the caret.
+    pub anchor: usize,
+    pub focused: bool,
+    pub caret_visible: bool,
+    pub caret_color: Color,
+    pub selection_color: Color,
+    pub blink_period: Duration,
+    /// Minimum width of the input area, so that the empty input can be focused by the mouse.
+    pub min_width: Real,
+    blink_elapsed: Duration,
+    selecting: bool,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self {
+            caret: 0,
+            anchor: 0,
+            focused: false,
+            caret_visible: true,
+            caret_color: Color::Black,
+            selection_color: Color::RGBA(0.2, 0.5, 1.0, 0.4),
+            blink_period: Self::BLINK_PERIOD,
+            min_width: 0.0,
+            blink_elapsed: Duration::default(),
+            selecting: false,
+        }
+    }
+}
+
+impl TextInput {
+    pub const BLINK_PERIOD: Duration = Duration::from_millis(500);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn caret_color(mut self, color: impl Into<Color>) -> Self {
+        self.caret_color = color.into();
+        self
+    }
+
+    pub fn selection_color(mut self, color: impl Into<Color>) -> Self {
+        self.selection_color = color.into();
+        self
+    }
+
+    pub fn min_width(mut self, width: Real) -> Self {
+        self.min_width = width;
+        self
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        if self.caret == self.anchor {
+            None
+        } else {
+            Some(self.caret.min(self.anchor)..self.caret.max(self.anchor))
+        }
+    }
+
+    /// Moves the caret, the selection is extended to the new position if `select` is true.
+    pub fn set_caret(&mut self, idx: usize, select: bool) {
+        self.caret = idx;
+        if !select {
+            self.anchor = idx;
+        }
+        self.show_caret();
+    }
+
+    /// Advances the caret blinking of the focused input. Returns true if the caret visibility is changed.
+    pub fn blink(&mut self, elapsed: Duration) -> bool {
+        if !self.focused {
+            return false;
+        }
+        self.blink_elapsed += elapsed;
+        if self.blink_elapsed >= self.blink_period {
+            self.blink_elapsed = Duration::default();
+            self.caret_visible = !self.caret_visible;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Time until the next caret blink of the focused input.
+    pub fn next_blink(&self) -> Option<Duration> {
+        self.focused
+            .then(|| self.blink_period.saturating_sub(self.blink_elapsed))
+    }
+
+    /// Takes the editing state of the previous input, e.g. when the view is rebuilt. The caret is limited by the new
+    /// text length.
+    pub fn keep_state(&mut self, old: &TextInput, len: usize) {
+        self.caret = old.caret.min(len);
+        self.anchor = old.anchor.min(len);
+        self.focused = old.focused;
+        self.caret_visible = old.caret_visible;
+        self.blink_elapsed = old.blink_elapsed;
+        self.selecting = old.selecting;
+    }
+
+    fn show_caret(&mut self) {
+        self.caret_visible = true;
+        self.blink_elapsed = Duration::default();
+    }
+}
+
+impl Text {
+    pub fn char_count(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// The nearest caret position to the `x` coordinate, which is given in the local coordinates of the text.
+    pub fn caret_idx_at(&self, x: Real) -> usize {
+        self.glyph_positions
+            .iter()
+            .position(|pos| x < pos.x + pos.width / 2.0)
+            .unwrap_or(self.glyph_positions.len())
+    }
+
+    /// The `x` coordinate of the caret before the char with the `idx` index.
+    pub fn caret_x(&self, idx: usize) -> Real {
+        match idx.min(self.glyph_positions.len()).checked_sub(1) {
+            Some(idx) => self.glyph_positions[idx].max_x(),
+            None => self.glyph_positions.first().map(|pos| pos.x).unwrap_or(self.x.val()),
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        let range = self.input.as_ref()?.selection()?;
+        Some(&self.content[self.byte_idx(range.start)..self.byte_idx(range.end)])
+    }
+
+    /// Replaces the selection or inserts the char at the caret. The control chars are ignored.
+    pub fn input_char(&mut self, ch: char) -> bool {
+        if ch.is_control() {
+            return false;
+        }
+        self.replace_selection(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Replaces the selected text with the `text`, or inserts it at the caret if there is no selection.
+    pub fn replace_selection(&mut self, text: &str) -> bool {
+        let range = match &self.input {
+            Some(input) => input.selection().unwrap_or(input.caret..input.caret),
+            None => return false,
+        };
+        let byte_range = self.byte_idx(range.start)..self.byte_idx(range.end);
+        self.content.replace_range(byte_range, text);
+        if let Some(input) = &mut self.input {
+            input.set_caret(range.start + text.chars().count(), false);
+        }
+        true
+    }
+
+    /// Copies the selected text to the clipboard. Returns true if there is the selection.
+    pub fn copy_selection(&mut self) -> bool {
+        match self.selected_text() {
+            Some(text) => {
+                Clipboard::set_text(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the selection with the clipboard text.
+    pub fn paste(&mut self) -> bool {
+        match Clipboard::get_text() {
+            Some(text) => self.replace_selection(&text),
+            None => false,
+        }
+    }
+
+    /// Deletes the selection or the char before the caret.
+    pub fn delete_backward(&mut self) -> bool {
+        self.delete(|caret, _| caret.checked_sub(1).map(|start| start..caret))
+    }
+
+    /// Deletes the selection or the char after the caret.
+    pub fn delete_forward(&mut self) -> bool {
+        self.delete(|caret, len| if caret < len { Some(caret..caret + 1) } else { None })
+    }
+
+    /// Applies the key press to the focused input. Returns true if the input is changed.
+    pub fn handle_key(&mut self, event: KeyboardEvent) -> bool {
+        let len = self.char_count();
+        let input = match &mut self.input {
+            Some(input) if input.focused => input,
+            _ => return false,
+        };
+        let select = event.modifiers.shift;
+        let keycode = match event.keycode {
+            Some(VirtualKeyCode::C) if event.modifiers.command() => Some(VirtualKeyCode::Copy),
+            Some(VirtualKeyCode::X) if event.modifiers.command() => Some(VirtualKeyCode::Cut),
+            Some(VirtualKeyCode::V) if event.modifiers.command() => Some(VirtualKeyCode::Paste),
+            keycode => keycode,
+        };
+        let caret = match keycode {
+            Some(VirtualKeyCode::Left) => match input.selection() {
+                Some(range) if !select => range.start,
+                _ => input.caret.saturating_sub(1),
+            },
+            Some(VirtualKeyCode::Right) => match input.selection() {
+                Some(range) if !select => range.end,
+                _ => (input.caret + 1).min(len),
+            },
+            Some(VirtualKeyCode::Home) => 0,
+            Some(VirtualKeyCode::End) => len,
+            Some(VirtualKeyCode::Backspace) => return self.delete_backward(),
+            Some(VirtualKeyCode::Delete) => return self.delete_forward(),
+            Some(VirtualKeyCode::Copy) => {
+                self.copy_selection();
+                return false;
+            }
+            Some(VirtualKeyCode::Cut) => return self.copy_selection() && self.delete_backward(),
+            Some(VirtualKeyCode::Paste) => return self.paste(),
+            _ => return false,
+        };
+        input.set_caret(caret, select);
+        true
+    }
+
+    /// Updates the input state by the input event: the mouse focuses the input, places the caret and selects the
+    /// text by dragging, the keys and the chars edit the focused input. Returns true if the input is changed.
+    pub fn handle_input(&mut self, event: InputEvent) -> bool {
+        if self.input.is_none() {
+            return false;
+        }
+
+        match event {
+            InputEvent::MouseDown(press) if press.button == MouseButton::Left => {
+                let hit = self.intersect(press.pos.x, press.pos.y);
+                let idx = self.caret_idx_at(self.local_point(press.pos.x, press.pos.y).0);
+                let input = self.input.as_mut().expect("Text input expected");
+                if hit {
+                    let select = input.focused && press.modifiers.shift;
+                    input.focused = true;
+                    input.selecting = true;
+                    input.set_caret(idx, select);
+                    true
+                } else if input.focused {
+                    input.focused = false;
+                    input.anchor = input.caret;
+                    true
+                } else {
+                    false
+                }
+            }
+            InputEvent::MouseMove(pos) => {
+                let idx = self.caret_idx_at(self.local_point(pos.x, pos.y).0);
+                let input = self.input.as_mut().expect("Text input expected");
+                if input.selecting && input.caret != idx {
+                    input.set_caret(idx, true);
+                    true
+                } else {
+                    false
+                }
+            }
+            InputEvent::MouseUp(release) if release.button == MouseButton::Left => {
+                self.input.as_mut().expect("Text input expected").selecting = false;
+                false
+            }
+            InputEvent::KeyDown(event) => self.handle_key(event),
+            InputEvent::Char(ch) if matches!(&self.input, Some(input) if input.focused) => self.input_char(ch),
+            _ => false,
+        }
+    }
+
+    fn delete(&mut self, range_at_caret: impl FnOnce(usize, usize) -> Option<Range<usize>>) -> bool {
+        let len = self.char_count();
+        let range = match &self.input {
+            Some(input) => match input.selection().or_else(|| range_at_caret(input.caret, len)) {
+                Some(range) => range,
+                None => return false,
+            },
+            None => return false,
+        };
+        let byte_range = self.byte_idx(range.start)..self.byte_idx(range.end);
+        self.content.replace_range(byte_range, "");
+        if let Some(input) = &mut self.input {
+            input.set_caret(range.start, false);
+        }
+        true
+    }
+
+    fn byte_idx(&self, idx: usize) -> usize {
+        self.content
+            .char_indices()
+            .nth(idx)
+            .map(|(byte_idx, _)| byte_idx)
+            .unwrap_or_else(|| self.content.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::Modifiers;
+
+    fn key(keycode: VirtualKeyCode) -> KeyboardEvent {
+        key_with(keycode, Modifiers::NONE)
+    }
+
+    fn key_with(keycode: VirtualKeyCode, modifiers: Modifiers) -> KeyboardEvent {
+        KeyboardEvent {
+            scancode: 0,
+            keycode: Some(keycode),
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn edit_with_keys_and_chars() {
+        let mut text = Text {
+            content: "hello".to_string(),
+            input: Some(TextInput {
+                caret: 5,
+                anchor: 5,
+                focused: true,
+                ..TextInput::default()
+            }),
+            ..Text::default()
+        };
+
+        assert!(text.input_char('!'));
+        assert!(text.handle_key(key(VirtualKeyCode::Home)));
+        assert!(text.input_char('>'));
+        assert!(!text.input_char('\u{8}'));
+        assert_eq!(text.content, ">hello!");
+
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::NONE
+        };
+        text.handle_key(key_with(VirtualKeyCode::Right, shift));
+        text.handle_key(key_with(VirtualKeyCode::Right, shift));
+        assert_eq!(text.selected_text(), Some("he"));
+
+        assert!(text.handle_key(key(VirtualKeyCode::Backspace)));
+        assert_eq!(text.content, ">llo!");
+        assert!(text.handle_key(key(VirtualKeyCode::Delete)));
+        assert_eq!(text.content, ">lo!");
+        assert_eq!(text.input.as_ref().map(|input| input.caret), Some(1));
+
+        text.handle_key(key_with(VirtualKeyCode::End, shift));
+        assert!(text.handle_key(key(VirtualKeyCode::Cut)));
+        assert_eq!(text.content, ">");
+        text.handle_key(key(VirtualKeyCode::Home));
+        assert!(text.handle_key(key(VirtualKeyCode::Paste)));
+        assert_eq!(text.content, "lo!>");
+    }
+
+    #[test]
+    fn edit_with_shortcuts() {
+        let mut text = Text {
+            content: "abc".to_string(),
+            input: Some(TextInput {
+                caret: 0,
+                anchor: 2,
+                focused: true,
+                ..TextInput::default()
+            }),
+            ..Text::default()
+        };
+        let command = if cfg!(target_os = "macos") {
+            Modifiers {
+                logo: true,
+                ..Modifiers::NONE
+            }
+        } else {
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::NONE
+            }
+        };
+
+        assert!(!text.handle_key(key(VirtualKeyCode::X)));
+        assert!(text.handle_key(key_with(VirtualKeyCode::X, command)));
+        assert_eq!(text.content, "c");
+        text.handle_key(key(VirtualKeyCode::End));
+        assert!(text.handle_key(key_with(VirtualKeyCode::V, command)));
+        assert_eq!(text.content, "cab");
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+/// Application value attached to the shape, e.g. the row index or the entity id. The clones share the value, so
+/// the user data are equal only if they refer to the same value.
+#[derive(Clone)]
+pub struct UserData(Rc<dyn Any>);
+
+impl UserData {
+    pub fn new(data: impl Any) -> Self {
+        Self(Rc::new(data))
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UserData(..)")
+    }
+}
+
+impl PartialEq for UserData {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downcast_and_eq() {
+        let data = UserData::new(42_usize);
+        assert!(data.is::<usize>());
+        assert_eq!(data.downcast_ref::<usize>(), Some(&42));
+        assert_eq!(data.downcast_ref::<u32>(), None);
+
+        assert_eq!(data.clone(), data);
+        assert_ne!(UserData::new(42_usize), data);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::HashMap, iter};
+
+use crate::{Fill, Real, Stroke};
+
+/// Named set of the shape properties. The shape own properties take precedence over the style.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Style {
+    pub transparency: Option<Real>,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+    /// Font size that the em values of the styled shape and its children are relative to.
+    pub font_size: Option<Real>,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.transparency = Some(transparency.into());
+        self
+    }
+
+    pub fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+
+    pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.stroke = Some(stroke.into());
+        self
+    }
+
+    pub fn font_size(mut self, font_size: impl Into<Real>) -> Self {
+        self.font_size = Some(font_size.into());
+        self
+    }
+
+    /// Returns the style with the values of `other` set over the values of this style.
+    pub fn merge(&self, other: &Style) -> Style {
+        Style {
+            transparency: other.transparency.or(self.transparency),
+            fill: other.fill.clone().or_else(|| self.fill.clone()),
+            stroke: other.stroke.clone().or_else(|| self.stroke.clone()),
+            font_size: other.font_size.or(self.font_size),
+        }
+    }
+}
+
+/// Interaction states of the primitive, which are switched by the mouse events.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+}
+
+/// Alternate styles of the primitive for the interaction states. Unlike the named style, the state style takes
+/// precedence over the shape own properties.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateStyles {
+    pub hover: Option<Style>,
+    pub pressed: Option<Style>,
+    pub focused: Option<Style>,
+}
+
+impl StateStyles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hover(mut self, style: Style) -> Self {
+        self.hover = Some(style);
+        self
+    }
+
+    pub fn pressed(mut self, style: Style) -> Self {
+        self.pressed = Some(style);
+        self
+    }
+
+    pub fn focused(mut self, style: Style) -> Self {
+        self.focused = Some(style);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hover.is_none() && self.pressed.is_none() && self.focused.is_none()
+    }
+
+    /// Merges the styles of the active states, the pressed style is set over the hover one, which is set over the
+    /// focused one. Returns `None` if no active state has a style.
+    pub fn resolve(&self, state: ShapeState) -> Option<Style> {
+        [
+            (state.focused, &self.focused),
+            (state.hovered, &self.hover),
+            (state.pressed, &self.pressed),
+        ]
+        .iter()
+        .filter(|(active, _)| *active)
+        .filter_map(|(_, style)| style.as_ref())
+        .fold(None, |resolved: Option<Style>, style| {
+            Some(resolved.map_or_else(|| style.clone(), |resolved| resolved.merge(style)))
+        })
+    }
+}
+
+/// Registry of the named styles, that can be switched as a whole, e.g. between the light and dark themes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Theme {
+    styles: HashMap<String, Style>,
+}
+
+impl Theme {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_style(mut self, name: impl Into<String>, style: Style) -> Self {
+        self.set_style(name, style);
+        self
+    }
+
+    pub fn set_style(&mut self, name: impl Into<String>, style: Style) {
+        self.styles.insert(name.into(), style);
+    }
+
+    pub fn style(&self, name: &str) -> Option<&Style> {
+        self.styles.get(name)
+    }
+
+    /// Resolves the dotted style name by cascading, so the `button.primary` style is set over the `button` style.
+    pub fn resolve(&self, name: &str) -> Style {
+        name.match_indices('.')
+            .map(|(idx, _)| idx)
+            .chain(iter::once(name.len()))
+            .filter_map(|end| self.styles.get(&name[..end]))
+            .fold(Style::default(), |style, next| style.merge(next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn resolve_cascade() {
+        let theme = Theme::new()
+            .with_style("button", Style::new().fill(Color::White).stroke(Color::Black))
+            .with_style("button.primary", Style::new().fill(Color::Blue));
+
+        let style = theme.resolve("button.primary");
+        assert_eq!(style.fill, Some(Color::Blue.into()));
+        assert_eq!(style.stroke, Some(Color::Black.into()));
+        assert_eq!(theme.resolve("button.unknown").fill, Some(Color::White.into()));
+        assert_eq!(theme.resolve("unknown"), Style::default());
+    }
+
+    #[test]
+    fn resolve_states() {
+        let states = StateStyles::new()
+            .hover(Style::new().fill(Color::Red).stroke(Color::Black))
+            .pressed(Style::new().fill(Color::Blue));
+
+        assert_eq!(states.resolve(ShapeState::default()), None);
+        let focused = ShapeState {
+            focused: true,
+            ..Default::default()
+        };
+        assert_eq!(states.resolve(focused), None);
+
+        let pressed = ShapeState {
+            hovered: true,
+            pressed: true,
+            ..Default::default()
+        };
+        let style = states.resolve(pressed).unwrap();
+        assert_eq!(style.fill, Some(Color::Blue.into()));
+        assert_eq!(style.stroke, Some(Color::Black.into()));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::Real;
+use std::f32::consts::PI;
+
+use crate::{BoundingBox, Real, RealValue};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Transform {
     Local(TransformMatrix),
     Global(TransformMatrix),
@@ -37,11 +40,26 @@ impl Transform {
         *self.skew(x, y)
     }
 
+    /// Set the horizontal skew of the transform by the angle.
+    pub fn with_skew_x(mut self, theta: Real) -> Self {
+        *self.skew_x(theta)
+    }
+
+    /// Set the vertical skew of the transform by the angle.
+    pub fn with_skew_y(mut self, theta: Real) -> Self {
+        *self.skew_y(theta)
+    }
+
     /// Set the rotation of the transform.
     pub fn with_rotation(mut self, theta: Real) -> Self {
         *self.rotate(theta)
     }
 
+    /// Set the rotation of the transform around the point.
+    pub fn with_rotation_around(mut self, theta: Real, x: Real, y: Real) -> Self {
+        *self.rotate_around(theta, x, y)
+    }
+
     pub fn transform(&mut self, modifier: impl Fn(&mut TransformMatrix)) {
         match self {
             Transform::Local(matrix) | Transform::Global(matrix) => modifier(matrix),
@@ -77,6 +95,13 @@ impl Transform {
         self
     }
 
+    pub fn rotate_around(&mut self, theta: Real, x: Real, y: Real) -> &mut Self {
+        self.transform(|matrix| {
+            matrix.rotate_around(theta, x, y);
+        });
+        self
+    }
+
     pub fn scale(&mut self, x: Real, y: Real) -> &mut Self {
         self.transform(|matrix| {
             matrix.scale(x, y);
@@ -91,6 +116,20 @@ impl Transform {
         self
     }
 
+    pub fn skew_x(&mut self, theta: Real) -> &mut Self {
+        self.transform(|matrix| {
+            matrix.skew_x(theta);
+        });
+        self
+    }
+
+    pub fn skew_y(&mut self, theta: Real) -> &mut Self {
+        self.transform(|matrix| {
+            matrix.skew_y(theta);
+        });
+        self
+    }
+
     pub fn is_absolute(&self) -> bool {
         match self {
             Transform::Global(_) | Transform::Calculated { local: None, .. } => true,
@@ -140,6 +179,49 @@ impl Transform {
         *self = Transform::Calculated { local, global };
         global
     }
+
+    /// Calculates the global matrix like `calculate_global`, but the local transform is applied around the origin
+    /// point in the local coordinates. The absolute transform is kept as it is.
+    pub fn calculate_global_around(&mut self, parent_global: TransformMatrix, (x, y): (Real, Real)) -> TransformMatrix {
+        let local = self.local_matrix();
+        let global = local
+            .map(|local| parent_global * local.around(x, y))
+            .or_else(|| self.global_matrix())
+            .unwrap();
+        *self = Transform::Calculated { local, global };
+        global
+    }
+}
+
+/// Point, around which the local transform rotates, scales and skews the shape, e.g. `TransformOrigin::center()`.
+/// The coordinates are the offsets from the top left corner of the shape bound, the percents are of the bound size.
+/// The group is resolved against the parent bound, because its own bound depends on the transformed children.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransformOrigin {
+    pub x: RealValue,
+    pub y: RealValue,
+}
+
+impl TransformOrigin {
+    pub fn new(x: impl Into<RealValue>, y: impl Into<RealValue>) -> Self {
+        Self {
+            x: x.into(),
+            y: y.into(),
+        }
+    }
+
+    pub fn center() -> Self {
+        Self::new(RealValue::pct(50.0), RealValue::pct(50.0))
+    }
+
+    /// The origin point in the local coordinates of the shape with the bound.
+    pub fn resolve(&self, bound: &BoundingBox) -> (Real, Real) {
+        let (mut x, mut y) = (self.x, self.y);
+        x.set_by_pct(bound.width());
+        y.set_by_pct(bound.height());
+        (bound.min_x + x.val(), bound.min_y + y.val())
+    }
 }
 
 /// Represents a transformation in 2D space.
@@ -151,6 +233,7 @@ impl Transform {
 /// **[0 0 1]** - only theoretical / does not really exist. Logically it is always [0 0 1].
 // TODO: need add transformation methods
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransformMatrix {
     pub matrix: [Real; 6],
 }
@@ -178,11 +261,26 @@ impl TransformMatrix {
         *self.skew(x, y)
     }
 
+    /// Set the horizontal skew of the transform by the angle.
+    pub fn with_skew_x(mut self, theta: Real) -> Self {
+        *self.skew_x(theta)
+    }
+
+    /// Set the vertical skew of the transform by the angle.
+    pub fn with_skew_y(mut self, theta: Real) -> Self {
+        *self.skew_y(theta)
+    }
+
     /// Set the rotation of the transform.
     pub fn with_rotation(mut self, theta: Real) -> Self {
         *self.rotate(theta)
     }
 
+    /// Set the rotation of the transform around the point.
+    pub fn with_rotation_around(mut self, theta: Real, x: Real, y: Real) -> Self {
+        *self.rotate_around(theta, x, y)
+    }
+
     pub fn translate(&mut self, x: Real, y: Real) -> &mut Self {
         self.matrix[4] = x;
         self.matrix[5] = y;
@@ -207,6 +305,15 @@ impl TransformMatrix {
         self
     }
 
+    /// Sets the rotation like the `rotate`, but around the point, i.e. the point stays where the transform has put
+    /// it before, instead of the origin.
+    pub fn rotate_around(&mut self, theta: Real, x: Real, y: Real) -> &mut Self {
+        let (before_x, before_y) = *self * (x, y);
+        self.rotate(theta);
+        let (after_x, after_y) = *self * (x, y);
+        self.translate_add(before_x - after_x, before_y - after_y)
+    }
+
     pub fn scale(&mut self, x: Real, y: Real) -> &mut Self {
         self.matrix[0] = x;
         self.matrix[3] = y;
@@ -223,20 +330,138 @@ impl TransformMatrix {
         self
     }
 
-    pub fn inverse(mut self) -> Self {
-        let inv_det = 1.0 / (self.matrix[0] * self.matrix[3] - self.matrix[2] * self.matrix[1]);
-        self.matrix[0] = self.matrix[3] * inv_det;
-        self.matrix[1] = -self.matrix[1] * inv_det;
-        self.matrix[2] = -self.matrix[2] * inv_det;
-        self.matrix[3] = self.matrix[0] * inv_det;
-        self.matrix[4] = (self.matrix[2] * self.matrix[5] - self.matrix[3] * self.matrix[4]) * inv_det;
-        self.matrix[5] = (self.matrix[1] * self.matrix[4] - self.matrix[0] * self.matrix[5]) * inv_det;
+    /// Sets the horizontal skew like the CSS `skewX`, i.e. the vertical lines are tilted by the angle, e.g. for the
+    /// italic-like slant. The vertical skew is kept.
+    pub fn skew_x(&mut self, theta: Real) -> &mut Self {
+        self.matrix[2] = theta.tan();
+        self
+    }
+
+    /// Sets the vertical skew like the CSS `skewY`, i.e. the horizontal lines are tilted by the angle.
+    pub fn skew_y(&mut self, theta: Real) -> &mut Self {
+        self.matrix[1] = theta.tan();
         self
     }
 
+    pub fn inverse(self) -> Self {
+        let [a, b, c, d, e, f] = self.matrix;
+        let inv_det = 1.0 / (a * d - c * b);
+        TransformMatrix {
+            matrix: [
+                d * inv_det,
+                -b * inv_det,
+                -c * inv_det,
+                a * inv_det,
+                (c * f - d * e) * inv_det,
+                (b * e - a * f) * inv_det,
+            ],
+        }
+    }
+
+    /// The transform applied around the point instead of the origin, i.e. the point is moved only by the translation.
+    pub fn around(mut self, x: Real, y: Real) -> Self {
+        let (translate_x, translate_y) = self.translate_xy();
+        let (moved_x, moved_y) = self * (x, y);
+        *self.translate_add(x - moved_x + translate_x, y - moved_y + translate_y)
+    }
+
     pub fn is_identity(&self) -> bool {
         self.matrix == [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]
     }
+
+    /// Multiplies the transform by the other one from the right, i.e. the other transform is applied to the points
+    /// first, like the child transform inside the parent.
+    pub fn post_multiply(&mut self, other: TransformMatrix) -> &mut Self {
+        *self *= other;
+        self
+    }
+
+    /// Multiplies the transform by the other one from the left, i.e. the other transform is applied to the points
+    /// last, like the camera over the scene.
+    pub fn pre_multiply(&mut self, other: TransformMatrix) -> &mut Self {
+        *self = other * *self;
+        self
+    }
+
+    /// Splits the transform into the translation, the rotation, the skew and the scale, which are applied in this
+    /// order from the left.
+    pub fn decompose(&self) -> DecomposedTransform {
+        let [a, b, c, d, e, f] = self.matrix;
+        let scale_x = (a * a + b * b).sqrt();
+        let rotation = b.atan2(a);
+        let (sin, cos) = rotation.sin_cos();
+        let shear = c * cos + d * sin;
+        let scale_y = d * cos - c * sin;
+        let skew = if scale_y != 0.0 { (shear / scale_y).atan() } else { 0.0 };
+        DecomposedTransform {
+            translate_x: e,
+            translate_y: f,
+            rotation,
+            skew,
+            scale_x,
+            scale_y,
+        }
+    }
+
+    /// Interpolates the transforms by their decompositions, `t` is in the range `0.0..=1.0`. Unlike the interpolation
+    /// of the matrix entries, the rotation is interpolated by the angle the shortest way round, so the tweened shape
+    /// turns instead of being squashed.
+    pub fn lerp(self, other: TransformMatrix, t: Real) -> TransformMatrix {
+        self.decompose().lerp(other.decompose(), t).recompose()
+    }
+}
+
+/// Transform split into the components, which are interpolated separately by the animations. The angles are in
+/// radians, the skew is horizontal like the `skew_x`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecomposedTransform {
+    pub translate_x: Real,
+    pub translate_y: Real,
+    pub rotation: Real,
+    pub skew: Real,
+    pub scale_x: Real,
+    pub scale_y: Real,
+}
+
+impl DecomposedTransform {
+    pub fn recompose(&self) -> TransformMatrix {
+        let (sin, cos) = self.rotation.sin_cos();
+        let shear = self.skew.tan() * self.scale_y;
+        TransformMatrix {
+            matrix: [
+                self.scale_x * cos,
+                self.scale_x * sin,
+                shear * cos - self.scale_y * sin,
+                shear * sin + self.scale_y * cos,
+                self.translate_x,
+                self.translate_y,
+            ],
+        }
+    }
+
+    pub fn lerp(self, other: DecomposedTransform, t: Real) -> DecomposedTransform {
+        let lerp = |from: Real, to: Real| from + (to - from) * t;
+        let mut turn = (other.rotation - self.rotation) % (2.0 * PI);
+        if turn > PI {
+            turn -= 2.0 * PI;
+        } else if turn < -PI {
+            turn += 2.0 * PI;
+        }
+        DecomposedTransform {
+            translate_x: lerp(self.translate_x, other.translate_x),
+            translate_y: lerp(self.translate_y, other.translate_y),
+            rotation: self.rotation + turn * t,
+            skew: lerp(self.skew, other.skew),
+            scale_x: lerp(self.scale_x, other.scale_x),
+            scale_y: lerp(self.scale_y, other.scale_y),
+        }
+    }
+}
+
+impl Default for TransformMatrix {
+    fn default() -> Self {
+        Self::identity()
+    }
 }
 
 /// Implementation of multiplication Trait for Transform.
@@ -259,6 +484,12 @@ impl std::ops::Mul for TransformMatrix {
     }
 }
 
+impl std::ops::MulAssign for TransformMatrix {
+    fn mul_assign(&mut self, rhs: TransformMatrix) {
+        *self = *self * rhs;
+    }
+}
+
 impl std::ops::Mul<(Real, Real)> for TransformMatrix {
     type Output = (Real, Real);
 
@@ -271,6 +502,24 @@ impl std::ops::Mul<(Real, Real)> for TransformMatrix {
     }
 }
 
+impl From<[Real; 6]> for TransformMatrix {
+    fn from(matrix: [Real; 6]) -> Self {
+        TransformMatrix { matrix }
+    }
+}
+
+impl From<TransformMatrix> for [Real; 6] {
+    fn from(transform: TransformMatrix) -> Self {
+        transform.matrix
+    }
+}
+
+impl From<TransformMatrix> for Transform {
+    fn from(matrix: TransformMatrix) -> Self {
+        Transform::Local(matrix)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -320,6 +569,11 @@ mod tests {
             matrix: [1.0, 22.2, 11.1, 1.0, 0.0, 0.0],
         });
 
+        let skew = 45_f32.to_radians();
+        trans_eq!(TransformMatrix::identity().with_skew_x(skew).with_skew_y(-skew), TransformMatrix {
+            matrix: [1.0, (-skew).tan(), skew.tan(), 1.0, 0.0, 0.0],
+        });
+
         let angle = 90_f32.to_radians();
         trans_eq!(TransformMatrix::identity().with_rotation(angle), TransformMatrix {
             matrix: [angle.cos(), angle.sin(), -angle.sin(), angle.cos(), 0.0, 0.0],
@@ -335,4 +589,85 @@ mod tests {
         let b = TransformMatrix::identity().with_skew(66.6, 1337.2);
         trans_not_eq!(a * b, b * a);
     }
+
+    #[test]
+    fn inverse_maps_back() {
+        let matrix = TransformMatrix::identity()
+            .with_translation(10.0, 20.0)
+            .with_rotation(90_f32.to_radians())
+            .with_scale(2.0, 4.0);
+        let (x, y) = matrix * (3.0, 5.0);
+        let (x, y) = matrix.inverse() * (x, y);
+        assert!((x - 3.0).abs() < 1e-4 && (y - 5.0).abs() < 1e-4);
+        trans_eq!(TransformMatrix::identity().inverse(), TransformMatrix::identity());
+    }
+
+    #[test]
+    fn compose_matrices() {
+        let parent = TransformMatrix::from([2.0, 0.0, 0.0, 2.0, 10.0, 0.0]);
+        let child = TransformMatrix::identity().with_translation(5.0, 5.0);
+
+        let mut composed = parent;
+        composed.post_multiply(child);
+        assert_eq!(composed * (0.0, 0.0), (20.0, 10.0));
+        assert_eq!(composed, parent * child);
+
+        let mut camera = child;
+        camera.pre_multiply(parent);
+        assert_eq!(camera, composed);
+
+        let mut assigned = parent;
+        assigned *= child;
+        assert_eq!(<[Real; 6]>::from(assigned), composed.matrix);
+        assert_eq!(Transform::from(parent).local_matrix(), Some(parent));
+    }
+
+    #[test]
+    fn interpolate_by_decomposition() {
+        let near = |matrix: TransformMatrix, expected: TransformMatrix| {
+            let pairs = matrix.matrix.iter().zip(&expected.matrix);
+            pairs.map(|(a, b)| (a - b).abs()).all(|diff| diff < 1e-4)
+        };
+        let matrix = TransformMatrix::identity()
+            .with_translation(10.0, 20.0)
+            .with_rotation(30_f32.to_radians())
+            * TransformMatrix::identity().with_skew_x(15_f32.to_radians())
+            * TransformMatrix::identity().with_scale(2.0, -3.0);
+        let decomposed = matrix.decompose();
+        assert!((decomposed.rotation - 30_f32.to_radians()).abs() < 1e-4);
+        assert!((decomposed.skew - 15_f32.to_radians()).abs() < 1e-4);
+        assert!(near(decomposed.recompose(), matrix));
+
+        let from = TransformMatrix::identity().with_rotation(170_f32.to_radians());
+        let to = TransformMatrix::identity()
+            .with_rotation(-170_f32.to_radians())
+            .with_translation(10.0, 0.0);
+        assert!(near(from.lerp(to, 0.0), from));
+        assert!(near(from.lerp(to, 1.0), to));
+        let half = TransformMatrix::identity().with_rotation(PI).with_translation(5.0, 0.0);
+        assert!(near(from.lerp(to, 0.5), half));
+    }
+
+    #[test]
+    fn rotate_around_pivot() {
+        let near = |(x, y): (Real, Real), (ex, ey): (Real, Real)| (x - ex).abs() < 1e-4 && (y - ey).abs() < 1e-4;
+        let angle = 90_f32.to_radians();
+        let matrix = TransformMatrix::identity()
+            .with_translation(5.0, 0.0)
+            .with_rotation_around(angle, 10.0, 10.0);
+        assert!(near(matrix * (10.0, 10.0), (15.0, 10.0)));
+        assert!(near(matrix * (20.0, 10.0), (15.0, 20.0)));
+
+        let rotation = TransformMatrix::identity().with_rotation(angle).around(10.0, 10.0);
+        assert!(near(rotation * (10.0, 10.0), (10.0, 10.0)));
+        assert!(near(rotation * (20.0, 10.0), (10.0, 20.0)));
+
+        let origin = TransformOrigin::center().resolve(&BoundingBox {
+            min_x: 10.0,
+            min_y: 20.0,
+            max_x: 30.0,
+            max_y: 60.0,
+        });
+        assert_eq!(origin, (20.0, 40.0));
+    }
 }
@@ -76,11 +76,29 @@ impl From<i32> for Pct<Real> {
     }
 }
 
+/// Value relative to the effective font size, which is the font size of the nearest text or style context.
+#[derive(Debug, Default, Clone, Copy, PartialOrd, PartialEq)]
+pub struct Em<T>(pub T);
+
+impl<T> From<T> for Em<T> {
+    fn from(v: T) -> Self {
+        Em(v)
+    }
+}
+
+impl From<i32> for Em<Real> {
+    fn from(v: i32) -> Self {
+        Em(v as Real)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueType {
     Auto,
     Px,
     Pct(Real),
+    Em(Real),
 }
 
 impl Default for ValueType {
@@ -90,6 +108,7 @@ impl Default for ValueType {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Value<T>(pub T, pub ValueType);
 
 impl<T: Debug + Default + Clone + Copy + PartialEq> Value<T> {
@@ -101,6 +120,10 @@ impl<T: Debug + Default + Clone + Copy + PartialEq> Value<T> {
         Value(Default::default(), ValueType::Pct(pct))
     }
 
+    pub fn em(em: Real) -> Self {
+        Value(Default::default(), ValueType::Em(em))
+    }
+
     pub fn auto() -> Self {
         Value(Default::default(), ValueType::Auto)
     }
@@ -132,6 +155,15 @@ impl Value<Real> {
             false
         }
     }
+
+    pub fn set_by_em(&mut self, font_size: Real) -> bool {
+        if let Value(ref mut v, ValueType::Em(em)) = self {
+            *v = *em * font_size;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl<T: Copy + Add<Output = T>> Add for Value<T> {
@@ -211,6 +243,18 @@ impl From<Pct<i32>> for RealValue {
     }
 }
 
+impl From<Em<Real>> for RealValue {
+    fn from(v: Em<Real>) -> Self {
+        RealValue::em(v.0)
+    }
+}
+
+impl From<Em<i32>> for RealValue {
+    fn from(v: Em<i32>) -> Self {
+        RealValue::em(v.0 as Real)
+    }
+}
+
 impl ConvertTo<Real> for i32 {
     fn convert(self) -> Real {
         self as Real
@@ -270,3 +314,27 @@ impl ConvertTo<Option<RealValue>> for Pct<i32> {
         Some(self.into())
     }
 }
+
+impl ConvertTo<RealValue> for Em<Real> {
+    fn convert(self) -> RealValue {
+        self.into()
+    }
+}
+
+impl ConvertTo<Option<RealValue>> for Em<Real> {
+    fn convert(self) -> Option<RealValue> {
+        Some(self.into())
+    }
+}
+
+impl ConvertTo<RealValue> for Em<i32> {
+    fn convert(self) -> RealValue {
+        self.into()
+    }
+}
+
+impl ConvertTo<Option<RealValue>> for Em<i32> {
+    fn convert(self) -> Option<RealValue> {
+        Some(self.into())
+    }
+}
@@ -0,0 +1,103 @@
+use std::{any::Any, collections::HashMap};
+
+use crate::{Comp, Model};
+
+/// Creates the component from the properties, returns `None` if the properties are of the other type.
+type Factory = Box<dyn Fn(Box<dyn Any>) -> Option<Comp>>;
+
+/// Component types by the names, so the component can be created from the name at runtime, e.g. for the screens
+/// described in the config files or the components of the plugins.
+#[derive(Default)]
+pub struct Registry {
+    factories: HashMap<String, Factory>,
+    defaults: HashMap<String, Box<dyn Fn() -> Comp>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the model, which is created by the `create` with the default properties or with the passed ones.
+    /// The model replaces the one with the same name.
+    pub fn register<M: Model>(&mut self, name: impl Into<String>)
+    where
+        M::Properties: Default + 'static,
+    {
+        let name = name.into();
+        self.register_factory(name.clone(), || Comp::new(M::create(Default::default())));
+        self.factories.insert(
+            name,
+            Box::new(|props| {
+                props
+                    .downcast::<M::Properties>()
+                    .ok()
+                    .map(|props| Comp::new(M::create(*props)))
+            }),
+        );
+    }
+
+    /// Registers the custom factory, e.g. the one which reads the properties from the config.
+    pub fn register_factory(&mut self, name: impl Into<String>, factory: impl Fn() -> Comp + 'static) {
+        let name = name.into();
+        self.factories.remove(&name);
+        self.defaults.insert(name, Box::new(factory));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.defaults.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.defaults.keys().map(String::as_str)
+    }
+
+    pub fn create(&self, name: &str) -> Option<Comp> {
+        self.defaults.get(name).map(|factory| factory())
+    }
+
+    /// Creates the component with the properties, returns `None` if the name is unknown, the component is registered
+    /// by the custom factory or its properties are of the other type.
+    pub fn create_with<P: 'static>(&self, name: &str, props: P) -> Option<Comp> {
+        self.factories.get(name).and_then(|factory| factory(Box::new(props)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::rect_node, ChangeView, Command, Node};
+
+    struct Badge(u32);
+
+    impl Model for Badge {
+        type Message = ();
+        type Properties = u32;
+
+        fn create(props: Self::Properties) -> Self {
+            Badge(props)
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            ChangeView::None.into()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            rect_node()
+        }
+    }
+
+    #[test]
+    fn create_by_name() {
+        let mut registry = Registry::new();
+        registry.register::<Badge>("badge");
+        registry.register_factory("seven", || Comp::new(Badge::create(7)));
+
+        assert_eq!(registry.create("badge").unwrap().model::<Badge>().0, 0);
+        assert_eq!(registry.create_with("badge", 3u32).unwrap().model::<Badge>().0, 3);
+        assert!(registry.create_with("badge", "3").is_none());
+        assert_eq!(registry.create("seven").unwrap().model::<Badge>().0, 7);
+        assert!(registry.create_with("seven", 3u32).is_none());
+        assert!(registry.create("label").is_none());
+    }
+}
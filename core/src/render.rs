@@ -1,6 +1,9 @@
-use std::fmt::Debug;
+use std::{ffi::c_void, fmt::Debug, time::Duration};
 
-use crate::{Color, CompositeShape};
+use crate::{
+    Color, CompositeShape, Damage, FontStyle, FontWeight, GlyphPos, Real, Text, TextMetrics, TextSpan, Theme,
+    TransformMatrix,
+};
 
 pub trait Render {
     type Error: Debug;
@@ -9,8 +12,195 @@ pub trait Render {
         Ok(())
     }
 
+    /// Initializes the render with the current GL context, the `loader` returns the addresses of its functions. The
+    /// renders which make their own GL function tables override it, the others are initialized by the `init`.
+    #[allow(unused_variables)]
+    fn init_gl(
+        &mut self, background_color: Color, loader: &mut dyn FnMut(&str) -> *const c_void,
+    ) -> Result<(), Self::Error> {
+        self.init(background_color)
+    }
+
+    /// Number of the MSAA samples, which the render requests for the GL context, `None` keeps the context settings.
+    fn multisampling(&self) -> Option<u16> {
+        None
+    }
+
+    /// Sets the window size in the physical pixels and its scale factor. The shapes are laid out in the logical pixels,
+    /// which are the physical ones divided by the `device_pixel_ratio`, and the render scales them when drawing, so
+    /// the scene keeps its size on the HiDPI displays.
     #[allow(unused_variables)]
     fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {}
 
+    /// Sets the theme for the shape styles, the next render recalculates the whole tree.
+    #[allow(unused_variables)]
+    fn set_theme(&mut self, theme: Theme) {}
+
+    /// Sets the camera transform, which transforms the scene into the window, e.g. the pan and zoom of the diagram.
+    /// The next render recalculates the whole tree, so the global transforms and the hit testing include it. The
+    /// renders without the camera ignore it.
+    #[allow(unused_variables)]
+    fn set_view_transform(&mut self, transform: TransformMatrix) {}
+
+    fn view_transform(&self) -> TransformMatrix {
+        TransformMatrix::identity()
+    }
+
+    /// Converts the window point in the logical pixels, e.g. the mouse position, into the scene coordinates.
+    fn screen_to_scene(&self, x: Real, y: Real) -> (Real, Real) {
+        self.view_transform().inverse() * (x, y)
+    }
+
+    /// Converts the scene point into the window coordinates in the logical pixels.
+    fn scene_to_screen(&self, x: Real, y: Real) -> (Real, Real) {
+        self.view_transform() * (x, y)
+    }
+
     fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error>;
+
+    /// Window areas in the physical pixels, which are redrawn by the last render. The renders which redraw the whole
+    /// window every frame return `None`.
+    fn damage(&self) -> Option<&Damage> {
+        None
+    }
+
+    /// Statistics of the last render, the renders which do not collect them return `None`.
+    fn stats(&self) -> Option<&RenderStats> {
+        None
+    }
+}
+
+/// Statistics of the render pass, e.g. for the performance overlay of the application.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RenderStats {
+    /// Number of the drawn shapes, including the texts and the groups.
+    pub shapes: usize,
+    /// Number of the drawn text shapes.
+    pub texts: usize,
+    /// Number of the cached group layers, which are reused from the last render.
+    pub cache_hits: usize,
+    /// Time of the layout recalculation, zero when the tree is not changed.
+    pub recalc_time: Duration,
+    /// Time of the drawing, zero when nothing is redrawn.
+    pub draw_time: Duration,
+}
+
+impl RenderStats {
+    pub fn frame_time(&self) -> Duration {
+        self.recalc_time + self.draw_time
+    }
+}
+
+/// Measures the text lines outside of the render pass, e.g. to size the labels in the layout code. The tests can
+/// use a measurer with fixed glyph sizes instead of the render.
+pub trait TextMeasurer {
+    type Error: Debug;
+
+    /// Returns the horizontal advance of the text line and the metrics of the font.
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error>;
+
+    fn measure_text(&mut self, text: &Text) -> Result<(Real, TextMetrics), Self::Error> {
+        self.measure_face(&text.content, TextFace::of_text(text), text.font_size.val())
+    }
+
+    /// Measures the text line by the font face, the measurers which do not resolve the faces measure it by the family.
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        self.measure(text, face.family, font_size)
+    }
+
+    /// Returns the glyph positions of the text line started at the zero origin. The default implementation measures
+    /// every prefix of the line, so the measurers with the glyph layout should override it.
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let mut positions = Vec::with_capacity(text.len());
+        for (idx, ch) in text.char_indices() {
+            let (x, _) = self.measure_face(&text[..idx], face, font_size)?;
+            let (width, _) = self.measure_face(ch.encode_utf8(&mut [0; 4]), face, font_size)?;
+            positions.push(GlyphPos { x, y: 0.0, width });
+        }
+        Ok(positions)
+    }
+}
+
+/// Font face of the text line, which the render resolves to the loaded font.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextFace<'a> {
+    pub family: &'a str,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+}
+
+impl<'a> TextFace<'a> {
+    pub fn of_text(text: &'a Text) -> Self {
+        Self {
+            family: &text.font_name,
+            weight: text.font_weight,
+            style: text.font_style,
+        }
+    }
+
+    /// Face of the span, inherited from the text if not set.
+    pub fn of_span(text: &'a Text, span: &'a TextSpan) -> Self {
+        Self {
+            family: span.font_name.as_ref().unwrap_or(&text.font_name),
+            weight: span.font_weight.unwrap_or(text.font_weight),
+            style: span.font_style.unwrap_or(text.font_style),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MonospaceMeasurer;
+
+    impl TextMeasurer for MonospaceMeasurer {
+        type Error = ();
+
+        fn measure(&mut self, text: &str, _font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), ()> {
+            let metrics = TextMetrics {
+                ascender: font_size * 0.8,
+                descender: -font_size * 0.2,
+                line_height: font_size,
+            };
+            Ok((text.chars().count() as Real * font_size * 0.5, metrics))
+        }
+    }
+
+    #[test]
+    fn measure_text() {
+        let text = Text {
+            content: "label".to_string(),
+            font_size: 20.0.into(),
+            ..Text::default()
+        };
+        let (advance, metrics) = MonospaceMeasurer.measure_text(&text).unwrap();
+        assert_eq!(advance, 50.0);
+        assert_eq!(metrics.line_height, 20.0);
+    }
+
+    #[test]
+    fn glyph_positions_by_prefixes() {
+        let text = Text {
+            content: "ab".to_string(),
+            ..Text::default()
+        };
+        let positions = MonospaceMeasurer
+            .glyph_positions(&text.content, TextFace::of_text(&text), 10.0)
+            .unwrap();
+        assert_eq!(positions, vec![
+            GlyphPos {
+                x: 0.0,
+                y: 0.0,
+                width: 5.0
+            },
+            GlyphPos {
+                x: 5.0,
+                y: 0.0,
+                width: 5.0
+            },
+        ]);
+    }
 }
@@ -0,0 +1,126 @@
+use std::{sync::Mutex, time::Duration};
+
+/// When the controller has to draw the next frame, so the idle UI is not redrawn at the full rate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NextFrame {
+    /// The view is changed, an animation is active or a task is woken, so the frame is drawn right away.
+    Now,
+    /// The nearest timer expires after the delay since the last frame.
+    After(Duration),
+    /// Nothing is scheduled, the next frame is drawn on the input or when the controller is woken.
+    Idle,
+}
+
+impl NextFrame {
+    /// The earlier of the frames.
+    pub fn min(self, other: Self) -> Self {
+        match (self, other) {
+            (NextFrame::Now, _) | (_, NextFrame::Now) => NextFrame::Now,
+            (NextFrame::After(delay), NextFrame::After(other_delay)) => NextFrame::After(delay.min(other_delay)),
+            (NextFrame::After(delay), NextFrame::Idle) | (NextFrame::Idle, NextFrame::After(delay)) => {
+                NextFrame::After(delay)
+            }
+            (NextFrame::Idle, NextFrame::Idle) => NextFrame::Idle,
+        }
+    }
+}
+
+type FrameWaker = Box<dyn Fn() + Send>;
+
+static FRAME_WAKER: Mutex<Option<FrameWaker>> = Mutex::new(None);
+
+/// Sets the callback, which wakes the idle controller, when a message is sent by the `Link` or a task is woken,
+/// e.g. from the other thread. The controller draws the next frame then, so the message is received.
+pub fn set_frame_waker(waker: impl Fn() + Send + 'static) {
+    if let Ok(mut current) = FRAME_WAKER.lock() {
+        *current = Some(Box::new(waker));
+    }
+}
+
+pub(crate) fn wake_frame() {
+    if let Ok(waker) = FRAME_WAKER.lock() {
+        if let Some(waker) = waker.as_ref() {
+            waker();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::rect_node, Command, Comp, EventName, Listener, Model, Node, Prim, Rect, Shape, SystemMessage, Text,
+        TextInput,
+    };
+
+    struct Blink {
+        animating: bool,
+    }
+
+    impl Model for Blink {
+        type Message = bool;
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Blink { animating: false }
+        }
+
+        fn update(&mut self, animating: Self::Message) -> Command<Self::Message> {
+            self.animating = animating;
+            Command::none().set_timeout(Duration::from_millis(500), false)
+        }
+
+        fn is_animating(&self) -> bool {
+            self.animating
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            rect_node()
+        }
+    }
+
+    #[test]
+    fn schedule_next_frame() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            NextFrame::After(ms(10)).min(NextFrame::After(ms(5))),
+            NextFrame::After(ms(5))
+        );
+        assert_eq!(NextFrame::Idle.min(NextFrame::After(ms(5))), NextFrame::After(ms(5)));
+        assert_eq!(NextFrame::After(ms(5)).min(NextFrame::Now), NextFrame::Now);
+
+        let mut comp = Comp::new(Blink::create(()));
+        // The first view is not drawn yet.
+        assert_eq!(comp.next_frame(), NextFrame::Now);
+        comp.update_view();
+        assert_eq!(comp.next_frame(), NextFrame::Idle);
+
+        comp.send::<Blink>(true);
+        assert_eq!(comp.next_frame(), NextFrame::Now);
+        comp.send_system_msg(SystemMessage::Draw(ms(300)));
+        assert_eq!(comp.next_frame(), NextFrame::Now);
+        comp.send_system_msg(SystemMessage::Draw(ms(200)));
+        assert_eq!(comp.next_frame(), NextFrame::After(ms(500)));
+    }
+
+    #[test]
+    fn schedule_prim_frames() {
+        let ms = Duration::from_millis;
+        let mut text_input = TextInput::default();
+        text_input.focused = true;
+        let text = Text {
+            content: "text".to_string(),
+            input: Some(text_input),
+            ..Text::default()
+        };
+        let mut input = Prim::<Blink>::new(Text::NAME.into(), Shape::Text(text), vec![], Default::default());
+        assert_eq!(input.next_frame(), NextFrame::After(TextInput::BLINK_PERIOD));
+        input.send_system_msg(SystemMessage::Draw(ms(100)), &mut vec![]);
+        assert_eq!(input.next_frame(), NextFrame::After(TextInput::BLINK_PERIOD - ms(100)));
+
+        let mut listeners = std::collections::HashMap::new();
+        listeners.insert(EventName::DRAW, vec![Listener::Draw(Box::new(|_| true))]);
+        let animated = Prim::<Blink>::new(Rect::NAME.into(), Shape::Rect(Rect::default()), vec![], listeners);
+        assert_eq!(animated.next_frame(), NextFrame::Now);
+    }
+}
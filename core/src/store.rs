@@ -0,0 +1,132 @@
+use std::{cell::RefCell, mem, rc::Rc};
+
+use crate::{Link, Model};
+
+/// Notifies the subscribed component about the state change. Returns false if the component is dropped.
+type Subscriber<S> = Box<dyn Fn(&S) -> bool>;
+
+struct StoreInner<S> {
+    state: S,
+    subscribers: Vec<Subscriber<S>>,
+}
+
+/// Application state, which is shared by several components, e.g. the document of the toolbar and the canvas. The
+/// clones of the store refer to the same state, so it can be passed by the properties or by the context. Each change
+/// of the state is sent to the subscribed components as the messages, they are received on the next frame.
+pub struct Store<S> {
+    inner: Rc<RefCell<StoreInner<S>>>,
+}
+
+impl<S: 'static> Store<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(StoreInner {
+                state,
+                subscribers: vec![],
+            })),
+        }
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&S) -> R) -> R {
+        f(&self.inner.borrow().state)
+    }
+
+    pub fn get(&self) -> S
+    where
+        S: Clone,
+    {
+        self.with(S::clone)
+    }
+
+    /// Subscribes the component of the `link` to the changes, the message of each change is made by the `map`.
+    pub fn subscribe<M: Model>(&self, link: Link<M>, map: impl Fn(&S) -> M::Message + 'static) {
+        self.inner
+            .borrow_mut()
+            .subscribers
+            .push(Box::new(move |state| link.send(map(state))));
+    }
+
+    /// Changes the state and notifies the subscribers, the dropped components are unsubscribed. The state is not
+    /// borrowed mutably while the subscribers are notified, so they can read the store.
+    pub fn update(&self, f: impl FnOnce(&mut S)) {
+        f(&mut self.inner.borrow_mut().state);
+        let mut subscribers = mem::take(&mut self.inner.borrow_mut().subscribers);
+        {
+            let inner = self.inner.borrow();
+            subscribers.retain(|notify| notify(&inner.state));
+        }
+        let mut inner = self.inner.borrow_mut();
+        subscribers.append(&mut inner.subscribers);
+        inner.subscribers = subscribers;
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.inner.borrow().subscribers.len()
+    }
+}
+
+impl<S> Clone for Store<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::rect_node, ChangeView, Command, Comp, Node};
+
+    struct Title(String);
+
+    impl Model for Title {
+        type Message = String;
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Title(String::new())
+        }
+
+        fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
+            self.0 = msg;
+            ChangeView::None.into()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            rect_node()
+        }
+    }
+
+    #[test]
+    fn notify_subscribers() {
+        let store = Store::new("draft".to_string());
+        let mut comp = Comp::new(Title::create(()));
+        store.subscribe(comp.link::<Title>(), |title: &String| title.to_uppercase());
+
+        store.update(|title| title.push_str(" 2"));
+        assert_eq!(comp.model::<Title>().0, "");
+        assert!(comp.poll_commands());
+        assert_eq!(comp.model::<Title>().0, "DRAFT 2");
+
+        drop(comp);
+        store.update(|title| title.clear());
+        assert_eq!(store.subscriber_count(), 0);
+        assert_eq!(store.get(), "");
+    }
+
+    #[test]
+    fn read_store_while_notified() {
+        let store = Store::new("draft".to_string());
+        let mut comp = Comp::new(Title::create(()));
+        let reader = store.clone();
+        store.subscribe(comp.link::<Title>(), move |_| {
+            format!("{} {}", reader.get(), reader.subscriber_count())
+        });
+
+        store.update(|title| title.push('!'));
+        assert!(comp.poll_commands());
+        assert_eq!(comp.model::<Title>().0, "draft! 0");
+        assert_eq!(store.subscriber_count(), 1);
+    }
+}
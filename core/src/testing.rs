@@ -0,0 +1,45 @@
+//! Helpers of the unit tests.
+
+use crate::{BoundingBox, Command, Model, Node, Prim, RecalcCache, Rect, Shape, TransformMatrix};
+
+/// Model of the nodes, which are built by hand in the tests. The messages are the names of the handled events.
+pub struct TestModel;
+
+impl Model for TestModel {
+    type Message = &'static str;
+    type Properties = ();
+
+    fn create(_props: Self::Properties) -> Self {
+        TestModel
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+        Command::none()
+    }
+
+    fn build_view(&self) -> Node<Self> {
+        unimplemented!()
+    }
+}
+
+/// Default rect without the children and the listeners, e.g. the view of the model, which is not inspected.
+pub fn rect_node<M: Model>() -> Node<M> {
+    Node::Prim(Prim::new(
+        Rect::NAME.into(),
+        Shape::Rect(Rect::default()),
+        vec![],
+        Default::default(),
+    ))
+}
+
+/// Cache of the recalculation in the identity transform with the default font size, the parent bound is empty.
+pub fn recalc_cache(bound: BoundingBox, revision: u64) -> RecalcCache {
+    RecalcCache {
+        parent_bound: Default::default(),
+        parent_transform: TransformMatrix::identity(),
+        font_size: 16.0,
+        bound,
+        global_bound: bound,
+        revision,
+    }
+}
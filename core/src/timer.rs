@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+/// The message of the timer, the interval produces the new message for each period.
+enum TimerTrigger<Msg> {
+    Once(Option<Msg>),
+    Repeat(Box<dyn Fn() -> Msg>),
+}
+
+pub(crate) struct Timer<Msg> {
+    id: Option<String>,
+    remaining: Duration,
+    period: Duration,
+    trigger: TimerTrigger<Msg>,
+}
+
+impl<Msg> Timer<Msg> {
+    pub(crate) fn timeout(delay: Duration, msg: Msg) -> Self {
+        Self {
+            id: None,
+            remaining: delay,
+            period: delay,
+            trigger: TimerTrigger::Once(Some(msg)),
+        }
+    }
+
+    pub(crate) fn interval(id: String, period: Duration, trigger: impl Fn() -> Msg + 'static) -> Self {
+        Self {
+            id: Some(id),
+            remaining: period,
+            period,
+            trigger: TimerTrigger::Repeat(Box::new(trigger)),
+        }
+    }
+}
+
+pub(crate) enum TimerOp<Msg> {
+    Set(Timer<Msg>),
+    Clear(String),
+}
+
+/// Timers of the component, they are advanced by the elapsed time of the draw messages.
+pub(crate) struct Timers<Msg> {
+    timers: Vec<Timer<Msg>>,
+}
+
+impl<Msg> Default for Timers<Msg> {
+    fn default() -> Self {
+        Self { timers: vec![] }
+    }
+}
+
+impl<Msg> Timers<Msg> {
+    /// Applies the operation, the interval replaces the one with the same id.
+    pub(crate) fn apply(&mut self, op: TimerOp<Msg>) {
+        let id = match &op {
+            TimerOp::Set(timer) => timer.id.clone(),
+            TimerOp::Clear(id) => Some(id.clone()),
+        };
+        if let Some(id) = id {
+            self.timers.retain(|timer| timer.id.as_ref() != Some(&id));
+        }
+        if let TimerOp::Set(timer) = op {
+            self.timers.push(timer);
+        }
+    }
+
+    /// Time until the nearest timer expires.
+    pub(crate) fn next_expiry(&self) -> Option<Duration> {
+        self.timers.iter().map(|timer| timer.remaining).min()
+    }
+
+    /// Advances the timers and returns the messages of the expired ones. The interval is fired once per advance,
+    /// even if several periods are elapsed, so that the slow frames do not produce the bursts of messages.
+    pub(crate) fn advance(&mut self, elapsed: Duration) -> Vec<Msg> {
+        let mut outputs = vec![];
+        self.timers.retain_mut(|timer| {
+            if timer.remaining > elapsed {
+                timer.remaining -= elapsed;
+                return true;
+            }
+            match &mut timer.trigger {
+                TimerTrigger::Once(msg) => {
+                    outputs.extend(msg.take());
+                    false
+                }
+                TimerTrigger::Repeat(trigger) => {
+                    outputs.push(trigger());
+                    let overdue = elapsed - timer.remaining;
+                    let period = timer.period.as_nanos().max(1);
+                    timer.remaining = timer.period - Duration::from_nanos((overdue.as_nanos() % period) as u64);
+                    true
+                }
+            }
+        });
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_and_interval() {
+        let ms = Duration::from_millis;
+        let mut timers = Timers::default();
+        timers.apply(TimerOp::Set(Timer::timeout(ms(250), "timeout")));
+        timers.apply(TimerOp::Set(Timer::interval("tick".into(), ms(100), || "tick")));
+
+        assert_eq!(timers.advance(ms(50)), Vec::<&str>::new());
+        assert_eq!(timers.advance(ms(60)), ["tick"]);
+        assert_eq!(timers.advance(ms(250)), ["timeout", "tick"]);
+        assert_eq!(timers.advance(ms(40)), ["tick"]);
+
+        timers.apply(TimerOp::Clear("tick".into()));
+        assert_eq!(timers.advance(ms(1000)), Vec::<&str>::new());
+    }
+}
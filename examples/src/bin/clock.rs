@@ -1,10 +1,10 @@
-use std::{env, f32::consts::PI};
+use std::{f32::consts::PI, time::Duration};
 
 use chrono::{DateTime, Datelike, Local, Timelike};
 
 use exgui::{
-    builder::*, AlignHor::*, AlignVer::*, ChangeView, Color, Comp, Gradient, Model, Node, PathCommand::*, Real,
-    SystemMessage,
+    builder::*, AlignHor::*, AlignVer::*, ChangeView, Color, Command, Comp, Gradient, Model, Node, PathCommand::*,
+    Real, SystemMessage,
 };
 use exgui_controller_glutin::{glutin, App};
 // use exgui_render_nanovg::NanovgRender as Render;
@@ -12,6 +12,7 @@ use exgui_render_pathfinder::PathfinderRender as Render;
 
 const INIT_WINDOW_SIZE: (u32, u32) = (480, 480);
 const TWO_PI: Real = 2.0 * PI;
+const TICK_TIMER: &str = "tick";
 
 #[derive(Debug, Default)]
 struct Clock {
@@ -54,21 +55,23 @@ impl Model for Clock {
     fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
         match msg {
             SystemMessage::Draw(_) => Some(Msg::Tick),
-            SystemMessage::WindowResized { width, height } => Some(Msg::ResizeWindow((width, height))),
+            SystemMessage::WindowResized(viewport) => Some(Msg::ResizeWindow((viewport.width, viewport.height))),
             _ => None,
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView {
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
         match msg {
-            Msg::ResizeWindow((w, h)) => self.size_recalc(w, h),
+            Msg::ResizeWindow((w, h)) => self.size_recalc(w, h).into(),
             Msg::Tick => {
                 let dt: DateTime<Local> = Local::now(); // e.g. `2018-11-28T21:45:59.324310806+09:00`
 
                 let prev_second = self.second;
                 self.second = f64::from(dt.second()) as f32;
+                // The next tick is at the start of the next second, so the frames are not drawn in between.
+                let next_tick = Duration::from_nanos(1_000_000_000 - u64::from(dt.nanosecond() % 1_000_000_000));
 
-                if (self.second - prev_second).abs() >= 1. {
+                let change_view = if (self.second - prev_second).abs() >= 1. {
                     let hour = dt.hour();
 
                     self.am = hour < 12;
@@ -95,7 +98,8 @@ impl Model for Clock {
                     ChangeView::Modify
                 } else {
                     ChangeView::None
-                }
+                };
+                Command::from(change_view).set_interval(TICK_TIMER, next_tick, || Msg::Tick)
             }
         }
     }
@@ -155,13 +159,13 @@ impl Model for Clock {
             .build();
         set.push(date);
 
-        let second_hand = comp(Hand::create(second_hand_props)).id("second hand").build();
+        let second_hand = comp_of::<Hand>(second_hand_props).id("second hand").build();
         set.push(second_hand);
 
-        let minute_hand = comp(Hand::create(minute_hand_props)).id("minute hand").build();
+        let minute_hand = comp_of::<Hand>(minute_hand_props).id("minute hand").build();
         set.push(minute_hand);
 
-        let hour_hand = comp(Hand::create(hour_hand_props)).id("hour hand").build();
+        let hour_hand = comp_of::<Hand>(hour_hand_props).id("hour hand").build();
         set.push(hour_hand);
 
         let boss = circle()
@@ -286,11 +290,11 @@ impl Model for Hand {
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView {
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
         match msg {
             HandMsg::ChangeTheta(theta) => {
                 self.theta = theta;
-                ChangeView::Modify
+                ChangeView::Modify.into()
             }
         }
     }
@@ -322,12 +326,9 @@ fn main() {
     .unwrap();
     app.init().unwrap();
 
-    let font_path = env::current_dir()
-        .unwrap()
-        .join("examples")
-        .join("resources")
-        .join("Roboto-Regular.ttf");
-    app.renderer_mut().load_font("Roboto-Regular", font_path).unwrap();
+    app.renderer_mut()
+        .load_font_from_memory("Roboto-Regular", include_bytes!("../../resources/Roboto-Regular.ttf"))
+        .unwrap();
 
     let comp = Comp::new(Clock::create(()));
     app.run(comp);
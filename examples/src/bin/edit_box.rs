@@ -1,8 +1,8 @@
 use std::{env, mem, time::Duration};
 
 use exgui::{
-    builder::*, ChangeView, Color, Comp, Model, MousePos, Node, PathCommand::*, Real, Shaped, SystemMessage, Text,
-    VirtualKeyCode,
+    builder::*, ChangeView, Color, Command, Comp, Model, MousePos, Node, PathCommand::*, Real, Shaped, SystemMessage,
+    Text, VirtualKeyCode,
 };
 use exgui_controller_glutin::{glutin, App};
 // use exgui_render_nanovg::NanovgRender as Render;
@@ -115,70 +115,75 @@ impl Model for EditBox {
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView {
+    /// The caret blinks while the edit box is focused.
+    fn is_animating(&self) -> bool {
+        self.focus
+    }
+
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
         match msg {
             Msg::OnFocus(pos) => {
                 self.focus = true;
                 if self.editable {
                     self.caret.update_action(CaretAction::Put(pos.x));
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 } else {
-                    ChangeView::None
+                    ChangeView::None.into()
                 }
             }
             Msg::OnKeyDown(keycode) if self.focus => match keycode {
                 VirtualKeyCode::Left => {
                     self.caret.update_action(CaretAction::MoveLeft);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 }
                 VirtualKeyCode::Right => {
                     self.caret.update_action(CaretAction::MoveRight);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 }
                 VirtualKeyCode::Home => {
                     self.caret.update_action(CaretAction::MoveStart);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 }
                 VirtualKeyCode::End => {
                     self.caret.update_action(CaretAction::MoveEnd);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 }
                 VirtualKeyCode::Delete if self.editable => {
                     self.caret.update_action(CaretAction::Delete);
-                    ChangeView::RecalcOnly
+                    ChangeView::RecalcOnly.into()
                 }
                 VirtualKeyCode::Backspace if self.editable => {
                     self.caret.update_action(CaretAction::Backspace);
-                    ChangeView::RecalcOnly
+                    ChangeView::RecalcOnly.into()
                 }
-                _ => ChangeView::None,
+                _ => ChangeView::None.into(),
             },
             Msg::Input(ch) if self.editable => {
                 if !(ch.is_ascii_control() || ch.is_control()) {
                     self.caret.update_action(CaretAction::Input(ch));
-                    ChangeView::RecalcOnly
+                    ChangeView::RecalcOnly.into()
                 } else {
-                    ChangeView::None
+                    ChangeView::None.into()
                 }
             }
             Msg::Draw(elapsed) if self.focus => {
                 self.caret.blink += elapsed;
                 if let CaretAction::Redraw = self.caret.action {
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 } else if self.caret.update_blink() {
                     self.caret.update_action(CaretAction::Blink);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 } else {
-                    ChangeView::None
+                    ChangeView::None.into()
                 }
             }
             Msg::OnBlur if self.focus => {
                 self.focus = false;
                 self.caret.reset();
                 self.caret.action = CaretAction::Blink;
-                ChangeView::Modify
+                ChangeView::Modify.into()
             }
-            _ => ChangeView::None,
+            _ => ChangeView::None.into(),
         }
     }
 
@@ -1,4 +1,4 @@
-use exgui::{builder::*, ChangeView, Color, Comp, LineJoin, Model, Node, PathCommand::*, Stroke};
+use exgui::{builder::*, ChangeView, Color, Command, Comp, LineJoin, Model, Node, PathCommand::*, Stroke};
 use exgui_controller_glutin::{glutin, App};
 use exgui_render_nanovg::NanovgRender as Render;
 // use exgui_render_pathfinder::PathfinderRender as Render;
@@ -21,13 +21,13 @@ impl Model for Smile {
         Smile { normal_face: true }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView {
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
         match msg {
             Msg::ToggleFace => {
                 self.normal_face = !self.normal_face;
-                ChangeView::Rebuild
+                ChangeView::Rebuild.into()
             }
-            Msg::Nope => ChangeView::None,
+            Msg::Nope => ChangeView::None.into(),
         }
     }
 
@@ -1,8 +1,8 @@
 use std::{env, time::Duration};
 
 use exgui::{
-    builder::*, ChangeView, Color, Comp, LineCap, LineJoin, Model, Node, PathCommand::*, Pct, Real, Shaped, Stroke,
-    SystemMessage, Transform, VirtualKeyCode,
+    builder::*, ChangeView, Color, Command, Comp, LineCap, LineJoin, Model, Node, PathCommand::*, Pct, Real, Shaped,
+    Stroke, SystemMessage, Transform, VirtualKeyCode,
 };
 use exgui_controller_glutin::{glutin, App};
 // use exgui_render_nanovg::NanovgRender as Render;
@@ -241,34 +241,38 @@ impl Model for Game {
 
     fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
         match msg {
-            SystemMessage::WindowResized { width, height } => Some(Msg::Resize {
-                width: width as Real,
-                height: height as Real,
+            SystemMessage::WindowResized(viewport) => Some(Msg::Resize {
+                width: viewport.width as Real,
+                height: viewport.height as Real,
             }),
             SystemMessage::Draw(elapsed) => Some(Msg::Draw(elapsed)),
             _ => None,
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ChangeView {
+    fn is_animating(&self) -> bool {
+        self.is_transient()
+    }
+
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
         match msg {
             Msg::Resize { width, height } => {
                 self.canvas.resize(width, height);
                 self.reset_docker();
-                ChangeView::Rebuild
+                ChangeView::Rebuild.into()
             }
             Msg::Draw(elapsed) => {
                 if self.is_transient() {
                     self.animate(elapsed);
-                    ChangeView::Modify
+                    ChangeView::Modify.into()
                 } else {
                     match self.state {
-                        GameState::LevelComplete => ChangeView::Modify,
+                        GameState::LevelComplete => ChangeView::Modify.into(),
                         GameState::NextLevel => {
                             self.next_level();
-                            ChangeView::Rebuild
+                            ChangeView::Rebuild.into()
                         }
-                        _ => ChangeView::None,
+                        _ => ChangeView::None.into(),
                     }
                 }
             }
@@ -276,11 +280,11 @@ impl Model for Game {
                 self.canvas
                     .scale_factor
                     .set((self.canvas.scale_factor.val() + delta * 0.1).max(0.01));
-                ChangeView::Rebuild
+                ChangeView::Rebuild.into()
             }
             Msg::KeyDown(VirtualKeyCode::Backspace) => {
                 self.reset_level();
-                ChangeView::Rebuild
+                ChangeView::Rebuild.into()
             }
             Msg::KeyDown(code) => {
                 match code {
@@ -293,9 +297,9 @@ impl Model for Game {
                     }
                     _ => (),
                 };
-                ChangeView::None
+                ChangeView::None.into()
             }
-            _ => ChangeView::None,
+            _ => ChangeView::None.into(),
         }
     }
 
@@ -1,11 +1,13 @@
 pub use exgui_builder as builder;
 pub use exgui_core::*;
 
+pub mod widget;
+
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
 
-    use crate::{ChangeView, Model, Node, Rect, Text};
+    use crate::{ChangeView, Circle, Color, Command, Comp, Model, Node, Rect, Text};
     use exgui_core::Shaped;
 
     #[derive(Debug, PartialEq)]
@@ -24,12 +26,12 @@ mod tests {
             Counter(prop)
         }
 
-        fn update(&mut self, msg: Self::Message) -> ChangeView {
+        fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
             match msg {
                 Msg::Increment => self.0 += 1,
                 Msg::Decrement => self.0 -= 1,
             }
-            ChangeView::Modify
+            ChangeView::Modify.into()
         }
 
         fn build_view(&self) -> Node<Self> {
@@ -100,4 +102,153 @@ mod tests {
         });
         assert_eq!(child.children.len(), 0);
     }
+
+    #[test]
+    fn find_by_id() {
+        let mut comp = Comp::new(Counter::create(0));
+        comp.update_view();
+        assert_eq!(comp.subtree().count(), 6);
+
+        let counter = comp.find_by_id("counter").and_then(|node| node.as_shape());
+        assert_eq!(counter.and_then(|shape| shape.text()).unwrap().content, "0");
+
+        let counter = comp
+            .find_by_id_mut("counter")
+            .and_then(|node| node.into_shape())
+            .unwrap();
+        counter.text_mut().unwrap().content = "9".to_string();
+        assert!(comp.has_changes());
+        assert!(comp.find_by_id("missing").is_none());
+    }
+
+    #[test]
+    fn child_branches() {
+        use crate::builder::*;
+
+        let view = |expanded| {
+            group::<Counter>()
+                .child_when(expanded, rect, || text("More"))
+                .child(match expanded {
+                    true => circle().build(),
+                    false => text("Less").build(),
+                })
+                .build()
+        };
+        let names = |view: Node<Counter>| -> Vec<_> {
+            view.into_prim()
+                .unwrap()
+                .children
+                .iter()
+                .map(|child| child.as_prim().unwrap().name.clone())
+                .collect()
+        };
+        assert_eq!(names(view(true)), [Rect::NAME, Circle::NAME]);
+        assert_eq!(names(view(false)), [Text::NAME, Text::NAME]);
+    }
+
+    #[test]
+    fn fragment_children() {
+        use crate::builder::*;
+
+        let items = || fragment(vec![text("first").build(), circle().build()]);
+        let view = rect::<Counter>()
+            .child(items())
+            .children(vec![items()])
+            .child(text("last"))
+            .build();
+        assert_eq!(view.as_prim().unwrap().children.len(), 5);
+        assert!(items().as_prim().unwrap().is_fragment());
+    }
+
+    #[test]
+    fn optional_attributes() {
+        use crate::builder::*;
+
+        let view = |fill: Option<Color>| {
+            rect::<Counter>()
+                .maybe(fill, |rect, fill| rect.fill(fill))
+                .child_if(fill.is_some(), text("Filled"))
+                .maybe_child(fill.map(|_| circle()))
+                .build()
+                .into_prim()
+                .unwrap()
+        };
+        let filled = view(Some(Color::Red));
+        assert!(filled.shape.rect().unwrap().fill.is_some());
+        assert_eq!(filled.children.len(), 2);
+        let empty = view(None);
+        assert!(empty.shape.rect().unwrap().fill.is_none());
+        assert!(empty.children.is_empty());
+    }
+
+    #[test]
+    fn set_props() {
+        let mut comp = Comp::new(Counter::create(0));
+        comp.update_view();
+        assert!(!comp.has_changes());
+        assert!(comp.update_view().is_none());
+
+        assert!(comp.set_props::<Counter>(5));
+        assert_eq!(comp.model::<Counter>(), &Counter(5));
+        assert!(comp.has_changes());
+        assert!(comp.update_view().is_redraw());
+    }
+
+    #[test]
+    fn declared_comp() {
+        let mut comp = Comp::from_props::<Counter>(3);
+        assert!(comp.try_model::<Counter>().is_none());
+        let link = comp.link::<Counter>();
+
+        comp.send::<Counter>(Msg::Increment);
+        assert_eq!(comp.model::<Counter>(), &Counter(4));
+        assert!(link.send(Msg::Decrement));
+        comp.poll_commands();
+        assert_eq!(comp.model::<Counter>(), &Counter(3));
+    }
+    #[test]
+    fn rebuilt_view_keeps_comps() {
+        use crate::builder::*;
+
+        struct Parent(i32);
+
+        impl Model for Parent {
+            type Message = i32;
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Parent(0)
+            }
+
+            fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
+                self.0 = msg;
+                ChangeView::Rebuild.into()
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                group()
+                    .child(comp_of::<Counter>(self.0).id("props"))
+                    .child(comp(Counter::create(self.0)).id("model"))
+                    .build()
+            }
+        }
+
+        let mut comp = Comp::new(Parent::create(()));
+        comp.update_view();
+        for id in ["props", "model"] {
+            let child = comp.find_by_id_mut(id).and_then(|node| node.into_comp()).unwrap();
+            child.send::<Counter>(Msg::Increment);
+        }
+
+        comp.send::<Parent>(10);
+        comp.update_view();
+        let model = |id| {
+            comp.find_by_id(id)
+                .and_then(|node| node.as_comp())
+                .unwrap()
+                .model::<Counter>()
+        };
+        assert_eq!(model("props"), &Counter(10));
+        assert_eq!(model("model"), &Counter(1));
+    }
 }
@@ -0,0 +1,5 @@
+//! Built-in components, which are made of the primitives of the builder.
+
+pub use self::virtual_list::*;
+
+pub mod virtual_list;
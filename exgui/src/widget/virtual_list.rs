@@ -0,0 +1,189 @@
+use std::{ops::Range, rc::Rc};
+
+use crate::{
+    builder::{rect, scroll_area, Builder, Entity, EventHandler, Primitive},
+    Callback, ChangeView, Command, Model, Node, Real, Transform,
+};
+
+/// Builds the view of the item by its index.
+pub type ItemView<T> = Rc<dyn Fn(usize, &T) -> Node<VirtualList<T>>>;
+
+pub struct VirtualListProps<T: 'static> {
+    pub items: Rc<Vec<T>>,
+    /// Height of each item, the items are laid out from the top of the list.
+    pub item_height: Real,
+    pub width: Real,
+    pub height: Real,
+    /// Count of the items, which are built beyond each edge of the viewport, so that the fast scroll does not show
+    /// the empty space.
+    pub overscan: usize,
+    pub item_view: ItemView<T>,
+    pub on_select: Option<Callback<usize>>,
+}
+
+impl<T: 'static> VirtualListProps<T> {
+    pub fn new(
+        items: Rc<Vec<T>>, item_height: Real, width: Real, height: Real,
+        item_view: impl Fn(usize, &T) -> Node<VirtualList<T>> + 'static,
+    ) -> Self {
+        Self {
+            items,
+            item_height,
+            width,
+            height,
+            overscan: 2,
+            item_view: Rc::new(item_view),
+            on_select: None,
+        }
+    }
+
+    pub fn overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    pub fn on_select(mut self, on_select: Callback<usize>) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+}
+
+pub enum VirtualListMsg {
+    Scroll(Real),
+    Select(usize),
+}
+
+/// List in the scroll area, which builds only the items in the viewport. The view is rebuilt when the scroll moves
+/// the other items into the viewport, and the patch of the view reuses the nodes of the remaining items.
+pub struct VirtualList<T: 'static> {
+    props: VirtualListProps<T>,
+    offset: Real,
+}
+
+impl<T: 'static> VirtualList<T> {
+    /// Indices of the items, which are built for the current scroll offset.
+    pub fn visible_range(&self) -> Range<usize> {
+        let props = &self.props;
+        if props.item_height <= 0.0 {
+            return 0..0;
+        }
+        let first = (self.offset / props.item_height).floor().max(0.0) as usize;
+        let last = ((self.offset + props.height) / props.item_height).ceil().max(0.0) as usize;
+        let len = props.items.len();
+        first.saturating_sub(props.overscan).min(len)..(last + props.overscan).min(len)
+    }
+
+    pub fn offset(&self) -> Real {
+        self.offset
+    }
+}
+
+impl<T: 'static> Model for VirtualList<T> {
+    type Message = VirtualListMsg;
+    type Properties = VirtualListProps<T>;
+
+    fn create(props: Self::Properties) -> Self {
+        Self { props, offset: 0.0 }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
+        match msg {
+            VirtualListMsg::Scroll(offset) => {
+                let range = self.visible_range();
+                self.offset = offset;
+                if self.visible_range() != range {
+                    return ChangeView::Rebuild.into();
+                }
+            }
+            VirtualListMsg::Select(idx) => {
+                if let Some(on_select) = &self.props.on_select {
+                    on_select.emit(idx);
+                }
+            }
+        }
+        ChangeView::None.into()
+    }
+
+    /// Keeps the scroll offset, so the new items do not reset the list to the top.
+    fn change(&mut self, props: Self::Properties) -> bool {
+        self.props = props;
+        true
+    }
+
+    fn build_view(&self) -> Node<Self> {
+        let props = &self.props;
+        let content_height = props.items.len() as Real * props.item_height;
+        let spacer = rect().width(props.width).height(content_height);
+
+        scroll_area(0.0, 0.0, props.width, props.height)
+            .child(spacer)
+            .children_for(self.visible_range(), |idx| {
+                rect()
+                    .width(props.width)
+                    .height(props.item_height)
+                    .transform(Transform::new().with_translation(0.0, idx as Real * props.item_height))
+                    .child((props.item_view)(idx, &props.items[idx]))
+                    .on_click_with(idx, |idx, _| VirtualListMsg::Select(idx))
+            })
+            .on_scroll(|on| VirtualListMsg::Scroll(on.event.offset.1))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::comp_of, Comp, NodeMut};
+
+    #[test]
+    fn visible_range_follows_offset() {
+        let items = Rc::new((0..100).collect::<Vec<u32>>());
+        let props = VirtualListProps::new(items, 20.0, 100.0, 50.0, |_, _| rect().build()).overscan(1);
+        let mut list = VirtualList::create(props);
+        assert_eq!(list.visible_range(), 0..4);
+
+        list.update(VirtualListMsg::Scroll(30.0));
+        assert_eq!(list.visible_range(), 0..5);
+
+        list.update(VirtualListMsg::Scroll(1960.0));
+        assert_eq!(list.visible_range(), 97..100);
+        assert_eq!(list.build_view().as_prim().unwrap().children.len(), 4);
+    }
+
+    struct Feed(u32);
+
+    impl Model for Feed {
+        type Message = u32;
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Feed(10)
+        }
+
+        fn update(&mut self, len: Self::Message) -> Command<Self::Message> {
+            self.0 = len;
+            ChangeView::Rebuild.into()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            let items = Rc::new((0..self.0).collect::<Vec<u32>>());
+            let props = VirtualListProps::new(items, 20.0, 100.0, 50.0, |_, _| rect().build());
+            rect().child(comp_of::<VirtualList<u32>>(props).id("list")).build()
+        }
+    }
+
+    #[test]
+    fn keep_offset_on_parent_rebuild() {
+        let mut comp = Comp::new(Feed::create(()));
+        comp.update_view();
+        let list = comp.find_by_id_mut("list").and_then(NodeMut::into_comp).unwrap();
+        list.send::<VirtualList<u32>>(VirtualListMsg::Scroll(60.0));
+
+        comp.send::<Feed>(20);
+        comp.update_view();
+        let list = comp.find_by_id("list").and_then(|node| node.as_comp()).unwrap();
+        let list = list.model::<VirtualList<u32>>();
+        assert_eq!(list.offset(), 60.0);
+        assert_eq!(list.props.items.len(), 20);
+    }
+}
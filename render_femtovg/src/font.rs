@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use exgui_core::{FontStyle, FontWeight};
+use femtovg::{FontId, TextContext};
+use font_kit::{
+    family_name::FamilyName,
+    handle::Handle,
+    properties::{Properties, Style, Weight},
+    source::SystemSource,
+};
+
+use crate::FemtovgRenderError;
+
+/// Resolves the font names of the texts to the fonts of the femtovg text context. Femtovg identifies the fonts by
+/// ids, so the cache keeps the names of the loaded fonts. The names which are not loaded by the render are looked up
+/// as the system font families, the CSS generic families like `sans-serif` or `monospace` are supported too. If the
+/// font is not found, the fallback fonts are tried in order.
+#[derive(Debug)]
+pub struct FontCache {
+    pub fallbacks: Vec<String>,
+    /// Loaded and resolved fonts by the names.
+    fonts: HashMap<String, FontId>,
+    /// Loaded faces of the font families.
+    faces: HashMap<String, Vec<FontFace>>,
+    matched_faces: HashMap<(String, FontWeight, FontStyle), FaceMatch>,
+}
+
+#[derive(Debug, Clone)]
+struct FontFace {
+    name: String,
+    weight: FontWeight,
+    style: FontStyle,
+}
+
+/// Font face found for the requested weight and style. If the font family has no such face, the closest face is
+/// used and the missing bold or italic is synthesized by the render.
+#[derive(Debug, Clone)]
+pub struct FaceMatch {
+    pub name: String,
+    pub synthetic_bold: bool,
+    pub synthetic_italic: bool,
+}
+
+impl Default for FontCache {
+    fn default() -> Self {
+        Self {
+            fallbacks: vec!["sans-serif".to_string()],
+            fonts: HashMap::new(),
+            faces: HashMap::new(),
+            matched_faces: HashMap::new(),
+        }
+    }
+}
+
+impl FontCache {
+    /// Registers the font loaded into the text context under the name.
+    pub fn add(&mut self, name: impl Into<String>, font: FontId) {
+        self.fonts.insert(name.into(), font);
+    }
+
+    pub fn resolve(&mut self, context: &TextContext, name: &str) -> Result<FontId, FemtovgRenderError> {
+        if let Ok(font) = self.find(name) {
+            return Ok(font);
+        }
+        if let Some(font) = load_system_font(context, name) {
+            self.add(name, font);
+            return Ok(font);
+        }
+
+        for fallback in &self.fallbacks {
+            if let Some(font) = self
+                .fonts
+                .get(fallback)
+                .copied()
+                .or_else(|| load_system_font(context, fallback))
+            {
+                self.fonts.insert(fallback.clone(), font);
+                self.fonts.insert(name.to_string(), font);
+                return Ok(font);
+            }
+        }
+        Err(FemtovgRenderError::FontNotFound(name.to_string()))
+    }
+
+    /// Finds the font, that is already loaded or resolved.
+    pub fn find(&self, name: &str) -> Result<FontId, FemtovgRenderError> {
+        self.fonts
+            .get(name)
+            .copied()
+            .ok_or_else(|| FemtovgRenderError::FontNotFound(name.to_string()))
+    }
+
+    /// Registers the loaded font as the face of the font family.
+    pub fn add_face(
+        &mut self, family: impl Into<String>, weight: FontWeight, style: FontStyle, name: impl Into<String>,
+    ) {
+        let family = family.into();
+        self.matched_faces
+            .retain(|(matched_family, ..), _| *matched_family != family);
+        self.faces.entry(family).or_default().push(FontFace {
+            name: name.into(),
+            weight,
+            style,
+        });
+    }
+
+    /// Resolves the face of the font family. The loaded faces go first, then the system faces. If the family has no
+    /// face of the weight and style, the closest face is matched.
+    pub fn resolve_face(
+        &mut self, context: &TextContext, family: &str, weight: FontWeight, style: FontStyle,
+    ) -> Result<(FontId, FaceMatch), FemtovgRenderError> {
+        let key = (family.to_string(), weight, style);
+        if let Some(face_match) = self.matched_faces.get(&key) {
+            return Ok((self.find(&face_match.name)?, face_match.clone()));
+        }
+
+        let faces = self.faces.get(family).map(|faces| faces.as_slice()).unwrap_or_default();
+        let exact = faces.iter().find(|face| face.weight == weight && face.style == style);
+        let face = if let Some(face) = exact {
+            face.clone()
+        } else if let Some((face, font)) = load_system_face(context, family, weight, style) {
+            self.fonts.insert(face.name.clone(), font);
+            self.faces.entry(family.to_string()).or_default().push(face.clone());
+            face
+        } else if let Some(face) = faces.iter().min_by_key(|face| {
+            (
+                u16::from(face.style != style),
+                (i32::from(face.weight.0) - i32::from(weight.0)).abs(),
+            )
+        }) {
+            face.clone()
+        } else {
+            self.resolve(context, family)?;
+            FontFace {
+                name: family.to_string(),
+                weight: FontWeight::NORMAL,
+                style: FontStyle::Normal,
+            }
+        };
+
+        let face_match = FaceMatch {
+            synthetic_bold: weight.is_bold() && !face.weight.is_bold(),
+            synthetic_italic: style != FontStyle::Normal && face.style == FontStyle::Normal,
+            name: face.name,
+        };
+        self.matched_faces.insert(key, face_match.clone());
+        Ok((self.find(&face_match.name)?, face_match))
+    }
+
+    /// Finds the face, that is already resolved.
+    pub fn find_face(
+        &self, family: &str, weight: FontWeight, style: FontStyle,
+    ) -> Result<(FontId, FaceMatch), FemtovgRenderError> {
+        let face_match = self
+            .matched_faces
+            .get(&(family.to_string(), weight, style))
+            .cloned()
+            .unwrap_or_else(|| FaceMatch {
+                name: family.to_string(),
+                synthetic_bold: false,
+                synthetic_italic: false,
+            });
+        Ok((self.find(&face_match.name)?, face_match))
+    }
+}
+
+fn family_name(name: &str) -> FamilyName {
+    match name {
+        "serif" => FamilyName::Serif,
+        "sans-serif" => FamilyName::SansSerif,
+        "monospace" => FamilyName::Monospace,
+        "cursive" => FamilyName::Cursive,
+        "fantasy" => FamilyName::Fantasy,
+        _ => FamilyName::Title(name.to_string()),
+    }
+}
+
+/// Loads the best match of the system font family.
+fn load_system_font(context: &TextContext, name: &str) -> Option<FontId> {
+    let handle = SystemSource::new()
+        .select_best_match(&[family_name(name)], &Properties::new())
+        .ok()?;
+    load_handle(context, handle)
+}
+
+/// Loads the system face of the font family, if the family has the face of the weight and style.
+fn load_system_face(
+    context: &TextContext, family: &str, weight: FontWeight, style: FontStyle,
+) -> Option<(FontFace, FontId)> {
+    if weight == FontWeight::NORMAL && style == FontStyle::Normal {
+        return None;
+    }
+
+    let source = SystemSource::new();
+    let properties = Properties {
+        weight: Weight(f32::from(weight.0)),
+        style: match style {
+            FontStyle::Normal => Style::Normal,
+            FontStyle::Italic => Style::Italic,
+            FontStyle::Oblique => Style::Oblique,
+        },
+        ..Properties::new()
+    };
+    let handle = source.select_best_match(&[family_name(family)], &properties).ok()?;
+    // The best match is the regular face, if the family has no face of the weight and style.
+    let regular = source
+        .select_best_match(&[family_name(family)], &Properties::new())
+        .ok()?;
+    if same_handle(&handle, &regular) {
+        return None;
+    }
+
+    let font = load_handle(context, handle)?;
+    let name = format!("{}:{}:{:?}", family, weight.0, style);
+    Some((FontFace { name, weight, style }, font))
+}
+
+fn load_handle(context: &TextContext, handle: Handle) -> Option<FontId> {
+    match handle {
+        Handle::Path { path, .. } => context.add_font_file(path).ok(),
+        // Femtovg copies the font data, so the data of the handle are not kept.
+        Handle::Memory { bytes, .. } => context.add_font_mem(&bytes).ok(),
+    }
+}
+
+fn same_handle(handle: &Handle, other: &Handle) -> bool {
+    match (handle, other) {
+        (
+            Handle::Path { path, font_index },
+            Handle::Path {
+                path: other_path,
+                font_index: other_index,
+            },
+        ) => path == other_path && font_index == other_index,
+        (
+            Handle::Memory { bytes, font_index },
+            Handle::Memory {
+                bytes: other_bytes,
+                font_index: other_index,
+            },
+        ) => bytes == other_bytes && font_index == other_index,
+        _ => false,
+    }
+}
@@ -0,0 +1,1516 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    f32::consts::PI,
+    ffi::c_void,
+    fmt, mem,
+    path::Path,
+    rc::Rc,
+    time::Instant,
+};
+
+use exgui_core::{
+    apply_filters, arc_to_cub_beziers, recalc_in_view, AlignHor, AlignVer, BoundingBox, Clip, Color, CompositeShape,
+    Damage, Fill, FillRule, FontStyle, FontWeight, GlyphPos, Gradient, Group, LayerState, LayoutMeasurer, LineCap,
+    LineJoin, Paint, PathCommand, Real, Render, RenderStats, Scissor, Shape, ShapeDefaults, Stroke, Text, TextCache,
+    TextFace, TextMeasurer, TextMetrics, TextSpan, Theme, Transform, TransformMatrix,
+};
+use femtovg::{
+    imgref::{ImgRef, ImgVec},
+    renderer::OpenGl,
+    rgb::RGBA8,
+    Align, Baseline, Canvas, Color as FemtovgColor, CompositeOperation, ErrorKind, FillRule as FemtovgFillRule, FontId,
+    FontMetrics, ImageFlags, ImageId, LineCap as FemtovgLineCap, LineJoin as FemtovgLineJoin, Paint as FemtovgPaint,
+    Path as FemtovgPath, PixelFormat, RenderTarget, Solidity, TextContext, TextMetrics as FemtovgTextMetrics,
+    Transform2D,
+};
+
+pub use self::font::{FaceMatch, FontCache};
+
+mod font;
+
+/// Conic gradients are drawn by the image patterns, because femtovg does not support them.
+const CONIC_IMAGE_SIZE: usize = 256;
+const CARET_WIDTH: Real = 1.5;
+/// Horizontal shift of the synthetic bold overdraw, relative to the font size.
+const SYNTHETIC_BOLD_SHIFT: f32 = 0.04;
+/// Slant of the synthetic italic.
+const SYNTHETIC_ITALIC_SKEW: Real = 0.2;
+
+struct ToFemtovgPaint<'a>(&'a Paint, &'a ImageCache, BoundingBox);
+
+impl<'a> ToFemtovgPaint<'a> {
+    fn to_femtovg_color(color: Color) -> FemtovgColor {
+        let [r, g, b, a] = color.as_arr();
+        FemtovgColor::rgbaf(r, g, b, a)
+    }
+
+    fn to_femtovg_gradient(gradient: &Gradient) -> FemtovgPaint {
+        match *gradient {
+            Gradient::Linear {
+                start: (start_x, start_y),
+                end: (end_x, end_y),
+                start_color,
+                end_color,
+            } => FemtovgPaint::linear_gradient(
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                Self::to_femtovg_color(start_color),
+                Self::to_femtovg_color(end_color),
+            ),
+            Gradient::Box {
+                position: (x, y),
+                size: (width, height),
+                radius,
+                feather,
+                start_color,
+                end_color,
+            } => FemtovgPaint::box_gradient(
+                x,
+                y,
+                width,
+                height,
+                radius,
+                feather,
+                Self::to_femtovg_color(start_color),
+                Self::to_femtovg_color(end_color),
+            ),
+            Gradient::Radial {
+                center: (x, y),
+                inner_radius,
+                outer_radius,
+                start_color,
+                end_color,
+            } => FemtovgPaint::radial_gradient(
+                x,
+                y,
+                inner_radius,
+                outer_radius,
+                Self::to_femtovg_color(start_color),
+                Self::to_femtovg_color(end_color),
+            ),
+            Gradient::Conic { .. } => unreachable!("The Conic gradient is drawn by the image pattern"),
+        }
+    }
+
+    fn conic_image_key(stops: &[(Real, Color)]) -> String {
+        format!("conic:{:?}", stops)
+    }
+
+    fn conic_image_data(stops: &[(Real, Color)]) -> Vec<RGBA8> {
+        let half_size = CONIC_IMAGE_SIZE as f32 / 2.0;
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (0..CONIC_IMAGE_SIZE * CONIC_IMAGE_SIZE)
+            .map(|idx| {
+                let x = (idx % CONIC_IMAGE_SIZE) as f32 + 0.5 - half_size;
+                let y = (idx / CONIC_IMAGE_SIZE) as f32 + 0.5 - half_size;
+                let offset = y.atan2(x).rem_euclid(2.0 * PI) / (2.0 * PI);
+                let [r, g, b, a] = Gradient::stops_color(stops, offset as Real).as_arr();
+                RGBA8::new(to_byte(r), to_byte(g), to_byte(b), to_byte(a))
+            })
+            .collect()
+    }
+
+    /// The pattern is centered at the gradient center and covers the shape bound.
+    fn conic_pattern(&self, center: (Real, Real), angle: Real, stops: &[(Real, Color)]) -> Option<FemtovgPaint> {
+        let image = *self.1.get(&Self::conic_image_key(stops))?;
+        let (cx, cy) = (center.0, center.1);
+        let bound = self.2;
+        let radius = [
+            (bound.min_x, bound.min_y),
+            (bound.min_x, bound.max_y),
+            (bound.max_x, bound.min_y),
+            (bound.max_x, bound.max_y),
+        ]
+        .iter()
+        .map(|(x, y)| ((x - cx) * (x - cx) + (y - cy) * (y - cy)).sqrt())
+        .fold(1.0, f32::max);
+
+        // The pattern rotates around its origin, so the origin is moved to keep the image center in place.
+        let (sin, cos) = angle.sin_cos();
+        Some(FemtovgPaint::image(
+            image,
+            cx - radius * (cos - sin),
+            cy - radius * (sin + cos),
+            2.0 * radius,
+            2.0 * radius,
+            angle,
+            1.0,
+        ))
+    }
+
+    /// Returns `None` if the image of the conic gradient is not created.
+    fn paint(&self) -> Option<FemtovgPaint> {
+        match *self.0 {
+            Paint::Color(color) => Some(FemtovgPaint::color(Self::to_femtovg_color(color))),
+            Paint::Gradient(Gradient::Conic {
+                center,
+                angle,
+                ref stops,
+            }) => self.conic_pattern(center, angle, stops),
+            Paint::Gradient(ref gradient) => Some(Self::to_femtovg_gradient(gradient)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FemtovgRenderError {
+    ContextIsNotInit,
+    InitFemtovgContextFailed(ErrorKind),
+    CreateFontError(ErrorKind, String),
+    CreateImageError(ErrorKind, String),
+    FontNotFound(String),
+    TextError(ErrorKind),
+    ReadLayerError(ErrorKind),
+}
+
+pub type ImageCache = HashMap<String, ImageId>;
+
+/// Offscreen images of the layer groups for the current render, keyed by the group address.
+type LayerCache = HashMap<*const Group, Layer>;
+
+struct Layer {
+    image: ImageId,
+    size: (f32, f32),
+    /// State of the cached group, its layer is kept for the next renders.
+    state: Option<LayerState>,
+}
+
+/// Offscreen images of the shapes with the circle and path clips in the current render.
+struct ClipMasks {
+    /// Render target, which the shapes are drawn to now.
+    target: RenderTarget,
+    /// Size of the render targets in the logical pixels.
+    size: (f32, f32),
+    /// The images are deleted after the drawing is flushed.
+    images: Vec<ImageId>,
+}
+
+impl ClipMasks {
+    fn new(target: RenderTarget, size: (f32, f32)) -> Self {
+        Self {
+            target,
+            size,
+            images: vec![],
+        }
+    }
+
+    fn delete(self, canvas: &mut Canvas<OpenGl>) {
+        for image in self.images {
+            canvas.delete_image(image);
+        }
+    }
+}
+
+/// Canvas state, which the shape is drawn with.
+struct DrawOptions {
+    alpha: f32,
+    /// Femtovg clips by the scissors only, so the shapes are clipped by the bounding scissors of the circle and path
+    /// clips, and the shapes with such clips are masked by the clip regions.
+    scissor: Option<Scissor>,
+    transform: Option<TransformMatrix>,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        Self {
+            alpha: 1.0,
+            scissor: None,
+            transform: None,
+        }
+    }
+}
+
+/// The render draws by femtovg over the OpenGL context. The text is measured by the text context, which the canvas
+/// shares, so the texts can be measured before the canvas is created. The layout is calculated by the shared
+/// `exgui_core::recalc`.
+#[derive(Default)]
+pub struct FemtovgRender {
+    pub canvas: Option<Canvas<OpenGl>>,
+    pub text_context: TextContext,
+    pub images: ImageCache,
+    pub fonts: FontCache,
+    /// Measured text lines of the layout, it is cleared when the fonts are loaded.
+    pub text_cache: TextCache,
+    pub width: f32,
+    pub height: f32,
+    pub device_pixel_ratio: f32,
+    pub theme: Rc<Theme>,
+    theme_changed: bool,
+    view_transform: TransformMatrix,
+    view_changed: bool,
+    /// Layers of the cached groups from the last render.
+    layers: LayerCache,
+    stats: RenderStats,
+}
+
+impl fmt::Debug for FemtovgRender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(FemtovgRender))
+            .field("canvas", &self.canvas.as_ref().map(|_| "..."))
+            .field("images", &self.images)
+            .field("fonts", &self.fonts)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("device_pixel_ratio", &self.device_pixel_ratio)
+            .field("theme", &self.theme)
+            .field("view_transform", &self.view_transform)
+            .field("layers", &self.layers.len())
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl Render for FemtovgRender {
+    type Error = FemtovgRenderError;
+
+    /// The canvas can not be created without the GL loader, so it has to be set by the `new`, the `with_canvas` or
+    /// the `init_gl`.
+    fn init(&mut self, _background_color: Color) -> Result<(), Self::Error> {
+        self.canvas
+            .as_ref()
+            .map(|_| ())
+            .ok_or(FemtovgRenderError::ContextIsNotInit)
+    }
+
+    fn init_gl(
+        &mut self, _background_color: Color, loader: &mut dyn FnMut(&str) -> *const c_void,
+    ) -> Result<(), Self::Error> {
+        if self.canvas.is_none() {
+            let renderer = unsafe { OpenGl::new_from_function(|symbol| loader(symbol)) }
+                .map_err(FemtovgRenderError::InitFemtovgContextFailed)?;
+            self.canvas = Some(Self::create_canvas(renderer, &self.text_context)?);
+        }
+        Ok(())
+    }
+
+    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
+        let dimensions = (physical_width as f32, physical_height as f32, device_pixel_ratio as f32);
+        if dimensions != (self.width, self.height, self.device_pixel_ratio) {
+            // The layers have the size of the window.
+            if let Some(canvas) = self.canvas.as_mut() {
+                for (_, layer) in self.layers.drain() {
+                    canvas.delete_image(layer.image);
+                }
+            }
+        }
+        self.width = dimensions.0;
+        self.height = dimensions.1;
+        self.device_pixel_ratio = dimensions.2;
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn set_view_transform(&mut self, transform: TransformMatrix) {
+        if transform != self.view_transform {
+            self.view_transform = transform;
+            self.view_changed = true;
+        }
+    }
+
+    fn view_transform(&self) -> TransformMatrix {
+        self.view_transform
+    }
+
+    fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let view_changed = mem::take(&mut self.view_changed);
+        let need_recalc = node.need_recalc().unwrap_or(true) || theme_changed || view_changed;
+        let need_redraw = node.need_redraw().unwrap_or(true) || theme_changed || view_changed;
+        let (width, height) = (self.width, self.height);
+        let device_pixel_ratio = self.device_pixel_ratio;
+        // The layout is calculated in the logical pixels, the canvas scales them by the device pixel ratio.
+        let logical_size = (width / device_pixel_ratio, height / device_pixel_ratio);
+        let mut stats = RenderStats::default();
+        if need_recalc {
+            let start = Instant::now();
+            let bound = BoundingBox {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: logical_size.0 as Real,
+                max_y: logical_size.1 as Real,
+            };
+            let theme = self.theme.clone();
+            recalc_in_view(self, node, bound, self.view_transform, theme, &mut Damage::new())?;
+            stats.recalc_time = start.elapsed();
+        }
+        let theme = &self.theme;
+        let canvas = self.canvas.as_mut().ok_or(FemtovgRenderError::ContextIsNotInit)?;
+        let images = &mut self.images;
+        let fonts = &mut self.fonts;
+        canvas.set_size(width as u32, height as u32, device_pixel_ratio);
+        canvas.reset_transform();
+        canvas.scale(device_pixel_ratio, device_pixel_ratio);
+        if need_redraw {
+            let start = Instant::now();
+            // The layers are drawn into the own render targets before the main pass.
+            let mut last_layers = mem::take(&mut self.layers);
+            let mut layers = LayerCache::new();
+            let mut masks = ClipMasks::new(RenderTarget::Screen, logical_size);
+            let result = Self::render_layers(
+                canvas,
+                images,
+                fonts,
+                &mut last_layers,
+                &mut layers,
+                &mut stats,
+                node,
+                logical_size,
+                device_pixel_ratio,
+                &mut ShapeDefaults::new(theme.clone()),
+            )
+            .and_then(|_| {
+                let mut defaults = ShapeDefaults::new(theme.clone());
+                Self::render_composite(
+                    canvas,
+                    images,
+                    fonts,
+                    &layers,
+                    &mut masks,
+                    &mut stats,
+                    node,
+                    None,
+                    &mut defaults,
+                )?;
+                // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+                for portal in node.portals() {
+                    let mut defaults = ShapeDefaults::new(theme.clone());
+                    Self::render_composite(
+                        canvas,
+                        images,
+                        fonts,
+                        &layers,
+                        &mut masks,
+                        &mut stats,
+                        portal,
+                        None,
+                        &mut defaults,
+                    )?;
+                }
+                Ok(())
+            });
+            canvas.flush();
+            masks.delete(canvas);
+            // The layers of the cached groups are kept for the next render.
+            for (key, layer) in layers {
+                if layer.state.is_some() {
+                    self.layers.insert(key, layer);
+                } else {
+                    canvas.delete_image(layer.image);
+                }
+            }
+            for layer in last_layers.into_values() {
+                canvas.delete_image(layer.image);
+            }
+            result?;
+            stats.draw_time = start.elapsed();
+        }
+        self.stats = stats;
+        Ok(need_redraw)
+    }
+
+    fn stats(&self) -> Option<&RenderStats> {
+        Some(&self.stats)
+    }
+}
+
+impl TextMeasurer for FemtovgRender {
+    type Error = FemtovgRenderError;
+
+    /// Measures the text by the text context, so the canvas is not needed.
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let font = self.fonts.resolve(&self.text_context, font_name)?;
+        self.measure_font(font, text, font_size)
+    }
+
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        let (font, _) = self
+            .fonts
+            .resolve_face(&self.text_context, face.family, face.weight, face.style)?;
+        self.measure_font(font, text, font_size)
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let (font, _) = self
+            .fonts
+            .resolve_face(&self.text_context, face.family, face.weight, face.style)?;
+        let text_metrics = self
+            .text_context
+            .measure_text(0.0, 0.0, text, &Self::measure_paint(font, font_size))
+            .map_err(FemtovgRenderError::TextError)?;
+        Ok(Self::char_positions(text, &text_metrics))
+    }
+}
+
+impl LayoutMeasurer for FemtovgRender {
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error> {
+        let canvas = self.canvas.as_mut().ok_or(FemtovgRenderError::ContextIsNotInit)?;
+        let image = Self::cached_image(canvas, &mut self.images, src)?;
+        let (width, height) = canvas
+            .image_size(image)
+            .map_err(|e| FemtovgRenderError::CreateImageError(e, src.to_string()))?;
+        Ok((width as Real, height as Real))
+    }
+
+    fn prepare_shape(&mut self, shape: &Shape, defaults: &ShapeDefaults) -> Result<(), Self::Error> {
+        let canvas = self.canvas.as_mut().ok_or(FemtovgRenderError::ContextIsNotInit)?;
+        Self::cached_conic_images(canvas, &mut self.images, shape, defaults)
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut self.text_cache)
+    }
+}
+
+impl FemtovgRender {
+    pub fn new(renderer: OpenGl, width: f32, height: f32, device_pixel_ratio: f32) -> Result<Self, FemtovgRenderError> {
+        let text_context = TextContext::default();
+        Ok(Self {
+            canvas: Some(Self::create_canvas(renderer, &text_context)?),
+            text_context,
+            images: ImageCache::new(),
+            fonts: FontCache::default(),
+            text_cache: TextCache::new(),
+            width,
+            height,
+            device_pixel_ratio,
+            theme: Rc::new(Theme::default()),
+            theme_changed: false,
+            view_transform: TransformMatrix::identity(),
+            view_changed: false,
+            layers: LayerCache::new(),
+            stats: RenderStats::default(),
+        })
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
+
+    /// Sets the canvas, which has to be created with the text context of the render.
+    pub fn with_canvas(mut self, canvas: Canvas<OpenGl>) -> Self {
+        self.canvas = Some(canvas);
+        self
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn with_device_pixel_ratio(mut self, device_pixel_ratio: f32) -> Self {
+        self.device_pixel_ratio = device_pixel_ratio;
+        self
+    }
+
+    pub fn load_font(
+        &mut self, name: impl Into<String>, path: impl AsRef<Path>,
+    ) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        let font = self
+            .text_context
+            .add_font_file(path.as_ref())
+            .map_err(|e| FemtovgRenderError::CreateFontError(e, display_path))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`.
+    pub fn load_font_from_memory(
+        &mut self, name: impl Into<String>, data: &'static [u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        let name = name.into();
+        let font = self
+            .text_context
+            .add_font_mem(data)
+            .map_err(|e| FemtovgRenderError::CreateFontError(e, name.clone()))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    pub fn load_image(&mut self, src: impl Into<String>) -> Result<(), <Self as Render>::Error> {
+        let canvas = self.canvas.as_mut().ok_or(FemtovgRenderError::ContextIsNotInit)?;
+        Self::cached_image(canvas, &mut self.images, &src.into())?;
+        Ok(())
+    }
+
+    fn create_canvas(renderer: OpenGl, text_context: &TextContext) -> Result<Canvas<OpenGl>, FemtovgRenderError> {
+        Canvas::new_with_text_context(renderer, text_context.clone())
+            .map_err(FemtovgRenderError::InitFemtovgContextFailed)
+    }
+
+    fn cached_image(
+        canvas: &mut Canvas<OpenGl>, images: &mut ImageCache, src: &str,
+    ) -> Result<ImageId, FemtovgRenderError> {
+        if let Some(image) = images.get(src) {
+            return Ok(*image);
+        }
+        let image = canvas
+            .load_image_file(src, ImageFlags::empty())
+            .map_err(|e| FemtovgRenderError::CreateImageError(e, src.to_string()))?;
+        images.insert(src.to_string(), image);
+        Ok(image)
+    }
+
+    fn cached_conic_images(
+        canvas: &mut Canvas<OpenGl>, images: &mut ImageCache, shape: &Shape, defaults: &ShapeDefaults,
+    ) -> Result<(), FemtovgRenderError> {
+        let mut paints = vec![
+            shape.fill().or(defaults.fill.as_ref()).map(|fill| fill.paint.clone()),
+            shape
+                .stroke()
+                .or(defaults.stroke.as_ref())
+                .map(|stroke| stroke.paint.clone()),
+        ];
+        if let Shape::Custom(custom) = shape {
+            for custom_path in custom.shape.draw() {
+                paints.push(custom_path.fill.map(|fill| fill.paint));
+                paints.push(custom_path.stroke.map(|stroke| stroke.paint));
+            }
+        }
+
+        for paint in paints.into_iter().flatten() {
+            if let Paint::Gradient(Gradient::Conic { stops, .. }) = paint {
+                let key = ToFemtovgPaint::conic_image_key(&stops);
+                if let Entry::Vacant(entry) = images.entry(key) {
+                    let data = ToFemtovgPaint::conic_image_data(&stops);
+                    let image = canvas
+                        .create_image(
+                            ImgRef::new(&data, CONIC_IMAGE_SIZE, CONIC_IMAGE_SIZE),
+                            ImageFlags::empty(),
+                        )
+                        .map_err(|e| FemtovgRenderError::CreateImageError(e, entry.key().clone()))?;
+                    entry.insert(image);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_composite<'a>(
+        canvas: &mut Canvas<OpenGl>, images: &ImageCache, fonts: &FontCache, layers: &LayerCache,
+        masks: &mut ClipMasks, stats: &mut RenderStats, composite: &'a dyn CompositeShape, text: Option<&'a Text>,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), FemtovgRenderError> {
+        let clip = composite.shape().map(Shape::clip);
+        if !matches!(clip, Some(Clip::Circle(_) | Clip::Path(_))) {
+            return Self::render_shape(canvas, images, fonts, layers, masks, stats, composite, text, defaults);
+        }
+
+        // The shape is drawn into the offscreen image, which is masked by the clip region and drawn to the current
+        // target then. The mask is the clip region filled by the opaque color.
+        let (width, height) = (canvas.width(), canvas.height());
+        let mut create_image = |canvas: &mut Canvas<OpenGl>| {
+            let image = canvas
+                .create_image_empty(
+                    width as usize,
+                    height as usize,
+                    PixelFormat::Rgba8,
+                    ImageFlags::PREMULTIPLIED,
+                )
+                .map_err(|e| FemtovgRenderError::CreateImageError(e, "clip mask".to_string()))?;
+            masks.images.push(image);
+            canvas.set_render_target(RenderTarget::Image(image));
+            canvas.clear_rect(0, 0, width, height, FemtovgColor::rgbaf(0.0, 0.0, 0.0, 0.0));
+            Ok::<_, FemtovgRenderError>(image)
+        };
+        let mask = create_image(canvas)?;
+        if let Some((outline, clip)) = clip.and_then(|clip| Some((clip.outline()?, clip))) {
+            let options = DrawOptions {
+                transform: clip.transform().and_then(Self::transform_matrix),
+                ..Default::default()
+            };
+            let paint = FemtovgPaint::color(FemtovgColor::black());
+            Self::draw_path(canvas, &Self::femtovg_path(&outline), Some(paint), None, &options);
+        }
+        let content = create_image(canvas)?;
+
+        let (target, outer_clip) = (masks.target, defaults.clip.clone());
+        masks.target = RenderTarget::Image(content);
+        let result = Self::render_shape(canvas, images, fonts, layers, masks, stats, composite, text, defaults);
+        masks.target = target;
+
+        let (logical_width, logical_height) = masks.size;
+        let mut rect = FemtovgPath::new();
+        rect.rect(0.0, 0.0, logical_width, logical_height);
+        canvas.save();
+        canvas.global_composite_operation(CompositeOperation::DestinationIn);
+        let mask = FemtovgPaint::image(mask, 0.0, 0.0, logical_width, logical_height, 0.0, 1.0);
+        canvas.fill_path(&rect, &mask);
+        canvas.restore();
+
+        canvas.set_render_target(target);
+        let options = DrawOptions {
+            scissor: outer_clip.bounding_scissor(),
+            ..Default::default()
+        };
+        let content = FemtovgPaint::image(content, 0.0, 0.0, logical_width, logical_height, 0.0, 1.0);
+        Self::draw_path(canvas, &rect, Some(content), None, &options);
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_shape<'a>(
+        canvas: &mut Canvas<OpenGl>, images: &ImageCache, fonts: &FontCache, layers: &LayerCache,
+        masks: &mut ClipMasks, stats: &mut RenderStats, composite: &'a dyn CompositeShape, mut text: Option<&'a Text>,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), FemtovgRenderError> {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        if let Some(shape) = composite.shape() {
+            stats.shapes += 1;
+            match shape {
+                Shape::Rect(rect) => {
+                    let mut path = FemtovgPath::new();
+                    let (x, y) = (rect.x.val(), rect.y.val());
+                    let (width, height) = (rect.width.val(), rect.height.val());
+                    if let Some(rounding) = rect.rounding {
+                        path.rounded_rect_varying(
+                            x,
+                            y,
+                            width,
+                            height,
+                            rounding.top_left.val(),
+                            rounding.top_right.val(),
+                            rounding.bottom_right.val(),
+                            rounding.bottom_left.val(),
+                        );
+                    } else {
+                        path.rect(x, y, width, height);
+                    }
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        Self::fill_paint(rect.fill.as_ref().or(defaults.fill.as_ref()), images, rect.bound),
+                        Self::stroke_paint(rect.stroke.as_ref().or(defaults.stroke.as_ref()), images, rect.bound),
+                        &Self::draw_options(rect.transparency, &rect.clip, &rect.transform, defaults),
+                    );
+                }
+                Shape::Circle(circle) => {
+                    let mut path = FemtovgPath::new();
+                    path.circle(circle.cx.val(), circle.cy.val(), circle.r.val());
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        Self::fill_paint(circle.fill.as_ref().or(defaults.fill.as_ref()), images, circle.bound),
+                        Self::stroke_paint(
+                            circle.stroke.as_ref().or(defaults.stroke.as_ref()),
+                            images,
+                            circle.bound,
+                        ),
+                        &Self::draw_options(circle.transparency, &circle.clip, &circle.transform, defaults),
+                    );
+                }
+                Shape::Ellipse(ellipse) => {
+                    let mut path = FemtovgPath::new();
+                    path.ellipse(
+                        ellipse.cx.val(),
+                        ellipse.cy.val(),
+                        ellipse.rx.val(),
+                        ellipse.ry.val(),
+                    );
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        Self::fill_paint(ellipse.fill.as_ref().or(defaults.fill.as_ref()), images, ellipse.bound),
+                        Self::stroke_paint(
+                            ellipse.stroke.as_ref().or(defaults.stroke.as_ref()),
+                            images,
+                            ellipse.bound,
+                        ),
+                        &Self::draw_options(ellipse.transparency, &ellipse.clip, &ellipse.transform, defaults),
+                    );
+                }
+                Shape::Path(path) => {
+                    Self::draw_path(
+                        canvas,
+                        &Self::femtovg_path(&path.cmd),
+                        Self::fill_paint(path.fill.as_ref().or(defaults.fill.as_ref()), images, path.bound),
+                        Self::stroke_paint(path.stroke.as_ref().or(defaults.stroke.as_ref()), images, path.bound),
+                        &Self::draw_options(path.transparency, &path.clip, &path.transform, defaults),
+                    );
+
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        Self::render_composite(
+                            canvas,
+                            images,
+                            fonts,
+                            layers,
+                            masks,
+                            stats,
+                            marker.shape.as_ref(),
+                            None,
+                            &mut defaults.clone(),
+                        )?;
+                    }
+                }
+                Shape::Polygon(polygon) => {
+                    let mut path = FemtovgPath::new();
+                    for contour in &polygon.contours {
+                        let mut points = contour.iter();
+                        if let Some(first) = points.next() {
+                            path.move_to(first[0], first[1]);
+                            for point in points {
+                                path.line_to(point[0], point[1]);
+                            }
+                            path.close();
+                        }
+                    }
+                    let fill_rule = match polygon.fill_rule {
+                        FillRule::NonZero => FemtovgFillRule::NonZero,
+                        FillRule::EvenOdd => FemtovgFillRule::EvenOdd,
+                    };
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        Self::fill_paint(polygon.fill.as_ref().or(defaults.fill.as_ref()), images, polygon.bound)
+                            .map(|paint| paint.with_fill_rule(fill_rule)),
+                        Self::stroke_paint(
+                            polygon.stroke.as_ref().or(defaults.stroke.as_ref()),
+                            images,
+                            polygon.bound,
+                        ),
+                        &Self::draw_options(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                    );
+                }
+                Shape::Image(image) => {
+                    let mut path = FemtovgPath::new();
+                    let (x, y) = (image.x.val(), image.y.val());
+                    let (width, height) = (image.width.val(), image.height.val());
+                    path.rect(x, y, width, height);
+                    let fill = Self::fill_paint(image.fill.as_ref().or(defaults.fill.as_ref()), images, image.bound);
+                    let pattern = images
+                        .get(&image.src)
+                        .map(|femtovg_image| FemtovgPaint::image(*femtovg_image, x, y, width, height, 0.0, 1.0));
+                    let stroke =
+                        Self::stroke_paint(image.stroke.as_ref().or(defaults.stroke.as_ref()), images, image.bound);
+                    let options = Self::draw_options(image.transparency, &image.clip, &image.transform, defaults);
+                    Self::draw(canvas, &options, |canvas| {
+                        for paint in fill.iter().chain(pattern.iter()) {
+                            canvas.fill_path(&path, paint);
+                        }
+                        if let Some(stroke) = stroke {
+                            canvas.stroke_path(&path, &stroke);
+                        }
+                    });
+                }
+                Shape::Text(this_text) => {
+                    stats.texts += 1;
+                    text = Some(this_text);
+                    Self::render_text(canvas, images, fonts, this_text, defaults)?;
+                }
+                Shape::Custom(custom) => {
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .as_ref()
+                            .or(custom.fill.as_ref())
+                            .or(defaults.fill.as_ref());
+                        let stroke = custom_path
+                            .stroke
+                            .as_ref()
+                            .or(custom.stroke.as_ref())
+                            .or(defaults.stroke.as_ref());
+                        Self::draw_path(
+                            canvas,
+                            &Self::femtovg_path(&custom_path.cmd),
+                            Self::fill_paint(fill, images, custom.bound),
+                            Self::stroke_paint(stroke, images, custom.bound),
+                            &Self::draw_options(custom.transparency, &custom.clip, &custom.transform, defaults),
+                        );
+                    }
+                }
+                Shape::Sector(sector) => {
+                    Self::draw_path(
+                        canvas,
+                        &Self::femtovg_path(&sector.commands()),
+                        Self::fill_paint(sector.fill.as_ref().or(defaults.fill.as_ref()), images, sector.bound),
+                        Self::stroke_paint(
+                            sector.stroke.as_ref().or(defaults.stroke.as_ref()),
+                            images,
+                            sector.bound,
+                        ),
+                        &Self::draw_options(sector.transparency, &sector.clip, &sector.transform, defaults),
+                    );
+                }
+                Shape::Ring(ring) => {
+                    let mut path = Self::femtovg_path(&ring.commands());
+                    if ring.is_full() {
+                        path.solidity(Solidity::Hole);
+                    }
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        Self::fill_paint(ring.fill.as_ref().or(defaults.fill.as_ref()), images, ring.bound),
+                        Self::stroke_paint(ring.stroke.as_ref().or(defaults.stroke.as_ref()), images, ring.bound),
+                        &Self::draw_options(ring.transparency, &ring.clip, &ring.transform, defaults),
+                    );
+                }
+                Shape::Group(group) => {
+                    defaults.apply_group(group);
+                    if group.need_layer() {
+                        if let Some(layer) = layers.get(&(group as *const Group)) {
+                            Self::draw_layer(canvas, layer, defaults);
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(canvas, images, fonts, layers, masks, stats, child, text, defaults)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_text(
+        canvas: &mut Canvas<OpenGl>, images: &ImageCache, fonts: &FontCache, text: &Text, defaults: &ShapeDefaults,
+    ) -> Result<(), FemtovgRenderError> {
+        let options = Self::draw_options(text.transparency, &text.clip, &text.transform, defaults);
+        let input = text.input.as_ref().filter(|input| input.focused);
+        if let Some(input) = input {
+            if let Some(range) = input.selection() {
+                let (start_x, end_x) = (text.caret_x(range.start), text.caret_x(range.end));
+                Self::draw_text_line_rect(canvas, text, start_x, end_x - start_x, input.selection_color, &options);
+            }
+        }
+
+        if let Some(text_path) = &text.path {
+            let (font, face_match) = fonts.find_face(&text.font_name, text.font_weight, text.font_style)?;
+            // Each glyph is drawn from its left edge.
+            let (_, baseline) = Self::text_align(AlignHor::Left, text.align.1);
+            let paint = Self::text_paint(text, font, defaults)
+                .with_text_align(Align::Left)
+                .with_text_baseline(baseline);
+            let text_matrix = text
+                .transform
+                .calculated_matrix()
+                .unwrap_or_else(|| text.transform.matrix());
+
+            let glyphs = text.content.chars().zip(&text.glyph_positions);
+            for ((ch, pos), glyph_transform) in glyphs.zip(&text_path.glyph_transforms) {
+                if let Some(glyph_transform) = glyph_transform {
+                    let glyph_options = DrawOptions {
+                        transform: Some(text_matrix * *glyph_transform),
+                        scissor: options.scissor,
+                        ..options
+                    };
+                    Self::draw_text(
+                        canvas,
+                        &face_match,
+                        (pos.x, text.y.val()),
+                        ch.encode_utf8(&mut [0; 4]),
+                        &paint,
+                        &glyph_options,
+                    )?;
+                }
+            }
+        } else if text.spans.is_empty() {
+            let (font, face_match) = fonts.find_face(&text.font_name, text.font_weight, text.font_style)?;
+            Self::draw_text(
+                canvas,
+                &face_match,
+                (text.x.val(), text.y.val()),
+                &text.content,
+                &Self::text_paint(text, font, defaults),
+                &options,
+            )?;
+        }
+        for span in text.spans.iter().filter(|_| text.path.is_none()) {
+            let (family, weight, style) = Self::span_face(text, span);
+            let (font, face_match) = fonts.find_face(family, weight, style)?;
+            Self::draw_text(
+                canvas,
+                &face_match,
+                (text.x.val() + span.offset, text.y.val()),
+                &span.content,
+                &Self::span_paint(text, span, font, defaults),
+                &options,
+            )?;
+        }
+
+        if let Some(fill) = text.fill.as_ref().or(defaults.fill.as_ref()) {
+            for line in text.decoration_lines() {
+                if let Some(paint) = ToFemtovgPaint(&fill.paint, images, text.bound).paint() {
+                    Self::fill_text_rect(canvas, line, &paint, &options);
+                }
+            }
+        }
+
+        if let Some(input) = input.filter(|input| input.caret_visible) {
+            let caret_x = text.caret_x(input.caret) - CARET_WIDTH / 2.0;
+            Self::draw_text_line_rect(canvas, text, caret_x, CARET_WIDTH, input.caret_color, &options);
+        }
+        Ok(())
+    }
+
+    /// Renders the children of the layer groups into the offscreen images, the nested layers go first. The layers of
+    /// the cached groups, which are not changed, are moved from the last layers. The `size` is in the logical pixels,
+    /// the images have the size in the physical ones.
+    #[allow(clippy::too_many_arguments)]
+    fn render_layers(
+        canvas: &mut Canvas<OpenGl>, images: &ImageCache, fonts: &FontCache, last_layers: &mut LayerCache,
+        layers: &mut LayerCache, stats: &mut RenderStats, composite: &dyn CompositeShape, size: (f32, f32),
+        device_pixel_ratio: f32, defaults: &mut ShapeDefaults,
+    ) -> Result<(), FemtovgRenderError> {
+        let mut portal_defaults = None;
+        let defaults = if composite.is_portal() {
+            portal_defaults.insert(ShapeDefaults::new(defaults.theme.clone()))
+        } else {
+            defaults
+        };
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        let mut layer_group = None;
+        if let Some(Shape::Group(group)) = composite.shape() {
+            defaults.apply_group(group);
+            if group.need_layer() {
+                layer_group = Some(group);
+            }
+        }
+
+        let mut layer_defaults = defaults.clone();
+        // The group transparency is applied once, when the layer is drawn.
+        layer_defaults.transparency = 0.0;
+        let layer_state = layer_group.and_then(|_| LayerState::of(composite, &layer_defaults));
+        if let (Some(group), Some(state)) = (layer_group, &layer_state) {
+            let key = group as *const Group;
+            if let Some(layer) = last_layers.remove(&key) {
+                if layer.state.as_ref() == Some(state) {
+                    stats.cache_hits += 1;
+                    layers.insert(key, layer);
+                    return Ok(());
+                }
+                canvas.delete_image(layer.image);
+            }
+        }
+
+        if let Some(children) = composite.children() {
+            // The layer children are not rendered in the main pass, so they do not change the outer defaults.
+            let mut children_defaults = layer_defaults.clone();
+            let defaults = if layer_group.is_some() {
+                &mut children_defaults
+            } else {
+                &mut *defaults
+            };
+            for child in children {
+                Self::render_layers(
+                    canvas,
+                    images,
+                    fonts,
+                    last_layers,
+                    layers,
+                    stats,
+                    child,
+                    size,
+                    device_pixel_ratio,
+                    defaults,
+                )?;
+            }
+        }
+
+        if let Some(group) = layer_group {
+            let width = (size.0 * device_pixel_ratio).round() as usize;
+            let height = (size.1 * device_pixel_ratio).round() as usize;
+            let target = canvas
+                .create_image_empty(width, height, PixelFormat::Rgba8, ImageFlags::PREMULTIPLIED)
+                .map_err(|e| FemtovgRenderError::CreateImageError(e, format!("layer {:?}", group.id())))?;
+            canvas.set_render_target(RenderTarget::Image(target));
+            canvas.clear_rect(
+                0,
+                0,
+                width as u32,
+                height as u32,
+                FemtovgColor::rgbaf(0.0, 0.0, 0.0, 0.0),
+            );
+            let mut result = Ok(());
+            let mut masks = ClipMasks::new(RenderTarget::Image(target), size);
+            if let Some(children) = composite.children_in_draw_order() {
+                result = children
+                    .into_iter()
+                    .filter(|child| !child.is_portal())
+                    .try_for_each(|child| {
+                        Self::render_composite(
+                            canvas,
+                            images,
+                            fonts,
+                            layers,
+                            &mut masks,
+                            stats,
+                            child,
+                            None,
+                            &mut layer_defaults,
+                        )
+                    });
+            }
+            // The screenshot flushes the drawing into the render target and reads it back.
+            let pixels = canvas.screenshot();
+            canvas.set_render_target(RenderTarget::Screen);
+            canvas.delete_image(target);
+            masks.delete(canvas);
+            result?;
+
+            let pixels = pixels
+                .map_err(FemtovgRenderError::ReadLayerError)?
+                .into_buf()
+                .into_iter()
+                .map(|pixel| u32::from_ne_bytes([pixel.r, pixel.g, pixel.b, pixel.a]))
+                .collect();
+            // The canvas units are the logical pixels, so the filters are scaled by the device pixel ratio.
+            let pixels: Vec<_> = apply_filters(&group.filters, pixels, width, height, device_pixel_ratio)
+                .into_iter()
+                .map(|pixel| {
+                    let [r, g, b, a] = pixel.to_ne_bytes();
+                    RGBA8::new(r, g, b, a)
+                })
+                .collect();
+
+            let image = canvas
+                .create_image(ImgVec::new(pixels, width, height).as_ref(), ImageFlags::PREMULTIPLIED)
+                .map_err(|e| FemtovgRenderError::CreateImageError(e, format!("layer {:?}", group.id())))?;
+            layers.insert(
+                group as *const Group,
+                Layer {
+                    image,
+                    size,
+                    state: layer_state,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn draw_layer(canvas: &mut Canvas<OpenGl>, layer: &Layer, defaults: &ShapeDefaults) {
+        let (width, height) = layer.size;
+        let mut path = FemtovgPath::new();
+        path.rect(0.0, 0.0, width, height);
+        let pattern = FemtovgPaint::image(layer.image, 0.0, 0.0, width, height, 0.0, 1.0);
+        let options = DrawOptions {
+            alpha: 1.0 - defaults.transparency,
+            ..Default::default()
+        };
+        Self::draw_path(canvas, &path, Some(pattern), None, &options);
+    }
+
+    fn femtovg_path(commands: &[PathCommand]) -> FemtovgPath {
+        use exgui_core::PathCommand::*;
+
+        let mut path = FemtovgPath::new();
+        let mut last_xy = [0.0, 0.0];
+        let mut bez_ctrls = [(0.0, 0.0), (0.0, 0.0)];
+
+        for cmd in commands.iter() {
+            match cmd {
+                Move(ref xy) => {
+                    last_xy = *xy;
+                    path.move_to(last_xy[0], last_xy[1]);
+                }
+                MoveRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    path.move_to(last_xy[0], last_xy[1]);
+                }
+                Line(ref xy) => {
+                    last_xy = *xy;
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                LineRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                LineAlonX(ref x) => {
+                    last_xy[0] = *x;
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                LineAlonXRel(ref x) => {
+                    last_xy[0] += *x;
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                LineAlonY(ref y) => {
+                    last_xy[1] = *y;
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                LineAlonYRel(ref y) => {
+                    last_xy[1] += *y;
+                    path.line_to(last_xy[0], last_xy[1]);
+                }
+                Close => path.close(),
+                BezCtrl(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], (xy[0], xy[1])];
+                }
+                BezCtrlRel(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], (last_xy[0] + xy[0], last_xy[1] + xy[1])];
+                }
+                BezReflectCtrl => {
+                    let reflected = (2.0 * last_xy[0] - bez_ctrls[1].0, 2.0 * last_xy[1] - bez_ctrls[1].1);
+                    bez_ctrls = [bez_ctrls[1], reflected];
+                }
+                QuadBezTo(ref xy) => {
+                    last_xy = *xy;
+                    path.quad_to(
+                        bez_ctrls[1].0,
+                        bez_ctrls[1].1,
+                        last_xy[0],
+                        last_xy[1],
+                    );
+                }
+                QuadBezToRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    path.quad_to(
+                        bez_ctrls[1].0,
+                        bez_ctrls[1].1,
+                        last_xy[0],
+                        last_xy[1],
+                    );
+                }
+                CubBezTo(ref xy) => {
+                    last_xy = *xy;
+                    path.bezier_to(
+                        bez_ctrls[0].0,
+                        bez_ctrls[0].1,
+                        bez_ctrls[1].0,
+                        bez_ctrls[1].1,
+                        last_xy[0],
+                        last_xy[1],
+                    );
+                }
+                CubBezToRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    path.bezier_to(
+                        bez_ctrls[0].0,
+                        bez_ctrls[0].1,
+                        bez_ctrls[1].0,
+                        bez_ctrls[1].1,
+                        last_xy[0],
+                        last_xy[1],
+                    );
+                }
+                Arc {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    Self::arc_to(&mut path, last_xy, *radius, *rotation, *large_arc, *sweep, *to);
+                    last_xy = *to;
+                }
+                ArcRel {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let to = [last_xy[0] + to[0], last_xy[1] + to[1]];
+                    Self::arc_to(&mut path, last_xy, *radius, *rotation, *large_arc, *sweep, to);
+                    last_xy = to;
+                }
+            }
+        }
+        path
+    }
+
+    fn arc_to(
+        path: &mut FemtovgPath, from: [Real; 2], radius: [Real; 2], rotation: Real, large_arc: bool, sweep: bool,
+        to: [Real; 2],
+    ) {
+        for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(from, radius, rotation, large_arc, sweep, to) {
+            path.bezier_to(
+                ctrl1[0],
+                ctrl1[1],
+                ctrl2[0],
+                ctrl2[1],
+                xy[0],
+                xy[1],
+            );
+        }
+    }
+
+    fn transform_matrix(transform: &Transform) -> Option<TransformMatrix> {
+        if transform.is_not_exist() {
+            None
+        } else {
+            Some(transform.calculated_matrix().unwrap_or_else(|| transform.matrix()))
+        }
+    }
+
+    fn femtovg_transform(matrix: TransformMatrix) -> Transform2D {
+        let [a, b, c, d, e, f] = matrix.matrix;
+        Transform2D([a, b, c, d, e, f])
+    }
+
+    fn draw_options(transparency: Real, clip: &Clip, transform: &Transform, defaults: &ShapeDefaults) -> DrawOptions {
+        DrawOptions {
+            alpha: (1.0 - transparency) * (1.0 - defaults.transparency),
+            scissor: clip.intersect(&defaults.clip).bounding_scissor(),
+            transform: Self::transform_matrix(transform),
+        }
+    }
+
+    /// Draws by the `draw` in the canvas state of the options, the state is restored after. The transforms of the
+    /// options are applied on top of the base transform of the canvas, which scales the logical pixels.
+    fn draw<R>(canvas: &mut Canvas<OpenGl>, options: &DrawOptions, draw: impl FnOnce(&mut Canvas<OpenGl>) -> R) -> R {
+        canvas.save();
+        canvas.set_global_alpha(options.alpha);
+        let base_transform = canvas.transform();
+        if let Some(scissor) = &options.scissor {
+            // The scissor is transformed by the transform, which is current when it is set.
+            if let Some(matrix) = Self::transform_matrix(&scissor.transform) {
+                canvas.set_transform(&Self::femtovg_transform(matrix));
+            }
+            canvas.scissor(
+                scissor.x.val(),
+                scissor.y.val(),
+                scissor.width.val(),
+                scissor.height.val(),
+            );
+            canvas.reset_transform();
+            canvas.set_transform(&base_transform);
+        }
+        if let Some(matrix) = options.transform {
+            canvas.set_transform(&Self::femtovg_transform(matrix));
+        }
+        let result = draw(canvas);
+        canvas.restore();
+        result
+    }
+
+    fn draw_path(
+        canvas: &mut Canvas<OpenGl>, path: &FemtovgPath, fill: Option<FemtovgPaint>, stroke: Option<FemtovgPaint>,
+        options: &DrawOptions,
+    ) {
+        Self::draw(canvas, options, |canvas| {
+            if let Some(fill) = fill {
+                canvas.fill_path(path, &fill);
+            }
+            if let Some(stroke) = stroke {
+                canvas.stroke_path(path, &stroke);
+            }
+        });
+    }
+
+    fn fill_paint(fill: Option<&Fill>, images: &ImageCache, bound: BoundingBox) -> Option<FemtovgPaint> {
+        ToFemtovgPaint(&fill?.paint, images, bound).paint()
+    }
+
+    fn stroke_paint(stroke: Option<&Stroke>, images: &ImageCache, bound: BoundingBox) -> Option<FemtovgPaint> {
+        let stroke = stroke?;
+        let line_cap = match stroke.line_cap {
+            LineCap::Butt => FemtovgLineCap::Butt,
+            LineCap::Round => FemtovgLineCap::Round,
+            LineCap::Square => FemtovgLineCap::Square,
+        };
+        let line_join = match stroke.line_join {
+            LineJoin::Miter => FemtovgLineJoin::Miter,
+            LineJoin::Round => FemtovgLineJoin::Round,
+            LineJoin::Bevel => FemtovgLineJoin::Bevel,
+        };
+        let paint = ToFemtovgPaint(&stroke.paint, images, bound).paint()?;
+        Some(
+            paint
+                .with_line_width(stroke.width)
+                .with_line_cap(line_cap)
+                .with_line_join(line_join)
+                .with_miter_limit(stroke.miter_limit),
+        )
+    }
+
+    /// Measures the text line started at the zero origin.
+    fn measure_font(
+        &self, font: FontId, text: &str, font_size: Real,
+    ) -> Result<(Real, TextMetrics), FemtovgRenderError> {
+        let paint = Self::measure_paint(font, font_size);
+        let text_metrics = self
+            .text_context
+            .measure_text(0.0, 0.0, text, &paint)
+            .map_err(FemtovgRenderError::TextError)?;
+        let font_metrics = self
+            .text_context
+            .measure_font(&paint)
+            .map_err(FemtovgRenderError::TextError)?;
+        Ok((Self::advance(&text_metrics), Self::metrics(&font_metrics)))
+    }
+
+    fn measure_paint(font: FontId, font_size: Real) -> FemtovgPaint {
+        FemtovgPaint::default()
+            .with_font(&[font])
+            .with_font_size(font_size)
+            .with_text_align(Align::Left)
+    }
+
+    fn metrics(metrics: &FontMetrics) -> TextMetrics {
+        TextMetrics {
+            ascender: metrics.ascender(),
+            descender: metrics.descender(),
+            line_height: metrics.height(),
+        }
+    }
+
+    /// Horizontal advance of the measured text, the drawn width of the glyphs can differ from it.
+    fn advance(metrics: &FemtovgTextMetrics) -> Real {
+        metrics
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.advance_x)
+            .sum::<f32>()
+            .max(metrics.width()) as Real
+    }
+
+    /// Positions of the chars of the content. Femtovg shapes the text, so a ligature is one glyph of several chars,
+    /// its width is divided between them evenly.
+    fn char_positions(content: &str, metrics: &FemtovgTextMetrics) -> Vec<GlyphPos> {
+        let glyphs = &metrics.glyphs;
+        let mut positions = Vec::with_capacity(glyphs.len());
+        for (idx, glyph) in glyphs.iter().enumerate() {
+            let end = glyphs.get(idx + 1).map(|next| next.byte_index).unwrap_or(content.len());
+            let chars = content
+                .get(glyph.byte_index..end)
+                .map(|chars| chars.chars().count())
+                .unwrap_or_default();
+            if chars == 0 {
+                continue;
+            }
+
+            let pen_x = glyph.x - glyph.bearing_x - glyph.offset_x;
+            let x = pen_x.min(glyph.x);
+            let width = ((pen_x + glyph.advance_x).max(glyph.x + glyph.width) - x) / chars as f32;
+            positions.extend((0..chars).map(|char_idx| GlyphPos {
+                x: x + width * char_idx as f32,
+                y: 0.0,
+                width,
+            }));
+        }
+        positions
+    }
+
+    /// Fills the rect of the text line height, it is used for the caret and the selection of the text input.
+    fn draw_text_line_rect(
+        canvas: &mut Canvas<OpenGl>, text: &Text, x: Real, width: Real, color: Color, options: &DrawOptions,
+    ) {
+        let height = text
+            .metrics
+            .map(|metrics| metrics.line_height)
+            .unwrap_or_else(|| text.font_size.val());
+        let rect = BoundingBox {
+            min_x: x,
+            min_y: text.y.val(),
+            max_x: x + width,
+            max_y: text.y.val() + height,
+        };
+        let paint = FemtovgPaint::color(ToFemtovgPaint::to_femtovg_color(color));
+        Self::fill_text_rect(canvas, rect, &paint, options);
+    }
+
+    fn fill_text_rect(canvas: &mut Canvas<OpenGl>, rect: BoundingBox, paint: &FemtovgPaint, options: &DrawOptions) {
+        let mut path = FemtovgPath::new();
+        path.rect(
+            rect.min_x,
+            rect.min_y,
+            rect.width(),
+            rect.height(),
+        );
+        Self::draw(canvas, options, |canvas| canvas.fill_path(&path, paint));
+    }
+
+    /// Font family, weight and style of the span, inherited from the text if not set.
+    fn span_face<'a>(text: &'a Text, span: &'a TextSpan) -> (&'a str, FontWeight, FontStyle) {
+        (
+            span.font_name.as_ref().unwrap_or(&text.font_name),
+            span.font_weight.unwrap_or(text.font_weight),
+            span.font_style.unwrap_or(text.font_style),
+        )
+    }
+
+    /// Draws the text with the matched face, the missing bold is synthesized by the shifted overdraw and the missing
+    /// italic by the skew around the baseline.
+    fn draw_text(
+        canvas: &mut Canvas<OpenGl>, face_match: &FaceMatch, (x, y): (f32, f32), content: &str, paint: &FemtovgPaint,
+        options: &DrawOptions,
+    ) -> Result<(), FemtovgRenderError> {
+        Self::draw(canvas, options, |canvas| {
+            if face_match.synthetic_italic {
+                let skew = TransformMatrix::identity()
+                    .with_skew(-SYNTHETIC_ITALIC_SKEW, 0.0)
+                    .with_translation(SYNTHETIC_ITALIC_SKEW * y as Real, 0.0);
+                canvas.set_transform(&Self::femtovg_transform(skew));
+            }
+
+            canvas.fill_text(x, y, content, paint)?;
+            if face_match.synthetic_bold {
+                canvas.fill_text(x + paint.font_size() * SYNTHETIC_BOLD_SHIFT, y, content, paint)?;
+            }
+            Ok(())
+        })
+        .map_err(FemtovgRenderError::TextError)
+    }
+
+    /// Paint of the span, which is aligned horizontally as a part of the span row.
+    fn span_paint(text: &Text, span: &TextSpan, font: FontId, defaults: &ShapeDefaults) -> FemtovgPaint {
+        let mut paint = Self::text_paint(text, font, defaults).with_text_align(Align::Left);
+        if let Some(Paint::Color(color)) = span.fill.as_ref().map(|fill| &fill.paint) {
+            paint.set_color(ToFemtovgPaint::to_femtovg_color(*color));
+        }
+        if let Some(font_size) = span.font_size {
+            paint.set_font_size(font_size.val());
+        }
+        paint
+    }
+
+    /// The text is drawn by the color of its fill, the gradients are not supported for the glyphs.
+    fn text_paint(text: &Text, font: FontId, defaults: &ShapeDefaults) -> FemtovgPaint {
+        let color = text
+            .fill
+            .as_ref()
+            .or(defaults.fill.as_ref())
+            .and_then(|fill| {
+                if let Paint::Color(color) = fill.paint {
+                    Some(color)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let (align, baseline) = Self::text_align(text.align.0, text.align.1);
+
+        FemtovgPaint::color(ToFemtovgPaint::to_femtovg_color(color))
+            .with_font(&[font])
+            .with_font_size(text.font_size.val())
+            .with_text_align(align)
+            .with_text_baseline(baseline)
+    }
+
+    fn text_align(align_hor: AlignHor, align_ver: AlignVer) -> (Align, Baseline) {
+        let align = match align_hor {
+            AlignHor::Left => Align::Left,
+            AlignHor::Right => Align::Right,
+            AlignHor::Center => Align::Center,
+        };
+        let baseline = match align_ver {
+            AlignVer::Bottom => Baseline::Bottom,
+            AlignVer::Middle => Baseline::Middle,
+            AlignVer::Baseline => Baseline::Alphabetic,
+            AlignVer::Top => Baseline::Top,
+        };
+        (align, baseline)
+    }
+}
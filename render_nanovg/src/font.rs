@@ -0,0 +1,251 @@
+use std::{collections::HashMap, slice, sync::Arc};
+
+use exgui_core::{FontStyle, FontWeight};
+use font_kit::{
+    family_name::FamilyName,
+    handle::Handle,
+    properties::{Properties, Style, Weight},
+    source::SystemSource,
+};
+use nanovg::{Context, Font as NanovgFont};
+
+use crate::NanovgRenderError;
+
+/// Resolves the font names of the texts to the fonts of the nanovg context. The names which are not loaded by the
+/// render are looked up as the system font families, the CSS generic families like `sans-serif` or `monospace` are
+/// supported too. If the font is not found, the fallback fonts are tried in order.
+#[derive(Debug)]
+pub struct FontCache {
+    pub fallbacks: Vec<String>,
+    /// Names of the fallback fonts used for the not found fonts.
+    resolved: HashMap<String, String>,
+    /// Loaded faces of the font families.
+    faces: HashMap<String, Vec<FontFace>>,
+    matched_faces: HashMap<(String, FontWeight, FontStyle), FaceMatch>,
+    /// Data of the system fonts loaded from the memory. Nanovg does not copy the font data, so they are kept while
+    /// the fonts are loaded into the context.
+    data: Vec<Arc<Vec<u8>>>,
+}
+
+#[derive(Debug, Clone)]
+struct FontFace {
+    name: String,
+    weight: FontWeight,
+    style: FontStyle,
+}
+
+/// Font face found for the requested weight and style. If the font family has no such face, the closest face is
+/// used and the missing bold or italic is synthesized by the render.
+#[derive(Debug, Clone)]
+pub struct FaceMatch {
+    pub name: String,
+    pub synthetic_bold: bool,
+    pub synthetic_italic: bool,
+}
+
+impl Default for FontCache {
+    fn default() -> Self {
+        Self {
+            fallbacks: vec!["sans-serif".to_string()],
+            resolved: HashMap::new(),
+            faces: HashMap::new(),
+            matched_faces: HashMap::new(),
+            data: vec![],
+        }
+    }
+}
+
+impl FontCache {
+    /// Forgets the loaded fonts, e.g. when the context they belong to is replaced. The font data are freed, so it
+    /// must not be called while the context is alive.
+    pub fn clear(&mut self) {
+        self.resolved.clear();
+        self.faces.clear();
+        self.matched_faces.clear();
+        self.data.clear();
+    }
+
+    pub fn resolve<'a>(&mut self, context: &'a Context, name: &str) -> Result<NanovgFont<'a>, NanovgRenderError> {
+        if let Ok(font) = self.find(context, name) {
+            return Ok(font);
+        }
+        if let Some(font) = load_system_font(context, name, &mut self.data) {
+            return Ok(font);
+        }
+
+        for fallback in &self.fallbacks {
+            if let Some(font) = NanovgFont::find(context, fallback)
+                .ok()
+                .or_else(|| load_system_font(context, fallback, &mut self.data))
+            {
+                self.resolved.insert(name.to_string(), fallback.clone());
+                return Ok(font);
+            }
+        }
+        Err(NanovgRenderError::FontNotFound(name.to_string()))
+    }
+
+    /// Finds the font, that is already loaded or resolved.
+    pub fn find<'a>(&self, context: &'a Context, name: &str) -> Result<NanovgFont<'a>, NanovgRenderError> {
+        let loaded_name = self.resolved.get(name).map(|name| name.as_str()).unwrap_or(name);
+        NanovgFont::find(context, loaded_name).map_err(|_| NanovgRenderError::FontNotFound(name.to_string()))
+    }
+
+    /// Registers the loaded font as the face of the font family.
+    pub fn add_face(
+        &mut self, family: impl Into<String>, weight: FontWeight, style: FontStyle, name: impl Into<String>,
+    ) {
+        let family = family.into();
+        self.matched_faces
+            .retain(|(matched_family, ..), _| *matched_family != family);
+        self.faces.entry(family).or_default().push(FontFace {
+            name: name.into(),
+            weight,
+            style,
+        });
+    }
+
+    /// Resolves the face of the font family. The loaded faces go first, then the system faces. If the family has no
+    /// face of the weight and style, the closest face is matched.
+    pub fn resolve_face<'a>(
+        &mut self, context: &'a Context, family: &str, weight: FontWeight, style: FontStyle,
+    ) -> Result<(NanovgFont<'a>, FaceMatch), NanovgRenderError> {
+        let key = (family.to_string(), weight, style);
+        if let Some(face_match) = self.matched_faces.get(&key) {
+            return Ok((self.find(context, &face_match.name)?, face_match.clone()));
+        }
+
+        let faces = self.faces.get(family).map(|faces| faces.as_slice()).unwrap_or_default();
+        let exact = faces.iter().find(|face| face.weight == weight && face.style == style);
+        let face = if let Some(face) = exact {
+            face.clone()
+        } else if let Some(face) = load_system_face(context, family, weight, style, &mut self.data) {
+            self.faces.entry(family.to_string()).or_default().push(face.clone());
+            face
+        } else if let Some(face) = faces.iter().min_by_key(|face| {
+            (
+                u16::from(face.style != style),
+                (i32::from(face.weight.0) - i32::from(weight.0)).abs(),
+            )
+        }) {
+            face.clone()
+        } else {
+            self.resolve(context, family)?;
+            FontFace {
+                name: family.to_string(),
+                weight: FontWeight::NORMAL,
+                style: FontStyle::Normal,
+            }
+        };
+
+        let face_match = FaceMatch {
+            synthetic_bold: weight.is_bold() && !face.weight.is_bold(),
+            synthetic_italic: style != FontStyle::Normal && face.style == FontStyle::Normal,
+            name: face.name,
+        };
+        self.matched_faces.insert(key, face_match.clone());
+        Ok((self.find(context, &face_match.name)?, face_match))
+    }
+
+    /// Finds the face, that is already resolved.
+    pub fn find_face<'a>(
+        &self, context: &'a Context, family: &str, weight: FontWeight, style: FontStyle,
+    ) -> Result<(NanovgFont<'a>, FaceMatch), NanovgRenderError> {
+        let face_match = self
+            .matched_faces
+            .get(&(family.to_string(), weight, style))
+            .cloned()
+            .unwrap_or_else(|| FaceMatch {
+                name: family.to_string(),
+                synthetic_bold: false,
+                synthetic_italic: false,
+            });
+        Ok((self.find(context, &face_match.name)?, face_match))
+    }
+}
+
+fn family_name(name: &str) -> FamilyName {
+    match name {
+        "serif" => FamilyName::Serif,
+        "sans-serif" => FamilyName::SansSerif,
+        "monospace" => FamilyName::Monospace,
+        "cursive" => FamilyName::Cursive,
+        "fantasy" => FamilyName::Fantasy,
+        _ => FamilyName::Title(name.to_string()),
+    }
+}
+
+/// Loads the best match of the system font family under the family name.
+fn load_system_font<'a>(context: &'a Context, name: &str, data: &mut Vec<Arc<Vec<u8>>>) -> Option<NanovgFont<'a>> {
+    let handle = SystemSource::new()
+        .select_best_match(&[family_name(name)], &Properties::new())
+        .ok()?;
+    load_handle(context, name, handle, data)
+}
+
+/// Loads the system face of the font family, if the family has the face of the weight and style.
+fn load_system_face(
+    context: &Context, family: &str, weight: FontWeight, style: FontStyle, data: &mut Vec<Arc<Vec<u8>>>,
+) -> Option<FontFace> {
+    if weight == FontWeight::NORMAL && style == FontStyle::Normal {
+        return None;
+    }
+
+    let source = SystemSource::new();
+    let properties = Properties {
+        weight: Weight(f32::from(weight.0)),
+        style: match style {
+            FontStyle::Normal => Style::Normal,
+            FontStyle::Italic => Style::Italic,
+            FontStyle::Oblique => Style::Oblique,
+        },
+        ..Properties::new()
+    };
+    let handle = source.select_best_match(&[family_name(family)], &properties).ok()?;
+    // The best match is the regular face, if the family has no face of the weight and style.
+    let regular = source
+        .select_best_match(&[family_name(family)], &Properties::new())
+        .ok()?;
+    if same_handle(&handle, &regular) {
+        return None;
+    }
+
+    let name = format!("{}:{}:{:?}", family, weight.0, style);
+    load_handle(context, &name, handle, data)?;
+    Some(FontFace { name, weight, style })
+}
+
+fn load_handle<'a>(
+    context: &'a Context, name: &str, handle: Handle, data: &mut Vec<Arc<Vec<u8>>>,
+) -> Option<NanovgFont<'a>> {
+    match handle {
+        Handle::Path { path, .. } => NanovgFont::from_file(context, name, path).ok(),
+        Handle::Memory { bytes, .. } => {
+            // SAFETY: The data are kept by the font cache, which is cleared after the context is dropped only.
+            let memory = unsafe { slice::from_raw_parts(bytes.as_ptr(), bytes.len()) };
+            let font = NanovgFont::from_memory(context, name, memory).ok()?;
+            data.push(bytes);
+            Some(font)
+        }
+    }
+}
+
+fn same_handle(handle: &Handle, other: &Handle) -> bool {
+    match (handle, other) {
+        (
+            Handle::Path { path, font_index },
+            Handle::Path {
+                path: other_path,
+                font_index: other_index,
+            },
+        ) => path == other_path && font_index == other_index,
+        (
+            Handle::Memory { bytes, font_index },
+            Handle::Memory {
+                bytes: other_bytes,
+                font_index: other_index,
+            },
+        ) => bytes == other_bytes && font_index == other_index,
+        _ => false,
+    }
+}
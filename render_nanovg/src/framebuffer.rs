@@ -0,0 +1,153 @@
+use std::{marker::PhantomData, ptr};
+
+use gl::types::{GLfloat, GLint, GLsizei, GLuint};
+
+/// Offscreen render target with the color texture and the stencil buffer, which nanovg needs for the fills. The
+/// texture is created cleared, it is drawn into while the framebuffer is bound.
+#[derive(Debug)]
+pub struct Framebuffer {
+    framebuffer: GLuint,
+    texture: GLuint,
+    renderbuffer: GLuint,
+    width: GLsizei,
+    height: GLsizei,
+}
+
+/// Binding of the framebuffer, the previous framebuffer and viewport are restored on drop.
+pub struct BoundFramebuffer<'a> {
+    framebuffer: PhantomData<&'a Framebuffer>,
+    prev_framebuffer: GLint,
+    prev_viewport: [GLint; 4],
+}
+
+impl Framebuffer {
+    pub fn new(width: GLsizei, height: GLsizei) -> Self {
+        let mut this = Self {
+            framebuffer: 0,
+            texture: 0,
+            renderbuffer: 0,
+            width,
+            height,
+        };
+
+        unsafe {
+            gl::GenTextures(1, &mut this.texture);
+            gl::BindTexture(gl::TEXTURE_2D, this.texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as GLint,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            gl::GenRenderbuffers(1, &mut this.renderbuffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, this.renderbuffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            gl::GenFramebuffers(1, &mut this.framebuffer);
+        }
+
+        let bound = this.bind((0, 0, width, height));
+        unsafe {
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, this.texture, 0);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                this.renderbuffer,
+            );
+
+            let mut prev_clear_color: [GLfloat; 4] = [0.0; 4];
+            gl::GetFloatv(gl::COLOR_CLEAR_VALUE, prev_clear_color.as_mut_ptr());
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+            let [r, g, b, a] = prev_clear_color;
+            gl::ClearColor(r, g, b, a);
+        }
+        drop(bound);
+        this
+    }
+
+    pub fn texture(&self) -> GLuint {
+        self.texture
+    }
+
+    pub fn size(&self) -> (GLsizei, GLsizei) {
+        (self.width, self.height)
+    }
+
+    /// Binds the framebuffer with the viewport `(x, y, width, height)`, which is in the GL coordinates of the
+    /// framebuffer, i.e. the rows go from bottom to top. The viewport may be out of the framebuffer, so the part of
+    /// the larger frame is drawn.
+    pub fn bind(&self, (x, y, width, height): (GLint, GLint, GLsizei, GLsizei)) -> BoundFramebuffer<'_> {
+        let mut bound = BoundFramebuffer {
+            framebuffer: PhantomData,
+            prev_framebuffer: 0,
+            prev_viewport: [0; 4],
+        };
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut bound.prev_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, bound.prev_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::Viewport(x, y, width, height);
+        }
+        bound
+    }
+
+    /// Reads the premultiplied RGBA pixels, the rows go from top to bottom.
+    pub fn read_pixels(&self) -> Vec<u32> {
+        let _bound = self.bind((0, 0, self.width, self.height));
+        let row_len = self.width as usize;
+        let mut pixels = vec![0_u32; row_len * self.height as usize];
+        unsafe {
+            gl::ReadPixels(
+                0,
+                0,
+                self.width,
+                self.height,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        }
+        pixels.chunks(row_len).rev().flatten().copied().collect()
+    }
+}
+
+/// Reads the RGBA bytes of the bound framebuffer, the rows go from top to bottom.
+pub fn read_bound_pixels(width: GLsizei, height: GLsizei) -> Vec<u8> {
+    let row_len = width.max(0) as usize * 4;
+    let mut data = vec![0_u8; row_len * height.max(0) as usize];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width, height, gl::RGBA, gl::UNSIGNED_BYTE, data.as_mut_ptr() as *mut _);
+    }
+    data.chunks(row_len.max(1)).rev().flatten().copied().collect()
+}
+
+impl Drop for BoundFramebuffer<'_> {
+    fn drop(&mut self) {
+        let [x, y, width, height] = self.prev_viewport;
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.prev_framebuffer as GLuint);
+            gl::Viewport(x, y, width, height);
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteRenderbuffers(1, &self.renderbuffer);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
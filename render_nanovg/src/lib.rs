@@ -1,25 +1,55 @@
-use std::{ops::Mul, path::Path};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    f32::consts::PI,
+    mem,
+    path::Path,
+    rc::Rc,
+    time::Instant,
+};
 
 use exgui_core::{
-    AlignHor, AlignVer, Clip, Color, CompositeShape, Fill, GlyphPos, Gradient, LineCap, LineJoin, Padding, Paint, Real,
-    Render, Shape, Stroke, Text, TextMetrics, Transform, TransformMatrix,
+    apply_filters, arc_to_cub_beziers, recalc_in_view, AlignHor, AlignVer, BoundingBox, Clip, Color, CompositeShape,
+    Damage, Filter, FontStyle, FontWeight, GlyphPos, Gradient, Group, LayerState, LayoutMeasurer, LineCap, LineJoin,
+    Paint, PathCommand, Real, Render, RenderStats, Shape, ShapeDefaults, Stroke, Text, TextCache, TextFace,
+    TextMeasurer, TextMetrics, TextSpan, Theme, Transform, TransformMatrix,
 };
+use image::{ImageError, ImageFormat, RgbaImage};
 use nanovg::{
-    Alignment, Clip as NanovgClip, Color as NanovgColor, Context, ContextBuilder, CreateFontError, Font as NanovgFont,
-    Frame, Gradient as NanovgGradient, LineCap as NanovgLineCap, LineJoin as NanovgLineJoin, Paint as NanovgPaint,
-    PathOptions, Scissor as NanovgScissor, StrokeOptions, TextOptions, Transform as NanovgTransform,
+    Alignment, BasicCompositeOperation, Clip as NanovgClip, Color as NanovgColor, CompositeOperation, Context,
+    ContextBuilder, CreateFontError, CreateImageError, Font as NanovgFont, Frame, GlyphPosition,
+    Gradient as NanovgGradient, Image as NanovgImage, ImagePattern, LineCap as NanovgLineCap,
+    LineJoin as NanovgLineJoin, Paint as NanovgPaint, Path as NanovgPath, PathOptions, Scissor as NanovgScissor,
+    Solidity, StrokeOptions, TextOptions, Transform as NanovgTransform, Winding,
+};
+
+pub use self::font::{FaceMatch, FontCache};
+use self::{
+    framebuffer::{read_bound_pixels, Framebuffer},
+    texture::{TextureImage, TexturePattern},
 };
 
-struct ToNanovgPaint(Paint);
+mod font;
+mod framebuffer;
+mod texture;
 
-impl ToNanovgPaint {
+/// Conic gradients are drawn by the image patterns, because nanovg does not support them.
+const CONIC_IMAGE_SIZE: usize = 256;
+const CARET_WIDTH: Real = 1.5;
+/// Horizontal shift of the synthetic bold overdraw, relative to the font size.
+const SYNTHETIC_BOLD_SHIFT: f32 = 0.04;
+/// Slant of the synthetic italic.
+const SYNTHETIC_ITALIC_SKEW: Real = 0.2;
+
+struct ToNanovgPaint<'a>(&'a Paint, &'a ImageCache, BoundingBox);
+
+impl<'a> ToNanovgPaint<'a> {
     fn to_nanovg_color(color: Color) -> NanovgColor {
         let [r, g, b, a] = color.as_arr();
         NanovgColor::new(r, g, b, a)
     }
 
-    fn to_nanovg_gradient(gradient: Gradient) -> NanovgGradient {
-        match gradient {
+    fn to_nanovg_gradient(gradient: &Gradient) -> NanovgGradient {
+        match *gradient {
             Gradient::Linear {
                 start: (start_x, start_y),
                 end: (end_x, end_y),
@@ -59,70 +89,237 @@ impl ToNanovgPaint {
                 start_color: Self::to_nanovg_color(start_color),
                 end_color: Self::to_nanovg_color(end_color),
             },
+            Gradient::Conic { .. } => unreachable!("The Conic gradient is drawn by the image pattern"),
         }
     }
+
+    fn conic_image_key(stops: &[(Real, Color)]) -> String {
+        format!("conic:{:?}", stops)
+    }
+
+    fn conic_image_data(stops: &[(Real, Color)]) -> Vec<u32> {
+        let half_size = CONIC_IMAGE_SIZE as f32 / 2.0;
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (0..CONIC_IMAGE_SIZE * CONIC_IMAGE_SIZE)
+            .map(|idx| {
+                let x = (idx % CONIC_IMAGE_SIZE) as f32 + 0.5 - half_size;
+                let y = (idx / CONIC_IMAGE_SIZE) as f32 + 0.5 - half_size;
+                let offset = y.atan2(x).rem_euclid(2.0 * PI) / (2.0 * PI);
+                let [r, g, b, a] = Gradient::stops_color(stops, offset as Real).as_arr();
+                u32::from_ne_bytes([to_byte(r), to_byte(g), to_byte(b), to_byte(a)])
+            })
+            .collect()
+    }
+
+    /// The pattern is centered at the gradient center and covers the shape bound.
+    fn conic_pattern(&self, center: (Real, Real), angle: Real, stops: &[(Real, Color)]) -> Option<ImagePattern<'a>> {
+        let image = self.1.get(&Self::conic_image_key(stops))?;
+        let (cx, cy) = (center.0 as f32, center.1 as f32);
+        let bound = self.2;
+        let radius = [
+            (bound.min_x, bound.min_y),
+            (bound.min_x, bound.max_y),
+            (bound.max_x, bound.min_y),
+            (bound.max_x, bound.max_y),
+        ]
+        .iter()
+        .map(|(x, y)| ((x - cx) * (x - cx) + (y - cy) * (y - cy)).sqrt())
+        .fold(1.0, f32::max);
+
+        // The pattern rotates around its origin, so the origin is moved to keep the image center in place.
+        let (sin, cos) = (angle as f32).sin_cos();
+        Some(ImagePattern {
+            image,
+            origin: (cx - radius * (cos - sin), cy - radius * (sin + cos)),
+            size: (2.0 * radius, 2.0 * radius),
+            angle: angle as f32,
+            alpha: 1.0,
+        })
+    }
 }
 
-impl NanovgPaint for ToNanovgPaint {
+impl<'a> NanovgPaint for ToNanovgPaint<'a> {
     fn fill(&self, context: &Context) {
-        match self.0 {
+        match *self.0 {
             Paint::Color(ref color) => Self::to_nanovg_color(*color).fill(context),
-            Paint::Gradient(ref gradient) => Self::to_nanovg_gradient(*gradient).fill(context),
+            Paint::Gradient(Gradient::Conic {
+                center,
+                angle,
+                ref stops,
+            }) => {
+                if let Some(pattern) = self.conic_pattern(center, angle, stops) {
+                    pattern.fill(context);
+                }
+            }
+            Paint::Gradient(ref gradient) => Self::to_nanovg_gradient(gradient).fill(context),
         }
     }
 
     fn stroke(&self, context: &Context) {
-        match self.0 {
+        match *self.0 {
             Paint::Color(ref color) => Self::to_nanovg_color(*color).stroke(context),
-            Paint::Gradient(ref gradient) => Self::to_nanovg_gradient(*gradient).stroke(context),
+            Paint::Gradient(Gradient::Conic {
+                center,
+                angle,
+                ref stops,
+            }) => {
+                if let Some(pattern) = self.conic_pattern(center, angle, stops) {
+                    pattern.stroke(context);
+                }
+            }
+            Paint::Gradient(ref gradient) => Self::to_nanovg_gradient(gradient).stroke(context),
         }
     }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
-pub struct BoundingBox {
-    pub min_x: Real,
-    pub min_y: Real,
-    pub max_x: Real,
-    pub max_y: Real,
+#[derive(Debug)]
+pub enum NanovgRenderError {
+    ContextIsNotInit,
+    InitNanovgContextFailed,
+    CreateFontError(CreateFontError, String),
+    CreateImageError(CreateImageError, String),
+    SaveImageError(ImageError, String),
+    FontNotFound(String),
+}
+
+pub type ImageCache = HashMap<String, NanovgImage<'static>>;
+
+/// Offscreen images of the layer groups for the current render, keyed by the group address.
+type LayerCache = HashMap<*const Group, Layer>;
+
+#[derive(Debug)]
+struct Layer {
+    image: LayerImage,
+    /// Position and size of the layer in the logical pixels of the window.
+    origin: (f32, f32),
+    size: (f32, f32),
+    /// State of the cached group, its layer is kept for the next renders.
+    state: Option<LayerState>,
+}
+
+#[derive(Debug)]
+enum LayerImage {
+    /// The framebuffer texture, which is drawn directly.
+    Texture(TextureImage),
+    /// The pixels of the layer, which are read back to apply the filters.
+    Filtered(NanovgImage<'static>),
 }
 
-impl BoundingBox {
-    pub fn width(&self) -> Real {
-        self.max_x - self.min_x
+/// Area of the layer in the physical pixels of the window, the rows go from top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LayerArea {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl LayerArea {
+    /// Returns the drawn area of the subtree, which is extended by the `spread` and limited by the `window`. The
+    /// subtree, which is drawn out of the window, has no area.
+    fn of(composite: &dyn CompositeShape, spread: Real, window: (i32, i32), device_pixel_ratio: f32) -> Option<Self> {
+        let bound = Self::drawn_bound(composite)?.outset(spread);
+        let min_x = ((bound.min_x * device_pixel_ratio).floor() as i32).max(0);
+        let min_y = ((bound.min_y * device_pixel_ratio).floor() as i32).max(0);
+        let max_x = ((bound.max_x * device_pixel_ratio).ceil() as i32).min(window.0);
+        let max_y = ((bound.max_y * device_pixel_ratio).ceil() as i32).min(window.1);
+        if min_x < max_x && min_y < max_y {
+            Some(Self {
+                x: min_x,
+                y: min_y,
+                width: max_x - min_x,
+                height: max_y - min_y,
+            })
+        } else {
+            None
+        }
     }
 
-    pub fn height(&self) -> Real {
-        self.max_y - self.min_y
+    fn drawn_bound(composite: &dyn CompositeShape) -> Option<BoundingBox> {
+        let bound = composite.recalc_cache().map(|cache| cache.global_bound);
+        composite
+            .children()
+            .into_iter()
+            .flatten()
+            .filter(|child| !child.is_portal())
+            .filter_map(Self::drawn_bound)
+            .fold(bound, |bound, child_bound| {
+                Some(bound.map_or(child_bound, |bound| bound.union(&child_bound)))
+            })
     }
-}
 
-impl Mul<BoundingBox> for TransformMatrix {
-    type Output = [(Real, Real); 4];
+    /// Viewport of the window frame in the GL coordinates of the layer framebuffer, so the frame is drawn shifted
+    /// by the area position.
+    fn viewport(&self, window: (i32, i32)) -> (i32, i32, i32, i32) {
+        (-self.x, self.y + self.height - window.1, window.0, window.1)
+    }
 
-    fn mul(self, rhs: BoundingBox) -> Self::Output {
-        [
-            self * (rhs.min_x, rhs.min_y),
-            self * (rhs.min_x, rhs.max_y),
-            self * (rhs.max_x, rhs.min_y),
-            self * (rhs.max_x, rhs.max_y),
-        ]
+    /// Position and size in the logical pixels.
+    fn logical(&self, device_pixel_ratio: f32) -> ((f32, f32), (f32, f32)) {
+        (
+            (self.x as f32 / device_pixel_ratio, self.y as f32 / device_pixel_ratio),
+            (
+                self.width as f32 / device_pixel_ratio,
+                self.height as f32 / device_pixel_ratio,
+            ),
+        )
     }
 }
 
-#[derive(Debug)]
-pub enum NanovgRenderError {
-    ContextIsNotInit,
-    InitNanovgContextFailed,
-    CreateFontError(CreateFontError, String),
+/// Offscreen images of the shapes with the circle and path clips for the current render, which are masked by the clip
+/// regions, keyed by the shape address.
+type ClipMaskCache = HashMap<*const Shape, Layer>;
+
+/// Quality options of the nanovg drawing, the context options are applied when the context is created by the `init`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NanovgOptions {
+    /// Antialiasing of the shape edges by nanovg, it is not needed when the GL context has the MSAA.
+    pub antialias: bool,
+    /// The strokes are drawn by the stencil buffer, so the overlapped translucent segments are not blended twice, but
+    /// it is slower.
+    pub stencil_strokes: bool,
+    /// Number of the MSAA samples, which the GL context is requested with.
+    pub multisampling: Option<u16>,
 }
 
+impl Default for NanovgOptions {
+    fn default() -> Self {
+        Self {
+            antialias: false,
+            stencil_strokes: true,
+            multisampling: None,
+        }
+    }
+}
+
+/// The render draws by nanovg over the OpenGL context. The layout is calculated by the shared `exgui_core::recalc`,
+/// the texts are measured in the frame of the recalculation.
 #[derive(Debug, Default)]
 pub struct NanovgRender {
-    pub context: Option<Context>,
+    /// The cached images, layers and fonts belong to the context, so they are cleared when it is replaced or dropped.
+    context: Option<Box<Context>>,
+    pub images: ImageCache,
+    pub fonts: FontCache,
+    /// Measured text lines of the layout, it is cleared when the fonts are loaded.
+    pub text_cache: TextCache,
     pub width: f32,
     pub height: f32,
     pub device_pixel_ratio: f32,
+    pub theme: Rc<Theme>,
+    pub options: NanovgOptions,
+    theme_changed: bool,
+    view_transform: TransformMatrix,
+    view_changed: bool,
+    /// Layers of the cached groups from the last render.
+    layers: LayerCache,
+    stats: RenderStats,
+}
+
+impl Drop for NanovgRender {
+    fn drop(&mut self) {
+        self.images.clear();
+        self.layers.clear();
+    }
 }
 
 impl Render for NanovgRender {
@@ -130,77 +327,298 @@ impl Render for NanovgRender {
 
     fn init(&mut self, _background_color: Color) -> Result<(), Self::Error> {
         if self.context.is_none() {
-            let context = ContextBuilder::new()
-                .stencil_strokes()
-                .build()
-                .map_err(|_| NanovgRenderError::InitNanovgContextFailed)?;
-            self.context = Some(context);
+            let mut builder = ContextBuilder::new();
+            if self.options.antialias {
+                builder = builder.antialias();
+            }
+            if self.options.stencil_strokes {
+                builder = builder.stencil_strokes();
+            }
+            let context = builder.build().map_err(|_| NanovgRenderError::InitNanovgContextFailed)?;
+            self.set_context(context);
         }
         Ok(())
     }
 
+    fn multisampling(&self) -> Option<u16> {
+        self.options.multisampling
+    }
+
     fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
-        self.width = physical_width as f32;
-        self.height = physical_height as f32;
-        self.device_pixel_ratio = device_pixel_ratio as f32;
+        let dimensions = (physical_width as f32, physical_height as f32, device_pixel_ratio as f32);
+        if dimensions != (self.width, self.height, self.device_pixel_ratio) {
+            // The layers are limited by the window.
+            self.layers.clear();
+        }
+        self.width = dimensions.0;
+        self.height = dimensions.1;
+        self.device_pixel_ratio = dimensions.2;
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn set_view_transform(&mut self, transform: TransformMatrix) {
+        if transform != self.view_transform {
+            self.view_transform = transform;
+            self.view_changed = true;
+        }
+    }
+
+    fn view_transform(&self) -> TransformMatrix {
+        self.view_transform
     }
 
     fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
-        let need_recalc = node.need_recalc().unwrap_or(true);
-        let need_redraw = node.need_redraw().unwrap_or(true);
-        let shared_self = &*self;
-        shared_self
-            .context
-            .as_ref()
-            .ok_or(NanovgRenderError::ContextIsNotInit)?
-            .frame(
-                (shared_self.width, shared_self.height),
-                shared_self.device_pixel_ratio,
-                move |frame| {
-                    let bound = BoundingBox {
-                        min_x: 0.0,
-                        min_y: 0.0,
-                        max_x: shared_self.width as Real,
-                        max_y: shared_self.height as Real,
-                    };
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let view_changed = mem::take(&mut self.view_changed);
+        let need_recalc = node.need_recalc().unwrap_or(true) || theme_changed || view_changed;
+        let need_redraw = node.need_redraw().unwrap_or(true) || theme_changed || view_changed;
+        let (theme, view_transform) = (&self.theme, self.view_transform);
+        let context = self.static_context()?;
+        // The frames are laid out in the logical pixels, nanovg scales them by the device pixel ratio.
+        let device_pixel_ratio = self.device_pixel_ratio;
+        let (width, height) = (self.width / device_pixel_ratio, self.height / device_pixel_ratio);
+        let images = &mut self.images;
+        let fonts = &mut self.fonts;
+        let text_cache = &mut self.text_cache;
+        let mut stats = RenderStats::default();
+        if need_recalc {
+            let start = Instant::now();
+            let mut result = Ok(());
+            context.frame((width, height), device_pixel_ratio, |frame| {
+                let bound = BoundingBox {
+                    min_x: 0.0,
+                    min_y: 0.0,
+                    max_x: width as Real,
+                    max_y: height as Real,
+                };
 
-                    if need_recalc {
-                        let mut defaults = ShapeDefaults::default();
-                        Self::recalc_composite(&frame, node, bound, TransformMatrix::identity(), &mut defaults);
-                    }
-                    if need_redraw {
-                        let mut defaults = ShapeDefaults::default();
-                        Self::render_composite(&frame, node, None, &mut defaults);
+                let mut measurer = FrameMeasurer {
+                    frame: &frame,
+                    context,
+                    images,
+                    fonts,
+                    text_cache,
+                };
+                let mut damage = Damage::new();
+                result = recalc_in_view(&mut measurer, node, bound, view_transform, theme.clone(), &mut damage)
+                    .map(|_| ());
+            });
+            result?;
+            stats.recalc_time = start.elapsed();
+        }
+        if need_redraw {
+            let start = Instant::now();
+            // The layers are drawn into the own framebuffers, so it can not be done inside the main frame.
+            let mut last_layers = mem::take(&mut self.layers);
+            let mut layers = LayerCache::new();
+            let mut masks = ClipMaskCache::new();
+            Self::render_layers(
+                context,
+                images,
+                fonts,
+                &mut last_layers,
+                &mut layers,
+                &mut masks,
+                &mut stats,
+                node,
+                (width, height),
+                device_pixel_ratio,
+                &mut ShapeDefaults::new(theme.clone()),
+            )?;
+
+            let mut result = Ok(());
+            context.frame((width, height), device_pixel_ratio, |frame| {
+                let mut defaults = ShapeDefaults::new(theme.clone());
+                result = Self::render_composite(
+                    &frame,
+                    images,
+                    fonts,
+                    &layers,
+                    &masks,
+                    &mut stats,
+                    node,
+                    None,
+                    &mut defaults,
+                );
+                // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+                for portal in node.portals() {
+                    if result.is_err() {
+                        break;
                     }
-                },
-            );
+                    let mut defaults = ShapeDefaults::new(theme.clone());
+                    result = Self::render_composite(
+                        &frame,
+                        images,
+                        fonts,
+                        &layers,
+                        &masks,
+                        &mut stats,
+                        portal,
+                        None,
+                        &mut defaults,
+                    );
+                }
+            });
+            result?;
+            self.layers = layers.into_iter().filter(|(_, layer)| layer.state.is_some()).collect();
+            stats.draw_time = start.elapsed();
+        }
+        self.stats = stats;
         Ok(need_redraw)
     }
+
+    fn stats(&self) -> Option<&RenderStats> {
+        Some(&self.stats)
+    }
+}
+
+/// Measurer of the layout pass, the texts are measured in the frame of the recalculation, so the frame is not
+/// started for every text.
+struct FrameMeasurer<'a> {
+    frame: &'a Frame<'a>,
+    context: &'static Context,
+    images: &'a mut ImageCache,
+    fonts: &'a mut FontCache,
+    text_cache: &'a mut TextCache,
 }
 
-#[derive(Default, Clone)]
-pub struct ShapeDefaults {
-    pub transparency: Real,
-    pub fill: Option<Fill>,
-    pub stroke: Option<Stroke>,
-    pub clip: Clip,
+impl<'a> FrameMeasurer<'a> {
+    fn measure_font(&self, nanovg_font: NanovgFont, text: &str, font_size: Real) -> (Real, TextMetrics) {
+        let options = NanovgRender::measure_options(font_size);
+        let (advance, _) = self.frame.text_bounds(nanovg_font, (0.0, 0.0), text, options);
+        (
+            advance as Real,
+            NanovgRender::metrics(&self.frame.text_metrics(nanovg_font, options)),
+        )
+    }
+}
+
+impl<'a> TextMeasurer for FrameMeasurer<'a> {
+    type Error = NanovgRenderError;
+
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let nanovg_font = self.fonts.resolve(self.context, font_name)?;
+        Ok(self.measure_font(nanovg_font, text, font_size))
+    }
+
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        let (nanovg_font, _) = self
+            .fonts
+            .resolve_face(self.context, face.family, face.weight, face.style)?;
+        Ok(self.measure_font(nanovg_font, text, font_size))
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let (nanovg_font, _) = self
+            .fonts
+            .resolve_face(self.context, face.family, face.weight, face.style)?;
+        // The metrics query sets the font for the glyph positions.
+        self.frame
+            .text_metrics(nanovg_font, NanovgRender::measure_options(font_size));
+        Ok(self
+            .frame
+            .text_glyph_positions((0.0, 0.0), text)
+            .map(NanovgRender::glyph_pos)
+            .collect())
+    }
+}
+
+impl<'a> LayoutMeasurer for FrameMeasurer<'a> {
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error> {
+        let (width, height) = NanovgRender::cached_image(self.context, self.images, src)?.size();
+        Ok((width as Real, height as Real))
+    }
+
+    fn prepare_shape(&mut self, shape: &Shape, defaults: &ShapeDefaults) -> Result<(), Self::Error> {
+        NanovgRender::cached_conic_images(self.context, self.images, shape, defaults)
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut *self.text_cache)
+    }
+}
+
+impl TextMeasurer for NanovgRender {
+    type Error = NanovgRenderError;
+
+    /// Measures the text in an empty frame, nothing is drawn.
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let context = self.static_context()?;
+        let fonts = &mut self.fonts;
+        let options = TextOptions {
+            size: font_size as f32,
+            ..Default::default()
+        };
+
+        let mut result = Err(NanovgRenderError::FontNotFound(font_name.to_string()));
+        let size = (self.width / self.device_pixel_ratio, self.height / self.device_pixel_ratio);
+        context.frame(size, self.device_pixel_ratio, |frame| {
+            result = fonts.resolve(context, font_name).map(|nanovg_font| {
+                let (advance, _) = frame.text_bounds(nanovg_font, (0.0, 0.0), text, options);
+                (
+                    advance as Real,
+                    Self::metrics(&frame.text_metrics(nanovg_font, options)),
+                )
+            });
+        });
+        result
+    }
 }
 
 impl NanovgRender {
     pub fn new(context: Context, width: f32, height: f32, device_pixel_ratio: f32) -> Self {
         Self {
-            context: Some(context),
+            context: Some(Box::new(context)),
+            images: ImageCache::new(),
+            fonts: FontCache::default(),
+            text_cache: TextCache::new(),
             width,
             height,
             device_pixel_ratio,
+            theme: Rc::new(Theme::default()),
+            options: NanovgOptions::default(),
+            theme_changed: false,
+            view_transform: TransformMatrix::identity(),
+            view_changed: false,
+            layers: LayerCache::new(),
+            stats: RenderStats::default(),
         }
     }
 
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
+
     pub fn with_context(mut self, context: Context) -> Self {
-        self.context = Some(context);
+        self.set_context(context);
         self
     }
 
+    pub fn context(&self) -> Option<&Context> {
+        self.context.as_deref()
+    }
+
+    /// Replaces the context, the images and the fonts of the previous context are dropped with it, so the fonts
+    /// loaded by the `load_font` have to be loaded again.
+    pub fn set_context(&mut self, context: Context) {
+        self.images.clear();
+        self.layers.clear();
+        self.text_cache.clear();
+        self.context = Some(Box::new(context));
+        // The font data are freed after the previous context, which reads them, is dropped.
+        self.fonts.clear();
+    }
+
     pub fn with_width(mut self, width: f32) -> Self {
         self.width = width;
         self
@@ -216,248 +634,159 @@ impl NanovgRender {
         self
     }
 
+    /// Sets the nanovg antialiasing, it is applied when the context is created by the `init`.
+    pub fn with_antialias(mut self, antialias: bool) -> Self {
+        self.options.antialias = antialias;
+        self
+    }
+
+    /// Sets the stencil strokes, it is applied when the context is created by the `init`.
+    pub fn with_stencil_strokes(mut self, stencil_strokes: bool) -> Self {
+        self.options.stencil_strokes = stencil_strokes;
+        self
+    }
+
+    /// Requests the MSAA with the number of the samples for the GL context, which the app creates.
+    pub fn with_multisampling(mut self, samples: u16) -> Self {
+        self.options.multisampling = Some(samples);
+        self
+    }
+
     pub fn load_font(
         &mut self, name: impl Into<String>, path: impl AsRef<Path>,
     ) -> Result<(), <Self as Render>::Error> {
         let name = name.into();
         let display_path = path.as_ref().display();
-        NanovgFont::from_file(
-            self.context.as_ref().ok_or(NanovgRenderError::ContextIsNotInit)?,
-            name.as_str(),
-            path.as_ref(),
-        )
-        .map_err(|e| NanovgRenderError::CreateFontError(e, format!("{}", display_path)))?;
+        NanovgFont::from_file(self.static_context()?, name.as_str(), path.as_ref())
+            .map_err(|e| NanovgRenderError::CreateFontError(e, format!("{}", display_path)))?;
+        self.text_cache.clear();
         Ok(())
     }
 
-    fn recalc_composite(
-        frame: &Frame, composite: &mut dyn CompositeShape, parent_bound: BoundingBox,
-        mut parent_global_transform: TransformMatrix, defaults: &mut ShapeDefaults,
-    ) -> BoundingBox {
-        let mut bound = parent_bound;
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`. Nanovg does not copy the
+    /// font data, so it must live as long as the render.
+    pub fn load_font_from_memory(
+        &mut self, name: impl Into<String>, data: &'static [u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        let name = name.into();
+        NanovgFont::from_memory(self.static_context()?, name.as_str(), data)
+            .map_err(|e| NanovgRenderError::CreateFontError(e, name.clone()))?;
+        self.text_cache.clear();
+        Ok(())
+    }
 
-        if let Some(shape) = composite.shape_mut() {
-            match shape {
-                Shape::Rect(rect) => {
-                    if rect.x.set_by_pct(parent_bound.width()) {
-                        rect.x.0 += parent_bound.min_x;
-                    }
-                    if rect.y.set_by_pct(parent_bound.height()) {
-                        rect.y.0 += parent_bound.min_y;
-                    }
-                    rect.width.set_by_pct(parent_bound.width());
-                    rect.height.set_by_pct(parent_bound.height());
-                    if let Some(rounding) = &mut rect.rounding {
-                        let radius = parent_bound.width().min(parent_bound.height());
-                        rounding.top_left.set_by_pct(radius);
-                        rounding.top_right.set_by_pct(radius);
-                        rounding.bottom_left.set_by_pct(radius);
-                        rounding.bottom_right.set_by_pct(radius);
-                    }
-                    Self::set_by_pct_padding(&mut rect.padding, &parent_bound);
-                    Self::set_by_pct_clip(&mut rect.clip, &parent_bound);
-
-                    parent_global_transform = rect.recalculate_transform(parent_global_transform);
-                    let (scale_x, scale_y) = parent_global_transform.scale_xy();
-                    parent_global_transform
-                        .translate_add(rect.padding.left.val() * scale_x, rect.padding.top.val() * scale_y);
-
-                    bound = BoundingBox {
-                        min_x: rect.x.val(),
-                        min_y: rect.y.val(),
-                        max_x: rect.x.val() + rect.width.val(),
-                        max_y: rect.y.val() + rect.height.val(),
-                    };
-                }
-                Shape::Circle(circle) => {
-                    if circle.cx.set_by_pct(parent_bound.width()) {
-                        circle.cx.0 += parent_bound.min_x;
-                    }
-                    if circle.cy.set_by_pct(parent_bound.height()) {
-                        circle.cy.0 += parent_bound.min_y;
-                    }
-                    circle.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
-                    Self::set_by_pct_padding(&mut circle.padding, &parent_bound);
-                    Self::set_by_pct_clip(&mut circle.clip, &parent_bound);
-
-                    parent_global_transform = circle.recalculate_transform(parent_global_transform);
-                    let (scale_x, scale_y) = parent_global_transform.scale_xy();
-                    parent_global_transform
-                        .translate_add(circle.padding.left.val() * scale_x, circle.padding.top.val() * scale_y);
-
-                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
-                    bound = BoundingBox {
-                        min_x: cx - r,
-                        min_y: cy - r,
-                        max_x: cx + r,
-                        max_y: cy + r,
-                    };
-                }
-                Shape::Text(text) => {
-                    if text.x.set_by_pct(parent_bound.width()) {
-                        text.x.0 += parent_bound.min_x;
-                    }
-                    if text.y.set_by_pct(parent_bound.height()) {
-                        text.y.0 += parent_bound.min_y;
-                    }
-                    Self::set_by_pct_clip(&mut text.clip, &parent_bound);
-
-                    parent_global_transform = text.recalculate_transform(parent_global_transform);
-
-                    let nanovg_font = NanovgFont::find(frame.context(), &text.font_name)
-                        .expect(&format!("Font '{}' not found", text.font_name));
-                    let text_options = Self::text_options(text, defaults);
-
-                    let metrics = frame.text_metrics(nanovg_font, text_options);
-                    text.metrics = Some(TextMetrics {
-                        ascender: metrics.ascender,
-                        descender: metrics.descender,
-                        line_height: metrics.line_height,
-                    });
-
-                    text.glyph_positions = frame
-                        .text_glyph_positions((text.x.val() as f32, text.y.val() as f32), &text.content)
-                        .map(|pos| {
-                            let x = pos.x.min(pos.min_x);
-                            GlyphPos {
-                                x,
-                                y: 0.0,
-                                width: pos.max_x - x,
-                            }
-                        })
-                        .collect();
-                    bound = BoundingBox {
-                        min_x: text.x.val(),
-                        min_y: text.y.val(),
-                        max_x: text.x.val() + text.glyph_positions.last().map(|pos| pos.max_x()).unwrap_or(0.0),
-                        max_y: text.y.val() + metrics.line_height as Real,
-                    };
-                }
-                Shape::Path(path) => {
-                    Self::set_by_pct_clip(&mut path.clip, &parent_bound);
-                    parent_global_transform = path.recalculate_transform(parent_global_transform);
-                }
-                Shape::Group(group) => {
-                    Self::set_by_pct_clip(&mut group.clip, &parent_bound);
-                    parent_global_transform = group.recalculate_transform(parent_global_transform);
+    pub fn load_image(&mut self, src: impl Into<String>) -> Result<(), <Self as Render>::Error> {
+        let context = self.static_context()?;
+        Self::cached_image(context, &mut self.images, &src.into())?;
+        Ok(())
+    }
 
-                    if let Some(transparency) = group.transparency {
-                        defaults.transparency = transparency;
-                    }
-                    if let Some(fill) = group.fill {
-                        defaults.fill = Some(fill);
-                    }
-                    if let Some(stroke) = group.stroke {
-                        defaults.stroke = Some(stroke);
-                    }
-                    if !group.clip.is_none() {
-                        defaults.clip = group.clip;
-                    }
+    /// Reads back the rendered pixels of the bound framebuffer with the straight alpha, e.g. to export the scene as an
+    /// image or to compare it with the golden image in the tests. It must be called after the render and before the
+    /// buffers swap, because the content of the back buffer is undefined after the swap.
+    pub fn snapshot(&self) -> RgbaImage {
+        let (width, height) = (self.width as u32, self.height as u32);
+        let mut data = read_bound_pixels(width as i32, height as i32);
+        // Nanovg blends the premultiplied colors.
+        for pixel in data.chunks_mut(4) {
+            let alpha = pixel[3];
+            if alpha != 0 && alpha != u8::MAX {
+                for channel in &mut pixel[..3] {
+                    *channel = (*channel as u32 * u8::MAX as u32 / alpha as u32).min(u8::MAX as u32) as u8;
                 }
             }
         }
+        RgbaImage::from_raw(width, height, data).expect("The pixel data has the size of the framebuffer")
+    }
 
-        let inner_bound = Self::calc_inner_bound(frame, composite, bound, parent_global_transform, defaults);
+    /// Saves the snapshot of the rendered pixels into the PNG file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        self.snapshot()
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(|e| NanovgRenderError::SaveImageError(e, display_path))
+    }
 
-        if let Some(shape) = composite.shape_mut() {
-            match shape {
-                Shape::Rect(rect) => {
-                    rect.x.set_by_auto(inner_bound.min_x);
-                    rect.y.set_by_auto(inner_bound.min_y);
-                    rect.width
-                        .set_by_auto(inner_bound.max_x - rect.x.val() + rect.padding.left_and_right().val());
-                    rect.height
-                        .set_by_auto(inner_bound.max_y - rect.y.val() + rect.padding.top_and_bottom().val());
-
-                    bound = BoundingBox {
-                        min_x: rect.x.val(),
-                        min_y: rect.y.val(),
-                        max_x: rect.x.val() + rect.width.val(),
-                        max_y: rect.y.val() + rect.height.val(),
-                    };
-                }
-                Shape::Circle(circle) => {
-                    circle.cx.set_by_auto(inner_bound.min_x + inner_bound.width() / 2.0);
-                    circle.cy.set_by_auto(inner_bound.min_y + inner_bound.height() / 2.0);
-                    circle.r.set_by_auto(
-                        (inner_bound.width() + circle.padding.left_and_right().val())
-                            .max(inner_bound.height() + circle.padding.top_and_bottom().val())
-                            / 2.0,
-                    );
+    /// The cached images and layers borrow the context, so the borrow is extended to the lifetime of the caches.
+    fn static_context(&self) -> Result<&'static Context, NanovgRenderError> {
+        let context = self.context.as_deref().ok_or(NanovgRenderError::ContextIsNotInit)?;
+        // SAFETY: The context is boxed, so its address is not changed while it is owned by the render. The caches,
+        // which borrow it, are cleared before the context is replaced by the `set_context` or dropped with the render.
+        Ok(unsafe { &*(context as *const Context) })
+    }
 
-                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
-                    bound = BoundingBox {
-                        min_x: cx - r,
-                        min_y: cy - r,
-                        max_x: cx + r,
-                        max_y: cy + r,
-                    };
-                }
-                Shape::Text(text) => {
-                    let transform = text.transform.matrix();
-                    let inner_bound_points = transform * inner_bound;
-                    let bound_points = transform * bound;
-
-                    bound.min_x = bound_points[0].0;
-                    bound.max_x = bound.min_x;
-                    bound.min_y = bound_points[0].1;
-                    bound.max_y = bound.min_y;
-                    for idx in 0..4 {
-                        bound.min_x = bound.min_x.min(bound_points[idx].0).min(inner_bound_points[idx].0);
-                        bound.max_x = bound.max_x.max(bound_points[idx].0).max(inner_bound_points[idx].0);
-                        bound.min_y = bound.min_y.min(bound_points[idx].1).min(inner_bound_points[idx].1);
-                        bound.max_y = bound.max_y.max(bound_points[idx].1).max(inner_bound_points[idx].1);
-                    }
-                }
-                _ => (),
-            }
+    fn cached_image<'a>(
+        context: &'static Context, images: &'a mut ImageCache, src: &str,
+    ) -> Result<&'a NanovgImage<'static>, NanovgRenderError> {
+        if !images.contains_key(src) {
+            let image = NanovgImage::new(context)
+                .build_from_file(src)
+                .map_err(|e| NanovgRenderError::CreateImageError(e, src.to_string()))?;
+            images.insert(src.to_string(), image);
         }
-        bound
+        Ok(&images[src])
     }
 
-    fn calc_inner_bound(
-        frame: &Frame, composite: &mut dyn CompositeShape, bound: BoundingBox,
-        parent_global_transform: TransformMatrix, defaults: &mut ShapeDefaults,
-    ) -> BoundingBox {
-        let mut child_bounds = Vec::new();
-        if let Some(children) = composite.children_mut() {
-            for child in children {
-                child_bounds.push(Self::recalc_composite(
-                    frame,
-                    child,
-                    bound,
-                    parent_global_transform,
-                    defaults,
-                ));
+    fn cached_conic_images(
+        context: &'static Context, images: &mut ImageCache, shape: &Shape, defaults: &ShapeDefaults,
+    ) -> Result<(), NanovgRenderError> {
+        let mut paints = vec![
+            shape.fill().or(defaults.fill.as_ref()).map(|fill| fill.paint.clone()),
+            shape
+                .stroke()
+                .or(defaults.stroke.as_ref())
+                .map(|stroke| stroke.paint.clone()),
+        ];
+        if let Shape::Custom(custom) = shape {
+            for custom_path in custom.shape.draw() {
+                paints.push(custom_path.fill.map(|fill| fill.paint));
+                paints.push(custom_path.stroke.map(|stroke| stroke.paint));
             }
         }
 
-        if child_bounds.is_empty() {
-            BoundingBox::default()
-        } else {
-            let mut inner_bound = child_bounds[0];
-            for bound in &child_bounds[1..] {
-                if bound.min_x < inner_bound.min_x {
-                    inner_bound.min_x = bound.min_x;
-                }
-                if bound.min_y < inner_bound.min_y {
-                    inner_bound.min_y = bound.min_y;
-                }
-                if bound.max_x > inner_bound.max_x {
-                    inner_bound.max_x = bound.max_x;
-                }
-                if bound.max_y > inner_bound.max_y {
-                    inner_bound.max_y = bound.max_y;
+        for paint in paints.into_iter().flatten() {
+            if let Paint::Gradient(Gradient::Conic { stops, .. }) = paint {
+                let key = ToNanovgPaint::conic_image_key(&stops);
+                if let Entry::Vacant(entry) = images.entry(key) {
+                    let image = NanovgImage::new(context)
+                        .build_from_rgba(
+                            CONIC_IMAGE_SIZE,
+                            CONIC_IMAGE_SIZE,
+                            &ToNanovgPaint::conic_image_data(&stops),
+                        )
+                        .map_err(|e| NanovgRenderError::CreateImageError(e, entry.key().clone()))?;
+                    entry.insert(image);
                 }
             }
-            inner_bound
         }
+        Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_composite<'a>(
-        frame: &Frame, composite: &'a dyn CompositeShape, mut text: Option<&'a Text>, defaults: &mut ShapeDefaults,
-    ) {
+        frame: &Frame, images: &ImageCache, fonts: &FontCache, layers: &LayerCache, masks: &ClipMaskCache,
+        stats: &mut RenderStats, composite: &'a dyn CompositeShape, text: Option<&'a Text>,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), NanovgRenderError> {
+        // The shapes with the circle and path clips are drawn before the frame, the clip regions are applied already.
+        if let Some(mask) = composite.shape().and_then(|shape| masks.get(&(shape as *const Shape))) {
+            stats.shapes += 1;
+            Self::draw_layer(frame, mask, 1.0);
+            return Ok(());
+        }
+        Self::render_shape(frame, images, fonts, layers, masks, stats, composite, text, defaults)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_shape<'a>(
+        frame: &Frame, images: &ImageCache, fonts: &FontCache, layers: &LayerCache, masks: &ClipMaskCache,
+        stats: &mut RenderStats, composite: &'a dyn CompositeShape, mut text: Option<&'a Text>,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), NanovgRenderError> {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
         if let Some(shape) = composite.shape() {
+            stats.shapes += 1;
             match shape {
                 Shape::Rect(rect) => {
                     frame.path(
@@ -475,13 +804,16 @@ impl NanovgRender {
                                 path.rect(rect_pos, rect_size);
                             }
                             if let Some(fill) = rect.fill.as_ref().or(defaults.fill.as_ref()) {
-                                path.fill(ToNanovgPaint(fill.paint), Default::default());
+                                path.fill(ToNanovgPaint(&fill.paint, images, rect.bound), Default::default());
                             };
                             if let Some(stroke) = rect.stroke.as_ref().or(defaults.stroke.as_ref()) {
-                                path.stroke(ToNanovgPaint(stroke.paint), Self::stroke_option(&stroke));
+                                path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, rect.bound),
+                                    Self::stroke_option(&stroke),
+                                );
                             }
                         },
-                        Self::path_options(rect.transparency, rect.clip, &rect.transform, defaults),
+                        Self::path_options(rect.transparency, &rect.clip, &rect.transform, defaults),
                     );
                 }
                 Shape::Circle(circle) => {
@@ -489,157 +821,691 @@ impl NanovgRender {
                         |path| {
                             path.circle((circle.cx.val() as f32, circle.cy.val() as f32), circle.r.val() as f32);
                             if let Some(fill) = circle.fill.as_ref().or(defaults.fill.as_ref()) {
-                                path.fill(ToNanovgPaint(fill.paint), Default::default());
+                                path.fill(ToNanovgPaint(&fill.paint, images, circle.bound), Default::default());
                             };
                             if let Some(stroke) = circle.stroke.as_ref().or(defaults.stroke.as_ref()) {
-                                path.stroke(ToNanovgPaint(stroke.paint), Self::stroke_option(&stroke));
+                                path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, circle.bound),
+                                    Self::stroke_option(&stroke),
+                                );
+                            }
+                        },
+                        Self::path_options(circle.transparency, &circle.clip, &circle.transform, defaults),
+                    );
+                }
+                Shape::Ellipse(ellipse) => {
+                    frame.path(
+                        |path| {
+                            path.ellipse(
+                                (ellipse.cx.val() as f32, ellipse.cy.val() as f32),
+                                ellipse.rx.val() as f32,
+                                ellipse.ry.val() as f32,
+                            );
+                            if let Some(fill) = ellipse.fill.as_ref().or(defaults.fill.as_ref()) {
+                                path.fill(ToNanovgPaint(&fill.paint, images, ellipse.bound), Default::default());
+                            };
+                            if let Some(stroke) = ellipse.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                                path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, ellipse.bound),
+                                    Self::stroke_option(&stroke),
+                                );
                             }
                         },
-                        Self::path_options(circle.transparency, circle.clip, &circle.transform, defaults),
+                        Self::path_options(ellipse.transparency, &ellipse.clip, &ellipse.transform, defaults),
                     );
                 }
                 Shape::Path(path) => {
                     frame.path(
                         |nvg_path| {
-                            use exgui_core::PathCommand::*;
-
-                            let mut last_xy = [0.0, 0.0];
-                            let mut bez_ctrls = [(0.0, 0.0), (0.0, 0.0)];
+                            Self::draw_path_commands(&nvg_path, &path.cmd);
+                            if let Some(fill) = path.fill.as_ref().or(defaults.fill.as_ref()) {
+                                nvg_path.fill(ToNanovgPaint(&fill.paint, images, path.bound), Default::default());
+                            };
+                            if let Some(stroke) = path.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                                nvg_path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, path.bound),
+                                    Self::stroke_option(&stroke),
+                                );
+                            }
+                        },
+                        Self::path_options(path.transparency, &path.clip, &path.transform, defaults),
+                    );
 
-                            for cmd in path.cmd.iter() {
-                                match cmd {
-                                    Move(ref xy) => {
-                                        last_xy = *xy;
-                                        nvg_path.move_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    MoveRel(ref xy) => {
-                                        last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
-                                        nvg_path.move_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    Line(ref xy) => {
-                                        last_xy = *xy;
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    LineRel(ref xy) => {
-                                        last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    LineAlonX(ref x) => {
-                                        last_xy[0] = *x;
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    LineAlonXRel(ref x) => {
-                                        last_xy[0] += *x;
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    LineAlonY(ref y) => {
-                                        last_xy[1] = *y;
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    LineAlonYRel(ref y) => {
-                                        last_xy[1] += *y;
-                                        nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
-                                    }
-                                    Close => nvg_path.close(),
-                                    BezCtrl(ref xy) => {
-                                        bez_ctrls = [bez_ctrls[1], (xy[0], xy[1])];
-                                    }
-                                    BezCtrlRel(ref xy) => {
-                                        bez_ctrls = [bez_ctrls[1], (last_xy[0] + xy[0], last_xy[1] + xy[1])];
-                                    }
-                                    QuadBezTo(ref xy) => {
-                                        last_xy = *xy;
-                                        nvg_path.quad_bezier_to(
-                                            (last_xy[0] as f32, last_xy[1] as f32),
-                                            (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
-                                        );
-                                    }
-                                    QuadBezToRel(ref xy) => {
-                                        last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
-                                        nvg_path.quad_bezier_to(
-                                            (last_xy[0] as f32, last_xy[1] as f32),
-                                            (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
-                                        );
-                                    }
-                                    CubBezTo(ref xy) => {
-                                        last_xy = *xy;
-                                        nvg_path.cubic_bezier_to(
-                                            (last_xy[0] as f32, last_xy[1] as f32),
-                                            (bez_ctrls[0].0 as f32, bez_ctrls[0].1 as f32),
-                                            (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
-                                        );
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        Self::render_composite(
+                            frame,
+                            images,
+                            fonts,
+                            layers,
+                            masks,
+                            stats,
+                            marker.shape.as_ref(),
+                            None,
+                            &mut defaults.clone(),
+                        )?;
+                    }
+                }
+                Shape::Polygon(polygon) => {
+                    if let Some(fill) = polygon.fill.as_ref().or(defaults.fill.as_ref()) {
+                        // Nanovg forces the direction of every sub-path by its solidity, so the region of the fill
+                        // rule is filled as the non-overlapping trapezoids.
+                        frame.path(
+                            |path| {
+                                for [first, rest @ ..] in polygon.fill_trapezoids() {
+                                    path.move_to((first[0] as f32, first[1] as f32));
+                                    for point in rest {
+                                        path.line_to((point[0] as f32, point[1] as f32));
                                     }
-                                    CubBezToRel(ref xy) => {
-                                        last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
-                                        nvg_path.cubic_bezier_to(
-                                            (last_xy[0] as f32, last_xy[1] as f32),
-                                            (bez_ctrls[0].0 as f32, bez_ctrls[0].1 as f32),
-                                            (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
-                                        );
+                                    path.close();
+                                }
+                                path.fill(ToNanovgPaint(&fill.paint, images, polygon.bound), Default::default());
+                            },
+                            Self::path_options(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                        );
+                    }
+                    if let Some(stroke) = polygon.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        frame.path(
+                            |path| {
+                                for contour in &polygon.contours {
+                                    let mut points = contour.iter();
+                                    if let Some(first) = points.next() {
+                                        path.move_to((first[0] as f32, first[1] as f32));
+                                        for point in points {
+                                            path.line_to((point[0] as f32, point[1] as f32));
+                                        }
+                                        path.close();
                                     }
-                                    _ => panic!("Not impl rendering cmd {:?}", cmd), // TODO: need refl impl
                                 }
-                            }
-                            if let Some(fill) = path.fill.as_ref().or(defaults.fill.as_ref()) {
-                                nvg_path.fill(ToNanovgPaint(fill.paint), Default::default());
+                                path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, polygon.bound),
+                                    Self::stroke_option(&stroke),
+                                );
+                            },
+                            Self::path_options(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                        );
+                    }
+                }
+                Shape::Image(image) => {
+                    frame.path(
+                        |path| {
+                            let image_pos = (image.x.val() as f32, image.y.val() as f32);
+                            let image_size = (image.width.val() as f32, image.height.val() as f32);
+                            path.rect(image_pos, image_size);
+                            if let Some(fill) = image.fill.as_ref().or(defaults.fill.as_ref()) {
+                                path.fill(ToNanovgPaint(&fill.paint, images, image.bound), Default::default());
                             };
-                            if let Some(stroke) = path.stroke.as_ref().or(defaults.stroke.as_ref()) {
-                                nvg_path.stroke(ToNanovgPaint(stroke.paint), Self::stroke_option(&stroke));
+                            if let Some(nanovg_image) = images.get(&image.src) {
+                                let pattern = ImagePattern {
+                                    image: nanovg_image,
+                                    origin: image_pos,
+                                    size: image_size,
+                                    angle: 0.0,
+                                    alpha: 1.0,
+                                };
+                                path.fill(pattern, Default::default());
+                            }
+                            if let Some(stroke) = image.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                                path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, image.bound),
+                                    Self::stroke_option(&stroke),
+                                );
                             }
                         },
-                        Self::path_options(path.transparency, path.clip, &path.transform, defaults),
+                        Self::path_options(image.transparency, &image.clip, &image.transform, defaults),
                     );
                 }
                 Shape::Text(this_text) => {
+                    stats.texts += 1;
                     text = Some(this_text);
 
-                    let nanovg_font = NanovgFont::find(frame.context(), &this_text.font_name)
-                        .expect(&format!("Font '{}' not found", this_text.font_name));
-                    let text_options = Self::text_options(this_text, defaults);
+                    let input = this_text.input.as_ref().filter(|input| input.focused);
+                    if let Some(input) = input {
+                        if let Some(range) = input.selection() {
+                            let (start_x, end_x) = (this_text.caret_x(range.start), this_text.caret_x(range.end));
+                            let color = input.selection_color;
+                            Self::draw_text_line_rect(frame, this_text, start_x, end_x - start_x, color, defaults);
+                        }
+                    }
 
-                    frame.text(
-                        nanovg_font,
-                        (this_text.x.val() as f32, this_text.y.val() as f32),
-                        &this_text.content,
-                        text_options,
-                    );
-                }
-                Shape::Group(group) => {
-                    if let Some(transparency) = group.transparency {
-                        defaults.transparency = transparency;
+                    if let Some(text_path) = &this_text.path {
+                        let (nanovg_font, face_match) = fonts.find_face(
+                            frame.context(),
+                            &this_text.font_name,
+                            this_text.font_weight,
+                            this_text.font_style,
+                        )?;
+                        let mut text_options = Self::text_options(this_text, defaults);
+                        // Each glyph is drawn from its left edge.
+                        text_options.align = Self::text_align(AlignHor::Left, this_text.align.1);
+                        let text_matrix = this_text
+                            .transform
+                            .calculated_matrix()
+                            .unwrap_or_else(|| this_text.transform.matrix());
+
+                        let glyphs = this_text.content.chars().zip(&this_text.glyph_positions);
+                        for ((ch, pos), glyph_transform) in glyphs.zip(&text_path.glyph_transforms) {
+                            if let Some(glyph_transform) = glyph_transform {
+                                let mut transform = text_options.transform.unwrap_or_else(NanovgTransform::new);
+                                transform.matrix = (text_matrix * *glyph_transform).matrix;
+                                let glyph_options = TextOptions {
+                                    transform: Some(transform),
+                                    ..text_options
+                                };
+                                Self::draw_text(
+                                    frame,
+                                    nanovg_font,
+                                    &face_match,
+                                    (pos.x as f32, this_text.y.val() as f32),
+                                    ch.encode_utf8(&mut [0; 4]),
+                                    glyph_options,
+                                );
+                            }
+                        }
+                    } else if this_text.spans.is_empty() {
+                        let (nanovg_font, face_match) = fonts.find_face(
+                            frame.context(),
+                            &this_text.font_name,
+                            this_text.font_weight,
+                            this_text.font_style,
+                        )?;
+                        let text_options = Self::text_options(this_text, defaults);
+
+                        Self::draw_text(
+                            frame,
+                            nanovg_font,
+                            &face_match,
+                            (this_text.x.val() as f32, this_text.y.val() as f32),
+                            &this_text.content,
+                            text_options,
+                        );
                     }
-                    if let Some(fill) = group.fill {
-                        defaults.fill = Some(fill);
+                    for span in this_text.spans.iter().filter(|_| this_text.path.is_none()) {
+                        let (family, weight, style) = Self::span_face(this_text, span);
+                        let (nanovg_font, face_match) = fonts.find_face(frame.context(), family, weight, style)?;
+                        Self::draw_text(
+                            frame,
+                            nanovg_font,
+                            &face_match,
+                            ((this_text.x.val() + span.offset) as f32, this_text.y.val() as f32),
+                            &span.content,
+                            Self::span_options(this_text, span, defaults),
+                        );
                     }
-                    if let Some(stroke) = group.stroke {
-                        defaults.stroke = Some(stroke);
+
+                    if let Some(fill) = this_text.fill.as_ref().or(defaults.fill.as_ref()) {
+                        for line in this_text.decoration_lines() {
+                            let paint = ToNanovgPaint(&fill.paint, images, this_text.bound);
+                            Self::fill_text_rect(frame, this_text, line, paint, defaults);
+                        }
+                    }
+
+                    if let Some(input) = input.filter(|input| input.caret_visible) {
+                        let caret_x = this_text.caret_x(input.caret) - CARET_WIDTH / 2.0;
+                        let color = input.caret_color;
+                        Self::draw_text_line_rect(frame, this_text, caret_x, CARET_WIDTH, color, defaults);
+                    }
+                }
+                Shape::Custom(custom) => {
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .as_ref()
+                            .or(custom.fill.as_ref())
+                            .or(defaults.fill.as_ref());
+                        let stroke = custom_path
+                            .stroke
+                            .as_ref()
+                            .or(custom.stroke.as_ref())
+                            .or(defaults.stroke.as_ref());
+                        frame.path(
+                            |nvg_path| {
+                                Self::draw_path_commands(&nvg_path, &custom_path.cmd);
+                                if let Some(fill) = fill {
+                                    nvg_path.fill(ToNanovgPaint(&fill.paint, images, custom.bound), Default::default());
+                                };
+                                if let Some(stroke) = stroke {
+                                    nvg_path.stroke(
+                                        ToNanovgPaint(&stroke.paint, images, custom.bound),
+                                        Self::stroke_option(stroke),
+                                    );
+                                }
+                            },
+                            Self::path_options(custom.transparency, &custom.clip, &custom.transform, defaults),
+                        );
                     }
-                    if !group.clip.is_none() {
-                        defaults.clip = group.clip;
+                }
+                Shape::Sector(sector) => {
+                    frame.path(
+                        |nvg_path| {
+                            Self::draw_path_commands(&nvg_path, &sector.commands());
+                            if let Some(fill) = sector.fill.as_ref().or(defaults.fill.as_ref()) {
+                                nvg_path.fill(ToNanovgPaint(&fill.paint, images, sector.bound), Default::default());
+                            };
+                            if let Some(stroke) = sector.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                                nvg_path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, sector.bound),
+                                    Self::stroke_option(&stroke),
+                                );
+                            }
+                        },
+                        Self::path_options(sector.transparency, &sector.clip, &sector.transform, defaults),
+                    );
+                }
+                Shape::Ring(ring) => {
+                    frame.path(
+                        |nvg_path| {
+                            Self::draw_path_commands(&nvg_path, &ring.commands());
+                            if ring.is_full() {
+                                nvg_path.winding(Winding::Solidity(Solidity::Hole));
+                            }
+                            if let Some(fill) = ring.fill.as_ref().or(defaults.fill.as_ref()) {
+                                nvg_path.fill(ToNanovgPaint(&fill.paint, images, ring.bound), Default::default());
+                            };
+                            if let Some(stroke) = ring.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                                nvg_path.stroke(
+                                    ToNanovgPaint(&stroke.paint, images, ring.bound),
+                                    Self::stroke_option(&stroke),
+                                );
+                            }
+                        },
+                        Self::path_options(ring.transparency, &ring.clip, &ring.transform, defaults),
+                    );
+                }
+                Shape::Group(group) => {
+                    defaults.apply_group(group);
+                    if group.need_layer() {
+                        if let Some(layer) = layers.get(&(group as *const Group)) {
+                            Self::draw_layer(frame, layer, (1.0 - defaults.transparency) as f32);
+                            return Ok(());
+                        }
                     }
                 }
             }
         }
-        if let Some(children) = composite.children() {
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(frame, images, fonts, layers, masks, stats, child, text, defaults)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the children of the layer groups and the shapes with the circle and path clips into the offscreen
+    /// images, the nested ones go first. The layers of the cached groups, which are not changed, are moved from the
+    /// last layers.
+    #[allow(clippy::too_many_arguments)]
+    fn render_layers(
+        context: &'static Context, images: &ImageCache, fonts: &FontCache, last_layers: &mut LayerCache,
+        layers: &mut LayerCache, masks: &mut ClipMaskCache, stats: &mut RenderStats, composite: &dyn CompositeShape,
+        size: (f32, f32), device_pixel_ratio: f32, defaults: &mut ShapeDefaults,
+    ) -> Result<(), NanovgRenderError> {
+        let mut portal_defaults = None;
+        let defaults = if composite.is_portal() {
+            portal_defaults.insert(ShapeDefaults::new(defaults.theme.clone()))
+        } else {
+            defaults
+        };
+        // The masked shape is drawn by the same defaults as in the main pass.
+        let mask_defaults = composite
+            .shape()
+            .filter(|shape| matches!(shape.clip(), Clip::Circle(_) | Clip::Path(_)))
+            .map(|_| defaults.clone());
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        let mut layer_group = None;
+        if let Some(Shape::Group(group)) = composite.shape() {
+            defaults.apply_group(group);
+            if group.need_layer() {
+                layer_group = Some(group);
+            }
+        }
+
+        let mut layer_defaults = defaults.clone();
+        // The group transparency is applied once, when the layer is drawn.
+        layer_defaults.transparency = 0.0;
+        let layer_state = layer_group.and_then(|_| LayerState::of(composite, &layer_defaults));
+        let mut cached = false;
+        if let (Some(group), Some(state)) = (layer_group, &layer_state) {
+            let key = group as *const Group;
+            if let Some(layer) = last_layers.remove(&key).filter(|layer| layer.state.as_ref() == Some(state)) {
+                stats.cache_hits += 1;
+                layers.insert(key, layer);
+                cached = true;
+            }
+        }
+
+        if let Some(children) = composite.children().filter(|_| !cached) {
+            // The layer children are not rendered in the main pass, so they do not change the outer defaults.
+            let mut children_defaults = layer_defaults.clone();
+            let defaults = if layer_group.is_some() {
+                &mut children_defaults
+            } else {
+                &mut *defaults
+            };
             for child in children {
-                Self::render_composite(frame, child, text, defaults);
+                Self::render_layers(
+                    context,
+                    images,
+                    fonts,
+                    last_layers,
+                    layers,
+                    masks,
+                    stats,
+                    child,
+                    size,
+                    device_pixel_ratio,
+                    defaults,
+                )?;
             }
         }
+
+        if let Some(group) = layer_group.filter(|_| !cached) {
+            let spread = group.filters.iter().map(Filter::spread).sum::<Real>();
+            let window = Self::physical_size(size, device_pixel_ratio);
+            if let Some(area) = LayerArea::of(composite, spread, window, device_pixel_ratio) {
+                let framebuffer = Self::draw_offscreen(context, size, device_pixel_ratio, area, |frame| {
+                    composite
+                        .children_in_draw_order()
+                        .into_iter()
+                        .flatten()
+                        .filter(|child| !child.is_portal())
+                        .try_for_each(|child| {
+                            Self::render_composite(
+                                frame,
+                                images,
+                                fonts,
+                                layers,
+                                masks,
+                                stats,
+                                child,
+                                None,
+                                &mut layer_defaults,
+                            )
+                        })
+                })?;
+                let image = if group.filters.is_empty() {
+                    LayerImage::Texture(TextureImage::new(context, framebuffer))
+                } else {
+                    let (width, height) = (area.width as usize, area.height as usize);
+                    let pixels = apply_filters(
+                        &group.filters,
+                        framebuffer.read_pixels(),
+                        width,
+                        height,
+                        device_pixel_ratio,
+                    );
+                    let image = NanovgImage::new(context)
+                        .premultiplied()
+                        .build_from_rgba(width, height, &pixels)
+                        .map_err(|e| NanovgRenderError::CreateImageError(e, format!("layer {:?}", group.id())))?;
+                    LayerImage::Filtered(image)
+                };
+                let (origin, size) = area.logical(device_pixel_ratio);
+                layers.insert(
+                    group as *const Group,
+                    Layer {
+                        image,
+                        origin,
+                        size,
+                        state: layer_state,
+                    },
+                );
+            }
+        }
+
+        if let (Some(shape), Some(mut mask_defaults)) = (composite.shape(), mask_defaults) {
+            let mask = Self::render_masked(
+                context,
+                images,
+                fonts,
+                layers,
+                masks,
+                stats,
+                composite,
+                size,
+                device_pixel_ratio,
+                &mut mask_defaults,
+            )?;
+            if let Some(mask) = mask {
+                masks.insert(shape as *const Shape, mask);
+            }
+        }
+        Ok(())
+    }
+
+    /// NanoVG clips by the scissors only, so the shape with the circle or path clip is drawn into the offscreen image
+    /// and the drawn pixels are kept inside the clip region by the mask image, which is the region filled by the
+    /// opaque color. The shape, which is drawn out of the window, has no masked image.
+    #[allow(clippy::too_many_arguments)]
+    fn render_masked(
+        context: &'static Context, images: &ImageCache, fonts: &FontCache, layers: &LayerCache, masks: &ClipMaskCache,
+        stats: &mut RenderStats, composite: &dyn CompositeShape, size: (f32, f32), device_pixel_ratio: f32,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<Option<Layer>, NanovgRenderError> {
+        let window = Self::physical_size(size, device_pixel_ratio);
+        let area = match LayerArea::of(composite, 0.0, window, device_pixel_ratio) {
+            Some(area) => area,
+            None => return Ok(None),
+        };
+        let (origin, area_size) = area.logical(device_pixel_ratio);
+
+        let clip = composite.shape().map(Shape::clip).unwrap_or(&Clip::None);
+        let framebuffer = Self::draw_offscreen(context, size, device_pixel_ratio, area, |frame| {
+            if let Some(outline) = clip.outline() {
+                frame.path(
+                    |path| {
+                        Self::draw_path_commands(&path, &outline);
+                        path.fill(NanovgColor::new(0.0, 0.0, 0.0, 1.0), Default::default());
+                    },
+                    PathOptions {
+                        transform: clip.transform().and_then(Self::nanovg_transform),
+                        ..Default::default()
+                    },
+                );
+            }
+            Ok(())
+        })?;
+        let mask = TextureImage::new(context, framebuffer);
+
+        let framebuffer = Self::draw_offscreen(context, size, device_pixel_ratio, area, |frame| {
+            Self::render_shape(frame, images, fonts, layers, masks, stats, composite, None, defaults)?;
+            frame.path(
+                |path| {
+                    path.rect(origin, area_size);
+                    let pattern = TexturePattern {
+                        image: &mask,
+                        origin,
+                        size: area_size,
+                        alpha: 1.0,
+                    };
+                    path.fill(pattern, Default::default());
+                },
+                PathOptions {
+                    composite_operation: CompositeOperation::Basic(BasicCompositeOperation::DestinationIn),
+                    ..Default::default()
+                },
+            );
+            Ok(())
+        })?;
+        Ok(Some(Layer {
+            image: LayerImage::Texture(TextureImage::new(context, framebuffer)),
+            origin,
+            size: area_size,
+            state: None,
+        }))
+    }
+
+    fn physical_size((width, height): (f32, f32), device_pixel_ratio: f32) -> (i32, i32) {
+        (
+            (width * device_pixel_ratio).round() as i32,
+            (height * device_pixel_ratio).round() as i32,
+        )
+    }
+
+    /// Draws the frame of the window `size` into the offscreen framebuffer, which has the layer area of the window
+    /// only.
+    fn draw_offscreen(
+        context: &'static Context, size: (f32, f32), device_pixel_ratio: f32, area: LayerArea,
+        mut draw: impl FnMut(&Frame) -> Result<(), NanovgRenderError>,
+    ) -> Result<Framebuffer, NanovgRenderError> {
+        let framebuffer = Framebuffer::new(area.width, area.height);
+        let bound = framebuffer.bind(area.viewport(Self::physical_size(size, device_pixel_ratio)));
+        let mut result = Ok(());
+        context.frame(size, device_pixel_ratio, |frame| result = draw(&frame));
+        drop(bound);
+        result.map(|_| framebuffer)
     }
 
-    fn set_by_pct_padding(padding: &mut Padding, parent_bound: &BoundingBox) {
-        padding.left.set_by_pct(parent_bound.width());
-        padding.right.set_by_pct(parent_bound.width());
-        padding.top.set_by_pct(parent_bound.height());
-        padding.bottom.set_by_pct(parent_bound.height());
+    fn draw_layer(frame: &Frame, layer: &Layer, alpha: f32) {
+        frame.path(
+            |path| {
+                path.rect(layer.origin, layer.size);
+                match &layer.image {
+                    LayerImage::Texture(image) => {
+                        let pattern = TexturePattern {
+                            image,
+                            origin: layer.origin,
+                            size: layer.size,
+                            alpha: 1.0,
+                        };
+                        path.fill(pattern, Default::default());
+                    },
+                    LayerImage::Filtered(image) => {
+                        let pattern = ImagePattern {
+                            image,
+                            origin: layer.origin,
+                            size: layer.size,
+                            angle: 0.0,
+                            alpha: 1.0,
+                        };
+                        path.fill(pattern, Default::default());
+                    },
+                }
+            },
+            PathOptions {
+                alpha,
+                ..Default::default()
+            },
+        );
     }
 
-    fn set_by_pct_clip(clip: &mut Clip, parent_bound: &BoundingBox) {
-        if let Clip::Scissor(scissor) = clip {
-            scissor.x.set_by_pct(parent_bound.width());
-            scissor.y.set_by_pct(parent_bound.height());
-            scissor.width.set_by_pct(parent_bound.width());
-            scissor.height.set_by_pct(parent_bound.height());
+    fn draw_path_commands(nvg_path: &NanovgPath, commands: &[PathCommand]) {
+        use exgui_core::PathCommand::*;
+
+        let mut last_xy = [0.0, 0.0];
+        let mut bez_ctrls = [(0.0, 0.0), (0.0, 0.0)];
+
+        for cmd in commands.iter() {
+            match cmd {
+                Move(ref xy) => {
+                    last_xy = *xy;
+                    nvg_path.move_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                MoveRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    nvg_path.move_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                Line(ref xy) => {
+                    last_xy = *xy;
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                LineRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                LineAlonX(ref x) => {
+                    last_xy[0] = *x;
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                LineAlonXRel(ref x) => {
+                    last_xy[0] += *x;
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                LineAlonY(ref y) => {
+                    last_xy[1] = *y;
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                LineAlonYRel(ref y) => {
+                    last_xy[1] += *y;
+                    nvg_path.line_to((last_xy[0] as f32, last_xy[1] as f32));
+                }
+                Close => nvg_path.close(),
+                BezCtrl(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], (xy[0], xy[1])];
+                }
+                BezCtrlRel(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], (last_xy[0] + xy[0], last_xy[1] + xy[1])];
+                }
+                BezReflectCtrl => {
+                    let reflected = (2.0 * last_xy[0] - bez_ctrls[1].0, 2.0 * last_xy[1] - bez_ctrls[1].1);
+                    bez_ctrls = [bez_ctrls[1], reflected];
+                }
+                QuadBezTo(ref xy) => {
+                    last_xy = *xy;
+                    nvg_path.quad_bezier_to(
+                        (last_xy[0] as f32, last_xy[1] as f32),
+                        (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
+                    );
+                }
+                QuadBezToRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    nvg_path.quad_bezier_to(
+                        (last_xy[0] as f32, last_xy[1] as f32),
+                        (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
+                    );
+                }
+                CubBezTo(ref xy) => {
+                    last_xy = *xy;
+                    nvg_path.cubic_bezier_to(
+                        (last_xy[0] as f32, last_xy[1] as f32),
+                        (bez_ctrls[0].0 as f32, bez_ctrls[0].1 as f32),
+                        (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
+                    );
+                }
+                CubBezToRel(ref xy) => {
+                    last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                    nvg_path.cubic_bezier_to(
+                        (last_xy[0] as f32, last_xy[1] as f32),
+                        (bez_ctrls[0].0 as f32, bez_ctrls[0].1 as f32),
+                        (bez_ctrls[1].0 as f32, bez_ctrls[1].1 as f32),
+                    );
+                }
+                Arc {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let segments = arc_to_cub_beziers(last_xy, *radius, *rotation, *large_arc, *sweep, *to);
+                    for [ctrl1, ctrl2, xy] in segments {
+                        nvg_path.cubic_bezier_to(
+                            (xy[0] as f32, xy[1] as f32),
+                            (ctrl1[0] as f32, ctrl1[1] as f32),
+                            (ctrl2[0] as f32, ctrl2[1] as f32),
+                        );
+                    }
+                    last_xy = *to;
+                }
+                ArcRel {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let to = [last_xy[0] + to[0], last_xy[1] + to[1]];
+                    let segments = arc_to_cub_beziers(last_xy, *radius, *rotation, *large_arc, *sweep, to);
+                    for [ctrl1, ctrl2, xy] in segments {
+                        nvg_path.cubic_bezier_to(
+                            (xy[0] as f32, xy[1] as f32),
+                            (ctrl1[0] as f32, ctrl1[1] as f32),
+                            (ctrl2[0] as f32, ctrl2[1] as f32),
+                        );
+                    }
+                    last_xy = to;
+                }
+            }
         }
     }
 
@@ -667,23 +1533,25 @@ impl NanovgRender {
         }
     }
 
+    /// NanoVG clips by the scissors only, so the circle and path clips are replaced by their bounding scissors. The
+    /// shapes with such clips are masked by the clip regions in the `render_masked`.
     fn nanovg_clip(clip: &Clip) -> NanovgClip {
-        match clip {
-            Clip::Scissor(scissor) => NanovgClip::Scissor(NanovgScissor {
+        match clip.bounding_scissor() {
+            Some(scissor) => NanovgClip::Scissor(NanovgScissor {
                 x: scissor.x.val() as f32,
                 y: scissor.y.val() as f32,
                 width: scissor.width.val() as f32,
                 height: scissor.height.val() as f32,
                 transform: Self::nanovg_transform(&scissor.transform),
             }),
-            Clip::None => NanovgClip::None,
+            None => NanovgClip::None,
         }
     }
 
-    fn path_options(transparency: Real, clip: Clip, transform: &Transform, defaults: &ShapeDefaults) -> PathOptions {
+    fn path_options(transparency: Real, clip: &Clip, transform: &Transform, defaults: &ShapeDefaults) -> PathOptions {
         PathOptions {
             alpha: ((1.0 - transparency) * (1.0 - defaults.transparency)) as f32,
-            clip: Self::nanovg_clip(&clip.or(defaults.clip)),
+            clip: Self::nanovg_clip(&clip.intersect(&defaults.clip)),
             transform: Self::nanovg_transform(transform),
             ..Default::default()
         }
@@ -709,8 +1577,116 @@ impl NanovgRender {
         }
     }
 
+    /// Options of the measured text line, it is aligned to the left, so the glyphs start at the zero origin.
+    fn measure_options(font_size: Real) -> TextOptions {
+        TextOptions {
+            size: font_size as f32,
+            align: Self::text_align(AlignHor::Left, AlignVer::Baseline),
+            ..Default::default()
+        }
+    }
+
+    fn metrics(metrics: &nanovg::TextMetrics) -> TextMetrics {
+        TextMetrics {
+            ascender: metrics.ascender,
+            descender: metrics.descender,
+            line_height: metrics.line_height,
+        }
+    }
+
+    fn glyph_pos(pos: GlyphPosition) -> GlyphPos {
+        let x = pos.x.min(pos.min_x);
+        GlyphPos {
+            x,
+            y: 0.0,
+            width: pos.max_x - x,
+        }
+    }
+
+    /// Fills the rect of the text line height, it is used for the caret and the selection of the text input.
+    fn draw_text_line_rect(frame: &Frame, text: &Text, x: Real, width: Real, color: Color, defaults: &ShapeDefaults) {
+        let height = text
+            .metrics
+            .map(|metrics| metrics.line_height)
+            .unwrap_or_else(|| text.font_size.val());
+        let rect = BoundingBox {
+            min_x: x,
+            min_y: text.y.val(),
+            max_x: x + width,
+            max_y: text.y.val() + height,
+        };
+        Self::fill_text_rect(frame, text, rect, ToNanovgPaint::to_nanovg_color(color), defaults);
+    }
+
+    fn fill_text_rect(
+        frame: &Frame, text: &Text, rect: BoundingBox, paint: impl NanovgPaint, defaults: &ShapeDefaults,
+    ) {
+        frame.path(
+            |path| {
+                path.rect(
+                    (rect.min_x as f32, rect.min_y as f32),
+                    (rect.width() as f32, rect.height() as f32),
+                );
+                path.fill(paint, Default::default());
+            },
+            Self::path_options(text.transparency, &text.clip, &text.transform, defaults),
+        );
+    }
+
+    /// Font family, weight and style of the span, inherited from the text if not set.
+    fn span_face<'a>(text: &'a Text, span: &'a TextSpan) -> (&'a str, FontWeight, FontStyle) {
+        (
+            span.font_name.as_ref().unwrap_or(&text.font_name),
+            span.font_weight.unwrap_or(text.font_weight),
+            span.font_style.unwrap_or(text.font_style),
+        )
+    }
+
+    /// Draws the text with the matched face, the missing bold is synthesized by the shifted overdraw and the missing
+    /// italic by the skew around the baseline.
+    fn draw_text(
+        frame: &Frame, font: NanovgFont, face_match: &FaceMatch, (x, y): (f32, f32), content: &str,
+        mut options: TextOptions,
+    ) {
+        if face_match.synthetic_italic {
+            let mut transform = options.transform.unwrap_or_else(NanovgTransform::new);
+            let skew = TransformMatrix::identity()
+                .with_skew(-SYNTHETIC_ITALIC_SKEW, 0.0)
+                .with_translation(SYNTHETIC_ITALIC_SKEW * y as Real, 0.0);
+            transform.matrix = (TransformMatrix {
+                matrix: transform.matrix,
+            } * skew)
+                .matrix;
+            options.transform = Some(transform);
+        }
+
+        frame.text(font, (x, y), content, options);
+        if face_match.synthetic_bold {
+            frame.text(font, (x + options.size * SYNTHETIC_BOLD_SHIFT, y), content, options);
+        }
+    }
+
+    /// Options of the span, which is aligned horizontally as a part of the span row.
+    fn span_options(text: &Text, span: &TextSpan, defaults: &ShapeDefaults) -> TextOptions {
+        let mut options = Self::text_options(text, defaults);
+        if let Some(Paint::Color(color)) = span.fill.as_ref().map(|fill| &fill.paint) {
+            options.color = Self::text_color(*color, text, defaults);
+        }
+        if let Some(font_size) = span.font_size {
+            options.size = font_size.val() as f32;
+        }
+        options.align = Self::text_align(AlignHor::Left, text.align.1);
+        options
+    }
+
+    fn text_color(color: Color, text: &Text, defaults: &ShapeDefaults) -> NanovgColor {
+        let mut color = ToNanovgPaint::to_nanovg_color(color);
+        color.set_alpha(color.alpha() * (1.0 - defaults.transparency) * (1.0 - text.transparency));
+        color
+    }
+
     fn text_options(text: &Text, defaults: &ShapeDefaults) -> TextOptions {
-        let mut color = ToNanovgPaint::to_nanovg_color(
+        let color = Self::text_color(
             text.fill
                 .as_ref()
                 .or(defaults.fill.as_ref())
@@ -722,29 +1698,32 @@ impl NanovgRender {
                     }
                 })
                 .unwrap_or_default(),
+            text,
+            defaults,
         );
-        color.set_alpha(color.alpha() * (1.0 - defaults.transparency) * (1.0 - text.transparency));
 
+        TextOptions {
+            color,
+            size: text.font_size.val() as f32,
+            align: Self::text_align(text.align.0, text.align.1),
+            clip: Self::nanovg_clip(&text.clip.intersect(&defaults.clip)),
+            transform: Self::nanovg_transform(&text.transform),
+            ..Default::default()
+        }
+    }
+
+    fn text_align(align_hor: AlignHor, align_ver: AlignVer) -> Alignment {
         let mut align = Alignment::new();
-        align = match text.align.0 {
+        align = match align_hor {
             AlignHor::Left => align.left(),
             AlignHor::Right => align.right(),
             AlignHor::Center => align.center(),
         };
-        align = match text.align.1 {
+        match align_ver {
             AlignVer::Bottom => align.bottom(),
             AlignVer::Middle => align.middle(),
             AlignVer::Baseline => align.baseline(),
             AlignVer::Top => align.top(),
-        };
-
-        TextOptions {
-            color,
-            size: text.font_size.val() as f32,
-            align,
-            clip: Self::nanovg_clip(&text.clip.or(defaults.clip)),
-            transform: Self::nanovg_transform(&text.transform),
-            ..Default::default()
         }
     }
 }
@@ -0,0 +1,94 @@
+use std::os::raw::{c_int, c_void};
+
+use gl::types::GLuint;
+use nanovg::{Context, Paint as NanovgPaint};
+
+use crate::framebuffer::Framebuffer;
+
+/// The rows of the framebuffer texture go from bottom to top.
+const NVG_IMAGE_FLIPY: c_int = 1 << 3;
+const NVG_IMAGE_PREMULTIPLIED: c_int = 1 << 4;
+/// The texture is deleted by the framebuffer, not by nanovg.
+const NVG_IMAGE_NODELETE: c_int = 1 << 16;
+
+/// The `NVGpaint` of nanovg.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NvgPaint {
+    xform: [f32; 6],
+    extent: [f32; 2],
+    radius: f32,
+    feather: f32,
+    inner_color: [f32; 4],
+    outer_color: [f32; 4],
+    image: c_int,
+}
+
+// Nanovg-rs creates the images of the pixel data only, so the images of the GL textures are created by the nanovg
+// functions, which are linked with nanovg-rs.
+extern "C" {
+    fn nvglCreateImageFromHandleGL3(ctx: *mut c_void, texture_id: GLuint, w: c_int, h: c_int, flags: c_int) -> c_int;
+    fn nvgDeleteImage(ctx: *mut c_void, image: c_int);
+    fn nvgImagePattern(
+        ctx: *mut c_void, ox: f32, oy: f32, ex: f32, ey: f32, angle: f32, image: c_int, alpha: f32,
+    ) -> NvgPaint;
+    fn nvgFillPaint(ctx: *mut c_void, paint: NvgPaint);
+    fn nvgStrokePaint(ctx: *mut c_void, paint: NvgPaint);
+}
+
+/// Nanovg image of the framebuffer texture, it is drawn without reading the pixels back.
+#[derive(Debug)]
+pub struct TextureImage {
+    context: &'static Context,
+    /// Owner of the texture, which is drawn until the image is dropped.
+    _framebuffer: Framebuffer,
+    image: c_int,
+}
+
+impl TextureImage {
+    pub fn new(context: &'static Context, framebuffer: Framebuffer) -> Self {
+        let (width, height) = framebuffer.size();
+        let flags = NVG_IMAGE_FLIPY | NVG_IMAGE_PREMULTIPLIED | NVG_IMAGE_NODELETE;
+        let image = unsafe { nvglCreateImageFromHandleGL3(raw(context), framebuffer.texture(), width, height, flags) };
+        Self {
+            context,
+            _framebuffer: framebuffer,
+            image,
+        }
+    }
+}
+
+impl Drop for TextureImage {
+    fn drop(&mut self) {
+        unsafe { nvgDeleteImage(raw(self.context), self.image) }
+    }
+}
+
+/// Image pattern of the texture image, like the `ImagePattern` of the nanovg-rs images.
+pub struct TexturePattern<'a> {
+    pub image: &'a TextureImage,
+    pub origin: (f32, f32),
+    pub size: (f32, f32),
+    pub alpha: f32,
+}
+
+impl<'a> TexturePattern<'a> {
+    fn paint(&self, context: &Context) -> NvgPaint {
+        let ((x, y), (width, height)) = (self.origin, self.size);
+        unsafe { nvgImagePattern(raw(context), x, y, width, height, 0.0, self.image.image, self.alpha) }
+    }
+}
+
+impl<'a> NanovgPaint for TexturePattern<'a> {
+    fn fill(&self, context: &Context) {
+        unsafe { nvgFillPaint(raw(context), self.paint(context)) }
+    }
+
+    fn stroke(&self, context: &Context) {
+        unsafe { nvgStrokePaint(raw(context), self.paint(context)) }
+    }
+}
+
+fn raw(context: &Context) -> *mut c_void {
+    context.raw() as *mut c_void
+}
@@ -2,15 +2,17 @@ use std::{
     fmt,
     fs::File,
     io::{self, Read},
-    iter,
-    ops::Mul,
+    iter, mem,
     path::Path,
+    rc::Rc,
     sync::Arc,
 };
 
 use exgui_core::{
-    AlignHor, AlignVer, Clip, Color, CompositeShape, Fill, GlyphPos, Gradient, LineCap, LineJoin, Padding, Paint, Real,
-    Render, Rounding, Shape, Stroke, Text, TextMetrics, Transform, TransformMatrix,
+    arc_to_cub_beziers, recalc_in_view, AlignHor, AlignVer, BoundingBox, Clip, Color, CompositeShape, Damage, Fill,
+    GlyphPos, Gradient, LayoutMeasurer, LineCap, LineJoin, Paint, PathCommand, Real, Render, Rounding, Shape,
+    ShapeDefaults, Stroke, Text, TextCache, TextFace, TextMeasurer, TextMetrics, TextSpan, Theme, Transform,
+    TransformMatrix,
 };
 use font_kit::handle::Handle;
 use pathfinder_canvas::{
@@ -34,37 +36,6 @@ use skribo::TextStyle;
 
 const PI_2: f32 = std::f32::consts::PI * 2.0;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
-pub struct BoundingBox {
-    pub min_x: Real,
-    pub min_y: Real,
-    pub max_x: Real,
-    pub max_y: Real,
-}
-
-impl BoundingBox {
-    pub fn width(&self) -> Real {
-        self.max_x - self.min_x
-    }
-
-    pub fn height(&self) -> Real {
-        self.max_y - self.min_y
-    }
-}
-
-impl Mul<BoundingBox> for TransformMatrix {
-    type Output = [(Real, Real); 4];
-
-    fn mul(self, rhs: BoundingBox) -> Self::Output {
-        [
-            self * (rhs.min_x, rhs.min_y),
-            self * (rhs.min_x, rhs.max_y),
-            self * (rhs.max_x, rhs.min_y),
-            self * (rhs.max_x, rhs.max_y),
-        ]
-    }
-}
-
 #[derive(Debug)]
 pub enum PathfinderRenderError {
     ContextIsNotInit,
@@ -93,6 +64,14 @@ pub struct PathfinderRender {
     pub width: u32,
     pub height: u32,
     pub framebuffer_size: Vector2I,
+    /// Device pixel ratio of the framebuffer, `None` is 1.
+    pub device_pixel_ratio: Option<f32>,
+    pub theme: Rc<Theme>,
+    pub text_cache: TextCache,
+    theme_changed: bool,
+    /// Camera transform of the scene, `None` is the identity.
+    view_transform: Option<TransformMatrix>,
+    view_changed: bool,
 }
 
 impl Render for PathfinderRender {
@@ -120,7 +99,8 @@ impl Render for PathfinderRender {
         Ok(())
     }
 
-    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, _device_pixel_ratio: f64) {
+    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
+        self.device_pixel_ratio = Some(device_pixel_ratio as f32);
         if self.width != physical_width || self.height != physical_height {
             let framebuffer_size = vec2i(physical_width as i32, physical_height as i32);
             self.width = physical_width;
@@ -134,33 +114,62 @@ impl Render for PathfinderRender {
         }
     }
 
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn set_view_transform(&mut self, transform: TransformMatrix) {
+        if transform != self.view_transform() {
+            self.view_transform = Some(transform);
+            self.view_changed = true;
+        }
+    }
+
+    fn view_transform(&self) -> TransformMatrix {
+        self.view_transform.unwrap_or_else(TransformMatrix::identity)
+    }
+
     fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let view_changed = mem::take(&mut self.view_changed);
+        let view_transform = self.view_transform();
         let renderer_context = self.context.as_mut().ok_or(PathfinderRenderError::ContextIsNotInit)?;
         let mut canvas_context =
             Canvas::new(self.framebuffer_size.to_f32()).get_context_2d(renderer_context.font_context.clone());
+        // The shapes are laid out in the logical pixels and scaled into the framebuffer.
+        let device_pixel_ratio = self.device_pixel_ratio.unwrap_or(1.0);
+        let base_transform = Transform2F::from_scale(vec2f(device_pixel_ratio, device_pixel_ratio));
+        canvas_context.set_transform(&base_transform);
 
         let bound = BoundingBox {
             min_x: 0.0,
             min_y: 0.0,
-            max_x: self.width as Real,
-            max_y: self.height as Real,
+            max_x: self.width as Real / device_pixel_ratio,
+            max_y: self.height as Real / device_pixel_ratio,
         };
 
         // Recalculate tree data and fill canvas
-        if node.need_recalc().unwrap_or(true) {
-            let mut defaults = ShapeDefaults::default();
-            Self::recalc_composite(
-                &mut canvas_context,
-                node,
-                bound,
-                TransformMatrix::identity(),
-                &mut defaults,
-            );
+        if node.need_recalc().unwrap_or(true) || theme_changed || view_changed {
+            let mut measurer = CanvasMeasurer {
+                canvas: &mut canvas_context,
+                text_cache: &mut self.text_cache,
+            };
+            let theme = self.theme.clone();
+            recalc_in_view(&mut measurer, node, bound, view_transform, theme, &mut Damage::new())?;
         }
 
-        if node.need_redraw().unwrap_or(true) {
-            let mut defaults = ShapeDefaults::default();
-            Self::render_composite(&mut canvas_context, node, None, &mut defaults);
+        if node.need_redraw().unwrap_or(true) || theme_changed || view_changed {
+            let mut defaults = ShapeDefaults::new(self.theme.clone());
+            Self::render_composite(&mut canvas_context, node, None, base_transform, &mut defaults);
+            // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+            for portal in node.portals() {
+                let mut defaults = ShapeDefaults::new(self.theme.clone());
+                Self::render_composite(&mut canvas_context, portal, None, base_transform, &mut defaults);
+            }
 
             // Render the canvas to screen.
             let scene = SceneProxy::from_scene(canvas_context.into_canvas().into_scene(), RayonExecutor);
@@ -172,14 +181,6 @@ impl Render for PathfinderRender {
     }
 }
 
-#[derive(Default, Clone)]
-pub struct ShapeDefaults {
-    pub transparency: Real,
-    pub fill: Option<Fill>,
-    pub stroke: Option<Stroke>,
-    pub clip: Clip,
-}
-
 impl PathfinderRender {
     pub fn new(physical_width: u32, physical_height: u32) -> Self {
         let mut render = Self::default();
@@ -187,271 +188,41 @@ impl PathfinderRender {
         render
     }
 
-    pub fn load_font(&mut self, _name: impl AsRef<str>, path: impl AsRef<Path>) -> Result<(), <Self as Render>::Error> {
-        let context = self.context.as_mut().ok_or(PathfinderRenderError::ContextIsNotInit)?;
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
 
+    pub fn load_font(&mut self, _name: impl AsRef<str>, path: impl AsRef<Path>) -> Result<(), <Self as Render>::Error> {
         let display_path = path.as_ref().display();
         let mut font_file_data = vec![];
         File::open(&path)
             .and_then(|mut file| file.read_to_end(&mut font_file_data))
             .map_err(|err| PathfinderRenderError::CreateFontError(err, format!("{}", display_path)))?;
-        context
-            .font_handles
-            .push(Handle::from_memory(Arc::new(font_file_data), 0));
-        context.font_context = CanvasFontContext::from_fonts(context.font_handles.clone().into_iter());
-
-        Ok(())
+        self.add_font(font_file_data)
     }
 
-    fn recalc_composite(
-        canvas: &mut CanvasRenderingContext2D, composite: &mut dyn CompositeShape, parent_bound: BoundingBox,
-        mut parent_global_transform: TransformMatrix, defaults: &mut ShapeDefaults,
-    ) -> BoundingBox {
-        let mut bound = parent_bound;
-
-        if let Some(shape) = composite.shape_mut() {
-            match shape {
-                Shape::Rect(rect) => {
-                    if rect.x.set_by_pct(parent_bound.width()) {
-                        rect.x.0 += parent_bound.min_x;
-                    }
-                    if rect.y.set_by_pct(parent_bound.height()) {
-                        rect.y.0 += parent_bound.min_y;
-                    }
-                    rect.width.set_by_pct(parent_bound.width());
-                    rect.height.set_by_pct(parent_bound.height());
-                    if let Some(rounding) = &mut rect.rounding {
-                        let radius = parent_bound.width().min(parent_bound.height());
-                        rounding.top_left.set_by_pct(radius);
-                        rounding.top_right.set_by_pct(radius);
-                        rounding.bottom_left.set_by_pct(radius);
-                        rounding.bottom_right.set_by_pct(radius);
-                    }
-                    Self::set_by_pct_padding(&mut rect.padding, &parent_bound);
-                    Self::set_by_pct_clip(&mut rect.clip, &parent_bound);
-
-                    parent_global_transform = rect.recalculate_transform(parent_global_transform);
-                    let (scale_x, scale_y) = parent_global_transform.scale_xy();
-                    parent_global_transform
-                        .translate_add(rect.padding.left.val() * scale_x, rect.padding.top.val() * scale_y);
-
-                    bound = BoundingBox {
-                        min_x: rect.x.val(),
-                        min_y: rect.y.val(),
-                        max_x: rect.x.val() + rect.width.val(),
-                        max_y: rect.y.val() + rect.height.val(),
-                    };
-                }
-                Shape::Circle(circle) => {
-                    if circle.cx.set_by_pct(parent_bound.width()) {
-                        circle.cx.0 += parent_bound.min_x;
-                    }
-                    if circle.cy.set_by_pct(parent_bound.height()) {
-                        circle.cy.0 += parent_bound.min_y;
-                    }
-                    circle.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
-                    Self::set_by_pct_padding(&mut circle.padding, &parent_bound);
-                    Self::set_by_pct_clip(&mut circle.clip, &parent_bound);
-
-                    parent_global_transform = circle.recalculate_transform(parent_global_transform);
-                    let (scale_x, scale_y) = parent_global_transform.scale_xy();
-                    parent_global_transform
-                        .translate_add(circle.padding.left.val() * scale_x, circle.padding.top.val() * scale_y);
-
-                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
-                    bound = BoundingBox {
-                        min_x: cx - r,
-                        min_y: cy - r,
-                        max_x: cx + r,
-                        max_y: cy + r,
-                    };
-                }
-                Shape::Text(text) => {
-                    if text.x.set_by_pct(parent_bound.width()) {
-                        text.x.0 += parent_bound.min_x;
-                    }
-                    if text.y.set_by_pct(parent_bound.height()) {
-                        text.y.0 += parent_bound.min_y;
-                    }
-                    Self::set_by_pct_clip(&mut text.clip, &parent_bound);
-
-                    parent_global_transform = text.recalculate_transform(parent_global_transform);
-
-                    canvas.save();
-                    Self::set_text_options(canvas, text, defaults);
-
-                    let content = if text.content.is_empty() { "a" } else { &text.content };
-                    let metrics = canvas.measure_text(content);
-                    let ascend = metrics.font_bounding_box_ascent.abs();
-                    let descend = metrics.font_bounding_box_descent.abs();
-                    let line_height = ascend + descend;
-                    text.metrics = Some(TextMetrics {
-                        ascender: ascend,
-                        descender: descend,
-                        line_height,
-                    });
-
-                    // todo: cache the glyph_positions
-                    let layout = skribo::layout(
-                        &TextStyle {
-                            size: canvas.font_size(),
-                        },
-                        &canvas.font(),
-                        &text.content,
-                    );
-
-                    text.glyph_positions.clear();
-                    let mut prev_pos: Option<Vector2F> = None;
-                    for pos in layout
-                        .glyphs
-                        .iter()
-                        .map(|glyph| glyph.offset)
-                        .chain(iter::once(layout.advance))
-                    {
-                        if let Some(prev_pos) = prev_pos {
-                            text.glyph_positions.push(GlyphPos {
-                                x: prev_pos.x(),
-                                y: prev_pos.y(),
-                                width: pos.x() - prev_pos.x(),
-                            });
-                        }
-                        prev_pos = Some(pos);
-                    }
-
-                    canvas.restore();
-
-                    bound = BoundingBox {
-                        min_x: text.x.val(),
-                        min_y: text.y.val(),
-                        max_x: text.x.val() + text.glyph_positions.last().map(|pos| pos.max_x()).unwrap_or(0.0),
-                        max_y: text.y.val() + line_height,
-                    };
-                }
-                Shape::Path(path) => {
-                    Self::set_by_pct_clip(&mut path.clip, &parent_bound);
-                    parent_global_transform = path.recalculate_transform(parent_global_transform);
-                }
-                Shape::Group(group) => {
-                    Self::set_by_pct_clip(&mut group.clip, &parent_bound);
-                    parent_global_transform = group.recalculate_transform(parent_global_transform);
-
-                    if let Some(transparency) = group.transparency {
-                        defaults.transparency = transparency;
-                    }
-                    if let Some(fill) = group.fill {
-                        defaults.fill = Some(fill);
-                    }
-                    if let Some(stroke) = group.stroke {
-                        defaults.stroke = Some(stroke);
-                    }
-                    if !group.clip.is_none() {
-                        defaults.clip = group.clip;
-                    }
-                }
-            }
-        }
-
-        let inner_bound = Self::calc_inner_bound(canvas, composite, bound, parent_global_transform, defaults);
-
-        if let Some(shape) = composite.shape_mut() {
-            match shape {
-                Shape::Rect(rect) => {
-                    rect.x.set_by_auto(inner_bound.min_x);
-                    rect.y.set_by_auto(inner_bound.min_y);
-                    rect.width
-                        .set_by_auto(inner_bound.max_x - rect.x.val() + rect.padding.left_and_right().val());
-                    rect.height
-                        .set_by_auto(inner_bound.max_y - rect.y.val() + rect.padding.top_and_bottom().val());
-
-                    bound = BoundingBox {
-                        min_x: rect.x.val(),
-                        min_y: rect.y.val(),
-                        max_x: rect.x.val() + rect.width.val(),
-                        max_y: rect.y.val() + rect.height.val(),
-                    };
-                }
-                Shape::Circle(circle) => {
-                    circle.cx.set_by_auto(inner_bound.min_x + inner_bound.width() / 2.0);
-                    circle.cy.set_by_auto(inner_bound.min_y + inner_bound.height() / 2.0);
-                    circle.r.set_by_auto(
-                        (inner_bound.width() + circle.padding.left_and_right().val())
-                            .max(inner_bound.height() + circle.padding.top_and_bottom().val())
-                            / 2.0,
-                    );
-
-                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
-                    bound = BoundingBox {
-                        min_x: cx - r,
-                        min_y: cy - r,
-                        max_x: cx + r,
-                        max_y: cy + r,
-                    };
-                }
-                Shape::Text(text) => {
-                    let transform = text.transform.matrix();
-                    let inner_bound_points = transform * inner_bound;
-                    let bound_points = transform * bound;
-
-                    bound.min_x = bound_points[0].0;
-                    bound.max_x = bound.min_x;
-                    bound.min_y = bound_points[0].1;
-                    bound.max_y = bound.min_y;
-                    for idx in 0..4 {
-                        bound.min_x = bound.min_x.min(bound_points[idx].0).min(inner_bound_points[idx].0);
-                        bound.max_x = bound.max_x.max(bound_points[idx].0).max(inner_bound_points[idx].0);
-                        bound.min_y = bound.min_y.min(bound_points[idx].1).min(inner_bound_points[idx].1);
-                        bound.max_y = bound.max_y.max(bound_points[idx].1).max(inner_bound_points[idx].1);
-                    }
-                }
-                _ => (),
-            }
-        }
-        bound
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`.
+    pub fn load_font_from_memory(
+        &mut self, _name: impl AsRef<str>, data: &[u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        self.add_font(data.to_vec())
     }
 
-    fn calc_inner_bound(
-        canvas: &mut CanvasRenderingContext2D, composite: &mut dyn CompositeShape, bound: BoundingBox,
-        parent_global_transform: TransformMatrix, defaults: &mut ShapeDefaults,
-    ) -> BoundingBox {
-        let mut child_bounds = Vec::new();
-        if let Some(children) = composite.children_mut() {
-            for child in children {
-                child_bounds.push(Self::recalc_composite(
-                    canvas,
-                    child,
-                    bound,
-                    parent_global_transform,
-                    defaults,
-                ));
-            }
-        }
-
-        if child_bounds.is_empty() {
-            BoundingBox::default()
-        } else {
-            let mut inner_bound = child_bounds[0];
-            for bound in &child_bounds[1..] {
-                if bound.min_x < inner_bound.min_x {
-                    inner_bound.min_x = bound.min_x;
-                }
-                if bound.min_y < inner_bound.min_y {
-                    inner_bound.min_y = bound.min_y;
-                }
-                if bound.max_x > inner_bound.max_x {
-                    inner_bound.max_x = bound.max_x;
-                }
-                if bound.max_y > inner_bound.max_y {
-                    inner_bound.max_y = bound.max_y;
-                }
-            }
-            inner_bound
-        }
+    fn add_font(&mut self, data: Vec<u8>) -> Result<(), PathfinderRenderError> {
+        let context = self.context.as_mut().ok_or(PathfinderRenderError::ContextIsNotInit)?;
+        context.font_handles.push(Handle::from_memory(Arc::new(data), 0));
+        context.font_context = CanvasFontContext::from_fonts(context.font_handles.clone().into_iter());
+        self.text_cache.clear();
+        Ok(())
     }
 
     fn render_composite<'a>(
         canvas: &mut CanvasRenderingContext2D, composite: &'a dyn CompositeShape, mut text: Option<&'a Text>,
-        defaults: &mut ShapeDefaults,
+        base_transform: Transform2F, defaults: &mut ShapeDefaults,
     ) {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
         canvas.save();
         if let Some(shape) = composite.shape() {
             match shape {
@@ -466,7 +237,14 @@ impl PathfinderRender {
                         path.rect(RectF::new(rect_pos, rect_size));
                         path
                     };
-                    Self::set_path_options(canvas, rect.transparency, rect.clip, &rect.transform, defaults);
+                    Self::set_path_options(
+                        canvas,
+                        rect.transparency,
+                        &rect.clip,
+                        &rect.transform,
+                        base_transform,
+                        defaults,
+                    );
                     if let Some(fill) = rect.fill.as_ref().or(defaults.fill.as_ref()) {
                         Self::set_fill_option(canvas, fill);
                         canvas.fill_path(rect_path.clone(), FillRule::Winding);
@@ -485,7 +263,14 @@ impl PathfinderRender {
                         path
                     };
 
-                    Self::set_path_options(canvas, circle.transparency, circle.clip, &circle.transform, defaults);
+                    Self::set_path_options(
+                        canvas,
+                        circle.transparency,
+                        &circle.clip,
+                        &circle.transform,
+                        base_transform,
+                        defaults,
+                    );
                     if let Some(fill) = circle.fill.as_ref().or(defaults.fill.as_ref()) {
                         Self::set_fill_option(canvas, fill);
                         canvas.fill_path(circle_path.clone(), FillRule::Winding);
@@ -495,141 +280,355 @@ impl PathfinderRender {
                         canvas.stroke_path(circle_path);
                     }
                 }
+                Shape::Ellipse(ellipse) => {
+                    let center = Vector2F::new(ellipse.cx.val(), ellipse.cy.val());
+                    let axes = Vector2F::new(ellipse.rx.val(), ellipse.ry.val());
+                    let ellipse_path = {
+                        let mut path = Path2D::new();
+                        path.ellipse(center, axes, 0.0, 0.0, PI_2);
+                        path
+                    };
+
+                    Self::set_path_options(
+                        canvas,
+                        ellipse.transparency,
+                        &ellipse.clip,
+                        &ellipse.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = ellipse.fill.as_ref().or(defaults.fill.as_ref()) {
+                        Self::set_fill_option(canvas, fill);
+                        canvas.fill_path(ellipse_path.clone(), FillRule::Winding);
+                    };
+                    if let Some(stroke) = ellipse.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        Self::set_stroke_option(canvas, stroke);
+                        canvas.stroke_path(ellipse_path);
+                    }
+                }
                 Shape::Path(path) => {
-                    use exgui_core::PathCommand::*;
+                    let draw_path = Self::path2d(&path.cmd);
+
+                    Self::set_path_options(
+                        canvas,
+                        path.transparency,
+                        &path.clip,
+                        &path.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = path.fill.as_ref().or(defaults.fill.as_ref()) {
+                        Self::set_fill_option(canvas, fill);
+                        canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                    };
+                    if let Some(stroke) = path.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        Self::set_stroke_option(canvas, stroke);
+                        canvas.stroke_path(draw_path);
+                    }
 
-                    let mut last_xy = Vector2F::new(0.0, 0.0);
-                    let mut bez_ctrls = [Vector2F::new(0.0, 0.0), Vector2F::new(0.0, 0.0)];
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        let marker_defaults = &mut defaults.clone();
+                        Self::render_composite(canvas, marker.shape.as_ref(), None, base_transform, marker_defaults);
+                    }
+                }
+                Shape::Polygon(polygon) => {
                     let mut draw_path = Path2D::new();
-
-                    for cmd in path.cmd.iter() {
-                        match cmd {
-                            Move(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.move_to(last_xy);
-                            }
-                            MoveRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.move_to(last_xy);
-                            }
-                            Line(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.line_to(last_xy);
-                            }
-                            LineRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.line_to(last_xy);
-                            }
-                            LineAlonX(ref x) => {
-                                last_xy.set_x(*x);
-                                draw_path.line_to(last_xy);
-                            }
-                            LineAlonXRel(ref x) => {
-                                last_xy.set_x(last_xy.x() + *x);
-                                draw_path.line_to(last_xy);
-                            }
-                            LineAlonY(ref y) => {
-                                last_xy.set_y(*y);
-                                draw_path.line_to(last_xy);
-                            }
-                            LineAlonYRel(ref y) => {
-                                last_xy.set_y(last_xy.y() + *y);
-                                draw_path.line_to(last_xy);
+                    for contour in polygon.contours.iter() {
+                        let mut points = contour.iter();
+                        if let Some(first) = points.next() {
+                            draw_path.move_to(Vector2F::new(first[0], first[1]));
+                            for point in points {
+                                draw_path.line_to(Vector2F::new(point[0], point[1]));
                             }
-                            Close => draw_path.close_path(),
-                            BezCtrl(ref xy) => {
-                                bez_ctrls = [bez_ctrls[1], Vector2F::new(xy[0], xy[1])];
-                            }
-                            BezCtrlRel(ref xy) => {
-                                bez_ctrls = [bez_ctrls[1], Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1])];
-                            }
-                            QuadBezTo(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
-                            }
-                            QuadBezToRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
-                            }
-                            CubBezTo(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
-                            }
-                            CubBezToRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
-                            }
-                            _ => panic!("Not impl rendering cmd {:?}", cmd), // TODO: need refl impl
+                            draw_path.close_path();
                         }
                     }
+                    let fill_rule = match polygon.fill_rule {
+                        exgui_core::FillRule::NonZero => FillRule::Winding,
+                        exgui_core::FillRule::EvenOdd => FillRule::EvenOdd,
+                    };
 
-                    Self::set_path_options(canvas, path.transparency, path.clip, &path.transform, defaults);
-                    if let Some(fill) = path.fill.as_ref().or(defaults.fill.as_ref()) {
+                    Self::set_path_options(
+                        canvas,
+                        polygon.transparency,
+                        &polygon.clip,
+                        &polygon.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = polygon.fill.as_ref().or(defaults.fill.as_ref()) {
                         Self::set_fill_option(canvas, fill);
-                        canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                        canvas.fill_path(draw_path.clone(), fill_rule);
                     };
-                    if let Some(stroke) = path.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                    if let Some(stroke) = polygon.stroke.as_ref().or(defaults.stroke.as_ref()) {
                         Self::set_stroke_option(canvas, stroke);
                         canvas.stroke_path(draw_path);
                     }
                 }
+                Shape::Image(image) => {
+                    // Image textures are not supported by this backend yet, only the frame is drawn.
+                    let image_pos = Vector2F::new(image.x.val() as f32, image.y.val() as f32);
+                    let image_size = Vector2F::new(image.width.val() as f32, image.height.val() as f32);
+                    let mut image_path = Path2D::new();
+                    image_path.rect(RectF::new(image_pos, image_size));
+
+                    Self::set_path_options(
+                        canvas,
+                        image.transparency,
+                        &image.clip,
+                        &image.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = image.fill.as_ref().or(defaults.fill.as_ref()) {
+                        Self::set_fill_option(canvas, fill);
+                        canvas.fill_path(image_path.clone(), FillRule::Winding);
+                    };
+                    if let Some(stroke) = image.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        Self::set_stroke_option(canvas, stroke);
+                        canvas.stroke_path(image_path);
+                    }
+                }
                 Shape::Text(this_text) => {
                     text = Some(this_text);
 
                     let pos = Vector2F::new(this_text.x.val(), this_text.y.val());
 
-                    Self::set_text_options(canvas, this_text, defaults);
+                    Self::set_text_options(canvas, this_text, base_transform, defaults);
+                    if this_text.spans.is_empty() {
+                        if let Some(fill) = this_text.fill.as_ref().or(defaults.fill.as_ref()) {
+                            Self::set_fill_option(canvas, fill);
+                            canvas.fill_text(&this_text.content, pos);
+                        };
+                        if let Some(stroke) = this_text.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                            Self::set_stroke_option(canvas, stroke);
+                            canvas.stroke_text(&this_text.content, pos);
+                        }
+                    } else {
+                        // The span row is already aligned by the span offsets.
+                        canvas.set_text_align(TextAlign::Left);
+                    }
+                    for span in &this_text.spans {
+                        Self::set_span_font(canvas, this_text, span);
+                        let pos = pos + Vector2F::new(span.offset, 0.0);
+                        if let Some(fill) = span
+                            .fill
+                            .as_ref()
+                            .or(this_text.fill.as_ref())
+                            .or(defaults.fill.as_ref())
+                        {
+                            Self::set_fill_option(canvas, fill);
+                            canvas.fill_text(&span.content, pos);
+                        };
+                        if let Some(stroke) = this_text.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                            Self::set_stroke_option(canvas, stroke);
+                            canvas.stroke_text(&span.content, pos);
+                        }
+                    }
+
                     if let Some(fill) = this_text.fill.as_ref().or(defaults.fill.as_ref()) {
                         Self::set_fill_option(canvas, fill);
-                        canvas.fill_text(&this_text.content, pos);
-                    };
-                    if let Some(stroke) = this_text.stroke.as_ref().or(defaults.stroke.as_ref()) {
-                        Self::set_stroke_option(canvas, stroke);
-                        canvas.stroke_text(&this_text.content, pos);
+                        for line in this_text.decoration_lines() {
+                            let mut path = Path2D::new();
+                            path.rect(RectF::new(
+                                Vector2F::new(line.min_x, line.min_y),
+                                Vector2F::new(line.width(), line.height()),
+                            ));
+                            canvas.fill_path(path, FillRule::Winding);
+                        }
                     }
                 }
-                Shape::Group(group) => {
-                    if let Some(transparency) = group.transparency {
-                        defaults.transparency = transparency;
-                    }
-                    if let Some(fill) = group.fill {
-                        defaults.fill = Some(fill);
+                Shape::Custom(custom) => {
+                    Self::set_path_options(
+                        canvas,
+                        custom.transparency,
+                        &custom.clip,
+                        &custom.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .or_else(|| custom.fill.clone())
+                            .or_else(|| defaults.fill.clone());
+                        let stroke = custom_path
+                            .stroke
+                            .or_else(|| custom.stroke.clone())
+                            .or_else(|| defaults.stroke.clone());
+                        let draw_path = Self::path2d(&custom_path.cmd);
+                        if let Some(fill) = fill {
+                            Self::set_fill_option(canvas, &fill);
+                            canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                        };
+                        if let Some(stroke) = stroke {
+                            Self::set_stroke_option(canvas, &stroke);
+                            canvas.stroke_path(draw_path);
+                        }
                     }
-                    if let Some(stroke) = group.stroke {
-                        defaults.stroke = Some(stroke);
+                }
+                Shape::Sector(sector) => {
+                    let draw_path = Self::path2d(&sector.commands());
+
+                    Self::set_path_options(
+                        canvas,
+                        sector.transparency,
+                        &sector.clip,
+                        &sector.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = sector.fill.as_ref().or(defaults.fill.as_ref()) {
+                        Self::set_fill_option(canvas, fill);
+                        canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                    };
+                    if let Some(stroke) = sector.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        Self::set_stroke_option(canvas, stroke);
+                        canvas.stroke_path(draw_path);
                     }
-                    if !group.clip.is_none() {
-                        defaults.clip = group.clip;
+                }
+                Shape::Ring(ring) => {
+                    let draw_path = Self::path2d(&ring.commands());
+
+                    Self::set_path_options(
+                        canvas,
+                        ring.transparency,
+                        &ring.clip,
+                        &ring.transform,
+                        base_transform,
+                        defaults,
+                    );
+                    if let Some(fill) = ring.fill.as_ref().or(defaults.fill.as_ref()) {
+                        Self::set_fill_option(canvas, fill);
+                        canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                    };
+                    if let Some(stroke) = ring.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        Self::set_stroke_option(canvas, stroke);
+                        canvas.stroke_path(draw_path);
                     }
                 }
+                Shape::Group(group) => defaults.apply_group(group),
             }
         }
         canvas.restore();
 
-        if let Some(children) = composite.children() {
-            for child in children {
-                Self::render_composite(canvas, child, text, defaults);
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(canvas, child, text, base_transform, defaults);
             }
         }
     }
 
-    fn set_by_pct_padding(padding: &mut Padding, parent_bound: &BoundingBox) {
-        padding.left.set_by_pct(parent_bound.width());
-        padding.right.set_by_pct(parent_bound.width());
-        padding.top.set_by_pct(parent_bound.height());
-        padding.bottom.set_by_pct(parent_bound.height());
-    }
+    fn path2d(commands: &[PathCommand]) -> Path2D {
+        use exgui_core::PathCommand::*;
 
-    fn set_by_pct_clip(clip: &mut Clip, parent_bound: &BoundingBox) {
-        if let Clip::Scissor(scissor) = clip {
-            scissor.x.set_by_pct(parent_bound.width());
-            scissor.y.set_by_pct(parent_bound.height());
-            scissor.width.set_by_pct(parent_bound.width());
-            scissor.height.set_by_pct(parent_bound.height());
+        let mut last_xy = Vector2F::new(0.0, 0.0);
+        let mut bez_ctrls = [Vector2F::new(0.0, 0.0), Vector2F::new(0.0, 0.0)];
+        let mut draw_path = Path2D::new();
+
+        for cmd in commands.iter() {
+            match cmd {
+                Move(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.move_to(last_xy);
+                }
+                MoveRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.move_to(last_xy);
+                }
+                Line(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.line_to(last_xy);
+                }
+                LineRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.line_to(last_xy);
+                }
+                LineAlonX(ref x) => {
+                    last_xy.set_x(*x);
+                    draw_path.line_to(last_xy);
+                }
+                LineAlonXRel(ref x) => {
+                    last_xy.set_x(last_xy.x() + *x);
+                    draw_path.line_to(last_xy);
+                }
+                LineAlonY(ref y) => {
+                    last_xy.set_y(*y);
+                    draw_path.line_to(last_xy);
+                }
+                LineAlonYRel(ref y) => {
+                    last_xy.set_y(last_xy.y() + *y);
+                    draw_path.line_to(last_xy);
+                }
+                Close => draw_path.close_path(),
+                BezCtrl(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], Vector2F::new(xy[0], xy[1])];
+                }
+                BezCtrlRel(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1])];
+                }
+                BezReflectCtrl => {
+                    bez_ctrls = [bez_ctrls[1], last_xy * 2.0 - bez_ctrls[1]];
+                }
+                QuadBezTo(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
+                }
+                QuadBezToRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
+                }
+                CubBezTo(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
+                }
+                CubBezToRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
+                }
+                Arc {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let from = [last_xy.x(), last_xy.y()];
+                    for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(from, *radius, *rotation, *large_arc, *sweep, *to) {
+                        draw_path.bezier_curve_to(
+                            Vector2F::new(ctrl1[0], ctrl1[1]),
+                            Vector2F::new(ctrl2[0], ctrl2[1]),
+                            Vector2F::new(xy[0], xy[1]),
+                        );
+                    }
+                    last_xy = Vector2F::new(to[0], to[1]);
+                }
+                ArcRel {
+                    radius,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                } => {
+                    let from = [last_xy.x(), last_xy.y()];
+                    let to = [from[0] + to[0], from[1] + to[1]];
+                    for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(from, *radius, *rotation, *large_arc, *sweep, to) {
+                        draw_path.bezier_curve_to(
+                            Vector2F::new(ctrl1[0], ctrl1[1]),
+                            Vector2F::new(ctrl2[0], ctrl2[1]),
+                            Vector2F::new(xy[0], xy[1]),
+                        );
+                    }
+                    last_xy = Vector2F::new(to[0], to[1]);
+                }
+            }
         }
+        draw_path
     }
 
-    fn pathfinder_transform(transform: &Transform, current_transform: Transform2F) -> Option<Transform2F> {
+    fn pathfinder_transform(
+        transform: &Transform, current_transform: Transform2F, base_transform: Transform2F,
+    ) -> Option<Transform2F> {
         if transform.is_not_exist() {
             None
         } else {
@@ -648,33 +647,28 @@ impl PathfinderRender {
             Some(if !transform.is_absolute() {
                 current_transform * pathfinder_transform
             } else {
-                pathfinder_transform
+                base_transform * pathfinder_transform
             })
         }
     }
 
-    fn clip_path(clip: &Clip, current_transform: Transform2F) -> Option<Path2D> {
-        match clip {
-            Clip::Scissor(scissor) => {
-                let mut clip_rect = RectF::new(
-                    vec2f(scissor.x.val() as f32, scissor.y.val() as f32),
-                    vec2f(scissor.width.val() as f32, scissor.height.val() as f32),
-                );
-                if let Some(transform) = Self::pathfinder_transform(&scissor.transform, current_transform) {
-                    clip_rect = transform * clip_rect;
-                }
-
-                let mut clip_path = Path2D::new();
-                clip_path.rect(clip_rect);
-                Some(clip_path)
+    fn clip_path(clip: &Clip, current_transform: Transform2F, base_transform: Transform2F) -> Option<Path2D> {
+        let outline = clip.outline()?;
+        let clip_path = Self::path2d(&outline);
+        let transform = clip.transform()?;
+        match Self::pathfinder_transform(transform, current_transform, base_transform) {
+            Some(transform) => {
+                let mut transformed_path = Path2D::new();
+                transformed_path.add_path(clip_path, &transform);
+                Some(transformed_path)
             }
-            Clip::None => None,
+            None => Some(clip_path),
         }
     }
 
     fn set_path_options(
-        canvas: &mut CanvasRenderingContext2D, transparency: Real, clip: Clip, transform: &Transform,
-        defaults: &ShapeDefaults,
+        canvas: &mut CanvasRenderingContext2D, transparency: Real, clip: &Clip, transform: &Transform,
+        base_transform: Transform2F, defaults: &ShapeDefaults,
     ) {
         let transparency = if transparency != 0.0 {
             transparency
@@ -683,20 +677,20 @@ impl PathfinderRender {
         };
         canvas.set_global_alpha(1.0 - transparency);
         let current_transform = canvas.transform();
-        if let Some(clip_path) = Self::clip_path(&clip.or(defaults.clip), current_transform) {
+        if let Some(clip_path) = Self::clip_path(&clip.intersect(&defaults.clip), current_transform, base_transform) {
             canvas.clip_path(clip_path, FillRule::Winding);
         }
-        if let Some(transform) = Self::pathfinder_transform(transform, current_transform) {
+        if let Some(transform) = Self::pathfinder_transform(transform, current_transform, base_transform) {
             canvas.set_transform(&transform);
         }
     }
 
     fn set_fill_option(canvas: &mut CanvasRenderingContext2D, fill: &Fill) {
-        canvas.set_fill_style(ToPathfinderPaint(fill.paint));
+        canvas.set_fill_style(ToPathfinderPaint(fill.paint.clone()));
     }
 
     fn set_stroke_option(canvas: &mut CanvasRenderingContext2D, stroke: &Stroke) {
-        canvas.set_stroke_style(ToPathfinderPaint(stroke.paint));
+        canvas.set_stroke_style(ToPathfinderPaint(stroke.paint.clone()));
         canvas.set_line_width(stroke.width);
         canvas.set_miter_limit(stroke.miter_limit);
         let line_cap = match stroke.line_cap {
@@ -713,7 +707,14 @@ impl PathfinderRender {
         canvas.set_line_join(line_join);
     }
 
-    fn set_text_options(canvas: &mut CanvasRenderingContext2D, text: &Text, defaults: &ShapeDefaults) {
+    fn set_span_font(canvas: &mut CanvasRenderingContext2D, text: &Text, span: &TextSpan) {
+        canvas.set_font(&[span.font_name.as_ref().unwrap_or(&text.font_name).as_str()][..]);
+        canvas.set_font_size(span.font_size.unwrap_or(text.font_size).val());
+    }
+
+    fn set_text_options(
+        canvas: &mut CanvasRenderingContext2D, text: &Text, base_transform: Transform2F, defaults: &ShapeDefaults,
+    ) {
         let transparency = if text.transparency != 0.0 {
             text.transparency
         } else {
@@ -734,15 +735,85 @@ impl PathfinderRender {
             AlignVer::Top => TextBaseline::Top,
         });
         let current_transform = canvas.transform();
-        if let Some(clip_path) = Self::clip_path(&text.clip.or(defaults.clip), current_transform) {
+        let clip = text.clip.intersect(&defaults.clip);
+        if let Some(clip_path) = Self::clip_path(&clip, current_transform, base_transform) {
             canvas.clip_path(clip_path, FillRule::Winding);
         }
-        if let Some(transform) = Self::pathfinder_transform(&text.transform, current_transform) {
+        if let Some(transform) = Self::pathfinder_transform(&text.transform, current_transform, base_transform) {
             canvas.set_transform(&transform);
         }
     }
 }
 
+/// Measurer of the layout pass, the texts are measured by the font context of the frame canvas.
+struct CanvasMeasurer<'a> {
+    canvas: &'a mut CanvasRenderingContext2D,
+    text_cache: &'a mut TextCache,
+}
+
+impl<'a> CanvasMeasurer<'a> {
+    fn set_font(&mut self, font_name: &str, font_size: Real) {
+        self.canvas.set_font(&[font_name][..]);
+        self.canvas.set_font_size(font_size);
+    }
+}
+
+impl<'a> TextMeasurer for CanvasMeasurer<'a> {
+    type Error = PathfinderRenderError;
+
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        self.canvas.save();
+        self.set_font(font_name, font_size);
+        let advance = self.canvas.measure_text(text).width;
+        // The font bound of the empty text is empty, so the metrics are measured by a letter.
+        let metrics = self.canvas.measure_text(if text.is_empty() { "a" } else { text });
+        self.canvas.restore();
+
+        let ascender = metrics.font_bounding_box_ascent.abs();
+        let descender = metrics.font_bounding_box_descent.abs();
+        Ok((advance, TextMetrics {
+            ascender,
+            descender,
+            line_height: ascender + descender,
+        }))
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        self.canvas.save();
+        self.set_font(face.family, font_size);
+        let layout = skribo::layout(
+            &TextStyle {
+                size: self.canvas.font_size(),
+            },
+            &self.canvas.font(),
+            text,
+        );
+        self.canvas.restore();
+
+        let offsets = layout.glyphs.iter().map(|glyph| glyph.offset);
+        let ends = offsets.clone().skip(1).chain(iter::once(layout.advance));
+        Ok(offsets
+            .zip(ends)
+            .map(|(pos, end)| GlyphPos {
+                x: pos.x(),
+                y: pos.y(),
+                width: end.x() - pos.x(),
+            })
+            .collect())
+    }
+}
+
+impl<'a> LayoutMeasurer for CanvasMeasurer<'a> {
+    /// The images are not loaded by this backend, so they have no natural size.
+    fn image_size(&mut self, _src: &str) -> Result<(Real, Real), Self::Error> {
+        Ok((0.0, 0.0))
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut *self.text_cache)
+    }
+}
+
 fn create_rounded_rect_path(rect_pos: Vector2F, rect_size: Vector2F, rounding: Rounding) -> Path2D {
     let rect = RectF::new(rect_pos, rect_size);
     let mut path = Path2D::new();
@@ -812,12 +883,28 @@ impl ToPathfinderPaint {
                 gradient.add_color_stop(Self::to_color(end_color).to_u8(), 1.0);
                 gradient
             }
+            Gradient::Conic { .. } => unreachable!("The Conic gradient is drawn by the solid color"),
+        }
+    }
+
+    /// Pathfinder does not support the conic gradients, so they are approximated by the average color of the turn.
+    fn conic_color(stops: &[(Real, Color)]) -> ColorF {
+        const SAMPLES: usize = 32;
+        let mut sum = [0.0; 4];
+        for idx in 0..SAMPLES {
+            let color = Gradient::stops_color(stops, (idx as Real + 0.5) / SAMPLES as Real);
+            for (sum, channel) in sum.iter_mut().zip(color.as_arr()) {
+                *sum += channel / SAMPLES as f32;
+            }
         }
+        let [r, g, b, a] = sum;
+        ColorF::new(r, g, b, a)
     }
 
     fn into_fill_style(self) -> FillStyle {
         match self.0 {
             Paint::Color(color) => FillStyle::Color(Self::to_color(color).to_u8()),
+            Paint::Gradient(Gradient::Conic { stops, .. }) => FillStyle::Color(Self::conic_color(&stops).to_u8()),
             Paint::Gradient(gradient) => FillStyle::Gradient(Self::to_gradient(gradient)),
         }
     }
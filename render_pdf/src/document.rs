@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, HashMap};
+
+use exgui_core::{BoundingBox, Color, FontCache, Gradient, Real, TransformMatrix};
+use image::RgbaImage;
+use miniz_oxide::deflate::{compress_to_vec_zlib, CompressionLevel};
+use pdf_writer::{
+    types::{CidFontType, FontFlags, FunctionShadingType, SystemInfo, UnicodeCmap},
+    Chunk, Filter, Finish, Name, Pdf, Rect, Ref, Str,
+};
+
+use crate::{
+    font::{descriptor_metrics, font_data, glyph_width, postscript_name},
+    PdfRenderError,
+};
+
+/// Number of the samples along each side of the box and conic gradients, which have no PDF shading type.
+const SAMPLED_GRADIENT_SIZE: usize = 128;
+
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// Page content, which is drawn in the page units with the y axis going down.
+struct Page {
+    width: Real,
+    height: Real,
+    content: Vec<u8>,
+}
+
+/// Font used by the pages, the glyphs are shown by their ids, which are the CIDs of the embedded font.
+struct FontResource {
+    resource: String,
+    /// Used glyphs with the characters, which they are shown for.
+    glyphs: BTreeMap<u16, char>,
+}
+
+/// Pages of the document and the resources shared by them. The images, the shadings and the graphics states are
+/// written as soon as they are used, the fonts are written when the document is finished, because the widths and the
+/// unicode map contain the used glyphs only.
+pub struct Document {
+    next_ref: i32,
+    chunk: Chunk,
+    pages: Vec<Page>,
+    page_open: bool,
+    fonts: BTreeMap<String, FontResource>,
+    images: HashMap<String, (String, Ref)>,
+    alpha_states: HashMap<(u32, bool), (String, Ref)>,
+    patterns: Vec<(String, Ref)>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            next_ref: 0,
+            chunk: Chunk::new(),
+            pages: Vec::new(),
+            page_open: false,
+            fonts: BTreeMap::new(),
+            images: HashMap::new(),
+            alpha_states: HashMap::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl Document {
+    fn alloc_ref(&mut self) -> Ref {
+        self.next_ref += 1;
+        Ref::new(self.next_ref)
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns `true` if the last page is drawn by the next render instead of a new page.
+    pub fn has_open_page(&self) -> bool {
+        self.page_open
+    }
+
+    /// Replaces the content of the open page or adds the new page.
+    pub fn set_page(&mut self, width: Real, height: Real, content: Vec<u8>) {
+        let page = Page { width, height, content };
+        match self.pages.last_mut() {
+            Some(last) if self.page_open => *last = page,
+            _ => self.pages.push(page),
+        }
+        self.page_open = true;
+    }
+
+    pub fn close_page(&mut self) {
+        self.page_open = false;
+    }
+
+    /// Returns the resource name of the font and records the glyphs shown by it.
+    pub fn use_glyphs(&mut self, font_name: &str, glyphs: impl IntoIterator<Item = (u32, char)>) -> String {
+        let resource_count = self.fonts.len();
+        let font = self.fonts.entry(font_name.to_string()).or_insert_with(|| FontResource {
+            resource: format!("F{}", resource_count),
+            glyphs: BTreeMap::new(),
+        });
+        for (glyph_id, ch) in glyphs {
+            font.glyphs.entry(glyph_id as u16).or_insert(ch);
+        }
+        font.resource.clone()
+    }
+
+    /// Returns the resource name of the image, the image is written on the first use.
+    pub fn image(&mut self, src: &str, image: &RgbaImage) -> String {
+        if let Some((name, _)) = self.images.get(src) {
+            return name.clone();
+        }
+
+        let level = CompressionLevel::DefaultLevel as u8;
+        let rgb: Vec<_> = image
+            .pixels()
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        let has_alpha = image.pixels().any(|pixel| pixel[3] < u8::MAX);
+        let (width, height) = (image.width() as i32, image.height() as i32);
+
+        let id = self.alloc_ref();
+        let mask_id = if has_alpha { Some(self.alloc_ref()) } else { None };
+        let encoded = compress_to_vec_zlib(&rgb, level);
+        let mut xobject = self.chunk.image_xobject(id, &encoded);
+        xobject.width(width).height(height).bits_per_component(8);
+        xobject.filter(Filter::FlateDecode);
+        xobject.color_space().device_rgb();
+        if let Some(mask_id) = mask_id {
+            xobject.s_mask(mask_id);
+        }
+        xobject.finish();
+
+        if let Some(mask_id) = mask_id {
+            let alphas: Vec<_> = image.pixels().map(|pixel| pixel[3]).collect();
+            let encoded = compress_to_vec_zlib(&alphas, level);
+            let mut mask = self.chunk.image_xobject(mask_id, &encoded);
+            mask.width(width).height(height).bits_per_component(8);
+            mask.filter(Filter::FlateDecode);
+            mask.color_space().device_gray();
+        }
+
+        let name = format!("Im{}", self.images.len());
+        self.images.insert(src.to_string(), (name.clone(), id));
+        name
+    }
+
+    /// Returns the resource name of the graphics state, which sets the alpha of the filling or the stroking.
+    pub fn alpha_state(&mut self, alpha: f32, stroke: bool) -> String {
+        let key = (alpha.to_bits(), stroke);
+        if let Some((name, _)) = self.alpha_states.get(&key) {
+            return name.clone();
+        }
+
+        let id = self.alloc_ref();
+        let mut state = self.chunk.ext_graphics(id);
+        if stroke {
+            state.stroking_alpha(alpha);
+        } else {
+            state.non_stroking_alpha(alpha);
+        }
+        state.finish();
+
+        let name = format!("Gs{}", self.alpha_states.len());
+        self.alpha_states.insert(key, (name.clone(), id));
+        name
+    }
+
+    /// Returns the resource name of the shading pattern of the gradient. The gradient is defined in the shape
+    /// coordinates, which are mapped into the page by the `matrix`. The gradients of the box and conic shapes are
+    /// sampled over the `bound` of the painted path.
+    pub fn gradient_pattern(&mut self, gradient: &Gradient, matrix: TransformMatrix, bound: BoundingBox) -> String {
+        let pattern_id = self.alloc_ref();
+        let function_id = self.alloc_ref();
+        match gradient {
+            Gradient::Linear {
+                start: (start_x, start_y),
+                end: (end_x, end_y),
+                start_color,
+                end_color,
+            } => {
+                self.write_color_function(function_id, *start_color, *end_color);
+                let mut pattern = self.chunk.shading_pattern(pattern_id);
+                pattern.matrix(matrix.matrix);
+                let mut shading = pattern.function_shading();
+                shading
+                    .shading_type(FunctionShadingType::Axial)
+                    .coords([*start_x, *start_y, *end_x, *end_y])
+                    .function(function_id)
+                    .extend([true, true]);
+                shading.color_space().device_rgb();
+            },
+            Gradient::Radial {
+                center: (x, y),
+                inner_radius,
+                outer_radius,
+                start_color,
+                end_color,
+            } => {
+                self.write_color_function(function_id, *start_color, *end_color);
+                let mut pattern = self.chunk.shading_pattern(pattern_id);
+                pattern.matrix(matrix.matrix);
+                let mut shading = pattern.function_shading();
+                shading
+                    .shading_type(FunctionShadingType::Radial)
+                    .coords([*x, *y, *inner_radius, *x, *y, *outer_radius])
+                    .function(function_id)
+                    .extend([true, true]);
+                shading.color_space().device_rgb();
+            },
+            Gradient::Box { .. } | Gradient::Conic { .. } => {
+                let samples = sample_gradient(gradient, bound);
+                let encoded = compress_to_vec_zlib(&samples, CompressionLevel::DefaultLevel as u8);
+                let size = SAMPLED_GRADIENT_SIZE as i32;
+                let mut function = self.chunk.sampled_function(function_id, &encoded);
+                function
+                    .domain([0.0, 1.0, 0.0, 1.0])
+                    .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+                    .size([size, size])
+                    .bits_per_sample(8)
+                    .filter(Filter::FlateDecode);
+                function.finish();
+
+                let mut pattern = self.chunk.shading_pattern(pattern_id);
+                pattern.matrix(matrix.matrix);
+                let mut shading = pattern.function_shading();
+                shading
+                    .shading_type(FunctionShadingType::Function)
+                    .domain([0.0, 1.0, 0.0, 1.0])
+                    .matrix([bound.width(), 0.0, 0.0, bound.height(), bound.min_x, bound.min_y])
+                    .function(function_id);
+                shading.color_space().device_rgb();
+            },
+        }
+
+        let name = format!("P{}", self.patterns.len());
+        self.patterns.push((name.clone(), pattern_id));
+        name
+    }
+
+    fn write_color_function(&mut self, id: Ref, start_color: Color, end_color: Color) {
+        let ([r0, g0, b0, _], [r1, g1, b1, _]) = (start_color.as_arr(), end_color.as_arr());
+        self.chunk
+            .exponential_function(id)
+            .domain([0.0, 1.0])
+            .c0([r0, g0, b0])
+            .c1([r1, g1, b1])
+            .n(1.0);
+    }
+
+    /// Writes the document with the pages and the used fonts embedded.
+    pub fn write(&self, font_cache: &FontCache) -> Result<Vec<u8>, PdfRenderError> {
+        let mut next_ref = self.next_ref;
+        let mut alloc_ref = || {
+            next_ref += 1;
+            Ref::new(next_ref)
+        };
+
+        let mut pdf = Pdf::new();
+        let catalog_id = alloc_ref();
+        let page_tree_id = alloc_ref();
+        let page_ids: Vec<_> = self.pages.iter().map(|_| (alloc_ref(), alloc_ref())).collect();
+        let font_ids: Vec<_> = self.fonts.values().map(|_| alloc_ref()).collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+            .count(self.pages.len() as i32);
+
+        for (page, (page_id, content_id)) in self.pages.iter().zip(&page_ids) {
+            let mut page_writer = pdf.page(*page_id);
+            page_writer
+                .parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, page.width, page.height))
+                .contents(*content_id);
+            let mut resources = page_writer.resources();
+            let mut fonts = resources.fonts();
+            for (font, font_id) in self.fonts.values().zip(&font_ids) {
+                fonts.pair(Name(font.resource.as_bytes()), *font_id);
+            }
+            fonts.finish();
+            let mut x_objects = resources.x_objects();
+            for (name, id) in self.images.values() {
+                x_objects.pair(Name(name.as_bytes()), *id);
+            }
+            x_objects.finish();
+            let mut states = resources.ext_g_states();
+            for (name, id) in self.alpha_states.values() {
+                states.pair(Name(name.as_bytes()), *id);
+            }
+            states.finish();
+            let mut patterns = resources.patterns();
+            for (name, id) in &self.patterns {
+                patterns.pair(Name(name.as_bytes()), *id);
+            }
+            patterns.finish();
+            resources.finish();
+            page_writer.finish();
+
+            let encoded = compress_to_vec_zlib(&page.content, CompressionLevel::DefaultLevel as u8);
+            pdf.stream(*content_id, &encoded).filter(Filter::FlateDecode);
+        }
+
+        for ((font_name, font), font_id) in self.fonts.iter().zip(font_ids) {
+            Self::write_font(&mut pdf, &mut alloc_ref, font_cache, font_name, font, font_id)?;
+        }
+
+        pdf.extend(&self.chunk);
+        Ok(pdf.finish())
+    }
+
+    /// Writes the font as the composite font with the identity encoding, so the glyph ids are shown directly.
+    fn write_font(
+        pdf: &mut Pdf, alloc_ref: &mut impl FnMut() -> Ref, font_cache: &FontCache, font_name: &str,
+        font: &FontResource, font_id: Ref,
+    ) -> Result<(), PdfRenderError> {
+        let (cid_font_id, descriptor_id, file_id, cmap_id) = (alloc_ref(), alloc_ref(), alloc_ref(), alloc_ref());
+        let data = font_data(font_cache, font_name)?;
+        let is_open_type = data.starts_with(b"OTTO");
+        let base_font: String = postscript_name(font_cache, font_name)?
+            .unwrap_or_else(|| font_name.to_string())
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() || ch == '-' {
+                    ch
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        pdf.type0_font(font_id)
+            .base_font(Name(base_font.as_bytes()))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font_id)
+            .to_unicode(cmap_id);
+
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font
+            .subtype(if is_open_type {
+                CidFontType::Type0
+            } else {
+                CidFontType::Type2
+            })
+            .base_font(Name(base_font.as_bytes()))
+            .system_info(SYSTEM_INFO)
+            .font_descriptor(descriptor_id);
+        if !is_open_type {
+            cid_font.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
+        let mut widths = cid_font.widths();
+        for &glyph_id in font.glyphs.keys() {
+            widths.consecutive(glyph_id, [glyph_width(font_cache, font_name, u32::from(glyph_id))?]);
+        }
+        widths.finish();
+        cid_font.finish();
+
+        let ([ascender, descender, cap_height], bound) = descriptor_metrics(font_cache, font_name)?;
+        let mut descriptor = pdf.font_descriptor(descriptor_id);
+        descriptor
+            .name(Name(base_font.as_bytes()))
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(bound.min_x, bound.min_y, bound.max_x, bound.max_y))
+            .italic_angle(0.0)
+            .ascent(ascender)
+            .descent(descender)
+            .cap_height(cap_height)
+            .stem_v(80.0);
+        if is_open_type {
+            descriptor.font_file3(file_id);
+        } else {
+            descriptor.font_file2(file_id);
+        }
+        descriptor.finish();
+
+        let encoded = compress_to_vec_zlib(&data, CompressionLevel::DefaultLevel as u8);
+        let mut file = pdf.stream(file_id, &encoded);
+        file.filter(Filter::FlateDecode);
+        if is_open_type {
+            file.pair(Name(b"Subtype"), Name(b"OpenType"));
+        } else {
+            file.pair(Name(b"Length1"), data.len() as i32);
+        }
+        file.finish();
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+        for (&glyph_id, &ch) in &font.glyphs {
+            cmap.pair(glyph_id, ch);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+        Ok(())
+    }
+}
+
+/// Samples the RGB colors of the gradient over the bound, the first coordinate changes fastest.
+fn sample_gradient(gradient: &Gradient, bound: BoundingBox) -> Vec<u8> {
+    let step = 1.0 / (SAMPLED_GRADIENT_SIZE - 1) as Real;
+    let mut samples = Vec::with_capacity(SAMPLED_GRADIENT_SIZE * SAMPLED_GRADIENT_SIZE * 3);
+    for row in 0..SAMPLED_GRADIENT_SIZE {
+        for column in 0..SAMPLED_GRADIENT_SIZE {
+            let x = bound.min_x + column as Real * step * bound.width();
+            let y = bound.min_y + row as Real * step * bound.height();
+            let [r, g, b, _] = gradient_color(gradient, x, y).as_arr();
+            samples.extend(
+                [r, g, b]
+                    .iter()
+                    .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8),
+            );
+        }
+    }
+    samples
+}
+
+/// Color of the box or conic gradient at the point.
+fn gradient_color(gradient: &Gradient, x: Real, y: Real) -> Color {
+    match *gradient {
+        Gradient::Box {
+            position: (box_x, box_y),
+            size: (width, height),
+            radius,
+            feather,
+            start_color,
+            end_color,
+        } => {
+            // The box gradient is the feathered rounded rect, in the same way as nanovg draws it.
+            let (half_width, half_height) = (width / 2.0, height / 2.0);
+            let (dx, dy) = (
+                (x - box_x - half_width).abs() - (half_width - radius),
+                (y - box_y - half_height).abs() - (half_height - radius),
+            );
+            let outside = (dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0)).sqrt();
+            let distance = dx.max(dy).min(0.0) + outside - radius;
+            let feather = feather.max(1.0);
+            start_color.lerp(end_color, ((distance + feather * 0.5) / feather).clamp(0.0, 1.0))
+        },
+        Gradient::Conic {
+            center: (cx, cy),
+            angle,
+            ref stops,
+        } => {
+            let offset = (((y - cy).atan2(x - cx) - angle) / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
+            Gradient::stops_color(stops, offset)
+        },
+        Gradient::Linear { start_color, .. } | Gradient::Radial { start_color, .. } => start_color,
+    }
+}
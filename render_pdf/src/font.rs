@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use exgui_core::{BoundingBox, FontCache, Real};
+
+use crate::PdfRenderError;
+
+/// Advance of the glyph in the thousandths of the em, as the PDF glyph widths are.
+pub fn glyph_width(fonts: &FontCache, name: &str, glyph_id: u32) -> Result<Real, PdfRenderError> {
+    let font = fonts.font(name)?;
+    let advance = font.advance(glyph_id).map(|advance| advance.x()).unwrap_or(0.0);
+    Ok(advance * 1000.0 / font.metrics().units_per_em as f32)
+}
+
+/// Data of the font file, which is embedded into the document.
+pub fn font_data(fonts: &FontCache, name: &str) -> Result<Arc<Vec<u8>>, PdfRenderError> {
+    fonts
+        .font(name)?
+        .copy_font_data()
+        .ok_or_else(|| PdfRenderError::FontDataNotFound(name.to_string()))
+}
+
+pub fn postscript_name(fonts: &FontCache, name: &str) -> Result<Option<String>, PdfRenderError> {
+    Ok(fonts.font(name)?.postscript_name())
+}
+
+/// Font metrics in the thousandths of the em: ascender, descender, cap height and the bound of the glyphs.
+pub fn descriptor_metrics(fonts: &FontCache, name: &str) -> Result<([Real; 3], BoundingBox), PdfRenderError> {
+    let metrics = fonts.font(name)?.metrics();
+    let scale = 1000.0 / metrics.units_per_em as f32;
+    let bound = BoundingBox {
+        min_x: metrics.bounding_box.min_x() * scale,
+        min_y: metrics.bounding_box.min_y() * scale,
+        max_x: metrics.bounding_box.max_x() * scale,
+        max_y: metrics.bounding_box.max_y() * scale,
+    };
+    Ok((
+        [
+            metrics.ascent * scale,
+            metrics.descent * scale,
+            metrics.cap_height * scale,
+        ],
+        bound,
+    ))
+}
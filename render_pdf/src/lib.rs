@@ -0,0 +1,915 @@
+use std::{borrow::Cow, collections::HashMap, fmt, fs, io, mem, path::Path as FilePath, rc::Rc, sync::Arc};
+
+use exgui_core::{
+    recalc, BoundingBox, Clip, Color, CompositeShape, FaceMatch, Fill, FillRule, FontCache, FontError, GlyphPos,
+    LayoutMeasurer, LineCap, LineJoin, Paint, Real, Render, Shape, ShapeDefaults, Stroke, Text, TextCache, TextFace,
+    TextMeasurer, TextMetrics, Theme, Transform, TransformMatrix,
+};
+use font_kit::{error::FontLoadingError, font::Font};
+use image::{ImageError, RgbaImage};
+use pdf_writer::{
+    types::{ColorSpaceOperand, LineCapStyle, LineJoinStyle, TextRenderingMode},
+    Content, Finish, Name, Str,
+};
+
+use self::{
+    document::Document,
+    font::glyph_width,
+    path::{commands_path, ellipse_path, polygon_path, rect_path, rounded_rect_path, Path},
+};
+
+mod document;
+mod font;
+mod path;
+
+const CARET_WIDTH: Real = 1.5;
+/// Width of the glyph outline stroke of the synthetic bold, relative to the font size.
+const SYNTHETIC_BOLD_STROKE: Real = 0.03;
+/// Slant of the synthetic italic.
+const SYNTHETIC_ITALIC_SKEW: Real = 0.2;
+
+#[derive(Debug)]
+pub enum PdfRenderError {
+    CreateFontError(FontLoadingError, String),
+    CreateImageError(ImageError, String),
+    FontError(FontError),
+    FontDataNotFound(String),
+    WriteError(io::Error, String),
+}
+
+impl From<FontError> for PdfRenderError {
+    fn from(err: FontError) -> Self {
+        PdfRenderError::FontError(err)
+    }
+}
+
+/// Decoded images of the image shapes, they are embedded into the document on the first use.
+pub type ImageCache = HashMap<String, RgbaImage>;
+
+/// Glyphs shown with the same text matrix, the glyph transform places the run on the text path.
+type GlyphRun = (Option<TransformMatrix>, Vec<(u32, GlyphPos)>);
+
+/// Shape state, which the path is drawn with.
+#[derive(Clone)]
+struct DrawOptions<'a> {
+    alpha: f32,
+    clip: Cow<'a, Clip>,
+    transform: TransformMatrix,
+}
+
+impl<'a> DrawOptions<'a> {
+    fn new(transparency: Real, clip: &'a Clip, transform: &Transform, defaults: &'a ShapeDefaults) -> Self {
+        let transform = if transform.is_not_exist() {
+            TransformMatrix::identity()
+        } else {
+            transform.calculated_matrix().unwrap_or_else(|| transform.matrix())
+        };
+        Self {
+            alpha: (1.0 - transparency) * (1.0 - defaults.transparency),
+            clip: clip.intersect(&defaults.clip),
+            transform,
+        }
+    }
+}
+
+/// Content of the page with the document, which keeps the resources used by the content.
+struct Canvas<'a> {
+    content: Content,
+    document: &'a mut Document,
+    /// Matrix of the page units to the PDF default space, which has the y axis going up.
+    page_matrix: TransformMatrix,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(document: &'a mut Document, height: Real) -> Self {
+        let page_matrix = TransformMatrix {
+            matrix: [1.0, 0.0, 0.0, -1.0, 0.0, height],
+        };
+        let mut content = Content::new();
+        content.transform(page_matrix.matrix);
+        Self {
+            content,
+            document,
+            page_matrix,
+        }
+    }
+
+    /// Saves the graphics state and sets the clip and the transform of the shape. The state is restored by `end`.
+    fn begin(&mut self, options: &DrawOptions) {
+        self.content.save_state();
+        if let (Some(outline), Some(transform)) = (options.clip.outline(), options.clip.transform()) {
+            let transform = if transform.is_not_exist() {
+                TransformMatrix::identity()
+            } else {
+                transform.calculated_matrix().unwrap_or_else(|| transform.matrix())
+            };
+            commands_path(&outline).transform(&transform).write(&mut self.content);
+            self.content.clip_nonzero().end_path();
+        }
+        if options.transform != TransformMatrix::identity() {
+            self.content.transform(options.transform.matrix);
+        }
+    }
+
+    fn end(&mut self) {
+        self.content.restore_state();
+    }
+
+    /// Sets the paint of the filling or the stroking. The gradient is evaluated over the `bound` of the painted path
+    /// if it has no PDF shading. The gradient colors are opaque, so only the shape transparency applies to them.
+    fn set_paint(&mut self, paint: &Paint, stroke: bool, bound: BoundingBox, options: &DrawOptions) {
+        let alpha = match paint {
+            Paint::Color(color) => {
+                let [r, g, b, a] = color.as_arr();
+                if stroke {
+                    self.content.set_stroke_rgb(r, g, b);
+                } else {
+                    self.content.set_fill_rgb(r, g, b);
+                }
+                a * options.alpha
+            },
+            Paint::Gradient(gradient) => {
+                let pattern = self
+                    .document
+                    .gradient_pattern(gradient, self.page_matrix * options.transform, bound);
+                if stroke {
+                    self.content
+                        .set_stroke_color_space(ColorSpaceOperand::Pattern)
+                        .set_stroke_pattern(None, Name(pattern.as_bytes()));
+                } else {
+                    self.content
+                        .set_fill_color_space(ColorSpaceOperand::Pattern)
+                        .set_fill_pattern(None, Name(pattern.as_bytes()));
+                }
+                options.alpha
+            },
+        };
+        if alpha < 1.0 {
+            let state = self.document.alpha_state(alpha.max(0.0), stroke);
+            self.content.set_parameters(Name(state.as_bytes()));
+        }
+    }
+
+    fn set_stroke_style(&mut self, stroke: &Stroke) {
+        self.content
+            .set_line_width(stroke.width)
+            .set_miter_limit(stroke.miter_limit)
+            .set_line_cap(match stroke.line_cap {
+                LineCap::Butt => LineCapStyle::ButtCap,
+                LineCap::Round => LineCapStyle::RoundCap,
+                LineCap::Square => LineCapStyle::ProjectingSquareCap,
+            })
+            .set_line_join(match stroke.line_join {
+                LineJoin::Miter => LineJoinStyle::MiterJoin,
+                LineJoin::Round => LineJoinStyle::RoundJoin,
+                LineJoin::Bevel => LineJoinStyle::BevelJoin,
+            });
+    }
+
+    fn fill_path(&mut self, path: &Path, fill_rule: FillRule, paint: &Paint, options: &DrawOptions) {
+        if let Some(bound) = path.bound() {
+            self.begin(options);
+            self.set_paint(paint, false, bound, options);
+            path.write(&mut self.content);
+            match fill_rule {
+                FillRule::NonZero => self.content.fill_nonzero(),
+                FillRule::EvenOdd => self.content.fill_even_odd(),
+            };
+            self.end();
+        }
+    }
+
+    fn stroke_path(&mut self, path: &Path, stroke: &Stroke, options: &DrawOptions) {
+        if let Some(bound) = path.bound() {
+            self.begin(options);
+            self.set_paint(&stroke.paint, true, bound, options);
+            self.set_stroke_style(stroke);
+            path.write(&mut self.content);
+            self.content.stroke();
+            self.end();
+        }
+    }
+
+    /// Draws the image stretched into the bound.
+    fn draw_image(&mut self, src: &str, image: &RgbaImage, bound: BoundingBox, options: &DrawOptions) {
+        let name = self.document.image(src, image);
+        self.begin(options);
+        if options.alpha < 1.0 {
+            let state = self.document.alpha_state(options.alpha.max(0.0), false);
+            self.content.set_parameters(Name(state.as_bytes()));
+        }
+        // The image is drawn into the unit square with the y axis going up.
+        self.content
+            .transform([bound.width(), 0.0, 0.0, -bound.height(), bound.min_x, bound.max_y])
+            .x_object(Name(name.as_bytes()));
+        self.end();
+    }
+}
+
+/// The render writes the shapes into the PDF pages as the vector paths, so the views can be printed or exported.
+/// The used fonts are embedded into the document and the texts are kept searchable and selectable. The layout is
+/// calculated by the shared `exgui_core::recalc` in the page units, which are the PDF points.
+///
+/// Every render draws the open page, the next page is started by the `new_page`. The layer groups are drawn
+/// directly into the page, so their filters are not applied.
+pub struct PdfRender {
+    pub images: ImageCache,
+    pub fonts: FontCache,
+    /// Measured text lines of the layout, it is cleared when the fonts are loaded.
+    pub text_cache: TextCache,
+    pub width: f32,
+    pub height: f32,
+    pub device_pixel_ratio: f32,
+    pub theme: Rc<Theme>,
+    theme_changed: bool,
+    background_color: Color,
+    document: Document,
+}
+
+impl fmt::Debug for PdfRender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(PdfRender))
+            .field("images", &self.images.keys().collect::<Vec<_>>())
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("device_pixel_ratio", &self.device_pixel_ratio)
+            .field("theme", &self.theme)
+            .field("pages", &self.document.page_count())
+            .finish()
+    }
+}
+
+impl Render for PdfRender {
+    type Error = PdfRenderError;
+
+    fn init(&mut self, background_color: Color) -> Result<(), Self::Error> {
+        self.background_color = background_color;
+        Ok(())
+    }
+
+    /// Sets the page size of the next drawn page, the page is sized in the logical pixels, which are the PDF points.
+    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
+        self.device_pixel_ratio = device_pixel_ratio as f32;
+        self.width = physical_width as f32 / self.device_pixel_ratio;
+        self.height = physical_height as f32 / self.device_pixel_ratio;
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let need_recalc = node.need_recalc().unwrap_or(true) || theme_changed;
+        let need_redraw = node.need_redraw().unwrap_or(true) || theme_changed || !self.document.has_open_page();
+        if need_recalc {
+            let bound = BoundingBox {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: self.width as Real,
+                max_y: self.height as Real,
+            };
+            let theme = self.theme.clone();
+            recalc(self, node, bound, theme)?;
+        }
+        if need_redraw {
+            let content = self.draw_page(node)?;
+            self.document.set_page(self.width, self.height, content);
+        }
+        Ok(need_redraw)
+    }
+}
+
+impl TextMeasurer for PdfRender {
+    type Error = PdfRenderError;
+
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let name = self.fonts.resolve(font_name)?;
+        Self::measure_font(&self.fonts, &name, text, font_size)
+    }
+
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Self::measure_font(&self.fonts, &face_match.name, text, font_size)
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Ok(self.fonts.glyph_positions(&face_match.name, text, font_size)?)
+    }
+}
+
+impl LayoutMeasurer for PdfRender {
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error> {
+        let image = Self::cached_image(&mut self.images, src)?;
+        Ok((image.width() as Real, image.height() as Real))
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut self.text_cache)
+    }
+}
+
+impl PdfRender {
+    /// Creates the render of the pages of the size in the PDF points, e.g. 595 x 842 for the A4 page.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            images: ImageCache::new(),
+            fonts: FontCache::default(),
+            text_cache: TextCache::new(),
+            width,
+            height,
+            device_pixel_ratio: 1.0,
+            theme: Rc::new(Theme::default()),
+            theme_changed: false,
+            background_color: Color::White,
+            document: Document::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
+
+    pub fn load_font(
+        &mut self, name: impl Into<String>, path: impl AsRef<FilePath>,
+    ) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        let font = Font::from_path(path.as_ref(), 0).map_err(|e| PdfRenderError::CreateFontError(e, display_path))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`.
+    pub fn load_font_from_memory(
+        &mut self, name: impl Into<String>, data: &'static [u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        let name = name.into();
+        let font = Font::from_bytes(Arc::new(data.to_vec()), 0)
+            .map_err(|e| PdfRenderError::CreateFontError(e, name.clone()))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    pub fn load_image(&mut self, src: impl Into<String>) -> Result<(), <Self as Render>::Error> {
+        Self::cached_image(&mut self.images, &src.into())?;
+        Ok(())
+    }
+
+    /// Closes the drawn page, so the next render draws the new page.
+    pub fn new_page(&mut self) {
+        self.document.close_page();
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.document.page_count()
+    }
+
+    /// Writes the document of the drawn pages.
+    pub fn to_pdf(&self) -> Result<Vec<u8>, <Self as Render>::Error> {
+        self.document.write(&self.fonts)
+    }
+
+    pub fn save(&self, path: impl AsRef<FilePath>) -> Result<(), <Self as Render>::Error> {
+        let data = self.to_pdf()?;
+        fs::write(path.as_ref(), data).map_err(|e| PdfRenderError::WriteError(e, path.as_ref().display().to_string()))
+    }
+
+    fn cached_image<'a>(images: &'a mut ImageCache, src: &str) -> Result<&'a RgbaImage, PdfRenderError> {
+        if !images.contains_key(src) {
+            let image = image::open(src).map_err(|e| PdfRenderError::CreateImageError(e, src.to_string()))?;
+            images.insert(src.to_string(), image.to_rgba8());
+        }
+        Ok(&images[src])
+    }
+
+    fn measure_font(
+        fonts: &FontCache, name: &str, text: &str, font_size: Real,
+    ) -> Result<(Real, TextMetrics), PdfRenderError> {
+        let advance = fonts
+            .glyph_positions(name, text, font_size)?
+            .last()
+            .map(|pos| pos.x + pos.width)
+            .unwrap_or(0.0);
+        Ok((advance, fonts.metrics(name, font_size)?))
+    }
+
+    /// Draws the tree into the content of the page.
+    fn draw_page(&mut self, node: &dyn CompositeShape) -> Result<Vec<u8>, PdfRenderError> {
+        let mut canvas = Canvas::new(&mut self.document, self.height);
+        if self.background_color.as_arr()[3] > 0.0 {
+            let page = BoundingBox {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: self.width,
+                max_y: self.height,
+            };
+            let options = DrawOptions {
+                alpha: 1.0,
+                clip: Cow::Owned(Clip::None),
+                transform: TransformMatrix::identity(),
+            };
+            canvas.fill_path(
+                &rect_path(page),
+                FillRule::NonZero,
+                &Paint::Color(self.background_color),
+                &options,
+            );
+        }
+
+        let theme = &self.theme;
+        Self::render_composite(
+            &mut canvas,
+            &self.images,
+            &mut self.fonts,
+            node,
+            &mut ShapeDefaults::new(theme.clone()),
+        )?;
+        // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+        for portal in node.portals() {
+            Self::render_composite(
+                &mut canvas,
+                &self.images,
+                &mut self.fonts,
+                portal,
+                &mut ShapeDefaults::new(theme.clone()),
+            )?;
+        }
+        Ok(canvas.content.finish())
+    }
+
+    fn render_composite(
+        canvas: &mut Canvas, images: &ImageCache, fonts: &mut FontCache, composite: &dyn CompositeShape,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), PdfRenderError> {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        if let Some(shape) = composite.shape() {
+            match shape {
+                Shape::Rect(rect) => {
+                    let bound = BoundingBox {
+                        min_x: rect.x.val(),
+                        min_y: rect.y.val(),
+                        max_x: rect.x.val() + rect.width.val(),
+                        max_y: rect.y.val() + rect.height.val(),
+                    };
+                    let path = if let Some(rounding) = rect.rounding {
+                        rounded_rect_path(
+                            bound,
+                            [
+                                rounding.top_left.val(),
+                                rounding.top_right.val(),
+                                rounding.bottom_right.val(),
+                                rounding.bottom_left.val(),
+                            ],
+                        )
+                    } else {
+                        rect_path(bound)
+                    };
+                    Self::draw_path(
+                        canvas,
+                        &path,
+                        FillRule::NonZero,
+                        rect.fill.as_ref().or(defaults.fill.as_ref()),
+                        rect.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(rect.transparency, &rect.clip, &rect.transform, defaults),
+                    );
+                },
+                Shape::Circle(circle) => {
+                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                    Self::draw_path(
+                        canvas,
+                        &ellipse_path(cx, cy, r, r),
+                        FillRule::NonZero,
+                        circle.fill.as_ref().or(defaults.fill.as_ref()),
+                        circle.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(circle.transparency, &circle.clip, &circle.transform, defaults),
+                    );
+                },
+                Shape::Ellipse(ellipse) => {
+                    Self::draw_path(
+                        canvas,
+                        &ellipse_path(ellipse.cx.val(), ellipse.cy.val(), ellipse.rx.val(), ellipse.ry.val()),
+                        FillRule::NonZero,
+                        ellipse.fill.as_ref().or(defaults.fill.as_ref()),
+                        ellipse.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ellipse.transparency, &ellipse.clip, &ellipse.transform, defaults),
+                    );
+                },
+                Shape::Path(path) => {
+                    Self::draw_path(
+                        canvas,
+                        &commands_path(&path.cmd),
+                        FillRule::NonZero,
+                        path.fill.as_ref().or(defaults.fill.as_ref()),
+                        path.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(path.transparency, &path.clip, &path.transform, defaults),
+                    );
+
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        Self::render_composite(canvas, images, fonts, marker.shape.as_ref(), &mut defaults.clone())?;
+                    }
+                },
+                Shape::Polygon(polygon) => {
+                    Self::draw_path(
+                        canvas,
+                        &polygon_path(&polygon.contours),
+                        polygon.fill_rule,
+                        polygon.fill.as_ref().or(defaults.fill.as_ref()),
+                        polygon.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                    );
+                },
+                Shape::Image(image) => {
+                    let bound = BoundingBox {
+                        min_x: image.x.val(),
+                        min_y: image.y.val(),
+                        max_x: image.x.val() + image.width.val(),
+                        max_y: image.y.val() + image.height.val(),
+                    };
+                    let options = DrawOptions::new(image.transparency, &image.clip, &image.transform, defaults);
+                    let path = rect_path(bound);
+                    if let Some(fill) = image.fill.as_ref().or(defaults.fill.as_ref()) {
+                        canvas.fill_path(&path, FillRule::NonZero, &fill.paint, &options);
+                    }
+                    if let Some(rgba) = images.get(&image.src) {
+                        canvas.draw_image(&image.src, rgba, bound, &options);
+                    }
+                    if let Some(stroke) = image.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        canvas.stroke_path(&path, stroke, &options);
+                    }
+                },
+                Shape::Text(text) => {
+                    Self::render_text(canvas, fonts, text, defaults)?;
+                },
+                Shape::Custom(custom) => {
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .as_ref()
+                            .or(custom.fill.as_ref())
+                            .or(defaults.fill.as_ref());
+                        let stroke = custom_path
+                            .stroke
+                            .as_ref()
+                            .or(custom.stroke.as_ref())
+                            .or(defaults.stroke.as_ref());
+                        Self::draw_path(
+                            canvas,
+                            &commands_path(&custom_path.cmd),
+                            FillRule::NonZero,
+                            fill,
+                            stroke,
+                            &DrawOptions::new(custom.transparency, &custom.clip, &custom.transform, defaults),
+                        );
+                    }
+                },
+                Shape::Sector(sector) => {
+                    Self::draw_path(
+                        canvas,
+                        &commands_path(&sector.commands()),
+                        FillRule::NonZero,
+                        sector.fill.as_ref().or(defaults.fill.as_ref()),
+                        sector.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(sector.transparency, &sector.clip, &sector.transform, defaults),
+                    );
+                },
+                Shape::Ring(ring) => {
+                    // The full ring is two circles, the inner one is the hole.
+                    let fill_rule = if ring.is_full() {
+                        FillRule::EvenOdd
+                    } else {
+                        FillRule::NonZero
+                    };
+                    Self::draw_path(
+                        canvas,
+                        &commands_path(&ring.commands()),
+                        fill_rule,
+                        ring.fill.as_ref().or(defaults.fill.as_ref()),
+                        ring.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ring.transparency, &ring.clip, &ring.transform, defaults),
+                    );
+                },
+                Shape::Group(group) => {
+                    defaults.apply_group(group);
+                },
+            }
+        }
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(canvas, images, fonts, child, defaults)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_path(
+        canvas: &mut Canvas, path: &Path, fill_rule: FillRule, fill: Option<&Fill>, stroke: Option<&Stroke>,
+        options: &DrawOptions,
+    ) {
+        if let Some(fill) = fill {
+            canvas.fill_path(path, fill_rule, &fill.paint, options);
+        }
+        if let Some(stroke) = stroke {
+            canvas.stroke_path(path, stroke, options);
+        }
+    }
+
+    fn render_text(
+        canvas: &mut Canvas, fonts: &mut FontCache, text: &Text, defaults: &ShapeDefaults,
+    ) -> Result<(), PdfRenderError> {
+        let options = DrawOptions::new(text.transparency, &text.clip, &text.transform, defaults);
+        let input = text.input.as_ref().filter(|input| input.focused);
+        if let Some(input) = input {
+            if let Some(range) = input.selection() {
+                let (start_x, end_x) = (text.caret_x(range.start), text.caret_x(range.end));
+                Self::fill_text_line_rect(canvas, text, start_x, end_x - start_x, input.selection_color, &options);
+            }
+        }
+
+        // The glyphs are not drawn until the text is measured.
+        let baseline = match text.baseline() {
+            Some(baseline) => baseline,
+            None => return Ok(()),
+        };
+        let paint = text
+            .fill
+            .as_ref()
+            .or(defaults.fill.as_ref())
+            .map(|fill| fill.paint.clone())
+            .unwrap_or_default();
+        if text.spans.is_empty() || text.path.is_some() {
+            let face_match = fonts.find_face(&text.font_name, text.font_weight, text.font_style);
+            let glyph_transforms = text
+                .path
+                .as_ref()
+                .map(|text_path| text_path.glyph_transforms.as_slice());
+            Self::draw_glyphs(
+                canvas,
+                fonts,
+                &face_match,
+                &text.content,
+                &text.glyph_positions,
+                glyph_transforms,
+                (baseline, text.font_size.val()),
+                &paint,
+                &options,
+            )?;
+        } else {
+            let mut glyph_positions = text.glyph_positions.as_slice();
+            for span in &text.spans {
+                let face = TextFace::of_span(text, span);
+                let face_match = fonts.find_face(face.family, face.weight, face.style);
+                let chars = span.content.chars().count().min(glyph_positions.len());
+                let span_paint = span.fill.as_ref().map(|fill| &fill.paint).unwrap_or(&paint);
+                Self::draw_glyphs(
+                    canvas,
+                    fonts,
+                    &face_match,
+                    &span.content,
+                    &glyph_positions[..chars],
+                    None,
+                    (baseline, text.span_font_size(span)),
+                    span_paint,
+                    &options,
+                )?;
+                glyph_positions = &glyph_positions[chars..];
+            }
+        }
+
+        if let Some(fill) = text.fill.as_ref().or(defaults.fill.as_ref()) {
+            for line in text.decoration_lines() {
+                canvas.fill_path(&rect_path(line), FillRule::NonZero, &fill.paint, &options);
+            }
+        }
+
+        if let Some(input) = input.filter(|input| input.caret_visible) {
+            let caret_x = text.caret_x(input.caret) - CARET_WIDTH / 2.0;
+            Self::fill_text_line_rect(canvas, text, caret_x, CARET_WIDTH, input.caret_color, &options);
+        }
+        Ok(())
+    }
+
+    /// Shows the glyphs of the embedded font placed by the glyph positions. The text on the path is placed by the
+    /// glyph transforms in addition, the glyphs without the transform are out of the path. The missing bold is
+    /// synthesized by the stroked glyph outlines and the missing italic by the skew of the text matrix.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_glyphs(
+        canvas: &mut Canvas, fonts: &mut FontCache, face_match: &FaceMatch, content: &str, positions: &[GlyphPos],
+        glyph_transforms: Option<&[Option<TransformMatrix>]>, (baseline, font_size): (Real, Real), paint: &Paint,
+        options: &DrawOptions,
+    ) -> Result<(), PdfRenderError> {
+        let (first, last) = match (positions.first(), positions.last()) {
+            (Some(first), Some(last)) if font_size > 0.0 => (first, last),
+            _ => return Ok(()),
+        };
+        // The paint of the glyphs is evaluated over the line of the glyphs.
+        let bound = BoundingBox {
+            min_x: first.x,
+            min_y: baseline - font_size,
+            max_x: last.x + last.width,
+            max_y: baseline,
+        };
+        let glyph_ids = fonts.glyph_ids(&face_match.name, content)?;
+        let resource = canvas
+            .document
+            .use_glyphs(&face_match.name, glyph_ids.iter().copied().zip(content.chars()));
+        let skew = if face_match.synthetic_italic {
+            SYNTHETIC_ITALIC_SKEW
+        } else {
+            0.0
+        };
+
+        canvas.begin(options);
+        canvas.set_paint(paint, false, bound, options);
+        if face_match.synthetic_bold {
+            canvas.set_paint(paint, true, bound, options);
+            canvas
+                .content
+                .set_line_width(font_size * SYNTHETIC_BOLD_STROKE)
+                .set_line_join(LineJoinStyle::RoundJoin);
+        }
+        // The runs of the glyphs are shown with the same text matrix, the text on the path places every glyph.
+        let runs: Vec<GlyphRun> = match glyph_transforms {
+            Some(glyph_transforms) => glyph_ids
+                .into_iter()
+                .zip(positions.iter().copied())
+                .enumerate()
+                .filter_map(|(idx, glyph)| {
+                    let glyph_transform = glyph_transforms.get(idx).copied().flatten()?;
+                    Some((Some(glyph_transform), vec![glyph]))
+                })
+                .collect(),
+            None => vec![(None, glyph_ids.into_iter().zip(positions.iter().copied()).collect())],
+        };
+
+        for (glyph_transform, glyphs) in runs {
+            let start_x = match glyphs.first() {
+                Some((_, pos)) => pos.x,
+                None => continue,
+            };
+            if let Some(glyph_transform) = glyph_transform {
+                canvas.content.save_state().transform(glyph_transform.matrix);
+            }
+            canvas.content.begin_text();
+            canvas
+                .content
+                .set_font(Name(resource.as_bytes()), font_size)
+                .set_text_matrix([1.0, 0.0, skew, -1.0, start_x, baseline]);
+            if face_match.synthetic_bold {
+                canvas.content.set_text_rendering_mode(TextRenderingMode::FillStroke);
+            }
+
+            let mut shown = canvas.content.show_positioned();
+            let mut items = shown.items();
+            let mut encoded = Vec::new();
+            let mut x = start_x;
+            for (glyph_id, pos) in glyphs {
+                // The adjustment moves the glyph back in the thousandths of the em.
+                let adjustment = (x - pos.x) * 1000.0 / font_size;
+                if adjustment.abs() > 0.01 {
+                    items.show(Str(&encoded)).adjust(adjustment);
+                    encoded.clear();
+                }
+                encoded.extend((glyph_id as u16).to_be_bytes());
+                x = pos.x + glyph_width(fonts, &face_match.name, glyph_id)? * font_size / 1000.0;
+            }
+            items.show(Str(&encoded));
+            items.finish();
+            shown.finish();
+            canvas.content.end_text();
+            if glyph_transform.is_some() {
+                canvas.content.restore_state();
+            }
+        }
+        canvas.end();
+        Ok(())
+    }
+
+    /// Fills the rect of the text line height, it is used for the caret and the selection of the text input.
+    fn fill_text_line_rect(
+        canvas: &mut Canvas, text: &Text, x: Real, width: Real, color: Color, options: &DrawOptions,
+    ) {
+        let height = text
+            .metrics
+            .map(|metrics| metrics.line_height)
+            .unwrap_or_else(|| text.font_size.val());
+        let rect = BoundingBox {
+            min_x: x,
+            min_y: text.y.val(),
+            max_x: x + width,
+            max_y: text.y.val() + height,
+        };
+        canvas.fill_path(&rect_path(rect), FillRule::NonZero, &Paint::Color(color), options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exgui_core::{Command, Gradient, Group, Model, Node, Prim, Rect};
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    use super::*;
+
+    struct Scene;
+
+    impl Model for Scene {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Scene
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            Command::none()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            unimplemented!()
+        }
+    }
+
+    fn rect(x: Real, fill: Fill) -> Node<Scene> {
+        let rect = Rect {
+            x: x.into(),
+            width: 20.0.into(),
+            height: 10.0.into(),
+            fill: Some(fill),
+            ..Default::default()
+        };
+        Node::Prim(Prim::new(
+            Rect::NAME.into(),
+            Shape::Rect(rect),
+            vec![],
+            Default::default(),
+        ))
+    }
+
+    /// Data of the streams of the PDF file.
+    fn streams(pdf: &[u8]) -> Vec<&[u8]> {
+        let find = |data: &[u8], pattern: &[u8]| data.windows(pattern.len()).position(|window| window == pattern);
+        let mut streams = Vec::new();
+        let mut rest = pdf;
+        while let Some(start) = find(rest, b"stream\n") {
+            rest = &rest[start + b"stream\n".len()..];
+            let end = find(rest, b"\nendstream").expect("Stream end expected");
+            streams.push(&rest[..end]);
+            rest = &rest[end + b"\nendstream".len()..];
+        }
+        streams
+    }
+
+    #[test]
+    fn export_pages() {
+        let mut render = PdfRender::new(100.0, 50.0);
+        let gradient = Fill::gradient(Gradient::Linear {
+            start: (30.0, 0.0),
+            end: (50.0, 0.0),
+            start_color: Color::Black,
+            end_color: Color::White,
+        });
+        let mut node = Node::Prim(Prim::new(
+            Group::NAME.into(),
+            Shape::Group(Group::default()),
+            vec![rect(0.0, Fill::color(Color::Red)), rect(30.0, gradient)],
+            Default::default(),
+        ));
+        assert!(render.render(&mut node).unwrap());
+        render.new_page();
+        assert!(render.render(&mut node).unwrap());
+        assert_eq!(render.page_count(), 2);
+
+        let pdf = render.to_pdf().unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/Type /Catalog"));
+        assert!(text.contains("/Count 2"));
+        assert_eq!(text.matches("/Type /Page\n").count(), 2);
+        assert_eq!(text.matches("/MediaBox [0 0 100 50]").count(), 2);
+        assert!(text.contains("/Coords [30 0 50 0]"));
+
+        let contents: Vec<_> = streams(&pdf)
+            .into_iter()
+            .map(|data| String::from_utf8(decompress_to_vec_zlib(data).unwrap()).unwrap())
+            .collect();
+        assert_eq!(contents.len(), 2);
+        for content in contents {
+            // The page is flipped, so the shapes are drawn in the layout coordinates.
+            assert!(content.starts_with("1 0 0 -1 0 50 cm\n"));
+            assert!(content.contains("1 0 0 rg\n0 0 m\n20 0 l\n20 10 l\n0 10 l\nh\nf\n"));
+            assert!(content.contains("/Pattern cs\n"));
+            assert!(content.contains("30 0 m\n50 0 l\n50 10 l\n30 10 l\nh\nf\n"));
+        }
+    }
+}
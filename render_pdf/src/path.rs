@@ -0,0 +1,322 @@
+use exgui_core::{arc_to_cub_beziers, BoundingBox, PathCommand, Real, TransformMatrix};
+use pdf_writer::Content;
+
+/// Distance of the cubic bezier control points from the corner, relative to the radius of the quarter arc.
+const KAPPA: Real = 0.552_284_8;
+
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    MoveTo([Real; 2]),
+    LineTo([Real; 2]),
+    CubicTo([Real; 2], [Real; 2], [Real; 2]),
+    Close,
+}
+
+/// Path of the PDF content, the PDF has no quadratic beziers, so they are elevated to the cubic ones.
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    segments: Vec<Segment>,
+    last_xy: [Real; 2],
+}
+
+impl Path {
+    pub fn move_to(&mut self, x: Real, y: Real) {
+        self.last_xy = [x, y];
+        self.segments.push(Segment::MoveTo([x, y]));
+    }
+
+    pub fn line_to(&mut self, x: Real, y: Real) {
+        self.last_xy = [x, y];
+        self.segments.push(Segment::LineTo([x, y]));
+    }
+
+    pub fn quad_to(&mut self, ctrl_x: Real, ctrl_y: Real, x: Real, y: Real) {
+        let [from_x, from_y] = self.last_xy;
+        self.cubic_to(
+            from_x + 2.0 / 3.0 * (ctrl_x - from_x),
+            from_y + 2.0 / 3.0 * (ctrl_y - from_y),
+            x + 2.0 / 3.0 * (ctrl_x - x),
+            y + 2.0 / 3.0 * (ctrl_y - y),
+            x,
+            y,
+        );
+    }
+
+    pub fn cubic_to(&mut self, ctrl1_x: Real, ctrl1_y: Real, ctrl2_x: Real, ctrl2_y: Real, x: Real, y: Real) {
+        self.last_xy = [x, y];
+        self.segments
+            .push(Segment::CubicTo([ctrl1_x, ctrl1_y], [ctrl2_x, ctrl2_y], [x, y]));
+    }
+
+    pub fn close(&mut self) {
+        self.segments.push(Segment::Close);
+    }
+
+    /// Bound of the path points including the bezier control points.
+    pub fn bound(&self) -> Option<BoundingBox> {
+        let mut points = self.segments.iter().flat_map(|segment| match *segment {
+            Segment::MoveTo(xy) | Segment::LineTo(xy) => vec![xy],
+            Segment::CubicTo(ctrl1, ctrl2, xy) => vec![ctrl1, ctrl2, xy],
+            Segment::Close => vec![],
+        });
+        let [x, y] = points.next()?;
+        let mut bound = BoundingBox {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        };
+        for [x, y] in points {
+            bound.min_x = bound.min_x.min(x);
+            bound.min_y = bound.min_y.min(y);
+            bound.max_x = bound.max_x.max(x);
+            bound.max_y = bound.max_y.max(y);
+        }
+        Some(bound)
+    }
+
+    /// Transformed copy of the path.
+    pub fn transform(&self, matrix: &TransformMatrix) -> Self {
+        let apply = |[x, y]: [Real; 2]| {
+            let (x, y) = *matrix * (x, y);
+            [x, y]
+        };
+        Self {
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| match *segment {
+                    Segment::MoveTo(xy) => Segment::MoveTo(apply(xy)),
+                    Segment::LineTo(xy) => Segment::LineTo(apply(xy)),
+                    Segment::CubicTo(ctrl1, ctrl2, xy) => Segment::CubicTo(apply(ctrl1), apply(ctrl2), apply(xy)),
+                    Segment::Close => Segment::Close,
+                })
+                .collect(),
+            last_xy: apply(self.last_xy),
+        }
+    }
+
+    /// Writes the path construction operators into the content, the path is painted by the following operator.
+    pub fn write(&self, content: &mut Content) {
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo([x, y]) => {
+                    content.move_to(x, y);
+                },
+                Segment::LineTo([x, y]) => {
+                    content.line_to(x, y);
+                },
+                Segment::CubicTo([x1, y1], [x2, y2], [x, y]) => {
+                    content.cubic_to(x1, y1, x2, y2, x, y);
+                },
+                Segment::Close => {
+                    content.close_path();
+                },
+            }
+        }
+    }
+}
+
+pub fn rect_path(bound: BoundingBox) -> Path {
+    let mut path = Path::default();
+    path.move_to(bound.min_x, bound.min_y);
+    path.line_to(bound.max_x, bound.min_y);
+    path.line_to(bound.max_x, bound.max_y);
+    path.line_to(bound.min_x, bound.max_y);
+    path.close();
+    path
+}
+
+/// Builds the rect with the rounded corners, the radii are in the order: top left, top right, bottom right and
+/// bottom left. The radii are limited by the half of the rect sides.
+pub fn rounded_rect_path(bound: BoundingBox, radii: [Real; 4]) -> Path {
+    let max_radius = bound.width().min(bound.height()) / 2.0;
+    let [top_left, top_right, bottom_right, bottom_left] = radii.map(|radius| radius.max(0.0).min(max_radius));
+    let (min_x, min_y, max_x, max_y) = (bound.min_x, bound.min_y, bound.max_x, bound.max_y);
+
+    let mut path = Path::default();
+    path.move_to(min_x + top_left, min_y);
+    path.line_to(max_x - top_right, min_y);
+    let k = top_right * KAPPA;
+    path.cubic_to(
+        max_x - top_right + k,
+        min_y,
+        max_x,
+        min_y + top_right - k,
+        max_x,
+        min_y + top_right,
+    );
+    path.line_to(max_x, max_y - bottom_right);
+    let k = bottom_right * KAPPA;
+    path.cubic_to(
+        max_x,
+        max_y - bottom_right + k,
+        max_x - bottom_right + k,
+        max_y,
+        max_x - bottom_right,
+        max_y,
+    );
+    path.line_to(min_x + bottom_left, max_y);
+    let k = bottom_left * KAPPA;
+    path.cubic_to(
+        min_x + bottom_left - k,
+        max_y,
+        min_x,
+        max_y - bottom_left + k,
+        min_x,
+        max_y - bottom_left,
+    );
+    path.line_to(min_x, min_y + top_left);
+    let k = top_left * KAPPA;
+    path.cubic_to(
+        min_x,
+        min_y + top_left - k,
+        min_x + top_left - k,
+        min_y,
+        min_x + top_left,
+        min_y,
+    );
+    path.close();
+    path
+}
+
+pub fn ellipse_path(cx: Real, cy: Real, rx: Real, ry: Real) -> Path {
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let mut path = Path::default();
+    path.move_to(cx + rx, cy);
+    path.cubic_to(cx + rx, cy + ky, cx + kx, cy + ry, cx, cy + ry);
+    path.cubic_to(cx - kx, cy + ry, cx - rx, cy + ky, cx - rx, cy);
+    path.cubic_to(cx - rx, cy - ky, cx - kx, cy - ry, cx, cy - ry);
+    path.cubic_to(cx + kx, cy - ry, cx + rx, cy - ky, cx + rx, cy);
+    path.close();
+    path
+}
+
+/// Builds the closed contours of the polygon.
+pub fn polygon_path(contours: &[Vec<[Real; 2]>]) -> Path {
+    let mut path = Path::default();
+    for contour in contours {
+        let mut points = contour.iter();
+        if let Some(first) = points.next() {
+            path.move_to(first[0], first[1]);
+            for xy in points {
+                path.line_to(xy[0], xy[1]);
+            }
+            path.close();
+        }
+    }
+    path
+}
+
+/// Builds the path of the commands, the arcs are converted into the cubic beziers.
+pub fn commands_path(commands: &[PathCommand]) -> Path {
+    use exgui_core::PathCommand::*;
+
+    let mut path = Path::default();
+    let mut last_xy = [0.0, 0.0];
+    let mut bez_ctrls = [[0.0, 0.0], [0.0, 0.0]];
+
+    for cmd in commands.iter() {
+        match *cmd {
+            Move(xy) => {
+                last_xy = xy;
+                path.move_to(last_xy[0], last_xy[1]);
+            },
+            MoveRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                path.move_to(last_xy[0], last_xy[1]);
+            },
+            Line(xy) => {
+                last_xy = xy;
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            LineRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            LineAlonX(x) => {
+                last_xy[0] = x;
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            LineAlonXRel(x) => {
+                last_xy[0] += x;
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            LineAlonY(y) => {
+                last_xy[1] = y;
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            LineAlonYRel(y) => {
+                last_xy[1] += y;
+                path.line_to(last_xy[0], last_xy[1]);
+            },
+            Close => path.close(),
+            BezCtrl(xy) => {
+                bez_ctrls = [bez_ctrls[1], xy];
+            },
+            BezCtrlRel(xy) => {
+                bez_ctrls = [bez_ctrls[1], [last_xy[0] + xy[0], last_xy[1] + xy[1]]];
+            },
+            BezReflectCtrl => {
+                let reflected = [2.0 * last_xy[0] - bez_ctrls[1][0], 2.0 * last_xy[1] - bez_ctrls[1][1]];
+                bez_ctrls = [bez_ctrls[1], reflected];
+            },
+            QuadBezTo(xy) => {
+                last_xy = xy;
+                path.quad_to(bez_ctrls[1][0], bez_ctrls[1][1], last_xy[0], last_xy[1]);
+            },
+            QuadBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                path.quad_to(bez_ctrls[1][0], bez_ctrls[1][1], last_xy[0], last_xy[1]);
+            },
+            CubBezTo(xy) => {
+                last_xy = xy;
+                path.cubic_to(
+                    bez_ctrls[0][0],
+                    bez_ctrls[0][1],
+                    bez_ctrls[1][0],
+                    bez_ctrls[1][1],
+                    last_xy[0],
+                    last_xy[1],
+                );
+            },
+            CubBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                path.cubic_to(
+                    bez_ctrls[0][0],
+                    bez_ctrls[0][1],
+                    bez_ctrls[1][0],
+                    bez_ctrls[1][1],
+                    last_xy[0],
+                    last_xy[1],
+                );
+            },
+            Arc {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    path.cubic_to(ctrl1[0], ctrl1[1], ctrl2[0], ctrl2[1], xy[0], xy[1]);
+                }
+                last_xy = to;
+            },
+            ArcRel {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                let to = [last_xy[0] + to[0], last_xy[1] + to[1]];
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    path.cubic_to(ctrl1[0], ctrl1[1], ctrl2[0], ctrl2[1], xy[0], xy[1]);
+                }
+                last_xy = to;
+            },
+        }
+    }
+    path
+}
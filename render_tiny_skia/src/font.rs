@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use font_kit::outline::OutlineSink;
+use pathfinder_geometry::{line_segment::LineSegment2F, vector::Vector2F};
+use tiny_skia::{Path, PathBuilder};
+
+use crate::TinySkiaRenderError;
+
+/// Fonts of the render, the glyph outlines are cached in the em units.
+pub type FontCache = exgui_core::FontCache<Option<Arc<Path>>>;
+
+/// Returns the cached outline of the glyph in the em units with the y axis going down. The glyphs without the
+/// outline, e.g. the space, have no path.
+pub fn glyph_path(fonts: &mut FontCache, name: &str, glyph_id: u32) -> Result<Option<Arc<Path>>, TinySkiaRenderError> {
+    fonts.glyph(name, glyph_id, |fonts| {
+        let mut sink = GlyphSink::new(1.0 / fonts.units_per_em(name)? as f32);
+        fonts.outline(name, glyph_id, &mut sink)?;
+        Ok(sink.builder.finish().map(Arc::new))
+    })
+}
+
+/// Collects the glyph outline into the path, the font units are scaled to the em units and flipped downward.
+struct GlyphSink {
+    builder: PathBuilder,
+    scale: f32,
+}
+
+impl GlyphSink {
+    fn new(scale: f32) -> Self {
+        Self {
+            builder: PathBuilder::new(),
+            scale,
+        }
+    }
+
+    fn point(&self, vector: Vector2F) -> (f32, f32) {
+        (vector.x() * self.scale, -vector.y() * self.scale)
+    }
+}
+
+impl OutlineSink for GlyphSink {
+    fn move_to(&mut self, to: Vector2F) {
+        let (x, y) = self.point(to);
+        self.builder.move_to(x, y);
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        let (x, y) = self.point(to);
+        self.builder.line_to(x, y);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let ((x1, y1), (x, y)) = (self.point(ctrl), self.point(to));
+        self.builder.quad_to(x1, y1, x, y);
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let ((x1, y1), (x2, y2), (x, y)) = (self.point(ctrl.from()), self.point(ctrl.to()), self.point(to));
+        self.builder.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
@@ -0,0 +1,1195 @@
+use std::{
+    borrow::Cow, collections::HashMap, f32::consts::PI, fmt, mem, path::Path as FilePath, rc::Rc, sync::Arc,
+    time::Instant,
+};
+
+use exgui_core::{
+    apply_filters, recalc_in_view, BoundingBox, Clip, Color, CompositeShape, Damage, FaceMatch, Fill, FillRule,
+    FontError, GlyphPos, Gradient, Group, LayerState, LayoutMeasurer, LineCap, LineJoin, Paint, Real, Render,
+    RenderStats, Shape, ShapeDefaults, Stroke, Text, TextCache, TextFace, TextMeasurer, TextMetrics, Theme, Transform,
+    TransformMatrix,
+};
+use font_kit::{error::FontLoadingError, font::Font};
+use image::{ImageError, ImageFormat, RgbaImage};
+use tiny_skia::{
+    BlendMode, Color as SkiaColor, FillRule as SkiaFillRule, FilterQuality, GradientStop, LineCap as SkiaLineCap,
+    LineJoin as SkiaLineJoin, LinearGradient, Mask, Paint as SkiaPaint, Path, Pattern, Pixmap, PixmapPaint, Point,
+    RadialGradient, Shader, SpreadMode, Stroke as SkiaStroke, Transform as SkiaTransform,
+};
+
+pub use self::font::FontCache;
+use self::{
+    font::glyph_path,
+    path::{commands_path, ellipse_path, polygon_path, rect, rect_path, rounded_rect_path},
+};
+
+mod font;
+mod path;
+
+const CARET_WIDTH: Real = 1.5;
+/// Horizontal shift of the synthetic bold overdraw, relative to the font size.
+const SYNTHETIC_BOLD_SHIFT: Real = 0.04;
+/// Slant of the synthetic italic.
+const SYNTHETIC_ITALIC_SKEW: Real = 0.2;
+/// Maximal side of the pixmaps, which the box and conic gradients are rasterized into.
+const MAX_GRADIENT_SIZE: Real = 2048.0;
+
+#[derive(Debug)]
+pub enum TinySkiaRenderError {
+    InvalidDimensions(u32, u32),
+    CreateFontError(FontLoadingError, String),
+    CreateImageError(ImageError, String),
+    SaveImageError(ImageError, String),
+    FontError(FontError),
+}
+
+impl From<FontError> for TinySkiaRenderError {
+    fn from(err: FontError) -> Self {
+        TinySkiaRenderError::FontError(err)
+    }
+}
+
+/// Decoded images of the image shapes with the premultiplied alpha.
+pub type ImageCache = HashMap<String, Pixmap>;
+
+/// Rendered layers of the cached groups, keyed by the group address.
+type LayerCache = HashMap<*const Group, CachedLayer>;
+
+struct CachedLayer {
+    state: LayerState,
+    pixmap: Pixmap,
+}
+
+/// Layers of the cached groups, the layers of the last render are moved into the current ones when they are drawn,
+/// so the layers of the removed groups are dropped.
+#[derive(Default)]
+struct Layers {
+    last: LayerCache,
+    current: LayerCache,
+}
+
+/// Shape state, which the path is drawn with.
+#[derive(Clone)]
+struct DrawOptions<'a> {
+    alpha: f32,
+    clip: Cow<'a, Clip>,
+    transform: SkiaTransform,
+}
+
+impl<'a> DrawOptions<'a> {
+    fn new(transparency: Real, clip: &'a Clip, transform: &Transform, defaults: &'a ShapeDefaults) -> Self {
+        let transform = if transform.is_not_exist() {
+            SkiaTransform::identity()
+        } else {
+            skia_transform(transform.calculated_matrix().unwrap_or_else(|| transform.matrix()))
+        };
+        Self {
+            alpha: (1.0 - transparency) * (1.0 - defaults.transparency),
+            clip: clip.intersect(&defaults.clip),
+            transform,
+        }
+    }
+}
+
+/// Pixmap, which the shapes are drawn into, with the mask of the last used clip, because the neighbouring shapes
+/// share the clip usually. The drawing is limited by the damage mask, when only the damaged areas are redrawn.
+/// The shapes are laid out in the logical pixels, so they are scaled by the device pixel ratio into the pixmap.
+struct Canvas<'a> {
+    pixmap: &'a mut Pixmap,
+    scale: SkiaTransform,
+    clip_mask: Option<(Clip, Mask)>,
+    damage_mask: Option<Mask>,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(pixmap: &'a mut Pixmap, device_pixel_ratio: f32, damage_mask: Option<Mask>) -> Self {
+        Self {
+            pixmap,
+            scale: SkiaTransform::from_scale(device_pixel_ratio, device_pixel_ratio),
+            clip_mask: None,
+            damage_mask,
+        }
+    }
+
+    /// Transform of the shape from the logical pixels into the pixels of the pixmap.
+    fn transform(&self, transform: SkiaTransform) -> SkiaTransform {
+        self.scale.pre_concat(transform)
+    }
+
+    /// Returns the mask of the clip, the mask is rasterized if the clip differs from the last one.
+    fn mask(&mut self, clip: &Clip) -> Option<&Mask> {
+        let outline = match clip.outline() {
+            Some(outline) => outline,
+            None => return self.damage_mask.as_ref(),
+        };
+        if self.clip_mask.as_ref().map(|(last_clip, _)| last_clip != clip).unwrap_or(true) {
+            self.clip_mask = None;
+            let mut mask = Mask::new(self.pixmap.width(), self.pixmap.height())?;
+            // The empty clip region clips everything out, so the mask stays empty.
+            if let (Some(path), Some(transform)) = (commands_path(&outline), clip.transform()) {
+                let transform = if transform.is_not_exist() {
+                    SkiaTransform::identity()
+                } else {
+                    skia_transform(transform.calculated_matrix().unwrap_or_else(|| transform.matrix()))
+                };
+                mask.fill_path(&path, SkiaFillRule::Winding, true, self.scale.pre_concat(transform));
+            }
+            if let Some(damage_mask) = &self.damage_mask {
+                for (coverage, damage) in mask.data_mut().iter_mut().zip(damage_mask.data()) {
+                    *coverage = (*coverage as u16 * *damage as u16 / 255) as u8;
+                }
+            }
+            self.clip_mask = Some((clip.clone(), mask));
+        }
+        self.clip_mask.as_ref().map(|(_, mask)| mask)
+    }
+
+    fn fill_path(&mut self, path: &Path, fill_rule: FillRule, paint: &Paint, options: &DrawOptions) {
+        let mut pattern = None;
+        if let Some(shader) = skia_shader(paint, options.alpha, path_bound(path), &mut pattern) {
+            let paint = SkiaPaint {
+                shader,
+                anti_alias: true,
+                ..Default::default()
+            };
+            let fill_rule = match fill_rule {
+                FillRule::NonZero => SkiaFillRule::Winding,
+                FillRule::EvenOdd => SkiaFillRule::EvenOdd,
+            };
+            let mask = self.mask(&options.clip).cloned();
+            let transform = self.transform(options.transform);
+            self.pixmap.fill_path(path, &paint, fill_rule, transform, mask.as_ref());
+        }
+    }
+
+    fn stroke_path(&mut self, path: &Path, stroke: &Stroke, options: &DrawOptions) {
+        let mut pattern = None;
+        if let Some(shader) = skia_shader(&stroke.paint, options.alpha, path_bound(path), &mut pattern) {
+            let paint = SkiaPaint {
+                shader,
+                anti_alias: true,
+                ..Default::default()
+            };
+            let line_cap = match stroke.line_cap {
+                LineCap::Butt => SkiaLineCap::Butt,
+                LineCap::Round => SkiaLineCap::Round,
+                LineCap::Square => SkiaLineCap::Square,
+            };
+            let line_join = match stroke.line_join {
+                LineJoin::Miter => SkiaLineJoin::Miter,
+                LineJoin::Round => SkiaLineJoin::Round,
+                LineJoin::Bevel => SkiaLineJoin::Bevel,
+            };
+            let stroke = SkiaStroke {
+                width: stroke.width,
+                miter_limit: stroke.miter_limit,
+                line_cap,
+                line_join,
+                dash: None,
+            };
+            let mask = self.mask(&options.clip).cloned();
+            let transform = self.transform(options.transform);
+            self.pixmap.stroke_path(path, &paint, &stroke, transform, mask.as_ref());
+        }
+    }
+
+    /// Draws the image stretched into the bound.
+    fn draw_image(&mut self, image: &Pixmap, bound: BoundingBox, options: &DrawOptions) {
+        if let Some(rect) = rect(bound) {
+            let pattern_transform = SkiaTransform::from_row(
+                bound.width() / image.width() as f32,
+                0.0,
+                0.0,
+                bound.height() / image.height() as f32,
+                bound.min_x,
+                bound.min_y,
+            );
+            let paint = SkiaPaint {
+                shader: Pattern::new(
+                    image.as_ref(),
+                    SpreadMode::Pad,
+                    FilterQuality::Bilinear,
+                    options.alpha,
+                    pattern_transform,
+                ),
+                anti_alias: true,
+                ..Default::default()
+            };
+            let mask = self.mask(&options.clip).cloned();
+            let transform = self.transform(options.transform);
+            self.pixmap.fill_rect(rect, &paint, transform, mask.as_ref());
+        }
+    }
+}
+
+/// The render rasterizes the shapes by tiny-skia into the in-memory pixmap, so it needs no GPU and no window. It can
+/// be used for the screenshot tests, the server-side image generation and the thumbnails. The text glyphs are filled
+/// from the font outlines. The layout is calculated by the shared `exgui_core::recalc`, which tracks the damage, so
+/// only the areas of the shapes changed since the last frame are redrawn.
+pub struct TinySkiaRender {
+    pub pixmap: Pixmap,
+    pub images: ImageCache,
+    pub fonts: FontCache,
+    /// Measured text lines of the layout, it is cleared when the fonts are loaded.
+    pub text_cache: TextCache,
+    pub device_pixel_ratio: f32,
+    pub theme: Rc<Theme>,
+    theme_changed: bool,
+    view_transform: TransformMatrix,
+    view_changed: bool,
+    background_color: Color,
+    damage: Damage,
+    /// The pixmap content is not valid, e.g. after the resize, so the next render redraws it whole.
+    full_redraw: bool,
+    layers: LayerCache,
+    stats: RenderStats,
+}
+
+impl fmt::Debug for TinySkiaRender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(TinySkiaRender))
+            .field("width", &self.pixmap.width())
+            .field("height", &self.pixmap.height())
+            .field("images", &self.images.keys().collect::<Vec<_>>())
+            .field("device_pixel_ratio", &self.device_pixel_ratio)
+            .field("theme", &self.theme)
+            .field("view_transform", &self.view_transform)
+            .finish()
+    }
+}
+
+impl Render for TinySkiaRender {
+    type Error = TinySkiaRenderError;
+
+    fn init(&mut self, background_color: Color) -> Result<(), Self::Error> {
+        self.background_color = background_color;
+        self.full_redraw = true;
+        Ok(())
+    }
+
+    /// Resizes the pixmap, the content of the resized pixmap is drawn by the next render.
+    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
+        self.device_pixel_ratio = device_pixel_ratio as f32;
+        if (physical_width, physical_height) != (self.pixmap.width(), self.pixmap.height()) {
+            if let Some(pixmap) = Pixmap::new(physical_width, physical_height) {
+                self.pixmap = pixmap;
+                self.full_redraw = true;
+            }
+        }
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn set_view_transform(&mut self, transform: TransformMatrix) {
+        if transform != self.view_transform {
+            self.view_transform = transform;
+            self.view_changed = true;
+        }
+    }
+
+    fn view_transform(&self) -> TransformMatrix {
+        self.view_transform
+    }
+
+    fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let view_changed = mem::take(&mut self.view_changed);
+        let need_recalc = node.need_recalc().unwrap_or(true) || theme_changed || view_changed;
+        let dpr = self.device_pixel_ratio;
+        let bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: self.pixmap.width() as Real,
+            max_y: self.pixmap.height() as Real,
+        };
+        // The layout is calculated in the logical pixels.
+        let logical_bound = BoundingBox {
+            max_x: bound.max_x / dpr,
+            max_y: bound.max_y / dpr,
+            ..bound
+        };
+        let mut stats = RenderStats::default();
+        let mut damage = Damage::new();
+        if need_recalc {
+            let theme = self.theme.clone();
+            let start = Instant::now();
+            recalc_in_view(self, node, logical_bound, self.view_transform, theme, &mut damage)?;
+            stats.recalc_time = start.elapsed();
+        }
+
+        // All the changes of the tree are recalculated, so the areas of the changed shapes are known by the damage.
+        let full_redraw = mem::take(&mut self.full_redraw) || theme_changed || view_changed;
+        self.damage.clear();
+        if full_redraw {
+            self.damage.add(bound);
+        } else {
+            // The damaged areas are extended to the whole physical pixels, so the redrawn areas have no antialiased
+            // edges.
+            for area in damage.rects() {
+                let area = BoundingBox {
+                    min_x: (area.min_x * dpr).floor(),
+                    min_y: (area.min_y * dpr).floor(),
+                    max_x: (area.max_x * dpr).ceil(),
+                    max_y: (area.max_y * dpr).ceil(),
+                };
+                if let Some(area) = area.intersect(&bound) {
+                    self.damage.add(area);
+                }
+            }
+        }
+        let need_redraw = !self.damage.is_empty();
+        if need_redraw {
+            let start = Instant::now();
+            let damage_mask = if full_redraw {
+                self.pixmap.fill(skia_color(self.background_color, 1.0));
+                None
+            } else {
+                Some(self.fill_damage_background()?)
+            };
+            let mut canvas = Canvas::new(&mut self.pixmap, dpr, damage_mask);
+            let mut layers = Layers {
+                last: mem::take(&mut self.layers),
+                current: LayerCache::new(),
+            };
+            let theme = &self.theme;
+            Self::render_composite(
+                &mut canvas,
+                &self.images,
+                &mut self.fonts,
+                &mut layers,
+                &mut stats,
+                node,
+                &mut ShapeDefaults::new(theme.clone()),
+            )?;
+            // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+            for portal in node.portals() {
+                Self::render_composite(
+                    &mut canvas,
+                    &self.images,
+                    &mut self.fonts,
+                    &mut layers,
+                    &mut stats,
+                    portal,
+                    &mut ShapeDefaults::new(theme.clone()),
+                )?;
+            }
+            self.layers = layers.current;
+            stats.draw_time = start.elapsed();
+        }
+        self.stats = stats;
+        Ok(need_redraw)
+    }
+
+    /// Areas of the pixmap, which are redrawn by the last render, the whole pixmap is redrawn after the resize and the
+    /// theme change.
+    fn damage(&self) -> Option<&Damage> {
+        Some(&self.damage)
+    }
+
+    fn stats(&self) -> Option<&RenderStats> {
+        Some(&self.stats)
+    }
+}
+
+impl TextMeasurer for TinySkiaRender {
+    type Error = TinySkiaRenderError;
+
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let name = self.fonts.resolve(font_name)?;
+        Self::measure_font(&self.fonts, &name, text, font_size)
+    }
+
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Self::measure_font(&self.fonts, &face_match.name, text, font_size)
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Ok(self.fonts.glyph_positions(&face_match.name, text, font_size)?)
+    }
+}
+
+impl LayoutMeasurer for TinySkiaRender {
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error> {
+        let image = Self::cached_image(&mut self.images, src)?;
+        Ok((image.width() as Real, image.height() as Real))
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut self.text_cache)
+    }
+}
+
+impl TinySkiaRender {
+    pub fn new(width: u32, height: u32) -> Result<Self, TinySkiaRenderError> {
+        Ok(Self {
+            pixmap: Pixmap::new(width, height).ok_or(TinySkiaRenderError::InvalidDimensions(width, height))?,
+            images: ImageCache::new(),
+            fonts: FontCache::default(),
+            text_cache: TextCache::new(),
+            device_pixel_ratio: 1.0,
+            theme: Rc::new(Theme::default()),
+            theme_changed: false,
+            view_transform: TransformMatrix::identity(),
+            view_changed: false,
+            background_color: Color::White,
+            damage: Damage::new(),
+            full_redraw: true,
+            layers: LayerCache::new(),
+            stats: RenderStats::default(),
+        })
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
+
+    pub fn with_background_color(mut self, background_color: Color) -> Self {
+        self.background_color = background_color;
+        self.full_redraw = true;
+        self
+    }
+
+    /// Rendered pixels in the RGBA format with the premultiplied alpha.
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    /// Copy of the rendered pixels with the straight alpha, e.g. to export the scene as an image or to compare it
+    /// with the golden image in the tests.
+    pub fn snapshot(&self) -> RgbaImage {
+        let data = self
+            .pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        RgbaImage::from_raw(self.pixmap.width(), self.pixmap.height(), data)
+            .expect("The pixel data has the size of the pixmap")
+    }
+
+    /// Saves the snapshot of the rendered pixels into the PNG file.
+    pub fn save_png(&self, path: impl AsRef<FilePath>) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        self.snapshot()
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(|e| TinySkiaRenderError::SaveImageError(e, display_path))
+    }
+
+    pub fn load_font(
+        &mut self, name: impl Into<String>, path: impl AsRef<FilePath>,
+    ) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        let font =
+            Font::from_path(path.as_ref(), 0).map_err(|e| TinySkiaRenderError::CreateFontError(e, display_path))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`.
+    pub fn load_font_from_memory(
+        &mut self, name: impl Into<String>, data: &'static [u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        let name = name.into();
+        let font = Font::from_bytes(Arc::new(data.to_vec()), 0)
+            .map_err(|e| TinySkiaRenderError::CreateFontError(e, name.clone()))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    pub fn load_image(&mut self, src: impl Into<String>) -> Result<(), <Self as Render>::Error> {
+        Self::cached_image(&mut self.images, &src.into())?;
+        Ok(())
+    }
+
+    /// Fills the damaged areas by the background and returns the mask of them, which limits the drawing of the shapes.
+    fn fill_damage_background(&mut self) -> Result<Mask, TinySkiaRenderError> {
+        let (width, height) = (self.pixmap.width(), self.pixmap.height());
+        let mut mask = Mask::new(width, height).ok_or(TinySkiaRenderError::InvalidDimensions(width, height))?;
+        let paint = SkiaPaint {
+            shader: Shader::SolidColor(skia_color(self.background_color, 1.0)),
+            blend_mode: BlendMode::Source,
+            ..Default::default()
+        };
+        for &area in self.damage.rects() {
+            if let (Some(rect), Some(path)) = (rect(area), rect_path(area)) {
+                self.pixmap.fill_rect(rect, &paint, SkiaTransform::identity(), None);
+                mask.fill_path(&path, SkiaFillRule::Winding, false, SkiaTransform::identity());
+            }
+        }
+        Ok(mask)
+    }
+
+    fn cached_image<'a>(images: &'a mut ImageCache, src: &str) -> Result<&'a Pixmap, TinySkiaRenderError> {
+        if !images.contains_key(src) {
+            let image = image::open(src)
+                .map_err(|e| TinySkiaRenderError::CreateImageError(e, src.to_string()))?
+                .to_rgba8();
+            let mut pixmap = Pixmap::new(image.width().max(1), image.height().max(1))
+                .ok_or(TinySkiaRenderError::InvalidDimensions(image.width(), image.height()))?;
+            for (pixel, rgba) in pixmap.pixels_mut().iter_mut().zip(image.pixels()) {
+                let [r, g, b, a] = rgba.0;
+                *pixel = tiny_skia::ColorU8::from_rgba(r, g, b, a).premultiply();
+            }
+            images.insert(src.to_string(), pixmap);
+        }
+        Ok(&images[src])
+    }
+
+    fn measure_font(
+        fonts: &FontCache, name: &str, text: &str, font_size: Real,
+    ) -> Result<(Real, TextMetrics), TinySkiaRenderError> {
+        let advance = fonts
+            .glyph_positions(name, text, font_size)?
+            .last()
+            .map(|pos| pos.x + pos.width)
+            .unwrap_or(0.0);
+        Ok((advance, fonts.metrics(name, font_size)?))
+    }
+
+    fn render_composite(
+        canvas: &mut Canvas, images: &ImageCache, fonts: &mut FontCache, layers: &mut Layers, stats: &mut RenderStats,
+        composite: &dyn CompositeShape, defaults: &mut ShapeDefaults,
+    ) -> Result<(), TinySkiaRenderError> {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        if let Some(shape) = composite.shape() {
+            stats.shapes += 1;
+            match shape {
+                Shape::Rect(rect) => {
+                    let bound = BoundingBox {
+                        min_x: rect.x.val(),
+                        min_y: rect.y.val(),
+                        max_x: rect.x.val() + rect.width.val(),
+                        max_y: rect.y.val() + rect.height.val(),
+                    };
+                    let path = if let Some(rounding) = rect.rounding {
+                        rounded_rect_path(
+                            bound,
+                            [
+                                rounding.top_left.val(),
+                                rounding.top_right.val(),
+                                rounding.bottom_right.val(),
+                                rounding.bottom_left.val(),
+                            ],
+                        )
+                    } else {
+                        rect_path(bound)
+                    };
+                    Self::draw_path(
+                        canvas,
+                        path,
+                        FillRule::NonZero,
+                        rect.fill.as_ref().or(defaults.fill.as_ref()),
+                        rect.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(rect.transparency, &rect.clip, &rect.transform, defaults),
+                    );
+                }
+                Shape::Circle(circle) => {
+                    let (cx, cy, r) = (circle.cx.val(), circle.cy.val(), circle.r.val());
+                    Self::draw_path(
+                        canvas,
+                        ellipse_path(cx, cy, r, r),
+                        FillRule::NonZero,
+                        circle.fill.as_ref().or(defaults.fill.as_ref()),
+                        circle.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(circle.transparency, &circle.clip, &circle.transform, defaults),
+                    );
+                }
+                Shape::Ellipse(ellipse) => {
+                    Self::draw_path(
+                        canvas,
+                        ellipse_path(ellipse.cx.val(), ellipse.cy.val(), ellipse.rx.val(), ellipse.ry.val()),
+                        FillRule::NonZero,
+                        ellipse.fill.as_ref().or(defaults.fill.as_ref()),
+                        ellipse.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ellipse.transparency, &ellipse.clip, &ellipse.transform, defaults),
+                    );
+                }
+                Shape::Path(path) => {
+                    Self::draw_path(
+                        canvas,
+                        commands_path(&path.cmd),
+                        FillRule::NonZero,
+                        path.fill.as_ref().or(defaults.fill.as_ref()),
+                        path.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(path.transparency, &path.clip, &path.transform, defaults),
+                    );
+
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        Self::render_composite(
+                            canvas,
+                            images,
+                            fonts,
+                            layers,
+                            stats,
+                            marker.shape.as_ref(),
+                            &mut defaults.clone(),
+                        )?;
+                    }
+                }
+                Shape::Polygon(polygon) => {
+                    Self::draw_path(
+                        canvas,
+                        polygon_path(&polygon.contours),
+                        polygon.fill_rule,
+                        polygon.fill.as_ref().or(defaults.fill.as_ref()),
+                        polygon.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                    );
+                }
+                Shape::Image(image) => {
+                    let bound = BoundingBox {
+                        min_x: image.x.val(),
+                        min_y: image.y.val(),
+                        max_x: image.x.val() + image.width.val(),
+                        max_y: image.y.val() + image.height.val(),
+                    };
+                    let options = DrawOptions::new(image.transparency, &image.clip, &image.transform, defaults);
+                    let path = rect_path(bound);
+                    if let (Some(path), Some(fill)) = (&path, image.fill.as_ref().or(defaults.fill.as_ref())) {
+                        canvas.fill_path(path, FillRule::NonZero, &fill.paint, &options);
+                    }
+                    if let Some(pixmap) = images.get(&image.src) {
+                        canvas.draw_image(pixmap, bound, &options);
+                    }
+                    if let (Some(path), Some(stroke)) = (&path, image.stroke.as_ref().or(defaults.stroke.as_ref())) {
+                        canvas.stroke_path(path, stroke, &options);
+                    }
+                }
+                Shape::Text(text) => {
+                    stats.texts += 1;
+                    Self::render_text(canvas, fonts, text, defaults)?;
+                }
+                Shape::Custom(custom) => {
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .as_ref()
+                            .or(custom.fill.as_ref())
+                            .or(defaults.fill.as_ref());
+                        let stroke = custom_path
+                            .stroke
+                            .as_ref()
+                            .or(custom.stroke.as_ref())
+                            .or(defaults.stroke.as_ref());
+                        Self::draw_path(
+                            canvas,
+                            commands_path(&custom_path.cmd),
+                            FillRule::NonZero,
+                            fill,
+                            stroke,
+                                &DrawOptions::new(custom.transparency, &custom.clip, &custom.transform, defaults),
+                        );
+                    }
+                }
+                Shape::Sector(sector) => {
+                    Self::draw_path(
+                        canvas,
+                        commands_path(&sector.commands()),
+                        FillRule::NonZero,
+                        sector.fill.as_ref().or(defaults.fill.as_ref()),
+                        sector.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(sector.transparency, &sector.clip, &sector.transform, defaults),
+                    );
+                }
+                Shape::Ring(ring) => {
+                    // The full ring is two circles, the inner one is the hole.
+                    let fill_rule = if ring.is_full() {
+                        FillRule::EvenOdd
+                    } else {
+                        FillRule::NonZero
+                    };
+                    Self::draw_path(
+                        canvas,
+                        commands_path(&ring.commands()),
+                        fill_rule,
+                        ring.fill.as_ref().or(defaults.fill.as_ref()),
+                        ring.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ring.transparency, &ring.clip, &ring.transform, defaults),
+                    );
+                }
+                Shape::Group(group) => {
+                    defaults.apply_group(group);
+                    if group.need_layer() {
+                        return Self::render_layer(canvas, images, fonts, layers, stats, composite, group, defaults);
+                    }
+                }
+            }
+        }
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(canvas, images, fonts, layers, stats, child, defaults)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the children of the group into the offscreen pixmap, applies the group filters to it and draws it
+    /// with the group transparency. The layer of the cached group is reused, while the group is not changed.
+    #[allow(clippy::too_many_arguments)]
+    fn render_layer(
+        canvas: &mut Canvas, images: &ImageCache, fonts: &mut FontCache, layers: &mut Layers, stats: &mut RenderStats,
+        composite: &dyn CompositeShape, group: &Group, defaults: &ShapeDefaults,
+    ) -> Result<(), TinySkiaRenderError> {
+        let (width, height) = (canvas.pixmap.width(), canvas.pixmap.height());
+        // The group transparency is applied once, when the layer is drawn.
+        let mut layer_defaults = ShapeDefaults {
+            transparency: 0.0,
+            ..defaults.clone()
+        };
+        let key = group as *const Group;
+        let state = LayerState::of(composite, &layer_defaults);
+        let cached = layers.last.remove(&key).filter(|layer| {
+            Some(&layer.state) == state.as_ref() && (layer.pixmap.width(), layer.pixmap.height()) == (width, height)
+        });
+        if let Some(layer) = cached {
+            stats.cache_hits += 1;
+            Self::draw_layer(canvas, &layer.pixmap, defaults);
+            layers.current.insert(key, layer);
+            return Ok(());
+        }
+
+        let mut layer = Pixmap::new(width, height).ok_or(TinySkiaRenderError::InvalidDimensions(width, height))?;
+        {
+            // The layer is drawn whole, because its filters spread the content out of the damaged areas.
+            let mut layer_canvas = Canvas::new(&mut layer, canvas.scale.sx, None);
+            if let Some(children) = composite.children_in_draw_order() {
+                for child in children.into_iter().filter(|child| !child.is_portal()) {
+                    Self::render_composite(
+                        &mut layer_canvas,
+                        images,
+                        fonts,
+                        layers,
+                        stats,
+                        child,
+                        &mut layer_defaults,
+                    )?;
+                }
+            }
+        }
+
+        if !group.filters.is_empty() {
+            let pixels = layer
+                .data()
+                .chunks_exact(4)
+                .map(|pixel| u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+                .collect();
+            // The shape units are the logical pixels, so the filters are scaled by the device pixel ratio.
+            let pixels = apply_filters(&group.filters, pixels, width as usize, height as usize, canvas.scale.sx);
+            for (data, pixel) in layer.data_mut().chunks_exact_mut(4).zip(pixels) {
+                data.copy_from_slice(&pixel.to_ne_bytes());
+            }
+        }
+
+        Self::draw_layer(canvas, &layer, defaults);
+        if let Some(state) = state {
+            layers.current.insert(key, CachedLayer { state, pixmap: layer });
+        }
+        Ok(())
+    }
+
+    fn draw_layer(canvas: &mut Canvas, layer: &Pixmap, defaults: &ShapeDefaults) {
+        let paint = PixmapPaint {
+            opacity: 1.0 - defaults.transparency,
+            ..Default::default()
+        };
+        canvas.pixmap.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &paint,
+            SkiaTransform::identity(),
+            canvas.damage_mask.as_ref(),
+        );
+    }
+
+    fn draw_path(
+        canvas: &mut Canvas, path: Option<Path>, fill_rule: FillRule, fill: Option<&Fill>, stroke: Option<&Stroke>,
+        options: &DrawOptions,
+    ) {
+        if let Some(path) = path {
+            if let Some(fill) = fill {
+                canvas.fill_path(&path, fill_rule, &fill.paint, options);
+            }
+            if let Some(stroke) = stroke {
+                canvas.stroke_path(&path, stroke, options);
+            }
+        }
+    }
+
+    fn render_text(
+        canvas: &mut Canvas, fonts: &mut FontCache, text: &Text, defaults: &ShapeDefaults,
+    ) -> Result<(), TinySkiaRenderError> {
+        let options = DrawOptions::new(text.transparency, &text.clip, &text.transform, defaults);
+        let input = text.input.as_ref().filter(|input| input.focused);
+        if let Some(input) = input {
+            if let Some(range) = input.selection() {
+                let (start_x, end_x) = (text.caret_x(range.start), text.caret_x(range.end));
+                Self::fill_text_line_rect(canvas, text, start_x, end_x - start_x, input.selection_color, &options);
+            }
+        }
+
+        // The glyphs are not drawn until the text is measured.
+        let baseline = match text.baseline() {
+            Some(baseline) => baseline,
+            None => return Ok(()),
+        };
+        let paint = text
+            .fill
+            .as_ref()
+            .or(defaults.fill.as_ref())
+            .map(|fill| fill.paint.clone())
+            .unwrap_or_default();
+        if text.spans.is_empty() || text.path.is_some() {
+            let face_match = fonts.find_face(&text.font_name, text.font_weight, text.font_style);
+            let glyph_transforms = text
+                .path
+                .as_ref()
+                .map(|text_path| text_path.glyph_transforms.as_slice());
+            Self::draw_glyphs(
+                canvas,
+                fonts,
+                &face_match,
+                &text.content,
+                &text.glyph_positions,
+                glyph_transforms,
+                (baseline, text.font_size.val()),
+                &paint,
+                &options,
+            )?;
+        } else {
+            let mut glyph_positions = text.glyph_positions.as_slice();
+            for span in &text.spans {
+                let face = TextFace::of_span(text, span);
+                let face_match = fonts.find_face(face.family, face.weight, face.style);
+                let chars = span.content.chars().count().min(glyph_positions.len());
+                let span_paint = span.fill.as_ref().map(|fill| &fill.paint).unwrap_or(&paint);
+                Self::draw_glyphs(
+                    canvas,
+                    fonts,
+                    &face_match,
+                    &span.content,
+                    &glyph_positions[..chars],
+                    None,
+                    (baseline, text.span_font_size(span)),
+                    span_paint,
+                    &options,
+                )?;
+                glyph_positions = &glyph_positions[chars..];
+            }
+        }
+
+        if let Some(fill) = text.fill.as_ref().or(defaults.fill.as_ref()) {
+            for line in text.decoration_lines() {
+                if let Some(path) = rect_path(line) {
+                    canvas.fill_path(&path, FillRule::NonZero, &fill.paint, &options);
+                }
+            }
+        }
+
+        if let Some(input) = input.filter(|input| input.caret_visible) {
+            let caret_x = text.caret_x(input.caret) - CARET_WIDTH / 2.0;
+            Self::fill_text_line_rect(canvas, text, caret_x, CARET_WIDTH, input.caret_color, &options);
+        }
+        Ok(())
+    }
+
+    /// Fills the glyph outlines placed by the glyph positions. The text on the path is placed by the glyph transforms
+    /// in addition, the glyphs without the transform are out of the path. The missing bold is synthesized by the
+    /// shifted overdraw and the missing italic by the skew around the baseline.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_glyphs(
+        canvas: &mut Canvas, fonts: &mut FontCache, face_match: &FaceMatch, content: &str, positions: &[GlyphPos],
+        glyph_transforms: Option<&[Option<TransformMatrix>]>, (baseline, font_size): (Real, Real), paint: &Paint,
+        options: &DrawOptions,
+    ) -> Result<(), TinySkiaRenderError> {
+        let glyph_ids = fonts.glyph_ids(&face_match.name, content)?;
+        let skew = if face_match.synthetic_italic {
+            SYNTHETIC_ITALIC_SKEW
+        } else {
+            0.0
+        };
+        for (idx, (glyph_id, pos)) in glyph_ids.into_iter().zip(positions).enumerate() {
+            let glyph_options = match glyph_transforms {
+                Some(glyph_transforms) => match glyph_transforms.get(idx).copied().flatten() {
+                    Some(glyph_transform) => DrawOptions {
+                        transform: options.transform.pre_concat(skia_transform(glyph_transform)),
+                        ..options.clone()
+                    },
+                    None => continue,
+                },
+                None => options.clone(),
+            };
+            let glyph_path = match glyph_path(fonts, &face_match.name, glyph_id)? {
+                Some(glyph_path) => glyph_path,
+                None => continue,
+            };
+
+            // The glyph is placed in the text coordinates, so the paint is evaluated in them too.
+            let mut shifts = vec![0.0];
+            if face_match.synthetic_bold {
+                shifts.push(font_size * SYNTHETIC_BOLD_SHIFT);
+            }
+            for shift in shifts {
+                let placement =
+                    SkiaTransform::from_row(font_size, 0.0, -skew * font_size, font_size, pos.x + shift, baseline);
+                if let Some(path) = glyph_path.as_ref().clone().transform(placement) {
+                    canvas.fill_path(&path, FillRule::NonZero, paint, &glyph_options);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills the rect of the text line height, it is used for the caret and the selection of the text input.
+    fn fill_text_line_rect(
+        canvas: &mut Canvas, text: &Text, x: Real, width: Real, color: Color, options: &DrawOptions,
+    ) {
+        let height = text
+            .metrics
+            .map(|metrics| metrics.line_height)
+            .unwrap_or_else(|| text.font_size.val());
+        let rect = BoundingBox {
+            min_x: x,
+            min_y: text.y.val(),
+            max_x: x + width,
+            max_y: text.y.val() + height,
+        };
+        if let Some(path) = rect_path(rect) {
+            canvas.fill_path(&path, FillRule::NonZero, &Paint::Color(color), options);
+        }
+    }
+}
+
+fn path_bound(path: &Path) -> BoundingBox {
+    let bounds = path.bounds();
+    BoundingBox {
+        min_x: bounds.left(),
+        min_y: bounds.top(),
+        max_x: bounds.right(),
+        max_y: bounds.bottom(),
+    }
+}
+
+fn skia_transform(matrix: TransformMatrix) -> SkiaTransform {
+    let [a, b, c, d, e, f] = matrix.matrix;
+    SkiaTransform::from_row(a, b, c, d, e, f)
+}
+
+fn skia_color(color: Color, alpha: f32) -> SkiaColor {
+    let [r, g, b, a] = color.as_arr();
+    SkiaColor::from_rgba(r, g, b, (a * alpha).clamp(0.0, 1.0)).unwrap_or(SkiaColor::TRANSPARENT)
+}
+
+/// Returns the shader of the paint in the shape coordinates. The box and conic gradients are not supported by
+/// tiny-skia, so they are rasterized over the path bound into the `pattern` pixmap, which the shader samples.
+fn skia_shader<'a>(
+    paint: &Paint, alpha: f32, bound: BoundingBox, pattern: &'a mut Option<Pixmap>,
+) -> Option<Shader<'a>> {
+    let two_stops = |start_offset: f32, start_color: Color, end_color: Color| {
+        vec![
+            GradientStop::new(start_offset, skia_color(start_color, alpha)),
+            GradientStop::new(1.0, skia_color(end_color, alpha)),
+        ]
+    };
+    match *paint {
+        Paint::Color(color) => Some(Shader::SolidColor(skia_color(color, alpha))),
+        Paint::Gradient(Gradient::Linear {
+            start: (start_x, start_y),
+            end: (end_x, end_y),
+            start_color,
+            end_color,
+        }) => LinearGradient::new(
+            Point::from_xy(start_x, start_y),
+            Point::from_xy(end_x, end_y),
+            two_stops(0.0, start_color, end_color),
+            SpreadMode::Pad,
+            SkiaTransform::identity(),
+        )
+        .or_else(|| Some(Shader::SolidColor(skia_color(end_color, alpha)))),
+        Paint::Gradient(Gradient::Radial {
+            center: (x, y),
+            inner_radius,
+            outer_radius,
+            start_color,
+            end_color,
+        }) => {
+            let inner_offset = if outer_radius > 0.0 {
+                (inner_radius / outer_radius).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            RadialGradient::new(
+                Point::from_xy(x, y),
+                Point::from_xy(x, y),
+                outer_radius,
+                two_stops(inner_offset, start_color, end_color),
+                SpreadMode::Pad,
+                SkiaTransform::identity(),
+            )
+            .or_else(|| Some(Shader::SolidColor(skia_color(end_color, alpha))))
+        }
+        Paint::Gradient(Gradient::Box {
+            position: (x, y),
+            size: (width, height),
+            radius,
+            feather,
+            start_color,
+            end_color,
+        }) => {
+            // The box gradient is the feathered rounded rect, in the same way as nanovg draws it.
+            let (half_width, half_height) = (width / 2.0, height / 2.0);
+            let (cx, cy) = (x + half_width, y + half_height);
+            let feather = feather.max(1.0);
+            let [start, end] = [start_color.as_arr(), end_color.as_arr()];
+            gradient_pattern(bound, alpha, pattern, |px, py| {
+                let (dx, dy) = (
+                    (px - cx).abs() - (half_width - radius),
+                    (py - cy).abs() - (half_height - radius),
+                );
+                let outside = (dx.max(0.0) * dx.max(0.0) + dy.max(0.0) * dy.max(0.0)).sqrt();
+                let distance = dx.max(dy).min(0.0) + outside - radius;
+                let t = ((distance + feather * 0.5) / feather).clamp(0.0, 1.0);
+                let mut color = [0.0; 4];
+                for (idx, channel) in color.iter_mut().enumerate() {
+                    *channel = start[idx] + (end[idx] - start[idx]) * t;
+                }
+                color
+            })
+        }
+        Paint::Gradient(Gradient::Conic {
+            center: (cx, cy),
+            angle,
+            ref stops,
+        }) => gradient_pattern(bound, alpha, pattern, |px, py| {
+            let offset = (((py - cy).atan2(px - cx) - angle) / (2.0 * PI)).rem_euclid(1.0);
+            Gradient::stops_color(stops, offset).as_arr()
+        }),
+    }
+}
+
+/// Rasterizes the gradient over the bound into the pattern pixmap. The pixmap is limited by the maximal gradient
+/// size and stretched over the bound by the shader.
+fn gradient_pattern<'a>(
+    bound: BoundingBox, alpha: f32, pattern: &'a mut Option<Pixmap>, color_at: impl Fn(Real, Real) -> [f32; 4],
+) -> Option<Shader<'a>> {
+    let width = bound.width().ceil().clamp(1.0, MAX_GRADIENT_SIZE);
+    let height = bound.height().ceil().clamp(1.0, MAX_GRADIENT_SIZE);
+    let (scale_x, scale_y) = (bound.width().max(1.0) / width, bound.height().max(1.0) / height);
+    let mut pixmap = Pixmap::new(width as u32, height as u32)?;
+    let pixmap_width = pixmap.width() as usize;
+    for (idx, pixel) in pixmap.pixels_mut().iter_mut().enumerate() {
+        let x = bound.min_x + ((idx % pixmap_width) as Real + 0.5) * scale_x;
+        let y = bound.min_y + ((idx / pixmap_width) as Real + 0.5) * scale_y;
+        let [r, g, b, a] = color_at(x, y);
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        *pixel = tiny_skia::ColorU8::from_rgba(to_byte(r), to_byte(g), to_byte(b), to_byte(a * alpha)).premultiply();
+    }
+
+    let pixmap = pattern.insert(pixmap);
+    Some(Pattern::new(
+        pixmap.as_ref(),
+        SpreadMode::Pad,
+        FilterQuality::Bilinear,
+        1.0,
+        SkiaTransform::from_row(scale_x, 0.0, 0.0, scale_y, bound.min_x, bound.min_y),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use exgui_core::{Circle, Command, Model, Node, Prim, Rect};
+    use image::Rgba;
+
+    use super::*;
+
+    struct Scene;
+
+    impl Model for Scene {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Scene
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            Command::none()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            unimplemented!()
+        }
+    }
+
+    fn render(name: &'static str, shape: Shape) -> RgbaImage {
+        let mut node: Node<Scene> = Node::Prim(Prim::new(name.into(), shape, vec![], Default::default()));
+        let mut render = TinySkiaRender::new(40, 40).unwrap();
+        assert!(render.render(&mut node).unwrap());
+        render.snapshot()
+    }
+
+    #[test]
+    fn render_clipped_circle() {
+        let circle = Circle {
+            cx: 20.0.into(),
+            cy: 20.0.into(),
+            r: 15.0.into(),
+            fill: Some(Fill::color(Color::Red)),
+            clip: Clip::new_scissor(0.0.into(), 0.0.into(), 20.0.into(), 40.0.into()),
+            ..Default::default()
+        };
+        let image = render(Circle::NAME, Shape::Circle(circle));
+
+        let (red, white) = (Rgba([255, 0, 0, 255]), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(10, 20), red);
+        assert_eq!(*image.get_pixel(19, 10), red);
+        // Clipped out by the scissor.
+        assert_eq!(*image.get_pixel(20, 20), white);
+        assert_eq!(*image.get_pixel(30, 20), white);
+        // Out of the circle.
+        assert_eq!(*image.get_pixel(6, 6), white);
+        assert_eq!(*image.get_pixel(10, 38), white);
+    }
+
+    #[test]
+    fn render_linear_gradient() {
+        let rect = Rect {
+            width: 40.0.into(),
+            height: 10.0.into(),
+            fill: Some(Fill::gradient(Gradient::Linear {
+                start: (0.0, 0.0),
+                end: (40.0, 0.0),
+                start_color: Color::Black,
+                end_color: Color::White,
+            })),
+            ..Default::default()
+        };
+        let image = render(Rect::NAME, Shape::Rect(rect));
+
+        let row: Vec<_> = (0..40).map(|x| *image.get_pixel(x, 5)).collect();
+        let is_gray = |pixel: &Rgba<u8>| pixel[0] == pixel[1] && pixel[1] == pixel[2] && pixel[3] == 255;
+        assert!(row.iter().all(is_gray));
+        assert!(row.windows(2).all(|pixels| pixels[0][0] <= pixels[1][0]));
+        assert!(row[0][0] < 8 && row[39][0] > 247);
+        assert!((row[20][0] as i32 - 128).abs() < 8);
+        // The area out of the rect keeps the background.
+        assert_eq!(*image.get_pixel(20, 20), Rgba([255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,179 @@
+use exgui_core::{arc_to_cub_beziers, BoundingBox, PathCommand, Real};
+use tiny_skia::{Path, PathBuilder, Rect};
+
+/// Distance of the cubic bezier control points from the corner, relative to the radius of the quarter arc.
+const KAPPA: Real = 0.552_284_8;
+
+pub fn rect(bound: BoundingBox) -> Option<Rect> {
+    Rect::from_ltrb(bound.min_x, bound.min_y, bound.max_x, bound.max_y)
+}
+
+pub fn rect_path(bound: BoundingBox) -> Option<Path> {
+    Some(PathBuilder::from_rect(rect(bound)?))
+}
+
+/// Builds the rect with the rounded corners, the radii are in the order: top left, top right, bottom right and
+/// bottom left. The radii are limited by the half of the rect sides.
+pub fn rounded_rect_path(bound: BoundingBox, radii: [Real; 4]) -> Option<Path> {
+    let max_radius = bound.width().min(bound.height()) / 2.0;
+    let [top_left, top_right, bottom_right, bottom_left] = radii.map(|radius| radius.max(0.0).min(max_radius));
+    let (min_x, min_y, max_x, max_y) = (bound.min_x, bound.min_y, bound.max_x, bound.max_y);
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(min_x + top_left, min_y);
+    builder.line_to(max_x - top_right, min_y);
+    let k = top_right * KAPPA;
+    builder.cubic_to(max_x - top_right + k, min_y, max_x, min_y + top_right - k, max_x, min_y + top_right);
+    builder.line_to(max_x, max_y - bottom_right);
+    let k = bottom_right * KAPPA;
+    builder.cubic_to(
+        max_x,
+        max_y - bottom_right + k,
+        max_x - bottom_right + k,
+        max_y,
+        max_x - bottom_right,
+        max_y,
+    );
+    builder.line_to(min_x + bottom_left, max_y);
+    let k = bottom_left * KAPPA;
+    builder.cubic_to(min_x + bottom_left - k, max_y, min_x, max_y - bottom_left + k, min_x, max_y - bottom_left);
+    builder.line_to(min_x, min_y + top_left);
+    let k = top_left * KAPPA;
+    builder.cubic_to(min_x, min_y + top_left - k, min_x + top_left - k, min_y, min_x + top_left, min_y);
+    builder.close();
+    builder.finish()
+}
+
+pub fn ellipse_path(cx: Real, cy: Real, rx: Real, ry: Real) -> Option<Path> {
+    PathBuilder::from_oval(Rect::from_xywh(cx - rx, cy - ry, 2.0 * rx, 2.0 * ry)?)
+}
+
+/// Builds the closed contours of the polygon.
+pub fn polygon_path(contours: &[Vec<[Real; 2]>]) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    for contour in contours {
+        let mut points = contour.iter();
+        if let Some(first) = points.next() {
+            builder.move_to(first[0], first[1]);
+            for xy in points {
+                builder.line_to(xy[0], xy[1]);
+            }
+            builder.close();
+        }
+    }
+    builder.finish()
+}
+
+/// Builds the path of the commands, the arcs are converted into the cubic beziers. The path without the segments is
+/// `None`.
+pub fn commands_path(commands: &[PathCommand]) -> Option<Path> {
+    use exgui_core::PathCommand::*;
+
+    let mut builder = PathBuilder::new();
+    let mut last_xy = [0.0, 0.0];
+    let mut bez_ctrls = [[0.0, 0.0], [0.0, 0.0]];
+
+    for cmd in commands.iter() {
+        match *cmd {
+            Move(xy) => {
+                last_xy = xy;
+                builder.move_to(last_xy[0], last_xy[1]);
+            }
+            MoveRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.move_to(last_xy[0], last_xy[1]);
+            }
+            Line(xy) => {
+                last_xy = xy;
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            LineRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            LineAlonX(x) => {
+                last_xy[0] = x;
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            LineAlonXRel(x) => {
+                last_xy[0] += x;
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            LineAlonY(y) => {
+                last_xy[1] = y;
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            LineAlonYRel(y) => {
+                last_xy[1] += y;
+                builder.line_to(last_xy[0], last_xy[1]);
+            }
+            Close => builder.close(),
+            BezCtrl(xy) => {
+                bez_ctrls = [bez_ctrls[1], xy];
+            }
+            BezCtrlRel(xy) => {
+                bez_ctrls = [bez_ctrls[1], [last_xy[0] + xy[0], last_xy[1] + xy[1]]];
+            }
+            BezReflectCtrl => {
+                let reflected = [2.0 * last_xy[0] - bez_ctrls[1][0], 2.0 * last_xy[1] - bez_ctrls[1][1]];
+                bez_ctrls = [bez_ctrls[1], reflected];
+            }
+            QuadBezTo(xy) => {
+                last_xy = xy;
+                builder.quad_to(bez_ctrls[1][0], bez_ctrls[1][1], last_xy[0], last_xy[1]);
+            }
+            QuadBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.quad_to(bez_ctrls[1][0], bez_ctrls[1][1], last_xy[0], last_xy[1]);
+            }
+            CubBezTo(xy) => {
+                last_xy = xy;
+                builder.cubic_to(
+                    bez_ctrls[0][0],
+                    bez_ctrls[0][1],
+                    bez_ctrls[1][0],
+                    bez_ctrls[1][1],
+                    last_xy[0],
+                    last_xy[1],
+                );
+            }
+            CubBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.cubic_to(
+                    bez_ctrls[0][0],
+                    bez_ctrls[0][1],
+                    bez_ctrls[1][0],
+                    bez_ctrls[1][1],
+                    last_xy[0],
+                    last_xy[1],
+                );
+            }
+            Arc {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    builder.cubic_to(ctrl1[0], ctrl1[1], ctrl2[0], ctrl2[1], xy[0], xy[1]);
+                }
+                last_xy = to;
+            }
+            ArcRel {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                let to = [last_xy[0] + to[0], last_xy[1] + to[1]];
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    builder.cubic_to(ctrl1[0], ctrl1[1], ctrl2[0], ctrl2[1], xy[0], xy[1]);
+                }
+                last_xy = to;
+            }
+        }
+    }
+    builder.finish()
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use font_kit::outline::OutlineSink;
+use lyon::{
+    math::{point, Point},
+    path::Path as LyonPath,
+    tessellation::{BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers},
+};
+use pathfinder_geometry::{line_segment::LineSegment2F, vector::Vector2F};
+
+use crate::WgpuRenderError;
+
+/// Tolerance of the glyph tessellation in the em units.
+const GLYPH_TOLERANCE: f32 = 0.005;
+
+/// Glyph mesh in the em units with the y axis going down.
+pub type GlyphMesh = VertexBuffers<Point, u32>;
+
+/// Fonts of the render, the glyphs are tessellated once and cached.
+pub type FontCache = exgui_core::FontCache<Arc<GlyphMesh>>;
+
+/// Returns the cached mesh of the glyph, the glyph is tessellated on the first use.
+pub fn glyph_mesh(fonts: &mut FontCache, name: &str, glyph_id: u32) -> Result<Arc<GlyphMesh>, WgpuRenderError> {
+    fonts.glyph(name, glyph_id, |fonts| {
+        let mut sink = GlyphSink::new(1.0 / fonts.units_per_em(name)? as f32);
+        fonts.outline(name, glyph_id, &mut sink)?;
+
+        let mut mesh = GlyphMesh::new();
+        FillTessellator::new()
+            .tessellate_path(
+                &sink.builder.build(),
+                &FillOptions::tolerance(GLYPH_TOLERANCE),
+                &mut BuffersBuilder::new(&mut mesh, |vertex: FillVertex| vertex.position()),
+            )
+            .map_err(WgpuRenderError::TessellationError)?;
+        Ok(Arc::new(mesh))
+    })
+}
+
+/// Collects the glyph outline into the lyon path, the font units are scaled to the em units and flipped downward.
+struct GlyphSink {
+    builder: lyon::path::builder::WithSvg<lyon::path::BuilderImpl>,
+    scale: f32,
+}
+
+impl GlyphSink {
+    fn new(scale: f32) -> Self {
+        Self {
+            builder: LyonPath::svg_builder(),
+            scale,
+        }
+    }
+
+    fn point(&self, vector: Vector2F) -> Point {
+        point(vector.x() * self.scale, -vector.y() * self.scale)
+    }
+}
+
+impl OutlineSink for GlyphSink {
+    fn move_to(&mut self, to: Vector2F) {
+        let to = self.point(to);
+        self.builder.move_to(to);
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        let to = self.point(to);
+        self.builder.line_to(to);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let (ctrl, to) = (self.point(ctrl), self.point(to));
+        self.builder.quadratic_bezier_to(ctrl, to);
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let (ctrl1, ctrl2, to) = (self.point(ctrl.from()), self.point(ctrl.to()), self.point(to));
+        self.builder.cubic_bezier_to(ctrl1, ctrl2, to);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
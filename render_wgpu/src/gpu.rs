@@ -0,0 +1,428 @@
+use std::{collections::HashMap, mem, num::NonZeroU64};
+
+use exgui_core::Color;
+use wgpu::util::DeviceExt;
+
+use crate::scene::{DrawUniform, Scene, Stencil, Vertex};
+
+const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Stencil8;
+
+/// Pipeline and the GPU resources of the render, they are created once the device is set.
+pub struct Gpu {
+    pipeline: wgpu::RenderPipeline,
+    /// Pipeline of the draws, which are clipped by the stencil buffer.
+    clipped_pipeline: wgpu::RenderPipeline,
+    /// Pipeline of the clip masks, which are written into the stencil buffer.
+    mask_pipeline: wgpu::RenderPipeline,
+    uniform_layout: wgpu::BindGroupLayout,
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    globals_buffer: wgpu::Buffer,
+    draw_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    /// Size of the draw uniform aligned to the minimal offset of the dynamic uniform buffer.
+    uniform_stride: u64,
+    /// Texture of the draws, that do not sample the image.
+    white: wgpu::BindGroup,
+    textures: HashMap<String, GpuTexture>,
+    /// Stencil buffer of the clip masks, it is recreated when the size of the target changes.
+    stencil: Option<(wgpu::TextureView, wgpu::Extent3d)>,
+}
+
+pub struct GpuTexture {
+    bind_group: wgpu::BindGroup,
+}
+
+impl Gpu {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("exgui shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("exgui uniforms"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: NonZeroU64::new(mem::size_of::<DrawUniform>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("exgui texture"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("exgui pipeline layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = create_pipeline(device, &layout, &shader, format, Stencil::None);
+        let clipped_pipeline = create_pipeline(device, &layout, &shader, format, Stencil::Test(0));
+        let mask_pipeline = create_pipeline(device, &layout, &shader, format, Stencil::Write(0));
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("exgui sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let globals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("exgui globals"),
+            size: mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let alignment = u64::from(device.limits().min_uniform_buffer_offset_alignment);
+        let uniform_stride = (mem::size_of::<DrawUniform>() as u64).div_ceil(alignment) * alignment;
+        let draw_buffer = create_draw_buffer(device, uniform_stride);
+        let uniform_bind_group = create_uniform_bind_group(device, &uniform_layout, &globals_buffer, &draw_buffer);
+
+        let white = create_texture(device, queue, &texture_layout, &sampler, 1, 1, &[255; 4]).bind_group;
+
+        Self {
+            pipeline,
+            clipped_pipeline,
+            mask_pipeline,
+            uniform_layout,
+            texture_layout,
+            sampler,
+            globals_buffer,
+            draw_buffer,
+            uniform_bind_group,
+            uniform_stride,
+            white,
+            textures: HashMap::new(),
+            stencil: None,
+        }
+    }
+
+    pub fn texture(&self, key: &str) -> Option<&GpuTexture> {
+        self.textures.get(key)
+    }
+
+    /// Uploads the RGBA image with the straight alpha, the shader premultiplies the sampled colors.
+    pub fn add_texture(
+        &mut self, device: &wgpu::Device, queue: &wgpu::Queue, key: impl Into<String>, width: u32, height: u32,
+        data: &[u8],
+    ) {
+        let texture = create_texture(device, queue, &self.texture_layout, &self.sampler, width, height, data);
+        self.textures.insert(key.into(), texture);
+    }
+
+    /// Draws the scene into the target texture, it is cleared with the background color first. The `width` and
+    /// `height` are the target size in the units of the scene, i.e. the logical pixels, so the scene is scaled to the
+    /// whole target.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self, device: &wgpu::Device, queue: &wgpu::Queue, target: &wgpu::Texture, scene: &Scene, width: f32,
+        height: f32, background_color: Color,
+    ) {
+        let view = &target.create_view(&wgpu::TextureViewDescriptor::default());
+        let size = target.size();
+        if self.stencil.as_ref().map(|(_, stencil_size)| *stencil_size) != Some(size) {
+            self.stencil = Some((create_stencil(device, size), size));
+        }
+
+        queue.write_buffer(
+            &self.globals_buffer,
+            0,
+            bytemuck::cast_slice(&[width, height, 0.0, 0.0]),
+        );
+
+        let uniforms_size = self.uniform_stride * scene.draws.len().max(1) as u64;
+        if uniforms_size > self.draw_buffer.size() {
+            self.draw_buffer = create_draw_buffer(device, uniforms_size.next_power_of_two());
+            self.uniform_bind_group =
+                create_uniform_bind_group(device, &self.uniform_layout, &self.globals_buffer, &self.draw_buffer);
+        }
+        let mut uniforms = vec![0; uniforms_size as usize];
+        for (idx, draw) in scene.draws.iter().enumerate() {
+            let offset = idx * self.uniform_stride as usize;
+            let uniform = bytemuck::bytes_of(&draw.uniform);
+            uniforms[offset..offset + uniform.len()].copy_from_slice(uniform);
+        }
+        queue.write_buffer(&self.draw_buffer, 0, &uniforms);
+
+        let buffers = if scene.draws.is_empty() {
+            None
+        } else {
+            let vertices = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("exgui vertices"),
+                contents: bytemuck::cast_slice(&scene.geometry.vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let indices = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("exgui indices"),
+                contents: bytemuck::cast_slice(&scene.geometry.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+            Some((vertices, indices))
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("exgui encoder"),
+        });
+        {
+            let [r, g, b, a] = background_color.as_arr();
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("exgui pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: f64::from(r * a),
+                            g: f64::from(g * a),
+                            b: f64::from(b * a),
+                            a: f64::from(a),
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: self.stencil.as_ref().map(|(stencil, _)| {
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view: stencil,
+                        depth_ops: None,
+                        stencil_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(0),
+                            store: wgpu::StoreOp::Discard,
+                        }),
+                    }
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if let Some((vertices, indices)) = &buffers {
+                pass.set_vertex_buffer(0, vertices.slice(..));
+                pass.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint32);
+                for (idx, draw) in scene.draws.iter().enumerate() {
+                    match draw.stencil {
+                        Stencil::None => pass.set_pipeline(&self.pipeline),
+                        Stencil::Test(reference) => {
+                            pass.set_pipeline(&self.clipped_pipeline);
+                            pass.set_stencil_reference(reference);
+                        },
+                        Stencil::Write(reference) => {
+                            pass.set_pipeline(&self.mask_pipeline);
+                            pass.set_stencil_reference(reference);
+                        },
+                    }
+                    let offset = (idx as u64 * self.uniform_stride) as u32;
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[offset]);
+                    let texture = draw
+                        .texture
+                        .as_ref()
+                        .and_then(|key| self.textures.get(key))
+                        .map(|texture| &texture.bind_group)
+                        .unwrap_or(&self.white);
+                    pass.set_bind_group(1, texture, &[]);
+                    pass.draw_indexed(draw.indices.clone(), 0, 0..1);
+                }
+            }
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Creates the pipeline of the draws with the `stencil` kind, the reference value of the stencil is ignored.
+fn create_pipeline(
+    device: &wgpu::Device, layout: &wgpu::PipelineLayout, shader: &wgpu::ShaderModule, format: wgpu::TextureFormat,
+    stencil: Stencil,
+) -> wgpu::RenderPipeline {
+    let (compare, pass_op, write_mask) = match stencil {
+        Stencil::None => (
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Keep,
+            wgpu::ColorWrites::ALL,
+        ),
+        Stencil::Test(_) => (
+            wgpu::CompareFunction::Equal,
+            wgpu::StencilOperation::Keep,
+            wgpu::ColorWrites::ALL,
+        ),
+        Stencil::Write(_) => (
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Replace,
+            wgpu::ColorWrites::empty(),
+        ),
+    };
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op,
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("exgui pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Vertex>() as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &Vertex::ATTRIBUTES,
+            }],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: STENCIL_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState {
+                front: face,
+                back: face,
+                read_mask: 0xff,
+                write_mask: 0xff,
+            },
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask,
+            })],
+        }),
+        multiview: None,
+    })
+}
+
+fn create_stencil(device: &wgpu::Device, size: wgpu::Extent3d) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("exgui stencil"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: STENCIL_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+fn create_draw_buffer(device: &wgpu::Device, size: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("exgui draws"),
+        size,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_uniform_bind_group(
+    device: &wgpu::Device, layout: &wgpu::BindGroupLayout, globals_buffer: &wgpu::Buffer, draw_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("exgui uniforms"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: globals_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: draw_buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(mem::size_of::<DrawUniform>() as u64),
+                }),
+            },
+        ],
+    })
+}
+
+fn create_texture(
+    device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout, sampler: &wgpu::Sampler, width: u32,
+    height: u32, data: &[u8],
+) -> GpuTexture {
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("exgui image"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        size,
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("exgui image"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    });
+    GpuTexture { bind_group }
+}
@@ -0,0 +1,762 @@
+use std::{borrow::Cow, collections::HashMap, fmt, mem, path::Path, rc::Rc, sync::Arc};
+
+use exgui_core::{
+    recalc_in_view, BoundingBox, Clip, Color, CompositeShape, Damage, FaceMatch, Fill, FillRule, FontError, GlyphPos,
+    Gradient, LayoutMeasurer, Paint, Real, Render, Shape, ShapeDefaults, Stroke, Text, TextCache, TextFace,
+    TextMeasurer, TextMetrics, Theme, Transform, TransformMatrix,
+};
+use font_kit::{error::FontLoadingError, font::Font};
+use image::{ImageError, RgbaImage};
+use lyon::{
+    math::{point, vector, Angle, Box2D},
+    path::{builder::BorderRadii, Path as LyonPath, Winding},
+    tessellation::TessellationError,
+};
+
+pub use self::font::FontCache;
+use self::{
+    font::glyph_mesh,
+    gpu::Gpu,
+    scene::{commands_path, conic_image_data, conic_image_key, rect_path, Placement, Scene},
+};
+
+mod font;
+mod gpu;
+mod scene;
+
+/// Width of the color ramp of the conic gradients.
+const CONIC_IMAGE_WIDTH: usize = 256;
+const CARET_WIDTH: Real = 1.5;
+/// Horizontal shift of the synthetic bold overdraw, relative to the font size.
+const SYNTHETIC_BOLD_SHIFT: Real = 0.04;
+/// Slant of the synthetic italic.
+const SYNTHETIC_ITALIC_SKEW: Real = 0.2;
+
+#[derive(Debug)]
+pub enum WgpuRenderError {
+    ContextIsNotInit,
+    SurfaceError(wgpu::SurfaceError),
+    TessellationError(TessellationError),
+    CreateFontError(FontLoadingError, String),
+    CreateImageError(ImageError, String),
+    FontError(FontError),
+}
+
+impl From<FontError> for WgpuRenderError {
+    fn from(err: FontError) -> Self {
+        WgpuRenderError::FontError(err)
+    }
+}
+
+/// Decoded images of the image shapes and the ramps of the conic gradients, they are uploaded to the GPU before the
+/// frame is drawn.
+pub type ImageCache = HashMap<String, RgbaImage>;
+
+/// Shape state, which the geometry is placed and painted with.
+#[derive(Clone)]
+struct DrawOptions<'a> {
+    alpha: f32,
+    clip: Cow<'a, Clip>,
+    transform: TransformMatrix,
+}
+
+impl<'a> DrawOptions<'a> {
+    fn new(transparency: Real, clip: &'a Clip, transform: &Transform, defaults: &'a ShapeDefaults) -> Self {
+        let transform = if transform.is_not_exist() {
+            TransformMatrix::identity()
+        } else {
+            transform.calculated_matrix().unwrap_or_else(|| transform.matrix())
+        };
+        Self {
+            alpha: (1.0 - transparency) * (1.0 - defaults.transparency),
+            clip: clip.intersect(&defaults.clip),
+            transform,
+        }
+    }
+
+    fn placement(&self, local: TransformMatrix) -> Placement {
+        Placement {
+            local,
+            global: self.transform,
+            alpha: self.alpha,
+        }
+    }
+}
+
+/// The render tessellates the shapes by lyon and draws the triangles by wgpu, so it runs on Vulkan, Metal, DX12 and
+/// WebGPU. The device and the surface are created by the application, e.g. for a winit window. The text glyphs are
+/// tessellated from the font outlines and cached. The layout is calculated by the shared `exgui_core::recalc`.
+///
+/// The layer groups are drawn directly into the frame, so their filters are not applied.
+pub struct WgpuRender {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    gpu: Option<Gpu>,
+    pub images: ImageCache,
+    pub fonts: FontCache,
+    /// Measured text lines of the layout, it is cleared when the fonts are loaded.
+    pub text_cache: TextCache,
+    pub width: f32,
+    pub height: f32,
+    pub device_pixel_ratio: f32,
+    pub theme: Rc<Theme>,
+    theme_changed: bool,
+    view_transform: TransformMatrix,
+    view_changed: bool,
+    background_color: Color,
+    scene: Scene,
+}
+
+impl fmt::Debug for WgpuRender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(WgpuRender))
+            .field("config", &self.config)
+            .field("images", &self.images.keys().collect::<Vec<_>>())
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("device_pixel_ratio", &self.device_pixel_ratio)
+            .field("theme", &self.theme)
+            .field("view_transform", &self.view_transform)
+            .finish()
+    }
+}
+
+impl Render for WgpuRender {
+    type Error = WgpuRenderError;
+
+    fn init(&mut self, background_color: Color) -> Result<(), Self::Error> {
+        self.background_color = background_color;
+        if self.gpu.is_none() {
+            self.surface.configure(&self.device, &self.config);
+            self.gpu = Some(Gpu::new(&self.device, &self.queue, self.config.format));
+        }
+        Ok(())
+    }
+
+    fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {
+        self.width = physical_width as f32;
+        self.height = physical_height as f32;
+        self.device_pixel_ratio = device_pixel_ratio as f32;
+
+        let (width, height) = (physical_width.max(1), physical_height.max(1));
+        if (width, height) != (self.config.width, self.config.height) {
+            self.config.width = width;
+            self.config.height = height;
+            if self.gpu.is_some() {
+                self.surface.configure(&self.device, &self.config);
+            }
+        }
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = Rc::new(theme);
+        self.theme_changed = true;
+    }
+
+    fn set_view_transform(&mut self, transform: TransformMatrix) {
+        if transform != self.view_transform {
+            self.view_transform = transform;
+            self.view_changed = true;
+        }
+    }
+
+    fn view_transform(&self) -> TransformMatrix {
+        self.view_transform
+    }
+
+    fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error> {
+        if self.gpu.is_none() {
+            return Err(WgpuRenderError::ContextIsNotInit);
+        }
+        let theme_changed = mem::take(&mut self.theme_changed);
+        if theme_changed {
+            node.clear_recalc_cache();
+        }
+        let view_changed = mem::take(&mut self.view_changed);
+        let need_recalc = node.need_recalc().unwrap_or(true) || theme_changed || view_changed;
+        let need_redraw = node.need_redraw().unwrap_or(true) || theme_changed || view_changed;
+        if need_recalc {
+            // The layout is calculated in the logical pixels, the shader scales them to the surface.
+            let bound = BoundingBox {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: (self.width / self.device_pixel_ratio) as Real,
+                max_y: (self.height / self.device_pixel_ratio) as Real,
+            };
+            let theme = self.theme.clone();
+            recalc_in_view(self, node, bound, self.view_transform, theme, &mut Damage::new())?;
+        }
+        if need_redraw {
+            let mut scene = mem::take(&mut self.scene);
+            scene.clear();
+            let result = self.draw_frame(&mut scene, node);
+            self.scene = scene;
+            result?;
+        }
+        Ok(need_redraw)
+    }
+}
+
+impl TextMeasurer for WgpuRender {
+    type Error = WgpuRenderError;
+
+    fn measure(&mut self, text: &str, font_name: &str, font_size: Real) -> Result<(Real, TextMetrics), Self::Error> {
+        let name = self.fonts.resolve(font_name)?;
+        Self::measure_font(&self.fonts, &name, text, font_size)
+    }
+
+    fn measure_face(
+        &mut self, text: &str, face: TextFace, font_size: Real,
+    ) -> Result<(Real, TextMetrics), Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Self::measure_font(&self.fonts, &face_match.name, text, font_size)
+    }
+
+    fn glyph_positions(&mut self, text: &str, face: TextFace, font_size: Real) -> Result<Vec<GlyphPos>, Self::Error> {
+        let face_match = self.fonts.resolve_face(face.family, face.weight, face.style)?;
+        Ok(self.fonts.glyph_positions(&face_match.name, text, font_size)?)
+    }
+}
+
+impl LayoutMeasurer for WgpuRender {
+    fn image_size(&mut self, src: &str) -> Result<(Real, Real), Self::Error> {
+        let image = Self::cached_image(&mut self.images, src)?;
+        Ok((image.width() as Real, image.height() as Real))
+    }
+
+    /// Creates the color ramps of the conic gradients of the shape.
+    fn prepare_shape(&mut self, shape: &Shape, defaults: &ShapeDefaults) -> Result<(), Self::Error> {
+        let mut paints = vec![
+            shape.fill().or(defaults.fill.as_ref()).map(|fill| fill.paint.clone()),
+            shape
+                .stroke()
+                .or(defaults.stroke.as_ref())
+                .map(|stroke| stroke.paint.clone()),
+        ];
+        if let Shape::Custom(custom) = shape {
+            for custom_path in custom.shape.draw() {
+                paints.push(custom_path.fill.map(|fill| fill.paint));
+                paints.push(custom_path.stroke.map(|stroke| stroke.paint));
+            }
+        }
+
+        for paint in paints.into_iter().flatten() {
+            if let Paint::Gradient(Gradient::Conic { stops, .. }) = paint {
+                self.images.entry(conic_image_key(&stops)).or_insert_with(|| {
+                    RgbaImage::from_raw(CONIC_IMAGE_WIDTH as u32, 1, conic_image_data(&stops, CONIC_IMAGE_WIDTH))
+                        .expect("The conic image data has the size of the image")
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn text_cache(&mut self) -> Option<&mut TextCache> {
+        Some(&mut self.text_cache)
+    }
+}
+
+impl WgpuRender {
+    /// Creates the render, which draws into the surface of the `format`. The surface is configured by the `init`.
+    pub fn new(
+        device: wgpu::Device, queue: wgpu::Queue, surface: wgpu::Surface<'static>, format: wgpu::TextureFormat,
+        width: u32, height: u32,
+    ) -> Self {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+        Self {
+            device,
+            queue,
+            surface,
+            config,
+            gpu: None,
+            images: ImageCache::new(),
+            fonts: FontCache::default(),
+            text_cache: TextCache::new(),
+            width: width as f32,
+            height: height as f32,
+            device_pixel_ratio: 1.0,
+            theme: Rc::new(Theme::default()),
+            theme_changed: false,
+            view_transform: TransformMatrix::identity(),
+            view_changed: false,
+            background_color: Color::White,
+            scene: Scene::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Rc::new(theme);
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.config.present_mode = present_mode;
+        self
+    }
+
+    pub fn with_device_pixel_ratio(mut self, device_pixel_ratio: f32) -> Self {
+        self.device_pixel_ratio = device_pixel_ratio;
+        self
+    }
+
+    pub fn load_font(
+        &mut self, name: impl Into<String>, path: impl AsRef<Path>,
+    ) -> Result<(), <Self as Render>::Error> {
+        let display_path = path.as_ref().display().to_string();
+        let font = Font::from_path(path.as_ref(), 0).map_err(|e| WgpuRenderError::CreateFontError(e, display_path))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    /// Loads the font from the data embedded into the binary, e.g. by `include_bytes!`.
+    pub fn load_font_from_memory(
+        &mut self, name: impl Into<String>, data: &'static [u8],
+    ) -> Result<(), <Self as Render>::Error> {
+        let name = name.into();
+        let font = Font::from_bytes(Arc::new(data.to_vec()), 0)
+            .map_err(|e| WgpuRenderError::CreateFontError(e, name.clone()))?;
+        self.fonts.add(name, font);
+        self.text_cache.clear();
+        Ok(())
+    }
+
+    pub fn load_image(&mut self, src: impl Into<String>) -> Result<(), <Self as Render>::Error> {
+        Self::cached_image(&mut self.images, &src.into())?;
+        Ok(())
+    }
+
+    fn cached_image<'a>(images: &'a mut ImageCache, src: &str) -> Result<&'a RgbaImage, WgpuRenderError> {
+        if !images.contains_key(src) {
+            let image = image::open(src).map_err(|e| WgpuRenderError::CreateImageError(e, src.to_string()))?;
+            images.insert(src.to_string(), image.to_rgba8());
+        }
+        Ok(&images[src])
+    }
+
+    fn measure_font(
+        fonts: &FontCache, name: &str, text: &str, font_size: Real,
+    ) -> Result<(Real, TextMetrics), WgpuRenderError> {
+        let advance = fonts
+            .glyph_positions(name, text, font_size)?
+            .last()
+            .map(|pos| pos.x + pos.width)
+            .unwrap_or(0.0);
+        Ok((advance, fonts.metrics(name, font_size)?))
+    }
+
+    /// Builds the scene of the tree and draws it into the current surface texture.
+    fn draw_frame(&mut self, scene: &mut Scene, node: &dyn CompositeShape) -> Result<(), WgpuRenderError> {
+        let theme = &self.theme;
+        Self::render_composite(
+            scene,
+            &mut self.fonts,
+            &self.images,
+            node,
+            &mut ShapeDefaults::new(theme.clone()),
+        )?;
+        // The portals are drawn last with the root defaults, so the ancestor clips do not apply to them.
+        for portal in node.portals() {
+            Self::render_composite(
+                scene,
+                &mut self.fonts,
+                &self.images,
+                portal,
+                &mut ShapeDefaults::new(theme.clone()),
+            )?;
+        }
+
+        let gpu = self.gpu.as_mut().ok_or(WgpuRenderError::ContextIsNotInit)?;
+        for (key, image) in &self.images {
+            if gpu.texture(key).is_none() {
+                gpu.add_texture(
+                    &self.device,
+                    &self.queue,
+                    key.clone(),
+                    image.width(),
+                    image.height(),
+                    image,
+                );
+            }
+        }
+
+        let frame = self
+            .surface
+            .get_current_texture()
+            .map_err(WgpuRenderError::SurfaceError)?;
+        gpu.draw(
+            &self.device,
+            &self.queue,
+            &frame.texture,
+            scene,
+            self.config.width as f32 / self.device_pixel_ratio,
+            self.config.height as f32 / self.device_pixel_ratio,
+            self.background_color,
+        );
+        frame.present();
+        Ok(())
+    }
+
+    fn render_composite(
+        scene: &mut Scene, fonts: &mut FontCache, images: &ImageCache, composite: &dyn CompositeShape,
+        defaults: &mut ShapeDefaults,
+    ) -> Result<(), WgpuRenderError> {
+        let mut styled_defaults = defaults.styled(composite.shape());
+        let defaults = styled_defaults.as_mut().unwrap_or(defaults);
+        if let Some(shape) = composite.shape() {
+            match shape {
+                Shape::Rect(rect) => {
+                    let (x, y) = (rect.x.val(), rect.y.val());
+                    let (width, height) = (rect.width.val(), rect.height.val());
+                    let path = if let Some(rounding) = rect.rounding {
+                        let mut builder = LyonPath::builder();
+                        builder.add_rounded_rectangle(
+                            &Box2D::new(point(x, y), point(x + width, y + height)),
+                            &BorderRadii {
+                                top_left: rounding.top_left.val(),
+                                top_right: rounding.top_right.val(),
+                                bottom_left: rounding.bottom_left.val(),
+                                bottom_right: rounding.bottom_right.val(),
+                            },
+                            Winding::Positive,
+                        );
+                        builder.build()
+                    } else {
+                        rect_path(BoundingBox {
+                            min_x: rect.x.val(),
+                            min_y: rect.y.val(),
+                            max_x: rect.x.val() + rect.width.val(),
+                            max_y: rect.y.val() + rect.height.val(),
+                        })
+                    };
+                    Self::draw_path(
+                        scene,
+                        &path,
+                        FillRule::NonZero,
+                        rect.fill.as_ref().or(defaults.fill.as_ref()),
+                        rect.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(rect.transparency, &rect.clip, &rect.transform, defaults),
+                    )?;
+                }
+                Shape::Circle(circle) => {
+                    let mut builder = LyonPath::builder();
+                    builder.add_circle(
+                        point(circle.cx.val(), circle.cy.val()),
+                        circle.r.val(),
+                        Winding::Positive,
+                    );
+                    Self::draw_path(
+                        scene,
+                        &builder.build(),
+                        FillRule::NonZero,
+                        circle.fill.as_ref().or(defaults.fill.as_ref()),
+                        circle.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(circle.transparency, &circle.clip, &circle.transform, defaults),
+                    )?;
+                }
+                Shape::Ellipse(ellipse) => {
+                    let mut builder = LyonPath::builder();
+                    builder.add_ellipse(
+                        point(ellipse.cx.val(), ellipse.cy.val()),
+                        vector(ellipse.rx.val(), ellipse.ry.val()),
+                        Angle::radians(0.0),
+                        Winding::Positive,
+                    );
+                    Self::draw_path(
+                        scene,
+                        &builder.build(),
+                        FillRule::NonZero,
+                        ellipse.fill.as_ref().or(defaults.fill.as_ref()),
+                        ellipse.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ellipse.transparency, &ellipse.clip, &ellipse.transform, defaults),
+                    )?;
+                }
+                Shape::Path(path) => {
+                    Self::draw_path(
+                        scene,
+                        &commands_path(&path.cmd),
+                        FillRule::NonZero,
+                        path.fill.as_ref().or(defaults.fill.as_ref()),
+                        path.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(path.transparency, &path.clip, &path.transform, defaults),
+                    )?;
+
+                    for marker in path.marker_start.iter().chain(path.marker_end.iter()) {
+                        Self::render_composite(scene, fonts, images, marker.shape.as_ref(), &mut defaults.clone())?;
+                    }
+                }
+                Shape::Polygon(polygon) => {
+                    let mut builder = LyonPath::builder();
+                    for contour in &polygon.contours {
+                        let mut points = contour.iter();
+                        if let Some(first) = points.next() {
+                            builder.begin(point(first[0], first[1]));
+                            for xy in points {
+                                builder.line_to(point(xy[0], xy[1]));
+                            }
+                            builder.close();
+                        }
+                    }
+                    Self::draw_path(
+                        scene,
+                        &builder.build(),
+                        polygon.fill_rule,
+                        polygon.fill.as_ref().or(defaults.fill.as_ref()),
+                        polygon.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(polygon.transparency, &polygon.clip, &polygon.transform, defaults),
+                    )?;
+                }
+                Shape::Image(image) => {
+                    let bound = BoundingBox {
+                        min_x: image.x.val(),
+                        min_y: image.y.val(),
+                        max_x: image.x.val() + image.width.val(),
+                        max_y: image.y.val() + image.height.val(),
+                    };
+                    let path = rect_path(bound);
+                    let options = DrawOptions::new(image.transparency, &image.clip, &image.transform, defaults);
+                    let placement = options.placement(TransformMatrix::identity());
+                    if let Some(fill) = image.fill.as_ref().or(defaults.fill.as_ref()) {
+                        scene.fill_path(&path, FillRule::NonZero, &fill.paint, &options.clip, placement)?;
+                    }
+                    if images.contains_key(&image.src) {
+                        scene.fill_image(&image.src, bound, &options.clip, placement)?;
+                    }
+                    if let Some(stroke) = image.stroke.as_ref().or(defaults.stroke.as_ref()) {
+                        scene.stroke_path(&path, stroke, &options.clip, placement)?;
+                    }
+                }
+                Shape::Text(text) => {
+                    Self::render_text(scene, fonts, text, defaults)?;
+                }
+                Shape::Custom(custom) => {
+                    for custom_path in custom.shape.draw() {
+                        let fill = custom_path
+                            .fill
+                            .as_ref()
+                            .or(custom.fill.as_ref())
+                            .or(defaults.fill.as_ref());
+                        let stroke = custom_path
+                            .stroke
+                            .as_ref()
+                            .or(custom.stroke.as_ref())
+                            .or(defaults.stroke.as_ref());
+                        Self::draw_path(
+                            scene,
+                            &commands_path(&custom_path.cmd),
+                            FillRule::NonZero,
+                            fill,
+                            stroke,
+                            &DrawOptions::new(custom.transparency, &custom.clip, &custom.transform, defaults),
+                        )?;
+                    }
+                }
+                Shape::Sector(sector) => {
+                    Self::draw_path(
+                        scene,
+                        &commands_path(&sector.commands()),
+                        FillRule::NonZero,
+                        sector.fill.as_ref().or(defaults.fill.as_ref()),
+                        sector.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(sector.transparency, &sector.clip, &sector.transform, defaults),
+                    )?;
+                }
+                Shape::Ring(ring) => {
+                    // The full ring is two circles, the inner one is the hole.
+                    let fill_rule = if ring.is_full() {
+                        FillRule::EvenOdd
+                    } else {
+                        FillRule::NonZero
+                    };
+                    Self::draw_path(
+                        scene,
+                        &commands_path(&ring.commands()),
+                        fill_rule,
+                        ring.fill.as_ref().or(defaults.fill.as_ref()),
+                        ring.stroke.as_ref().or(defaults.stroke.as_ref()),
+                        &DrawOptions::new(ring.transparency, &ring.clip, &ring.transform, defaults),
+                    )?;
+                }
+                Shape::Group(group) => {
+                    defaults.apply_group(group);
+                }
+            }
+        }
+        if let Some(children) = composite.children_in_draw_order() {
+            for child in children.into_iter().filter(|child| !child.is_portal()) {
+                Self::render_composite(scene, fonts, images, child, defaults)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_path(
+        scene: &mut Scene, path: &LyonPath, fill_rule: FillRule, fill: Option<&Fill>, stroke: Option<&Stroke>,
+        options: &DrawOptions,
+    ) -> Result<(), WgpuRenderError> {
+        let placement = options.placement(TransformMatrix::identity());
+        if let Some(fill) = fill {
+            scene.fill_path(path, fill_rule, &fill.paint, &options.clip, placement)?;
+        }
+        if let Some(stroke) = stroke {
+            scene.stroke_path(path, stroke, &options.clip, placement)?;
+        }
+        Ok(())
+    }
+
+    fn render_text(
+        scene: &mut Scene, fonts: &mut FontCache, text: &Text, defaults: &ShapeDefaults,
+    ) -> Result<(), WgpuRenderError> {
+        let options = DrawOptions::new(text.transparency, &text.clip, &text.transform, defaults);
+        let input = text.input.as_ref().filter(|input| input.focused);
+        if let Some(input) = input {
+            if let Some(range) = input.selection() {
+                let (start_x, end_x) = (text.caret_x(range.start), text.caret_x(range.end));
+                Self::fill_text_line_rect(scene, text, start_x, end_x - start_x, input.selection_color, &options)?;
+            }
+        }
+
+        // The glyphs are not drawn until the text is measured.
+        let baseline = match text.baseline() {
+            Some(baseline) => baseline,
+            None => return Ok(()),
+        };
+        let paint = text
+            .fill
+            .as_ref()
+            .or(defaults.fill.as_ref())
+            .map(|fill| fill.paint.clone())
+            .unwrap_or_default();
+        if text.spans.is_empty() || text.path.is_some() {
+            let face_match = fonts.find_face(&text.font_name, text.font_weight, text.font_style);
+            let glyph_transforms = text
+                .path
+                .as_ref()
+                .map(|text_path| text_path.glyph_transforms.as_slice());
+            Self::draw_glyphs(
+                scene,
+                fonts,
+                &face_match,
+                &text.content,
+                &text.glyph_positions,
+                glyph_transforms,
+                (baseline, text.font_size.val()),
+                &paint,
+                &options,
+            )?;
+        } else {
+            let mut glyph_positions = text.glyph_positions.as_slice();
+            for span in &text.spans {
+                let face = TextFace::of_span(text, span);
+                let face_match = fonts.find_face(face.family, face.weight, face.style);
+                let chars = span.content.chars().count().min(glyph_positions.len());
+                let span_paint = span.fill.as_ref().map(|fill| &fill.paint).unwrap_or(&paint);
+                Self::draw_glyphs(
+                    scene,
+                    fonts,
+                    &face_match,
+                    &span.content,
+                    &glyph_positions[..chars],
+                    None,
+                    (baseline, text.span_font_size(span)),
+                    span_paint,
+                    &options,
+                )?;
+                glyph_positions = &glyph_positions[chars..];
+            }
+        }
+
+        if let Some(fill) = text.fill.as_ref().or(defaults.fill.as_ref()) {
+            for line in text.decoration_lines() {
+                Self::fill_text_rect(scene, line, &fill.paint, &options)?;
+            }
+        }
+
+        if let Some(input) = input.filter(|input| input.caret_visible) {
+            let caret_x = text.caret_x(input.caret) - CARET_WIDTH / 2.0;
+            Self::fill_text_line_rect(scene, text, caret_x, CARET_WIDTH, input.caret_color, &options)?;
+        }
+        Ok(())
+    }
+
+    /// Places the cached glyph meshes by the glyph positions. The text on the path is placed by the glyph transforms
+    /// in addition, the glyphs without the transform are out of the path. The missing bold is synthesized by the
+    /// shifted overdraw and the missing italic by the skew around the baseline.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_glyphs(
+        scene: &mut Scene, fonts: &mut FontCache, face_match: &FaceMatch, content: &str, positions: &[GlyphPos],
+        glyph_transforms: Option<&[Option<TransformMatrix>]>, (baseline, font_size): (Real, Real), paint: &Paint,
+        options: &DrawOptions,
+    ) -> Result<(), WgpuRenderError> {
+        let glyph_ids = fonts.glyph_ids(&face_match.name, content)?;
+        let skew = if face_match.synthetic_italic {
+            SYNTHETIC_ITALIC_SKEW
+        } else {
+            0.0
+        };
+        for (idx, (glyph_id, pos)) in glyph_ids.into_iter().zip(positions).enumerate() {
+            let global = match glyph_transforms {
+                Some(glyph_transforms) => match glyph_transforms.get(idx).copied().flatten() {
+                    Some(glyph_transform) => options.transform * glyph_transform,
+                    None => continue,
+                },
+                None => options.transform,
+            };
+            let mesh = glyph_mesh(fonts, &face_match.name, glyph_id)?;
+            let mut local = TransformMatrix {
+                matrix: [font_size, 0.0, -skew * font_size, font_size, pos.x, baseline],
+            };
+            let placement = Placement {
+                local,
+                global,
+                alpha: options.alpha,
+            };
+            scene.fill_mesh(&mesh, paint, &options.clip, placement)?;
+            if face_match.synthetic_bold {
+                local.matrix[4] += font_size * SYNTHETIC_BOLD_SHIFT;
+                scene.fill_mesh(&mesh, paint, &options.clip, Placement { local, ..placement })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills the rect of the text line height, it is used for the caret and the selection of the text input.
+    fn fill_text_line_rect(
+        scene: &mut Scene, text: &Text, x: Real, width: Real, color: Color, options: &DrawOptions,
+    ) -> Result<(), WgpuRenderError> {
+        let height = text
+            .metrics
+            .map(|metrics| metrics.line_height)
+            .unwrap_or_else(|| text.font_size.val());
+        let rect = BoundingBox {
+            min_x: x,
+            min_y: text.y.val(),
+            max_x: x + width,
+            max_y: text.y.val() + height,
+        };
+        Self::fill_text_rect(scene, rect, &Paint::Color(color), options)
+    }
+
+    fn fill_text_rect(
+        scene: &mut Scene, rect: BoundingBox, paint: &Paint, options: &DrawOptions,
+    ) -> Result<(), WgpuRenderError> {
+        scene.fill_path(
+            &rect_path(rect),
+            FillRule::NonZero,
+            paint,
+            &options.clip,
+            options.placement(TransformMatrix::identity()),
+        )
+    }
+}
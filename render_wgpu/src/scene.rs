@@ -0,0 +1,708 @@
+use std::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use exgui_core::{
+    arc_to_cub_beziers, BoundingBox, Clip, Color, FillRule, Gradient, LineCap, LineJoin, Paint, PathCommand, Real,
+    Stroke, TransformMatrix,
+};
+use lyon::{
+    math::{point, Point},
+    path::Path as LyonPath,
+    tessellation::{
+        BuffersBuilder, FillOptions, FillRule as LyonFillRule, FillTessellator, FillVertex, LineCap as LyonLineCap,
+        LineJoin as LyonLineJoin, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+    },
+};
+
+use crate::WgpuRenderError;
+
+/// Maximum distance of the flattened curves from the exact ones, in the local units of the shape.
+pub const TOLERANCE: f32 = 0.1;
+/// Extent of the scissor, that covers the whole window.
+const NO_SCISSOR_EXTENT: f32 = 1.0e6;
+/// Maximum reference value of the 8-bit stencil buffer.
+const MAX_STENCIL: u32 = 255;
+
+const PAINT_GRADIENT: f32 = 0.0;
+const PAINT_IMAGE: f32 = 1.0;
+const PAINT_CONIC: f32 = 2.0;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Pod, Zeroable)]
+pub struct Vertex {
+    /// Position in the window.
+    pub screen: [f32; 2],
+    /// Position in the local coordinates of the shape, which the paint is evaluated in.
+    pub local: [f32; 2],
+}
+
+impl Vertex {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+
+    fn new(position: Point, local: TransformMatrix, global: TransformMatrix) -> Self {
+        let (local_x, local_y) = local * (position.x as Real, position.y as Real);
+        let (screen_x, screen_y) = global * (local_x, local_y);
+        Self {
+            screen: [screen_x, screen_y],
+            local: [local_x, local_y],
+        }
+    }
+}
+
+/// Uniform data of the draw call, it has the layout of the `Draw` struct of the shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct DrawUniform {
+    scissor_matrix: [f32; 4],
+    scissor_translation_extent: [f32; 4],
+    paint_matrix: [f32; 4],
+    paint_translation_extent: [f32; 4],
+    inner_color: [f32; 4],
+    outer_color: [f32; 4],
+    params: [f32; 4],
+}
+
+impl DrawUniform {
+    fn new(paint: PaintUniform, clip: &Clip, alpha: f32) -> Self {
+        // The shader clips by the bounding scissors of the circle and path clips, their regions are applied by the
+        // stencil buffer.
+        let (scissor_matrix, scissor_extent) = match clip.bounding_scissor() {
+            Some(scissor) => {
+                let (half_width, half_height) = (scissor.width.val() / 2.0, scissor.height.val() / 2.0);
+                let center = TransformMatrix::identity()
+                    .with_translation(scissor.x.val() + half_width, scissor.y.val() + half_height);
+                let transform = scissor
+                    .transform
+                    .calculated_matrix()
+                    .unwrap_or_else(|| scissor.transform.matrix());
+                ((transform * center).inverse(), [half_width, half_height])
+            }
+            None => (TransformMatrix::identity(), [NO_SCISSOR_EXTENT, NO_SCISSOR_EXTENT]),
+        };
+        let [a, b, c, d, e, f] = scissor_matrix.matrix;
+        let [pa, pb, pc, pd, pe, pf] = paint.matrix.inverse().matrix;
+        Self {
+            scissor_matrix: [a, b, c, d],
+            scissor_translation_extent: [e, f, scissor_extent[0], scissor_extent[1]],
+            paint_matrix: [pa, pb, pc, pd],
+            paint_translation_extent: [pe, pf, paint.extent[0], paint.extent[1]],
+            inner_color: paint.inner_color,
+            outer_color: paint.outer_color,
+            params: [paint.kind, paint.radius, paint.feather, alpha],
+        }
+    }
+}
+
+/// Paint in the unified form of the shader: the gradients are the feathered rounded rects in the paint space.
+struct PaintUniform {
+    kind: f32,
+    matrix: TransformMatrix,
+    extent: [f32; 2],
+    radius: f32,
+    feather: f32,
+    inner_color: [f32; 4],
+    outer_color: [f32; 4],
+}
+
+impl PaintUniform {
+    fn color(color: Color) -> Self {
+        let color = color.as_arr();
+        Self {
+            kind: PAINT_GRADIENT,
+            matrix: TransformMatrix::identity(),
+            extent: [0.0, 0.0],
+            radius: 0.0,
+            feather: 1.0,
+            inner_color: color,
+            outer_color: color,
+        }
+    }
+
+    fn gradient(
+        matrix: TransformMatrix, extent: [f32; 2], radius: f32, feather: f32, inner: Color, outer: Color,
+    ) -> Self {
+        Self {
+            kind: PAINT_GRADIENT,
+            matrix,
+            extent,
+            radius,
+            feather: feather.max(1.0),
+            inner_color: inner.as_arr(),
+            outer_color: outer.as_arr(),
+        }
+    }
+
+    fn from_paint(paint: &Paint) -> Self {
+        match *paint {
+            Paint::Color(color) => Self::color(color),
+            Paint::Gradient(Gradient::Linear {
+                start: (start_x, start_y),
+                end: (end_x, end_y),
+                start_color,
+                end_color,
+            }) => {
+                // The linear gradient is the huge rect, that is feathered along the gradient direction.
+                let large = 1.0e5;
+                let (dx, dy) = (end_x - start_x, end_y - start_y);
+                let distance = (dx * dx + dy * dy).sqrt();
+                let (dx, dy) = if distance > 1.0e-4 {
+                    (dx / distance, dy / distance)
+                } else {
+                    (0.0, 1.0)
+                };
+                let matrix = TransformMatrix {
+                    matrix: [dy, -dx, dx, dy, start_x - dx * large, start_y - dy * large],
+                };
+                Self::gradient(
+                    matrix,
+                    [large, large + distance * 0.5],
+                    0.0,
+                    distance,
+                    start_color,
+                    end_color,
+                )
+            }
+            Paint::Gradient(Gradient::Box {
+                position: (x, y),
+                size: (width, height),
+                radius,
+                feather,
+                start_color,
+                end_color,
+            }) => Self::gradient(
+                TransformMatrix::identity().with_translation(x + width / 2.0, y + height / 2.0),
+                [width / 2.0, height / 2.0],
+                radius,
+                feather,
+                start_color,
+                end_color,
+            ),
+            Paint::Gradient(Gradient::Radial {
+                center: (x, y),
+                inner_radius,
+                outer_radius,
+                start_color,
+                end_color,
+            }) => {
+                let radius = (inner_radius + outer_radius) / 2.0;
+                Self::gradient(
+                    TransformMatrix::identity().with_translation(x, y),
+                    [radius, radius],
+                    radius,
+                    outer_radius - inner_radius,
+                    start_color,
+                    end_color,
+                )
+            }
+            Paint::Gradient(Gradient::Conic {
+                center: (x, y),
+                angle,
+                ..
+            }) => Self {
+                kind: PAINT_CONIC,
+                matrix: TransformMatrix::identity().with_translation(x, y)
+                    * TransformMatrix::identity().with_rotation(angle),
+                extent: [1.0, 1.0],
+                radius: 0.0,
+                feather: 1.0,
+                inner_color: [1.0; 4],
+                outer_color: [1.0; 4],
+            },
+        }
+    }
+
+    fn image(x: Real, y: Real, width: Real, height: Real) -> Self {
+        Self {
+            kind: PAINT_IMAGE,
+            matrix: TransformMatrix::identity().with_translation(x, y),
+            extent: [width, height],
+            radius: 0.0,
+            feather: 1.0,
+            inner_color: [1.0; 4],
+            outer_color: [1.0; 4],
+        }
+    }
+}
+
+/// Key of the image cache, which the draw call samples. The draws without the image sample the white texture.
+pub type TextureKey = Option<String>;
+
+/// Stencil state of the draw call, the circle and path clips are applied by the stencil buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stencil {
+    /// The draw is not clipped by the stencil buffer.
+    None,
+    /// The draw writes the reference value into the stencil buffer in the clip region, the colors are not written.
+    Write(u32),
+    /// The draw is kept, where the stencil buffer has the reference value.
+    Test(u32),
+}
+
+pub struct DrawCall {
+    pub indices: Range<u32>,
+    pub uniform: DrawUniform,
+    pub texture: TextureKey,
+    pub stencil: Stencil,
+}
+
+/// Triangles of the whole frame with the draw calls, which are uploaded to the GPU at once.
+#[derive(Default)]
+pub struct Scene {
+    pub geometry: VertexBuffers<Vertex, u32>,
+    pub draws: Vec<DrawCall>,
+    /// The clip, which is written into the stencil buffer last, with its reference value. The following draws with
+    /// the same clip test the stencil buffer without writing the clip again.
+    mask: Option<(Clip, u32)>,
+}
+
+/// Placement of the tessellated geometry: the `local` matrix maps the geometry into the local coordinates of the
+/// shape and the `global` one maps the local coordinates into the window.
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    pub local: TransformMatrix,
+    pub global: TransformMatrix,
+    pub alpha: f32,
+}
+
+impl Scene {
+    pub fn clear(&mut self) {
+        self.geometry.vertices.clear();
+        self.geometry.indices.clear();
+        self.draws.clear();
+        self.mask = None;
+    }
+
+    pub fn fill_path(
+        &mut self, path: &LyonPath, fill_rule: FillRule, paint: &Paint, clip: &Clip, placement: Placement,
+    ) -> Result<(), WgpuRenderError> {
+        let start = self.geometry.indices.len() as u32;
+        let options = FillOptions::tolerance(TOLERANCE).with_fill_rule(match fill_rule {
+            FillRule::NonZero => LyonFillRule::NonZero,
+            FillRule::EvenOdd => LyonFillRule::EvenOdd,
+        });
+        let mut builder = BuffersBuilder::new(&mut self.geometry, |vertex: FillVertex| {
+            Vertex::new(vertex.position(), placement.local, placement.global)
+        });
+        FillTessellator::new()
+            .tessellate_path(path, &options, &mut builder)
+            .map_err(WgpuRenderError::TessellationError)?;
+        self.push_draw(start, paint, clip, placement.alpha)
+    }
+
+    pub fn stroke_path(
+        &mut self, path: &LyonPath, stroke: &Stroke, clip: &Clip, placement: Placement,
+    ) -> Result<(), WgpuRenderError> {
+        let start = self.geometry.indices.len() as u32;
+        let options = stroke_options(stroke);
+        let mut builder = BuffersBuilder::new(&mut self.geometry, |vertex: StrokeVertex| {
+            Vertex::new(vertex.position(), placement.local, placement.global)
+        });
+        StrokeTessellator::new()
+            .tessellate_path(path, &options, &mut builder)
+            .map_err(WgpuRenderError::TessellationError)?;
+        self.push_draw(start, &stroke.paint, clip, placement.alpha)
+    }
+
+    /// Adds the geometry which is tessellated already, e.g. the cached glyph.
+    pub fn fill_mesh(
+        &mut self, mesh: &VertexBuffers<Point, u32>, paint: &Paint, clip: &Clip, placement: Placement,
+    ) -> Result<(), WgpuRenderError> {
+        let start = self.geometry.indices.len() as u32;
+        let base = self.geometry.vertices.len() as u32;
+        self.geometry.vertices.extend(
+            mesh.vertices
+                .iter()
+                .map(|position| Vertex::new(*position, placement.local, placement.global)),
+        );
+        self.geometry
+            .indices
+            .extend(mesh.indices.iter().map(|index| base + index));
+        self.push_draw(start, paint, clip, placement.alpha)
+    }
+
+    pub fn fill_image(
+        &mut self, src: &str, bound: BoundingBox, clip: &Clip, placement: Placement,
+    ) -> Result<(), WgpuRenderError> {
+        let start = self.geometry.indices.len() as u32;
+        let path = rect_path(bound);
+        let mut builder = BuffersBuilder::new(&mut self.geometry, |vertex: FillVertex| {
+            Vertex::new(vertex.position(), placement.local, placement.global)
+        });
+        FillTessellator::new()
+            .tessellate_path(&path, &FillOptions::tolerance(TOLERANCE), &mut builder)
+            .map_err(WgpuRenderError::TessellationError)?;
+        let end = self.geometry.indices.len() as u32;
+        let paint = PaintUniform::image(bound.min_x, bound.min_y, bound.width(), bound.height());
+        let stencil = self.clip_stencil(clip)?;
+        self.draws.push(DrawCall {
+            indices: start..end,
+            uniform: DrawUniform::new(paint, clip, placement.alpha),
+            texture: Some(src.to_string()),
+            stencil,
+        });
+        Ok(())
+    }
+
+    fn push_draw(&mut self, start: u32, paint: &Paint, clip: &Clip, alpha: f32) -> Result<(), WgpuRenderError> {
+        let end = self.geometry.indices.len() as u32;
+        if start == end {
+            return Ok(());
+        }
+        let texture = match paint {
+            Paint::Gradient(Gradient::Conic { stops, .. }) => Some(conic_image_key(stops)),
+            _ => None,
+        };
+        let stencil = self.clip_stencil(clip)?;
+        self.draws.push(DrawCall {
+            indices: start..end,
+            uniform: DrawUniform::new(PaintUniform::from_paint(paint), clip, alpha),
+            texture,
+            stencil,
+        });
+        Ok(())
+    }
+
+    /// Writes the region of the circle or path clip into the stencil buffer, unless it is written by the last mask
+    /// already. Every mask has the own reference value, so the regions of the previous masks do not pass the test.
+    fn clip_stencil(&mut self, clip: &Clip) -> Result<Stencil, WgpuRenderError> {
+        let outline = match clip {
+            Clip::Circle(_) | Clip::Path(_) => clip.outline().unwrap_or_default(),
+            _ => return Ok(Stencil::None),
+        };
+        let reference = match &self.mask {
+            Some((mask, reference)) if mask == clip => return Ok(Stencil::Test(*reference)),
+            Some((_, reference)) if *reference < MAX_STENCIL => reference + 1,
+            Some(_) => {
+                // The reference values are used up, so the stencil buffer is cleared to reuse them.
+                let extent = NO_SCISSOR_EXTENT as Real;
+                let bound = BoundingBox {
+                    min_x: -extent,
+                    min_y: -extent,
+                    max_x: extent,
+                    max_y: extent,
+                };
+                self.push_mask(&rect_path(bound), TransformMatrix::identity(), 0)?;
+                1
+            }
+            None => 1,
+        };
+        let transform = clip
+            .transform()
+            .map(|transform| transform.calculated_matrix().unwrap_or_else(|| transform.matrix()))
+            .unwrap_or_else(TransformMatrix::identity);
+        self.push_mask(&commands_path(&outline), transform, reference)?;
+        self.mask = Some((clip.clone(), reference));
+        Ok(Stencil::Test(reference))
+    }
+
+    fn push_mask(
+        &mut self, path: &LyonPath, transform: TransformMatrix, reference: u32,
+    ) -> Result<(), WgpuRenderError> {
+        let start = self.geometry.indices.len() as u32;
+        let mut builder = BuffersBuilder::new(&mut self.geometry, |vertex: FillVertex| {
+            Vertex::new(vertex.position(), TransformMatrix::identity(), transform)
+        });
+        FillTessellator::new()
+            .tessellate_path(path, &FillOptions::tolerance(TOLERANCE), &mut builder)
+            .map_err(WgpuRenderError::TessellationError)?;
+        self.draws.push(DrawCall {
+            indices: start..self.geometry.indices.len() as u32,
+            uniform: DrawUniform::new(PaintUniform::color(Color::White), &Clip::None, 1.0),
+            texture: None,
+            stencil: Stencil::Write(reference),
+        });
+        Ok(())
+    }
+}
+
+pub fn conic_image_key(stops: &[(Real, Color)]) -> String {
+    format!("conic:{:?}", stops)
+}
+
+/// Color ramp of the conic gradient, which is sampled by the angle.
+pub fn conic_image_data(stops: &[(Real, Color)], width: usize) -> Vec<u8> {
+    let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (0..width)
+        .flat_map(|idx| {
+            let [r, g, b, a] = Gradient::stops_color(stops, idx as Real / (width - 1) as Real).as_arr();
+            vec![to_byte(r), to_byte(g), to_byte(b), to_byte(a)]
+        })
+        .collect()
+}
+
+fn stroke_options(stroke: &Stroke) -> StrokeOptions {
+    let line_cap = match stroke.line_cap {
+        LineCap::Butt => LyonLineCap::Butt,
+        LineCap::Round => LyonLineCap::Round,
+        LineCap::Square => LyonLineCap::Square,
+    };
+    let line_join = match stroke.line_join {
+        LineJoin::Miter => LyonLineJoin::Miter,
+        LineJoin::Round => LyonLineJoin::Round,
+        LineJoin::Bevel => LyonLineJoin::Bevel,
+    };
+    StrokeOptions::tolerance(TOLERANCE)
+        .with_line_width(stroke.width)
+        .with_line_cap(line_cap)
+        .with_line_join(line_join)
+        .with_miter_limit(stroke.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT))
+}
+
+pub fn rect_path(bound: BoundingBox) -> LyonPath {
+    let mut builder = LyonPath::svg_builder();
+    builder.move_to(point(bound.min_x, bound.min_y));
+    builder.line_to(point(bound.max_x, bound.min_y));
+    builder.line_to(point(bound.max_x, bound.max_y));
+    builder.line_to(point(bound.min_x, bound.max_y));
+    builder.close();
+    builder.build()
+}
+
+/// Builds the lyon path of the commands, the arcs are converted into the cubic beziers.
+pub fn commands_path(commands: &[PathCommand]) -> LyonPath {
+    use exgui_core::PathCommand::*;
+
+    let to_point = |xy: [Real; 2]| point(xy[0], xy[1]);
+    let mut builder = LyonPath::svg_builder();
+    let mut last_xy = [0.0, 0.0];
+    let mut bez_ctrls = [[0.0, 0.0], [0.0, 0.0]];
+
+    for cmd in commands.iter() {
+        match *cmd {
+            Move(xy) => {
+                last_xy = xy;
+                builder.move_to(to_point(last_xy));
+            }
+            MoveRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.move_to(to_point(last_xy));
+            }
+            Line(xy) => {
+                last_xy = xy;
+                builder.line_to(to_point(last_xy));
+            }
+            LineRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.line_to(to_point(last_xy));
+            }
+            LineAlonX(x) => {
+                last_xy[0] = x;
+                builder.line_to(to_point(last_xy));
+            }
+            LineAlonXRel(x) => {
+                last_xy[0] += x;
+                builder.line_to(to_point(last_xy));
+            }
+            LineAlonY(y) => {
+                last_xy[1] = y;
+                builder.line_to(to_point(last_xy));
+            }
+            LineAlonYRel(y) => {
+                last_xy[1] += y;
+                builder.line_to(to_point(last_xy));
+            }
+            Close => builder.close(),
+            BezCtrl(xy) => {
+                bez_ctrls = [bez_ctrls[1], xy];
+            }
+            BezCtrlRel(xy) => {
+                bez_ctrls = [bez_ctrls[1], [last_xy[0] + xy[0], last_xy[1] + xy[1]]];
+            }
+            BezReflectCtrl => {
+                let reflected = [2.0 * last_xy[0] - bez_ctrls[1][0], 2.0 * last_xy[1] - bez_ctrls[1][1]];
+                bez_ctrls = [bez_ctrls[1], reflected];
+            }
+            QuadBezTo(xy) => {
+                last_xy = xy;
+                builder.quadratic_bezier_to(to_point(bez_ctrls[1]), to_point(last_xy));
+            }
+            QuadBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.quadratic_bezier_to(to_point(bez_ctrls[1]), to_point(last_xy));
+            }
+            CubBezTo(xy) => {
+                last_xy = xy;
+                builder.cubic_bezier_to(to_point(bez_ctrls[0]), to_point(bez_ctrls[1]), to_point(last_xy));
+            }
+            CubBezToRel(xy) => {
+                last_xy = [last_xy[0] + xy[0], last_xy[1] + xy[1]];
+                builder.cubic_bezier_to(to_point(bez_ctrls[0]), to_point(bez_ctrls[1]), to_point(last_xy));
+            }
+            Arc {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    builder.cubic_bezier_to(to_point(ctrl1), to_point(ctrl2), to_point(xy));
+                }
+                last_xy = to;
+            }
+            ArcRel {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                let to = [last_xy[0] + to[0], last_xy[1] + to[1]];
+                for [ctrl1, ctrl2, xy] in arc_to_cub_beziers(last_xy, radius, rotation, large_arc, sweep, to) {
+                    builder.cubic_bezier_to(to_point(ctrl1), to_point(ctrl2), to_point(xy));
+                }
+                last_xy = to;
+            }
+        }
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use exgui_core::{Path, Transform};
+
+    use super::*;
+
+    fn placement() -> Placement {
+        Placement {
+            local: TransformMatrix::identity(),
+            global: TransformMatrix::identity().with_translation(10.0, 20.0),
+            alpha: 0.5,
+        }
+    }
+
+    fn rect(width: Real, height: Real) -> LyonPath {
+        rect_path(BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: width,
+            max_y: height,
+        })
+    }
+
+    fn stencils(scene: &Scene) -> Vec<Stencil> {
+        scene.draws.iter().map(|draw| draw.stencil).collect()
+    }
+
+    fn screen_bound(scene: &Scene, draw: &DrawCall) -> BoundingBox {
+        let indices = &scene.geometry.indices[draw.indices.start as usize..draw.indices.end as usize];
+        let mut points = indices.iter().map(|&idx| scene.geometry.vertices[idx as usize].screen);
+        let [x, y] = points.next().expect("Draw vertices expected");
+        points.fold(
+            BoundingBox {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            },
+            |bound, [x, y]| BoundingBox {
+                min_x: bound.min_x.min(x),
+                min_y: bound.min_y.min(y),
+                max_x: bound.max_x.max(x),
+                max_y: bound.max_y.max(y),
+            },
+        )
+    }
+
+    #[test]
+    fn fill_path_placement() {
+        let mut scene = Scene::default();
+        let paint = Paint::Color(Color::Red);
+        scene
+            .fill_path(&rect(30.0, 40.0), FillRule::NonZero, &paint, &Clip::None, placement())
+            .unwrap();
+
+        assert_eq!(stencils(&scene), vec![Stencil::None]);
+        let draw = &scene.draws[0];
+        assert_eq!(draw.indices, 0..6);
+        assert_eq!(draw.texture, None);
+        assert_eq!(draw.uniform.params[3], 0.5);
+        assert_eq!(draw.uniform.inner_color, Color::Red.as_arr());
+        assert_eq!(screen_bound(&scene, draw), BoundingBox {
+            min_x: 10.0,
+            min_y: 20.0,
+            max_x: 40.0,
+            max_y: 60.0,
+        });
+        let local_max = scene
+            .geometry
+            .vertices
+            .iter()
+            .map(|vertex| vertex.local)
+            .fold([0.0_f32; 2], |max, [x, y]| [max[0].max(x), max[1].max(y)]);
+        assert_eq!(local_max, [30.0, 40.0]);
+    }
+
+    #[test]
+    fn scissor_uniform() {
+        let clip = Clip::new_scissor(10.into(), 20.into(), 100.into(), 50.into());
+        let uniform = DrawUniform::new(PaintUniform::color(Color::Black), &clip, 1.0);
+        assert_eq!(uniform.scissor_matrix, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(uniform.scissor_translation_extent, [-60.0, -45.0, 50.0, 25.0]);
+
+        let uniform = DrawUniform::new(PaintUniform::color(Color::Black), &Clip::None, 1.0);
+        let extent = NO_SCISSOR_EXTENT;
+        assert_eq!(uniform.scissor_translation_extent, [0.0, 0.0, extent, extent]);
+    }
+
+    #[test]
+    fn clip_stencil_masks() {
+        let mut scene = Scene::default();
+        let paint = Paint::Color(Color::Black);
+        let mut circle = Clip::new_circle(50.into(), 50.into(), 10.into());
+        if let Some(transform) = circle.transform_mut() {
+            *transform = Transform::Calculated {
+                local: None,
+                global: TransformMatrix::identity().with_translation(100.0, 0.0),
+            };
+        }
+        let square = Clip::new_path(Path {
+            cmd: Path::builder()
+                .move_to([0.0, 0.0])
+                .line_to([5.0, 0.0])
+                .line_to([5.0, 5.0])
+                .line_to([0.0, 5.0])
+                .close()
+                .build(),
+            ..Path::default()
+        });
+
+        for clip in [&circle, &circle, &square, &Clip::None] {
+            scene
+                .fill_path(&rect(10.0, 10.0), FillRule::NonZero, &paint, clip, placement())
+                .unwrap();
+        }
+        assert_eq!(stencils(&scene), vec![
+            Stencil::Write(1),
+            Stencil::Test(1),
+            Stencil::Test(1),
+            Stencil::Write(2),
+            Stencil::Test(2),
+            Stencil::None,
+        ]);
+
+        // The mask is placed by the clip transform, not by the shape one.
+        let bound = screen_bound(&scene, &scene.draws[0]);
+        assert!((bound.min_x - 140.0).abs() < 0.1 && (bound.max_x - 160.0).abs() < 0.1);
+        assert!((bound.min_y - 40.0).abs() < 0.1 && (bound.max_y - 60.0).abs() < 0.1);
+
+        scene.clear();
+        assert!(scene.draws.is_empty() && scene.mask.is_none());
+    }
+
+    #[test]
+    fn clip_stencil_wraps_reference() {
+        let mut scene = Scene {
+            mask: Some((Clip::new_circle(0.into(), 0.into(), 1.into()), MAX_STENCIL)),
+            ..Scene::default()
+        };
+        let clip = Clip::new_circle(5.into(), 5.into(), 5.into());
+        let paint = Paint::Color(Color::Black);
+        scene
+            .fill_path(&rect(10.0, 10.0), FillRule::NonZero, &paint, &clip, placement())
+            .unwrap();
+        let expected = vec![Stencil::Write(0), Stencil::Write(1), Stencil::Test(1)];
+        assert_eq!(stencils(&scene), expected);
+    }
+}
@@ -0,0 +1,80 @@
+// Draws the tessellated triangles with the paint of the draw call. The paints are evaluated in the paint space,
+// which the local coordinates of the shape are mapped into by the paint matrix, in the same way as nanovg does.
+
+struct Globals {
+    viewport: vec4<f32>,
+}
+
+// The matrices are in the column-major form [a b c d] and [e f], the extents follow the translation.
+struct Draw {
+    scissor_matrix: vec4<f32>,
+    scissor_translation_extent: vec4<f32>,
+    paint_matrix: vec4<f32>,
+    paint_translation_extent: vec4<f32>,
+    inner_color: vec4<f32>,
+    outer_color: vec4<f32>,
+    // Paint kind, radius, feather and alpha.
+    params: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var<uniform> draw: Draw;
+@group(1) @binding(0) var paint_texture: texture_2d<f32>;
+@group(1) @binding(1) var paint_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) screen: vec2<f32>,
+    @location(1) local: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) screen: vec2<f32>, @location(1) local: vec2<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(
+        2.0 * screen.x / globals.viewport.x - 1.0,
+        1.0 - 2.0 * screen.y / globals.viewport.y,
+        0.0,
+        1.0,
+    );
+    out.screen = screen;
+    out.local = local;
+    return out;
+}
+
+fn apply_matrix(matrix: vec4<f32>, translation: vec2<f32>, point: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        matrix.x * point.x + matrix.z * point.y + translation.x,
+        matrix.y * point.x + matrix.w * point.y + translation.y,
+    );
+}
+
+fn sd_round_rect(point: vec2<f32>, extent: vec2<f32>, radius: f32) -> f32 {
+    let d = abs(point) - (extent - vec2<f32>(radius, radius));
+    return min(max(d.x, d.y), 0.0) + length(max(d, vec2<f32>(0.0, 0.0))) - radius;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scissor_point = apply_matrix(draw.scissor_matrix, draw.scissor_translation_extent.xy, in.screen);
+    let scissor_distance = abs(scissor_point) - draw.scissor_translation_extent.zw;
+    let scissor = clamp(0.5 - max(scissor_distance.x, scissor_distance.y), 0.0, 1.0);
+
+    let point = apply_matrix(draw.paint_matrix, draw.paint_translation_extent.xy, in.local);
+    let extent = draw.paint_translation_extent.zw;
+    let kind = draw.params.x;
+    var color: vec4<f32>;
+    if kind < 0.5 {
+        let feather = draw.params.z;
+        let d = clamp((sd_round_rect(point, extent, draw.params.y) + feather * 0.5) / feather, 0.0, 1.0);
+        color = mix(draw.inner_color, draw.outer_color, d);
+    } else if kind < 1.5 {
+        color = textureSample(paint_texture, paint_sampler, point / extent);
+    } else {
+        let t = fract(atan2(point.y, point.x) / 6.28318530718);
+        color = textureSample(paint_texture, paint_sampler, vec2<f32>(t, 0.5));
+    }
+
+    let alpha = color.a * draw.params.w * scissor;
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
@@ -0,0 +1,645 @@
+use std::{
+    f32::consts::PI,
+    fmt::{self, Write},
+    fs, io,
+    path::Path as FsPath,
+};
+
+use exgui_core::{
+    AlignHor, AlignVer, Clip, Color, CompositeShape, Fill, FillRule, FontStyle, Gradient, LineCap, LineJoin, Paint,
+    PathCommand, Real, Shape, Stroke, Text, TransformMatrix,
+};
+
+/// Writes the shape tree as the SVG document of the given size. The tree should be recalculated by the render, so
+/// the values and the transforms are resolved. The box gradients have no SVG counterparts, they are written as the
+/// radial ones, and the conic ones as the color of their first stop. The texts along the paths are written as the
+/// straight ones.
+pub fn to_svg(root: &dyn CompositeShape, width: Real, height: Real) -> String {
+    let mut exporter = Exporter::default();
+    let mut body = String::new();
+    exporter
+        .composite(&mut body, root, TransformMatrix::identity())
+        .expect("Writing to the string is infallible");
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" fill="none">"#,
+        width, height
+    );
+    if !exporter.defs.is_empty() {
+        svg.push_str("<defs>");
+        svg.push_str(&exporter.defs);
+        svg.push_str("</defs>");
+    }
+    svg.push_str(&body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save_svg(root: &dyn CompositeShape, width: Real, height: Real, path: impl AsRef<FsPath>) -> io::Result<()> {
+    fs::write(path, to_svg(root, width, height))
+}
+
+#[derive(Default)]
+struct Exporter {
+    defs: String,
+    next_id: usize,
+}
+
+impl Exporter {
+    fn composite(&mut self, out: &mut String, composite: &dyn CompositeShape, parent: TransformMatrix) -> fmt::Result {
+        let mut matrix = parent;
+        let mut is_group = false;
+        if let Some(shape) = composite.shape() {
+            let transform = shape.transform();
+            matrix = transform.global_matrix().unwrap_or_else(|| parent * transform.matrix());
+            if let Shape::Group(group) = shape {
+                // The children have the absolute transforms, so the group has no own one.
+                is_group = true;
+                out.push_str("<g");
+                write_id(out, shape.id())?;
+                self.write_clip(out, &group.clip, parent)?;
+                if let Some(transparency) = group.transparency {
+                    write!(out, r#" opacity="{}""#, 1.0 - transparency)?;
+                }
+                self.write_fill(out, group.fill.as_ref())?;
+                self.write_stroke(out, group.stroke.as_ref())?;
+                out.push('>');
+            } else {
+                self.shape(out, shape, matrix, parent)?;
+            }
+        }
+        for child in composite.children_in_draw_order().unwrap_or_default() {
+            self.composite(out, child, matrix)?;
+        }
+        if is_group {
+            out.push_str("</g>");
+        }
+        Ok(())
+    }
+
+    fn shape(
+        &mut self, out: &mut String, shape: &Shape, matrix: TransformMatrix, parent: TransformMatrix,
+    ) -> fmt::Result {
+        let clip = shape.clip();
+        // The clip is set on the wrapping group, otherwise it would be transformed by the shape transform.
+        let clipped = clip.scissor().is_some();
+        if clipped {
+            out.push_str("<g");
+            self.write_clip(out, clip, parent)?;
+            out.push('>');
+        }
+
+        match shape {
+            Shape::Rect(rect) => {
+                out.push_str("<rect");
+                write!(
+                    out,
+                    r#" x="{}" y="{}" width="{}" height="{}""#,
+                    rect.x.val(),
+                    rect.y.val(),
+                    rect.width.val(),
+                    rect.height.val()
+                )?;
+                // The SVG rect has the same radius for all corners.
+                if let Some(radius) = rect
+                    .rounding
+                    .map(|rounding| rounding.top_left.val())
+                    .filter(|r| *r > 0.0)
+                {
+                    write!(out, r#" rx="{}""#, radius)?;
+                }
+            }
+            Shape::Circle(circle) => {
+                out.push_str("<circle");
+                write!(
+                    out,
+                    r#" cx="{}" cy="{}" r="{}""#,
+                    circle.cx.val(),
+                    circle.cy.val(),
+                    circle.r.val()
+                )?;
+            }
+            Shape::Ellipse(ellipse) => {
+                out.push_str("<ellipse");
+                write!(
+                    out,
+                    r#" cx="{}" cy="{}" rx="{}" ry="{}""#,
+                    ellipse.cx.val(),
+                    ellipse.cy.val(),
+                    ellipse.rx.val(),
+                    ellipse.ry.val()
+                )?;
+            }
+            Shape::Path(path) => {
+                out.push_str("<path");
+                write!(out, r#" d="{}""#, path_data(&path.cmd))?;
+            }
+            Shape::Polygon(polygon) => {
+                out.push_str("<path");
+                let mut data = String::new();
+                for contour in polygon.contours.iter().filter(|contour| !contour.is_empty()) {
+                    for (idx, [x, y]) in contour.iter().enumerate() {
+                        write!(data, "{}{} {} ", if idx == 0 { "M" } else { "L" }, x, y)?;
+                    }
+                    data.push_str("Z ");
+                }
+                write!(out, r#" d="{}""#, data.trim_end())?;
+                if polygon.fill_rule == FillRule::EvenOdd {
+                    out.push_str(r#" fill-rule="evenodd""#);
+                }
+            }
+            Shape::Sector(sector) => {
+                out.push_str("<path");
+                write!(out, r#" d="{}""#, path_data(&sector.commands()))?;
+            }
+            Shape::Ring(ring) => {
+                out.push_str("<path");
+                write!(out, r#" d="{}" fill-rule="evenodd""#, path_data(&ring.commands()))?;
+            }
+            Shape::Image(image) => {
+                out.push_str("<image");
+                write!(
+                    out,
+                    r#" href="{}" x="{}" y="{}" width="{}" height="{}""#,
+                    escape(&image.src),
+                    image.x.val(),
+                    image.y.val(),
+                    image.width.val(),
+                    image.height.val()
+                )?;
+            }
+            Shape::Custom(custom) => {
+                out.push_str("<g");
+                write_id(out, shape.id())?;
+                write_transform(out, matrix)?;
+                write_opacity(out, custom.transparency)?;
+                self.write_fill(out, custom.fill.as_ref())?;
+                self.write_stroke(out, custom.stroke.as_ref())?;
+                out.push('>');
+                for path in custom.shape.draw() {
+                    write!(out, r#"<path d="{}""#, path_data(&path.cmd))?;
+                    self.write_fill(out, path.fill.as_ref())?;
+                    self.write_stroke(out, path.stroke.as_ref())?;
+                    out.push_str("/>");
+                }
+                out.push_str("</g>");
+            }
+            Shape::Text(text) => return self.text(out, text, matrix, clipped),
+            Shape::Group(_) => {}
+        }
+
+        if !matches!(shape, Shape::Custom(_) | Shape::Group(_)) {
+            write_id(out, shape.id())?;
+            write_transform(out, matrix)?;
+            write_opacity(out, shape.transparency().unwrap_or_default())?;
+            self.write_fill(out, shape.fill())?;
+            self.write_stroke(out, shape.stroke())?;
+            out.push_str("/>");
+        }
+        if clipped {
+            out.push_str("</g>");
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, out: &mut String, text: &Text, matrix: TransformMatrix, clipped: bool) -> fmt::Result {
+        out.push_str("<text");
+        write_id(out, text.id())?;
+        write!(out, r#" x="{}" y="{}""#, text.x.val(), text.y.val())?;
+        if !text.font_name.is_empty() {
+            write!(out, r#" font-family="{}""#, escape(&text.font_name))?;
+        }
+        write!(out, r#" font-size="{}""#, text.font_size.val())?;
+        write_font(out, Some(text.font_weight.0), Some(text.font_style))?;
+        let anchor = match text.align.0 {
+            AlignHor::Left => "start",
+            AlignHor::Center => "middle",
+            AlignHor::Right => "end",
+        };
+        let baseline = match text.align.1 {
+            AlignVer::Top => "hanging",
+            AlignVer::Middle => "central",
+            AlignVer::Baseline => "alphabetic",
+            AlignVer::Bottom => "text-after-edge",
+        };
+        write!(out, r#" text-anchor="{}" dominant-baseline="{}""#, anchor, baseline)?;
+        let decorations: Vec<_> = [
+            (text.decoration.underline, "underline"),
+            (text.decoration.line_through, "line-through"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect();
+        if !decorations.is_empty() {
+            write!(out, r#" text-decoration="{}""#, decorations.join(" "))?;
+        }
+        write_transform(out, matrix)?;
+        write_opacity(out, text.transparency)?;
+        self.write_fill(out, text.fill.as_ref())?;
+        self.write_stroke(out, text.stroke.as_ref())?;
+        out.push('>');
+
+        if text.spans.is_empty() {
+            out.push_str(&escape(&text.content));
+        }
+        for span in &text.spans {
+            out.push_str("<tspan");
+            if let Some(font_name) = &span.font_name {
+                write!(out, r#" font-family="{}""#, escape(font_name))?;
+            }
+            if let Some(font_size) = span.font_size {
+                write!(out, r#" font-size="{}""#, font_size.val())?;
+            }
+            write_font(out, span.font_weight.map(|weight| weight.0), span.font_style)?;
+            self.write_fill(out, span.fill.as_ref())?;
+            write!(out, ">{}</tspan>", escape(&span.content))?;
+        }
+        out.push_str("</text>");
+        if clipped {
+            out.push_str("</g>");
+        }
+        Ok(())
+    }
+
+    fn write_clip(&mut self, out: &mut String, clip: &Clip, parent: TransformMatrix) -> fmt::Result {
+        let transform = match clip.transform() {
+            Some(transform) => transform,
+            None => return Ok(()),
+        };
+        let id = self.new_id("clip");
+        let matrix = transform.global_matrix().unwrap_or_else(|| parent * transform.matrix());
+        write!(self.defs, r#"<clipPath id="{}">"#, id)?;
+        match clip {
+            Clip::Scissor(scissor) => write!(
+                self.defs,
+                r#"<rect x="{}" y="{}" width="{}" height="{}""#,
+                scissor.x.val(),
+                scissor.y.val(),
+                scissor.width.val(),
+                scissor.height.val()
+            )?,
+            Clip::Circle(circle) => write!(
+                self.defs,
+                r#"<circle cx="{}" cy="{}" r="{}""#,
+                circle.cx.val(),
+                circle.cy.val(),
+                circle.r.val()
+            )?,
+            Clip::Path(path) => write!(self.defs, r#"<path d="{}""#, path_data(&path.cmd))?,
+            Clip::None => (),
+        }
+        write_transform(&mut self.defs, matrix)?;
+        self.defs.push_str("/></clipPath>");
+        write!(out, r#" clip-path="url(#{})""#, id)
+    }
+
+    fn write_fill(&mut self, out: &mut String, fill: Option<&Fill>) -> fmt::Result {
+        match fill {
+            Some(fill) => self.write_paint(out, "fill", &fill.paint),
+            None => Ok(()),
+        }
+    }
+
+    fn write_stroke(&mut self, out: &mut String, stroke: Option<&Stroke>) -> fmt::Result {
+        let stroke = match stroke {
+            Some(stroke) => stroke,
+            None => return Ok(()),
+        };
+        self.write_paint(out, "stroke", &stroke.paint)?;
+        let line_cap = match stroke.line_cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        };
+        let line_join = match stroke.line_join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        };
+        write!(
+            out,
+            r#" stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}""#,
+            stroke.width, line_cap, line_join, stroke.miter_limit
+        )
+    }
+
+    fn write_paint(&mut self, out: &mut String, name: &str, paint: &Paint) -> fmt::Result {
+        let color = match paint {
+            Paint::Color(color) => *color,
+            Paint::Gradient(Gradient::Conic { stops, .. }) => stops.first().map_or(Color::Black, |(_, color)| *color),
+            Paint::Gradient(gradient) => {
+                let id = self.gradient(gradient)?;
+                return write!(out, r#" {}="url(#{})""#, name, id);
+            }
+        };
+        let (hex, alpha) = color_hex(color);
+        write!(out, r#" {}="{}""#, name, hex)?;
+        if alpha < 1.0 {
+            write!(out, r#" {}-opacity="{}""#, name, alpha)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the gradient in the user space of the shape, as the renders draw it.
+    fn gradient(&mut self, gradient: &Gradient) -> Result<String, fmt::Error> {
+        let id = self.new_id("gradient");
+        let stops = match gradient {
+            Gradient::Linear {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => {
+                write!(
+                    self.defs,
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+                    id, start.0, start.1, end.0, end.1
+                )?;
+                vec![(0.0, *start_color), (1.0, *end_color)]
+            }
+            Gradient::Radial {
+                center,
+                inner_radius,
+                outer_radius,
+                start_color,
+                end_color,
+            } => {
+                write_radial(&mut self.defs, &id, *center, *inner_radius, *outer_radius)?;
+                vec![(0.0, *start_color), (1.0, *end_color)]
+            }
+            Gradient::Box {
+                position,
+                size,
+                radius,
+                feather,
+                start_color,
+                end_color,
+            } => {
+                let center = (position.0 + size.0 / 2.0, position.1 + size.1 / 2.0);
+                let inner_radius = (size.0.min(size.1) / 2.0 - radius - feather / 2.0).max(0.0);
+                let outer_radius = size.0.max(size.1) / 2.0 + feather / 2.0;
+                write_radial(&mut self.defs, &id, center, inner_radius, outer_radius)?;
+                vec![(0.0, *start_color), (1.0, *end_color)]
+            }
+            Gradient::Conic { .. } => unreachable!("The conic gradient is written as the color"),
+        };
+        for (offset, color) in stops {
+            let (hex, alpha) = color_hex(color);
+            write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                offset, hex, alpha
+            )?;
+        }
+        self.defs.push_str(match gradient {
+            Gradient::Linear { .. } => "</linearGradient>",
+            _ => "</radialGradient>",
+        });
+        Ok(id)
+    }
+
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+}
+
+fn write_radial(
+    out: &mut String, id: &str, center: (Real, Real), inner_radius: Real, outer_radius: Real,
+) -> fmt::Result {
+    write!(
+        out,
+        r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" fr="{}" r="{}">"#,
+        id, center.0, center.1, inner_radius, outer_radius
+    )
+}
+
+fn write_id(out: &mut String, id: Option<&str>) -> fmt::Result {
+    match id {
+        Some(id) => write!(out, r#" id="{}""#, escape(id)),
+        None => Ok(()),
+    }
+}
+
+fn write_transform(out: &mut String, matrix: TransformMatrix) -> fmt::Result {
+    if matrix.is_identity() {
+        return Ok(());
+    }
+    let [a, b, c, d, e, f] = matrix.matrix;
+    write!(out, r#" transform="matrix({} {} {} {} {} {})""#, a, b, c, d, e, f)
+}
+
+fn write_opacity(out: &mut String, transparency: Real) -> fmt::Result {
+    if transparency > 0.0 {
+        write!(out, r#" opacity="{}""#, 1.0 - transparency)?;
+    }
+    Ok(())
+}
+
+fn write_font(out: &mut String, weight: Option<u16>, style: Option<FontStyle>) -> fmt::Result {
+    if let Some(weight) = weight.filter(|weight| *weight != 400) {
+        write!(out, r#" font-weight="{}""#, weight)?;
+    }
+    match style {
+        Some(FontStyle::Italic) => out.push_str(r#" font-style="italic""#),
+        Some(FontStyle::Oblique) => out.push_str(r#" font-style="oblique""#),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The SVG path data with the absolute coordinates. The current point and the control points are tracked the same
+/// way as the renders do it.
+fn path_data(commands: &[PathCommand]) -> String {
+    use exgui_core::PathCommand::*;
+
+    let mut data = String::new();
+    let mut last = [0.0, 0.0];
+    let mut ctrls = [[0.0, 0.0]; 2];
+    for cmd in commands {
+        let item = match *cmd {
+            Move(xy) => {
+                last = xy;
+                format!("M{} {}", last[0], last[1])
+            }
+            MoveRel(xy) => {
+                last = [last[0] + xy[0], last[1] + xy[1]];
+                format!("M{} {}", last[0], last[1])
+            }
+            Line(xy) => {
+                last = xy;
+                format!("L{} {}", last[0], last[1])
+            }
+            LineRel(xy) => {
+                last = [last[0] + xy[0], last[1] + xy[1]];
+                format!("L{} {}", last[0], last[1])
+            }
+            LineAlonX(x) => {
+                last[0] = x;
+                format!("L{} {}", last[0], last[1])
+            }
+            LineAlonXRel(x) => {
+                last[0] += x;
+                format!("L{} {}", last[0], last[1])
+            }
+            LineAlonY(y) => {
+                last[1] = y;
+                format!("L{} {}", last[0], last[1])
+            }
+            LineAlonYRel(y) => {
+                last[1] += y;
+                format!("L{} {}", last[0], last[1])
+            }
+            Close => "Z".to_string(),
+            BezCtrl(xy) => {
+                ctrls = [ctrls[1], xy];
+                continue;
+            }
+            BezCtrlRel(xy) => {
+                ctrls = [ctrls[1], [last[0] + xy[0], last[1] + xy[1]]];
+                continue;
+            }
+            BezReflectCtrl => {
+                ctrls = [ctrls[1], [2.0 * last[0] - ctrls[1][0], 2.0 * last[1] - ctrls[1][1]]];
+                continue;
+            }
+            QuadBezTo(xy) | QuadBezToRel(xy) => {
+                last = match cmd {
+                    QuadBezTo(_) => xy,
+                    _ => [last[0] + xy[0], last[1] + xy[1]],
+                };
+                format!("Q{} {} {} {}", ctrls[1][0], ctrls[1][1], last[0], last[1])
+            }
+            CubBezTo(xy) | CubBezToRel(xy) => {
+                last = match cmd {
+                    CubBezTo(_) => xy,
+                    _ => [last[0] + xy[0], last[1] + xy[1]],
+                };
+                format!(
+                    "C{} {} {} {} {} {}",
+                    ctrls[0][0], ctrls[0][1], ctrls[1][0], ctrls[1][1], last[0], last[1]
+                )
+            }
+            Arc {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            }
+            | ArcRel {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                last = match cmd {
+                    Arc { .. } => to,
+                    _ => [last[0] + to[0], last[1] + to[1]],
+                };
+                format!(
+                    "A{} {} {} {} {} {} {}",
+                    radius[0],
+                    radius[1],
+                    rotation * 180.0 / PI,
+                    large_arc as u8,
+                    sweep as u8,
+                    last[0],
+                    last[1]
+                )
+            }
+        };
+        if !data.is_empty() {
+            data.push(' ');
+        }
+        data.push_str(&item);
+    }
+    data
+}
+
+/// The `#rrggbb` color and its alpha.
+fn color_hex(color: Color) -> (String, Real) {
+    let [r, g, b, a] = color.as_arr();
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b)), a)
+}
+
+fn escape(source: &str) -> String {
+    let mut escaped = String::with_capacity(source.len());
+    for c in source.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use exgui_core::{ChangeView, Command, Model, Node, Shaped};
+
+    use super::*;
+    use crate::Svg;
+
+    struct Icon;
+
+    impl Model for Icon {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_props: Self::Properties) -> Self {
+            Icon
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> Command<Self::Message> {
+            ChangeView::None.into()
+        }
+
+        fn build_view(&self) -> Node<Self> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn export_imported() {
+        let svg = Svg::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="48" viewBox="0 0 24 24">
+                <linearGradient id="shade" x2="0" y2="1">
+                    <stop offset="0" stop-color="#fff"/>
+                    <stop offset="1" stop-color="#000"/>
+                </linearGradient>
+                <g transform="translate(2 2)" opacity="0.5">
+                    <rect y="10" width="20" height="10" rx="2" fill="url(#shade)" stroke="#00f"/>
+                    <path d="M0 0 H10 Q 15 5 10 10 Z" fill="none" stroke="red" stroke-width="2"/>
+                    <text x="12" y="22" font-size="4" font-family="Roboto">A &amp; B</text>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        let node: Node<Icon> = svg.to_node();
+        let exported = to_svg(&node, svg.width, svg.height);
+        assert!(exported.contains(r#"<path d="M0 0 L10 0 Q15 5 10 10 Z" transform="matrix(2 0 0 2 4 4)""#));
+        assert!(exported.contains(">A &amp; B</text>"));
+
+        let imported = Svg::parse(&exported).unwrap();
+        // The root group of the document is exported too, as the nested group.
+        let group = &imported.root.children[0].children[0];
+        assert_eq!(group.shape.group().unwrap().transparency, Some(0.5));
+        let original = &svg.root.children[0].children;
+        for (shape, original) in group.children.iter().zip(original) {
+            assert_eq!(shape.shape.fill(), original.shape.fill());
+            assert_eq!(shape.shape.stroke(), original.shape.stroke());
+        }
+        let rect = group.children[0].shape.rect().unwrap();
+        assert_eq!((rect.y.val(), rect.width.val()), (10.0, 20.0));
+        assert_eq!(rect.transform.matrix().matrix, [2.0, 0.0, 0.0, 2.0, 4.0, 4.0]);
+        let text = group.children[2].shape.text().unwrap();
+        assert_eq!((text.content.as_str(), text.font_name.as_str()), ("A & B", "Roboto"));
+    }
+}
@@ -0,0 +1,800 @@
+//! Import of the SVG documents into the shape trees, e.g. the icons and the illustrations. The static subset of SVG
+//! is supported: the groups, the basic shapes, the paths and the texts with the fills, the strokes, the gradients and
+//! the transforms. The other elements, e.g. the filters or the masks, are skipped.
+//!
+//! The shape trees of the views can be exported back to SVG, e.g. for the vector screenshots or the print output.
+
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt, fs, io, path::Path as FsPath, str::FromStr};
+
+use exgui_core::{
+    AlignHor, AlignVer, Circle, Color, Ellipse, Fill, FillRule, Gradient, Group, LineCap, LineJoin, Model, Node, Paint,
+    Path, PathCommand, Polygon, Prim, Real, RealValue, Rect, Rounding, Shape, Stroke, Text, Transform, TransformMatrix,
+};
+use roxmltree::Document;
+
+pub use self::export::*;
+
+pub mod export;
+
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+#[derive(Debug)]
+pub enum SvgError {
+    Io(io::Error),
+    Xml(roxmltree::Error),
+    NotSvg(String),
+    InvalidAttribute { name: String, value: String },
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SvgError::Io(err) => write!(f, "SVG reading error: {}", err),
+            SvgError::Xml(err) => write!(f, "SVG parsing error: {}", err),
+            SvgError::NotSvg(name) => write!(f, "Root element is not svg: {}", name),
+            SvgError::InvalidAttribute { name, value } => {
+                write!(f, "Invalid value of the {} attribute: {}", name, value)
+            }
+        }
+    }
+}
+
+impl Error for SvgError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SvgError::Io(err) => Some(err),
+            SvgError::Xml(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SvgError {
+    fn from(err: io::Error) -> Self {
+        SvgError::Io(err)
+    }
+}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(err: roxmltree::Error) -> Self {
+        SvgError::Xml(err)
+    }
+}
+
+/// Imported shape with the shapes of the nested elements, the children are not empty only for the groups.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgNode {
+    pub shape: Shape,
+    pub children: Vec<SvgNode>,
+}
+
+impl SvgNode {
+    /// Makes the view node of the shape tree, so the one document can be put in the several views.
+    pub fn to_node<M: Model>(&self) -> Node<M> {
+        let name = match self.shape {
+            Shape::Rect(_) => Rect::NAME,
+            Shape::Circle(_) => Circle::NAME,
+            Shape::Ellipse(_) => Ellipse::NAME,
+            Shape::Path(_) => Path::NAME,
+            Shape::Polygon(_) => Polygon::NAME,
+            Shape::Text(_) => Text::NAME,
+            _ => Group::NAME,
+        };
+        let children = self.children.iter().map(SvgNode::to_node).collect();
+        Node::Prim(Prim::new(
+            Cow::Borrowed(name),
+            self.shape.clone(),
+            children,
+            Default::default(),
+        ))
+    }
+}
+
+/// The document size and its shapes, the root group maps the view box to the document size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svg {
+    pub width: Real,
+    pub height: Real,
+    pub root: SvgNode,
+}
+
+impl Svg {
+    pub fn parse(source: &str) -> Result<Self, SvgError> {
+        let doc = Document::parse(source)?;
+        let root = doc.root_element();
+        if !root.has_tag_name("svg") {
+            return Err(SvgError::NotSvg(root.tag_name().name().to_string()));
+        }
+
+        let view_box = attr(root, "viewBox")
+            .map(|value| parse_attr::<svgtypes::ViewBox>("viewBox", value))
+            .transpose()?;
+        let size = |name, view_box_size| -> Result<Real, SvgError> {
+            match attr(root, name) {
+                Some(value) if !value.trim().ends_with('%') => {
+                    Ok(parse_attr::<svgtypes::Length>(name, value)?.number as Real)
+                }
+                _ => Ok(view_box_size),
+            }
+        };
+        let width = size("width", view_box.map_or(100.0, |view_box| view_box.w as Real))?;
+        let height = size("height", view_box.map_or(100.0, |view_box| view_box.h as Real))?;
+
+        let importer = Importer {
+            gradients: doc
+                .descendants()
+                .filter(|node| node.has_tag_name("linearGradient") || node.has_tag_name("radialGradient"))
+                .filter_map(|node| node.attribute("id").map(|id| (id, node)))
+                .collect(),
+            viewport: (width, height),
+        };
+        let style = importer.inherit(root, &Inherited::default())?;
+        let mut root_node = importer.group(root, &style)?;
+        if let (Some(view_box), Shape::Group(group)) = (view_box, &mut root_node.shape) {
+            // The view box is fitted into the document size and centered, as the default `xMidYMid meet`.
+            let scale = (width / view_box.w as Real).min(height / view_box.h as Real);
+            let x = (width - view_box.w as Real * scale) / 2.0 - view_box.x as Real * scale;
+            let y = (height - view_box.h as Real * scale) / 2.0 - view_box.y as Real * scale;
+            let view_box = TransformMatrix {
+                matrix: [scale, 0.0, 0.0, scale, x, y],
+            };
+            group.transform = Transform::Local(view_box * group.transform.matrix());
+        }
+        Ok(Self {
+            width,
+            height,
+            root: root_node,
+        })
+    }
+
+    pub fn load(path: impl AsRef<FsPath>) -> Result<Self, SvgError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_node<M: Model>(&self) -> Node<M> {
+        self.root.to_node()
+    }
+}
+
+/// Paint of the SVG element before the gradient resolving.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaintRef<'a> {
+    None,
+    Color(Color),
+    Url(&'a str),
+}
+
+/// Properties, which are inherited by the nested elements.
+#[derive(Debug, Clone)]
+struct Inherited<'a> {
+    color: Color,
+    fill: PaintRef<'a>,
+    fill_opacity: Real,
+    fill_rule: FillRule,
+    stroke: PaintRef<'a>,
+    stroke_opacity: Real,
+    stroke_width: Real,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: Real,
+    font_size: Real,
+    font_family: Option<String>,
+    text_anchor: AlignHor,
+}
+
+impl Default for Inherited<'_> {
+    fn default() -> Self {
+        Self {
+            color: Color::Black,
+            fill: PaintRef::Color(Color::Black),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: PaintRef::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            font_size: 16.0,
+            font_family: None,
+            text_anchor: AlignHor::Left,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+    Diagonal,
+}
+
+type XmlNode<'a, 'input> = roxmltree::Node<'a, 'input>;
+
+struct Importer<'a, 'input> {
+    gradients: HashMap<&'a str, XmlNode<'a, 'input>>,
+    viewport: (Real, Real),
+}
+
+impl<'a, 'input> Importer<'a, 'input> {
+    fn element(&self, node: XmlNode<'a, 'input>, parent: &Inherited<'a>) -> Result<Option<SvgNode>, SvgError> {
+        if attr(node, "display") == Some("none") {
+            return Ok(None);
+        }
+        let style = self.inherit(node, parent)?;
+        let node = match node.tag_name().name() {
+            "svg" | "g" | "a" | "switch" => self.group(node, &style)?,
+            "rect" => self.rect(node, &style)?,
+            "circle" => {
+                let cx = self.length(node, "cx", Axis::X, &style)?.unwrap_or_default();
+                let cy = self.length(node, "cy", Axis::Y, &style)?.unwrap_or_default();
+                let r = self.length(node, "r", Axis::Diagonal, &style)?.unwrap_or_default();
+                let bounds = [cx - r, cy - r, cx + r, cy + r];
+                self.leaf(Shape::Circle(Circle {
+                    id: id(node),
+                    cx: cx.into(),
+                    cy: cy.into(),
+                    r: r.into(),
+                    transparency: self.transparency(node)?,
+                    fill: self.fill(&style, bounds),
+                    stroke: self.stroke(&style, bounds),
+                    transform: transform(node)?,
+                    ..Default::default()
+                }))
+            }
+            "ellipse" => {
+                let cx = self.length(node, "cx", Axis::X, &style)?.unwrap_or_default();
+                let cy = self.length(node, "cy", Axis::Y, &style)?.unwrap_or_default();
+                let rx = self.length(node, "rx", Axis::X, &style)?.unwrap_or_default();
+                let ry = self.length(node, "ry", Axis::Y, &style)?.unwrap_or_default();
+                let bounds = [cx - rx, cy - ry, cx + rx, cy + ry];
+                self.leaf(Shape::Ellipse(Ellipse {
+                    id: id(node),
+                    cx: cx.into(),
+                    cy: cy.into(),
+                    rx: rx.into(),
+                    ry: ry.into(),
+                    transparency: self.transparency(node)?,
+                    fill: self.fill(&style, bounds),
+                    stroke: self.stroke(&style, bounds),
+                    transform: transform(node)?,
+                    ..Default::default()
+                }))
+            }
+            "line" => {
+                let point = |x, y| -> Result<[Real; 2], SvgError> {
+                    Ok([
+                        self.length(node, x, Axis::X, &style)?.unwrap_or_default(),
+                        self.length(node, y, Axis::Y, &style)?.unwrap_or_default(),
+                    ])
+                };
+                let (start, end) = (point("x1", "y1")?, point("x2", "y2")?);
+                let cmd = vec![PathCommand::Move(start), PathCommand::Line(end)];
+                self.path(node, &style, cmd, &[start, end])?
+            }
+            "polyline" | "polygon" => {
+                let points: Vec<_> = svgtypes::PointsParser::from(attr(node, "points").unwrap_or_default())
+                    .map(|(x, y)| [x as Real, y as Real])
+                    .collect();
+                if node.has_tag_name("polyline") {
+                    let cmd = points
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, &point)| match idx {
+                            0 => PathCommand::Move(point),
+                            _ => PathCommand::Line(point),
+                        })
+                        .collect();
+                    self.path(node, &style, cmd, &points)?
+                } else {
+                    let bounds = bounds(&points);
+                    self.leaf(Shape::Polygon(Polygon {
+                        id: id(node),
+                        contours: vec![points],
+                        fill_rule: style.fill_rule,
+                        transparency: self.transparency(node)?,
+                        fill: self.fill(&style, bounds),
+                        stroke: self.stroke(&style, bounds),
+                        transform: transform(node)?,
+                        ..Default::default()
+                    }))
+                }
+            }
+            "path" => {
+                let mut cmd = vec![];
+                let mut points = vec![];
+                for segment in svgtypes::SimplifyingPathParser::from(attr(node, "d").unwrap_or_default()) {
+                    // The path is rendered up to the first error, as the SVG spec requires.
+                    let segment = match segment {
+                        Ok(segment) => segment,
+                        Err(_) => break,
+                    };
+                    // The control points are added to the bounds too, it is enough for the gradient mapping.
+                    let mut point = |x: f64, y: f64| {
+                        points.push([x as Real, y as Real]);
+                        points[points.len() - 1]
+                    };
+                    match segment {
+                        svgtypes::SimplePathSegment::MoveTo { x, y } => cmd.push(PathCommand::Move(point(x, y))),
+                        svgtypes::SimplePathSegment::LineTo { x, y } => cmd.push(PathCommand::Line(point(x, y))),
+                        svgtypes::SimplePathSegment::CurveTo { x1, y1, x2, y2, x, y } => {
+                            cmd.push(PathCommand::BezCtrl(point(x1, y1)));
+                            cmd.push(PathCommand::BezCtrl(point(x2, y2)));
+                            cmd.push(PathCommand::CubBezTo(point(x, y)));
+                        }
+                        svgtypes::SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                            cmd.push(PathCommand::BezCtrl(point(x1, y1)));
+                            cmd.push(PathCommand::QuadBezTo(point(x, y)));
+                        }
+                        svgtypes::SimplePathSegment::ClosePath => cmd.push(PathCommand::Close),
+                    }
+                }
+                self.path(node, &style, cmd, &points)?
+            }
+            "text" => self.text(node, &style)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(node))
+    }
+
+    fn group(&self, node: XmlNode<'a, 'input>, style: &Inherited<'a>) -> Result<SvgNode, SvgError> {
+        let mut children = vec![];
+        for child in node.children().filter(XmlNode::is_element) {
+            children.extend(self.element(child, style)?);
+        }
+        let transparency = self.transparency(node)?;
+        Ok(SvgNode {
+            shape: Shape::Group(Group {
+                id: id(node),
+                transparency: if transparency > 0.0 { Some(transparency) } else { None },
+                transform: transform(node)?,
+                ..Default::default()
+            }),
+            children,
+        })
+    }
+
+    fn rect(&self, node: XmlNode<'a, 'input>, style: &Inherited<'a>) -> Result<SvgNode, SvgError> {
+        let x = self.length(node, "x", Axis::X, style)?.unwrap_or_default();
+        let y = self.length(node, "y", Axis::Y, style)?.unwrap_or_default();
+        let width = self.length(node, "width", Axis::X, style)?.unwrap_or_default();
+        let height = self.length(node, "height", Axis::Y, style)?.unwrap_or_default();
+        let rx = self.length(node, "rx", Axis::X, style)?;
+        let ry = self.length(node, "ry", Axis::Y, style)?;
+        let rounding = rx.or(ry).map(|radius| {
+            let radius = RealValue::px(radius.min(width / 2.0).min(height / 2.0));
+            Rounding {
+                top_left: radius,
+                top_right: radius,
+                bottom_left: radius,
+                bottom_right: radius,
+            }
+        });
+        let bounds = [x, y, x + width, y + height];
+        Ok(self.leaf(Shape::Rect(Rect {
+            id: id(node),
+            x: x.into(),
+            y: y.into(),
+            width: width.into(),
+            height: height.into(),
+            rounding,
+            transparency: self.transparency(node)?,
+            fill: self.fill(style, bounds),
+            stroke: self.stroke(style, bounds),
+            transform: transform(node)?,
+            ..Default::default()
+        })))
+    }
+
+    fn path(
+        &self, node: XmlNode<'a, 'input>, style: &Inherited<'a>, cmd: Vec<PathCommand>, points: &[[Real; 2]],
+    ) -> Result<SvgNode, SvgError> {
+        let bounds = bounds(points);
+        Ok(self.leaf(Shape::Path(Path {
+            id: id(node),
+            cmd,
+            transparency: self.transparency(node)?,
+            fill: self.fill(style, bounds),
+            stroke: self.stroke(style, bounds),
+            transform: transform(node)?,
+            ..Default::default()
+        })))
+    }
+
+    fn text(&self, node: XmlNode<'a, 'input>, style: &Inherited<'a>) -> Result<SvgNode, SvgError> {
+        // The positions of the separate glyphs are not supported, so only the first one is taken.
+        let first = |name| attr(node, name).and_then(|value| value.split([' ', ',']).find(|item| !item.is_empty()));
+        let coord = |name, axis| -> Result<Real, SvgError> {
+            match first(name) {
+                Some(value) => self.to_px(name, value, axis, style),
+                None => Ok(0.0),
+            }
+        };
+        let (x, y) = (coord("x", Axis::X)?, coord("y", Axis::Y)?);
+        let content = node
+            .descendants()
+            .filter(XmlNode::is_text)
+            .filter_map(|text| text.text())
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let bounds = [x, y - style.font_size, x, y];
+        Ok(self.leaf(Shape::Text(Text {
+            id: id(node),
+            content,
+            x: x.into(),
+            y: y.into(),
+            font_name: style.font_family.clone().unwrap_or_default(),
+            font_size: style.font_size.into(),
+            align: (style.text_anchor, AlignVer::Baseline),
+            transparency: self.transparency(node)?,
+            fill: self.fill(style, bounds),
+            stroke: self.stroke(style, bounds),
+            transform: transform(node)?,
+            ..Default::default()
+        })))
+    }
+
+    fn leaf(&self, shape: Shape) -> SvgNode {
+        SvgNode {
+            shape,
+            children: vec![],
+        }
+    }
+
+    fn inherit(&self, node: XmlNode<'a, 'input>, parent: &Inherited<'a>) -> Result<Inherited<'a>, SvgError> {
+        let mut style = parent.clone();
+        if let Some(value) = attr(node, "color") {
+            style.color = color(parse_attr("color", value)?);
+        }
+        if let Some(value) = attr(node, "fill") {
+            style.fill = self.paint_ref("fill", value, &style, parent.fill)?;
+        }
+        if let Some(value) = attr(node, "stroke") {
+            style.stroke = self.paint_ref("stroke", value, &style, parent.stroke)?;
+        }
+        let opacity = |name, current| -> Result<Real, SvgError> {
+            match attr(node, name) {
+                Some(value) => Ok(parse_attr::<svgtypes::Number>(name, value)?.0.clamp(0.0, 1.0) as Real),
+                None => Ok(current),
+            }
+        };
+        style.fill_opacity = opacity("fill-opacity", style.fill_opacity)?;
+        style.stroke_opacity = opacity("stroke-opacity", style.stroke_opacity)?;
+        if let Some(value) = attr(node, "fill-rule") {
+            style.fill_rule = match value {
+                "evenodd" => FillRule::EvenOdd,
+                _ => FillRule::NonZero,
+            };
+        }
+        if let Some(value) = attr(node, "font-size") {
+            style.font_size = self.to_px("font-size", value, Axis::Y, parent)?;
+        }
+        if let Some(value) = attr(node, "stroke-width") {
+            style.stroke_width = self.to_px("stroke-width", value, Axis::Diagonal, &style)?;
+        }
+        if let Some(value) = attr(node, "stroke-miterlimit") {
+            style.miter_limit = parse_attr::<svgtypes::Number>("stroke-miterlimit", value)?.0 as Real;
+        }
+        if let Some(value) = attr(node, "stroke-linecap") {
+            style.line_cap = match value {
+                "round" => LineCap::Round,
+                "square" => LineCap::Square,
+                _ => LineCap::Butt,
+            };
+        }
+        if let Some(value) = attr(node, "stroke-linejoin") {
+            style.line_join = match value {
+                "round" => LineJoin::Round,
+                "bevel" => LineJoin::Bevel,
+                _ => LineJoin::Miter,
+            };
+        }
+        if let Some(value) = attr(node, "font-family") {
+            let family = value.split(',').next().unwrap_or_default();
+            style.font_family = Some(family.trim().trim_matches(['\'', '"']).to_string());
+        }
+        if let Some(value) = attr(node, "text-anchor") {
+            style.text_anchor = match value {
+                "middle" => AlignHor::Center,
+                "end" => AlignHor::Right,
+                _ => AlignHor::Left,
+            };
+        }
+        Ok(style)
+    }
+
+    fn paint_ref(
+        &self, name: &str, value: &'a str, style: &Inherited<'a>, inherited: PaintRef<'a>,
+    ) -> Result<PaintRef<'a>, SvgError> {
+        let paint = svgtypes::Paint::from_str(value).map_err(|_| invalid(name, value))?;
+        Ok(match paint {
+            svgtypes::Paint::None => PaintRef::None,
+            svgtypes::Paint::Inherit | svgtypes::Paint::ContextFill | svgtypes::Paint::ContextStroke => inherited,
+            svgtypes::Paint::CurrentColor => PaintRef::Color(style.color),
+            svgtypes::Paint::Color(value) => PaintRef::Color(color(value)),
+            svgtypes::Paint::FuncIRI(id, _) => PaintRef::Url(id),
+        })
+    }
+
+    fn fill(&self, style: &Inherited<'a>, bounds: [Real; 4]) -> Option<Fill> {
+        self.paint(style.fill, style.fill_opacity, bounds)
+            .map(|paint| Fill { paint })
+    }
+
+    fn stroke(&self, style: &Inherited<'a>, bounds: [Real; 4]) -> Option<Stroke> {
+        self.paint(style.stroke, style.stroke_opacity, bounds)
+            .map(|paint| Stroke {
+                paint,
+                width: style.stroke_width,
+                line_cap: style.line_cap,
+                line_join: style.line_join,
+                miter_limit: style.miter_limit,
+            })
+    }
+
+    /// Resolves the paint with the opacity. The gradient is reduced to its first and last stops, and its bounding
+    /// box units are mapped to the `bounds` of the shape as `[min_x, min_y, max_x, max_y]`.
+    fn paint(&self, paint: PaintRef<'a>, opacity: Real, bounds: [Real; 4]) -> Option<Paint> {
+        let id = match paint {
+            PaintRef::None => return None,
+            PaintRef::Color(color) => return Some(Paint::Color(with_opacity(color, opacity))),
+            PaintRef::Url(id) => id,
+        };
+        let node = *self.gradients.get(id)?;
+        let stops = self.gradient_stops(node);
+        let start_color = with_opacity(*stops.first()?, opacity);
+        let end_color = with_opacity(*stops.last()?, opacity);
+
+        let user_space = self.gradient_attr(node, "gradientUnits") == Some("userSpaceOnUse");
+        let coord = |name, default: Real, axis| {
+            let value = self
+                .gradient_attr(node, name)
+                .and_then(|value| svgtypes::Length::from_str(value).ok());
+            let (min, size) = match axis {
+                Axis::X => (bounds[0], bounds[2] - bounds[0]),
+                Axis::Y => (bounds[1], bounds[3] - bounds[1]),
+                Axis::Diagonal => (0.0, ((bounds[2] - bounds[0]) + (bounds[3] - bounds[1])) / 2.0),
+            };
+            match value {
+                Some(length) if user_space && length.unit != svgtypes::LengthUnit::Percent => length.number as Real,
+                Some(length) => {
+                    let fraction = match length.unit {
+                        svgtypes::LengthUnit::Percent => length.number / 100.0,
+                        _ => length.number,
+                    };
+                    min + fraction as Real * size
+                }
+                None => min + default * size,
+            }
+        };
+        let gradient = if node.has_tag_name("linearGradient") {
+            Gradient::Linear {
+                start: (coord("x1", 0.0, Axis::X), coord("y1", 0.0, Axis::Y)),
+                end: (coord("x2", 1.0, Axis::X), coord("y2", 0.0, Axis::Y)),
+                start_color,
+                end_color,
+            }
+        } else {
+            Gradient::Radial {
+                center: (coord("cx", 0.5, Axis::X), coord("cy", 0.5, Axis::Y)),
+                inner_radius: coord("fr", 0.0, Axis::Diagonal),
+                outer_radius: coord("r", 0.5, Axis::Diagonal),
+                start_color,
+                end_color,
+            }
+        };
+        Some(Paint::Gradient(gradient))
+    }
+
+    /// Colors of the gradient stops, the stops can be inherited from the referenced gradient.
+    fn gradient_stops(&self, node: XmlNode<'a, 'input>) -> Vec<Color> {
+        let stops: Vec<_> = node
+            .children()
+            .filter(|child| child.has_tag_name("stop"))
+            .map(|stop| {
+                let color = attr(stop, "stop-color")
+                    .and_then(|value| svgtypes::Color::from_str(value).ok())
+                    .map_or(Color::Black, color);
+                let opacity = attr(stop, "stop-opacity")
+                    .and_then(|value| svgtypes::Number::from_str(value).ok())
+                    .map_or(1.0, |number| number.0 as Real);
+                with_opacity(color, opacity)
+            })
+            .collect();
+        match self.gradient_ref(node) {
+            Some(referenced) if stops.is_empty() => self.gradient_stops(referenced),
+            _ => stops,
+        }
+    }
+
+    /// The attribute of the gradient or of the gradients, which it references.
+    fn gradient_attr(&self, node: XmlNode<'a, 'input>, name: &str) -> Option<&'a str> {
+        let mut node = Some(node);
+        let mut depth = 0;
+        while let Some(current) = node {
+            if let Some(value) = current.attribute(name) {
+                return Some(value);
+            }
+            depth += 1;
+            node = self.gradient_ref(current).filter(|_| depth < 8);
+        }
+        None
+    }
+
+    fn gradient_ref(&self, node: XmlNode<'a, 'input>) -> Option<XmlNode<'a, 'input>> {
+        let href = node.attribute((XLINK_NS, "href")).or_else(|| node.attribute("href"))?;
+        self.gradients
+            .get(href.trim_start_matches('#'))
+            .copied()
+            .filter(|referenced| *referenced != node)
+    }
+
+    fn transparency(&self, node: XmlNode<'a, 'input>) -> Result<Real, SvgError> {
+        match attr(node, "opacity") {
+            Some(value) => Ok(1.0 - parse_attr::<svgtypes::Number>("opacity", value)?.0.clamp(0.0, 1.0) as Real),
+            None => Ok(0.0),
+        }
+    }
+
+    fn length(
+        &self, node: XmlNode<'a, 'input>, name: &str, axis: Axis, style: &Inherited<'a>,
+    ) -> Result<Option<Real>, SvgError> {
+        attr(node, name)
+            .map(|value| self.to_px(name, value, axis, style))
+            .transpose()
+    }
+
+    /// Converts the length to the pixels, the percents are relative to the document size.
+    fn to_px(&self, name: &str, value: &str, axis: Axis, style: &Inherited<'a>) -> Result<Real, SvgError> {
+        use svgtypes::LengthUnit::*;
+
+        let length = parse_attr::<svgtypes::Length>(name, value)?;
+        let number = length.number as Real;
+        let (width, height) = self.viewport;
+        Ok(match length.unit {
+            None | Px => number,
+            Em => number * style.font_size,
+            Ex => number * style.font_size / 2.0,
+            In => number * 96.0,
+            Cm => number * 96.0 / 2.54,
+            Mm => number * 96.0 / 25.4,
+            Pt => number * 4.0 / 3.0,
+            Pc => number * 16.0,
+            Percent => {
+                let base = match axis {
+                    Axis::X => width,
+                    Axis::Y => height,
+                    Axis::Diagonal => (width * width + height * height).sqrt() / std::f32::consts::SQRT_2,
+                };
+                number * base / 100.0
+            }
+        })
+    }
+}
+
+/// The property of the element, the declaration of the `style` attribute overrides the presentation attribute.
+fn attr<'a>(node: XmlNode<'a, '_>, name: &str) -> Option<&'a str> {
+    let declared = node.attribute("style").and_then(|style| {
+        style.split(';').rev().find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            if key.trim() == name {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
+    });
+    declared.or_else(|| node.attribute(name)).map(str::trim)
+}
+
+fn parse_attr<T: FromStr>(name: &str, value: &str) -> Result<T, SvgError> {
+    value.parse().map_err(|_| invalid(name, value))
+}
+
+fn invalid(name: &str, value: &str) -> SvgError {
+    SvgError::InvalidAttribute {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn id(node: XmlNode) -> Option<String> {
+    node.attribute("id").map(str::to_string)
+}
+
+fn transform(node: XmlNode) -> Result<Transform, SvgError> {
+    match node.attribute("transform") {
+        Some(value) => {
+            let svgtypes::Transform { a, b, c, d, e, f } = parse_attr("transform", value)?;
+            Ok(Transform::Local(TransformMatrix {
+                matrix: [a as Real, b as Real, c as Real, d as Real, e as Real, f as Real],
+            }))
+        }
+        None => Ok(Transform::new()),
+    }
+}
+
+fn color(color: svgtypes::Color) -> Color {
+    let channel = |value: u8| value as Real / 255.0;
+    Color::RGBA(
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+        channel(color.alpha),
+    )
+}
+
+fn with_opacity(color: Color, opacity: Real) -> Color {
+    let [r, g, b, a] = color.as_arr();
+    Color::RGBA(r, g, b, a * opacity)
+}
+
+/// Bounds of the points as `[min_x, min_y, max_x, max_y]`.
+fn bounds(points: &[[Real; 2]]) -> [Real; 4] {
+    if points.is_empty() {
+        return [0.0; 4];
+    }
+    points.iter().fold(
+        [Real::MAX, Real::MAX, Real::MIN, Real::MIN],
+        |[min_x, min_y, max_x, max_y], [x, y]| [min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exgui_core::Shaped;
+
+    #[test]
+    fn import_shapes() {
+        let svg = Svg::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="48" viewBox="0 0 24 24" fill="red">
+                <defs>
+                    <linearGradient id="shade" x1="0" y1="0" x2="0" y2="1">
+                        <stop offset="0" stop-color="#fff"/>
+                        <stop offset="1" stop-color="#000" stop-opacity="0.5"/>
+                    </linearGradient>
+                </defs>
+                <g id="icon" transform="translate(2 2)" stroke="blue" style="stroke-width: 2">
+                    <rect x="0" y="10" width="20" height="10" rx="2" fill="url(#shade)"/>
+                    <path d="M0 0 L10 0 Q 15 5 10 10 Z" fill="none"/>
+                    <circle cx="5" cy="5" r="3" display="none"/>
+                </g>
+                <text x="12" y="22" font-size="4" text-anchor="middle">Save <tspan>all</tspan></text>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!((svg.width, svg.height), (48.0, 48.0));
+        assert_eq!(svg.root.shape.group().unwrap().transform.matrix().matrix, [
+            2.0, 0.0, 0.0, 2.0, 0.0, 0.0
+        ]);
+        assert_eq!(svg.root.children.len(), 2);
+
+        let icon = &svg.root.children[0];
+        assert_eq!(icon.shape.id(), Some("icon"));
+        assert_eq!(icon.children.len(), 2);
+        let rect = icon.children[0].shape.rect().unwrap();
+        assert_eq!(rect.stroke.as_ref().unwrap().width, 2.0);
+        assert_eq!(
+            rect.fill.as_ref().unwrap().paint,
+            Paint::Gradient(Gradient::Linear {
+                start: (0.0, 10.0),
+                end: (0.0, 20.0),
+                start_color: Color::RGBA(1.0, 1.0, 1.0, 1.0),
+                end_color: Color::RGBA(0.0, 0.0, 0.0, 0.5),
+            })
+        );
+        let path = icon.children[1].shape.path().unwrap();
+        assert!(path.fill.is_none());
+        assert_eq!(path.cmd.len(), 5);
+
+        let text = svg.root.children[1].shape.text().unwrap();
+        assert_eq!(text.content, "Save all");
+        assert_eq!(text.align.0, AlignHor::Center);
+        assert_eq!(text.fill, Some(Color::RGBA(1.0, 0.0, 0.0, 1.0).into()));
+
+        assert!(matches!(Svg::parse("<g/>"), Err(SvgError::NotSvg(_))));
+    }
+}